@@ -0,0 +1,9 @@
+ALTER TABLE profiles ADD COLUMN entropy_quota_bytes INTEGER; -- NULL = server default
+
+CREATE TABLE IF NOT EXISTS entropy_usage (
+    profile_id INTEGER NOT NULL,
+    usage_date DATE NOT NULL, -- UTC day the bytes were drawn
+    bytes_used INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(profile_id, usage_date),
+    FOREIGN KEY(profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
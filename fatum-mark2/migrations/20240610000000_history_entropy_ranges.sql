@@ -0,0 +1,2 @@
+ALTER TABLE history ADD COLUMN entropy_batch_id INTEGER; -- Batch the consumed bytes came from, NULL for live entropy
+ALTER TABLE history ADD COLUMN entropy_ranges TEXT; -- JSON array of {start, end} byte offsets into that pool
@@ -0,0 +1,9 @@
+-- Rows belong to the user that created them. Rows from before auth existed stay NULL
+-- until the first admin is bootstrapped, who then adopts them.
+ALTER TABLE profiles ADD COLUMN owner_id INTEGER REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE history ADD COLUMN owner_id INTEGER REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE quantum_entropy_batches ADD COLUMN owner_id INTEGER REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_profiles_owner ON profiles(owner_id);
+CREATE INDEX IF NOT EXISTS idx_history_owner ON history(owner_id);
+CREATE INDEX IF NOT EXISTS idx_batches_owner ON quantum_entropy_batches(owner_id);
@@ -0,0 +1,9 @@
+-- Simulation jobs still running when the server shut down, kept so they can be resubmitted.
+CREATE TABLE IF NOT EXISTS interrupted_jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job_id INTEGER NOT NULL, -- In-memory job id at the time; ids restart with the server
+    owner_id INTEGER,
+    request JSON NOT NULL, -- The original POST /simulate body
+    interrupted_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(owner_id) REFERENCES users(id) ON DELETE CASCADE
+);
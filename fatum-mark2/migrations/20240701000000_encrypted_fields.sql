@@ -0,0 +1,3 @@
+-- With FATUM_ENCRYPTION_KEY set, a profile's birth fields are stored here as one sealed
+-- value and the plain birth columns are left NULL.
+ALTER TABLE profiles ADD COLUMN birth_data TEXT;
@@ -0,0 +1,24 @@
+-- Pulses are stored as raw bytes instead of hex text. Rows that were already encrypted as
+-- text ('enc:...') cannot be converted here; they keep hex_value and are re-sealed as
+-- bytes on the next startup with the encryption key.
+CREATE TABLE quantum_entropy_data_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    batch_id INTEGER NOT NULL,
+    pulse_round INTEGER,
+    pulse BLOB, -- Raw pulse bytes, or nonce || ciphertext || tag when encrypted
+    encrypted BOOLEAN NOT NULL DEFAULT 0,
+    hex_value TEXT, -- Legacy text form, NULL once converted
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(batch_id) REFERENCES quantum_entropy_batches(id) ON DELETE CASCADE
+);
+
+INSERT INTO quantum_entropy_data_new (id, batch_id, pulse_round, pulse, hex_value, created_at)
+SELECT id, batch_id, pulse_round,
+       CASE WHEN hex_value LIKE 'enc:%' THEN NULL ELSE unhex(hex_value) END,
+       CASE WHEN hex_value LIKE 'enc:%' THEN hex_value ELSE NULL END,
+       created_at
+FROM quantum_entropy_data;
+
+DROP TABLE quantum_entropy_data;
+ALTER TABLE quantum_entropy_data_new RENAME TO quantum_entropy_data;
+CREATE INDEX IF NOT EXISTS idx_entropy_data_batch ON quantum_entropy_data(batch_id, id);
@@ -0,0 +1,12 @@
+-- Named tool configurations a user can reload, e.g. a house's construction year and facing.
+CREATE TABLE IF NOT EXISTS presets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    owner_id INTEGER NOT NULL,
+    tool_type TEXT NOT NULL, -- 'fengshui', 'zeri', ...
+    name TEXT NOT NULL,
+    config JSON NOT NULL, -- The tool's request body (or the part of it worth keeping)
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(owner_id, tool_type, name),
+    FOREIGN KEY(owner_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,20 @@
+-- Tool runs repeated on a cron schedule, e.g. a daily flying star chart at 06:00
+CREATE TABLE IF NOT EXISTS schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    owner_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    tool_type TEXT NOT NULL,
+    config JSON NOT NULL, -- Request body the tool is run with
+    cron TEXT NOT NULL, -- minute hour day month weekday, server local time
+    webhook_url TEXT, -- Optional URL POSTed the result of each run
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    next_run_at DATETIME, -- UTC; NULL when disabled or the expression never fires
+    last_run_at DATETIME,
+    last_history_id INTEGER,
+    last_error TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(owner_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY(last_history_id) REFERENCES history(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_schedules_due ON schedules(enabled, next_run_at);
@@ -0,0 +1,14 @@
+-- User-registered URLs notified of server events, signed with a per-hook secret
+CREATE TABLE IF NOT EXISTS webhooks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    owner_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL, -- HMAC key, sealed like other sensitive columns when encryption is on
+    events JSON NOT NULL, -- Array of event names to send; empty means all
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    last_delivery_at DATETIME,
+    last_status INTEGER, -- HTTP status of the last attempt, NULL if it never got a response
+    last_error TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(owner_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,21 @@
+-- Visits to generated geolocation points, for judging outcomes against attractor power
+CREATE TABLE IF NOT EXISTS trips (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    owner_id INTEGER NOT NULL,
+    history_id INTEGER, -- The geolocation report the target came from; NULL once that entry is deleted
+    target_kind TEXT NOT NULL, -- attractor or void
+    target_rank INTEGER NOT NULL,
+    -- Copied from the report so the trip stays analysable without it
+    latitude REAL NOT NULL,
+    longitude REAL NOT NULL,
+    power REAL NOT NULL,
+    visited_at DATETIME NOT NULL,
+    notes TEXT,
+    photos JSON NOT NULL, -- Array of photo metadata (file name, time, position, caption); no image data
+    rating INTEGER NOT NULL, -- Subjective outcome, 1 (nothing) to 5 (remarkable)
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(owner_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY(history_id) REFERENCES history(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_trips_owner ON trips(owner_id, visited_at);
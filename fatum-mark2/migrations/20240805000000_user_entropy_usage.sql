@@ -0,0 +1,8 @@
+-- Beacon draws made without a profile are metered against the calling user
+CREATE TABLE IF NOT EXISTS user_entropy_usage (
+    user_id INTEGER NOT NULL,
+    usage_date DATE NOT NULL, -- UTC day the bytes were drawn
+    bytes_used INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(user_id, usage_date),
+    FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS profiles (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    birth_year BIGINT,
+    birth_month BIGINT,
+    birth_day BIGINT,
+    birth_hour BIGINT,
+    gender TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS history (
+    id BIGSERIAL PRIMARY KEY,
+    profile_id BIGINT REFERENCES profiles(id),
+    tool_type TEXT NOT NULL, -- 'fengshui', 'divination'
+    summary TEXT, -- Short JSON or text summary
+    full_report JSONB, -- The full JSON output
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS quantum_entropy_batches (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'collecting', -- 'collecting', 'completed'
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS quantum_entropy_data (
+    id BIGSERIAL PRIMARY KEY,
+    batch_id BIGINT NOT NULL REFERENCES quantum_entropy_batches(id) ON DELETE CASCADE,
+    pulse_round BIGINT,
+    hex_value TEXT NOT NULL, -- Stored as hex string or base64
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,8 @@
+ALTER TABLE profiles ADD COLUMN entropy_quota_bytes BIGINT; -- NULL = server default
+
+CREATE TABLE IF NOT EXISTS entropy_usage (
+    profile_id BIGINT NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+    usage_date DATE NOT NULL, -- UTC day the bytes were drawn
+    bytes_used BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY(profile_id, usage_date)
+);
@@ -0,0 +1,8 @@
+-- Simulation jobs still running when the server shut down, kept so they can be resubmitted.
+CREATE TABLE IF NOT EXISTS interrupted_jobs (
+    id BIGSERIAL PRIMARY KEY,
+    job_id BIGINT NOT NULL, -- In-memory job id at the time; ids restart with the server
+    owner_id BIGINT REFERENCES users(id) ON DELETE CASCADE,
+    request JSONB NOT NULL, -- The original POST /simulate body
+    interrupted_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,10 @@
+-- Pulses are stored as raw bytes instead of hex text. Rows that were already encrypted as
+-- text ('enc:...') cannot be converted here; they keep hex_value and are re-sealed as
+-- bytes on the next startup with the encryption key.
+ALTER TABLE quantum_entropy_data ADD COLUMN pulse BYTEA; -- Raw pulse bytes, or nonce || ciphertext || tag when encrypted
+ALTER TABLE quantum_entropy_data ADD COLUMN encrypted BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE quantum_entropy_data ALTER COLUMN hex_value DROP NOT NULL; -- Legacy text form, NULL once converted
+
+UPDATE quantum_entropy_data SET pulse = decode(hex_value, 'hex'), hex_value = NULL WHERE hex_value NOT LIKE 'enc:%';
+
+CREATE INDEX IF NOT EXISTS idx_entropy_data_batch ON quantum_entropy_data(batch_id, id);
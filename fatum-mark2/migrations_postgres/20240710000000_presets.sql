@@ -0,0 +1,11 @@
+-- Named tool configurations a user can reload, e.g. a house's construction year and facing.
+CREATE TABLE IF NOT EXISTS presets (
+    id BIGSERIAL PRIMARY KEY,
+    owner_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tool_type TEXT NOT NULL, -- 'fengshui', 'zeri', ...
+    name TEXT NOT NULL,
+    config JSONB NOT NULL, -- The tool's request body (or the part of it worth keeping)
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(owner_id, tool_type, name)
+);
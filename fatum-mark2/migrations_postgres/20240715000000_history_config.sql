@@ -0,0 +1,2 @@
+-- Request that produced an entry, so auto-archived tool runs can be re-run or audited
+ALTER TABLE history ADD COLUMN config JSONB;
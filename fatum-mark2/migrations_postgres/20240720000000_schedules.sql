@@ -0,0 +1,18 @@
+-- Tool runs repeated on a cron schedule, e.g. a daily flying star chart at 06:00
+CREATE TABLE IF NOT EXISTS schedules (
+    id BIGSERIAL PRIMARY KEY,
+    owner_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    tool_type TEXT NOT NULL,
+    config JSONB NOT NULL, -- Request body the tool is run with
+    cron TEXT NOT NULL, -- minute hour day month weekday, server local time
+    webhook_url TEXT, -- Optional URL POSTed the result of each run
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMP, -- UTC; NULL when disabled or the expression never fires
+    last_run_at TIMESTAMP,
+    last_history_id BIGINT REFERENCES history(id) ON DELETE SET NULL,
+    last_error TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_schedules_due ON schedules(enabled, next_run_at);
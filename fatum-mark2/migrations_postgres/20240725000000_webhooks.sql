@@ -0,0 +1,13 @@
+-- User-registered URLs notified of server events, signed with a per-hook secret
+CREATE TABLE IF NOT EXISTS webhooks (
+    id BIGSERIAL PRIMARY KEY,
+    owner_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL, -- HMAC key, sealed like other sensitive columns when encryption is on
+    events JSONB NOT NULL, -- Array of event names to send; empty means all
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_delivery_at TIMESTAMP,
+    last_status BIGINT, -- HTTP status of the last attempt, NULL if it never got a response
+    last_error TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
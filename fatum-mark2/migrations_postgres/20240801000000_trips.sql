@@ -0,0 +1,19 @@
+-- Visits to generated geolocation points, for judging outcomes against attractor power
+CREATE TABLE IF NOT EXISTS trips (
+    id BIGSERIAL PRIMARY KEY,
+    owner_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    history_id BIGINT REFERENCES history(id) ON DELETE SET NULL, -- The geolocation report the target came from
+    target_kind TEXT NOT NULL, -- attractor or void
+    target_rank BIGINT NOT NULL,
+    -- Copied from the report so the trip stays analysable without it
+    latitude DOUBLE PRECISION NOT NULL,
+    longitude DOUBLE PRECISION NOT NULL,
+    power DOUBLE PRECISION NOT NULL,
+    visited_at TIMESTAMP NOT NULL,
+    notes TEXT,
+    photos JSONB NOT NULL, -- Array of photo metadata (file name, time, position, caption); no image data
+    rating BIGINT NOT NULL, -- Subjective outcome, 1 (nothing) to 5 (remarkable)
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_trips_owner ON trips(owner_id, visited_at);
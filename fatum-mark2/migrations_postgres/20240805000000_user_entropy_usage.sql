@@ -0,0 +1,7 @@
+-- Beacon draws made without a profile are metered against the calling user
+CREATE TABLE IF NOT EXISTS user_entropy_usage (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    usage_date DATE NOT NULL, -- UTC day the bytes were drawn
+    bytes_used BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY(user_id, usage_date)
+);
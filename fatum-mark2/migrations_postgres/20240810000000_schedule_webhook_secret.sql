@@ -0,0 +1,2 @@
+-- HMAC key for a schedule's own webhook_url, sealed like webhooks.secret
+ALTER TABLE schedules ADD COLUMN webhook_secret TEXT;
@@ -10,6 +10,6 @@ pub struct Cli {}
 pub async fn handle_cli() {
     let _cli = Cli::parse();
     // Default and only behavior: Start Web Server
-    println!("Starting Web Server...");
+    tracing::info!("Starting web server");
     fatum_mark2::server::start_server().await;
 }
@@ -124,14 +124,15 @@ impl CurbyClient {
     /// 2. If successful, uses that seed to initialize a ChaCha20 CSPRNG.
     /// 3. If the network call fails, falls back to the OS entropy source (OsRng).
     /// 4. Generates the requested amount of random bytes.
+    #[tracing::instrument(name = "beacon_fetch", skip(self))]
     pub async fn fetch_bulk_randomness(&mut self, min_bytes: usize) -> Result<Vec<u8>> {
         let seed = match self.fetch_single_pulse().await {
             Ok(s) => {
-                println!("Successfully seeded with Quantum Entropy.");
+                tracing::info!("Seeded with quantum entropy");
                 s
             },
             Err(e) => {
-                eprintln!("Quantum Fetch Failed ({}), falling back to OS Entropy.", e);
+                tracing::warn!(error = %e, "Quantum fetch failed, falling back to OS entropy");
                 let mut os_seed = [0u8; 32];
                 OsRng.fill_bytes(&mut os_seed);
                 os_seed.to_vec()
@@ -157,6 +158,7 @@ impl CurbyClient {
     }
 
     /// Fetches the raw randomness payload from the latest valid Pulse.
+    #[tracing::instrument(name = "beacon_pulse", skip(self), err(Display))]
     async fn fetch_single_pulse(&mut self) -> Result<Vec<u8>> {
         let chain_id = self.get_quantum_chain_id().await?;
         let latest_url = format!("{}/api/chains/{}/pulses/latest", self.base_url, chain_id);
@@ -1,10 +1,89 @@
-use sqlx::{SqlitePool, migrate::MigrateDatabase};
+use sqlx::{migrate::MigrateDatabase, pool::PoolOptions};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::services::crypto;
+
+// The backend is picked at build time: SQLite by default, PostgreSQL with
+// `--features postgres`. Queries are written once using `$N` placeholders and
+// `RETURNING`, which both dialects accept.
+#[cfg(not(feature = "postgres"))]
+pub type Driver = sqlx::Sqlite;
+#[cfg(feature = "postgres")]
+pub type Driver = sqlx::Postgres;
+
+pub type DbPool = sqlx::Pool<Driver>;
+
+#[cfg(not(feature = "postgres"))]
+pub const DEFAULT_DATABASE_URL: &str = "sqlite:fatum.db";
+#[cfg(feature = "postgres")]
+pub const DEFAULT_DATABASE_URL: &str = "postgres://localhost/fatum";
+
+#[cfg(not(feature = "postgres"))]
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations");
+#[cfg(feature = "postgres")]
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations_postgres");
 
 pub struct Db {
-    pub pool: SqlitePool,
+    pub pool: DbPool,
+}
+
+/// Connection pool sizing, read from `FATUM_DB_*` environment variables.
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(600)),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// `FATUM_DB_MAX_CONNECTIONS`, `FATUM_DB_MIN_CONNECTIONS`, `FATUM_DB_ACQUIRE_TIMEOUT_SECS`
+    /// and `FATUM_DB_IDLE_TIMEOUT_SECS` (0 disables the idle timeout).
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            max_connections: var("FATUM_DB_MAX_CONNECTIONS").filter(|n| *n > 0).unwrap_or(defaults.max_connections),
+            min_connections: var("FATUM_DB_MIN_CONNECTIONS").unwrap_or(defaults.min_connections),
+            acquire_timeout: var("FATUM_DB_ACQUIRE_TIMEOUT_SECS").map(Duration::from_secs).unwrap_or(defaults.acquire_timeout),
+            idle_timeout: match var::<u64>("FATUM_DB_IDLE_TIMEOUT_SECS") {
+                Some(0) => None,
+                Some(secs) => Some(Duration::from_secs(secs)),
+                None => defaults.idle_timeout,
+            },
+        }
+    }
+
+    fn options(&self) -> PoolOptions<Driver> {
+        let options = PoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections.min(self.max_connections))
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout);
+        // CURRENT_TIMESTAMP is UTC in SQLite; make Postgres sessions agree
+        #[cfg(feature = "postgres")]
+        let options = options.after_connect(|conn, _meta| Box::pin(async move {
+            sqlx::query("SET TIME ZONE 'UTC'").execute(conn).await.map(|_| ())
+        }));
+        options
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -14,62 +93,462 @@ pub struct QuantumBatch {
     pub status: String,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    pub owner_id: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuantumEntropyData {
     pub id: i64,
     pub batch_id: i64,
     pub pulse_round: Option<i64>,
-    pub hex_value: String,
+    /// The pulse's raw bytes.
+    pub pulse: Vec<u8>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// A `quantum_entropy_data` row as stored: bytes (possibly sealed), or legacy hex text.
+#[derive(sqlx::FromRow)]
+struct StoredPulse {
+    id: i64,
+    batch_id: i64,
+    pulse_round: Option<i64>,
+    pulse: Option<Vec<u8>>,
+    encrypted: bool,
+    hex_value: Option<String>,
+    created_at: Option<NaiveDateTime>,
+}
+
+impl StoredPulse {
+    fn bytes(&self) -> Result<Vec<u8>> {
+        match (&self.pulse, &self.hex_value) {
+            (Some(sealed), _) if self.encrypted => match crypto::cipher()? {
+                Some(c) => c.open_bytes(PULSE_CONTEXT, sealed),
+                None => anyhow::bail!("Entropy pulse {} is encrypted but no FATUM_ENCRYPTION_KEY is configured", self.id),
+            },
+            (Some(bytes), _) => Ok(bytes.clone()),
+            // Written before pulses were stored as bytes
+            (None, Some(hex_value)) => Ok(hex::decode(crypto::open(HEX_VALUE_CONTEXT, hex_value)?)?),
+            (None, None) => anyhow::bail!("Entropy pulse {} has no data", self.id),
+        }
+    }
+
+    fn decode(self) -> Result<QuantumEntropyData> {
+        Ok(QuantumEntropyData {
+            pulse: self.bytes()?,
+            id: self.id,
+            batch_id: self.batch_id,
+            pulse_round: self.pulse_round,
+            created_at: self.created_at,
+        })
+    }
+}
+
+/// The bytes to store for a pulse and whether they are sealed.
+fn seal_pulse(bytes: &[u8]) -> Result<(Vec<u8>, bool)> {
+    match crypto::cipher()? {
+        Some(c) => Ok((c.seal_bytes(PULSE_CONTEXT, bytes)?, true)),
+        None => Ok((bytes.to_vec(), false)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Profile {
+    pub id: i64,
+    pub name: String,
+    pub birth_year: Option<i64>,
+    pub birth_month: Option<i64>,
+    pub birth_day: Option<i64>,
+    pub birth_hour: Option<i64>,
+    pub gender: Option<String>,
+    pub entropy_quota_bytes: Option<i64>,
+    pub owner_id: Option<i64>,
+    /// Sealed birth fields when encryption is on; unpacked into the fields above on read.
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub birth_data: Option<String>,
+}
+
+/// Encryption contexts: a sealed value only opens in the column it was written for.
+const BIRTH_DATA_CONTEXT: &str = "profiles.birth_data";
+const PULSE_CONTEXT: &str = "quantum_entropy_data.pulse";
+/// Pulses encrypted before they were stored as bytes.
+const HEX_VALUE_CONTEXT: &str = "quantum_entropy_data.hex_value";
+const WEBHOOK_SECRET_CONTEXT: &str = "webhooks.secret";
+const SCHEDULE_WEBHOOK_SECRET_CONTEXT: &str = "schedules.webhook_secret";
+
+/// What gets sealed into `profiles.birth_data`.
+#[derive(Serialize, Deserialize)]
+struct BirthData {
+    year: Option<i64>,
+    month: Option<i64>,
+    day: Option<i64>,
+    hour: Option<i64>,
+    gender: Option<String>,
+}
+
+impl BirthData {
+    /// The values to bind for the plain birth columns and `birth_data`: either in clear,
+    /// or all plain columns NULL and everything sealed.
+    fn to_columns(&self) -> Result<(BirthData, Option<String>)> {
+        if crypto::cipher()?.is_none() {
+            return Ok((BirthData { gender: self.gender.clone(), ..*self }, None));
+        }
+        let sealed = crypto::seal(BIRTH_DATA_CONTEXT, &serde_json::to_string(self)?)?;
+        Ok((BirthData { year: None, month: None, day: None, hour: None, gender: None }, Some(sealed)))
+    }
+}
+
+/// A profile whose birth fields are still stored in clear.
+#[derive(sqlx::FromRow)]
+struct PlainBirthRow {
+    id: i64,
+    birth_year: Option<i64>,
+    birth_month: Option<i64>,
+    birth_day: Option<i64>,
+    birth_hour: Option<i64>,
+    gender: Option<String>,
+}
+
+impl Profile {
+    /// Replaces the plain birth fields with the sealed ones, if this row has them.
+    fn unseal(mut self) -> Result<Self> {
+        if let Some(sealed) = self.birth_data.take() {
+            let birth: BirthData = serde_json::from_str(&crypto::open(BIRTH_DATA_CONTEXT, &sealed)?)?;
+            self.birth_year = birth.year;
+            self.birth_month = birth.month;
+            self.birth_day = birth.day;
+            self.birth_hour = birth.hour;
+            self.gender = birth.gender;
+        }
+        Ok(self)
+    }
+}
+
+/// The editable fields of a profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileFields {
+    pub name: String,
+    pub birth_year: i64,
+    pub birth_month: i64,
+    pub birth_day: i64,
+    pub birth_hour: i64,
+    pub gender: String,
+    pub entropy_quota_bytes: Option<i64>,
+}
+
+impl ProfileFields {
+    fn birth(&self) -> BirthData {
+        BirthData {
+            year: Some(self.birth_year),
+            month: Some(self.birth_month),
+            day: Some(self.birth_day),
+            hour: Some(self.birth_hour),
+            gender: Some(self.gender.clone()),
+        }
+    }
+}
+
+/// A history entry to be stored.
+#[derive(Debug, Clone)]
+pub struct NewHistory {
+    pub profile_id: Option<i64>,
+    pub tool_type: String,
+    pub summary: String,
+    pub full_report: serde_json::Value,
+    /// The tool request that produced the report, when known.
+    pub config: Option<serde_json::Value>,
+    pub entropy_batch_id: Option<i64>,
+    /// JSON array of `EntropyRange`s, stored as TEXT.
+    pub entropy_ranges: Option<String>,
+}
+
+/// A history entry as listed, without its report.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub tool_type: String,
+    pub summary: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub profile_name: Option<String>,
+    pub entropy_batch_id: Option<i64>,
+    #[serde(serialize_with = "serialize_json_text")]
+    pub entropy_ranges: Option<String>,
+}
+
+/// A single history entry including its stored report.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct HistoryDetail {
+    pub id: i64,
+    pub profile_id: Option<i64>,
+    pub profile_name: Option<String>,
+    pub tool_type: String,
+    pub summary: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub entropy_batch_id: Option<i64>,
+    #[serde(serialize_with = "serialize_json_text")]
+    pub entropy_ranges: Option<String>,
+    pub config: Option<serde_json::Value>,
+    pub full_report: Option<serde_json::Value>,
+}
+
+/// Emits a JSON column stored as TEXT as structured JSON instead of a string.
+fn serialize_json_text<S: serde::Serializer>(value: &Option<String>, ser: S) -> Result<S::Ok, S::Error> {
+    let parsed = value.as_deref().and_then(|v| serde_json::from_str::<serde_json::Value>(v).ok());
+    parsed.serialize(ser)
+}
+
+/// A saved configuration for one tool.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Preset {
+    pub id: i64,
+    pub tool_type: String,
+    pub name: String,
+    pub config: serde_json::Value,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// A tool run repeated on a cron schedule.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Schedule {
+    pub id: i64,
+    pub owner_id: i64,
+    pub name: String,
+    pub tool_type: String,
+    pub config: serde_json::Value,
+    pub cron: String,
+    pub webhook_url: Option<String>,
+    pub enabled: bool,
+    /// UTC. `None` while disabled.
+    pub next_run_at: Option<NaiveDateTime>,
+    pub last_run_at: Option<NaiveDateTime>,
+    pub last_history_id: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    /// As stored (sealed when encryption is on); see `signing_secret`. `None` for
+    /// schedules created before their webhooks were signed.
+    #[serde(skip)]
+    pub webhook_secret: Option<String>,
+}
+
+impl Schedule {
+    /// The key `webhook_url` deliveries are signed with, if the schedule has one.
+    pub fn signing_secret(&self) -> Result<Option<String>> {
+        self.webhook_secret.as_deref().map(|s| crypto::open(SCHEDULE_WEBHOOK_SECRET_CONTEXT, s)).transpose()
+    }
+}
+
+/// The user-editable columns of a schedule.
+#[derive(Debug, Clone)]
+pub struct ScheduleFields {
+    pub name: String,
+    pub tool_type: String,
+    pub config: serde_json::Value,
+    pub cron: String,
+    pub webhook_url: Option<String>,
+    pub enabled: bool,
+}
+
+const SCHEDULE_COLUMNS: &str = "id, owner_id, name, tool_type, config, cron, webhook_url, enabled, next_run_at, last_run_at, last_history_id, last_error, created_at, webhook_secret";
+
+/// A URL notified of a user's server events.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Webhook {
+    pub id: i64,
+    pub owner_id: i64,
+    pub url: String,
+    /// As stored (sealed when encryption is on); see `signing_secret`.
+    #[serde(skip)]
+    pub secret: String,
+    /// Event names to deliver; empty means all of them.
+    #[sqlx(json)]
+    pub events: Vec<String>,
+    pub enabled: bool,
+    pub last_delivery_at: Option<NaiveDateTime>,
+    pub last_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+impl Webhook {
+    pub fn signing_secret(&self) -> Result<String> {
+        crypto::open(WEBHOOK_SECRET_CONTEXT, &self.secret)
+    }
+
+    pub fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event)
+    }
+}
+
+const WEBHOOK_COLUMNS: &str = "id, owner_id, url, secret, events, enabled, last_delivery_at, last_status, last_error, created_at";
+
+/// Metadata for a photo taken on a trip; the image itself is stored elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotoMeta {
+    pub file_name: String,
+    #[serde(default)]
+    pub taken_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub caption: Option<String>,
+}
+
+/// A logged visit to a geolocation attractor or void.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Trip {
+    pub id: i64,
+    pub owner_id: i64,
+    /// The geolocation report the target came from; `None` once that entry is deleted.
+    pub history_id: Option<i64>,
+    pub target_kind: String,
+    pub target_rank: i64,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The target's power as reported when the trip was logged.
+    pub power: f64,
+    pub visited_at: NaiveDateTime,
+    pub notes: Option<String>,
+    #[sqlx(json)]
+    pub photos: Vec<PhotoMeta>,
+    /// Subjective outcome, 1-5.
+    pub rating: i64,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// What a trip went to, copied from its report.
+#[derive(Debug, Clone)]
+pub struct TripTarget {
+    pub history_id: i64,
+    pub target_kind: String,
+    pub target_rank: i64,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub power: f64,
+}
+
+/// The user-editable columns of a trip.
+#[derive(Debug, Clone)]
+pub struct TripFields {
+    pub visited_at: NaiveDateTime,
+    pub notes: Option<String>,
+    pub photos: Vec<PhotoMeta>,
+    pub rating: i64,
+}
+
+const TRIP_COLUMNS: &str = "id, owner_id, history_id, target_kind, target_rank, latitude, longitude, power, visited_at, notes, photos, rating, created_at";
+
+/// What happens to a profile's history entries when the profile is deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryOnDelete {
+    /// Keep the entries, detached from any profile.
+    Orphan,
+    /// Delete the entries along with the profile.
+    Cascade,
+}
+
+impl HistoryOnDelete {
+    /// Server default, overridable with `FATUM_PROFILE_DELETE_HISTORY=cascade|orphan`.
+    pub fn from_env() -> Self {
+        match std::env::var("FATUM_PROFILE_DELETE_HISTORY").as_deref() {
+            Ok("cascade") => HistoryOnDelete::Cascade,
+            _ => HistoryOnDelete::Orphan,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub is_admin: bool,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// API key metadata; the key itself is never stored, only its hash.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: i64,
+    pub user_id: i64,
+    pub label: Option<String>,
     pub created_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+/// A simulation job that was cut off by a server shutdown.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InterruptedJobRow {
+    pub id: i64,
+    pub job_id: i64,
+    pub owner_id: Option<i64>,
+    pub request: serde_json::Value,
+    pub interrupted_at: Option<NaiveDateTime>,
 }
 
 impl Db {
     pub async fn new(db_url: &str) -> Result<Self> {
-        if !sqlx::Sqlite::database_exists(db_url).await.unwrap_or(false) {
-            println!("Creating database: {}", db_url);
-            sqlx::Sqlite::create_database(db_url).await?;
+        Self::with_pool_config(db_url, &PoolConfig::from_env()).await
+    }
+
+    pub async fn with_pool_config(db_url: &str, config: &PoolConfig) -> Result<Self> {
+        if !Driver::database_exists(db_url).await.unwrap_or(false) {
+            tracing::info!(db_url, "Creating database");
+            Driver::create_database(db_url).await?;
         }
 
-        let pool = SqlitePool::connect(db_url).await?;
+        let pool = config.options().connect(db_url).await?;
 
         // Run migrations
-        sqlx::migrate!("./migrations")
-            .run(&pool)
-            .await?;
+        MIGRATOR.run(&pool).await?;
 
+        let db = Self { pool };
+        if crypto::cipher()?.is_some() {
+            db.seal_plaintext_rows().await?;
+        }
+        Ok(db)
+    }
+
+    /// A private, migrated in-memory database (single connection so it is shared).
+    #[cfg(all(test, not(feature = "postgres")))]
+    pub async fn in_memory() -> Result<Self> {
+        let config = PoolConfig { max_connections: 1, idle_timeout: None, ..PoolConfig::default() };
+        let pool = config.options().connect("sqlite::memory:").await?;
+        MIGRATOR.run(&pool).await?;
         Ok(Self { pool })
     }
 
     // === QUANTUM BATCH OPERATIONS ===
 
-    pub async fn create_batch(&self, name: &str) -> Result<i64> {
-        let id = sqlx::query("INSERT INTO quantum_entropy_batches (name, status) VALUES (?, 'collecting')")
+    pub async fn create_batch(&self, name: &str, owner_id: Option<i64>) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar("INSERT INTO quantum_entropy_batches (name, status, owner_id) VALUES ($1, 'collecting', $2) RETURNING id")
             .bind(name)
-            .execute(&self.pool)
-            .await?
-            .last_insert_rowid();
+            .bind(owner_id)
+            .fetch_one(&self.pool)
+            .await?;
         Ok(id)
     }
 
     pub async fn get_batch(&self, id: i64) -> Result<QuantumBatch> {
-        let batch = sqlx::query_as::<_, QuantumBatch>("SELECT * FROM quantum_entropy_batches WHERE id = ?")
+        let batch = sqlx::query_as::<_, QuantumBatch>("SELECT * FROM quantum_entropy_batches WHERE id = $1")
             .bind(id)
             .fetch_one(&self.pool)
             .await?;
         Ok(batch)
     }
 
-    pub async fn list_batches(&self) -> Result<Vec<QuantumBatch>> {
-        let batches = sqlx::query_as::<_, QuantumBatch>("SELECT * FROM quantum_entropy_batches ORDER BY created_at DESC")
+    pub async fn list_batches(&self, owner_id: i64) -> Result<Vec<QuantumBatch>> {
+        let batches = sqlx::query_as::<_, QuantumBatch>("SELECT * FROM quantum_entropy_batches WHERE owner_id = $1 ORDER BY created_at DESC")
+            .bind(owner_id)
             .fetch_all(&self.pool)
             .await?;
         Ok(batches)
     }
 
     pub async fn update_batch_status(&self, id: i64, status: &str) -> Result<()> {
-        sqlx::query("UPDATE quantum_entropy_batches SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        sqlx::query("UPDATE quantum_entropy_batches SET status = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2")
             .bind(status)
             .bind(id)
             .execute(&self.pool)
@@ -77,29 +556,881 @@ impl Db {
         Ok(())
     }
 
-    pub async fn insert_entropy(&self, batch_id: i64, pulse_round: Option<u64>, hex_value: &str) -> Result<()> {
-        sqlx::query("INSERT INTO quantum_entropy_data (batch_id, pulse_round, hex_value) VALUES (?, ?, ?)")
+    pub async fn insert_entropy(&self, batch_id: i64, pulse_round: Option<u64>, pulse: &[u8]) -> Result<()> {
+        let (pulse, encrypted) = seal_pulse(pulse)?;
+        sqlx::query("INSERT INTO quantum_entropy_data (batch_id, pulse_round, pulse, encrypted) VALUES ($1, $2, $3, $4)")
             .bind(batch_id)
             .bind(pulse_round.map(|v| v as i64))
-            .bind(hex_value)
+            .bind(pulse)
+            .bind(encrypted)
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
     pub async fn get_batch_entropy(&self, batch_id: i64) -> Result<Vec<QuantumEntropyData>> {
-        let data = sqlx::query_as::<_, QuantumEntropyData>("SELECT * FROM quantum_entropy_data WHERE batch_id = ? ORDER BY id ASC")
+        let rows = sqlx::query_as::<_, StoredPulse>(
+            "SELECT id, batch_id, pulse_round, pulse, encrypted, hex_value, created_at FROM quantum_entropy_data WHERE batch_id = $1 ORDER BY id ASC"
+        )
             .bind(batch_id)
             .fetch_all(&self.pool)
             .await?;
-        Ok(data)
+        rows.into_iter().map(StoredPulse::decode).collect()
     }
 
     pub async fn get_batch_size(&self, batch_id: i64) -> Result<i64> {
-        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM quantum_entropy_data WHERE batch_id = ?")
+        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM quantum_entropy_data WHERE batch_id = $1")
             .bind(batch_id)
             .fetch_one(&self.pool)
             .await?;
         Ok(row.0)
     }
+
+    // === PROFILE OPERATIONS ===
+
+    pub async fn create_profile(&self, owner_id: i64, fields: &ProfileFields) -> Result<i64> {
+        let (birth, sealed) = fields.birth().to_columns()?;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO profiles (name, birth_year, birth_month, birth_day, birth_hour, gender, entropy_quota_bytes, owner_id, birth_data) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id"
+        )
+            .bind(&fields.name)
+            .bind(birth.year)
+            .bind(birth.month)
+            .bind(birth.day)
+            .bind(birth.hour)
+            .bind(birth.gender)
+            .bind(fields.entropy_quota_bytes)
+            .bind(owner_id)
+            .bind(sealed)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    /// A user's profiles, newest first.
+    pub async fn list_profiles(&self, owner_id: i64) -> Result<Vec<Profile>> {
+        let profiles = sqlx::query_as::<_, Profile>(
+            "SELECT id, name, birth_year, birth_month, birth_day, birth_hour, gender, entropy_quota_bytes, owner_id, birth_data FROM profiles WHERE owner_id = $1 ORDER BY created_at DESC, id DESC"
+        )
+            .bind(owner_id)
+            .fetch_all(&self.pool)
+            .await?;
+        profiles.into_iter().map(Profile::unseal).collect()
+    }
+
+    pub async fn get_profile(&self, id: i64) -> Result<Profile> {
+        let profile = sqlx::query_as::<_, Profile>(
+            "SELECT id, name, birth_year, birth_month, birth_day, birth_hour, gender, entropy_quota_bytes, owner_id, birth_data FROM profiles WHERE id = $1"
+        )
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+        profile.unseal()
+    }
+
+    /// Replaces a profile's fields. Returns false if the profile does not exist.
+    pub async fn update_profile(&self, id: i64, fields: &ProfileFields) -> Result<bool> {
+        let (birth, sealed) = fields.birth().to_columns()?;
+        let res = sqlx::query(
+            "UPDATE profiles SET name = $1, birth_year = $2, birth_month = $3, birth_day = $4, birth_hour = $5, gender = $6, entropy_quota_bytes = $7, birth_data = $8 WHERE id = $9"
+        )
+            .bind(&fields.name)
+            .bind(birth.year)
+            .bind(birth.month)
+            .bind(birth.day)
+            .bind(birth.hour)
+            .bind(birth.gender)
+            .bind(fields.entropy_quota_bytes)
+            .bind(sealed)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Deletes a profile, handling its history per `history`. Entropy usage rows go with it.
+    ///
+    /// Returns `None` if the profile does not exist, otherwise how many history rows were
+    /// deleted (cascade) or detached (orphan).
+    pub async fn delete_profile(&self, id: i64, history: HistoryOnDelete) -> Result<Option<u64>> {
+        let mut tx = self.pool.begin().await?;
+        let sql = match history {
+            HistoryOnDelete::Orphan => "UPDATE history SET profile_id = NULL WHERE profile_id = $1",
+            HistoryOnDelete::Cascade => "DELETE FROM history WHERE profile_id = $1",
+        };
+        let affected = sqlx::query(sql).bind(id).execute(&mut *tx).await?.rows_affected();
+        let deleted = sqlx::query("DELETE FROM profiles WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            // Nothing to delete; roll back so no history is touched
+            return Ok(None);
+        }
+        tx.commit().await?;
+        Ok(Some(affected))
+    }
+
+    // === HISTORY OPERATIONS ===
+
+    pub async fn insert_history(&self, owner_id: i64, entry: &NewHistory) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO history (profile_id, tool_type, summary, full_report, config, entropy_batch_id, entropy_ranges, owner_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id"
+        )
+            .bind(entry.profile_id)
+            .bind(&entry.tool_type)
+            .bind(&entry.summary)
+            .bind(&entry.full_report)
+            .bind(&entry.config)
+            .bind(entry.entropy_batch_id)
+            .bind(&entry.entropy_ranges)
+            .bind(owner_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    /// A user's most recent history entries, newest first.
+    pub async fn list_history(&self, owner_id: i64, limit: i64) -> Result<Vec<HistoryEntry>> {
+        let rows = sqlx::query_as::<_, HistoryEntry>(
+            "SELECT h.id, h.tool_type, h.summary, h.created_at, p.name as profile_name, h.entropy_batch_id, h.entropy_ranges
+             FROM history h
+             LEFT JOIN profiles p ON h.profile_id = p.id
+             WHERE h.owner_id = $1
+             ORDER BY h.created_at DESC, h.id DESC LIMIT $2"
+        )
+            .bind(owner_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    /// One history entry with its report, if it exists and belongs to `owner_id`.
+    pub async fn get_history(&self, id: i64, owner_id: i64) -> Result<Option<HistoryDetail>> {
+        let row = sqlx::query_as::<_, HistoryDetail>(
+            "SELECT h.id, h.profile_id, p.name as profile_name, h.tool_type, h.summary, h.created_at,
+                    h.entropy_batch_id, h.entropy_ranges, h.config, h.full_report
+             FROM history h
+             LEFT JOIN profiles p ON h.profile_id = p.id
+             WHERE h.id = $1 AND h.owner_id = $2"
+        )
+            .bind(id)
+            .bind(owner_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row)
+    }
+
+    /// Returns false if there was no such entry owned by `owner_id`.
+    pub async fn delete_history(&self, id: i64, owner_id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM history WHERE id = $1 AND owner_id = $2")
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // === ENCRYPTION ===
+
+    /// Seals birth data and entropy written before encryption was turned on.
+    async fn seal_plaintext_rows(&self) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let profiles: Vec<PlainBirthRow> = sqlx::query_as(
+            "SELECT id, birth_year, birth_month, birth_day, birth_hour, gender FROM profiles
+             WHERE birth_data IS NULL
+               AND (birth_year IS NOT NULL OR birth_month IS NOT NULL OR birth_day IS NOT NULL OR birth_hour IS NOT NULL OR gender IS NOT NULL)"
+        )
+            .fetch_all(&mut *tx)
+            .await?;
+        for row in &profiles {
+            let birth = BirthData {
+                year: row.birth_year,
+                month: row.birth_month,
+                day: row.birth_day,
+                hour: row.birth_hour,
+                gender: row.gender.clone(),
+            };
+            let (_, sealed) = birth.to_columns()?;
+            sqlx::query(
+                "UPDATE profiles SET birth_year = NULL, birth_month = NULL, birth_day = NULL, birth_hour = NULL, gender = NULL, birth_data = $1 WHERE id = $2"
+            )
+                .bind(sealed)
+                .bind(row.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Plain pulses, and pulses sealed as text before the switch to bytes
+        let entropy: Vec<StoredPulse> = sqlx::query_as(
+            "SELECT id, batch_id, pulse_round, pulse, encrypted, hex_value, created_at FROM quantum_entropy_data
+             WHERE encrypted = FALSE OR hex_value IS NOT NULL"
+        )
+            .fetch_all(&mut *tx)
+            .await?;
+        for row in &entropy {
+            let (pulse, encrypted) = seal_pulse(&row.bytes()?)?;
+            sqlx::query("UPDATE quantum_entropy_data SET pulse = $1, encrypted = $2, hex_value = NULL WHERE id = $3")
+                .bind(pulse)
+                .bind(encrypted)
+                .bind(row.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let secrets: Vec<(i64, String)> = sqlx::query_as("SELECT id, secret FROM webhooks")
+            .fetch_all(&mut *tx)
+            .await?;
+        let secrets: Vec<_> = secrets.into_iter().filter(|(_, secret)| !crypto::is_sealed(secret)).collect();
+        for (id, secret) in &secrets {
+            sqlx::query("UPDATE webhooks SET secret = $1 WHERE id = $2")
+                .bind(crypto::seal(WEBHOOK_SECRET_CONTEXT, secret)?)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let schedule_secrets: Vec<(i64, String)> = sqlx::query_as("SELECT id, webhook_secret FROM schedules WHERE webhook_secret IS NOT NULL")
+            .fetch_all(&mut *tx)
+            .await?;
+        let schedule_secrets: Vec<_> = schedule_secrets.into_iter().filter(|(_, secret)| !crypto::is_sealed(secret)).collect();
+        for (id, secret) in &schedule_secrets {
+            sqlx::query("UPDATE schedules SET webhook_secret = $1 WHERE id = $2")
+                .bind(crypto::seal(SCHEDULE_WEBHOOK_SECRET_CONTEXT, secret)?)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        if !profiles.is_empty() || !entropy.is_empty() || !secrets.is_empty() || !schedule_secrets.is_empty() {
+            tracing::info!(
+                profiles = profiles.len(), entropy_rows = entropy.len(), webhooks = secrets.len(), schedules = schedule_secrets.len(),
+                "Encrypted existing plaintext rows"
+            );
+        }
+        Ok(())
+    }
+
+    // === PRESET OPERATIONS ===
+
+    /// Fails with a unique violation if the user already has a preset of that name for the tool.
+    pub async fn create_preset(&self, owner_id: i64, tool_type: &str, name: &str, config: &serde_json::Value) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO presets (owner_id, tool_type, name, config) VALUES ($1, $2, $3, $4) RETURNING id"
+        )
+            .bind(owner_id)
+            .bind(tool_type)
+            .bind(name)
+            .bind(config)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    /// A user's presets, optionally for one tool only, by name.
+    pub async fn list_presets(&self, owner_id: i64, tool_type: Option<&str>) -> Result<Vec<Preset>> {
+        let presets = sqlx::query_as::<_, Preset>(
+            "SELECT id, tool_type, name, config, created_at, updated_at FROM presets
+             WHERE owner_id = $1 AND ($2 IS NULL OR tool_type = $2)
+             ORDER BY tool_type, name"
+        )
+            .bind(owner_id)
+            .bind(tool_type)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(presets)
+    }
+
+    pub async fn get_preset(&self, id: i64, owner_id: i64) -> Result<Option<Preset>> {
+        let preset = sqlx::query_as::<_, Preset>(
+            "SELECT id, tool_type, name, config, created_at, updated_at FROM presets WHERE id = $1 AND owner_id = $2"
+        )
+            .bind(id)
+            .bind(owner_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(preset)
+    }
+
+    /// Renames and/or replaces a preset's config. Returns false if there is no such preset.
+    pub async fn update_preset(&self, id: i64, owner_id: i64, name: &str, config: &serde_json::Value) -> Result<bool> {
+        let res = sqlx::query(
+            "UPDATE presets SET name = $1, config = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $3 AND owner_id = $4"
+        )
+            .bind(name)
+            .bind(config)
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn delete_preset(&self, id: i64, owner_id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM presets WHERE id = $1 AND owner_id = $2")
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // === SCHEDULE OPERATIONS ===
+
+    pub async fn create_schedule(&self, owner_id: i64, fields: &ScheduleFields, next_run_at: Option<NaiveDateTime>, webhook_secret: &str) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO schedules (owner_id, name, tool_type, config, cron, webhook_url, enabled, next_run_at, webhook_secret)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id"
+        )
+            .bind(owner_id)
+            .bind(&fields.name)
+            .bind(&fields.tool_type)
+            .bind(&fields.config)
+            .bind(&fields.cron)
+            .bind(&fields.webhook_url)
+            .bind(fields.enabled)
+            .bind(next_run_at)
+            .bind(crypto::seal(SCHEDULE_WEBHOOK_SECRET_CONTEXT, webhook_secret)?)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn list_schedules(&self, owner_id: i64) -> Result<Vec<Schedule>> {
+        let schedules = sqlx::query_as::<_, Schedule>(&format!("SELECT {} FROM schedules WHERE owner_id = $1 ORDER BY name", SCHEDULE_COLUMNS))
+            .bind(owner_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(schedules)
+    }
+
+    pub async fn get_schedule(&self, id: i64, owner_id: i64) -> Result<Option<Schedule>> {
+        let schedule = sqlx::query_as::<_, Schedule>(&format!("SELECT {} FROM schedules WHERE id = $1 AND owner_id = $2", SCHEDULE_COLUMNS))
+            .bind(id)
+            .bind(owner_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(schedule)
+    }
+
+    /// Returns false if there is no such schedule owned by `owner_id`.
+    pub async fn update_schedule(&self, id: i64, owner_id: i64, fields: &ScheduleFields, next_run_at: Option<NaiveDateTime>) -> Result<bool> {
+        let res = sqlx::query(
+            "UPDATE schedules SET name = $1, tool_type = $2, config = $3, cron = $4, webhook_url = $5, enabled = $6, next_run_at = $7
+             WHERE id = $8 AND owner_id = $9"
+        )
+            .bind(&fields.name)
+            .bind(&fields.tool_type)
+            .bind(&fields.config)
+            .bind(&fields.cron)
+            .bind(&fields.webhook_url)
+            .bind(fields.enabled)
+            .bind(next_run_at)
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Gives a schedule that predates signed deliveries its webhook secret.
+    pub async fn set_schedule_webhook_secret(&self, id: i64, owner_id: i64, secret: &str) -> Result<bool> {
+        let res = sqlx::query("UPDATE schedules SET webhook_secret = $1 WHERE id = $2 AND owner_id = $3")
+            .bind(crypto::seal(SCHEDULE_WEBHOOK_SECRET_CONTEXT, secret)?)
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn delete_schedule(&self, id: i64, owner_id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM schedules WHERE id = $1 AND owner_id = $2")
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Enabled schedules whose next run is at or before `now` (UTC), most overdue first.
+    pub async fn due_schedules(&self, now: NaiveDateTime) -> Result<Vec<Schedule>> {
+        let schedules = sqlx::query_as::<_, Schedule>(&format!(
+            "SELECT {} FROM schedules WHERE enabled AND next_run_at <= $1 ORDER BY next_run_at", SCHEDULE_COLUMNS
+        ))
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(schedules)
+    }
+
+    /// Moves a due schedule on to its next run before it is executed.
+    ///
+    /// Returns false if another runner already claimed this run.
+    pub async fn claim_schedule(&self, id: i64, due_at: NaiveDateTime, next_run_at: Option<NaiveDateTime>) -> Result<bool> {
+        let res = sqlx::query(
+            "UPDATE schedules SET next_run_at = $1, last_run_at = CURRENT_TIMESTAMP WHERE id = $2 AND next_run_at = $3"
+        )
+            .bind(next_run_at)
+            .bind(id)
+            .bind(due_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Stores the outcome of a run: the history entry it produced, or why it failed.
+    pub async fn finish_schedule_run(&self, id: i64, history_id: Option<i64>, error: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE schedules SET last_history_id = $1, last_error = $2 WHERE id = $3")
+            .bind(history_id)
+            .bind(error)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // === WEBHOOK OPERATIONS ===
+
+    pub async fn create_webhook(&self, owner_id: i64, url: &str, secret: &str, events: &[String], enabled: bool) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO webhooks (owner_id, url, secret, events, enabled) VALUES ($1, $2, $3, $4, $5) RETURNING id"
+        )
+            .bind(owner_id)
+            .bind(url)
+            .bind(crypto::seal(WEBHOOK_SECRET_CONTEXT, secret)?)
+            .bind(sqlx::types::Json(events))
+            .bind(enabled)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn list_webhooks(&self, owner_id: i64) -> Result<Vec<Webhook>> {
+        let hooks = sqlx::query_as::<_, Webhook>(&format!("SELECT {} FROM webhooks WHERE owner_id = $1 ORDER BY id", WEBHOOK_COLUMNS))
+            .bind(owner_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(hooks)
+    }
+
+    pub async fn get_webhook(&self, id: i64, owner_id: i64) -> Result<Option<Webhook>> {
+        let hook = sqlx::query_as::<_, Webhook>(&format!("SELECT {} FROM webhooks WHERE id = $1 AND owner_id = $2", WEBHOOK_COLUMNS))
+            .bind(id)
+            .bind(owner_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(hook)
+    }
+
+    /// Returns false if there is no such webhook owned by `owner_id`. The secret is kept.
+    pub async fn update_webhook(&self, id: i64, owner_id: i64, url: &str, events: &[String], enabled: bool) -> Result<bool> {
+        let res = sqlx::query("UPDATE webhooks SET url = $1, events = $2, enabled = $3 WHERE id = $4 AND owner_id = $5")
+            .bind(url)
+            .bind(sqlx::types::Json(events))
+            .bind(enabled)
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn delete_webhook(&self, id: i64, owner_id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND owner_id = $2")
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// A user's enabled webhooks subscribed to `event`.
+    pub async fn webhooks_for_event(&self, owner_id: i64, event: &str) -> Result<Vec<Webhook>> {
+        let hooks = sqlx::query_as::<_, Webhook>(&format!("SELECT {} FROM webhooks WHERE owner_id = $1 AND enabled", WEBHOOK_COLUMNS))
+            .bind(owner_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(hooks.into_iter().filter(|h| h.wants(event)).collect())
+    }
+
+    /// Stores the outcome of a delivery's final attempt.
+    pub async fn record_webhook_delivery(&self, id: i64, status: Option<u16>, error: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE webhooks SET last_delivery_at = CURRENT_TIMESTAMP, last_status = $1, last_error = $2 WHERE id = $3")
+            .bind(status.map(i64::from))
+            .bind(error)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // === TRIP OPERATIONS ===
+
+    pub async fn create_trip(&self, owner_id: i64, target: &TripTarget, fields: &TripFields) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO trips (owner_id, history_id, target_kind, target_rank, latitude, longitude, power, visited_at, notes, photos, rating)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING id"
+        )
+            .bind(owner_id)
+            .bind(target.history_id)
+            .bind(&target.target_kind)
+            .bind(target.target_rank)
+            .bind(target.latitude)
+            .bind(target.longitude)
+            .bind(target.power)
+            .bind(fields.visited_at)
+            .bind(&fields.notes)
+            .bind(sqlx::types::Json(&fields.photos))
+            .bind(fields.rating)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    /// A user's trips, oldest visit first, optionally only those to one report's targets.
+    pub async fn list_trips(&self, owner_id: i64, history_id: Option<i64>) -> Result<Vec<Trip>> {
+        let filter = if history_id.is_some() { " AND history_id = $2" } else { "" };
+        let sql = format!("SELECT {} FROM trips WHERE owner_id = $1{} ORDER BY visited_at, id", TRIP_COLUMNS, filter);
+        let mut query = sqlx::query_as::<_, Trip>(&sql).bind(owner_id);
+        if let Some(history_id) = history_id {
+            query = query.bind(history_id);
+        }
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+
+    pub async fn get_trip(&self, id: i64, owner_id: i64) -> Result<Option<Trip>> {
+        let trip = sqlx::query_as::<_, Trip>(&format!("SELECT {} FROM trips WHERE id = $1 AND owner_id = $2", TRIP_COLUMNS))
+            .bind(id)
+            .bind(owner_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(trip)
+    }
+
+    /// Returns false if there is no such trip owned by `owner_id`. The target is fixed.
+    pub async fn update_trip(&self, id: i64, owner_id: i64, fields: &TripFields) -> Result<bool> {
+        let res = sqlx::query("UPDATE trips SET visited_at = $1, notes = $2, photos = $3, rating = $4 WHERE id = $5 AND owner_id = $6")
+            .bind(fields.visited_at)
+            .bind(&fields.notes)
+            .bind(sqlx::types::Json(&fields.photos))
+            .bind(fields.rating)
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn delete_trip(&self, id: i64, owner_id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM trips WHERE id = $1 AND owner_id = $2")
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // === OWNERSHIP ===
+
+    /// Owner of a profile; `RowNotFound` if the profile does not exist.
+    pub async fn profile_owner(&self, id: i64) -> Result<Option<i64>> {
+        let row: (Option<i64>,) = sqlx::query_as("SELECT owner_id FROM profiles WHERE id = $1")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.0)
+    }
+
+    /// Owner of an entropy batch; `RowNotFound` if the batch does not exist.
+    pub async fn batch_owner(&self, id: i64) -> Result<Option<i64>> {
+        let row: (Option<i64>,) = sqlx::query_as("SELECT owner_id FROM quantum_entropy_batches WHERE id = $1")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.0)
+    }
+
+    /// Hands every unowned profile, history entry and batch to `user_id`.
+    ///
+    /// Used when the first admin is created so data from single-user installs stays visible.
+    pub async fn claim_unowned_rows(&self, user_id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for table in ["profiles", "history", "quantum_entropy_batches"] {
+            sqlx::query(&format!("UPDATE {} SET owner_id = $1 WHERE owner_id IS NULL", table))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // === INTERRUPTED JOBS ===
+
+    pub async fn save_interrupted_job(&self, job_id: u64, owner_id: Option<i64>, request: &serde_json::Value) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar("INSERT INTO interrupted_jobs (job_id, owner_id, request) VALUES ($1, $2, $3) RETURNING id")
+            .bind(job_id as i64)
+            .bind(owner_id)
+            .bind(request)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn list_interrupted_jobs(&self, owner_id: i64) -> Result<Vec<InterruptedJobRow>> {
+        let rows = sqlx::query_as::<_, InterruptedJobRow>(
+            "SELECT id, job_id, owner_id, request, interrupted_at FROM interrupted_jobs WHERE owner_id = $1 ORDER BY id DESC"
+        )
+            .bind(owner_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    /// Removes an interrupted job record; false if it does not exist or belongs to someone else.
+    pub async fn delete_interrupted_job(&self, id: i64, owner_id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM interrupted_jobs WHERE id = $1 AND owner_id = $2")
+            .bind(id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    // === USER & API KEY OPERATIONS ===
+
+    pub async fn count_users(&self) -> Result<i64> {
+        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.0)
+    }
+
+    pub async fn create_user(&self, username: &str, is_admin: bool) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar("INSERT INTO users (username, is_admin) VALUES ($1, $2) RETURNING id")
+            .bind(username)
+            .bind(is_admin)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    /// Creates the first (admin) user in a single statement, so concurrent bootstrap
+    /// requests cannot both succeed. Returns `None` if any user already exists.
+    pub async fn create_first_user(&self, username: &str) -> Result<Option<i64>> {
+        let id: Option<i64> = sqlx::query_scalar(
+            "INSERT INTO users (username, is_admin) SELECT $1, $2
+             WHERE NOT EXISTS (SELECT 1 FROM users) RETURNING id"
+        )
+            .bind(username)
+            .bind(true)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    pub async fn get_user(&self, id: i64) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>("SELECT id, username, is_admin, created_at FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(user)
+    }
+
+    pub async fn list_users(&self) -> Result<Vec<User>> {
+        let users = sqlx::query_as::<_, User>("SELECT id, username, is_admin, created_at FROM users ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(users)
+    }
+
+    /// Returns false if no such user existed.
+    pub async fn delete_user(&self, id: i64) -> Result<bool> {
+        let res = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn insert_api_key(&self, user_id: i64, key_hash: &str, label: Option<&str>) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar("INSERT INTO api_keys (user_id, key_hash, label) VALUES ($1, $2, $3) RETURNING id")
+            .bind(user_id)
+            .bind(key_hash)
+            .bind(label)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    /// Looks up the user owning a key hash and stamps the key's `last_used_at`.
+    pub async fn authenticate_key(&self, key_hash: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT u.id, u.username, u.is_admin, u.created_at FROM api_keys k JOIN users u ON k.user_id = u.id WHERE k.key_hash = $1"
+        )
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        if user.is_some() {
+            sqlx::query("UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP WHERE key_hash = $1")
+                .bind(key_hash)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(user)
+    }
+
+    pub async fn list_api_keys(&self, user_id: i64) -> Result<Vec<ApiKey>> {
+        let keys = sqlx::query_as::<_, ApiKey>(
+            "SELECT id, user_id, label, created_at, last_used_at FROM api_keys WHERE user_id = $1 ORDER BY id"
+        )
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(keys)
+    }
+
+    pub async fn get_api_key(&self, id: i64) -> Result<ApiKey> {
+        let key = sqlx::query_as::<_, ApiKey>("SELECT id, user_id, label, created_at, last_used_at FROM api_keys WHERE id = $1")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(key)
+    }
+
+    pub async fn delete_api_key(&self, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM api_keys WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // === BACKUP & RESTORE ===
+
+    /// Writes a consistent snapshot of the whole database to `path`, which must not exist yet.
+    #[cfg(not(feature = "postgres"))]
+    pub async fn backup_to(&self, path: &Path) -> Result<()> {
+        let path = path.to_str().ok_or_else(|| anyhow::anyhow!("Backup path is not valid UTF-8"))?;
+        sqlx::query("VACUUM INTO $1").bind(path).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "postgres")]
+    pub async fn backup_to(&self, _path: &Path) -> Result<()> {
+        anyhow::bail!("Built-in backups are only available for SQLite; use pg_dump for PostgreSQL")
+    }
+
+    /// Replaces every table's contents with those of the snapshot at `path`.
+    ///
+    /// The snapshot must be at the same migration version as the live database.
+    #[cfg(not(feature = "postgres"))]
+    pub async fn restore_from(&self, path: &Path) -> Result<()> {
+        use sqlx::Connection;
+
+        let path = path.to_str().ok_or_else(|| anyhow::anyhow!("Backup path is not valid UTF-8"))?;
+        // ATTACH is per connection, so the whole restore runs on one
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("ATTACH DATABASE $1 AS backup").bind(path).execute(&mut *conn).await?;
+        // Emptying a table would otherwise fire ON DELETE actions on tables already restored
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await?;
+
+        let result = async {
+            let version = "SELECT MAX(version) FROM {}._sqlx_migrations";
+            let live: Option<i64> = sqlx::query_scalar(&version.replace("{}", "main")).fetch_one(&mut *conn).await?;
+            let snapshot: Option<i64> = sqlx::query_scalar(&version.replace("{}", "backup")).fetch_one(&mut *conn).await?;
+            if live != snapshot {
+                anyhow::bail!("Backup is at schema version {:?} but the database is at {:?}", snapshot, live);
+            }
+
+            let tables: Vec<String> = sqlx::query_scalar(
+                "SELECT name FROM main.sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '_sqlx_migrations'"
+            )
+                .fetch_all(&mut *conn)
+                .await?;
+
+            let mut tx = conn.begin().await?;
+            for table in &tables {
+                sqlx::query(&format!("DELETE FROM main.\"{}\"", table)).execute(&mut *tx).await?;
+                sqlx::query(&format!("INSERT INTO main.\"{0}\" SELECT * FROM backup.\"{0}\"", table))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+            Ok(())
+        }.await;
+
+        sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await?;
+        sqlx::query("DETACH DATABASE backup").execute(&mut *conn).await?;
+        result
+    }
+
+    #[cfg(feature = "postgres")]
+    pub async fn restore_from(&self, _path: &Path) -> Result<()> {
+        anyhow::bail!("Built-in restore is only available for SQLite; use pg_restore for PostgreSQL")
+    }
+
+    // === ENTROPY QUOTA OPERATIONS ===
+
+    /// Returns the profile's configured daily entropy allowance, `None` if it uses the server default.
+    pub async fn get_profile_quota(&self, profile_id: i64) -> Result<Option<i64>> {
+        let row: (Option<i64>,) = sqlx::query_as("SELECT entropy_quota_bytes FROM profiles WHERE id = $1")
+            .bind(profile_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.0)
+    }
+
+    pub async fn get_entropy_usage(&self, profile_id: i64, day: NaiveDate) -> Result<i64> {
+        let row: Option<(i64,)> = sqlx::query_as("SELECT bytes_used FROM entropy_usage WHERE profile_id = $1 AND usage_date = $2")
+            .bind(profile_id)
+            .bind(day)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| r.0).unwrap_or(0))
+    }
+
+    pub async fn add_entropy_usage(&self, profile_id: i64, day: NaiveDate, bytes: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO entropy_usage (profile_id, usage_date, bytes_used) VALUES ($1, $2, $3)
+             ON CONFLICT(profile_id, usage_date) DO UPDATE SET bytes_used = entropy_usage.bytes_used + excluded.bytes_used"
+        )
+            .bind(profile_id)
+            .bind(day)
+            .bind(bytes)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_user_entropy_usage(&self, user_id: i64, day: NaiveDate) -> Result<i64> {
+        let row: Option<(i64,)> = sqlx::query_as("SELECT bytes_used FROM user_entropy_usage WHERE user_id = $1 AND usage_date = $2")
+            .bind(user_id)
+            .bind(day)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| r.0).unwrap_or(0))
+    }
+
+    pub async fn add_user_entropy_usage(&self, user_id: i64, day: NaiveDate, bytes: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO user_entropy_usage (user_id, usage_date, bytes_used) VALUES ($1, $2, $3)
+             ON CONFLICT(user_id, usage_date) DO UPDATE SET bytes_used = user_entropy_usage.bytes_used + excluded.bytes_used"
+        )
+            .bind(user_id)
+            .bind(day)
+            .bind(bytes)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
 }
+
+#[cfg(all(test, not(feature = "postgres")))]
+#[path = "db_tests.rs"]
+mod tests;
@@ -0,0 +1,342 @@
+use chrono::NaiveDate;
+
+use crate::db::{Db, HistoryOnDelete, NewHistory, PhotoMeta, ProfileFields, ScheduleFields, TripFields, TripTarget};
+
+fn fields(name: &str) -> ProfileFields {
+    ProfileFields {
+        name: name.to_string(),
+        birth_year: 1990,
+        birth_month: 5,
+        birth_day: 17,
+        birth_hour: 8,
+        gender: "F".to_string(),
+        entropy_quota_bytes: None,
+    }
+}
+
+fn entry(profile_id: Option<i64>, summary: &str) -> NewHistory {
+    NewHistory {
+        profile_id,
+        tool_type: "feng_shui".to_string(),
+        summary: summary.to_string(),
+        full_report: serde_json::json!({ "score": 7 }),
+        config: Some(serde_json::json!({ "facing_degrees": 180.0 })),
+        entropy_batch_id: None,
+        entropy_ranges: Some(r#"[{"batch_id":1,"start":0,"end":4}]"#.to_string()),
+    }
+}
+
+async fn setup() -> (Db, i64, i64) {
+    let db = Db::in_memory().await.unwrap();
+    let alice = db.create_user("alice", false).await.unwrap();
+    let bob = db.create_user("bob", false).await.unwrap();
+    (db, alice, bob)
+}
+
+#[tokio::test]
+async fn test_profile_crud() {
+    let (db, alice, bob) = setup().await;
+    let id = db.create_profile(alice, &fields("Home")).await.unwrap();
+
+    let listed = db.list_profiles(alice).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name, "Home");
+    assert_eq!(listed[0].owner_id, Some(alice));
+    assert!(db.list_profiles(bob).await.unwrap().is_empty());
+
+    let mut changed = fields("Office");
+    changed.entropy_quota_bytes = Some(1024);
+    assert!(db.update_profile(id, &changed).await.unwrap());
+    let profile = db.get_profile(id).await.unwrap();
+    assert_eq!(profile.name, "Office");
+    assert_eq!(profile.entropy_quota_bytes, Some(1024));
+    assert!(!db.update_profile(id + 100, &changed).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_history_is_scoped_to_owner() {
+    let (db, alice, bob) = setup().await;
+    let profile = db.create_profile(alice, &fields("Home")).await.unwrap();
+    let id = db.insert_history(alice, &entry(Some(profile), "first")).await.unwrap();
+
+    let listed = db.list_history(alice, 50).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].profile_name.as_deref(), Some("Home"));
+
+    let detail = db.get_history(id, alice).await.unwrap().unwrap();
+    assert_eq!(detail.full_report, Some(serde_json::json!({ "score": 7 })));
+    assert_eq!(detail.config, Some(serde_json::json!({ "facing_degrees": 180.0 })));
+    let json = serde_json::to_value(&detail).unwrap();
+    assert_eq!(json["entropy_ranges"][0]["end"], 4, "ranges serialize as JSON, not text");
+
+    assert!(db.list_history(bob, 50).await.unwrap().is_empty());
+    assert!(db.get_history(id, bob).await.unwrap().is_none());
+    assert!(!db.delete_history(id, bob).await.unwrap());
+    assert!(db.delete_history(id, alice).await.unwrap());
+    assert!(db.get_history(id, alice).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_list_history_limit_newest_first() {
+    let (db, alice, _) = setup().await;
+    for i in 0..5 {
+        db.insert_history(alice, &entry(None, &format!("run {}", i))).await.unwrap();
+    }
+    let listed = db.list_history(alice, 3).await.unwrap();
+    let summaries: Vec<_> = listed.iter().map(|h| h.summary.clone().unwrap()).collect();
+    assert_eq!(summaries, vec!["run 4", "run 3", "run 2"]);
+}
+
+#[tokio::test]
+async fn test_delete_profile_history_rules() {
+    let (db, alice, _) = setup().await;
+    let kept = db.create_profile(alice, &fields("Kept")).await.unwrap();
+    let dropped = db.create_profile(alice, &fields("Dropped")).await.unwrap();
+    let orphan = db.insert_history(alice, &entry(Some(kept), "orphan me")).await.unwrap();
+    let cascade = db.insert_history(alice, &entry(Some(dropped), "delete me")).await.unwrap();
+
+    assert_eq!(db.delete_profile(kept, HistoryOnDelete::Orphan).await.unwrap(), Some(1));
+    let detail = db.get_history(orphan, alice).await.unwrap().unwrap();
+    assert_eq!(detail.profile_id, None);
+
+    assert_eq!(db.delete_profile(dropped, HistoryOnDelete::Cascade).await.unwrap(), Some(1));
+    assert!(db.get_history(cascade, alice).await.unwrap().is_none());
+
+    assert_eq!(db.delete_profile(dropped, HistoryOnDelete::Cascade).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_backup_and_restore_roundtrip() {
+    // In-memory databases cannot be snapshotted with VACUUM INTO, so this one lives on disk
+    let dir = std::env::temp_dir().join(format!("fatum-backup-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let db = Db::new(&format!("sqlite:{}", dir.join("live.db").display())).await.unwrap();
+    let alice = db.create_user("alice", false).await.unwrap();
+    let kept = db.create_profile(alice, &fields("Before backup")).await.unwrap();
+    db.insert_history(alice, &entry(Some(kept), "kept")).await.unwrap();
+
+    let snapshot = dir.join("snapshot.db");
+    db.backup_to(&snapshot).await.unwrap();
+
+    // Changes after the snapshot are rolled back by the restore
+    db.create_profile(alice, &fields("After backup")).await.unwrap();
+    db.delete_profile(kept, HistoryOnDelete::Cascade).await.unwrap();
+    db.restore_from(&snapshot).await.unwrap();
+
+    let names: Vec<_> = db.list_profiles(alice).await.unwrap().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["Before backup"]);
+    assert_eq!(db.list_history(alice, 50).await.unwrap().len(), 1);
+
+    db.pool.close().await;
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_restore_oldest_backup_with_retention_full() {
+    use crate::services::backup::{self, BackupConfig};
+
+    let dir = std::env::temp_dir().join(format!("fatum-restore-oldest-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let db = Db::new(&format!("sqlite:{}", dir.join("live.db").display())).await.unwrap();
+    let config = BackupConfig { dir: dir.join("backups"), interval: None, keep: 2 };
+    let alice = db.create_user("alice", false).await.unwrap();
+
+    db.create_profile(alice, &fields("Oldest")).await.unwrap();
+    let oldest = backup::create_backup(&db, &config, None).await.unwrap();
+    // Backup names carry millisecond timestamps
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    db.create_profile(alice, &fields("Newest")).await.unwrap();
+    backup::create_backup(&db, &config, None).await.unwrap();
+    assert_eq!(backup::list_backups(&config).unwrap().len(), config.keep);
+
+    // The pre-restore snapshot must not prune the file being restored
+    let path = backup::find_backup(&config, &oldest.name).unwrap();
+    let safety = backup::restore_backup(&db, &config, &path).await.unwrap();
+
+    let names: Vec<_> = db.list_profiles(alice).await.unwrap().into_iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["Oldest"]);
+    let kept: Vec<_> = backup::list_backups(&config).unwrap().into_iter().map(|b| b.name).collect();
+    assert_eq!(kept.len(), config.keep);
+    assert_eq!(kept[0], safety.name);
+
+    db.pool.close().await;
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_first_user_is_created_only_once() {
+    let db = Db::in_memory().await.unwrap();
+    let id = db.create_first_user("root").await.unwrap().unwrap();
+    assert!(db.get_user(id).await.unwrap().unwrap().is_admin);
+    assert_eq!(db.create_first_user("intruder").await.unwrap(), None);
+    assert_eq!(db.count_users().await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_user_entropy_usage_accumulates_per_day() {
+    let (db, alice, bob) = setup().await;
+    let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    db.add_user_entropy_usage(alice, today, 1024).await.unwrap();
+    db.add_user_entropy_usage(alice, today, 512).await.unwrap();
+
+    assert_eq!(db.get_user_entropy_usage(alice, today).await.unwrap(), 1536);
+    assert_eq!(db.get_user_entropy_usage(alice, today.succ_opt().unwrap()).await.unwrap(), 0);
+    assert_eq!(db.get_user_entropy_usage(bob, today).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_entropy_pulses_stored_as_bytes() {
+    let (db, alice, _) = setup().await;
+    let batch = db.create_batch("pulses", Some(alice)).await.unwrap();
+    let pulse: Vec<u8> = (0..64).map(|i| (i * 7) as u8).collect();
+    db.insert_entropy(batch, Some(42), &pulse).await.unwrap();
+
+    // Rows from before the switch to BLOBs still read back through their hex text
+    sqlx::query("INSERT INTO quantum_entropy_data (batch_id, hex_value) VALUES ($1, 'deadbeef')")
+        .bind(batch)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+    let rows = db.get_batch_entropy(batch).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].pulse, pulse);
+    assert_eq!(rows[0].pulse_round, Some(42));
+    assert_eq!(rows[1].pulse, vec![0xde, 0xad, 0xbe, 0xef]);
+
+    let stored: Vec<u8> = sqlx::query_scalar("SELECT pulse FROM quantum_entropy_data WHERE id = $1")
+        .bind(rows[0].id)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 64, "stored raw, not hex encoded");
+}
+
+#[tokio::test]
+async fn test_presets_are_scoped_and_unique() {
+    let (db, alice, bob) = setup().await;
+    let config = serde_json::json!({ "construction_year": 1995, "facing_degrees": 180.0 });
+    let id = db.create_preset(alice, "fengshui", "Home", &config).await.unwrap();
+    db.create_preset(alice, "zeri", "Wedding", &serde_json::json!({})).await.unwrap();
+    assert!(db.create_preset(alice, "fengshui", "Home", &config).await.is_err(), "names are unique per tool");
+    db.create_preset(bob, "fengshui", "Home", &config).await.unwrap();
+
+    let fengshui = db.list_presets(alice, Some("fengshui")).await.unwrap();
+    assert_eq!(fengshui.len(), 1);
+    assert_eq!(fengshui[0].config, config);
+    assert_eq!(db.list_presets(alice, None).await.unwrap().len(), 2);
+
+    let moved = serde_json::json!({ "construction_year": 2004, "facing_degrees": 90.0 });
+    assert!(db.update_preset(id, alice, "New home", &moved).await.unwrap());
+    let preset = db.get_preset(id, alice).await.unwrap().unwrap();
+    assert_eq!((preset.name.as_str(), &preset.config), ("New home", &moved));
+
+    assert!(db.get_preset(id, bob).await.unwrap().is_none());
+    assert!(!db.delete_preset(id, bob).await.unwrap());
+    assert!(db.delete_preset(id, alice).await.unwrap());
+    assert!(db.get_preset(id, alice).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_due_schedules_are_claimed_once() {
+    let (db, alice, bob) = setup().await;
+    let fields = ScheduleFields {
+        name: "Morning chart".to_string(),
+        tool_type: "fengshui".to_string(),
+        config: serde_json::json!({ "facing_degrees": 180.0 }),
+        cron: "0 6 * * *".to_string(),
+        webhook_url: None,
+        enabled: true,
+    };
+    let six = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(6, 0, 0).unwrap();
+    let id = db.create_schedule(alice, &fields, Some(six), "whsec_s").await.unwrap();
+    let paused = ScheduleFields { enabled: false, ..fields.clone() };
+    db.create_schedule(alice, &paused, Some(six), "whsec_p").await.unwrap();
+
+    assert!(db.due_schedules(six - chrono::Duration::minutes(1)).await.unwrap().is_empty());
+    let due = db.due_schedules(six).await.unwrap();
+    assert_eq!(due.iter().map(|s| s.id).collect::<Vec<_>>(), vec![id], "disabled schedules never run");
+
+    let tomorrow = six + chrono::Duration::days(1);
+    assert!(db.claim_schedule(id, six, Some(tomorrow)).await.unwrap());
+    assert!(!db.claim_schedule(id, six, Some(tomorrow)).await.unwrap(), "a run is claimed once");
+    assert!(db.due_schedules(six + chrono::Duration::hours(1)).await.unwrap().is_empty());
+
+    db.finish_schedule_run(id, None, Some("beacon down")).await.unwrap();
+    let schedule = db.get_schedule(id, alice).await.unwrap().unwrap();
+    assert_eq!(schedule.next_run_at, Some(tomorrow));
+    assert_eq!(schedule.last_error.as_deref(), Some("beacon down"));
+    assert!(schedule.last_run_at.is_some());
+    assert_eq!(schedule.signing_secret().unwrap().as_deref(), Some("whsec_s"));
+    assert!(db.get_schedule(id, bob).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_webhooks_filter_by_event_and_owner() {
+    let (db, alice, bob) = setup().await;
+    let all = db.create_webhook(alice, "https://example.com/all", "whsec_a", &[], true).await.unwrap();
+    let harvests = db.create_webhook(alice, "https://example.com/harvest", "whsec_h", &["harvest.completed".to_string()], true).await.unwrap();
+    db.create_webhook(alice, "https://example.com/off", "whsec_o", &[], false).await.unwrap();
+    db.create_webhook(bob, "https://example.com/bob", "whsec_b", &[], true).await.unwrap();
+
+    let ids = |hooks: Vec<crate::db::Webhook>| hooks.iter().map(|h| h.id).collect::<Vec<_>>();
+    assert_eq!(ids(db.webhooks_for_event(alice, "harvest.completed").await.unwrap()), vec![all, harvests]);
+    assert_eq!(ids(db.webhooks_for_event(alice, "anomaly.detected").await.unwrap()), vec![all], "disabled and unsubscribed hooks are skipped");
+
+    let hook = db.get_webhook(all, alice).await.unwrap().unwrap();
+    assert_eq!(hook.signing_secret().unwrap(), "whsec_a");
+    assert!(db.get_webhook(all, bob).await.unwrap().is_none());
+    assert!(!db.delete_webhook(all, bob).await.unwrap());
+
+    db.record_webhook_delivery(all, Some(503), Some("HTTP 503 Service Unavailable")).await.unwrap();
+    let hook = db.get_webhook(all, alice).await.unwrap().unwrap();
+    assert_eq!(hook.last_status, Some(503));
+    assert!(hook.last_delivery_at.is_some());
+
+    assert!(db.update_webhook(all, alice, "https://example.com/all", &[], false).await.unwrap());
+    assert!(db.webhooks_for_event(alice, "anomaly.detected").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_trips_are_scoped_and_outlive_their_report() {
+    let (db, alice, bob) = setup().await;
+    let scan = db.insert_history(alice, &NewHistory { tool_type: "geolocation".to_string(), ..entry(None, "scan") }).await.unwrap();
+    let target = TripTarget {
+        history_id: scan,
+        target_kind: "attractor".to_string(),
+        target_rank: 1,
+        latitude: 51.5,
+        longitude: -0.12,
+        power: 4.2,
+    };
+    let visit = |day: u32, rating: i64| TripFields {
+        visited_at: NaiveDate::from_ymd_opt(2024, 8, day).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+        notes: Some("Found a fox".to_string()),
+        photos: vec![PhotoMeta { file_name: "fox.jpg".to_string(), taken_at: None, latitude: Some(51.5), longitude: None, caption: None }],
+        rating,
+    };
+    let late = db.create_trip(alice, &target, &visit(9, 4)).await.unwrap();
+    let early = db.create_trip(alice, &TripTarget { target_kind: "void".to_string(), ..target.clone() }, &visit(2, 2)).await.unwrap();
+
+    let trips = db.list_trips(alice, Some(scan)).await.unwrap();
+    assert_eq!(trips.iter().map(|t| t.id).collect::<Vec<_>>(), vec![early, late], "oldest visit first");
+    assert_eq!(trips[1].photos[0].file_name, "fox.jpg");
+    assert!(db.list_trips(alice, Some(scan + 1)).await.unwrap().is_empty());
+    assert!(db.list_trips(bob, None).await.unwrap().is_empty());
+    assert!(db.get_trip(late, bob).await.unwrap().is_none());
+    assert!(!db.update_trip(late, bob, &visit(9, 1)).await.unwrap());
+
+    assert!(db.update_trip(late, alice, &TripFields { photos: vec![], ..visit(10, 5) }).await.unwrap());
+    let trip = db.get_trip(late, alice).await.unwrap().unwrap();
+    assert_eq!((trip.rating, trip.photos.len(), trip.power), (5, 0, 4.2));
+
+    // The log survives deleting the report it came from
+    assert!(db.delete_history(scan, alice).await.unwrap());
+    let trip = db.get_trip(late, alice).await.unwrap().unwrap();
+    assert_eq!(trip.history_id, None);
+    assert!(!db.delete_trip(late, bob).await.unwrap());
+    assert!(db.delete_trip(late, alice).await.unwrap());
+    assert_eq!(db.list_trips(alice, None).await.unwrap().len(), 1);
+}
@@ -0,0 +1,72 @@
+/// Walker/Vose alias table for O(1) weighted sampling.
+///
+/// Building the table is O(n); each draw then costs a single uniform number and
+/// one comparison, regardless of how many options there are.
+#[derive(Debug, Clone)]
+pub struct AliasTable {
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    /// Builds the table from non-negative weights (they need not sum to 1).
+    ///
+    /// Negative or non-finite weights count as zero. If nothing is left with
+    /// positive weight, every entry is treated as equally likely.
+    pub fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let clean: Vec<f64> = weights.iter().map(|&w| if w.is_finite() && w > 0.0 { w } else { 0.0 }).collect();
+        let sum: f64 = clean.iter().sum();
+
+        // Scale so the average bucket holds exactly 1.0
+        let mut scaled: Vec<f64> = if sum > 0.0 {
+            clean.iter().map(|w| w * n as f64 / sum).collect()
+        } else {
+            vec![1.0; n]
+        };
+
+        let mut prob = vec![1.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+        let mut small = Vec::new();
+        let mut large = Vec::new();
+        for (i, &p) in scaled.iter().enumerate() {
+            if p < 1.0 { small.push(i); } else { large.push(i); }
+        }
+
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            prob[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] -= 1.0 - scaled[s];
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Leftovers are 1.0 up to floating point drift
+        for i in small.into_iter().chain(large) {
+            prob[i] = 1.0;
+        }
+
+        Self { prob, alias }
+    }
+
+    pub fn len(&self) -> usize {
+        self.prob.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prob.is_empty()
+    }
+
+    /// Maps a uniform `r` in [0, 1) to an index.
+    ///
+    /// The integer part of `r * n` picks the bucket and the fractional part
+    /// decides between the bucket and its alias, so one draw suffices.
+    pub fn sample(&self, r: f64) -> usize {
+        let n = self.prob.len();
+        let scaled = r * n as f64;
+        let bucket = (scaled as usize).min(n - 1);
+        let frac = scaled - bucket as f64;
+        if frac < self.prob[bucket] { bucket } else { self.alias[bucket] }
+    }
+}
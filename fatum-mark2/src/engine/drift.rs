@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use super::SimulationSession;
+
+/// Settings for `SimulationSession::scan_entropy_drift`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriftScanOptions {
+    /// Size of the non-overlapping windows the pool is cut into.
+    pub window_bytes: usize,
+    /// |Z| above which a window's bit bias or run count is reported.
+    pub z_threshold: f64,
+    /// Shortest run of one repeated byte value that is reported.
+    pub min_repeat: usize,
+}
+
+impl Default for DriftScanOptions {
+    fn default() -> Self {
+        Self {
+            window_bytes: 64,
+            z_threshold: 3.0,
+            min_repeat: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotspotKind {
+    /// Too many ones or zeros in the window.
+    Bias,
+    /// Bits flip too rarely (clumping) or too often (alternation).
+    Runs,
+    /// The same byte value repeated back to back.
+    Repeat,
+}
+
+/// A byte range of the entropy pool that deviates from what uniform noise would produce.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntropyHotspot {
+    pub kind: HotspotKind,
+    /// Start offset into the pool (inclusive).
+    pub start: usize,
+    /// End offset into the pool (exclusive).
+    pub end: usize,
+    /// Signed Z-score for `Bias` and `Runs`; run length for `Repeat`.
+    pub score: f64,
+    pub description: String,
+}
+
+impl SimulationSession {
+    /// Scans the raw entropy pool for local statistical anomalies.
+    ///
+    /// Works on the bytes themselves, independent of any options or weights, and
+    /// does not move the cursor. Hotspots are returned in pool order.
+    pub fn scan_entropy_drift(&self, opts: &DriftScanOptions) -> Vec<EntropyHotspot> {
+        let pool = &self.entropy_pool;
+        let mut hotspots = Vec::new();
+
+        // 1. Window checks: bit bias and Wald-Wolfowitz runs test
+        let window = opts.window_bytes.max(1);
+        for (w, chunk) in pool.chunks_exact(window).enumerate() {
+            let start = w * window;
+            let end = start + window;
+            let n = (chunk.len() * 8) as f64;
+            let ones = chunk.iter().map(|b| b.count_ones()).sum::<u32>() as f64;
+
+            let bias_z = (ones - n / 2.0) / (n / 4.0).sqrt();
+            if bias_z.abs() > opts.z_threshold {
+                hotspots.push(EntropyHotspot {
+                    kind: HotspotKind::Bias,
+                    start,
+                    end,
+                    score: bias_z,
+                    description: format!(
+                        "Bytes {}..{} lean toward {} ({:.1}% ones, Z={:.2})",
+                        start, end, if bias_z > 0.0 { "ones" } else { "zeros" }, 100.0 * ones / n, bias_z
+                    ),
+                });
+            }
+
+            if let Some(runs_z) = runs_z_score(chunk) {
+                if runs_z.abs() > opts.z_threshold {
+                    hotspots.push(EntropyHotspot {
+                        kind: HotspotKind::Runs,
+                        start,
+                        end,
+                        score: runs_z,
+                        description: format!(
+                            "Bytes {}..{} {} (runs Z={:.2})",
+                            start, end, if runs_z < 0.0 { "clump into long runs" } else { "alternate too regularly" }, runs_z
+                        ),
+                    });
+                }
+            }
+        }
+
+        // 2. Repeated byte values
+        let min_repeat = opts.min_repeat.max(2);
+        let mut i = 0;
+        while i < pool.len() {
+            let mut j = i + 1;
+            while j < pool.len() && pool[j] == pool[i] {
+                j += 1;
+            }
+            let len = j - i;
+            if len >= min_repeat {
+                hotspots.push(EntropyHotspot {
+                    kind: HotspotKind::Repeat,
+                    start: i,
+                    end: j,
+                    score: len as f64,
+                    description: format!("Byte 0x{:02x} repeats {} times at offset {}", pool[i], len, i),
+                });
+            }
+            i = j;
+        }
+
+        hotspots.sort_by_key(|h| h.start);
+        hotspots
+    }
+}
+
+/// Z-score of the number of bit runs in `bytes`, or `None` if all bits are equal.
+fn runs_z_score(bytes: &[u8]) -> Option<f64> {
+    let bits = bytes.iter().flat_map(|b| (0..8).map(move |k| (b >> k) & 1));
+    let mut ones = 0.0_f64;
+    let mut zeros = 0.0_f64;
+    let mut runs = 0.0_f64;
+    let mut prev = None;
+    for bit in bits {
+        if bit == 1 { ones += 1.0 } else { zeros += 1.0 }
+        if prev != Some(bit) {
+            runs += 1.0;
+        }
+        prev = Some(bit);
+    }
+    let n = ones + zeros;
+    if ones == 0.0 || zeros == 0.0 {
+        return None;
+    }
+    let expected = 2.0 * ones * zeros / n + 1.0;
+    let variance = 2.0 * ones * zeros * (2.0 * ones * zeros - n) / (n * n * (n - 1.0));
+    if variance <= 0.0 {
+        return None;
+    }
+    Some((runs - expected) / variance.sqrt())
+}
@@ -1,13 +1,25 @@
 use std::collections::HashMap;
-use rand::{Rng, SeedableRng};
+use rand::{Rng, RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 pub mod timeline;
+pub mod alias;
+pub mod stats;
+pub mod drift;
+pub mod walk;
+pub mod ranking;
+pub mod tournament;
+
+use alias::AliasTable;
+use stats::{ChiSquareTest, OptionStats};
 
 /// Represents a persistent session for running simulations.
 ///
-/// Holds the master seed derived from the Quantum Entropy source.
+/// Holds the master seed derived from the Quantum Entropy source. The session is a
+/// cursor: every draw advances `pool_index` (or the fallback PRNG), so successive
+/// simulations on the same session never reuse the same bytes.
 #[derive(Debug)]
 pub struct SimulationSession {
     // If we have a stream of pre-fetched quantum numbers, we use them.
@@ -15,6 +27,10 @@ pub struct SimulationSession {
     pub pool_index: usize,
     // Fallback for hybrid mode or if pool runs out (though we want to avoid this in pure mode)
     pub seed: [u8; 32],
+    /// Draws served by the fallback stream because the pool could not cover them.
+    pub fallback_draws: usize,
+    // Fallback stream, seeded once so it also advances across calls.
+    rng: ChaCha20Rng,
 }
 
 /// A snapshot of the simulation at a specific step index.
@@ -27,6 +43,53 @@ pub struct TimeStep {
     pub distribution: HashMap<String, usize>,
 }
 
+/// Tunable knobs for `simulate_decision`.
+///
+/// Missing fields fall back to the defaults when deserialized from a request body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationOptions {
+    /// |Z| above which an option is reported as an anomaly (3.0 ≈ 99.7% significance).
+    pub z_threshold: f64,
+    /// Number of `TimeStep` snapshots to record over the run.
+    pub time_series_points: usize,
+    /// Minimum |Cohen's h| an option must also reach to count as an anomaly.
+    /// Raising it ignores deviations that are significant but practically tiny.
+    pub min_effect_size: f64,
+    /// Enables sequential early stopping: the run halts once the current leader is the
+    /// true most likely option with at least this probability (e.g. 0.99).
+    pub early_stop_confidence: Option<f64>,
+    /// Smallest ratio between the best and second-best option probabilities worth
+    /// telling apart when stopping early. Must be > 1; closer to 1 means longer runs.
+    pub early_stop_ratio: f64,
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        Self {
+            z_threshold: 3.0,
+            time_series_points: 20,
+            min_effect_size: 0.0,
+            early_stop_confidence: None,
+            early_stop_ratio: 1.5,
+        }
+    }
+}
+
+impl SimulationOptions {
+    /// True if `stats` deviate from expectation by more than `z_threshold` with at least `min_effect_size`.
+    pub fn is_anomaly(&self, stats: &OptionStats) -> bool {
+        stats.z_score.abs() > self.z_threshold && stats.effect_size.abs() >= self.min_effect_size
+    }
+}
+
+/// A half-open byte range `[start, end)` of a session's entropy pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntropyRange {
+    pub start: usize,
+    pub end: usize,
+}
+
 /// The result of a simulation run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulationReport {
@@ -35,6 +98,46 @@ pub struct SimulationReport {
     pub distribution: HashMap<String, usize>,
     pub anomalies: Vec<String>,
     pub time_series: Vec<TimeStep>,
+    /// True if early stopping ended the run before the requested iteration count.
+    /// `total_simulations` then holds the iterations actually used.
+    #[serde(default)]
+    pub stopped_early: bool,
+    /// Per-option confidence intervals, z-scores and effect sizes, in option order.
+    #[serde(default)]
+    pub option_stats: Vec<OptionStats>,
+    /// Goodness-of-fit against the expected weights (None for fewer than two live options).
+    #[serde(default)]
+    pub chi_square: Option<ChiSquareTest>,
+    /// Pool bytes this run consumed, so the result can be replayed against the archived batch.
+    #[serde(default)]
+    pub entropy_ranges: Vec<EntropyRange>,
+    /// Draws served by the fallback PRNG after the pool ran out (not covered by `entropy_ranges`).
+    #[serde(default)]
+    pub fallback_draws: usize,
+}
+
+/// Digest identifying `context` under `domain`: SHA-256 of the domain, a zero byte and the context.
+pub fn context_digest(domain: &str, context: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(domain.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(context.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Ties raw entropy to a context (e.g. the question asked) before it seeds a session.
+///
+/// Each 32-byte chunk is replaced by SHA-256(context digest ‖ chunk index ‖ chunk), so the
+/// same beacon bytes give unrelated draws for different contexts. The length is preserved.
+pub fn bind_entropy(entropy: &[u8], domain: &str, context: &str) -> Vec<u8> {
+    let key = context_digest(domain, context);
+    entropy.chunks(32).enumerate().flat_map(|(i, chunk)| {
+        let mut hasher = Sha256::new();
+        hasher.update(key);
+        hasher.update((i as u64).to_le_bytes());
+        hasher.update(chunk);
+        hasher.finalize().into_iter().take(chunk.len())
+    }).collect()
 }
 
 impl SimulationSession {
@@ -49,29 +152,79 @@ impl SimulationSession {
         Self {
             entropy_pool: entropy,
             pool_index: 0,
-            seed
+            seed,
+            fallback_draws: 0,
+            rng: ChaCha20Rng::from_seed(seed),
         }
     }
 
-    // Helper to get next random float [0, 1)
-    pub fn next_f64(&mut self, rng: &mut ChaCha20Rng) -> f64 {
-        // If we have at least 8 bytes left in pool, use them to form f64
+    /// Number of pool bytes not yet consumed.
+    pub fn remaining_pool_bytes(&self) -> usize {
+        self.entropy_pool.len().saturating_sub(self.pool_index)
+    }
+
+    /// Next raw 64-bit value: 8 pool bytes (little endian), or the fallback stream once the pool is spent.
+    pub fn next_u64(&mut self) -> u64 {
         if self.pool_index + 8 <= self.entropy_pool.len() {
             let mut bytes = [0u8; 8];
-            for i in 0..8 {
-                bytes[i] = self.entropy_pool[self.pool_index + i];
-            }
+            bytes.copy_from_slice(&self.entropy_pool[self.pool_index..self.pool_index + 8]);
             self.pool_index += 8;
-            // Convert u64 to f64 [0,1)
-            let u = u64::from_le_bytes(bytes);
-            // Standard conversion: (u >> 11) * 2^-53
-            let f = (u >> 11) as f64 * 1.1102230246251565e-16;
-            return f;
+            return u64::from_le_bytes(bytes);
         }
 
         // Fallback to PRNG if pool empty (Hybrid/Legacy mode)
         // Or if user didn't provide enough entropy.
-        rng.gen()
+        self.fallback_draws += 1;
+        self.rng.next_u64()
+    }
+
+    // Helper to get next random float [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        // Standard conversion: (u >> 11) * 2^-53
+        (self.next_u64() >> 11) as f64 * 1.1102230246251565e-16
+    }
+
+    /// Uniform integer in `[low, high)` without modulo bias.
+    ///
+    /// Draws that fall in the short final block of the u64 range are rejected and
+    /// redrawn, so every value is exactly equally likely. Panics if `low >= high`.
+    pub fn next_u64_in_range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "next_u64_in_range: empty range {}..{}", low, high);
+        let span = high - low;
+        // 2^64 mod span: values below this would over-represent the low residues
+        let threshold = span.wrapping_neg() % span;
+        loop {
+            let x = self.next_u64();
+            if x >= threshold {
+                return low + x % span;
+            }
+        }
+    }
+
+    /// True with probability `p` (clamped to [0, 1]).
+    pub fn next_bool(&mut self, p: f64) -> bool {
+        self.next_f64() < p.clamp(0.0, 1.0)
+    }
+
+    /// Shuffles `items` in place (Fisher-Yates), every permutation equally likely.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.next_u64_in_range(0, i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// Draws 32 bytes for seeding a derived generator, advancing the cursor.
+    pub fn next_seed(&mut self) -> [u8; 32] {
+        let mut seed = [0u8; 32];
+        if self.pool_index + 32 <= self.entropy_pool.len() {
+            seed.copy_from_slice(&self.entropy_pool[self.pool_index..self.pool_index + 32]);
+            self.pool_index += 32;
+        } else {
+            self.fallback_draws += 1;
+            self.rng.fill(&mut seed);
+        }
+        seed
     }
 
     /// Runs a Monte Carlo simulation to select an option from the list.
@@ -79,24 +232,31 @@ impl SimulationSession {
     /// * `options`: The list of choices (e.g., "North", "South").
     /// * `weights`: Optional probability weights. If None, assumes equal probability.
     /// * `simulations`: Number of iterations to run (e.g., 1,000,000).
+    /// * `sim_options`: Anomaly thresholds and time-series resolution.
+    ///
+    /// Consumes entropy from the session, so repeated calls continue where the
+    /// previous one stopped rather than replaying the same bytes.
     pub fn simulate_decision(
-        &self,
+        &mut self,
         options: &[String],
         weights: Option<&[f64]>,
-        simulations: usize
+        simulations: usize,
+        sim_options: &SimulationOptions,
     ) -> SimulationReport {
-        // We need mutable access to consume the pool.
-        // But simulate_decision takes &self.
-        // We will clone the session locally or use RefCell.
-        // Given the signature, let's clone the pool logic or modify the signature.
-        // But to avoid breaking all callers, let's internally use a mutable copy of the index/pool.
-        // Actually, since SimulationSession owns the pool, it should be mutable.
-        // But we can't change signature easily without refactoring everything.
-        // However, this is a "Tool", so we can cheat by using interior mutability or just copying the necessary parts.
-
-        // Better approach: Create a local mutable "runner" from self.
-        let mut local_pool_index = self.pool_index;
+        self.simulate_decision_with_progress(options, weights, simulations, sim_options, |_| {})
+    }
 
+    /// Same as `simulate_decision`, but invokes `on_step` with every `TimeStep` as soon
+    /// as it is recorded, so callers can stream convergence while the run is in progress.
+    #[tracing::instrument(name = "simulate_decision", skip_all, fields(options = options.len(), simulations))]
+    pub fn simulate_decision_with_progress<F: FnMut(&TimeStep)>(
+        &mut self,
+        options: &[String],
+        weights: Option<&[f64]>,
+        simulations: usize,
+        sim_options: &SimulationOptions,
+        mut on_step: F,
+    ) -> SimulationReport {
         let mut distribution: HashMap<String, usize> = HashMap::new();
         for opt in options {
             distribution.insert(opt.clone(), 0);
@@ -110,39 +270,36 @@ impl SimulationSession {
                 distribution,
                 anomalies: vec![],
                 time_series: vec![],
+                stopped_early: false,
+                option_stats: vec![],
+                chi_square: None,
+                entropy_ranges: vec![],
+                fallback_draws: 0,
             };
         }
 
-        // Initialize CSPRNG with the quantum seed (as fallback)
-        let mut rng = ChaCha20Rng::from_seed(self.seed);
         let mut counts = vec![0; num_options];
         let mut time_series = Vec::new();
 
-        // Build Cumulative Distribution Function (CDF) for weighted selection
-        let mut cdf = Vec::with_capacity(num_options);
-        if let Some(w) = weights {
-            let sum: f64 = w.iter().sum();
-            let mut acc = 0.0;
-            for &val in w {
-                acc += val / sum;
-                cdf.push(acc);
-            }
-        } else {
-            // Equal weights
-            let step = 1.0 / num_options as f64;
-            let mut acc = 0.0;
-            for _ in 0..num_options {
-                acc += step;
-                cdf.push(acc);
+        // Normalized option probabilities (missing weights count as zero)
+        let probs: Vec<f64> = match weights {
+            Some(w) => {
+                let raw: Vec<f64> = (0..num_options).map(|i| w.get(i).copied().unwrap_or(0.0)).collect();
+                let table_sum: f64 = raw.iter().filter(|v| v.is_finite() && **v > 0.0).sum();
+                if table_sum > 0.0 {
+                    raw.iter().map(|v| if v.is_finite() && *v > 0.0 { v / table_sum } else { 0.0 }).collect()
+                } else {
+                    vec![1.0 / num_options as f64; num_options]
+                }
             }
-        }
-        // Clamp final value to 1.0 to handle floating point drift
-        if let Some(last) = cdf.last_mut() {
-            *last = 1.0;
-        }
+            None => vec![1.0 / num_options as f64; num_options],
+        };
 
-        // Determine reporting interval (record ~20 data points)
-        let step_size = (simulations / 20).max(1);
+        // Alias table gives O(1) draws regardless of option count
+        let sampler = AliasTable::new(&probs);
+
+        // Determine reporting interval (record ~time_series_points data points)
+        let step_size = (simulations / sim_options.time_series_points.max(1)).max(1);
 
         // Adjust simulation count if strictly using pool?
         // For now, we attempt to use pool, fallback to RNG if needed,
@@ -150,46 +307,71 @@ impl SimulationSession {
         // The user wanted "ONLY use quantum random numbers", but if they request 1M sims and have 1KB entropy,
         // we can't do it. We will proceed with what we have.
 
-        for i in 1..=simulations {
-            // Manual next_f64 logic using local index
-            let r: f64 = if local_pool_index + 8 <= self.entropy_pool.len() {
-                let mut bytes = [0u8; 8];
-                for k in 0..8 {
-                    bytes[k] = self.entropy_pool[local_pool_index + k];
-                }
-                local_pool_index += 8;
-                let u = u64::from_le_bytes(bytes);
-                (u >> 11) as f64 * 1.1102230246251565e-16
-            } else {
-                rng.gen()
-            };
+        // Sequential stopping rule (Bechhofer-Kiefer-Sobel): stop once
+        // sum_{j != leader} ratio^-(n_leader - n_j) <= (1 - confidence) / confidence
+        let stop_rule = sim_options.early_stop_confidence
+            .filter(|c| *c > 0.0 && *c < 1.0 && num_options > 1 && sim_options.early_stop_ratio > 1.0)
+            .map(|c| ((1.0 - c) / c, sim_options.early_stop_ratio.ln()));
+        // Checking costs O(options), so thin it out for very wide option lists
+        let check_every = (num_options / 8).max(1);
 
-            // Select option based on CDF
-            let mut choice_idx = 0;
-            for (idx, &threshold) in cdf.iter().enumerate() {
-                if r <= threshold {
-                    choice_idx = idx;
-                    break;
-                }
-            }
-            if choice_idx >= num_options { choice_idx = num_options - 1; }
+        let pool_start = self.pool_index;
+        let mut used = simulations;
+        for i in 1..=simulations {
+            let r = self.next_f64();
 
+            let choice_idx = sampler.sample(r);
             counts[choice_idx] += 1;
 
+            let decided = match stop_rule {
+                Some((bound, ln_ratio)) if i % check_every == 0 => {
+                    let lead = counts.iter().copied().max().unwrap_or(0);
+                    let mut leader_seen = false;
+                    let mut sum = 0.0;
+                    for &c in &counts {
+                        if c == lead && !leader_seen {
+                            leader_seen = true;
+                            continue;
+                        }
+                        sum += (-((lead - c) as f64) * ln_ratio).exp();
+                    }
+                    sum <= bound
+                }
+                _ => false,
+            };
+
             // Record Time Series Data
-            if i % step_size == 0 || i == simulations {
+            if i % step_size == 0 || i == simulations || decided {
                  let mut step_dist = HashMap::new();
                  for (idx, count) in counts.iter().enumerate() {
                     if let Some(opt) = options.get(idx) {
                         step_dist.insert(opt.clone(), *count);
                     }
                  }
-                 time_series.push(TimeStep {
+                 let step = TimeStep {
                      step_index: i,
                      distribution: step_dist,
-                 });
+                 };
+                 on_step(&step);
+                 time_series.push(step);
+            }
+
+            if decided {
+                used = i;
+                break;
             }
         }
+        let stopped_early = used < simulations;
+        let simulations = used;
+
+        // One draw is 8 pool bytes; whatever the pool could not cover came from the fallback stream
+        let pool_end = self.pool_index;
+        let entropy_ranges = if pool_end > pool_start {
+            vec![EntropyRange { start: pool_start, end: pool_end }]
+        } else {
+            vec![]
+        };
+        let fallback_draws = simulations - (pool_end - pool_start) / 8;
 
         // Populate final results
         for (i, count) in counts.iter().enumerate() {
@@ -208,35 +390,33 @@ impl SimulationSession {
             }
         }
 
-        // Anomaly Detection (Z-Score Analysis)
+        // Anomaly Detection (Z-Score Analysis) and per-option statistics
         let mut anomalies = Vec::new();
+        let mut option_stats = Vec::with_capacity(num_options);
         for (idx, opt) in options.iter().enumerate() {
-            let weight_prob = if let Some(w) = weights {
-                 w[idx] / w.iter().sum::<f64>()
-            } else {
-                 1.0 / num_options as f64
-            };
-
-            let expected = simulations as f64 * weight_prob;
-            let std_dev = (simulations as f64 * weight_prob * (1.0 - weight_prob)).sqrt();
+            let opt_stats = OptionStats::new(opt.clone(), counts[idx], simulations, probs[idx]);
 
-            let count = *distribution.get(opt).unwrap_or(&0);
-            let diff = count as f64 - expected;
-            let z_score = if std_dev > 0.0 { diff / std_dev } else { 0.0 };
-
-            // Z-Score > 3.0 indicates 99.7% significance (statistically unlikely event)
-            if z_score.abs() > 3.0 {
-                 let direction = if z_score > 0.0 { "high" } else { "low" };
-                 anomalies.push(format!("Option '{}' is significant {} (Z={:.2})", opt, direction, z_score));
+            // Z-Score > 3.0 (the default) indicates 99.7% significance (statistically unlikely event)
+            if sim_options.is_anomaly(&opt_stats) {
+                 let direction = if opt_stats.z_score > 0.0 { "high" } else { "low" };
+                 anomalies.push(format!("Option '{}' is significant {} (Z={:.2})", opt, direction, opt_stats.z_score));
             }
+            option_stats.push(opt_stats);
         }
 
+        let chi_square = stats::chi_square_test(&counts, &probs);
+
         SimulationReport {
             total_simulations: simulations,
             winner,
             distribution,
             anomalies,
             time_series,
+            stopped_early,
+            option_stats,
+            chi_square,
+            entropy_ranges,
+            fallback_draws,
         }
     }
 }
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use super::SimulationSession;
+
+/// Repeat-run statistics for draws without replacement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingReport {
+    pub runs: usize,
+    /// Size of each draw (clamped to the number of options).
+    pub k: usize,
+    /// The ordered draw from the final run.
+    pub last_draw: Vec<String>,
+    /// How often each option made it into the drawn set.
+    pub top_k_frequency: HashMap<String, usize>,
+    /// `position_counts[option][p]` is how often the option was drawn at position p (0-based).
+    pub position_counts: HashMap<String, Vec<usize>>,
+    /// Options ordered by Borda points (k points for first place down to 1 for k-th).
+    pub consensus_ranking: Vec<String>,
+    /// The unordered set drawn most often, with its count.
+    pub most_common_set: Vec<String>,
+    pub most_common_set_count: usize,
+}
+
+impl SimulationSession {
+    /// Draws `k` distinct options in quantum order (a full shuffle when `k == options.len()`).
+    ///
+    /// With weights this is successive weighted sampling (Plackett-Luce): each pick is
+    /// proportional to the weights of the options still left. Options with zero weight
+    /// only come up once every positively weighted option has been drawn.
+    pub fn draw_k_of_n(&mut self, options: &[String], weights: Option<&[f64]>, k: usize) -> Vec<String> {
+        self.draw_indices(options.len(), weights, k)
+            .into_iter()
+            .map(|i| options[i].clone())
+            .collect()
+    }
+
+    /// Repeats `draw_k_of_n` `runs` times and aggregates how options rank.
+    pub fn simulate_ranking(
+        &mut self,
+        options: &[String],
+        weights: Option<&[f64]>,
+        k: usize,
+        runs: usize,
+    ) -> RankingReport {
+        let n = options.len();
+        let k = k.min(n);
+        let mut top_k = vec![0usize; n];
+        let mut positions = vec![vec![0usize; k]; n];
+        let mut borda = vec![0usize; n];
+        let mut sets: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut last = Vec::new();
+
+        for _ in 0..runs {
+            let draw = self.draw_indices(n, weights, k);
+            for (pos, &idx) in draw.iter().enumerate() {
+                top_k[idx] += 1;
+                positions[idx][pos] += 1;
+                borda[idx] += k - pos;
+            }
+            let mut set = draw.clone();
+            set.sort_unstable();
+            *sets.entry(set).or_insert(0) += 1;
+            last = draw;
+        }
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| borda[b].cmp(&borda[a]).then(a.cmp(&b)));
+
+        // Ties go to the lexicographically smallest index set so the report is deterministic
+        let (best_set, best_count) = sets.into_iter()
+            .max_by(|(sa, ca), (sb, cb)| ca.cmp(cb).then(sb.cmp(sa)))
+            .unwrap_or_default();
+
+        RankingReport {
+            runs,
+            k,
+            last_draw: last.into_iter().map(|i| options[i].clone()).collect(),
+            top_k_frequency: options.iter().cloned().zip(top_k).collect(),
+            position_counts: options.iter().cloned().zip(positions).collect(),
+            consensus_ranking: order.into_iter().map(|i| options[i].clone()).collect(),
+            most_common_set: best_set.into_iter().map(|i| options[i].clone()).collect(),
+            most_common_set_count: best_count,
+        }
+    }
+
+    fn draw_indices(&mut self, n: usize, weights: Option<&[f64]>, k: usize) -> Vec<usize> {
+        let mut remaining: Vec<(usize, f64)> = (0..n)
+            .map(|i| {
+                let w = match weights {
+                    Some(w) => w.get(i).copied().filter(|v| v.is_finite() && *v > 0.0).unwrap_or(0.0),
+                    None => 1.0,
+                };
+                (i, w)
+            })
+            .collect();
+
+        let mut drawn = Vec::with_capacity(k.min(n));
+        while drawn.len() < k && !remaining.is_empty() {
+            let total: f64 = remaining.iter().map(|(_, w)| w).sum();
+            let pick = if total > 0.0 {
+                // Walk the remaining weights; fall back to the last positive one on rounding
+                let target = self.next_f64() * total;
+                let mut acc = 0.0;
+                let mut chosen = None;
+                for (pos, (_, w)) in remaining.iter().enumerate() {
+                    if *w <= 0.0 {
+                        continue;
+                    }
+                    acc += w;
+                    chosen = Some(pos);
+                    if target < acc {
+                        break;
+                    }
+                }
+                chosen.unwrap_or(0)
+            } else {
+                self.next_u64_in_range(0, remaining.len() as u64) as usize
+            };
+            drawn.push(remaining.remove(pick).0);
+        }
+        drawn
+    }
+}
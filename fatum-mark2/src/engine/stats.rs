@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+
+/// Two-sided 95% normal quantile.
+pub const Z_95: f64 = 1.959963984540054;
+
+/// Per-option statistics attached to a `SimulationReport`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptionStats {
+    pub option: String,
+    pub count: usize,
+    pub observed_probability: f64,
+    pub expected_probability: f64,
+    /// 95% Wilson score interval for the observed probability.
+    pub ci_low: f64,
+    pub ci_high: f64,
+    pub z_score: f64,
+    /// Cohen's h between observed and expected probability (0.2 small, 0.5 medium, 0.8 large).
+    pub effect_size: f64,
+}
+
+impl OptionStats {
+    /// Scores `count` hits out of `total` trials against the `expected_probability` of a hit.
+    pub fn new(option: String, count: usize, total: usize, expected_probability: f64) -> Self {
+        let expected = total as f64 * expected_probability;
+        let std_dev = (total as f64 * expected_probability * (1.0 - expected_probability)).sqrt();
+        let z_score = if std_dev > 0.0 { (count as f64 - expected) / std_dev } else { 0.0 };
+        let observed_probability = if total > 0 { count as f64 / total as f64 } else { 0.0 };
+        let (ci_low, ci_high) = wilson_interval(count, total, Z_95);
+        Self {
+            option,
+            count,
+            observed_probability,
+            expected_probability,
+            ci_low,
+            ci_high,
+            z_score,
+            effect_size: cohens_h(observed_probability, expected_probability),
+        }
+    }
+}
+
+/// Pearson chi-square goodness-of-fit of the observed counts against the expected weights.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChiSquareTest {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+    /// Cramér's V style effect size: sqrt(chi2 / (N * df)).
+    pub effect_size: f64,
+}
+
+/// Wilson score interval for `successes` out of `n` trials.
+pub fn wilson_interval(successes: usize, n: usize, z: f64) -> (f64, f64) {
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+    let n = n as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let denom = 1.0 + z2 / n;
+    let centre = (p + z2 / (2.0 * n)) / denom;
+    let half = z * ((p * (1.0 - p) / n) + z2 / (4.0 * n * n)).sqrt() / denom;
+    // At p = 0 or 1 the matching bound is exact; avoid rounding drift past the point estimate
+    let low = if successes == 0 { 0.0 } else { (centre - half).max(0.0) };
+    let high = if p >= 1.0 { 1.0 } else { (centre + half).min(1.0) };
+    (low, high)
+}
+
+/// Cohen's h effect size between two proportions.
+pub fn cohens_h(p1: f64, p2: f64) -> f64 {
+    2.0 * p1.clamp(0.0, 1.0).sqrt().asin() - 2.0 * p2.clamp(0.0, 1.0).sqrt().asin()
+}
+
+/// Chi-square test of `counts` against `expected_probs` (which sum to 1).
+///
+/// Categories with zero expected probability are left out. Returns `None` if
+/// fewer than two categories remain or there were no trials.
+pub fn chi_square_test(counts: &[usize], expected_probs: &[f64]) -> Option<ChiSquareTest> {
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let mut statistic = 0.0;
+    let mut categories = 0;
+    for (&count, &p) in counts.iter().zip(expected_probs) {
+        if p <= 0.0 {
+            continue;
+        }
+        let expected = total as f64 * p;
+        let diff = count as f64 - expected;
+        statistic += diff * diff / expected;
+        categories += 1;
+    }
+    if categories < 2 {
+        return None;
+    }
+    let df = categories - 1;
+    Some(ChiSquareTest {
+        statistic,
+        degrees_of_freedom: df,
+        p_value: chi_square_sf(statistic, df),
+        effect_size: (statistic / (total as f64 * df as f64)).sqrt(),
+    })
+}
+
+/// Survival function P(X >= x) of the chi-square distribution with `df` degrees of freedom.
+pub fn chi_square_sf(x: f64, df: usize) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    gamma_q(df as f64 / 2.0, x / 2.0)
+}
+
+/// Regularized upper incomplete gamma Q(a, x) (Numerical Recipes, series / continued fraction).
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x < a + 1.0 {
+        1.0 - gamma_p_series(a, x)
+    } else {
+        gamma_q_continued_fraction(a, x)
+    }
+}
+
+fn gamma_p_series(a: f64, x: f64) -> f64 {
+    let mut ap = a;
+    let mut sum = 1.0 / a;
+    let mut del = sum;
+    for _ in 0..500 {
+        ap += 1.0;
+        del *= x / ap;
+        sum += del;
+        if del.abs() < sum.abs() * 1e-15 {
+            break;
+        }
+    }
+    (sum.ln() - x + a * x.ln() - ln_gamma(a)).exp()
+}
+
+fn gamma_q_continued_fraction(a: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / TINY;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..500 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < TINY { d = TINY; }
+        c = b + an / c;
+        if c.abs() < TINY { c = TINY; }
+        d = 1.0 / d;
+        let del = d * c;
+        h *= del;
+        if (del - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    (-x + a * x.ln() - ln_gamma(a)).exp() * h
+}
+
+/// Lanczos approximation of ln Γ(x) for x > 0.
+fn ln_gamma(x: f64) -> f64 {
+    const COEF: [f64; 6] = [
+        76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5,
+    ];
+    let mut y = x;
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut ser = 1.000000000190015;
+    for c in COEF {
+        y += 1.0;
+        ser += c / y;
+    }
+    -tmp + (2.5066282746310005 * ser / x).ln()
+}
@@ -1,6 +1,11 @@
 #[cfg(test)]
 mod tests {
-    use crate::engine::SimulationSession;
+    use crate::engine::{EntropyRange, SimulationOptions, SimulationSession};
+    use crate::engine::alias::AliasTable;
+    use crate::engine::stats;
+    use crate::engine::drift::{DriftScanOptions, HotspotKind};
+    use crate::engine::tournament::{elo_expected, Bracket, TournamentConfig, INITIAL_RATING};
+    use std::time::Instant;
 
     #[test]
     fn test_simulation_distribution() {
@@ -14,10 +19,10 @@ mod tests {
         // For this test, we'll verify structural correctness.
 
         let entropy = vec![1, 3, 5, 2];
-        let session = SimulationSession::new(entropy);
+        let mut session = SimulationSession::new(entropy);
         let options = vec!["A".to_string(), "B".to_string()];
 
-        let report = session.simulate_decision(&options, None, 100);
+        let report = session.simulate_decision(&options, None, 100, &SimulationOptions::default());
 
         assert_eq!(report.total_simulations, 100);
         assert!(report.distribution.contains_key("A"));
@@ -29,10 +34,10 @@ mod tests {
     #[test]
     fn test_empty_options() {
         let entropy = vec![1, 2, 3];
-        let session = SimulationSession::new(entropy);
+        let mut session = SimulationSession::new(entropy);
         let options: Vec<String> = vec![];
 
-        let report = session.simulate_decision(&options, None, 10);
+        let report = session.simulate_decision(&options, None, 10, &SimulationOptions::default());
 
         assert_eq!(report.total_simulations, 0);
         assert_eq!(report.winner, "None");
@@ -42,13 +47,13 @@ mod tests {
     fn test_consistency_from_same_seed() {
         // Same entropy should produce same results (deterministic PRNG from seed)
         let entropy = vec![42, 100, 200];
-        let session1 = SimulationSession::new(entropy.clone());
-        let session2 = SimulationSession::new(entropy.clone());
+        let mut session1 = SimulationSession::new(entropy.clone());
+        let mut session2 = SimulationSession::new(entropy.clone());
 
         let options = vec!["A".to_string(), "B".to_string(), "C".to_string()];
 
-        let report1 = session1.simulate_decision(&options, None, 1000);
-        let report2 = session2.simulate_decision(&options, None, 1000);
+        let report1 = session1.simulate_decision(&options, None, 1000, &SimulationOptions::default());
+        let report2 = session2.simulate_decision(&options, None, 1000, &SimulationOptions::default());
 
         assert_eq!(report1.winner, report2.winner);
         assert_eq!(report1.distribution, report2.distribution);
@@ -67,15 +72,437 @@ mod tests {
             entropy[i] = 0xFF;
         }
 
-        let session = SimulationSession::new(entropy.clone());
+        let mut session = SimulationSession::new(entropy.clone());
         let options = vec!["A".to_string(), "B".to_string()];
 
         // Run 2 simulations.
-        let report = session.simulate_decision(&options, None, 2);
+        let report = session.simulate_decision(&options, None, 2, &SimulationOptions::default());
 
         // Iteration 1: 0.0 -> A
         // Iteration 2: ~1.0 -> B
         assert_eq!(*report.distribution.get("A").unwrap(), 1);
         assert_eq!(*report.distribution.get("B").unwrap(), 1);
     }
+
+    #[test]
+    fn test_session_cursor_persists_across_calls() {
+        // Pool of 2 draws: first all 0x00 (-> A), then all 0xFF (-> B).
+        // With a persistent cursor, the second call must see the 0xFF bytes.
+        let mut entropy = vec![0u8; 16];
+        for b in entropy.iter_mut().skip(8) {
+            *b = 0xFF;
+        }
+
+        let mut session = SimulationSession::new(entropy);
+        let options = vec!["A".to_string(), "B".to_string()];
+
+        let first = session.simulate_decision(&options, None, 1, &SimulationOptions::default());
+        assert_eq!(session.pool_index, 8);
+        let second = session.simulate_decision(&options, None, 1, &SimulationOptions::default());
+        assert_eq!(session.pool_index, 16);
+        assert_eq!(session.remaining_pool_bytes(), 0);
+
+        assert_eq!(first.winner, "A");
+        assert_eq!(second.winner, "B");
+    }
+
+    #[test]
+    fn test_fallback_rng_advances_across_calls() {
+        // Empty pool: everything comes from the fallback PRNG, which must not restart per call.
+        let mut session = SimulationSession::new(vec![7, 7, 7]);
+        let a = session.next_f64();
+        let b = session.next_f64();
+        assert_ne!(a, b);
+
+        let options: Vec<String> = (0..16).map(|i| i.to_string()).collect();
+        let r1 = session.simulate_decision(&options, None, 200, &SimulationOptions::default());
+        let r2 = session.simulate_decision(&options, None, 200, &SimulationOptions::default());
+        assert_ne!(r1.distribution, r2.distribution);
+    }
+
+    #[test]
+    fn test_progress_callback_matches_time_series() {
+        let mut session = SimulationSession::new(vec![9, 8, 7, 6]);
+        let options = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+
+        let mut streamed = Vec::new();
+        let report = session.simulate_decision_with_progress(&options, None, 1000, &SimulationOptions::default(), |step| {
+            streamed.push(step.step_index);
+        });
+
+        let recorded: Vec<usize> = report.time_series.iter().map(|s| s.step_index).collect();
+        assert_eq!(streamed, recorded);
+        assert_eq!(streamed.last(), Some(&1000));
+    }
+
+    #[test]
+    fn test_alias_table_matches_weights() {
+        // Sweep r over a fine uniform grid; bucket frequencies must match the weights.
+        let weights = [1.0, 2.0, 3.0, 4.0];
+        let table = AliasTable::new(&weights);
+        let mut counts = [0usize; 4];
+        let steps = 100_000;
+        for i in 0..steps {
+            let r = (i as f64 + 0.5) / steps as f64;
+            counts[table.sample(r)] += 1;
+        }
+        for (i, &w) in weights.iter().enumerate() {
+            let observed = counts[i] as f64 / steps as f64;
+            assert!((observed - w / 10.0).abs() < 1e-3, "bucket {} got {}", i, observed);
+        }
+    }
+
+    #[test]
+    fn test_alias_table_zero_and_degenerate_weights() {
+        let table = AliasTable::new(&[0.0, 5.0, 0.0]);
+        for i in 0..1000 {
+            assert_eq!(table.sample(i as f64 / 1000.0), 1);
+        }
+
+        // All-zero weights fall back to uniform instead of producing NaNs
+        let uniform = AliasTable::new(&[0.0, 0.0]);
+        assert_eq!(uniform.sample(0.25), 0);
+        assert_eq!(uniform.sample(0.75), 1);
+    }
+
+    #[test]
+    fn test_weighted_simulation_respects_zero_weight() {
+        let mut session = SimulationSession::new(vec![3, 1, 4, 1, 5]);
+        let options = vec!["Never".to_string(), "Always".to_string()];
+        let report = session.simulate_decision(&options, Some(&[0.0, 1.0]), 500, &SimulationOptions::default());
+        assert_eq!(report.distribution["Never"], 0);
+        assert_eq!(report.winner, "Always");
+    }
+
+    /// Alias sampling vs. the previous linear CDF scan.
+    ///
+    /// Run with `cargo test --release bench_alias_vs_linear_cdf -- --ignored --nocapture`.
+    #[test]
+    fn test_chi_square_p_values_match_tables() {
+        // Critical values at alpha = 0.05
+        assert!((stats::chi_square_sf(3.841, 1) - 0.05).abs() < 1e-3);
+        assert!((stats::chi_square_sf(11.070, 5) - 0.05).abs() < 1e-3);
+        assert!((stats::chi_square_sf(0.0, 3) - 1.0).abs() < 1e-12);
+        assert!(stats::chi_square_sf(100.0, 2) < 1e-20);
+    }
+
+    #[test]
+    fn test_report_includes_intervals_and_goodness_of_fit() {
+        let mut session = SimulationSession::new(vec![9, 8, 7, 6]);
+        let options = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let weights = [1.0, 2.0, 0.0];
+        let report = session.simulate_decision(&options, Some(&weights), 30_000, &SimulationOptions::default());
+
+        assert_eq!(report.option_stats.len(), 3);
+        for s in &report.option_stats {
+            assert!(s.ci_low <= s.observed_probability && s.observed_probability <= s.ci_high);
+            assert!(s.ci_low <= s.expected_probability + 0.02 && s.expected_probability - 0.02 <= s.ci_high);
+            assert!(s.effect_size.abs() < 0.05, "{:?}", s);
+        }
+        assert_eq!(report.option_stats[2].count, 0);
+
+        // Zero-weight option is excluded, leaving one degree of freedom
+        let chi = report.chi_square.expect("chi-square for two live options");
+        assert_eq!(chi.degrees_of_freedom, 1);
+        assert!(chi.p_value > 0.001, "{:?}", chi);
+
+        // A clearly skewed sample is rejected
+        let skewed = stats::chi_square_test(&[700, 300], &[0.5, 0.5]).unwrap();
+        assert!(skewed.p_value < 1e-10);
+        assert!((skewed.effect_size - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_simulation_options_control_anomalies_and_resolution() {
+        let options = vec!["A".to_string(), "B".to_string()];
+        let weights = [0.4, 0.6];
+        let run = |opts: &SimulationOptions| {
+            let mut session = SimulationSession::new(vec![5, 5, 5]);
+            session.simulate_decision(&options, Some(&weights), 5_000, opts)
+        };
+
+        let default = run(&SimulationOptions::default());
+        assert_eq!(default.time_series.len(), 20);
+
+        let coarse = run(&SimulationOptions { time_series_points: 5, ..Default::default() });
+        assert_eq!(coarse.time_series.len(), 5);
+        assert_eq!(coarse.distribution, default.distribution);
+
+        // Nothing is flagged when the threshold is out of reach
+        let strict = run(&SimulationOptions { z_threshold: 1e9, ..Default::default() });
+        assert!(strict.anomalies.is_empty());
+
+        // Everything is flagged at Z > 0 unless the effect-size floor filters it
+        let loose = run(&SimulationOptions { z_threshold: 0.0, ..Default::default() });
+        let max_h = loose.option_stats.iter().map(|s| s.effect_size.abs()).fold(0.0, f64::max);
+        let floored = run(&SimulationOptions { z_threshold: 0.0, min_effect_size: max_h + 0.01, ..Default::default() });
+        assert!(loose.anomalies.len() >= floored.anomalies.len());
+        assert!(floored.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_early_stop_halts_lopsided_runs() {
+        let options = vec!["Likely".to_string(), "Rare".to_string(), "Never".to_string()];
+        let weights = [0.9, 0.1, 0.0];
+        let opts = SimulationOptions { early_stop_confidence: Some(0.99), ..Default::default() };
+
+        let mut session = SimulationSession::new(vec![4, 2]);
+        let report = session.simulate_decision(&options, Some(&weights), 1_000_000, &opts);
+
+        assert!(report.stopped_early);
+        assert!(report.total_simulations < 1_000);
+        assert_eq!(report.winner, "Likely");
+        assert_eq!(report.distribution.values().sum::<usize>(), report.total_simulations);
+        // The final snapshot is always the stopping point
+        assert_eq!(report.time_series.last().unwrap().step_index, report.total_simulations);
+
+        // Off by default: the full run is used
+        let mut session = SimulationSession::new(vec![4, 2]);
+        let full = session.simulate_decision(&options, Some(&weights), 2_000, &SimulationOptions::default());
+        assert!(!full.stopped_early);
+        assert_eq!(full.total_simulations, 2_000);
+    }
+
+    #[test]
+    fn test_entropy_drift_scan_finds_planted_anomalies() {
+        // Pseudo-random background from a fixed xorshift so the test is deterministic
+        let mut x: u64 = 0x9E3779B97F4A7C15;
+        let mut pool: Vec<u8> = (0..1024).map(|_| {
+            x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+            (x >> 24) as u8
+        }).collect();
+        // Plant a saturated window, a repeat, and an alternating window
+        for b in &mut pool[128..192] { *b = 0xFF; }
+        for b in &mut pool[500..506] { *b = 0x42; }
+        for b in &mut pool[640..704] { *b = 0x55; }
+
+        let session = SimulationSession::new(pool);
+        let hotspots = session.scan_entropy_drift(&DriftScanOptions::default());
+
+        assert!(hotspots.iter().any(|h| h.kind == HotspotKind::Bias && h.start == 128 && h.score > 0.0));
+        assert!(hotspots.iter().any(|h| h.kind == HotspotKind::Repeat && h.start == 500 && h.end == 506));
+        assert!(hotspots.iter().any(|h| h.kind == HotspotKind::Runs && h.start == 640 && h.score > 0.0));
+        // Scanning never touches the cursor
+        assert_eq!(session.pool_index, 0);
+        assert!(hotspots.windows(2).all(|w| w[0].start <= w[1].start));
+    }
+
+    #[test]
+    fn test_simulate_walk_follows_transition_matrix() {
+        let states = vec!["Calm".to_string(), "Storm".to_string(), "End".to_string()];
+        // Calm mostly stays calm, Storm always returns to Calm, End is absorbing
+        let matrix = vec![
+            vec![0.8, 0.2, 0.0],
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0],
+        ];
+        let mut session = SimulationSession::new(vec![3, 1, 4]);
+        let report = session.simulate_walk(&states, &matrix, 0, 10_000).unwrap();
+
+        assert_eq!(report.path.len(), 10_001);
+        assert_eq!(report.occupancy.values().sum::<usize>(), 10_001);
+        assert_eq!(report.occupancy["End"], 0);
+        assert_eq!(report.first_visit["End"], None);
+        // Storm never repeats and never leads anywhere but Calm
+        assert_eq!(report.longest_stay["Storm"], 0);
+        assert_eq!(report.transition_counts[1][1] + report.transition_counts[1][2], 0);
+        // Stationary share of Storm is 0.2 / 1.2
+        let storm = report.occupancy["Storm"] as f64 / 10_001.0;
+        assert!((storm - 1.0 / 6.0).abs() < 0.02, "storm share {}", storm);
+
+        let mut session = SimulationSession::new(vec![3, 1, 4]);
+        let absorbed = session.simulate_walk(&states, &matrix, 2, 50).unwrap();
+        assert_eq!(absorbed.final_state, "End");
+        assert_eq!(absorbed.longest_stay["End"], 50);
+
+        assert!(session.simulate_walk(&states, &matrix[..2], 0, 1).is_err());
+        assert!(session.simulate_walk(&states, &matrix, 3, 1).is_err());
+    }
+
+    #[test]
+    fn test_draw_k_of_n_without_replacement() {
+        let options: Vec<String> = (1..=10).map(|i| i.to_string()).collect();
+        let mut session = SimulationSession::new(vec![8, 6, 7, 5, 3, 0, 9]);
+
+        let full = session.draw_k_of_n(&options, None, 10);
+        let mut sorted = full.clone();
+        sorted.sort_by_key(|s| s.parse::<u32>().unwrap());
+        assert_eq!(sorted, options);
+
+        let pick = session.draw_k_of_n(&options, None, 3);
+        assert_eq!(pick.len(), 3);
+        assert!(pick[0] != pick[1] && pick[1] != pick[2] && pick[0] != pick[2]);
+        assert_eq!(session.draw_k_of_n(&options, None, 50).len(), 10);
+    }
+
+    #[test]
+    fn test_simulate_ranking_statistics() {
+        let options = vec!["Heavy".to_string(), "Mid".to_string(), "Light".to_string(), "Zero".to_string()];
+        let weights = [6.0, 3.0, 1.0, 0.0];
+        let mut session = SimulationSession::new(vec![1, 1, 2, 3, 5, 8]);
+        let report = session.simulate_ranking(&options, Some(&weights), 2, 5_000);
+
+        assert_eq!(report.k, 2);
+        assert_eq!(report.top_k_frequency.values().sum::<usize>(), 2 * 5_000);
+        // Zero weight never makes a top-2 while three positive options remain
+        assert_eq!(report.top_k_frequency["Zero"], 0);
+        assert_eq!(report.consensus_ranking, vec!["Heavy", "Mid", "Light", "Zero"]);
+        assert_eq!(report.most_common_set, vec!["Heavy", "Mid"]);
+        // P(Heavy first) = 0.6
+        let heavy_first = report.position_counts["Heavy"][0] as f64 / 5_000.0;
+        assert!((heavy_first - 0.6).abs() < 0.03, "{}", heavy_first);
+        assert_eq!(report.last_draw.len(), 2);
+    }
+
+    #[test]
+    fn test_round_robin_tournament() {
+        let options = vec!["Strong".to_string(), "Even".to_string(), "Weak".to_string(), "Zero".to_string()];
+        let weights = [4.0, 1.0, 1.0, 0.0];
+        let mut session = SimulationSession::new(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+        let config = TournamentConfig { rounds: Some(500), ..Default::default() };
+        let report = session.simulate_tournament(&options, Some(&weights), &config);
+
+        assert_eq!(report.matches, 500 * 6);
+        assert_eq!(report.winner, "Strong");
+        assert_eq!(report.standings.last().unwrap().option, "Zero");
+        // Zero strength never wins a match; Strong beats Even 4 times in 5
+        assert_eq!(report.standings[3].wins, 0);
+        let strong_over_even = report.head_to_head[0][1] as f64 / 500.0;
+        assert!((strong_over_even - 0.8).abs() < 0.06, "{}", strong_over_even);
+        assert!(report.standings.iter().all(|s| s.wins + s.losses == 3 * 500 && s.titles == 0));
+        // Elo is zero-sum
+        let mean = report.standings.iter().map(|s| s.rating).sum::<f64>() / 4.0;
+        assert!((mean - INITIAL_RATING).abs() < 1e-6);
+        assert!((elo_expected(1600.0, 1400.0) - 0.7597).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_single_elimination_gives_byes() {
+        let options: Vec<String> = (1..=5).map(|i| format!("Option {}", i)).collect();
+        let mut session = SimulationSession::new(vec![2, 7, 1, 8]);
+        let config = TournamentConfig { format: Bracket::SingleElimination, rounds: Some(100), k_factor: Some(16.0) };
+        let report = session.simulate_tournament(&options, None, &config);
+
+        // Five entrants need four matches to leave one champion
+        assert_eq!(report.matches, 4 * 100);
+        assert_eq!(report.standings.iter().map(|s| s.titles).sum::<usize>(), 100);
+        assert_eq!(report.standings.iter().map(|s| s.wins).sum::<usize>(), 400);
+        assert!(report.standings.iter().all(|s| s.titles <= s.wins));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["format"], "single_elimination");
+    }
+
+    #[test]
+    fn test_integer_bool_and_shuffle_helpers() {
+        // A pool value below the rejection threshold must be skipped, not folded with modulo.
+        // For span 3, threshold = 2^64 mod 3 = 1, so a raw 0 is rejected and the next value used.
+        let mut pool = 0u64.to_le_bytes().to_vec();
+        pool.extend(7u64.to_le_bytes());
+        let mut session = SimulationSession::new(pool);
+        assert_eq!(session.next_u64_in_range(10, 13), 10 + 7 % 3);
+        assert_eq!(session.pool_index, 16);
+
+        let mut session = SimulationSession::new(vec![4, 4, 4]);
+        let mut counts = [0usize; 6];
+        for _ in 0..60_000 {
+            let v = session.next_u64_in_range(0, 6);
+            counts[v as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| (c as f64 - 10_000.0).abs() < 500.0), "{:?}", counts);
+
+        assert!(!session.next_bool(0.0));
+        assert!(session.next_bool(1.0));
+        let trues = (0..10_000).filter(|_| session.next_bool(0.3)).count();
+        assert!((trues as f64 / 10_000.0 - 0.3).abs() < 0.02);
+
+        let mut items: Vec<u32> = (0..20).collect();
+        session.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+        assert_ne!(items, sorted);
+    }
+
+    #[test]
+    fn test_report_records_consumed_entropy_range() {
+        let options = vec!["A".to_string(), "B".to_string()];
+        let mut session = SimulationSession::new((0..100).collect());
+
+        let first = session.simulate_decision(&options, None, 5, &SimulationOptions::default());
+        assert_eq!(first.entropy_ranges, vec![EntropyRange { start: 0, end: 40 }]);
+        assert_eq!(first.fallback_draws, 0);
+
+        // 60 bytes left: 7 pooled draws, then the fallback stream
+        let second = session.simulate_decision(&options, None, 10, &SimulationOptions::default());
+        assert_eq!(second.entropy_ranges, vec![EntropyRange { start: 40, end: 96 }]);
+        assert_eq!(second.fallback_draws, 3);
+
+        let third = session.simulate_decision(&options, None, 4, &SimulationOptions::default());
+        assert!(third.entropy_ranges.is_empty());
+        assert_eq!(third.fallback_draws, 4);
+        assert_eq!(session.fallback_draws, 7);
+    }
+
+    #[test]
+    fn test_alias_table_agrees_with_linear_cdf() {
+        // Both samplers over the same uniform grid must land on each option equally often
+        let weights: Vec<f64> = (1..=50).map(|i| i as f64).collect();
+        let sum: f64 = weights.iter().sum();
+        let mut acc = 0.0;
+        let cdf: Vec<f64> = weights.iter().map(|w| { acc += w / sum; acc }).collect();
+        let table = AliasTable::new(&weights);
+
+        let steps = 200_000;
+        let (mut linear, mut alias) = (vec![0usize; 50], vec![0usize; 50]);
+        for i in 0..steps {
+            let r = (i as f64 + 0.5) / steps as f64;
+            linear[cdf.iter().position(|&t| r <= t).unwrap_or(49)] += 1;
+            alias[table.sample(r)] += 1;
+        }
+        for i in 0..50 {
+            let gap = (linear[i] as f64 - alias[i] as f64).abs() / steps as f64;
+            assert!(gap < 1e-4, "option {}: linear {} vs alias {}", i, linear[i], alias[i]);
+        }
+
+        // And the engine's weighted draws follow the same distribution
+        let options: Vec<String> = (0..50).map(|i| format!("opt{}", i)).collect();
+        let mut session = SimulationSession::new(vec![1, 2, 3]);
+        let draws = 200_000;
+        let report = session.simulate_decision(&options, Some(&weights), draws, &SimulationOptions::default());
+        for (i, w) in weights.iter().enumerate() {
+            let observed = report.distribution[&options[i]] as f64 / draws as f64;
+            assert!((observed - w / sum).abs() < 0.002, "option {} got {}", i, observed);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_alias_vs_linear_cdf() {
+        let num_options = 500;
+        let draws = 2_000_000;
+        let weights: Vec<f64> = (1..=num_options).map(|i| i as f64).collect();
+        let options: Vec<String> = (0..num_options).map(|i| format!("opt{}", i)).collect();
+
+        let sum: f64 = weights.iter().sum();
+        let mut acc = 0.0;
+        let cdf: Vec<f64> = weights.iter().map(|w| { acc += w / sum; acc }).collect();
+        let mut session = SimulationSession::new(vec![1, 2, 3]);
+        let started = Instant::now();
+        let mut linear_counts = vec![0usize; num_options];
+        for _ in 0..draws {
+            let r = session.next_f64();
+            let idx = cdf.iter().position(|&t| r <= t).unwrap_or(num_options - 1);
+            linear_counts[idx] += 1;
+        }
+        let linear = started.elapsed();
+
+        let mut session = SimulationSession::new(vec![1, 2, 3]);
+        let started = Instant::now();
+        let report = session.simulate_decision(&options, Some(&weights), draws, &SimulationOptions::default());
+        let alias = started.elapsed();
+
+        println!("{} options x {} draws: linear CDF {:?}, alias engine {:?}", num_options, draws, linear, alias);
+        assert_eq!(report.total_simulations, draws);
+        assert_eq!(linear_counts.iter().sum::<usize>(), draws);
+    }
 }
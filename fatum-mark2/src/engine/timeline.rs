@@ -1,8 +1,10 @@
 use crate::engine::SimulationSession;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use crate::tools::chinese_meta::{get_stem_element, HEAVENLY_STEMS};
+use crate::tools::feng_shui::BaZiProfile;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
-use rand::SeedableRng;
-use rand_chacha::ChaCha20Rng;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimelineState {
@@ -10,6 +12,9 @@ pub struct TimelineState {
     pub score: f64,        // 0.0 to 100.0
     pub dominant_element: String,
     pub elemental_values: HashMap<String, f64>,
+    /// Element of the luck pillar governing this step, when run from a BaZi profile.
+    #[serde(default)]
+    pub luck_element: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,21 +30,186 @@ pub struct AggregateStep {
     pub avg_score: f64,
     pub variance: f64,
     pub element_distribution: HashMap<String, usize>, // Count of dominant elements
+    // Score percentiles across worlds, for fan charts
+    #[serde(default)]
+    pub p5: f64,
+    #[serde(default)]
+    pub p25: f64,
+    #[serde(default)]
+    pub p50: f64,
+    #[serde(default)]
+    pub p75: f64,
+    #[serde(default)]
+    pub p95: f64,
 }
 
+/// Maximum number of sample paths returned in `ManyWorldsResult::paths`.
+pub const MAX_SAMPLE_PATHS: usize = 50;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ManyWorldsResult {
-    pub paths: Vec<TimelinePath>, // Subset of paths
+    pub paths: Vec<TimelinePath>, // Sample spread evenly across final-score ranks
     pub aggregate_stats: Vec<AggregateStep>, // Average/Min/Max per year
+    #[serde(default)]
+    pub total_worlds: usize,
+    /// Hex-encoded base seed; pass it back via `with_seed` to reproduce the run.
+    #[serde(default)]
+    pub seed: String,
+    /// Worlds with the highest, lowest and median final score.
+    #[serde(default)]
+    pub best_path: Option<TimelinePath>,
+    #[serde(default)]
+    pub worst_path: Option<TimelinePath>,
+    #[serde(default)]
+    pub median_path: Option<TimelinePath>,
+}
+
+/// The five elements in generating-cycle order: each one feeds the next
+/// (Wood -> Fire -> Earth -> Metal -> Water -> Wood) and controls the one after that
+/// (Wood -> Earth, Fire -> Metal, Earth -> Water, Metal -> Wood, Water -> Fire).
+pub const WU_XING: [&str; 5] = ["Wood", "Fire", "Earth", "Metal", "Water"];
+
+/// Interaction strengths for the five-element model, applied at every step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WuXingParams {
+    /// Fraction of an element's value added to the element it generates (Wood feeds Fire).
+    pub generation_strength: f64,
+    /// Fraction of an element's value removed from the element it controls (Water controls Fire).
+    pub control_strength: f64,
+    /// Share of the generated amount the generating element loses ("the child drains the mother").
+    pub drain_ratio: f64,
+    /// Largest quantum perturbation applied to each element per step (+/-).
+    pub flux_amplitude: f64,
+    /// Energy the active luck pillar adds to its element every step.
+    pub luck_strength: f64,
+}
+
+impl Default for WuXingParams {
+    fn default() -> Self {
+        Self {
+            generation_strength: 0.10,
+            control_strength: 0.08,
+            drain_ratio: 0.5,
+            flux_amplitude: 3.0,
+            luck_strength: 2.0,
+        }
+    }
+}
+
+/// The natal chart's influence on a timeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NatalInfluence {
+    pub day_master_element: String,
+    pub favorable_elements: Vec<String>,
+    /// Stem element of each 10-year luck pillar, starting at `luck_start_age`.
+    pub luck_pillars: Vec<String>,
+    /// Age at simulation step 0.
+    pub start_age: usize,
+    /// Age the first luck pillar begins; earlier ages also use it.
+    #[serde(default)]
+    pub luck_start_age: usize,
 }
 
+impl NatalInfluence {
+    /// Derives the natal influence from a BaZi chart.
+    ///
+    /// Uses the chart's luck pillars when it has them. Without a gender they cannot be
+    /// placed, so they are approximated by stepping forward from the month pillar from
+    /// birth, one stem per decade.
+    pub fn from_bazi(profile: &BaZiProfile, start_age: usize) -> Self {
+        let stem_idx = |pillar: &str| {
+            let stem = pillar.split_whitespace().next().unwrap_or("");
+            HEAVENLY_STEMS.iter().position(|s| *s == stem)
+        };
+
+        let day_master_element = HEAVENLY_STEMS.iter().position(|s| *s == profile.day_master)
+            .map(|i| get_stem_element(i).to_string())
+            .unwrap_or_else(|| "Earth".to_string());
+        let favorable_elements = profile.favorable_elements.iter()
+            .filter(|e| WU_XING.contains(&e.as_str()))
+            .cloned()
+            .collect();
+        let (luck_pillars, luck_start_age) = match (profile.luck_pillars.as_slice(), stem_idx(&profile.month_pillar)) {
+            ([], Some(month_stem)) => ((1..=12).map(|i| get_stem_element(month_stem + i).to_string()).collect(), 0),
+            ([], None) => (vec![], 0),
+            (pillars, _) => (
+                pillars.iter().map(|p| p.element.clone()).collect(),
+                profile.luck_start_age.unwrap_or(0.0).round() as usize,
+            ),
+        };
+
+        Self { day_master_element, favorable_elements, luck_pillars, start_age, luck_start_age }
+    }
+
+    /// Starting balance: an even base, with the day master and favorable elements raised.
+    pub fn start_elements(&self) -> HashMap<String, f64> {
+        let mut elements: HashMap<String, f64> = WU_XING.iter().map(|e| (e.to_string(), 20.0)).collect();
+        if let Some(v) = elements.get_mut(&self.day_master_element) {
+            *v += 30.0;
+        }
+        for fav in &self.favorable_elements {
+            if let Some(v) = elements.get_mut(fav) {
+                *v += 10.0;
+            }
+        }
+        elements
+    }
+
+    /// Element of the luck pillar active at simulation step `step`.
+    pub fn luck_element(&self, step: usize) -> Option<&str> {
+        let age = (self.start_age + step).saturating_sub(self.luck_start_age);
+        let idx = (age / 10).min(self.luck_pillars.len().checked_sub(1)?);
+        self.luck_pillars.get(idx).map(|s| s.as_str())
+    }
+}
+
+/// Runs many-worlds timelines.
+///
+/// Each world draws from its own ChaCha20 stream: the base seed is taken from the
+/// session once per `simulate` call, and world `i` uses stream `i` of that key. A
+/// world's result therefore depends only on the seed and its index, so adding worlds
+/// or spreading them across threads never changes earlier worlds.
 pub struct TimelineSimulator<'a> {
     session: &'a mut SimulationSession,
+    params: WuXingParams,
+    natal: Option<NatalInfluence>,
+    seed: Option<[u8; 32]>,
+    threads: usize,
 }
 
 impl<'a> TimelineSimulator<'a> {
     pub fn new(session: &'a mut SimulationSession) -> Self {
-        Self { session }
+        Self { session, params: WuXingParams::default(), natal: None, seed: None, threads: 1 }
+    }
+
+    /// Uses a fixed base seed instead of drawing one from the session.
+    pub fn with_seed(mut self, seed: [u8; 32]) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Spreads worlds over up to `threads` OS threads. Results are identical for any count.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Creates a simulator driven by a BaZi chart: each step receives the element of
+    /// the luck pillar active at that age. Use `natal_start_elements` as the start map.
+    pub fn from_bazi(session: &'a mut SimulationSession, profile: &BaZiProfile, current_age: usize) -> Self {
+        Self { natal: Some(NatalInfluence::from_bazi(profile, current_age)), ..Self::new(session) }
+    }
+
+    /// Starting elements implied by the natal chart, if the simulator has one.
+    pub fn natal_start_elements(&self) -> Option<HashMap<String, f64>> {
+        self.natal.as_ref().map(|n| n.start_elements())
+    }
+
+    /// Overrides the default interaction strengths.
+    pub fn with_params(mut self, params: WuXingParams) -> Self {
+        self.params = params;
+        self
     }
 
     /// Simulates branching timelines.
@@ -47,74 +217,33 @@ impl<'a> TimelineSimulator<'a> {
     /// * `start_elements`: Initial elemental balance (Wood, Fire, Earth, Metal, Water).
     /// * `duration`: Number of steps (e.g., years) to simulate.
     /// * `num_worlds`: Number of timelines to generate.
+    #[tracing::instrument(name = "simulate_timeline", skip_all, fields(duration, num_worlds))]
     pub fn simulate(
         &mut self,
         start_elements: HashMap<String, f64>,
         duration: usize,
         num_worlds: usize,
     ) -> ManyWorldsResult {
-        let mut all_paths = Vec::with_capacity(num_worlds);
-        let mut rng = ChaCha20Rng::from_seed(self.session.seed);
-
-        for i in 0..num_worlds {
-            let mut current_elements = start_elements.clone();
-            let mut steps = Vec::with_capacity(duration);
-
-            // Initial score calculation
-            let mut current_score = self.calculate_score(&current_elements);
-
-            for step in 0..duration {
-                // Evolve elements based on Entropy
-                let entropy_flux = self.session.next_f64(&mut rng);
-
-                // Determine which element gets boosted/drained
-                // 0.0-0.2: Wood, 0.2-0.4: Fire, etc.
-                let element_idx = (entropy_flux * 5.0) as usize;
-                let boosted_element = match element_idx {
-                    0 => "Wood",
-                    1 => "Fire",
-                    2 => "Earth",
-                    3 => "Metal",
-                    _ => "Water",
-                };
-
-                // Apply flux
-                // A second random number determines magnitude
-                let magnitude = self.session.next_f64(&mut rng) * 10.0 - 2.0; // -2 to +8 range
-
-                if let Some(val) = current_elements.get_mut(boosted_element) {
-                    *val = (*val + magnitude).max(0.0);
-                }
+        let base_seed = self.seed.unwrap_or_else(|| self.session.next_seed());
+        let params = &self.params;
+        let natal = self.natal.as_ref();
+        let start = &start_elements;
 
-                // Normalization (optional, to keep values sane)
-                // But let's just let them drift for now to see "extreme" timelines.
-
-                // Calculate Dominant Element
-                let mut max_val = -1.0;
-                let mut dom = "Unknown".to_string();
-                for (k, v) in &current_elements {
-                    if *v > max_val {
-                        max_val = *v;
-                        dom = k.clone();
-                    }
-                }
-
-                current_score = self.calculate_score(&current_elements);
-
-                steps.push(TimelineState {
-                    step_index: step,
-                    score: current_score,
-                    dominant_element: dom,
-                    elemental_values: current_elements.clone(),
-                });
-            }
-
-            all_paths.push(TimelinePath {
-                id: i,
-                final_score: current_score,
-                steps,
-            });
-        }
+        let all_paths: Vec<TimelinePath> = if self.threads > 1 && num_worlds > 1 {
+            let chunk = num_worlds.div_ceil(self.threads);
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..num_worlds).step_by(chunk).map(|first| {
+                    scope.spawn(move || {
+                        (first..(first + chunk).min(num_worlds))
+                            .map(|i| run_world(i, base_seed, start, duration, params, natal))
+                            .collect::<Vec<_>>()
+                    })
+                }).collect();
+                handles.into_iter().flat_map(|h| h.join().expect("timeline worker panicked")).collect()
+            })
+        } else {
+            (0..num_worlds).map(|i| run_world(i, base_seed, start, duration, params, natal)).collect()
+        };
 
         // Calculate Aggregates
         let mut aggregates = Vec::new();
@@ -122,42 +251,163 @@ impl<'a> TimelineSimulator<'a> {
             let mut total_score = 0.0;
             let mut score_sq_sum = 0.0;
             let mut elem_dist = HashMap::new();
+            let mut scores = Vec::with_capacity(num_worlds);
 
             for path in &all_paths {
                 if let Some(s) = path.steps.get(step) {
                     total_score += s.score;
                     score_sq_sum += s.score * s.score;
+                    scores.push(s.score);
                     *elem_dist.entry(s.dominant_element.clone()).or_insert(0) += 1;
                 }
             }
 
             let avg = total_score / num_worlds as f64;
             let variance = (score_sq_sum / num_worlds as f64) - (avg * avg);
+            scores.sort_by(|a, b| a.total_cmp(b));
 
             aggregates.push(AggregateStep {
                 step_index: step,
                 avg_score: avg,
                 variance,
                 element_distribution: elem_dist,
+                p5: percentile(&scores, 0.05),
+                p25: percentile(&scores, 0.25),
+                p50: percentile(&scores, 0.50),
+                p75: percentile(&scores, 0.75),
+                p95: percentile(&scores, 0.95),
             });
         }
 
-        // Return top 50 paths to avoid massive JSON payload
-        let paths_to_return = all_paths.into_iter().take(50).collect();
+        // Rank worlds by final score to pick representatives
+        let mut ranked: Vec<usize> = (0..all_paths.len()).collect();
+        ranked.sort_by(|&a, &b| all_paths[a].final_score.total_cmp(&all_paths[b].final_score));
+        let pick = |rank: usize| ranked.get(rank).map(|&i| all_paths[i].clone());
+        let worst_path = pick(0);
+        let best_path = ranked.len().checked_sub(1).and_then(pick);
+        let median_path = pick(ranked.len() / 2);
+
+        // Return an evenly spread sample (worst to best) to avoid massive JSON payload
+        let sample = ranked.len().min(MAX_SAMPLE_PATHS);
+        let paths_to_return = (0..sample)
+            .map(|k| if sample > 1 { k * (ranked.len() - 1) / (sample - 1) } else { 0 })
+            .filter_map(pick)
+            .collect();
 
         ManyWorldsResult {
             paths: paths_to_return,
             aggregate_stats: aggregates,
+            total_worlds: num_worlds,
+            seed: hex::encode(base_seed),
+            best_path,
+            worst_path,
+            median_path,
+        }
+    }
+}
+
+/// Simulates one world on stream `id` of `base_seed`.
+fn run_world(
+    id: usize,
+    base_seed: [u8; 32],
+    start_elements: &HashMap<String, f64>,
+    duration: usize,
+    params: &WuXingParams,
+    natal: Option<&NatalInfluence>,
+) -> TimelinePath {
+    let mut rng = ChaCha20Rng::from_seed(base_seed);
+    rng.set_stream(id as u64);
+
+    let mut current_elements = start_elements.clone();
+    let mut steps = Vec::with_capacity(duration);
+
+    // Initial score calculation
+    let mut current_score = calculate_score(&current_elements);
+
+    for step in 0..duration {
+        // Evolve elements through the generating and controlling cycles
+        step_wu_xing(params, &mut rng, &mut current_elements);
+
+        // The active luck pillar feeds its element
+        let luck_element = natal.and_then(|n| n.luck_element(step)).map(|e| e.to_string());
+        if let Some(e) = &luck_element {
+            if let Some(v) = current_elements.get_mut(e) {
+                *v += params.luck_strength;
+            }
+        }
+
+        // Calculate Dominant Element
+        let mut max_val = -1.0;
+        let mut dom = "Unknown".to_string();
+        for name in WU_XING {
+            let v = current_elements.get(name).copied().unwrap_or(0.0);
+            if v > max_val {
+                max_val = v;
+                dom = name.to_string();
+            }
         }
+
+        current_score = calculate_score(&current_elements);
+
+        steps.push(TimelineState {
+            step_index: step,
+            score: current_score,
+            dominant_element: dom,
+            elemental_values: current_elements.clone(),
+            luck_element,
+        });
+    }
+
+    TimelinePath {
+        id,
+        final_score: current_score,
+        steps,
+    }
+}
+
+/// Advances the five elements by one step.
+///
+/// All interactions are computed from the same snapshot, so the order of
+/// elements does not matter. Entropy scales this step's generation and control
+/// strengths (0.5x to 1.5x) and adds a small perturbation to each element.
+fn step_wu_xing(params: &WuXingParams, rng: &mut ChaCha20Rng, elements: &mut HashMap<String, f64>) {
+    let values: Vec<f64> = WU_XING.iter().map(|e| elements.get(*e).copied().unwrap_or(0.0)).collect();
+    let gen = params.generation_strength * (0.5 + rng.gen::<f64>());
+    let ctrl = params.control_strength * (0.5 + rng.gen::<f64>());
+
+    let mut delta = [0.0; 5];
+    for i in 0..5 {
+        // Generating cycle: i feeds i+1 and is drained by it
+        let fed = gen * values[i];
+        delta[(i + 1) % 5] += fed;
+        delta[i] -= fed * params.drain_ratio;
+        // Controlling cycle: i restrains i+2
+        delta[(i + 2) % 5] -= ctrl * values[i];
+    }
+
+    for (i, name) in WU_XING.iter().enumerate() {
+        let flux = (rng.gen::<f64>() * 2.0 - 1.0) * params.flux_amplitude;
+        elements.insert(name.to_string(), (values[i] + delta[i] + flux).max(0.0));
     }
+}
+
+fn calculate_score(elements: &HashMap<String, f64>) -> f64 {
+    // Simple scoring: Balance is better? Or just sum?
+    // Let's assume a "Flow" score where standard deviation is low (balanced) is higher score?
+    // Or maybe just the sum of energy.
+    // Let's go with Sum of Energy for now.
+    elements.values().sum()
+}
 
-    fn calculate_score(&self, elements: &HashMap<String, f64>) -> f64 {
-        // Simple scoring: Balance is better? Or just sum?
-        // Let's assume a "Flow" score where standard deviation is low (balanced) is higher score?
-        // Or maybe just the sum of energy.
-        // Let's go with Sum of Energy for now.
-        elements.values().sum()
+/// Linear-interpolated percentile `q` (0.0 - 1.0) of already sorted values.
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
     }
+    let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64)
 }
 
 #[cfg(test)]
@@ -182,4 +432,122 @@ mod tests {
         assert_eq!(result.paths[0].steps.len(), 10);
         assert_eq!(result.aggregate_stats.len(), 10);
     }
+
+    fn only(pairs: &[(&str, f64)]) -> HashMap<String, f64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_wu_xing_generating_cycle() {
+        let mut session = SimulationSession::new(vec![7; 64]);
+        let params = WuXingParams { control_strength: 0.0, flux_amplitude: 0.0, ..Default::default() };
+        let mut simulator = TimelineSimulator::new(&mut session).with_params(params);
+
+        // Wood alone feeds Fire and is drained by it; nothing else moves
+        let result = simulator.simulate(only(&[("Wood", 50.0)]), 1, 1);
+        let values = &result.paths[0].steps[0].elemental_values;
+        assert!(values["Fire"] > 0.0);
+        assert!(values["Wood"] < 50.0);
+        assert_eq!(values["Earth"], 0.0);
+        assert_eq!(values["Water"], 0.0);
+    }
+
+    #[test]
+    fn test_wu_xing_controlling_cycle() {
+        let mut session = SimulationSession::new(vec![7; 64]);
+        let params = WuXingParams { generation_strength: 0.0, flux_amplitude: 0.0, ..Default::default() };
+        let mut simulator = TimelineSimulator::new(&mut session).with_params(params);
+
+        // Water controls Fire; Fire in turn restrains Metal, which is empty
+        let result = simulator.simulate(only(&[("Water", 50.0), ("Fire", 30.0)]), 3, 1);
+        let steps = &result.paths[0].steps;
+        assert!(steps[0].elemental_values["Fire"] < 30.0);
+        assert!(steps[2].elemental_values["Fire"] < steps[0].elemental_values["Fire"]);
+        assert_eq!(steps[2].elemental_values["Water"], 50.0);
+        assert_eq!(steps[2].dominant_element, "Water");
+    }
+
+    #[test]
+    fn test_percentile_bands_and_representative_paths() {
+        let mut session = SimulationSession::new((0..=255).collect());
+        let mut simulator = TimelineSimulator::new(&mut session);
+        let start = only(&[("Wood", 20.0), ("Fire", 20.0), ("Earth", 20.0), ("Metal", 20.0), ("Water", 20.0)]);
+
+        let result = simulator.simulate(start, 6, 120);
+        assert_eq!(result.total_worlds, 120);
+        assert_eq!(result.paths.len(), MAX_SAMPLE_PATHS);
+
+        for agg in &result.aggregate_stats {
+            assert!(agg.p5 <= agg.p25 && agg.p25 <= agg.p50 && agg.p50 <= agg.p75 && agg.p75 <= agg.p95);
+        }
+
+        let best = result.best_path.unwrap();
+        let worst = result.worst_path.unwrap();
+        let median = result.median_path.unwrap();
+        assert!(worst.final_score <= median.final_score && median.final_score <= best.final_score);
+        // The sample runs from worst to best
+        assert_eq!(result.paths.first().unwrap().id, worst.id);
+        assert_eq!(result.paths.last().unwrap().id, best.id);
+
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0, 5.0], 0.5), 3.0);
+        assert_eq!(percentile(&[0.0, 10.0], 0.25), 2.5);
+    }
+
+    #[test]
+    fn test_worlds_are_independent_of_count_and_threads() {
+        let start = only(&[("Wood", 20.0), ("Fire", 20.0), ("Earth", 20.0), ("Metal", 20.0), ("Water", 20.0)]);
+        let seed = [42u8; 32];
+        let run = |worlds: usize, threads: usize| {
+            let mut session = SimulationSession::new(vec![]);
+            let mut sim = TimelineSimulator::new(&mut session).with_seed(seed).with_threads(threads);
+            let mut result = sim.simulate(start.clone(), 8, worlds);
+            let mut all = vec![result.worst_path.take().unwrap()];
+            all.extend(result.paths);
+            all
+        };
+        let final_of = |paths: &[TimelinePath], id: usize| paths.iter().find(|p| p.id == id).map(|p| p.final_score);
+
+        let small = run(3, 1);
+        let large = run(40, 1);
+        let parallel = run(40, 4);
+        for id in 0..3 {
+            if let (Some(a), Some(b)) = (final_of(&small, id), final_of(&large, id)) {
+                assert_eq!(a, b, "world {} changed when adding worlds", id);
+            }
+        }
+        for (a, b) in large.iter().zip(&parallel) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.final_score, b.final_score);
+        }
+
+        // Without an explicit seed, each run draws a fresh one from the session
+        let mut session = SimulationSession::new((0..=255).collect());
+        let mut sim = TimelineSimulator::new(&mut session);
+        let first = sim.simulate(start.clone(), 2, 2);
+        let second = sim.simulate(start.clone(), 2, 2);
+        assert_ne!(first.seed, second.seed);
+    }
+
+    #[test]
+    fn test_timeline_from_bazi_profile() {
+        let profile = BaZiProfile {
+            year_pillar: "Geng Wu (Horse)".to_string(),
+            month_pillar: "Bing Xu (Dog)".to_string(),
+            day_pillar: "Jia Zi (Rat)".to_string(),
+            hour_pillar: "Jia Zi (Rat)".to_string(),
+            day_master: "Jia".to_string(),
+            favorable_elements: vec!["Water".to_string(), "Solar Term Adjusted".to_string()],
+            ..Default::default()
+        };
+        // Age 28: Bing month -> luck pillars Ding, Wu, Ji (Earth for ages 20-29), then Geng (Metal)
+        let mut session = SimulationSession::new(vec![9; 64]);
+        let mut simulator = TimelineSimulator::from_bazi(&mut session, &profile, 28);
+        let start = simulator.natal_start_elements().unwrap();
+        assert_eq!(start["Wood"], 50.0);
+        assert_eq!(start["Water"], 30.0);
+
+        let result = simulator.simulate(start, 4, 1);
+        let lucks: Vec<_> = result.paths[0].steps.iter().map(|s| s.luck_element.clone().unwrap()).collect();
+        assert_eq!(lucks, vec!["Earth", "Earth", "Metal", "Metal"]);
+    }
 }
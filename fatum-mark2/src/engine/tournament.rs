@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use super::{EntropyRange, SimulationSession};
+
+/// Elo rating every option starts from.
+pub const INITIAL_RATING: f64 = 1500.0;
+/// Elo K-factor when none is given: the most a rating moves in one match.
+pub const DEFAULT_K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bracket {
+    /// Every option meets every other, in quantum-shuffled order.
+    #[default]
+    RoundRobin,
+    /// Randomly seeded knockout brackets; odd fields give byes.
+    SingleElimination,
+}
+
+/// How a head-to-head tournament is played.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TournamentConfig {
+    #[serde(default)]
+    pub format: Bracket,
+    /// Round-robin passes, or knockout tournaments, to play (1 by default).
+    #[serde(default)]
+    pub rounds: Option<usize>,
+    #[serde(default)]
+    pub k_factor: Option<f64>,
+}
+
+/// One option's record over the tournament.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Standing {
+    pub option: String,
+    pub rating: f64,
+    pub wins: usize,
+    pub losses: usize,
+    /// Knockout tournaments won (always 0 for round robin).
+    pub titles: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentReport {
+    pub format: Bracket,
+    pub rounds: usize,
+    pub matches: usize,
+    /// The option with the highest final rating.
+    pub winner: String,
+    /// Options by final rating, highest first.
+    pub standings: Vec<Standing>,
+    /// `head_to_head[i][j]` is how often option i beat option j, in option order.
+    pub head_to_head: Vec<Vec<usize>>,
+    pub entropy_ranges: Vec<EntropyRange>,
+    pub fallback_draws: usize,
+}
+
+/// Chance that a player rated `a` beats one rated `b`.
+pub fn elo_expected(a: f64, b: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((b - a) / 400.0))
+}
+
+struct Table {
+    ratings: Vec<f64>,
+    wins: Vec<Vec<usize>>,
+    strength: Vec<f64>,
+    k: f64,
+    matches: usize,
+}
+
+impl Table {
+    /// Plays `a` against `b` on one quantum draw and returns the winner.
+    ///
+    /// `a` wins with probability strength(a) / (strength(a) + strength(b)), even odds
+    /// if both are zero (Bradley-Terry).
+    fn play(&mut self, session: &mut SimulationSession, a: usize, b: usize) -> usize {
+        let total = self.strength[a] + self.strength[b];
+        let p = if total > 0.0 { self.strength[a] / total } else { 0.5 };
+        let (winner, loser) = if session.next_bool(p) { (a, b) } else { (b, a) };
+        let expected = elo_expected(self.ratings[winner], self.ratings[loser]);
+        self.ratings[winner] += self.k * (1.0 - expected);
+        self.ratings[loser] -= self.k * (1.0 - expected);
+        self.wins[winner][loser] += 1;
+        self.matches += 1;
+        winner
+    }
+}
+
+impl SimulationSession {
+    /// Ranks options by playing them off in pairs with Elo scoring, instead of one categorical draw.
+    ///
+    /// * `weights`: Optional strengths; each match is won in proportion to them. Equal when None.
+    pub fn simulate_tournament(&mut self, options: &[String], weights: Option<&[f64]>, config: &TournamentConfig) -> TournamentReport {
+        let n = options.len();
+        let rounds = config.rounds.unwrap_or(1);
+        let strength = (0..n).map(|i| match weights {
+            Some(w) => w.get(i).copied().filter(|v| v.is_finite() && *v > 0.0).unwrap_or(0.0),
+            None => 1.0,
+        }).collect();
+        let mut table = Table {
+            ratings: vec![INITIAL_RATING; n],
+            wins: vec![vec![0; n]; n],
+            strength,
+            k: config.k_factor.unwrap_or(DEFAULT_K_FACTOR),
+            matches: 0,
+        };
+        let mut titles = vec![0usize; n];
+
+        let pool_start = self.pool_index;
+        let fallback_start = self.fallback_draws;
+        for _ in 0..rounds {
+            match config.format {
+                Bracket::RoundRobin => {
+                    // Elo is order dependent, so each pass plays its fixtures in a fresh order
+                    let mut fixtures: Vec<(usize, usize)> = (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b))).collect();
+                    self.shuffle(&mut fixtures);
+                    for (a, b) in fixtures {
+                        table.play(self, a, b);
+                    }
+                }
+                Bracket::SingleElimination => {
+                    let mut field: Vec<usize> = (0..n).collect();
+                    self.shuffle(&mut field);
+                    while field.len() > 1 {
+                        // An odd one out at the end of the draw gets a bye
+                        field = field.chunks(2).map(|pair| match *pair {
+                            [a, b] => table.play(self, a, b),
+                            [a] => a,
+                            _ => unreachable!(),
+                        }).collect();
+                    }
+                    if let Some(&champion) = field.first() {
+                        titles[champion] += 1;
+                    }
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| table.ratings[b].total_cmp(&table.ratings[a]).then(a.cmp(&b)));
+        let standings: Vec<Standing> = order.iter().map(|&i| Standing {
+            option: options[i].clone(),
+            rating: table.ratings[i],
+            wins: table.wins[i].iter().sum(),
+            losses: table.wins.iter().map(|row| row[i]).sum(),
+            titles: titles[i],
+        }).collect();
+
+        let pool_end = self.pool_index;
+        TournamentReport {
+            format: config.format,
+            rounds,
+            matches: table.matches,
+            winner: standings.first().map_or_else(|| "None".to_string(), |s| s.option.clone()),
+            standings,
+            head_to_head: table.wins,
+            entropy_ranges: if pool_end > pool_start { vec![EntropyRange { start: pool_start, end: pool_end }] } else { vec![] },
+            fallback_draws: self.fallback_draws - fallback_start,
+        }
+    }
+}
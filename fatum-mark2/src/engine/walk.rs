@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use super::alias::AliasTable;
+use super::SimulationSession;
+
+/// The result of a Markov chain walk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkReport {
+    pub steps: usize,
+    pub start_state: String,
+    pub final_state: String,
+    /// Visited state indices (into the `states` slice), starting with the start state.
+    /// Has `steps + 1` entries.
+    pub path: Vec<usize>,
+    /// How many times each state was occupied along the path (start included).
+    pub occupancy: HashMap<String, usize>,
+    /// `transition_counts[i][j]` is how often the walk moved from state i to state j.
+    pub transition_counts: Vec<Vec<usize>>,
+    /// Step at which each state was first entered (None if never reached).
+    pub first_visit: HashMap<String, Option<usize>>,
+    /// Longest consecutive stay in each state, in steps.
+    pub longest_stay: HashMap<String, usize>,
+}
+
+impl SimulationSession {
+    /// Drives a Markov chain over `states` for `steps` transitions using the session's entropy.
+    ///
+    /// * `transitions`: Row-stochastic matrix; row i holds the weights of moving from
+    ///   state i to each state (rows need not sum to 1). A row with no positive weight
+    ///   makes that state absorbing.
+    /// * `start`: Index of the initial state.
+    pub fn simulate_walk(
+        &mut self,
+        states: &[String],
+        transitions: &[Vec<f64>],
+        start: usize,
+        steps: usize,
+    ) -> Result<WalkReport> {
+        let n = states.len();
+        if n == 0 {
+            bail!("simulate_walk needs at least one state");
+        }
+        if transitions.len() != n || transitions.iter().any(|row| row.len() != n) {
+            bail!("Transition matrix must be {}x{}", n, n);
+        }
+        if start >= n {
+            bail!("Start state {} is out of range (0..{})", start, n);
+        }
+
+        // One alias table per row; None marks an absorbing state
+        let rows: Vec<Option<AliasTable>> = transitions.iter().map(|row| {
+            if row.iter().any(|w| w.is_finite() && *w > 0.0) { Some(AliasTable::new(row)) } else { None }
+        }).collect();
+
+        let mut path = Vec::with_capacity(steps + 1);
+        let mut occupancy = vec![0usize; n];
+        let mut transition_counts = vec![vec![0usize; n]; n];
+        let mut first_visit: Vec<Option<usize>> = vec![None; n];
+        let mut longest_stay = vec![0usize; n];
+
+        let mut current = start;
+        let mut stay = 0;
+        path.push(current);
+        occupancy[current] += 1;
+        first_visit[current] = Some(0);
+
+        for step in 1..=steps {
+            let next = match &rows[current] {
+                Some(table) => table.sample(self.next_f64()),
+                None => current,
+            };
+            transition_counts[current][next] += 1;
+
+            if next == current {
+                stay += 1;
+            } else {
+                longest_stay[current] = longest_stay[current].max(stay);
+                stay = 0;
+            }
+            if first_visit[next].is_none() {
+                first_visit[next] = Some(step);
+            }
+            occupancy[next] += 1;
+            path.push(next);
+            current = next;
+        }
+        longest_stay[current] = longest_stay[current].max(stay);
+
+        Ok(WalkReport {
+            steps,
+            start_state: states[start].clone(),
+            final_state: states[current].clone(),
+            path,
+            occupancy: states.iter().cloned().zip(occupancy).collect(),
+            transition_counts,
+            first_visit: states.iter().cloned().zip(first_visit).collect(),
+            longest_stay: states.iter().cloned().zip(longest_stay).collect(),
+        })
+    }
+}
@@ -3,6 +3,17 @@ pub mod engine;
 pub mod server;
 pub mod tools;
 pub mod db;
+pub mod telemetry;
 pub mod services {
+    pub mod auth;
+    pub mod backup;
+    pub mod cron;
+    pub mod crypto;
     pub mod entropy;
+    pub mod events;
+    pub mod intention;
+    pub mod jobs;
+    pub mod quantum;
+    pub mod quota;
+    pub mod webhooks;
 }
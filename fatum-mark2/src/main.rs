@@ -1,12 +1,15 @@
 mod cli;
 use cli::handler::handle_cli;
 use anyhow::Result;
+use std::time::Duration;
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
+fn main() -> Result<()> {
+    // Initialize tracing (RUST_LOG / FATUM_LOG_FORMAT)
+    fatum_mark2::telemetry::init();
 
-    handle_cli().await;
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(handle_cli());
+    // Interrupted simulations are already persisted; don't wait for their blocking threads
+    runtime.shutdown_timeout(Duration::from_secs(1));
     Ok(())
 }
@@ -0,0 +1,44 @@
+use axum::{Json, Extension};
+use serde::Deserialize;
+
+use super::AppState;
+use super::auth::{require_admin, AuthUser};
+use super::error::{AppError, AppResult};
+use crate::services::backup::{self, BackupInfo};
+
+#[derive(Deserialize)]
+pub struct RestoreInput {
+    name: String,
+}
+
+/// Takes a backup now.
+pub async fn create_backup(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<BackupInfo>> {
+    require_admin(&user)?;
+    let info = backup::create_backup(&state.db, backup::config(), None).await?;
+    Ok(Json(info))
+}
+
+pub async fn list_backups(
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<Vec<BackupInfo>>> {
+    require_admin(&user)?;
+    Ok(Json(backup::list_backups(backup::config())?))
+}
+
+/// Restores a backup by name, after snapshotting the current state.
+pub async fn restore_backup(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Json(input): Json<RestoreInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    require_admin(&user)?;
+    let path = backup::find_backup(backup::config(), &input.name)
+        .ok_or_else(|| AppError::not_found(format!("Backup {:?} not found", input.name)))?;
+    let safety = backup::restore_backup(&state.db, backup::config(), &path)
+        .await
+        .map_err(|e| AppError::bad_request(format!("Restore failed: {:#}", e)))?;
+    Ok(Json(serde_json::json!({ "restored": input.name, "safety_backup": safety })))
+}
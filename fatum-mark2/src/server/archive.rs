@@ -0,0 +1,113 @@
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+
+use super::AppState;
+use super::auth::AuthUser;
+use super::error::AppResult;
+use crate::db::NewHistory;
+use crate::engine::EntropyRange;
+use crate::services::events::{self, ServerEvent};
+
+lazy_static::lazy_static! {
+    // FATUM_AUTO_ARCHIVE=0 leaves archiving to requests that ask for it with ?archive=true
+    static ref AUTO_ARCHIVE: bool = std::env::var("FATUM_AUTO_ARCHIVE")
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(true);
+}
+
+/// `?archive=false` keeps a tool run out of history; `?archive=true` saves it even when
+/// auto-archiving is off server-wide.
+#[derive(Deserialize)]
+pub struct ArchiveParams {
+    archive: Option<bool>,
+}
+
+impl ArchiveParams {
+    fn enabled(&self) -> bool {
+        self.archive.unwrap_or(*AUTO_ARCHIVE)
+    }
+}
+
+/// What gets recorded about a tool run besides its report.
+pub struct ToolRun {
+    pub tool_type: &'static str,
+    pub profile_id: Option<i64>,
+    pub summary: String,
+    /// The request as received.
+    pub config: serde_json::Value,
+    pub entropy_batch_id: Option<i64>,
+}
+
+impl ToolRun {
+    pub fn into_entry(self, report: &serde_json::Value) -> NewHistory {
+        NewHistory {
+            profile_id: self.profile_id,
+            tool_type: self.tool_type.to_string(),
+            summary: self.summary,
+            full_report: report.clone(),
+            config: Some(self.config),
+            entropy_batch_id: self.entropy_batch_id,
+            entropy_ranges: report_ranges(report)
+                .filter(|r| !r.is_empty())
+                .map(|r| serde_json::to_string(&r).unwrap()),
+        }
+    }
+}
+
+/// The report's own `entropy_ranges`, as stored in the history column.
+pub fn report_ranges(report: &serde_json::Value) -> Option<Vec<EntropyRange>> {
+    report.get("entropy_ranges").and_then(|v| serde_json::from_value(v.clone()).ok())
+}
+
+/// Saves a history entry and announces it to `/ws` clients.
+pub async fn record(state: &AppState, user: &AuthUser, entry: NewHistory) -> AppResult<i64> {
+    let id = state.db.insert_history(user.id, &entry).await?;
+    events::emit(&state.events, ServerEvent::HistoryCreated {
+        id,
+        owner_id: user.id,
+        profile_id: entry.profile_id,
+        tool_type: entry.tool_type,
+        summary: entry.summary,
+    });
+    Ok(id)
+}
+
+/// Archives a finished tool run unless the caller opted out, returning the history id.
+///
+/// A failed save is logged rather than returned: the caller has already been charged for
+/// the entropy, so the report is still worth sending back.
+pub async fn archive_run(
+    state: &AppState,
+    user: &AuthUser,
+    params: &ArchiveParams,
+    run: ToolRun,
+    report: &serde_json::Value,
+) -> Option<i64> {
+    if !params.enabled() {
+        return None;
+    }
+    let tool = run.tool_type;
+    match record(state, user, run.into_entry(report)).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            tracing::warn!(tool, error = %e, "Could not archive tool run");
+            None
+        }
+    }
+}
+
+/// Header carrying the history id of an archived run, for responses without a JSON object body.
+pub const HISTORY_ID_HEADER: &str = "x-history-id";
+
+/// Responds with a tool's report, tagged with its history id if it was archived: in the
+/// `X-History-Id` header, and as `history_id` when the report is a JSON object.
+pub fn respond(mut report: serde_json::Value, history_id: Option<i64>) -> Response {
+    let Some(id) = history_id else {
+        return Json(report).into_response();
+    };
+    if let Some(fields) = report.as_object_mut() {
+        fields.insert("history_id".to_string(), id.into());
+    }
+    ([(HISTORY_ID_HEADER, id.to_string())], Json(report)).into_response()
+}
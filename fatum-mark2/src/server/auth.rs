@@ -0,0 +1,196 @@
+use axum::{
+    Json, Extension,
+    extract::{Path, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    http::{header, Method},
+};
+use serde::Deserialize;
+
+use super::AppState;
+use super::error::{AppError, AppResult};
+use super::versioning::split_version;
+use crate::db::User;
+use crate::services::auth::{generate_api_key, hash_api_key};
+
+/// The user behind the API key of the current request, set by `require_api_key`.
+pub type AuthUser = User;
+
+/// Pulls the key from `Authorization: Bearer`, `X-API-Key`, or an `api_key` query
+/// parameter (browsers cannot set headers on WebSocket upgrades).
+fn extract_key(req: &Request) -> Option<String> {
+    let headers = req.headers();
+    if let Some(v) = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()) {
+        if let Some(token) = v.strip_prefix("Bearer ") {
+            return Some(token.trim().to_string());
+        }
+    }
+    if let Some(v) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) {
+        return Some(v.trim().to_string());
+    }
+    req.uri().query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("api_key="))
+        .map(|v| v.to_string())
+}
+
+/// Middleware guarding `/api` and `/ws`: requests need a valid API key.
+///
+/// The one exception is `POST /api/users` while no users exist, which bootstraps
+/// the first admin on a fresh install.
+pub async fn require_api_key(
+    Extension(state): Extension<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path();
+    if !(path.starts_with("/api/") || path == "/ws") {
+        return next.run(req).await;
+    }
+
+    if let Some(key) = extract_key(&req) {
+        return match state.db.authenticate_key(&hash_api_key(&key)).await {
+            Ok(Some(user)) => {
+                req.extensions_mut().insert::<AuthUser>(user);
+                next.run(req).await
+            }
+            Ok(None) => AppError::Unauthorized("Invalid API key".to_string()).into_response(),
+            Err(e) => AppError::from(e).into_response(),
+        };
+    }
+
+    if req.method() == Method::POST && split_version(path).1 == "/api/users" {
+        match state.db.count_users().await {
+            Ok(0) => return next.run(req).await,
+            Ok(_) => {}
+            Err(e) => return AppError::from(e).into_response(),
+        }
+    }
+    AppError::Unauthorized("API key required".to_string()).into_response()
+}
+
+// === USER MANAGEMENT HANDLERS ===
+
+pub(super) fn require_admin(user: &AuthUser) -> AppResult<()> {
+    if user.is_admin { Ok(()) } else { Err(AppError::Forbidden("Admin only".to_string())) }
+}
+
+/// Admins may act on any user, everyone else only on themselves.
+fn require_self_or_admin(user: &AuthUser, user_id: i64) -> AppResult<()> {
+    if user.is_admin || user.id == user_id { Ok(()) } else { Err(AppError::Forbidden("Not allowed".to_string())) }
+}
+
+#[derive(Deserialize)]
+pub struct CreateUserInput {
+    username: String,
+    #[serde(default)]
+    is_admin: bool,
+}
+
+#[derive(Deserialize)]
+pub struct CreateKeyInput {
+    label: Option<String>,
+}
+
+/// Returns the calling user and their keys.
+pub async fn get_me(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<serde_json::Value>> {
+    let keys = state.db.list_api_keys(user.id).await?;
+    Ok(Json(serde_json::json!({ "user": user, "api_keys": keys })))
+}
+
+pub async fn list_users(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<serde_json::Value>> {
+    require_admin(&user)?;
+    let users = state.db.list_users().await?;
+    Ok(Json(serde_json::json!(users)))
+}
+
+/// Creates a user and returns its first API key (shown only once).
+///
+/// Admin only, except for the bootstrap call that creates the first (admin) user.
+pub async fn create_user(
+    Extension(state): Extension<AppState>,
+    caller: Option<Extension<AuthUser>>,
+    Json(input): Json<CreateUserInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    let bootstrap = caller.is_none();
+    let is_admin = match caller {
+        Some(Extension(u)) => {
+            require_admin(&u)?;
+            input.is_admin
+        }
+        None => true, // Bootstrap: the middleware only lets this through with zero users
+    };
+    let username = input.username.trim();
+    if username.is_empty() {
+        return Err(AppError::bad_request("username is required"));
+    }
+
+    let id = if bootstrap {
+        // Another request may have bootstrapped since the middleware checked
+        let id = state.db.create_first_user(username).await?
+            .ok_or_else(|| AppError::Unauthorized("API key required".to_string()))?;
+        // Data from before auth existed belongs to the first admin
+        state.db.claim_unowned_rows(id).await?;
+        id
+    } else {
+        state.db.create_user(username, is_admin).await?
+    };
+    let key = generate_api_key();
+    state.db.insert_api_key(id, &hash_api_key(&key), Some("initial")).await?;
+    Ok(Json(serde_json::json!({ "id": id, "username": username, "is_admin": is_admin, "api_key": key })))
+}
+
+pub async fn delete_user(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    require_admin(&user)?;
+    if id == user.id {
+        return Err(AppError::bad_request("Cannot delete your own account"));
+    }
+    if !state.db.delete_user(id).await? {
+        return Err(AppError::not_found(format!("User {} not found", id)));
+    }
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
+
+pub async fn list_user_keys(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    require_self_or_admin(&user, id)?;
+    let keys = state.db.list_api_keys(id).await?;
+    Ok(Json(serde_json::json!(keys)))
+}
+
+/// Issues an additional key for a user (admins for anyone, users for themselves).
+pub async fn create_user_key(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    Json(input): Json<CreateKeyInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    require_self_or_admin(&user, id)?;
+    let key = generate_api_key();
+    let key_id = state.db.insert_api_key(id, &hash_api_key(&key), input.label.as_deref()).await?;
+    Ok(Json(serde_json::json!({ "id": key_id, "api_key": key })))
+}
+
+pub async fn revoke_key(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    let key = state.db.get_api_key(id).await?;
+    require_self_or_admin(&user, key.user_id)?;
+    state.db.delete_api_key(id).await?;
+    Ok(Json(serde_json::json!({ "revoked": id })))
+}
@@ -0,0 +1,133 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use super::validation::FieldError;
+use crate::services::quota::QuotaExceeded;
+
+/// Error returned by API handlers, rendered as `{"error": "..."}` with a matching status.
+///
+/// Validation failures also carry `"fields": [{"field", "message"}]`.
+#[derive(Debug)]
+pub enum AppError {
+    /// 400: the request itself is invalid.
+    BadRequest(String),
+    /// 400: one or more fields failed validation.
+    Validation(Vec<FieldError>),
+    /// 401: missing or unknown API key.
+    Unauthorized(String),
+    /// 403: authenticated but not allowed.
+    Forbidden(String),
+    /// 404: the referenced row does not exist (or belongs to someone else).
+    NotFound(String),
+    /// 406: the client asked for an API version this server does not speak.
+    NotAcceptable(String),
+    /// 409: the write clashes with existing data (e.g. a taken username).
+    Conflict(String),
+    /// 429: the profile's entropy allowance is used up.
+    TooManyRequests(String),
+    /// 502: the randomness beacon could not be reached.
+    BadGateway(String),
+    /// 500: anything else.
+    Internal(anyhow::Error),
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn bad_request(msg: impl ToString) -> Self {
+        AppError::BadRequest(msg.to_string())
+    }
+
+    pub fn not_found(msg: impl ToString) -> Self {
+        AppError::NotFound(msg.to_string())
+    }
+
+    /// Maps a beacon fetch failure.
+    pub fn beacon(e: impl std::fmt::Display) -> Self {
+        AppError::BadGateway(format!("Failed to fetch entropy: {}", e))
+    }
+
+    /// Maps an error from a tool computation: storage and network failures keep their
+    /// own status, anything else is a problem with the input (invalid date etc.).
+    pub fn from_tool(e: anyhow::Error) -> Self {
+        if e.downcast_ref::<sqlx::Error>().is_some() || e.downcast_ref::<QuotaExceeded>().is_some() {
+            return e.into();
+        }
+        if e.chain().any(|c| c.is::<reqwest::Error>()) {
+            return AppError::beacon(e);
+        }
+        AppError::BadRequest(e.to_string())
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) | AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::BadRequest(m)
+            | AppError::Unauthorized(m)
+            | AppError::Forbidden(m)
+            | AppError::NotFound(m)
+            | AppError::NotAcceptable(m)
+            | AppError::Conflict(m)
+            | AppError::TooManyRequests(m)
+            | AppError::BadGateway(m) => f.write_str(m),
+            AppError::Validation(fields) => {
+                let parts: Vec<String> = fields.iter().map(|e| format!("{} {}", e.field, e.message)).collect();
+                write!(f, "Invalid request: {}", parts.join("; "))
+            }
+            AppError::Internal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        if let AppError::Internal(e) = &self {
+            tracing::error!(error = format!("{:#}", e), "Internal error");
+        }
+        let mut body = serde_json::json!({ "error": self.to_string() });
+        if let AppError::Validation(fields) = &self {
+            body["fields"] = serde_json::json!(fields);
+        }
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(e: anyhow::Error) -> Self {
+        if e.downcast_ref::<QuotaExceeded>().is_some() {
+            return AppError::TooManyRequests(e.to_string());
+        }
+        match e.downcast::<sqlx::Error>() {
+            Ok(db_err) => db_err.into(),
+            Err(e) => AppError::Internal(e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => AppError::NotFound("Not found".to_string()),
+            sqlx::Error::Database(db) if db.is_unique_violation() => AppError::Conflict(db.message().to_string()),
+            other => AppError::Internal(other.into()),
+        }
+    }
+}
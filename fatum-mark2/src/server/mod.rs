@@ -1,82 +1,346 @@
 use axum::{
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post},
     Json, Router, Extension,
-    response::{IntoResponse, Response},
+    extract::{Path, Query, ws::{Message, WebSocket, WebSocketUpgrade}},
+    response::{IntoResponse, Response, sse::{Event, KeepAlive, Sse}},
     http::{header, StatusCode},
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
 use tower_http::services::ServeDir;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
+use tracing::Instrument;
 use serde::{Deserialize, Serialize};
 
+mod admin;
+mod archive;
+mod auth;
+mod error;
+mod rate_limit;
+mod schedules;
+mod trips;
+mod validation;
+mod versioning;
+mod webhooks;
+
+use archive::{ArchiveParams, ToolRun};
+use auth::AuthUser;
+use error::{AppError, AppResult};
+use validation::ValidJson;
+
 use crate::client::CurbyClient;
-use crate::engine::SimulationSession;
-use crate::engine::timeline::TimelineSimulator;
-use crate::tools::feng_shui::{FengShuiConfig, generate_report, VirtualCure};
-use crate::tools::divination::DivinationTool;
+use crate::engine::{EntropyRange, SimulationOptions, SimulationSession};
+use crate::engine::timeline::{TimelineSimulator, WuXingParams};
+use crate::tools::astronomy::Location;
+use crate::tools::feng_shui::{FengShuiConfig, FengShuiReport, generate_report, calculate_bazi, VirtualCure};
+use crate::tools::floor_plan::Room;
+use crate::tools::i18n::Locale;
+use crate::tools::property_compare::{self, PropertyComparisonConfig, compare_properties};
+use crate::tools::divination::{bind_question, DivinationConfig, DivinationTool};
+use crate::tools::liu_yao::{LiuYaoConfig, cast_liu_yao};
+use crate::tools::tarot::{TarotConfig, draw_spread};
+use crate::tools::astrology_western::{NatalConfig, calculate_natal_chart};
+use crate::tools::astrology_vedic::{VedicConfig, calculate_vedic_chart};
+use crate::tools::astrology_reports::{SynastryConfig, TransitConfig, calculate_synastry, scan_transits};
+use crate::tools::runes::{RuneConfig, cast_runes};
+use crate::tools::numerology::{self, NumerologyConfig, calculate_numerology};
 use crate::tools::pdf_generator::generate_pdf;
-use crate::tools::ze_ri::{DateSelectionConfig, calculate_auspiciousness};
-use crate::tools::zi_wei::{ZiWeiConfig, generate_ziwei_chart};
+use crate::tools::ze_ri::{self, DateSelectionConfig, calculate_auspiciousness};
+use crate::tools::zi_wei::{self, ZiWeiConfig, generate_ziwei_chart};
 use crate::tools::da_liu_ren::{DaLiuRenConfig, generate_da_liu_ren};
-use crate::tools::entanglement::{EntanglementRequest, calculate_entanglement};
-use crate::db::Db;
+use crate::tools::qimen_directions::{QiMenConfig, recommend_directions};
+use crate::tools::entanglement::{EntanglementMode, EntanglementRequest, calculate_entanglement};
+use crate::tools::geolocation::{self, GeolocationConfig, GeolocationTool};
+use crate::tools::decision::{self, DecisionInput, DecisionTool, DecisionTree};
+use crate::db::{Db, HistoryDetail, HistoryEntry, HistoryOnDelete, NewHistory, Preset, Profile, ProfileFields};
+use crate::services::backup;
 use crate::services::entropy;
+use crate::services::events::{self, EventSender, ServerEvent};
+use crate::services::intention;
+use crate::services::jobs::{self, JobEvent};
+use crate::services::quota;
 use std::collections::HashMap;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::BroadcastStream;
 
 #[derive(Clone)]
 pub struct AppState {
     db: Arc<Db>,
+    // Server-wide push channel, fanned out to `/ws` clients
+    events: EventSender,
+    // Flips to true once shutdown starts, so long-lived connections can close
+    shutdown: watch::Receiver<bool>,
 }
 
+/// How long running simulation jobs get to finish before they are persisted as interrupted.
+const JOB_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long the harvester gets to finish saving an in-flight pulse.
+const HARVEST_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub async fn start_server() {
-    let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:fatum.db".to_string());
+    let db_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| crate::db::DEFAULT_DATABASE_URL.to_string());
     let db = Db::new(&db_url).await.expect("Failed to initialize database");
-    let shared_state = AppState { db: Arc::new(db) };
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let shared_state = AppState { db: Arc::new(db), events: events::channel(), shutdown: shutdown_rx };
+    let db = shared_state.db.clone();
+    let events_tx = shared_state.events.clone();
+    backup::spawn_scheduler(db.clone(), backup::config().clone(), shared_state.shutdown.clone());
+    schedules::spawn_runner(shared_state.clone());
+    crate::services::webhooks::spawn_dispatcher(db.clone(), events_tx.subscribe(), shared_state.shutdown.clone());
+
+    // Endpoints that pull entropy from CURBy, throttled per client
+    let beacon = Router::new()
+        .route("/tools/fengshui", post(handle_fengshui))
+        .route("/tools/fengshui/pdf", post(handle_fengshui_pdf))
+        .route("/tools/fengshui/compare", post(handle_fengshui_compare))
+        .route("/tools/divination", post(handle_divination))
+        .route("/tools/liuyao", post(handle_liuyao))
+        .route("/tools/tarot", post(handle_tarot))
+        .route("/tools/runes", post(handle_runes))
+        .route("/tools/numerology", post(handle_numerology))
+        .route("/tools/ziwei", post(handle_ziwei))
+        .route("/tools/zeri", post(handle_zeri))
+        .route("/tools/entanglement", post(handle_entanglement))
+        .route("/tools/geolocation", post(handle_geolocation))
+        .route("/tools/decision", post(handle_decision))
+        .route("/tools/many_worlds", post(handle_many_worlds))
+        .route("/tools/timeline", post(handle_timeline))
+        .route("/simulate", post(start_simulation))
+        .route("/schedules/{id}/run", post(schedules::run_schedule_now))
+        .route_layer(middleware::from_fn(rate_limit::limit_beacon));
+
+    // Served at /api/v1 and, as a compatibility shim for older frontends, at /api
+    let api = Router::new()
+        .merge(beacon)
+        .route("/tools/decision/check", post(check_decision_tree))
+        .route("/tools/daliuren", post(handle_daliuren))
+        .route("/tools/qimen", post(handle_qimen))
+        .route("/tools/natal", post(handle_natal))
+        .route("/tools/transits", post(handle_transits))
+        .route("/tools/synastry", post(handle_synastry))
+        .route("/tools/vedic", post(handle_vedic))
+        .route("/simulate/{job_id}/progress", get(simulation_progress))
+        .route("/simulate/interrupted", get(list_interrupted_jobs))
+        .route("/simulate/interrupted/{id}", delete(delete_interrupted_job))
+        .route("/profiles", get(list_profiles).post(create_profile))
+        .route("/profiles/{id}", axum::routing::put(update_profile).delete(delete_profile))
+        .route("/profiles/{id}/quota", get(get_profile_quota))
+        .route("/presets", get(list_presets).post(create_preset))
+        .route("/presets/{id}", get(get_preset).put(update_preset).delete(delete_preset))
+        .route("/schedules", get(schedules::list_schedules).post(schedules::create_schedule))
+        .route("/schedules/{id}", get(schedules::get_schedule).put(schedules::update_schedule).delete(schedules::delete_schedule))
+        .route("/webhooks", get(webhooks::list_webhooks).post(webhooks::create_webhook))
+        .route("/webhooks/{id}", axum::routing::put(webhooks::update_webhook).delete(webhooks::delete_webhook))
+        .route("/webhooks/{id}/test", post(webhooks::test_webhook))
+        .route("/trips", get(trips::list_trips).post(trips::create_trip))
+        .route("/trips/stats", get(trips::trip_stats))
+        .route("/trips/{id}", get(trips::get_trip).put(trips::update_trip).delete(trips::delete_trip))
+        .route("/history", get(list_history).post(save_history))
+        .route("/history/{id}", get(get_history).delete(delete_history))
+        .route("/entropy/batches", get(list_entropy_batches).post(create_entropy_batch))
+        .route("/entropy/harvest/start", post(start_harvest))
+        .route("/entropy/harvest/stop", post(stop_harvest))
+        .route("/entropy/harvest/status", get(harvest_status))
+        .route("/me", get(auth::get_me))
+        .route("/users", get(auth::list_users).post(auth::create_user))
+        .route("/users/{id}", delete(auth::delete_user))
+        .route("/users/{id}/keys", get(auth::list_user_keys).post(auth::create_user_key))
+        .route("/keys/{id}", delete(auth::revoke_key))
+        .route("/admin/backup", post(admin::create_backup))
+        .route("/admin/backups", get(admin::list_backups))
+        .route("/admin/restore", post(admin::restore_backup));
 
     let app = Router::new()
-        .route("/api/tools/fengshui", post(handle_fengshui))
-        .route("/api/tools/fengshui/pdf", post(handle_fengshui_pdf))
-        .route("/api/tools/divination", post(handle_divination))
-        .route("/api/tools/zeri", post(handle_zeri))
-        .route("/api/tools/ziwei", post(handle_ziwei))
-        .route("/api/tools/daliuren", post(handle_daliuren))
-        .route("/api/tools/entanglement", post(handle_entanglement))
-        .route("/api/tools/many_worlds", post(handle_many_worlds))
-        .route("/api/profiles", get(list_profiles).post(create_profile))
-        .route("/api/history", get(list_history).post(save_history))
-        .route("/api/entropy/batches", get(list_entropy_batches).post(create_entropy_batch))
-        .route("/api/entropy/harvest/start", post(start_harvest))
-        .route("/api/entropy/harvest/stop", post(stop_harvest))
-        .route("/api/entropy/harvest/status", get(harvest_status))
+        .nest(&format!("/api/v{}", versioning::CURRENT_VERSION), api.clone())
+        .nest("/api", api)
+        .route("/ws", get(ws_handler))
         .fallback_service(ServeDir::new("static"))
-        .layer(Extension(shared_state));
+        .layer(middleware::from_fn(versioning::negotiate_version))
+        // Runs inside the Extension layer so it can reach the DB
+        .layer(middleware::from_fn(auth::require_api_key))
+        .layer(Extension(shared_state))
+        // Outermost: tag every request with an x-request-id (kept if the client sent one),
+        // log it in a span and echo it back on the response
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(TraceLayer::new_for_http().make_span_with(|req: &axum::http::Request<_>| {
+            let request_id = req.headers().get("x-request-id").and_then(|v| v.to_str().ok()).unwrap_or("-");
+            tracing::info_span!("http", method = %req.method(), uri = %req.uri().path(), request_id)
+        }).on_response(DefaultOnResponse::new().level(tracing::Level::INFO)))
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    println!("FATUM-MARK2 Server listening on http://{}", addr);
+    tracing::info!(%addr, "FATUM-MARK2 server listening");
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let drain_db = db.clone();
+    let (drain_tx, drain_rx) = tokio::sync::oneshot::channel();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            tracing::info!("Shutdown requested, no longer accepting connections");
+            let _ = shutdown_tx.send(true);
+            // The listener closes as soon as this resolves; running jobs drain meanwhile,
+            // and interrupting them ends the progress streams `serve` is waiting on
+            let _ = drain_tx.send(tokio::spawn(async move { drain_jobs(&drain_db).await }));
+        })
+        .await
+        .unwrap();
+    if let Ok(drain) = drain_rx.await {
+        let _ = drain.await;
+    }
+
+    // Connections are closed; stop background work and release the database
+    entropy::shutdown_harvesting(db.clone(), &events_tx, HARVEST_FLUSH_TIMEOUT).await;
+    db.pool.close().await;
+    tracing::info!("Server stopped");
 }
 
-#[derive(Deserialize)]
+/// Resolves on Ctrl+C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Gives running simulations `JOB_DRAIN_TIMEOUT` to finish, then fails the rest (which
+/// ends their progress streams) and saves their requests to `interrupted_jobs`.
+async fn drain_jobs(db: &Db) {
+    let running = jobs::running_count();
+    if running == 0 {
+        return;
+    }
+    tracing::info!(running, "Waiting for running simulation jobs");
+    if jobs::wait_idle(JOB_DRAIN_TIMEOUT).await {
+        return;
+    }
+    for job in jobs::interrupt_running("Server shutting down") {
+        if let Err(e) = db.save_interrupted_job(job.job_id, job.owner_id, &job.request).await {
+            tracing::error!(job_id = job.job_id, error = %e, "Failed to persist interrupted job");
+        }
+    }
+}
+
+/// Bytes drawn per Feng Shui report (matches the live fetch size in `generate_report`).
+const FENGSHUI_ENTROPY_BYTES: i64 = 4096;
+/// Bytes drawn per quantum Zi Wei chart (one draw per palace simulation).
+const ZIWEI_ENTROPY_BYTES: usize = zi_wei::PALACE_SIMULATIONS * 8;
+/// Bytes drawn per quantum Ze Ri selection (one draw per date simulation).
+const ZERI_ENTROPY_BYTES: usize = ze_ri::DATE_SIMULATIONS * 8;
+/// Bytes drawn per Many Worlds run.
+const MANY_WORLDS_ENTROPY_BYTES: usize = 2048;
+/// Bytes drawn per Entropy Stream entanglement run (the stream's seed).
+const ENTANGLEMENT_ENTROPY_BYTES: usize = 32;
+/// Bytes drawn per divination, Liu Yao or tarot cast.
+const CAST_ENTROPY_BYTES: usize = 1024;
+/// Bytes drawn per rune cast.
+const RUNES_ENTROPY_BYTES: usize = 512;
+/// Bytes drawn per numerology resonance simulation (one draw per trial).
+const NUMEROLOGY_ENTROPY_BYTES: usize = numerology::RESONANCE_TRIALS * 8;
+
+/// Rejects profile or batch ids that belong to another user.
+///
+/// Foreign and missing rows both map to 404 so ids can't be probed.
+async fn check_ownership(state: &AppState, user: &AuthUser, profile_id: Option<i64>, batch_id: Option<i64>) -> AppResult<()> {
+    for (what, id) in [("Profile", profile_id), ("Batch", batch_id)] {
+        let Some(id) = id else { continue };
+        let owner = if what == "Profile" { state.db.profile_owner(id).await } else { state.db.batch_owner(id).await };
+        match owner.map_err(AppError::from) {
+            Ok(owner) if owner == Some(user.id) => continue,
+            Ok(_) | Err(AppError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        return Err(AppError::not_found(format!("{} {} not found", what, id)));
+    }
+    Ok(())
+}
+
+/// Charges a tool's beacon draw to the requesting profile, or to the caller's own
+/// allowance when no profile is named, so every fetch is metered. An exhausted
+/// allowance maps to 429.
+async fn charge_entropy(state: &AppState, user: &AuthUser, profile_id: Option<i64>, bytes: i64) -> AppResult<()> {
+    match profile_id {
+        Some(pid) => {
+            check_ownership(state, user, Some(pid), None).await?;
+            quota::consume(&state.db, pid, user.id, bytes).await?;
+        }
+        None => {
+            quota::consume_for_user(&state.db, user.id, bytes).await?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
 struct FengShuiApiInput {
+    profile_id: Option<i64>,
     birth_year: Option<i32>,
     birth_month: Option<u32>,
     birth_day: Option<u32>,
     birth_hour: Option<u32>,
     gender: Option<String>,
+    location: Option<Location>,
     construction_year: Option<i32>,
     facing_degrees: Option<f64>,
+    water_exit_degrees: Option<f64>,
+    water_features: Option<Vec<f64>>,
     intention: Option<String>,
     quantum_mode: Option<bool>,
     virtual_cures: Option<Vec<VirtualCure>>,
     entropy_batch_id: Option<i64>,
+    simulation_options: Option<SimulationOptions>,
+    rooms: Option<Vec<Room>>,
+    annual_years: Option<[i32; 2]>,
+    locale: Option<Locale>,
 }
 
-async fn handle_fengshui(
-    Extension(state): Extension<AppState>,
-    Json(payload): Json<FengShuiApiInput>,
-) -> Json<serde_json::Value> {
+impl FengShuiApiInput {
+    fn tool_run(&self) -> ToolRun {
+        let mut summary = format!(
+            "Facing {}°, built {}",
+            self.facing_degrees.unwrap_or(180.0),
+            self.construction_year.unwrap_or(2024)
+        );
+        if let Some(intention) = self.intention.as_deref().filter(|i| !i.trim().is_empty()) {
+            summary.push_str(&format!(" ({})", intention.trim()));
+        }
+        ToolRun {
+            tool_type: "fengshui",
+            profile_id: self.profile_id,
+            summary,
+            config: serde_json::to_value(self).unwrap(),
+            entropy_batch_id: self.entropy_batch_id,
+        }
+    }
+}
+
+/// Checks access, charges the entropy and builds the report; shared by the JSON and PDF
+/// endpoints and by scheduled runs.
+async fn run_fengshui(state: &AppState, user: &AuthUser, payload: FengShuiApiInput) -> AppResult<(FengShuiReport, ToolRun)> {
+    check_ownership(state, user, None, payload.entropy_batch_id).await?;
+    // Replaying a stored batch draws nothing from the beacon
+    if payload.entropy_batch_id.is_none() {
+        charge_entropy(state, user, payload.profile_id, FENGSHUI_ENTROPY_BYTES).await?;
+    }
+    let run = payload.tool_run();
     let now = chrono::Local::now();
     use chrono::Datelike;
     let config = FengShuiConfig {
@@ -85,8 +349,11 @@ async fn handle_fengshui(
         birth_day: payload.birth_day,
         birth_hour: payload.birth_hour,
         gender: payload.gender,
+        location: payload.location,
         construction_year: payload.construction_year.unwrap_or(2024),
         facing_degrees: payload.facing_degrees.unwrap_or(180.0),
+        water_exit_degrees: payload.water_exit_degrees,
+        water_features: payload.water_features,
         current_year: Some(now.year()),
         current_month: Some(now.month()),
         current_day: Some(now.day()),
@@ -94,151 +361,961 @@ async fn handle_fengshui(
         quantum_mode: payload.quantum_mode.unwrap_or(false),
         virtual_cures: payload.virtual_cures,
         entropy_batch_id: payload.entropy_batch_id,
+        simulation_options: payload.simulation_options,
+        rooms: payload.rooms,
+        annual_years: payload.annual_years,
+        locale: payload.locale,
     };
 
     // Need to pass DB reference to generate_report if using batch
-    match generate_report(config, Some(state.db.clone())).await {
-        Ok(report) => Json(serde_json::to_value(report).unwrap()),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
-    }
+    let report = generate_report(config, Some(state.db.clone())).await.map_err(AppError::from_tool)?;
+    Ok((report, run))
+}
+
+async fn handle_fengshui(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<FengShuiApiInput>,
+) -> AppResult<Response> {
+    let (report, run) = run_fengshui(&state, &user, payload).await?;
+    let report = serde_json::to_value(report).unwrap();
+    let history_id = archive::archive_run(&state, &user, &archive, run, &report).await;
+    Ok(archive::respond(report, history_id))
 }
 
+/// Like `handle_fengshui`, but returns the report as a PDF; the history id, if archived,
+/// is in the `X-History-Id` header.
 async fn handle_fengshui_pdf(
     Extension(state): Extension<AppState>,
-    Json(payload): Json<FengShuiApiInput>,
-) -> Response {
-    let now = chrono::Local::now();
-    use chrono::Datelike;
-    let config = FengShuiConfig {
-        birth_year: payload.birth_year,
-        birth_month: payload.birth_month,
-        birth_day: payload.birth_day,
-        birth_hour: payload.birth_hour,
-        gender: payload.gender,
-        construction_year: payload.construction_year.unwrap_or(2024),
-        facing_degrees: payload.facing_degrees.unwrap_or(180.0),
-        current_year: Some(now.year()),
-        current_month: Some(now.month()),
-        current_day: Some(now.day()),
-        intention: payload.intention,
-        quantum_mode: payload.quantum_mode.unwrap_or(false),
-        virtual_cures: payload.virtual_cures,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<FengShuiApiInput>,
+) -> AppResult<Response> {
+    let (report, run) = run_fengshui(&state, &user, payload).await?;
+    let pdf_bytes = generate_pdf(&report)?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &serde_json::to_value(&report).unwrap()).await;
+    let mut response = (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/pdf")],
+        pdf_bytes
+    ).into_response();
+    if let Some(id) = history_id {
+        response.headers_mut().insert(archive::HISTORY_ID_HEADER, id.into());
+    }
+    Ok(response)
+}
+
+/// Ranks candidate homes for one occupant; the tie-breaks come from the batch or a beacon
+/// fetch of one draw per home.
+async fn run_fengshui_compare(state: &AppState, user: &AuthUser, payload: PropertyComparisonConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    check_ownership(state, user, None, payload.entropy_batch_id).await?;
+    let bytes = payload.properties.len() * property_compare::BYTES_PER_PROPERTY;
+    let entropy = match payload.entropy_batch_id {
+        Some(batch_id) => load_batch_entropy(state, batch_id).await?,
+        None => {
+            charge_entropy(state, user, payload.profile_id, bytes as i64).await?;
+            CurbyClient::new().fetch_bulk_randomness(bytes).await.map_err(AppError::beacon)?
+        }
+    };
+    let mut session = SimulationSession::new(entropy);
+    let report = compare_properties(&mut session, &payload).map_err(AppError::from_tool)?;
+    let run = ToolRun {
+        tool_type: "fengshui_compare",
+        profile_id: payload.profile_id,
+        summary: match report.properties.first() {
+            Some(best) => format!("{} homes compared, best: {} ({:+.1})", report.properties.len(), best.name, best.total),
+            None => "No homes compared".to_string(),
+        },
+        config: serde_json::to_value(&payload).unwrap(),
         entropy_batch_id: payload.entropy_batch_id,
     };
+    Ok((serde_json::to_value(report).unwrap(), run))
+}
 
-    match generate_report(config, Some(state.db.clone())).await {
-        Ok(report) => {
-            match generate_pdf(&report) {
-                Ok(pdf_bytes) => {
-                    (
-                        StatusCode::OK,
-                        [(header::CONTENT_TYPE, "application/pdf")],
-                        pdf_bytes
-                    ).into_response()
-                },
-                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
-            }
+async fn handle_fengshui_compare(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<PropertyComparisonConfig>,
+) -> AppResult<Response> {
+    let (report, run) = run_fengshui_compare(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &report).await;
+    Ok(archive::respond(report, history_id))
+}
+
+/// Plain mode returns the qualifying dates; quantum mode wraps them as `{ dates, quantum }`.
+async fn run_zeri(state: &AppState, user: &AuthUser, payload: DateSelectionConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    let run = ToolRun {
+        tool_type: "zeri",
+        profile_id: None,
+        summary: match payload.intention.as_deref() {
+            Some(intention) => format!("{} to {} ({})", payload.start_date, payload.end_date, intention),
+            None => format!("{} to {}", payload.start_date, payload.end_date),
         },
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
-    }
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    let quantum = payload.quantum_mode.then(|| payload.simulation_options.clone().unwrap_or_default());
+    let results = calculate_auspiciousness(payload).map_err(AppError::BadRequest)?;
+    let Some(sim_options) = quantum else {
+        return Ok((serde_json::to_value(results).unwrap(), run));
+    };
+    charge_entropy(state, user, None, ZERI_ENTROPY_BYTES as i64).await?;
+    let entropy = CurbyClient::new().fetch_bulk_randomness(ZERI_ENTROPY_BYTES).await.map_err(AppError::beacon)?;
+    let mut session = SimulationSession::new(entropy);
+    let pick = ze_ri::run_quantum_selection(&mut session, &results, &sim_options);
+    Ok((serde_json::json!({ "dates": results, "quantum": pick }), run))
 }
 
 async fn handle_zeri(
-    Json(payload): Json<DateSelectionConfig>,
-) -> Json<serde_json::Value> {
-    match calculate_auspiciousness(payload) {
-        Ok(results) => Json(serde_json::to_value(results).unwrap()),
-        Err(e) => Json(serde_json::json!({ "error": e })),
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<DateSelectionConfig>,
+) -> AppResult<Response> {
+    let (results, run) = run_zeri(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &results).await;
+    Ok(archive::respond(results, history_id))
+}
+
+async fn run_ziwei(state: &AppState, user: &AuthUser, payload: ZiWeiConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    let run = ToolRun {
+        tool_type: "ziwei",
+        profile_id: None,
+        summary: format!(
+            "Born {}-{:02}-{:02} {:02}:00 ({})",
+            payload.birth_year, payload.birth_month, payload.birth_day, payload.birth_hour, payload.gender
+        ),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    let quantum = payload.quantum_mode.then(|| payload.simulation_options.clone().unwrap_or_default());
+    let mut chart = generate_ziwei_chart(payload).map_err(AppError::BadRequest)?;
+    if let Some(sim_options) = quantum {
+        charge_entropy(state, user, None, ZIWEI_ENTROPY_BYTES as i64).await?;
+        let entropy = CurbyClient::new().fetch_bulk_randomness(ZIWEI_ENTROPY_BYTES).await.map_err(AppError::beacon)?;
+        let mut session = SimulationSession::new(entropy);
+        chart.quantum = Some(zi_wei::run_quantum_analysis(&mut session, &chart, &sim_options));
     }
+    Ok((serde_json::to_value(chart).unwrap(), run))
 }
 
 async fn handle_ziwei(
-    Json(payload): Json<ZiWeiConfig>,
-) -> Json<serde_json::Value> {
-    match generate_ziwei_chart(payload) {
-        Ok(chart) => Json(serde_json::to_value(chart).unwrap()),
-        Err(e) => Json(serde_json::json!({ "error": e })),
-    }
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<ZiWeiConfig>,
+) -> AppResult<Response> {
+    let (chart, run) = run_ziwei(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &chart).await;
+    Ok(archive::respond(chart, history_id))
+}
+
+fn run_daliuren(payload: DaLiuRenConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    let config = serde_json::to_value(&payload).unwrap();
+    let chart = generate_da_liu_ren(payload).map_err(AppError::BadRequest)?;
+    let run = ToolRun {
+        tool_type: "daliuren",
+        profile_id: None,
+        summary: format!("Transmissions {}", chart.three_transmissions.join(" → ")),
+        config,
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(chart).unwrap(), run))
 }
 
 async fn handle_daliuren(
-    Json(payload): Json<DaLiuRenConfig>,
-) -> Json<serde_json::Value> {
-    match generate_da_liu_ren(payload) {
-        Ok(chart) => Json(serde_json::to_value(chart).unwrap()),
-        Err(e) => Json(serde_json::json!({ "error": e })),
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<DaLiuRenConfig>,
+) -> AppResult<Response> {
+    let (chart, run) = run_daliuren(payload)?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &chart).await;
+    Ok(archive::respond(chart, history_id))
+}
+
+fn run_qimen(payload: QiMenConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    let config = serde_json::to_value(&payload).unwrap();
+    let reading = recommend_directions(&payload);
+    let best = reading.forecast.first().and_then(|h| h.ranking.first());
+    let run = ToolRun {
+        tool_type: "qimen",
+        profile_id: None,
+        summary: match best {
+            Some(d) => format!("{}: best direction {} ({:+})", reading.chart.time_label, d.direction, d.score),
+            None => reading.chart.time_label.clone(),
+        },
+        config,
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(reading).unwrap(), run))
+}
+
+async fn handle_qimen(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<QiMenConfig>,
+) -> AppResult<Response> {
+    let (reading, run) = run_qimen(payload)?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &reading).await;
+    Ok(archive::respond(reading, history_id))
+}
+
+fn run_natal(payload: NatalConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    let chart = calculate_natal_chart(&payload).map_err(AppError::from_tool)?;
+    let placement = |i: usize| format!("{:?} in {}", chart.planets[i].body, chart.planets[i].position.sign);
+    let run = ToolRun {
+        tool_type: "natal",
+        profile_id: None,
+        summary: format!("{}, {}, {} rising", placement(0), placement(1), chart.ascendant.sign),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(chart).unwrap(), run))
+}
+
+async fn handle_natal(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<NatalConfig>,
+) -> AppResult<Response> {
+    let (chart, run) = run_natal(payload)?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &chart).await;
+    Ok(archive::respond(chart, history_id))
+}
+
+fn run_transits(payload: TransitConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    let report = scan_transits(&payload).map_err(AppError::from_tool)?;
+    let run = ToolRun {
+        tool_type: "transits",
+        profile_id: None,
+        summary: format!("{} exact transits {} to {}", report.hits.len(), payload.start_date, payload.end_date),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(report).unwrap(), run))
+}
+
+async fn handle_transits(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<TransitConfig>,
+) -> AppResult<Response> {
+    let (report, run) = run_transits(payload)?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &report).await;
+    Ok(archive::respond(report, history_id))
+}
+
+fn run_synastry(payload: SynastryConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    let report = calculate_synastry(&payload).map_err(AppError::from_tool)?;
+    let run = ToolRun {
+        tool_type: "synastry",
+        profile_id: None,
+        summary: format!("{} aspects, harmony {:.0}%", report.aspects.len(), report.harmony_score),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(report).unwrap(), run))
+}
+
+async fn handle_synastry(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<SynastryConfig>,
+) -> AppResult<Response> {
+    let (report, run) = run_synastry(payload)?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &report).await;
+    Ok(archive::respond(report, history_id))
+}
+
+fn run_vedic(payload: VedicConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    let chart = calculate_vedic_chart(&payload).map_err(AppError::from_tool)?;
+    let run = ToolRun {
+        tool_type: "vedic",
+        profile_id: None,
+        summary: format!("{} Lagna, Moon in {}, {} dasha", chart.lagna.rashi, chart.janma_nakshatra.name, chart.dashas[0].lord),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(chart).unwrap(), run))
+}
+
+async fn handle_vedic(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<VedicConfig>,
+) -> AppResult<Response> {
+    let (chart, run) = run_vedic(payload)?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &chart).await;
+    Ok(archive::respond(chart, history_id))
+}
+
+/// A stored batch's pulses end to end; an empty batch is a bad request.
+async fn load_batch_entropy(state: &AppState, batch_id: i64) -> AppResult<Vec<u8>> {
+    let entropy: Vec<u8> = state.db.get_batch_entropy(batch_id).await?
+        .into_iter().flat_map(|row| row.pulse).collect();
+    if entropy.is_empty() {
+        return Err(AppError::bad_request(format!("Entropy batch {} has no pulses yet", batch_id)));
     }
+    Ok(entropy)
 }
 
-async fn handle_divination() -> Json<serde_json::Value> {
-    let mut client = CurbyClient::new();
-    // Fetch entropy
-    if let Ok(entropy) = client.fetch_bulk_randomness(1024).await {
-        let session = SimulationSession::new(entropy);
-        match DivinationTool::cast_hexagram(&session) {
-            Ok(hex) => Json(serde_json::to_value(hex).unwrap()),
-            Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+/// Checks the batch, casts from it (or a fresh beacon fetch) and enforces strict mode; shared
+/// by the endpoint and scheduled runs.
+async fn run_divination(state: &AppState, user: &AuthUser, payload: DivinationConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    check_ownership(state, user, None, payload.entropy_batch_id).await?;
+    let entropy = match payload.entropy_batch_id {
+        Some(batch_id) => load_batch_entropy(state, batch_id).await?,
+        None => {
+            charge_entropy(state, user, None, CAST_ENTROPY_BYTES as i64).await?;
+            CurbyClient::new().fetch_bulk_randomness(CAST_ENTROPY_BYTES).await.map_err(AppError::beacon)?
         }
-    } else {
-        Json(serde_json::json!({ "error": "Failed to fetch entropy" }))
+    };
+    // Tie the cast to the question asked
+    let question = payload.question.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let (entropy, question_digest) = bind_question(entropy, question);
+    let mut session = SimulationSession::new(entropy);
+    let mut hex = DivinationTool::cast_hexagram(&mut session, payload.method)?;
+    if payload.strict_quantum && session.fallback_draws > 0 {
+        return Err(AppError::bad_request(format!(
+            "Entropy ran out after {} bytes; strict quantum mode does not fall back to the PRNG",
+            session.entropy_pool.len()
+        )));
     }
+    hex.question = question.map(str::to_string);
+    hex.question_digest = question_digest;
+    let run = ToolRun {
+        tool_type: "divination",
+        profile_id: None,
+        summary: match &hex.question {
+            Some(q) => format!("Hexagram {}: {} (asked: {})", hex.number, hex.name, q),
+            None => format!("Hexagram {}: {}", hex.number, hex.name),
+        },
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: payload.entropy_batch_id,
+    };
+    Ok((serde_json::to_value(hex).unwrap(), run))
+}
+
+async fn handle_divination(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    payload: Option<ValidJson<DivinationConfig>>,
+) -> AppResult<Response> {
+    let payload = payload.map(|ValidJson(p)| p).unwrap_or_default();
+    let (hex, run) = run_divination(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &hex).await;
+    Ok(archive::respond(hex, history_id))
+}
+
+async fn run_liuyao(state: &AppState, user: &AuthUser, payload: LiuYaoConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    charge_entropy(state, user, None, CAST_ENTROPY_BYTES as i64).await?;
+    let entropy = CurbyClient::new().fetch_bulk_randomness(CAST_ENTROPY_BYTES).await.map_err(AppError::beacon)?;
+    let mut session = SimulationSession::new(entropy);
+    let chart = cast_liu_yao(&mut session, &payload);
+    let run = ToolRun {
+        tool_type: "liuyao",
+        profile_id: None,
+        summary: match &chart.changed_name {
+            Some(changed) => format!("{} → {} ({} palace)", chart.name, changed, chart.palace),
+            None => format!("{} ({} palace)", chart.name, chart.palace),
+        },
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(chart).unwrap(), run))
+}
+
+async fn handle_liuyao(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<LiuYaoConfig>,
+) -> AppResult<Response> {
+    let (chart, run) = run_liuyao(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &chart).await;
+    Ok(archive::respond(chart, history_id))
+}
+
+async fn run_tarot(state: &AppState, user: &AuthUser, payload: TarotConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    charge_entropy(state, user, None, CAST_ENTROPY_BYTES as i64).await?;
+    let entropy = CurbyClient::new().fetch_bulk_randomness(CAST_ENTROPY_BYTES).await.map_err(AppError::beacon)?;
+    let mut session = SimulationSession::new(entropy);
+    let reading = draw_spread(&mut session, &payload);
+    let run = ToolRun {
+        tool_type: "tarot",
+        profile_id: None,
+        summary: reading.cards.iter()
+            .map(|c| if c.reversed { format!("{} (reversed)", c.name) } else { c.name.clone() })
+            .collect::<Vec<_>>()
+            .join(", "),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(reading).unwrap(), run))
+}
+
+async fn handle_tarot(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    payload: Option<ValidJson<TarotConfig>>,
+) -> AppResult<Response> {
+    let payload = payload.map(|ValidJson(p)| p).unwrap_or_default();
+    let (reading, run) = run_tarot(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &reading).await;
+    Ok(archive::respond(reading, history_id))
+}
+
+async fn run_runes(state: &AppState, user: &AuthUser, payload: RuneConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    charge_entropy(state, user, None, RUNES_ENTROPY_BYTES as i64).await?;
+    let entropy = CurbyClient::new().fetch_bulk_randomness(RUNES_ENTROPY_BYTES).await.map_err(AppError::beacon)?;
+    let mut session = SimulationSession::new(entropy);
+    let reading = cast_runes(&mut session, &payload);
+    let run = ToolRun {
+        tool_type: "runes",
+        profile_id: None,
+        summary: reading.runes.iter()
+            .map(|r| if r.reversed { format!("{} (merkstave)", r.name) } else { r.name.clone() })
+            .collect::<Vec<_>>()
+            .join(", "),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(reading).unwrap(), run))
+}
+
+async fn handle_runes(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    payload: Option<ValidJson<RuneConfig>>,
+) -> AppResult<Response> {
+    let payload = payload.map(|ValidJson(p)| p).unwrap_or_default();
+    let (reading, run) = run_runes(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &reading).await;
+    Ok(archive::respond(reading, history_id))
+}
+
+/// Fills the name and birth date from the profile where the request leaves them out.
+async fn run_numerology(state: &AppState, user: &AuthUser, payload: NumerologyConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    // 1. Resolve the inputs
+    check_ownership(state, user, payload.profile_id, None).await?;
+    let profile = match payload.profile_id {
+        Some(pid) => Some(state.db.get_profile(pid).await?),
+        None => None,
+    };
+    let name = match (&payload.name, &profile) {
+        (Some(name), _) => name.clone(),
+        (None, Some(p)) => p.name.clone(),
+        (None, None) => return Err(AppError::bad_request("Provide a name or a profile_id")),
+    };
+    let birth_date = match (payload.birth_date, &profile) {
+        (Some(date), _) => date,
+        (None, Some(p)) => match (p.birth_year, p.birth_month, p.birth_day) {
+            (Some(y), Some(m), Some(d)) => chrono::NaiveDate::from_ymd_opt(y as i32, m as u32, d as u32)
+                .ok_or_else(|| AppError::bad_request("Profile has an invalid birth date"))?,
+            _ => return Err(AppError::bad_request("Profile has no birth date")),
+        },
+        (None, None) => return Err(AppError::bad_request("Provide a birth_date or a profile_id")),
+    };
+    let target_date = payload.target_date.unwrap_or_else(|| chrono::Local::now().date_naive());
+
+    // 2. Calculate, with the resonance simulation if asked for
+    let mut session = if payload.resonance {
+        charge_entropy(state, user, payload.profile_id, NUMEROLOGY_ENTROPY_BYTES as i64).await?;
+        let entropy = CurbyClient::new().fetch_bulk_randomness(NUMEROLOGY_ENTROPY_BYTES).await.map_err(AppError::beacon)?;
+        Some(SimulationSession::new(entropy))
+    } else {
+        None
+    };
+    let report = calculate_numerology(&name, birth_date, target_date, session.as_mut()).map_err(AppError::from_tool)?;
+    let run = ToolRun {
+        tool_type: "numerology",
+        profile_id: payload.profile_id,
+        summary: format!("Life Path {}, Expression {}, Personal Year {}", report.life_path.value, report.expression.value, report.personal_year.value),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((serde_json::to_value(report).unwrap(), run))
+}
+
+async fn handle_numerology(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<NumerologyConfig>,
+) -> AppResult<Response> {
+    let (report, run) = run_numerology(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &report).await;
+    Ok(archive::respond(report, history_id))
+}
+
+/// Entropy Stream runs seed from the batch or a beacon fetch, unless replaying a seed.
+async fn run_entanglement(state: &AppState, user: &AuthUser, payload: EntanglementRequest) -> AppResult<(serde_json::Value, ToolRun)> {
+    check_ownership(state, user, None, payload.entropy_batch_id).await?;
+    let mut session = match (&payload.mode, &payload.seed, payload.entropy_batch_id) {
+        (EntanglementMode::EntropyStream, None, Some(batch_id)) => Some(SimulationSession::new(load_batch_entropy(state, batch_id).await?)),
+        (EntanglementMode::EntropyStream, None, None) => {
+            charge_entropy(state, user, None, ENTANGLEMENT_ENTROPY_BYTES as i64).await?;
+            Some(SimulationSession::new(
+                CurbyClient::new().fetch_bulk_randomness(ENTANGLEMENT_ENTROPY_BYTES).await.map_err(AppError::beacon)?,
+            ))
+        }
+        _ => None,
+    };
+    let report = calculate_entanglement(&payload, session.as_mut()).map_err(AppError::from_tool)?;
+    let run = ToolRun {
+        tool_type: "entanglement",
+        profile_id: None,
+        summary: match &report.group {
+            Some(group) => format!("{} group of {}, coherence {:.2}", report.mode, group.members.len(), group.coherence),
+            None => format!("{} resonance {:.2}", report.mode, report.resonance_score),
+        },
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: payload.entropy_batch_id,
+    };
+    Ok((serde_json::to_value(report).unwrap(), run))
 }
 
 async fn handle_entanglement(
-    Json(payload): Json<EntanglementRequest>,
-) -> Json<serde_json::Value> {
-    match calculate_entanglement(&payload) {
-        Ok(report) => Json(serde_json::to_value(report).unwrap()),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
-    }
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<EntanglementRequest>,
+) -> AppResult<Response> {
+    let (report, run) = run_entanglement(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &report).await;
+    Ok(archive::respond(report, history_id))
 }
 
-#[derive(Deserialize)]
+/// Scatters the point cloud from the batch or a beacon fetch sized to the point count, and
+/// answers with GeoJSON ready for a map layer.
+async fn run_geolocation(state: &AppState, user: &AuthUser, payload: GeolocationConfig) -> AppResult<(serde_json::Value, ToolRun)> {
+    check_ownership(state, user, None, payload.entropy_batch_id).await?;
+    let entropy = match payload.entropy_batch_id {
+        Some(batch_id) => load_batch_entropy(state, batch_id).await?,
+        None => {
+            let bytes = payload.points.unwrap_or(geolocation::DEFAULT_POINTS) * geolocation::BYTES_PER_POINT;
+            charge_entropy(state, user, None, bytes as i64).await?;
+            CurbyClient::new().fetch_bulk_randomness(bytes).await.map_err(AppError::beacon)?
+        }
+    };
+    let (entropy, intention) = intention::GEOLOCATION.bind(entropy, payload.intention.as_deref());
+    let mut session = SimulationSession::new(entropy);
+    let mut report = GeolocationTool::generate_location(&mut session, &payload).map_err(AppError::from_tool)?;
+    report.intention = intention;
+    let run = ToolRun {
+        tool_type: "geolocation",
+        profile_id: None,
+        summary: match report.attractors.first() {
+            Some(a) => format!(
+                "Attractor at {:.5}, {:.5} (power {:.2}, {:.0} m {:.0}°)",
+                a.point.latitude, a.point.longitude, a.power, a.distance_m, a.bearing_deg
+            ),
+            None => format!("{} points within {:.0} m", report.points.len(), report.radius_m),
+        },
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: payload.entropy_batch_id,
+    };
+    Ok((geolocation::to_geojson(&report), run))
+}
+
+async fn handle_geolocation(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<GeolocationConfig>,
+) -> AppResult<Response> {
+    let (geojson, run) = run_geolocation(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &geojson).await;
+    Ok(archive::respond(geojson, history_id))
+}
+
+/// Beacon bytes per decision; longer runs continue on the seeded fallback stream.
+const DECISION_ENTROPY_BYTES: usize = 4096;
+
+/// The decision engine, run to completion within the request (flat options or a tree).
+async fn run_decision(state: &AppState, user: &AuthUser, payload: DecisionInput) -> AppResult<(serde_json::Value, ToolRun)> {
+    // The profile is archived with the run even when a batch means nothing is charged to it
+    check_ownership(state, user, payload.profile_id, payload.entropy_batch_id).await?;
+    let entropy = match payload.entropy_batch_id {
+        Some(batch_id) => load_batch_entropy(state, batch_id).await?,
+        None => {
+            charge_entropy(state, user, payload.profile_id, DECISION_ENTROPY_BYTES as i64).await?;
+            CurbyClient::new().fetch_bulk_randomness(DECISION_ENTROPY_BYTES).await.map_err(AppError::beacon)?
+        }
+    };
+    // The question asked is the decision's intention
+    let (entropy, intention) = intention::DECISION.bind(entropy, payload.question.as_deref());
+    let mut session = SimulationSession::new(entropy);
+    let report = DecisionTool::decide(&mut session, &payload).map_err(AppError::from_tool)?;
+    let run = ToolRun {
+        tool_type: "decision",
+        profile_id: payload.profile_id,
+        summary: match payload.question.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            Some(q) => format!("{}: {}", q, report.winner()),
+            None => format!("Decision: {}", report.winner()),
+        },
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: payload.entropy_batch_id,
+    };
+    // Every mode's report carries the binding next to its entropy ranges
+    let mut report = serde_json::to_value(report).unwrap();
+    report["intention"] = serde_json::to_value(intention).unwrap();
+    Ok((report, run))
+}
+
+async fn handle_decision(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<DecisionInput>,
+) -> AppResult<Response> {
+    let (report, run) = run_decision(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &report).await;
+    Ok(archive::respond(report, history_id))
+}
+
+/// Structural diagnostics for a decision tree, without simulating it.
+async fn check_decision_tree(ValidJson(tree): ValidJson<DecisionTree>) -> Json<serde_json::Value> {
+    let diagnostics = decision::check_tree(&tree);
+    let valid = diagnostics.iter().all(|d| d.severity != decision::Severity::Error);
+    Json(serde_json::json!({ "valid": valid, "diagnostics": diagnostics }))
+}
+
+#[derive(Serialize, Deserialize)]
 struct ManyWorldsRequest {
+    profile_id: Option<i64>,
     birth_year: Option<i32>,
     duration: Option<usize>,
     num_worlds: Option<usize>,
 }
 
-async fn handle_many_worlds(
-    Json(payload): Json<ManyWorldsRequest>,
-) -> Json<serde_json::Value> {
+async fn run_many_worlds(state: &AppState, user: &AuthUser, payload: ManyWorldsRequest) -> AppResult<(serde_json::Value, ToolRun)> {
+    charge_entropy(state, user, payload.profile_id, MANY_WORLDS_ENTROPY_BYTES as i64).await?;
     let mut client = CurbyClient::new();
     // We need a lot of entropy for many worlds!
-    if let Ok(entropy) = client.fetch_bulk_randomness(2048).await {
-        let mut session = SimulationSession::new(entropy);
-        let mut sim = TimelineSimulator::new(&mut session);
-
-        // Simple initialization of elements based on birth year modulo
-        // In a real app, we'd use full BaZi
-        let birth_year = payload.birth_year.unwrap_or(1990);
-        let element_idx = (birth_year % 10) / 2;
-        let base_element = match element_idx {
-            0 => "Metal",
-            1 => "Water",
-            2 => "Wood",
-            3 => "Fire",
-            _ => "Earth",
-        };
+    let entropy = client.fetch_bulk_randomness(MANY_WORLDS_ENTROPY_BYTES).await.map_err(AppError::beacon)?;
+    let mut session = SimulationSession::new(entropy);
+    let mut sim = TimelineSimulator::new(&mut session);
+
+    // Simple initialization of elements based on birth year modulo
+    // In a real app, we'd use full BaZi
+    let birth_year = payload.birth_year.unwrap_or(1990);
+    let element_idx = (birth_year % 10) / 2;
+    let base_element = match element_idx {
+        0 => "Metal",
+        1 => "Water",
+        2 => "Wood",
+        3 => "Fire",
+        _ => "Earth",
+    };
+
+    let mut start_elements = HashMap::new();
+    start_elements.insert("Wood".to_string(), 20.0);
+    start_elements.insert("Fire".to_string(), 20.0);
+    start_elements.insert("Earth".to_string(), 20.0);
+    start_elements.insert("Metal".to_string(), 20.0);
+    start_elements.insert("Water".to_string(), 20.0);
+
+    if let Some(v) = start_elements.get_mut(base_element) {
+        *v += 30.0; // Boost birth element
+    }
+
+    let duration = payload.duration.unwrap_or(10);
+    let num_worlds = payload.num_worlds.unwrap_or(100);
+
+    let result = serde_json::to_value(sim.simulate(start_elements, duration, num_worlds)).unwrap();
+    let run = ToolRun {
+        tool_type: "many_worlds",
+        profile_id: payload.profile_id,
+        summary: format!("{} worlds over {} years", num_worlds, duration),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
+    Ok((result, run))
+}
+
+async fn handle_many_worlds(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<ManyWorldsRequest>,
+) -> AppResult<Response> {
+    let (result, run) = run_many_worlds(&state, &user, payload).await?;
+    let history_id = archive::archive_run(&state, &user, &archive, run, &result).await;
+    Ok(archive::respond(result, history_id))
+}
 
-        let mut start_elements = HashMap::new();
-        start_elements.insert("Wood".to_string(), 20.0);
-        start_elements.insert("Fire".to_string(), 20.0);
-        start_elements.insert("Earth".to_string(), 20.0);
-        start_elements.insert("Metal".to_string(), 20.0);
-        start_elements.insert("Water".to_string(), 20.0);
+/// Bytes drawn per timeline run.
+const TIMELINE_ENTROPY_BYTES: usize = 2048;
 
-        if let Some(v) = start_elements.get_mut(base_element) {
-            *v += 30.0; // Boost birth element
+#[derive(Serialize, Deserialize)]
+struct TimelineRequest {
+    /// Seeds the run from this profile's BaZi chart (and meters the entropy to it).
+    profile_id: Option<i64>,
+    /// Explicit starting balance; overrides the profile's natal elements if both are given.
+    start_elements: Option<HashMap<String, f64>>,
+    duration: Option<usize>,
+    num_worlds: Option<usize>,
+    params: Option<WuXingParams>,
+    /// Hex seed from a previous result's `seed`, to reproduce that run.
+    seed: Option<String>,
+}
+
+/// Runs `TimelineSimulator` and returns a `ManyWorldsResult`.
+async fn run_timeline(state: &AppState, user: &AuthUser, payload: TimelineRequest) -> AppResult<(serde_json::Value, ToolRun)> {
+    // 1. Resolve the natal chart, if a profile was given
+    check_ownership(state, user, payload.profile_id, None).await?;
+    let bazi = match payload.profile_id {
+        Some(pid) => {
+            let profile = state.db.get_profile(pid).await?;
+            let (Some(y), Some(m), Some(d)) = (profile.birth_year, profile.birth_month, profile.birth_day) else {
+                return Err(AppError::bad_request("Profile has no birth date"));
+            };
+            let hour = profile.birth_hour.unwrap_or(12) as u32;
+            let chart = calculate_bazi(y as i32, m as u32, d as u32, hour, profile.gender.as_deref(), None, None).map_err(AppError::bad_request)?;
+            use chrono::Datelike;
+            let age = (chrono::Local::now().year() as i64 - y).max(0) as usize;
+            Some((chart, age))
         }
+        None => None,
+    };
+    if bazi.is_none() && payload.start_elements.is_none() {
+        return Err(AppError::bad_request("Provide start_elements or a profile_id"));
+    }
+    let seed = match payload.seed.as_deref().map(hex::decode) {
+        None => None,
+        Some(Ok(bytes)) if bytes.len() == 32 => {
+            let mut seed = [0u8; 32];
+            seed.copy_from_slice(&bytes);
+            Some(seed)
+        }
+        Some(_) => return Err(AppError::bad_request("seed must be 64 hex characters")),
+    };
 
-        let duration = payload.duration.unwrap_or(10);
-        let num_worlds = payload.num_worlds.unwrap_or(100);
+    charge_entropy(state, user, payload.profile_id, TIMELINE_ENTROPY_BYTES as i64).await?;
+    let (duration, num_worlds) = (payload.duration.unwrap_or(10), payload.num_worlds.unwrap_or(100));
+    let run = ToolRun {
+        tool_type: "timeline",
+        profile_id: payload.profile_id,
+        summary: format!("{} worlds over {} years", num_worlds, duration),
+        config: serde_json::to_value(&payload).unwrap(),
+        entropy_batch_id: None,
+    };
 
-        let result = sim.simulate(start_elements, duration, num_worlds);
-        Json(serde_json::to_value(result).unwrap())
-    } else {
-        Json(serde_json::json!({ "error": "Failed to fetch entropy for simulation" }))
+    // 2. Run the simulation
+    let mut client = CurbyClient::new();
+    let entropy = client.fetch_bulk_randomness(TIMELINE_ENTROPY_BYTES).await.map_err(AppError::beacon)?;
+    let mut session = SimulationSession::new(entropy);
+    let mut sim = match &bazi {
+        Some((chart, age)) => TimelineSimulator::from_bazi(&mut session, chart, *age),
+        None => TimelineSimulator::new(&mut session),
+    };
+    if let Some(params) = payload.params {
+        sim = sim.with_params(params);
+    }
+    if let Some(seed) = seed {
+        sim = sim.with_seed(seed);
+    }
+    let start_elements = payload.start_elements
+        .or_else(|| sim.natal_start_elements())
+        .unwrap_or_default();
+
+    let result = serde_json::to_value(sim.simulate(start_elements, duration, num_worlds)).unwrap();
+    Ok((result, run))
+}
+
+async fn handle_timeline(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(archive): Query<ArchiveParams>,
+    ValidJson(payload): ValidJson<TimelineRequest>,
+) -> AppResult<Response> {
+    let (result, run) = run_timeline(&state, &user, payload).await?;
+    // Archived with its seed, so the run can be reproduced from history
+    let history_id = archive::archive_run(&state, &user, &archive, run, &result).await;
+    Ok(archive::respond(result, history_id))
+}
+
+// === SIMULATION JOBS ===
+
+/// Bytes fetched per background simulation; longer runs continue on the seeded fallback stream.
+const SIMULATE_ENTROPY_BYTES: usize = 4096;
+
+#[derive(Serialize, Deserialize)]
+struct SimulateRequest {
+    profile_id: Option<i64>,
+    options: Vec<String>,
+    weights: Option<Vec<f64>>,
+    simulations: Option<usize>,
+    simulation_options: Option<SimulationOptions>,
+}
+
+/// Starts a decision simulation in the background and returns its job id.
+///
+/// Progress is streamed from `GET /api/simulate/{job_id}/progress`.
+async fn start_simulation(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ValidJson(payload): ValidJson<SimulateRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    charge_entropy(&state, &user, payload.profile_id, SIMULATE_ENTROPY_BYTES as i64).await?;
+
+    let job_id = jobs::create_job(Some(user.id), serde_json::to_value(&payload).unwrap());
+    let tx = state.events.clone();
+    // Detached from the request, so the job gets its own span (linked by job_id)
+    let span = tracing::info_span!("simulation_job", job_id, user_id = user.id);
+    tokio::spawn(async move {
+        let fail = |error: String| {
+            tracing::warn!(%error, "Simulation job failed");
+            events::emit(&tx, ServerEvent::JobFailed { job_id, owner_id: user.id, error: error.clone() });
+            jobs::publish(job_id, JobEvent::Failed(error));
+        };
+        let mut client = CurbyClient::new();
+        let entropy = match client.fetch_bulk_randomness(SIMULATE_ENTROPY_BYTES).await {
+            Ok(e) => e,
+            Err(e) => {
+                fail(e.to_string());
+                return;
+            }
+        };
+        let simulations = payload.simulations.unwrap_or(10_000);
+        let sim_options = payload.simulation_options.unwrap_or_default();
+        let step_tx = tx.clone();
+        let job_span = tracing::Span::current();
+        let result = tokio::task::spawn_blocking(move || {
+            let _guard = job_span.enter();
+            let mut session = SimulationSession::new(entropy);
+            let mut publish_step = jobs::step_publisher(job_id);
+            session.simulate_decision_with_progress(
+                &payload.options,
+                payload.weights.as_deref(),
+                simulations,
+                &sim_options,
+                |step| {
+                    publish_step(step);
+                    events::emit(&step_tx, ServerEvent::JobProgress { job_id, owner_id: user.id, step: step.clone() });
+                },
+            )
+        }).await;
+        match result {
+            Ok(report) => {
+                for stats in report.option_stats.iter().filter(|s| s.z_score.abs() > events::ANOMALY_ALERT_Z) {
+                    events::emit(&tx, ServerEvent::AnomalyDetected {
+                        job_id,
+                        owner_id: user.id,
+                        option: stats.option.clone(),
+                        z_score: stats.z_score,
+                    });
+                }
+                events::emit(&tx, ServerEvent::JobDone { job_id, owner_id: user.id, report: report.clone() });
+                jobs::publish(job_id, JobEvent::Done(report));
+            }
+            Err(e) => fail(e.to_string()),
+        }
+    }.instrument(span));
+
+    Ok(Json(serde_json::json!({ "job_id": job_id })))
+}
+
+/// Server-Sent Events stream of a simulation job: `step` events carrying `TimeStep`s,
+/// then a single `done` (final `SimulationReport`) or `error` event.
+async fn simulation_progress(
+    Extension(user): Extension<AuthUser>,
+    Path(job_id): Path<u64>,
+) -> AppResult<Response> {
+    let (backlog, rx) = jobs::subscribe(job_id, &user).ok_or_else(|| AppError::not_found("Unknown job"))?;
+
+    let live = BroadcastStream::new(rx).filter_map(|r| r.ok());
+    let stream = tokio_stream::iter(backlog)
+        .chain(live)
+        .map(|ev| match &ev {
+            JobEvent::Step(step) => Event::default().event(ev.name()).json_data(step),
+            JobEvent::Done(report) => Event::default().event(ev.name()).json_data(report),
+            JobEvent::Failed(msg) => Ok(Event::default().event(ev.name()).data(msg)),
+        });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()).into_response())
+}
+
+/// Simulation jobs of the caller that were cut off by a server shutdown.
+async fn list_interrupted_jobs(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<serde_json::Value>> {
+    let rows = state.db.list_interrupted_jobs(user.id).await?;
+    Ok(Json(serde_json::json!(rows)))
+}
+
+async fn delete_interrupted_job(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !state.db.delete_interrupted_job(id, user.id).await? {
+        return Err(AppError::not_found(format!("Interrupted job {} not found", id)));
+    }
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
+
+// === PUSH CHANNEL ===
+
+/// WebSocket feed of `ServerEvent`s (harvester, jobs, history) as JSON text frames.
+///
+/// Every event belongs to one user, and each socket only gets its own user's events.
+async fn ws_handler(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let rx = state.events.subscribe();
+    let shutdown = state.shutdown.clone();
+    ws.on_upgrade(move |socket| ws_forward(socket, rx, user.id, shutdown))
+}
+
+async fn ws_forward(
+    mut socket: WebSocket,
+    mut rx: tokio::sync::broadcast::Receiver<ServerEvent>,
+    user_id: i64,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    use tokio::sync::broadcast::error::RecvError;
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+            event = rx.recv() => {
+                let text = match event {
+                    Ok(ev) if ev.owner_id() != user_id => continue,
+                    Ok(ev) => serde_json::to_string(&ev).unwrap(),
+                    // Slow client: tell it how much it missed and carry on
+                    Err(RecvError::Lagged(n)) => serde_json::json!({ "type": "lagged", "missed": n }).to_string(),
+                    Err(RecvError::Closed) => break,
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => {
+                // Clients only listen; stop on close or error
+                match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                }
+            }
+        }
     }
 }
 
@@ -256,57 +1333,62 @@ struct StartHarvestInput {
 
 async fn list_entropy_batches(
     Extension(state): Extension<AppState>,
-) -> Json<serde_json::Value> {
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<serde_json::Value>> {
     // We should also get the size for each batch
-    match state.db.list_batches().await {
-        Ok(batches) => {
-            // Enrich with size
-            let mut result = Vec::new();
-            for b in batches {
-                let size = state.db.get_batch_size(b.id).await.unwrap_or(0);
-                result.push(serde_json::json!({
-                    "id": b.id,
-                    "name": b.name,
-                    "status": b.status,
-                    "created_at": b.created_at,
-                    "count": size,
-                    // Each pulse is 512 bits = 64 bytes
-                    "size_bytes": size * 64
-                }));
-            }
-            Json(serde_json::json!(result))
-        },
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+    let batches = state.db.list_batches(user.id).await?;
+    // Enrich with size
+    let mut result = Vec::new();
+    for b in batches {
+        let size = state.db.get_batch_size(b.id).await.unwrap_or(0);
+        result.push(serde_json::json!({
+            "id": b.id,
+            "name": b.name,
+            "status": b.status,
+            "created_at": b.created_at,
+            "count": size,
+            // Each pulse is 512 bits = 64 bytes
+            "size_bytes": size * 64
+        }));
     }
+    Ok(Json(serde_json::json!(result)))
 }
 
 async fn create_entropy_batch(
     Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
     Json(input): Json<CreateBatchInput>,
-) -> Json<serde_json::Value> {
-    match state.db.create_batch(&input.name).await {
-        Ok(id) => Json(serde_json::json!({ "id": id })),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
-    }
+) -> AppResult<Json<serde_json::Value>> {
+    let id = state.db.create_batch(&input.name, Some(user.id)).await?;
+    Ok(Json(serde_json::json!({ "id": id })))
 }
 
 async fn start_harvest(
     Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
     Json(input): Json<StartHarvestInput>,
-) -> Json<serde_json::Value> {
-    entropy::start_harvesting(state.db.clone(), input.batch_id).await;
-    Json(serde_json::json!({ "status": "started" }))
+) -> AppResult<Json<serde_json::Value>> {
+    check_ownership(&state, &user, None, Some(input.batch_id)).await?;
+    if !entropy::start_harvesting(state.db.clone(), input.batch_id, user.id, state.events.clone()).await {
+        return Err(AppError::Conflict("A harvest is already running".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "status": "started" })))
 }
 
+/// Stops the caller's harvest; admins can stop anyone's.
 async fn stop_harvest(
     Extension(state): Extension<AppState>,
-) -> Json<serde_json::Value> {
-    entropy::stop_harvesting(state.db.clone()).await;
-    Json(serde_json::json!({ "status": "stopped" }))
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !entropy::stop_harvesting(state.db.clone(), &user, &state.events).await {
+        return Err(AppError::not_found("No harvest of yours is running"));
+    }
+    Ok(Json(serde_json::json!({ "status": "stopped" })))
 }
 
-async fn harvest_status() -> Json<serde_json::Value> {
-    let batch_id = entropy::get_harvest_status().await;
+/// The caller's running harvest, if any; another user's batch is not shown.
+async fn harvest_status(Extension(user): Extension<AuthUser>) -> Json<serde_json::Value> {
+    let batch_id = entropy::get_harvest_status(user.id).await;
     Json(serde_json::json!({ "active_batch_id": batch_id }))
 }
 
@@ -320,54 +1402,161 @@ struct ProfileInput {
     birth_day: i32,
     birth_hour: i32,
     gender: String,
+    entropy_quota_bytes: Option<i64>,
 }
 
-#[derive(sqlx::FromRow, Serialize)]
-struct ProfileRow {
-    id: i64,
-    name: String,
-    birth_year: Option<i64>,
-    birth_month: Option<i64>,
-    birth_day: Option<i64>,
-    birth_hour: Option<i64>,
-    gender: Option<String>,
+impl ProfileInput {
+    fn fields(self) -> ProfileFields {
+        ProfileFields {
+            name: self.name,
+            birth_year: self.birth_year.into(),
+            birth_month: self.birth_month.into(),
+            birth_day: self.birth_day.into(),
+            birth_hour: self.birth_hour.into(),
+            gender: self.gender,
+            entropy_quota_bytes: self.entropy_quota_bytes,
+        }
+    }
+}
+
+/// Only admins set a profile's daily entropy quota; others may send back the current value unchanged.
+fn check_quota_change(user: &AuthUser, current: Option<i64>, requested: Option<i64>) -> AppResult<()> {
+    if requested == current || user.is_admin {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden("Only admins can change entropy_quota_bytes".to_string()))
+    }
+}
+
+#[derive(Deserialize)]
+struct DeleteProfileParams {
+    /// `orphan` keeps the profile's history detached, `cascade` deletes it.
+    history: Option<HistoryOnDelete>,
 }
 
 async fn create_profile(
     Extension(state): Extension<AppState>,
-    Json(input): Json<ProfileInput>,
-) -> Json<serde_json::Value> {
-    let res = sqlx::query(
-        "INSERT INTO profiles (name, birth_year, birth_month, birth_day, birth_hour, gender) VALUES (?, ?, ?, ?, ?, ?)"
-    )
-    .bind(input.name)
-    .bind(input.birth_year)
-    .bind(input.birth_month)
-    .bind(input.birth_day)
-    .bind(input.birth_hour)
-    .bind(input.gender)
-    .execute(&state.db.pool)
-    .await;
-
-    match res {
-        Ok(r) => Json(serde_json::json!({ "id": r.last_insert_rowid() })),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
-    }
+    Extension(user): Extension<AuthUser>,
+    ValidJson(input): ValidJson<ProfileInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    check_quota_change(&user, None, input.entropy_quota_bytes)?;
+    let id = state.db.create_profile(user.id, &input.fields()).await?;
+    Ok(Json(serde_json::json!({ "id": id })))
 }
 
 async fn list_profiles(
     Extension(state): Extension<AppState>,
-) -> Json<serde_json::Value> {
-    let res = sqlx::query_as::<_, ProfileRow>("SELECT id, name, birth_year, birth_month, birth_day, birth_hour, gender FROM profiles ORDER BY created_at DESC")
-        .fetch_all(&state.db.pool)
-        .await;
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<Vec<Profile>>> {
+    Ok(Json(state.db.list_profiles(user.id).await?))
+}
 
-    match res {
-        Ok(rows) => {
-             Json(serde_json::json!(rows))
-        },
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+/// Replaces all fields of a profile.
+async fn update_profile(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    ValidJson(input): ValidJson<ProfileInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    check_ownership(&state, &user, Some(id), None).await?;
+    check_quota_change(&user, state.db.get_profile_quota(id).await?, input.entropy_quota_bytes)?;
+    if !state.db.update_profile(id, &input.fields()).await? {
+        return Err(AppError::not_found(format!("Profile {} not found", id)));
     }
+    Ok(Json(serde_json::json!({ "id": id })))
+}
+
+/// Deletes a profile; `?history=cascade|orphan` overrides the server default for its history.
+async fn delete_profile(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    Query(params): Query<DeleteProfileParams>,
+) -> AppResult<Json<serde_json::Value>> {
+    check_ownership(&state, &user, Some(id), None).await?;
+    let rule = params.history.unwrap_or_else(HistoryOnDelete::from_env);
+    let affected = state.db.delete_profile(id, rule).await?
+        .ok_or_else(|| AppError::not_found(format!("Profile {} not found", id)))?;
+    Ok(Json(serde_json::json!({ "deleted": id, "history": rule, "history_rows": affected })))
+}
+
+async fn get_profile_quota(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    check_ownership(&state, &user, Some(id), None).await?;
+    let status = quota::get_quota_status(&state.db, id).await?;
+    Ok(Json(serde_json::to_value(status).unwrap()))
+}
+
+#[derive(Deserialize)]
+struct PresetInput {
+    tool_type: String,
+    name: String,
+    config: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct PresetUpdate {
+    name: String,
+    config: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct PresetFilter {
+    tool: Option<String>,
+}
+
+/// Lists the caller's presets; `?tool=fengshui` narrows to one tool.
+async fn list_presets(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(filter): Query<PresetFilter>,
+) -> AppResult<Json<Vec<Preset>>> {
+    Ok(Json(state.db.list_presets(user.id, filter.tool.as_deref()).await?))
+}
+
+async fn create_preset(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ValidJson(input): ValidJson<PresetInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    let id = state.db.create_preset(user.id, &input.tool_type, input.name.trim(), &input.config).await?;
+    Ok(Json(serde_json::json!({ "id": id })))
+}
+
+async fn get_preset(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<Preset>> {
+    let preset = state.db.get_preset(id, user.id).await?
+        .ok_or_else(|| AppError::not_found(format!("Preset {} not found", id)))?;
+    Ok(Json(preset))
+}
+
+async fn update_preset(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    ValidJson(input): ValidJson<PresetUpdate>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !state.db.update_preset(id, user.id, input.name.trim(), &input.config).await? {
+        return Err(AppError::not_found(format!("Preset {} not found", id)));
+    }
+    Ok(Json(serde_json::json!({ "id": id })))
+}
+
+async fn delete_preset(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !state.db.delete_preset(id, user.id).await? {
+        return Err(AppError::not_found(format!("Preset {} not found", id)));
+    }
+    Ok(Json(serde_json::json!({ "deleted": id })))
 }
 
 #[derive(Serialize, Deserialize)]
@@ -376,53 +1565,64 @@ struct HistoryInput {
     tool_type: String,
     summary: String,
     full_report: serde_json::Value,
+    config: Option<serde_json::Value>,
+    entropy_batch_id: Option<i64>,
+    /// Defaults to the report's own `entropy_ranges`, if it has any.
+    entropy_ranges: Option<Vec<EntropyRange>>,
 }
 
-#[derive(sqlx::FromRow, Serialize)]
-struct HistoryRow {
-    id: i64,
-    tool_type: String,
-    summary: Option<String>,
-    created_at: Option<chrono::NaiveDateTime>, // or String depending on driver
-    profile_name: Option<String>,
-}
+/// Most history entries returned by the list endpoint.
+const HISTORY_LIST_LIMIT: i64 = 50;
 
 async fn save_history(
     Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
     Json(input): Json<HistoryInput>,
-) -> Json<serde_json::Value> {
-    let res = sqlx::query(
-        "INSERT INTO history (profile_id, tool_type, summary, full_report) VALUES (?, ?, ?, ?)"
-    )
-    .bind(input.profile_id)
-    .bind(input.tool_type)
-    .bind(input.summary)
-    .bind(input.full_report)
-    .execute(&state.db.pool)
-    .await;
-
-    match res {
-        Ok(r) => Json(serde_json::json!({ "id": r.last_insert_rowid() })),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
-    }
+) -> AppResult<Json<serde_json::Value>> {
+    check_ownership(&state, &user, input.profile_id, input.entropy_batch_id).await?;
+    // Keep the exact pool bytes behind the result so it can be verified later
+    let ranges = input.entropy_ranges.or_else(|| archive::report_ranges(&input.full_report));
+    let entry = NewHistory {
+        profile_id: input.profile_id,
+        tool_type: input.tool_type,
+        summary: input.summary,
+        full_report: input.full_report,
+        config: input.config,
+        entropy_batch_id: input.entropy_batch_id,
+        entropy_ranges: ranges.filter(|r| !r.is_empty()).map(|r| serde_json::to_string(&r).unwrap()),
+    };
+
+    let id = archive::record(&state, &user, entry).await?;
+    Ok(Json(serde_json::json!({ "id": id })))
 }
 
 async fn list_history(
     Extension(state): Extension<AppState>,
-) -> Json<serde_json::Value> {
-    let res = sqlx::query_as::<_, HistoryRow>(
-        "SELECT h.id, h.tool_type, h.summary, h.created_at, p.name as profile_name
-         FROM history h
-         LEFT JOIN profiles p ON h.profile_id = p.id
-         ORDER BY h.created_at DESC LIMIT 50"
-    )
-    .fetch_all(&state.db.pool)
-    .await;
-
-    match res {
-        Ok(rows) => {
-             Json(serde_json::json!(rows))
-        },
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() })),
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<Vec<HistoryEntry>>> {
+    Ok(Json(state.db.list_history(user.id, HISTORY_LIST_LIMIT).await?))
+}
+
+/// Returns one history entry with its full stored report.
+async fn get_history(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<HistoryDetail>> {
+    let row = state.db.get_history(id, user.id).await?
+        .ok_or_else(|| AppError::not_found(format!("History entry {} not found", id)))?;
+    Ok(Json(row))
+}
+
+async fn delete_history(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !state.db.delete_history(id, user.id).await? {
+        return Err(AppError::not_found(format!("History entry {} not found", id)));
     }
+
+    events::emit(&state.events, ServerEvent::HistoryDeleted { id, owner_id: user.id });
+    Ok(Json(serde_json::json!({ "deleted": id })))
 }
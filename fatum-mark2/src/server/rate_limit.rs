@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::auth::AuthUser;
+use super::error::AppError;
+
+/// Default sustained rate for beacon-backed endpoints, per client.
+const DEFAULT_PER_MINUTE: f64 = 10.0;
+/// Default number of requests a client may make back to back.
+const DEFAULT_BURST: f64 = 5.0;
+/// Above this many tracked clients, full (idle) buckets are dropped.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+lazy_static::lazy_static! {
+    static ref BUCKETS: Mutex<HashMap<String, Bucket>> = Mutex::new(HashMap::new());
+    static ref PER_MINUTE: f64 = env_f64("FATUM_BEACON_RATE_PER_MINUTE", DEFAULT_PER_MINUTE);
+    static ref BURST: f64 = env_f64("FATUM_BEACON_BURST", DEFAULT_BURST).max(1.0);
+}
+
+fn env_f64(name: &str, default: f64) -> f64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|v: &f64| v.is_finite() && *v > 0.0)
+        .unwrap_or(default)
+}
+
+/// Takes one token from `key`'s bucket. On refusal returns the seconds until a token is free.
+fn take_token(key: &str) -> Result<(), u64> {
+    let rate = *PER_MINUTE / 60.0;
+    let burst = *BURST;
+    let now = Instant::now();
+    let mut buckets = BUCKETS.lock().unwrap();
+
+    if buckets.len() > MAX_TRACKED_CLIENTS {
+        buckets.retain(|_, b| b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < burst);
+    }
+
+    let bucket = buckets.entry(key.to_string()).or_insert(Bucket { tokens: burst, updated: now });
+    bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate).min(burst);
+    bucket.updated = now;
+
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        Ok(())
+    } else {
+        Err(((1.0 - bucket.tokens) / rate).ceil() as u64)
+    }
+}
+
+/// Token bucket in front of the endpoints that pull entropy from CURBy.
+///
+/// Clients are keyed by user when authenticated and by IP otherwise. Rate and burst
+/// come from `FATUM_BEACON_RATE_PER_MINUTE` and `FATUM_BEACON_BURST`.
+pub async fn limit_beacon(req: Request, next: Next) -> Response {
+    let key = match req.extensions().get::<AuthUser>() {
+        Some(user) => format!("user:{}", user.id),
+        None => match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+            None => "anonymous".to_string(),
+        },
+    };
+
+    match take_token(&key) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => {
+            let mut resp = AppError::TooManyRequests(format!(
+                "Rate limit exceeded for beacon-backed endpoints; retry in {} s", retry_after
+            )).into_response();
+            resp.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            resp
+        }
+    }
+}
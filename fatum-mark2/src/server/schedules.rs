@@ -0,0 +1,368 @@
+use axum::{extract::Path, Extension, Json};
+use chrono::{Local, NaiveDateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::Instrument;
+
+use super::archive::{self, ToolRun};
+use super::auth::AuthUser;
+use super::error::{AppError, AppResult};
+use super::validation::{self, Validate, ValidJson};
+use super::webhooks::check_destination;
+use super::{AppState, FengShuiApiInput, ManyWorldsRequest, TimelineRequest};
+use crate::db::{Schedule, ScheduleFields};
+use crate::services::cron::CronSchedule;
+use crate::services::events::{self, ServerEvent};
+use crate::services::webhooks::{self, Delivery, DeliveryOutcome, RetryPolicy};
+use crate::tools::da_liu_ren::DaLiuRenConfig;
+use crate::tools::divination::DivinationConfig;
+use crate::tools::liu_yao::LiuYaoConfig;
+use crate::tools::tarot::TarotConfig;
+use crate::tools::astrology_western::NatalConfig;
+use crate::tools::astrology_vedic::VedicConfig;
+use crate::tools::astrology_reports::{SynastryConfig, TransitConfig};
+use crate::tools::runes::RuneConfig;
+use crate::tools::numerology::NumerologyConfig;
+use crate::tools::qimen_directions::QiMenConfig;
+use crate::tools::entanglement::EntanglementRequest;
+use crate::tools::geolocation::GeolocationConfig;
+use crate::tools::decision::DecisionInput;
+use crate::tools::ze_ri::DateSelectionConfig;
+use crate::tools::zi_wei::ZiWeiConfig;
+
+/// How often the runner looks for due schedules.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Tools a schedule can run.
+pub const SCHEDULABLE_TOOLS: &[&str] = &["fengshui", "zeri", "ziwei", "daliuren", "qimen", "natal", "transits", "synastry", "vedic", "divination", "liuyao", "tarot", "runes", "numerology", "entanglement", "geolocation", "decision", "many_worlds", "timeline"];
+
+#[derive(Deserialize)]
+pub struct ScheduleInput {
+    pub name: String,
+    pub tool_type: String,
+    /// The tool's request body, as it would be POSTed to `/tools/{tool_type}`.
+    pub config: serde_json::Value,
+    /// `minute hour day month weekday` in server local time, e.g. `0 6 * * *`.
+    pub cron: String,
+    pub webhook_url: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+impl ScheduleInput {
+    fn fields(&self) -> ScheduleFields {
+        ScheduleFields {
+            name: self.name.trim().to_string(),
+            tool_type: self.tool_type.clone(),
+            config: self.config.clone(),
+            cron: self.cron.trim().to_string(),
+            webhook_url: self.webhook_url.clone().filter(|u| !u.trim().is_empty()),
+            enabled: self.enabled.unwrap_or(true),
+        }
+    }
+}
+
+/// A schedule's config, parsed and validated as the tool's own request type.
+enum ToolRequest {
+    FengShui(FengShuiApiInput),
+    ZeRi(DateSelectionConfig),
+    ZiWei(ZiWeiConfig),
+    DaLiuRen(DaLiuRenConfig),
+    QiMen(QiMenConfig),
+    Natal(NatalConfig),
+    Transits(TransitConfig),
+    Synastry(SynastryConfig),
+    Vedic(VedicConfig),
+    Divination(DivinationConfig),
+    LiuYao(LiuYaoConfig),
+    Tarot(TarotConfig),
+    Runes(RuneConfig),
+    Numerology(NumerologyConfig),
+    Entanglement(EntanglementRequest),
+    Geolocation(GeolocationConfig),
+    Decision(DecisionInput),
+    ManyWorlds(ManyWorldsRequest),
+    Timeline(TimelineRequest),
+}
+
+fn parse_config<T: DeserializeOwned + Validate>(config: &serde_json::Value) -> AppResult<T> {
+    let payload: T = serde_json::from_value(config.clone())
+        .map_err(|e| AppError::bad_request(format!("Invalid config: {}", e)))?;
+    validation::validate(&payload)?;
+    Ok(payload)
+}
+
+impl ToolRequest {
+    fn parse(tool_type: &str, config: &serde_json::Value) -> AppResult<Self> {
+        Ok(match tool_type {
+            "fengshui" => ToolRequest::FengShui(parse_config(config)?),
+            "zeri" => ToolRequest::ZeRi(parse_config(config)?),
+            "ziwei" => ToolRequest::ZiWei(parse_config(config)?),
+            "daliuren" => ToolRequest::DaLiuRen(parse_config(config)?),
+            "qimen" => ToolRequest::QiMen(parse_config(config)?),
+            "natal" => ToolRequest::Natal(parse_config(config)?),
+            "transits" => ToolRequest::Transits(parse_config(config)?),
+            "synastry" => ToolRequest::Synastry(parse_config(config)?),
+            "vedic" => ToolRequest::Vedic(parse_config(config)?),
+            // Casting once took no body, so older schedules may store `null`
+            "divination" if config.is_null() => ToolRequest::Divination(DivinationConfig::default()),
+            "divination" => ToolRequest::Divination(parse_config(config)?),
+            "liuyao" => ToolRequest::LiuYao(parse_config(config)?),
+            "tarot" => ToolRequest::Tarot(parse_config(config)?),
+            "runes" => ToolRequest::Runes(parse_config(config)?),
+            "numerology" => ToolRequest::Numerology(parse_config(config)?),
+            "entanglement" => ToolRequest::Entanglement(parse_config(config)?),
+            "geolocation" => ToolRequest::Geolocation(parse_config(config)?),
+            "decision" => ToolRequest::Decision(parse_config(config)?),
+            "many_worlds" => ToolRequest::ManyWorlds(parse_config(config)?),
+            "timeline" => ToolRequest::Timeline(parse_config(config)?),
+            other => return Err(AppError::bad_request(format!("Tool {:?} cannot be scheduled", other))),
+        })
+    }
+
+    /// Runs the tool as `user`, with the same access checks and metering as its endpoint.
+    async fn run(self, state: &AppState, user: &AuthUser) -> AppResult<(serde_json::Value, ToolRun)> {
+        match self {
+            ToolRequest::FengShui(payload) => {
+                let (report, run) = super::run_fengshui(state, user, payload).await?;
+                Ok((serde_json::to_value(report).unwrap(), run))
+            }
+            ToolRequest::ZeRi(payload) => super::run_zeri(state, user, payload).await,
+            ToolRequest::ZiWei(payload) => super::run_ziwei(state, user, payload).await,
+            ToolRequest::DaLiuRen(payload) => super::run_daliuren(payload),
+            ToolRequest::QiMen(payload) => super::run_qimen(payload),
+            ToolRequest::Natal(payload) => super::run_natal(payload),
+            ToolRequest::Transits(payload) => super::run_transits(payload),
+            ToolRequest::Synastry(payload) => super::run_synastry(payload),
+            ToolRequest::Vedic(payload) => super::run_vedic(payload),
+            ToolRequest::Divination(payload) => super::run_divination(state, user, payload).await,
+            ToolRequest::LiuYao(payload) => super::run_liuyao(state, user, payload).await,
+            ToolRequest::Tarot(payload) => super::run_tarot(state, user, payload).await,
+            ToolRequest::Runes(payload) => super::run_runes(state, user, payload).await,
+            ToolRequest::Numerology(payload) => super::run_numerology(state, user, payload).await,
+            ToolRequest::Entanglement(payload) => super::run_entanglement(state, user, payload).await,
+            ToolRequest::Geolocation(payload) => super::run_geolocation(state, user, payload).await,
+            ToolRequest::Decision(payload) => super::run_decision(state, user, payload).await,
+            ToolRequest::ManyWorlds(payload) => super::run_many_worlds(state, user, payload).await,
+            ToolRequest::Timeline(payload) => super::run_timeline(state, user, payload).await,
+        }
+    }
+}
+
+/// The next time a schedule fires after now, as stored (UTC). `None` when disabled.
+fn next_run(fields: &ScheduleFields) -> Option<NaiveDateTime> {
+    if !fields.enabled {
+        return None;
+    }
+    let cron = CronSchedule::parse(&fields.cron).ok()?;
+    cron.next_after(&Local::now()).map(|t| t.with_timezone(&Utc).naive_utc())
+}
+
+// === CRUD ===
+
+pub async fn list_schedules(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<Vec<Schedule>>> {
+    Ok(Json(state.db.list_schedules(user.id).await?))
+}
+
+/// Creates a schedule; the config is checked against the tool's request rules up front.
+///
+/// The response is the only time the secret its `webhook_url` deliveries are signed with
+/// is shown.
+pub async fn create_schedule(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ValidJson(input): ValidJson<ScheduleInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    ToolRequest::parse(&input.tool_type, &input.config)?;
+    let fields = input.fields();
+    if let Some(url) = &fields.webhook_url {
+        check_destination("webhook_url", url).await?;
+    }
+    let secret = webhooks::new_secret();
+    let id = state.db.create_schedule(user.id, &fields, next_run(&fields), &secret).await?;
+    let schedule = state.db.get_schedule(id, user.id).await?
+        .ok_or_else(|| AppError::not_found(format!("Schedule {} not found", id)))?;
+    let mut body = serde_json::to_value(schedule).unwrap();
+    body["webhook_secret"] = secret.into();
+    Ok(Json(body))
+}
+
+pub async fn get_schedule(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<Schedule>> {
+    let schedule = state.db.get_schedule(id, user.id).await?
+        .ok_or_else(|| AppError::not_found(format!("Schedule {} not found", id)))?;
+    Ok(Json(schedule))
+}
+
+/// Replaces a schedule; its next run is recomputed from the (new) expression.
+///
+/// A schedule from before signed deliveries is given a webhook secret here, returned
+/// once in this response.
+pub async fn update_schedule(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    ValidJson(input): ValidJson<ScheduleInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    ToolRequest::parse(&input.tool_type, &input.config)?;
+    let fields = input.fields();
+    if let Some(url) = &fields.webhook_url {
+        check_destination("webhook_url", url).await?;
+    }
+    if !state.db.update_schedule(id, user.id, &fields, next_run(&fields)).await? {
+        return Err(AppError::not_found(format!("Schedule {} not found", id)));
+    }
+    let Json(schedule) = get_schedule(Extension(state.clone()), Extension(user.clone()), Path(id)).await?;
+    let new_secret = match schedule.webhook_secret {
+        Some(_) => None,
+        None => {
+            let secret = webhooks::new_secret();
+            state.db.set_schedule_webhook_secret(id, user.id, &secret).await?;
+            Some(secret)
+        }
+    };
+    let mut body = serde_json::to_value(&schedule).unwrap();
+    if let Some(secret) = new_secret {
+        body["webhook_secret"] = secret.into();
+    }
+    Ok(Json(body))
+}
+
+pub async fn delete_schedule(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !state.db.delete_schedule(id, user.id).await? {
+        return Err(AppError::not_found(format!("Schedule {} not found", id)));
+    }
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
+
+/// Runs a schedule immediately, without moving its next run.
+pub async fn run_schedule_now(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    let schedule = state.db.get_schedule(id, user.id).await?
+        .ok_or_else(|| AppError::not_found(format!("Schedule {} not found", id)))?;
+    let history_id = run_and_record(&state, &schedule).await?;
+    Ok(Json(serde_json::json!({ "history_id": history_id })))
+}
+
+// === RUNNER ===
+
+/// Checks for due schedules every `POLL_INTERVAL` until shutdown.
+pub fn spawn_runner(state: AppState) {
+    let mut shutdown = state.shutdown.clone();
+    tokio::spawn(async move {
+        tracing::info!(poll_secs = POLL_INTERVAL.as_secs(), "Schedule runner started");
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = shutdown.changed() => break,
+            }
+            if let Err(e) = run_due(&state).await {
+                tracing::error!(error = %e, "Could not check schedules");
+            }
+        }
+    }.instrument(tracing::info_span!("schedule_runner")));
+}
+
+async fn run_due(state: &AppState) -> anyhow::Result<()> {
+    for schedule in state.db.due_schedules(Utc::now().naive_utc()).await? {
+        let Some(due_at) = schedule.next_run_at else { continue };
+        // Move it on first, so a slow or failing run is not repeated every poll
+        let fields = ScheduleFields {
+            name: schedule.name.clone(),
+            tool_type: schedule.tool_type.clone(),
+            config: schedule.config.clone(),
+            cron: schedule.cron.clone(),
+            webhook_url: schedule.webhook_url.clone(),
+            enabled: schedule.enabled,
+        };
+        if !state.db.claim_schedule(schedule.id, due_at, next_run(&fields)).await? {
+            continue;
+        }
+        let span = tracing::info_span!("scheduled_run", schedule_id = schedule.id, tool = %schedule.tool_type);
+        // Failures are recorded on the schedule and sent to its webhook
+        let _ = run_and_record(state, &schedule).instrument(span).await;
+    }
+    Ok(())
+}
+
+/// Runs a schedule's tool as its owner, archives the result and notifies the webhook.
+///
+/// The outcome is stored on the schedule (`last_history_id` / `last_error`) either way.
+async fn run_and_record(state: &AppState, schedule: &Schedule) -> AppResult<i64> {
+    let outcome = execute(state, schedule).await;
+    let (history_id, error) = match &outcome {
+        Ok((id, _)) => (Some(*id), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    state.db.finish_schedule_run(schedule.id, history_id, error.as_deref()).await?;
+    match &error {
+        None => tracing::info!(history_id, "Scheduled run archived"),
+        Some(error) => tracing::warn!(%error, "Scheduled run failed"),
+    }
+    events::emit(&state.events, ServerEvent::ScheduleRun {
+        schedule_id: schedule.id,
+        owner_id: schedule.owner_id,
+        name: schedule.name.clone(),
+        history_id,
+        error: error.clone(),
+    });
+
+    // The schedule's own webhook gets the full report; registered webhooks get the event.
+    // Reports carry birth data, so they are never sent unsigned.
+    let signed_url = match (schedule.webhook_url.clone(), schedule.signing_secret()) {
+        (Some(url), Ok(Some(secret))) => Some((url, secret)),
+        (Some(url), Ok(None)) => {
+            tracing::warn!(%url, "Schedule has no webhook secret yet; save it again to sign deliveries");
+            None
+        }
+        (Some(url), Err(e)) => {
+            tracing::warn!(%url, error = %e, "Could not open schedule webhook secret");
+            None
+        }
+        (None, _) => None,
+    };
+    if let Some((url, secret)) = signed_url {
+        let event = if error.is_none() { webhooks::SCHEDULE_COMPLETED } else { webhooks::SCHEDULE_FAILED };
+        let delivery = Delivery::new(event, serde_json::json!({
+            "schedule_id": schedule.id,
+            "schedule_name": schedule.name,
+            "tool_type": schedule.tool_type,
+            "history_id": history_id,
+            "report": outcome.as_ref().ok().map(|(_, report)| report),
+            "error": error,
+        }));
+        tokio::spawn(async move {
+            let outcome = match webhooks::client_for(&url).await {
+                Ok(client) => webhooks::deliver(&client, &url, Some(&secret), &delivery, RetryPolicy::default()).await,
+                Err(e) => DeliveryOutcome { attempts: 0, status: None, error: Some(format!("{:#}", e)) },
+            };
+            if let Some(error) = outcome.error {
+                tracing::warn!(%url, attempts = outcome.attempts, %error, "Schedule webhook delivery failed");
+            }
+        }.in_current_span());
+    }
+    outcome.map(|(id, _)| id)
+}
+
+async fn execute(state: &AppState, schedule: &Schedule) -> AppResult<(i64, serde_json::Value)> {
+    let user = state.db.get_user(schedule.owner_id).await?
+        .ok_or_else(|| AppError::not_found(format!("User {} not found", schedule.owner_id)))?;
+    let (report, mut run) = ToolRequest::parse(&schedule.tool_type, &schedule.config)?.run(state, &user).await?;
+    run.summary = format!("[{}] {}", schedule.name, run.summary);
+    // Scheduled runs always go to history; that is where their results are read
+    let id = archive::record(state, &user, run.into_entry(&report)).await?;
+    Ok((id, report))
+}
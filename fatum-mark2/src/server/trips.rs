@@ -0,0 +1,142 @@
+use axum::{extract::{Path, Query}, Extension, Json};
+use serde::Deserialize;
+
+use super::auth::AuthUser;
+use super::error::{AppError, AppResult};
+use super::validation::ValidJson;
+use super::AppState;
+use crate::db::{PhotoMeta, Trip, TripFields, TripTarget};
+use crate::tools::geolocation::{outcome_stats, OutcomeStats, TripOutcome};
+
+/// How a visit went; the editable part of a trip.
+#[derive(Deserialize)]
+pub struct VisitInput {
+    /// When the spot was visited; defaults to now.
+    pub visited_at: Option<chrono::NaiveDateTime>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub photos: Vec<PhotoMeta>,
+    /// 1 (nothing of note) to 5 (remarkable).
+    pub rating: i64,
+}
+
+/// A new trip: which attractor or void of which archived geolocation report, and the visit.
+#[derive(Deserialize)]
+pub struct TripInput {
+    pub history_id: i64,
+    /// "attractor" or "void".
+    pub target_kind: String,
+    /// The target's 1-based rank in the report.
+    pub target_rank: i64,
+    #[serde(flatten)]
+    pub visit: VisitInput,
+}
+
+#[derive(Deserialize)]
+pub struct TripFilter {
+    pub history_id: Option<i64>,
+}
+
+impl VisitInput {
+    fn fields(self) -> TripFields {
+        TripFields {
+            visited_at: self.visited_at.unwrap_or_else(|| chrono::Utc::now().naive_utc()),
+            notes: self.notes.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+            photos: self.photos,
+            rating: self.rating,
+        }
+    }
+}
+
+async fn find(state: &AppState, id: i64, user: &AuthUser) -> AppResult<Trip> {
+    state.db.get_trip(id, user.id).await?
+        .ok_or_else(|| AppError::not_found(format!("Trip {} not found", id)))
+}
+
+/// Looks the target up in the archived report, so a trip records where it went and how strong it was.
+async fn target(state: &AppState, input: &TripInput, user: &AuthUser) -> AppResult<TripTarget> {
+    let entry = state.db.get_history(input.history_id, user.id).await?
+        .ok_or_else(|| AppError::not_found(format!("History entry {} not found", input.history_id)))?;
+    if entry.tool_type != "geolocation" {
+        return Err(AppError::bad_request(format!("History entry {} is not a geolocation report", entry.id)));
+    }
+    let features = entry.full_report.as_ref().and_then(|r| r["features"].as_array()).cloned().unwrap_or_default();
+    let feature = features.iter()
+        .find(|f| f["properties"]["kind"] == input.target_kind.as_str() && f["properties"]["rank"] == input.target_rank)
+        .ok_or_else(|| AppError::bad_request(format!("Report {} has no {} {}", entry.id, input.target_kind, input.target_rank)))?;
+    let coordinates = &feature["geometry"]["coordinates"];
+    match (coordinates[0].as_f64(), coordinates[1].as_f64(), feature["properties"]["power"].as_f64()) {
+        (Some(longitude), Some(latitude), Some(power)) => Ok(TripTarget {
+            history_id: entry.id,
+            target_kind: input.target_kind.clone(),
+            target_rank: input.target_rank,
+            latitude,
+            longitude,
+            power,
+        }),
+        _ => Err(AppError::bad_request(format!("Report {} has a malformed {} {}", entry.id, input.target_kind, input.target_rank))),
+    }
+}
+
+pub async fn list_trips(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(filter): Query<TripFilter>,
+) -> AppResult<Json<Vec<Trip>>> {
+    Ok(Json(state.db.list_trips(user.id, filter.history_id).await?))
+}
+
+pub async fn get_trip(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<Trip>> {
+    Ok(Json(find(&state, id, &user).await?))
+}
+
+/// Logs a visit to an attractor or void of an archived geolocation report.
+pub async fn create_trip(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ValidJson(input): ValidJson<TripInput>,
+) -> AppResult<Json<Trip>> {
+    let target = target(&state, &input, &user).await?;
+    let id = state.db.create_trip(user.id, &target, &input.visit.fields()).await?;
+    Ok(Json(find(&state, id, &user).await?))
+}
+
+pub async fn update_trip(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    ValidJson(input): ValidJson<VisitInput>,
+) -> AppResult<Json<Trip>> {
+    if !state.db.update_trip(id, user.id, &input.fields()).await? {
+        return Err(AppError::not_found(format!("Trip {} not found", id)));
+    }
+    Ok(Json(find(&state, id, &user).await?))
+}
+
+pub async fn delete_trip(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !state.db.delete_trip(id, user.id).await? {
+        return Err(AppError::not_found(format!("Trip {} not found", id)));
+    }
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
+
+/// Ratings against target strength over the user's trips (or one report's).
+pub async fn trip_stats(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Query(filter): Query<TripFilter>,
+) -> AppResult<Json<OutcomeStats>> {
+    let trips = state.db.list_trips(user.id, filter.history_id).await?;
+    let outcomes: Vec<TripOutcome> = trips.into_iter()
+        .map(|t| TripOutcome { kind: t.target_kind, power: t.power, rating: t.rating as f64 })
+        .collect();
+    Ok(Json(outcome_stats(&outcomes)))
+}
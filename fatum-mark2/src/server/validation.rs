@@ -0,0 +1,661 @@
+use axum::{
+    Json,
+    extract::{FromRequest, OptionalFromRequest, Request},
+};
+use chrono::{Datelike, NaiveDate, Timelike};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Display;
+
+use super::error::AppError;
+use super::{FengShuiApiInput, ManyWorldsRequest, PresetInput, PresetUpdate, ProfileInput, SimulateRequest, TimelineRequest};
+use super::schedules::{ScheduleInput, SCHEDULABLE_TOOLS};
+use super::trips::{TripInput, VisitInput};
+use super::webhooks::WebhookInput;
+use crate::engine::SimulationOptions;
+use crate::services::cron::CronSchedule;
+use crate::services::webhooks::EVENT_TYPES;
+use crate::tools::astronomy::Location;
+use crate::tools::da_liu_ren::DaLiuRenConfig;
+use crate::tools::divination::DivinationConfig;
+use crate::tools::liu_yao::LiuYaoConfig;
+use crate::tools::tarot::TarotConfig;
+use crate::tools::astrology_western::NatalConfig;
+use crate::tools::astrology_vedic::VedicConfig;
+use crate::tools::astrology_reports::{SynastryConfig, TransitConfig};
+use crate::tools::runes::RuneConfig;
+use crate::tools::numerology::NumerologyConfig;
+use crate::tools::qimen_directions::{QiMenConfig, MAX_HOURS_AHEAD};
+use crate::tools::entanglement::EntanglementRequest;
+use crate::tools::geolocation::GeolocationConfig;
+use crate::tools::decision::{check_tree, DecisionInput, DecisionTree, Severity};
+use crate::tools::floor_plan::{self, Room};
+use crate::tools::property_compare::PropertyComparisonConfig;
+use crate::tools::ze_ri::DateSelectionConfig;
+use crate::tools::zi_wei::ZiWeiConfig;
+
+/// Birth years the calendar conversions are trusted for.
+const MIN_YEAR: i32 = 1900;
+const MAX_YEAR: i32 = 2100;
+/// Longest window the date selector scans in one request.
+const MAX_DATE_SPAN_DAYS: i64 = 366;
+const MAX_DURATION: usize = 500;
+const MAX_WORLDS: usize = 100_000;
+const MAX_SIMULATIONS: usize = 10_000_000;
+/// Decisions run inside the request, so they get a smaller budget than background jobs.
+const MAX_DECISION_SIMULATIONS: usize = 1_000_000;
+/// Snapshots per run; each one is stored with the job and sent to every subscriber.
+const MAX_TIME_SERIES_POINTS: usize = 1000;
+const MAX_TREE_NODES: usize = 1000;
+const MAX_TREE_BRANCHES: usize = 100;
+/// Round robin plays n(n-1)/2 matches a pass, so tournaments take fewer options and passes.
+const MAX_TOURNAMENT_OPTIONS: usize = 64;
+const MAX_TOURNAMENT_ROUNDS: usize = 1000;
+const GENDERS: &[&str] = &["M", "F"];
+/// Tools a preset can be saved for.
+const PRESET_TOOLS: &[&str] = &["fengshui", "zeri", "ziwei", "daliuren", "qimen", "natal", "transits", "synastry", "vedic", "liuyao", "tarot", "runes", "numerology", "entanglement", "geolocation", "decision", "many_worlds", "timeline", "simulate", "fengshui_compare"];
+const MAX_PRESET_NAME: usize = 100;
+const MAX_QUESTION: usize = 1000;
+const MAX_BIRTH_NAME: usize = 200;
+/// Members in a group entanglement (66 pairings).
+const MAX_GROUP: usize = 12;
+/// Points per geolocation scan (16 entropy bytes each).
+const MAX_GEO_POINTS: usize = 200_000;
+/// Widest geolocation scan, in metres.
+const MAX_GEO_RADIUS_M: f64 = 100_000.0;
+const MAX_EXCLUSIONS: usize = 32;
+const MAX_EXCLUSION_VERTICES: usize = 256;
+const MAX_WEBHOOK_URL: usize = 2048;
+const MAX_TRIP_NOTES: usize = 10_000;
+const MAX_TRIP_PHOTOS: usize = 50;
+const TRIP_TARGETS: &[&str] = &["attractor", "void"];
+const MAX_ROOMS: usize = 32;
+const MAX_ROOM_POINTS: usize = 64;
+const MAX_WATER_FEATURES: usize = 24;
+/// Candidate homes per comparison.
+const MAX_PROPERTIES: usize = 20;
+/// Most BaZi annual pillars one report lists.
+const MAX_ANNUAL_YEARS: i32 = 120;
+
+/// One rejected input field.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Collects every problem with a request so the client can fix them in one go.
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn error(&mut self, field: &str, message: impl Into<String>) -> &mut Self {
+        self.errors.push(FieldError { field: field.to_string(), message: message.into() });
+        self
+    }
+
+    pub fn check(&mut self, field: &str, ok: bool, message: impl Into<String>) -> &mut Self {
+        if !ok {
+            self.error(field, message);
+        }
+        self
+    }
+
+    /// Inclusive range check; `None` passes (optional fields are checked only when present).
+    pub fn range<T: PartialOrd + Display + Copy>(&mut self, field: &str, value: Option<T>, min: T, max: T) -> &mut Self {
+        if let Some(v) = value {
+            if !(v >= min && v <= max) {
+                self.error(field, format!("must be between {} and {} (got {})", min, max, v));
+            }
+        }
+        self
+    }
+
+    pub fn one_of(&mut self, field: &str, value: Option<&str>, allowed: &[&str]) -> &mut Self {
+        if let Some(v) = value {
+            if !allowed.contains(&v) {
+                self.error(field, format!("must be one of {} (got {:?})", allowed.join(", "), v));
+            }
+        }
+        self
+    }
+
+    pub fn not_blank(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(field, !value.trim().is_empty(), "must not be empty")
+    }
+
+    /// Birth year/month/day/hour/gender, plus a check that the day exists in that month.
+    pub fn birth(&mut self, year: Option<i64>, month: Option<i64>, day: Option<i64>, hour: Option<i64>, gender: Option<&str>) -> &mut Self {
+        let before = self.errors.len();
+        self.range("birth_year", year, MIN_YEAR as i64, MAX_YEAR as i64)
+            .range("birth_month", month, 1, 12)
+            .range("birth_day", day, 1, 31)
+            .range("birth_hour", hour, 0, 23)
+            .one_of("gender", gender, GENDERS);
+        if self.errors.len() == before {
+            if let (Some(y), Some(m), Some(d)) = (year, month, day) {
+                if NaiveDate::from_ymd_opt(y as i32, m as u32, d as u32).is_none() {
+                    self.error("birth_day", format!("{}-{:02}-{:02} is not a calendar date", y, m, d));
+                }
+            }
+        }
+        self
+    }
+
+    pub fn finish(self) -> Result<(), AppError> {
+        if self.errors.is_empty() { Ok(()) } else { Err(AppError::Validation(self.errors)) }
+    }
+}
+
+/// Field-level checks for a request payload.
+pub trait Validate {
+    fn validate(&self, v: &mut Validator);
+}
+
+/// JSON body extractor that runs `Validate` before the handler sees the payload.
+///
+/// Malformed JSON is a plain 400; semantic problems come back as a 400 listing every bad field.
+pub struct ValidJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = <Json<T> as FromRequest<S>>::from_request(req, state)
+            .await
+            .map_err(|e| AppError::bad_request(e.body_text()))?;
+        validate(&value)?;
+        Ok(ValidJson(value))
+    }
+}
+
+/// A missing body (no `Content-Type`) is `None`; a body that is present must still be valid.
+impl<T, S> OptionalFromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        let value = <Json<T> as OptionalFromRequest<S>>::from_request(req, state)
+            .await
+            .map_err(|e| AppError::bad_request(e.body_text()))?;
+        let Some(Json(value)) = value else { return Ok(None) };
+        validate(&value)?;
+        Ok(Some(ValidJson(value)))
+    }
+}
+
+/// Runs a payload's `Validate` rules, for payloads that did not arrive through `ValidJson`.
+pub fn validate<T: Validate>(value: &T) -> Result<(), AppError> {
+    let mut v = Validator::default();
+    value.validate(&mut v);
+    v.finish()
+}
+
+// === REQUEST RULES ===
+
+impl Validate for FengShuiApiInput {
+    fn validate(&self, v: &mut Validator) {
+        v.birth(
+            self.birth_year.map(i64::from),
+            self.birth_month.map(i64::from),
+            self.birth_day.map(i64::from),
+            self.birth_hour.map(i64::from),
+            self.gender.as_deref(),
+        )
+        .range("construction_year", self.construction_year, 1800, 2200)
+        .range("facing_degrees", self.facing_degrees, 0.0, 360.0)
+        .range("water_exit_degrees", self.water_exit_degrees, 0.0, 360.0)
+        .check("intention", self.intention.as_ref().is_none_or(|i| i.chars().count() <= MAX_QUESTION), format!("must be at most {} characters", MAX_QUESTION));
+        if let Some(features) = &self.water_features {
+            v.check("water_features", features.len() <= MAX_WATER_FEATURES, format!("must have at most {} entries", MAX_WATER_FEATURES))
+                .check("water_features", features.iter().all(|d| (0.0..=360.0).contains(d)), "degrees must be between 0 and 360");
+        }
+        location(v, self.location.as_ref());
+        annual_years(v, self.annual_years);
+        rooms(v, "rooms", self.rooms.as_deref());
+        simulation_options(v, self.simulation_options.as_ref());
+    }
+}
+
+impl Validate for PropertyComparisonConfig {
+    fn validate(&self, v: &mut Validator) {
+        v.birth(
+            self.birth_year.map(i64::from),
+            self.birth_month.map(i64::from),
+            self.birth_day.map(i64::from),
+            self.birth_hour.map(i64::from),
+            self.gender.as_deref(),
+        )
+        .range("current_year", self.current_year, MIN_YEAR, MAX_YEAR)
+        .check("properties", !self.properties.is_empty(), "must list at least one home")
+        .check("properties", self.properties.len() <= MAX_PROPERTIES, format!("must have at most {} homes", MAX_PROPERTIES));
+        location(v, self.location.as_ref());
+        for (i, property) in self.properties.iter().enumerate() {
+            let field = |name: &str| format!("properties[{}].{}", i, name);
+            v.not_blank(&field("name"), &property.name)
+                .range(&field("construction_year"), Some(property.construction_year), 1800, 2200)
+                .range(&field("facing_degrees"), Some(property.facing_degrees), 0.0, 360.0);
+            rooms(v, &field("rooms"), property.rooms.as_deref());
+        }
+    }
+}
+
+impl Validate for ManyWorldsRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.range("birth_year", self.birth_year, MIN_YEAR, MAX_YEAR)
+            .range("duration", self.duration, 1, MAX_DURATION)
+            .range("num_worlds", self.num_worlds, 1, MAX_WORLDS);
+    }
+}
+
+impl Validate for TimelineRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.range("duration", self.duration, 1, MAX_DURATION)
+            .range("num_worlds", self.num_worlds, 1, MAX_WORLDS);
+        if let Some(elements) = &self.start_elements {
+            for (name, value) in elements {
+                v.check(
+                    "start_elements",
+                    crate::engine::timeline::WU_XING.contains(&name.as_str()),
+                    format!("unknown element {:?}", name),
+                )
+                .check("start_elements", value.is_finite() && *value >= 0.0, format!("{} must be a non-negative number", name));
+            }
+        }
+    }
+}
+
+impl Validate for SimulateRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.check("options", !self.options.is_empty(), "must list at least one option")
+            .range("simulations", self.simulations, 1, MAX_SIMULATIONS);
+        if let Some(weights) = &self.weights {
+            v.check(
+                "weights",
+                weights.len() == self.options.len(),
+                format!("must have one weight per option ({} options, {} weights)", self.options.len(), weights.len()),
+            )
+            .check("weights", weights.iter().all(|w| w.is_finite() && *w >= 0.0), "must be non-negative numbers");
+        }
+        simulation_options(v, self.simulation_options.as_ref());
+    }
+}
+
+impl Validate for DecisionInput {
+    fn validate(&self, v: &mut Validator) {
+        simulation_options(v, self.simulation_options.as_ref());
+        v.range("simulations", self.simulations, 1, MAX_DECISION_SIMULATIONS)
+            .range("perturbation", self.perturbation, 0.0, 0.99)
+            .check("question", self.question.as_ref().is_none_or(|q| q.len() <= MAX_QUESTION), format!("must be at most {} characters", MAX_QUESTION));
+        let Some(tree) = &self.tree else {
+            v.check("options", !self.options.is_empty(), "must list at least one option, or give a tree");
+            if let Some(weights) = &self.weights {
+                v.check(
+                    "weights",
+                    weights.len() == self.options.len(),
+                    format!("must have one weight per option ({} options, {} weights)", self.options.len(), weights.len()),
+                )
+                .check("weights", weights.iter().all(|w| w.is_finite() && *w >= 0.0), "must be non-negative numbers");
+            }
+            if let Some(tournament) = &self.tournament {
+                v.range("options", Some(self.options.len()), 2, MAX_TOURNAMENT_OPTIONS)
+                    .range("tournament.rounds", tournament.rounds, 1, MAX_TOURNAMENT_ROUNDS)
+                    .range("tournament.k_factor", tournament.k_factor, 0.0, 400.0);
+            }
+            return;
+        };
+        v.check("tree", self.options.is_empty() && self.weights.is_none(), "give either options or a tree, not both")
+            .check("tournament", self.tournament.is_none(), "tournaments play off options, not a tree");
+        tree_shape(v, "tree", tree);
+        // Structural errors point at the node or branch at fault; warnings come back with the report
+        for d in check_tree(tree).into_iter().filter(|d| d.severity == Severity::Error) {
+            let field = match (d.node, d.branch) {
+                (Some(i), Some(j)) => format!("tree.nodes[{}].branches[{}]", i, j),
+                (Some(i), None) => format!("tree.nodes[{}]", i),
+                _ => "tree.root_id".to_string(),
+            };
+            v.error(&field, d.message);
+        }
+    }
+}
+
+/// Size and per-field checks for a decision tree; `check_tree` covers how the nodes connect.
+impl Validate for DecisionTree {
+    fn validate(&self, v: &mut Validator) {
+        tree_shape(v, "", self);
+    }
+}
+
+fn tree_shape(v: &mut Validator, prefix: &str, tree: &DecisionTree) {
+    let field = |name: String| if prefix.is_empty() { name } else { format!("{}.{}", prefix, name) };
+    v.range(&field("nodes".to_string()), Some(tree.nodes.len()), 1, MAX_TREE_NODES);
+    for (i, node) in tree.nodes.iter().enumerate() {
+        v.not_blank(&field(format!("nodes[{}].id", i)), &node.id)
+            .check(&field(format!("nodes[{}].utility", i)), node.utility.is_none_or(f64::is_finite), "must be a number")
+            .check(&field(format!("nodes[{}].branches", i)), node.branches.len() <= MAX_TREE_BRANCHES, format!("at most {} branches", MAX_TREE_BRANCHES));
+        for (j, branch) in node.branches.iter().enumerate() {
+            let at = format!("nodes[{}].branches[{}]", i, j);
+            v.not_blank(&field(format!("{}.label", at)), &branch.label)
+                .check(&field(format!("{}.weight", at)), branch.weight.is_finite() && branch.weight >= 0.0, "must be a non-negative number")
+                .check(&field(format!("{}.utility", at)), branch.utility.is_none_or(f64::is_finite), "must be a number");
+        }
+    }
+}
+
+impl Validate for ProfileInput {
+    fn validate(&self, v: &mut Validator) {
+        v.not_blank("name", &self.name)
+            .birth(
+                Some(self.birth_year.into()),
+                Some(self.birth_month.into()),
+                Some(self.birth_day.into()),
+                Some(self.birth_hour.into()),
+                Some(&self.gender),
+            )
+            .range("entropy_quota_bytes", self.entropy_quota_bytes, 0, i64::MAX);
+    }
+}
+
+impl Validate for DateSelectionConfig {
+    fn validate(&self, v: &mut Validator) {
+        let span = (self.end_date - self.start_date).num_days();
+        v.check("end_date", span >= 0, "must not be before start_date")
+            .check("end_date", span <= MAX_DATE_SPAN_DAYS, format!("range may span at most {} days", MAX_DATE_SPAN_DAYS))
+            .range("user_birth_year", self.user_birth_year, MIN_YEAR, MAX_YEAR)
+            .range("user_birth_date", self.user_birth_date.map(|d| d.year()), MIN_YEAR, MAX_YEAR)
+            .range("user_birth_hour", self.user_birth_hour, 0, 23);
+        simulation_options(v, self.simulation_options.as_ref());
+    }
+}
+
+impl Validate for ZiWeiConfig {
+    fn validate(&self, v: &mut Validator) {
+        let (year, month, day, hour) = match self.birth_datetime {
+            Some(dt) => (dt.year(), dt.month(), dt.day(), dt.hour()),
+            None => (self.birth_year, self.birth_month, self.birth_day, self.birth_hour),
+        };
+        v.birth(Some(year.into()), Some(month.into()), Some(day.into()), Some(hour.into()), Some(&self.gender));
+        location(v, self.location.as_ref());
+        annual_years(v, self.annual_years);
+        simulation_options(v, self.simulation_options.as_ref());
+    }
+}
+
+impl Validate for DivinationConfig {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(question) = &self.question {
+            v.check("question", question.chars().count() <= MAX_QUESTION, format!("must be at most {} characters", MAX_QUESTION));
+        }
+    }
+}
+
+impl Validate for DaLiuRenConfig {
+    fn validate(&self, v: &mut Validator) {
+        v.range("day_stem_idx", Some(self.day_stem_idx), 0, 9)
+            .range("day_branch_idx", Some(self.day_branch_idx), 0, 11)
+            .range("hour_branch_idx", Some(self.hour_branch_idx), 0, 11)
+            .range("solar_term_idx", Some(self.solar_term_idx), 0, 23);
+        location(v, self.location.as_ref());
+    }
+}
+
+impl Validate for QiMenConfig {
+    fn validate(&self, v: &mut Validator) {
+        v.range("datetime", Some(self.datetime.year()), MIN_YEAR, MAX_YEAR)
+            .range("hours_ahead", Some(self.hours_ahead), 0, MAX_HOURS_AHEAD);
+        location(v, self.location.as_ref());
+    }
+}
+
+impl Validate for NatalConfig {
+    fn validate(&self, v: &mut Validator) {
+        natal(v, "", self);
+    }
+}
+
+impl Validate for TransitConfig {
+    fn validate(&self, v: &mut Validator) {
+        let span = (self.end_date - self.start_date).num_days();
+        natal(v, "natal.", &self.natal);
+        v.range("start_date", Some(self.start_date.year()), MIN_YEAR, MAX_YEAR)
+            .check("end_date", span >= 0, "must not be before start_date")
+            .check("end_date", span <= MAX_DATE_SPAN_DAYS, format!("range may span at most {} days", MAX_DATE_SPAN_DAYS));
+    }
+}
+
+impl Validate for SynastryConfig {
+    fn validate(&self, v: &mut Validator) {
+        natal(v, "first.", &self.first);
+        natal(v, "second.", &self.second);
+    }
+}
+
+impl Validate for VedicConfig {
+    fn validate(&self, v: &mut Validator) {
+        v.range("datetime", Some(self.datetime.year()), MIN_YEAR, MAX_YEAR)
+            .range("latitude", Some(self.latitude), -90.0, 90.0);
+        location(v, Some(&self.location));
+    }
+}
+
+impl Validate for LiuYaoConfig {
+    fn validate(&self, v: &mut Validator) {
+        v.range("datetime", self.datetime.map(|d| d.year()), MIN_YEAR, MAX_YEAR);
+        location(v, self.location.as_ref());
+    }
+}
+
+impl Validate for TarotConfig {
+    fn validate(&self, _v: &mut Validator) {}
+}
+
+impl Validate for RuneConfig {
+    fn validate(&self, _v: &mut Validator) {}
+}
+
+impl Validate for NumerologyConfig {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(name) = &self.name {
+            v.not_blank("name", name)
+                .check("name", name.chars().count() <= MAX_BIRTH_NAME, format!("must be at most {} characters", MAX_BIRTH_NAME));
+        }
+        v.range("birth_date", self.birth_date.map(|d| d.year()), MIN_YEAR, MAX_YEAR)
+            .range("target_date", self.target_date.map(|d| d.year()), MIN_YEAR, MAX_YEAR);
+    }
+}
+
+impl Validate for PresetInput {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("tool_type", Some(&self.tool_type), PRESET_TOOLS);
+        preset_fields(v, &self.name, &self.config);
+    }
+}
+
+impl Validate for PresetUpdate {
+    fn validate(&self, v: &mut Validator) {
+        preset_fields(v, &self.name, &self.config);
+    }
+}
+
+impl Validate for ScheduleInput {
+    fn validate(&self, v: &mut Validator) {
+        v.not_blank("name", &self.name)
+            .check("name", self.name.chars().count() <= MAX_PRESET_NAME, format!("must be at most {} characters", MAX_PRESET_NAME))
+            .one_of("tool_type", Some(&self.tool_type), SCHEDULABLE_TOOLS)
+            .check("config", self.config.is_object(), "must be a JSON object");
+        if let Err(e) = CronSchedule::parse(&self.cron) {
+            v.error("cron", format!("{:#}", e));
+        }
+        if let Some(url) = &self.webhook_url {
+            webhook_url(v, "webhook_url", url);
+        }
+    }
+}
+
+impl Validate for WebhookInput {
+    fn validate(&self, v: &mut Validator) {
+        webhook_url(v, "url", self.url.trim());
+        for event in &self.events {
+            v.one_of("events", Some(event), EVENT_TYPES);
+        }
+    }
+}
+
+impl Validate for VisitInput {
+    fn validate(&self, v: &mut Validator) {
+        v.range("rating", Some(self.rating), 1, 5)
+            .check("notes", self.notes.as_ref().is_none_or(|n| n.len() <= MAX_TRIP_NOTES), format!("must be at most {} characters", MAX_TRIP_NOTES))
+            .check("photos", self.photos.len() <= MAX_TRIP_PHOTOS, format!("at most {} photos", MAX_TRIP_PHOTOS));
+        for (i, photo) in self.photos.iter().enumerate() {
+            v.not_blank(&format!("photos[{}].file_name", i), &photo.file_name)
+                .range(&format!("photos[{}].latitude", i), photo.latitude, -90.0, 90.0)
+                .range(&format!("photos[{}].longitude", i), photo.longitude, -180.0, 180.0);
+        }
+    }
+}
+
+impl Validate for TripInput {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("target_kind", Some(&self.target_kind), TRIP_TARGETS)
+            .range("target_rank", Some(self.target_rank), 1, i64::MAX);
+        self.visit.validate(v);
+    }
+}
+
+fn webhook_url(v: &mut Validator, field: &str, url: &str) {
+    v.check(field, url.starts_with("https://") || url.starts_with("http://"), "must be an http(s) URL")
+        .check(field, url.len() <= MAX_WEBHOOK_URL, format!("must be at most {} characters", MAX_WEBHOOK_URL));
+}
+
+/// Engine tuning sent with a quantum run.
+impl Validate for SimulationOptions {
+    fn validate(&self, v: &mut Validator) {
+        v.range("simulation_options.time_series_points", Some(self.time_series_points), 1, MAX_TIME_SERIES_POINTS)
+            .check("simulation_options.z_threshold", self.z_threshold.is_finite() && self.z_threshold > 0.0, "must be a positive number")
+            .check("simulation_options.min_effect_size", self.min_effect_size.is_finite() && self.min_effect_size >= 0.0, "must be a non-negative number")
+            .check(
+                "simulation_options.early_stop_confidence",
+                self.early_stop_confidence.is_none_or(|c| c > 0.0 && c < 1.0),
+                "must be between 0 and 1 (exclusive)",
+            )
+            .check("simulation_options.early_stop_ratio", self.early_stop_ratio.is_finite() && self.early_stop_ratio > 1.0, "must be a number greater than 1");
+    }
+}
+
+fn simulation_options(v: &mut Validator, options: Option<&SimulationOptions>) {
+    if let Some(options) = options {
+        options.validate(v);
+    }
+}
+
+fn location(v: &mut Validator, location: Option<&Location>) {
+    if let Some(loc) = location {
+        v.range("location.longitude", Some(loc.longitude), -180.0, 180.0)
+            .range("location.utc_offset", Some(loc.utc_offset), -12.0, 14.0);
+    }
+}
+
+/// Birth chart fields, named under `prefix` when the chart is nested (e.g. "first.").
+fn natal(v: &mut Validator, prefix: &str, config: &NatalConfig) {
+    let field = |name: &str| format!("{}{}", prefix, name);
+    v.range(&field("datetime"), Some(config.datetime.year()), MIN_YEAR, MAX_YEAR)
+        .range(&field("latitude"), Some(config.latitude), -90.0, 90.0)
+        .range(&field("location.longitude"), Some(config.location.longitude), -180.0, 180.0)
+        .range(&field("location.utc_offset"), Some(config.location.utc_offset), -12.0, 14.0);
+}
+
+/// Floor plan rooms, named under `field` (e.g. "properties[0].rooms").
+fn rooms(v: &mut Validator, field: &str, rooms: Option<&[Room]>) {
+    let Some(rooms) = rooms else { return };
+    v.check(field, rooms.len() <= MAX_ROOMS, format!("must have at most {} rooms", MAX_ROOMS));
+    for (i, room) in rooms.iter().enumerate() {
+        let field = format!("{}[{}]", field, i);
+        v.not_blank(&format!("{}.name", field), &room.name);
+        if let Some(polygon) = &room.polygon {
+            v.check(&format!("{}.polygon", field), polygon.len() <= MAX_ROOM_POINTS, format!("must have at most {} points", MAX_ROOM_POINTS))
+                .check(
+                    &format!("{}.polygon", field),
+                    polygon.iter().flatten().all(|c| (0.0..=3.0).contains(c)),
+                    "coordinates must be between 0 and 3",
+                );
+        }
+        if let Err(e) = floor_plan::palace_shares(room) {
+            v.error(&field, e.to_string());
+        }
+    }
+}
+
+fn annual_years(v: &mut Validator, years: Option<[i32; 2]>) {
+    if let Some([first, last]) = years {
+        v.range("annual_years", Some(first), MIN_YEAR, MAX_YEAR)
+            .range("annual_years", Some(last), MIN_YEAR, MAX_YEAR)
+            .check("annual_years", first <= last && last - first < MAX_ANNUAL_YEARS, format!("must be ascending and span at most {} years", MAX_ANNUAL_YEARS));
+    }
+}
+
+fn preset_fields(v: &mut Validator, name: &str, config: &serde_json::Value) {
+    v.not_blank("name", name)
+        .check("name", name.chars().count() <= MAX_PRESET_NAME, format!("must be at most {} characters", MAX_PRESET_NAME))
+        .check("config", config.is_object(), "must be a JSON object");
+}
+
+impl Validate for EntanglementRequest {
+    fn validate(&self, v: &mut Validator) {
+        if self.group.is_empty() {
+            v.not_blank("profile1_data", &self.profile1_data)
+                .not_blank("profile2_data", &self.profile2_data);
+        } else {
+            v.range("group", Some(self.group.len()), 3, MAX_GROUP);
+            for (i, member) in self.group.iter().enumerate() {
+                v.not_blank(&format!("group[{}]", i), member);
+            }
+        }
+        if let Some(charts) = &self.charts {
+            natal(v, "charts.first.", &charts.first);
+            natal(v, "charts.second.", &charts.second);
+        }
+        for (field, birth) in [("birth1", &self.birth1), ("birth2", &self.birth2)] {
+            if let Some(birth) = birth {
+                v.range(&format!("{}.datetime", field), Some(birth.datetime.year()), MIN_YEAR, MAX_YEAR)
+                    .one_of(&format!("{}.gender", field), birth.gender.as_deref(), GENDERS);
+            }
+        }
+    }
+}
+
+impl Validate for GeolocationConfig {
+    fn validate(&self, v: &mut Validator) {
+        v.range("latitude", Some(self.latitude), -90.0, 90.0)
+            .range("longitude", Some(self.longitude), -180.0, 180.0)
+            .range("radius_m", Some(self.radius_m), 1.0, MAX_GEO_RADIUS_M)
+            .range("points", self.points, 1, MAX_GEO_POINTS)
+            .check("intention", self.intention.as_ref().is_none_or(|i| i.chars().count() <= MAX_QUESTION), format!("must be at most {} characters", MAX_QUESTION))
+            .check("exclusions", self.exclusions.len() <= MAX_EXCLUSIONS, format!("at most {} zones", MAX_EXCLUSIONS));
+        for (i, zone) in self.exclusions.iter().enumerate() {
+            let field = format!("exclusions[{}]", i);
+            v.range(&format!("{}.polygon", field), Some(zone.polygon.len()), 1, MAX_EXCLUSION_VERTICES)
+                .range(&format!("{}.buffer_m", field), Some(zone.buffer_m), 0.0, MAX_GEO_RADIUS_M)
+                .check(
+                    &format!("{}.buffer_m", field),
+                    zone.polygon.len() >= 3 || zone.buffer_m > 0.0,
+                    "a line or point zone needs a buffer",
+                );
+            for (j, p) in zone.polygon.iter().enumerate() {
+                v.range(&format!("{}.polygon[{}].latitude", field, j), Some(p.latitude), -90.0, 90.0)
+                    .range(&format!("{}.polygon[{}].longitude", field, j), Some(p.longitude), -180.0, 180.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "validation_tests.rs"]
+mod tests;
@@ -0,0 +1,37 @@
+use serde_json::json;
+
+use super::{validate, Validate};
+use crate::server::error::AppError;
+use crate::server::SimulateRequest;
+
+/// Names of the fields `value` is rejected for, in order.
+fn rejected<T: Validate>(value: &T) -> Vec<String> {
+    match validate(value) {
+        Ok(()) => Vec::new(),
+        Err(AppError::Validation(errors)) => errors.into_iter().map(|e| e.field).collect(),
+        Err(e) => panic!("unexpected error {:?}", e),
+    }
+}
+
+fn simulate(options: serde_json::Value) -> SimulateRequest {
+    serde_json::from_value(json!({ "options": ["A", "B"], "simulations": 10_000_000, "simulation_options": options })).unwrap()
+}
+
+#[test]
+fn test_simulation_options_are_bounded() {
+    assert!(rejected(&simulate(json!({}))).is_empty());
+    assert!(rejected(&simulate(json!({ "time_series_points": 1000, "early_stop_confidence": 0.99 }))).is_empty());
+
+    // One snapshot per iteration would flood the job log and every subscriber
+    assert_eq!(rejected(&simulate(json!({ "time_series_points": 10_000_000 }))), ["simulation_options.time_series_points"]);
+    assert_eq!(rejected(&simulate(json!({ "time_series_points": 0 }))), ["simulation_options.time_series_points"]);
+    assert_eq!(
+        rejected(&simulate(json!({ "z_threshold": 0.0, "min_effect_size": -1.0, "early_stop_confidence": 1.0, "early_stop_ratio": 1.0 }))),
+        [
+            "simulation_options.z_threshold",
+            "simulation_options.min_effect_size",
+            "simulation_options.early_stop_confidence",
+            "simulation_options.early_stop_ratio",
+        ]
+    );
+}
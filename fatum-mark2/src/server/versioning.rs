@@ -0,0 +1,88 @@
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::error::AppError;
+
+/// Version served at `/api/v1` and, for now, at the legacy unversioned `/api` paths.
+pub const CURRENT_VERSION: u32 = 1;
+pub const SUPPORTED_VERSIONS: &[u32] = &[1];
+/// Media type prefix for `Accept`-based negotiation, e.g. `application/vnd.fatum.v1+json`.
+const VENDOR_MEDIA_PREFIX: &str = "application/vnd.fatum.v";
+
+/// Splits `/api/v{N}/rest` into `(Some(N), "/api/rest")`; other paths pass through unchanged.
+pub fn split_version(path: &str) -> (Option<u32>, String) {
+    if let Some(rest) = path.strip_prefix("/api/v") {
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let tail = &rest[digits.len()..];
+        if !digits.is_empty() && (tail.is_empty() || tail.starts_with('/')) {
+            if let Ok(v) = digits.parse() {
+                return (Some(v), format!("/api{}", tail));
+            }
+        }
+    }
+    (None, path.to_string())
+}
+
+/// Version asked for through `X-API-Version` or a vendor `Accept` type, if any.
+fn requested_version(req: &Request) -> Result<Option<u32>, AppError> {
+    let headers = req.headers();
+    if let Some(v) = headers.get("x-api-version").and_then(|v| v.to_str().ok()) {
+        let v = v.trim().trim_start_matches('v');
+        return v.parse().map(Some).map_err(|_| AppError::bad_request(format!("Invalid X-API-Version {:?}", v)));
+    }
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()).unwrap_or("");
+    for media in accept.split(',') {
+        if let Some(rest) = media.trim().strip_prefix(VENDOR_MEDIA_PREFIX) {
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            if let Ok(v) = digits.parse() {
+                return Ok(Some(v));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Middleware for `/api` requests:
+///
+/// 1. A version in the path wins; otherwise `X-API-Version` / `Accept` may pick one, and
+///    unsupported versions are refused with 406 listing what is available.
+/// 2. Every response carries `X-API-Version`.
+/// 3. Unversioned (legacy) paths are served by the current version but flagged with
+///    `Deprecation` and a `Link` to their versioned successor.
+pub async fn negotiate_version(req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    if !path.starts_with("/api/") {
+        return next.run(req).await;
+    }
+
+    let (path_version, unversioned) = split_version(&path);
+    let version = match path_version {
+        Some(v) => v,
+        None => match requested_version(&req) {
+            Ok(v) => v.unwrap_or(CURRENT_VERSION),
+            Err(e) => return e.into_response(),
+        },
+    };
+    if !SUPPORTED_VERSIONS.contains(&version) {
+        let supported: Vec<String> = SUPPORTED_VERSIONS.iter().map(|v| v.to_string()).collect();
+        return AppError::NotAcceptable(format!(
+            "API version {} is not supported (supported: {})", version, supported.join(", ")
+        )).into_response();
+    }
+
+    let mut resp = next.run(req).await;
+    let headers = resp.headers_mut();
+    headers.insert("x-api-version", HeaderValue::from(version));
+    if path_version.is_none() {
+        let successor = unversioned.replacen("/api", &format!("/api/v{}", version), 1);
+        headers.insert("deprecation", HeaderValue::from_static("true"));
+        if let Ok(link) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor)) {
+            headers.insert(header::LINK, link);
+        }
+    }
+    resp
+}
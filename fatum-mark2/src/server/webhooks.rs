@@ -0,0 +1,88 @@
+use axum::{extract::Path, Extension, Json};
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::auth::AuthUser;
+use super::error::{AppError, AppResult};
+use super::validation::{FieldError, ValidJson};
+use super::AppState;
+use crate::db::Webhook;
+use crate::services::webhooks::{self, Delivery, DeliveryOutcome, RetryPolicy};
+
+#[derive(Deserialize)]
+pub struct WebhookInput {
+    pub url: String,
+    /// Events to deliver (see `webhooks::EVENT_TYPES`); omitted or empty means all.
+    #[serde(default)]
+    pub events: Vec<String>,
+    pub enabled: Option<bool>,
+}
+
+/// Refuses a webhook URL whose host resolves to an internal address.
+pub async fn check_destination(field: &str, url: &str) -> AppResult<()> {
+    webhooks::resolve_destination(url).await.map(|_| ()).map_err(|e| {
+        AppError::Validation(vec![FieldError { field: field.to_string(), message: format!("{:#}", e) }])
+    })
+}
+
+async fn find(state: &AppState, id: i64, user: &AuthUser) -> AppResult<Webhook> {
+    state.db.get_webhook(id, user.id).await?
+        .ok_or_else(|| AppError::not_found(format!("Webhook {} not found", id)))
+}
+
+pub async fn list_webhooks(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<Json<Vec<Webhook>>> {
+    Ok(Json(state.db.list_webhooks(user.id).await?))
+}
+
+/// Registers a webhook. The response is the only time its signing secret is shown.
+pub async fn create_webhook(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    ValidJson(input): ValidJson<WebhookInput>,
+) -> AppResult<Json<serde_json::Value>> {
+    check_destination("url", input.url.trim()).await?;
+    let secret = webhooks::new_secret();
+    let id = state.db.create_webhook(user.id, input.url.trim(), &secret, &input.events, input.enabled.unwrap_or(true)).await?;
+    let mut body = serde_json::to_value(find(&state, id, &user).await?).unwrap();
+    body["secret"] = secret.into();
+    Ok(Json(body))
+}
+
+pub async fn update_webhook(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+    ValidJson(input): ValidJson<WebhookInput>,
+) -> AppResult<Json<Webhook>> {
+    check_destination("url", input.url.trim()).await?;
+    if !state.db.update_webhook(id, user.id, input.url.trim(), &input.events, input.enabled.unwrap_or(true)).await? {
+        return Err(AppError::not_found(format!("Webhook {} not found", id)));
+    }
+    Ok(Json(find(&state, id, &user).await?))
+}
+
+pub async fn delete_webhook(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    if !state.db.delete_webhook(id, user.id).await? {
+        return Err(AppError::not_found(format!("Webhook {} not found", id)));
+    }
+    Ok(Json(serde_json::json!({ "deleted": id })))
+}
+
+/// Sends a signed `ping` once, without retries, and reports how the receiver answered.
+pub async fn test_webhook(
+    Extension(state): Extension<AppState>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<i64>,
+) -> AppResult<Json<DeliveryOutcome>> {
+    let hook = find(&state, id, &user).await?;
+    let delivery = Delivery::new(webhooks::PING, serde_json::json!({ "webhook_id": hook.id }));
+    let policy = RetryPolicy { max_attempts: 1, base_delay: Duration::ZERO, ..RetryPolicy::default() };
+    Ok(Json(webhooks::send(&state.db, &hook, &delivery, policy).await))
+}
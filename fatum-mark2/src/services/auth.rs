@@ -0,0 +1,17 @@
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Prefix that makes keys easy to spot in configs and logs.
+pub const API_KEY_PREFIX: &str = "fm2_";
+
+/// Generates a new random API key (prefix + 32 bytes from the OS RNG, hex encoded).
+pub fn generate_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", API_KEY_PREFIX, hex::encode(bytes))
+}
+
+/// SHA-256 hex digest stored in place of the key.
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
@@ -0,0 +1,203 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::{watch, Mutex};
+use tracing::Instrument;
+
+use crate::db::Db;
+
+const FILE_PREFIX: &str = "fatum-";
+const FILE_SUFFIX: &str = ".db";
+
+lazy_static::lazy_static! {
+    static ref CONFIG: BackupConfig = BackupConfig::from_env();
+    // Backups and restores never overlap
+    static ref BACKUP_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Where backups go, how often they are taken and how many are kept.
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub dir: PathBuf,
+    /// `None` disables scheduled backups (manual ones still work).
+    pub interval: Option<Duration>,
+    pub keep: usize,
+}
+
+impl BackupConfig {
+    /// `FATUM_BACKUP_DIR` (default `backups`), `FATUM_BACKUP_INTERVAL_HOURS` (default 24,
+    /// 0 disables the schedule) and `FATUM_BACKUP_KEEP` (default 7).
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.parse().ok())
+        }
+        let hours: f64 = var("FATUM_BACKUP_INTERVAL_HOURS").filter(|h: &f64| h.is_finite()).unwrap_or(24.0);
+        Self {
+            dir: std::env::var("FATUM_BACKUP_DIR").map(PathBuf::from).unwrap_or_else(|_| PathBuf::from("backups")),
+            interval: (hours > 0.0).then(|| Duration::from_secs_f64(hours * 3600.0)),
+            keep: var("FATUM_BACKUP_KEEP").unwrap_or(7).max(1),
+        }
+    }
+}
+
+/// Server-wide settings, read once from the environment.
+pub fn config() -> &'static BackupConfig {
+    &CONFIG
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BackupInfo {
+    fn from_path(path: &Path) -> Result<Self> {
+        let meta = std::fs::metadata(path)?;
+        Ok(Self {
+            name: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            size_bytes: meta.len(),
+            created_at: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH).into(),
+        })
+    }
+}
+
+/// Only plain file names this module produced are accepted, so requests cannot
+/// point a restore anywhere else on disk.
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with(FILE_PREFIX)
+        && name.ends_with(FILE_SUFFIX)
+        && !name.contains(['/', '\\'])
+        && !name.contains("..")
+}
+
+/// Path of an existing backup, if `name` is one.
+pub fn find_backup(config: &BackupConfig, name: &str) -> Option<PathBuf> {
+    if !is_backup_name(name) {
+        return None;
+    }
+    let path = config.dir.join(name);
+    path.is_file().then_some(path)
+}
+
+/// Existing backups, newest first.
+pub fn list_backups(config: &BackupConfig) -> Result<Vec<BackupInfo>> {
+    let entries = match std::fs::read_dir(&config.dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut backups = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.file_name().and_then(|n| n.to_str()).is_some_and(is_backup_name) {
+            backups.push(BackupInfo::from_path(&path)?);
+        }
+    }
+    // Names embed a sortable UTC timestamp
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+/// Takes a snapshot now, then prunes old ones down to `config.keep`.
+///
+/// `label` is appended to the file name (e.g. `pre-restore`).
+pub async fn create_backup(db: &Db, config: &BackupConfig, label: Option<&str>) -> Result<BackupInfo> {
+    let _guard = BACKUP_LOCK.lock().await;
+    let info = write_snapshot(db, config, label).await?;
+    prune_logged(config)?;
+    Ok(info)
+}
+
+/// Writes a snapshot without pruning, so callers decide when old files may go.
+async fn write_snapshot(db: &Db, config: &BackupConfig, label: Option<&str>) -> Result<BackupInfo> {
+    std::fs::create_dir_all(&config.dir)?;
+    let stamp = Utc::now().format("%Y%m%d-%H%M%S%.3f");
+    let name = match label {
+        Some(label) => format!("{}{}-{}{}", FILE_PREFIX, stamp, label, FILE_SUFFIX),
+        None => format!("{}{}{}", FILE_PREFIX, stamp, FILE_SUFFIX),
+    };
+    let path = config.dir.join(&name);
+
+    db.backup_to(&path).await?;
+    let info = BackupInfo::from_path(&path)?;
+    tracing::info!(backup = %info.name, size_bytes = info.size_bytes, "Database backup written");
+    Ok(info)
+}
+
+fn prune_logged(config: &BackupConfig) -> Result<()> {
+    let removed = prune(config)?;
+    if removed > 0 {
+        tracing::info!(removed, keep = config.keep, "Pruned old backups");
+    }
+    Ok(())
+}
+
+/// Deletes all but the newest `config.keep` backups; returns how many went.
+fn prune(config: &BackupConfig) -> Result<usize> {
+    let backups = list_backups(config)?;
+    let mut removed = 0;
+    for old in backups.iter().skip(config.keep) {
+        std::fs::remove_file(config.dir.join(&old.name))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Restores the named backup over the live database.
+///
+/// A `pre-restore` snapshot of the current state is taken first and returned, so a
+/// mistaken restore can itself be undone. Pruning waits until the restore has
+/// succeeded, so restoring the oldest backup while retention is full still works.
+pub async fn restore_backup(db: &Db, config: &BackupConfig, path: &Path) -> Result<BackupInfo> {
+    let _guard = BACKUP_LOCK.lock().await;
+    let safety = write_snapshot(db, config, Some("pre-restore")).await?;
+    db.restore_from(path).await?;
+    tracing::warn!(backup = %path.display(), safety_backup = %safety.name, "Database restored from backup");
+    prune_logged(config)?;
+    Ok(safety)
+}
+
+/// Runs scheduled backups until shutdown.
+///
+/// The first one is due one interval after the newest existing backup, so frequent
+/// restarts neither skip nor pile up backups.
+pub fn spawn_scheduler(db: Arc<Db>, config: BackupConfig, mut shutdown: watch::Receiver<bool>) {
+    let Some(interval) = config.interval else {
+        tracing::info!("Scheduled backups disabled");
+        return;
+    };
+    if cfg!(feature = "postgres") {
+        tracing::info!("Scheduled backups are SQLite only; use pg_dump for PostgreSQL");
+        return;
+    }
+
+    let span = tracing::info_span!("backup_scheduler", dir = %config.dir.display());
+    tokio::spawn(async move {
+        let age = list_backups(&config)
+            .ok()
+            .and_then(|b| b.first().map(|newest| newest.created_at))
+            .and_then(|at| (Utc::now() - at).to_std().ok());
+        let mut delay = match age {
+            Some(age) => interval.saturating_sub(age),
+            None => Duration::ZERO,
+        };
+        tracing::info!(every_secs = interval.as_secs(), keep = config.keep, first_in_secs = delay.as_secs(), "Backup scheduler started");
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = shutdown.changed() => break,
+            }
+            if let Err(e) = create_backup(&db, &config, None).await {
+                tracing::error!(error = %e, "Scheduled backup failed");
+            }
+            delay = interval;
+        }
+    }.instrument(span));
+}
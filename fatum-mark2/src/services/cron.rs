@@ -0,0 +1,147 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, TimeZone, Timelike};
+
+/// How far ahead `next_after` looks before deciding an expression never fires (e.g. `0 0 30 2 *`).
+const SEARCH_DAYS: u32 = 366 * 5;
+
+/// A five-field cron expression: `minute hour day-of-month month day-of-week`.
+///
+/// Fields take `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/15`, `8-18/2`).
+/// Day-of-week runs 0-6 from Sunday (7 is Sunday too). As in classic cron, when both day
+/// fields are restricted a day matching either one fires. `@hourly`, `@daily`, `@weekly`,
+/// `@monthly` and `@yearly` are accepted as shorthands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!("expected 5 fields (minute hour day month weekday), got {}", fields.len());
+        };
+
+        let mut weekdays = parse_field(weekday, 0, 7).context("weekday")?;
+        // 7 is an alias for Sunday
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59).context("minute")?,
+            hours: parse_field(hour, 0, 23).context("hour")?,
+            days: parse_field(day, 1, 31).context("day")?,
+            months: parse_field(month, 1, 12).context("month")?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn matches_day(&self, date: chrono::NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, as wall-clock time.
+    pub fn next_naive_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date();
+        for day_offset in 0..SEARCH_DAYS {
+            if self.matches_day(date) {
+                // Only the first day starts part-way through
+                let (from_hour, from_minute) = if day_offset == 0 { (start.hour(), start.minute()) } else { (0, 0) };
+                for hour in from_hour..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+                    let first_minute = if hour == from_hour { from_minute } else { 0 };
+                    if let Some(minute) = (first_minute..60).find(|m| self.minutes & (1 << m) != 0) {
+                        return date.and_hms_opt(hour, minute, 0);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    /// The next run after `after`, read in `after`'s time zone.
+    ///
+    /// Wall-clock times skipped by a DST change are skipped; repeated ones fire once.
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let mut naive = after.naive_local();
+        loop {
+            naive = self.next_naive_after(naive)?;
+            if let Some(next) = tz.from_local_datetime(&naive).earliest() {
+                if next > *after {
+                    return Some(next);
+                }
+            }
+        }
+    }
+}
+
+/// Parses one field to a bitmask of the allowed values.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(|| anyhow!("bad step in {:?}", part))?),
+            None => (part, 1),
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((lo, hi)) => (parse_value(lo, min, max)?, parse_value(hi, min, max)?),
+                // `5/15` means from 5 to the end in steps of 15
+                None if part.contains('/') => (parse_value(range, min, max)?, max),
+                None => {
+                    let v = parse_value(range, min, max)?;
+                    (v, v)
+                }
+            },
+        };
+        if lo > hi {
+            bail!("range {:?} is backwards", part);
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32> {
+    let v: u32 = value.parse().map_err(|_| anyhow!("{:?} is not a number", value))?;
+    if v < min || v > max {
+        bail!("{} is outside {}-{}", v, min, max);
+    }
+    Ok(v)
+}
+
+#[cfg(test)]
+#[path = "cron_tests.rs"]
+mod tests;
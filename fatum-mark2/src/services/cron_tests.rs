@@ -0,0 +1,56 @@
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use super::CronSchedule;
+
+fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, 0).unwrap()
+}
+
+fn next(expr: &str, after: NaiveDateTime) -> NaiveDateTime {
+    CronSchedule::parse(expr).unwrap().next_naive_after(after).unwrap()
+}
+
+#[test]
+fn test_daily_at_six() {
+    assert_eq!(next("0 6 * * *", at(2024, 3, 1, 5, 59)), at(2024, 3, 1, 6, 0));
+    // Strictly after: a run at 06:00 schedules the next one for tomorrow
+    assert_eq!(next("0 6 * * *", at(2024, 3, 1, 6, 0)), at(2024, 3, 2, 6, 0));
+    assert_eq!(next("@daily", at(2024, 12, 31, 12, 0)), at(2025, 1, 1, 0, 0));
+}
+
+#[test]
+fn test_steps_ranges_and_lists() {
+    assert_eq!(next("*/15 * * * *", at(2024, 3, 1, 10, 7)), at(2024, 3, 1, 10, 15));
+    assert_eq!(next("30 8-18/4 * * *", at(2024, 3, 1, 12, 31)), at(2024, 3, 1, 16, 30));
+    assert_eq!(next("0 0 1,15 * *", at(2024, 3, 2, 0, 0)), at(2024, 3, 15, 0, 0));
+}
+
+#[test]
+fn test_weekdays() {
+    // 2024-03-01 is a Friday
+    assert_eq!(next("0 9 * * 1-5", at(2024, 3, 1, 10, 0)), at(2024, 3, 4, 9, 0));
+    assert_eq!(next("0 9 * * 7", at(2024, 3, 1, 10, 0)), at(2024, 3, 3, 9, 0), "7 is Sunday");
+    // Both day fields restricted: either one matches
+    assert_eq!(next("0 0 10 * 6", at(2024, 3, 1, 0, 0)), at(2024, 3, 2, 0, 0));
+}
+
+#[test]
+fn test_impossible_dates_never_fire() {
+    let feb30 = CronSchedule::parse("0 0 30 2 *").unwrap();
+    assert!(feb30.next_naive_after(at(2024, 1, 1, 0, 0)).is_none());
+    assert_eq!(next("0 0 29 2 *", at(2024, 3, 1, 0, 0)), at(2028, 2, 29, 0, 0));
+}
+
+#[test]
+fn test_rejects_bad_expressions() {
+    for bad in ["", "* * * *", "60 * * * *", "* 24 * * *", "0 0 0 * *", "*/0 * * * *", "5-1 * * * *", "a * * * *"] {
+        assert!(CronSchedule::parse(bad).is_err(), "{:?} should be rejected", bad);
+    }
+}
+
+#[test]
+fn test_next_after_in_time_zone() {
+    let after = Utc.with_ymd_and_hms(2024, 3, 1, 6, 30, 0).unwrap();
+    let next = CronSchedule::parse("0 6 * * *").unwrap().next_after(&after).unwrap();
+    assert_eq!(next, Utc.with_ymd_and_hms(2024, 3, 2, 6, 0, 0).unwrap());
+}
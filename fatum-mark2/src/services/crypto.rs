@@ -0,0 +1,125 @@
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+/// Marks a value as sealed by this module; anything else is treated as legacy plaintext.
+const SEALED_PREFIX: &str = "enc:v1:";
+
+lazy_static::lazy_static! {
+    static ref CIPHER: Result<Option<FieldCipher>, String> = FieldCipher::from_env().map_err(|e| format!("{:#}", e));
+}
+
+/// AES-256-GCM for individual column values.
+///
+/// Each value gets a fresh random nonce and is bound to a context string (the column it
+/// belongs in), so ciphertext copied into another column will not decrypt.
+pub struct FieldCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl FieldCipher {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        let key = UnboundKey::new(&AES_256_GCM, key).map_err(|_| anyhow!("Encryption key must be 32 bytes"))?;
+        Ok(Self { key: LessSafeKey::new(key), rng: SystemRandom::new() })
+    }
+
+    /// Key from `FATUM_ENCRYPTION_KEY` (64 hex chars or base64), or from the file named by
+    /// `FATUM_ENCRYPTION_KEY_FILE`. `None` when neither is set: values are stored in clear.
+    pub fn from_env() -> Result<Option<Self>> {
+        let encoded = match (std::env::var("FATUM_ENCRYPTION_KEY"), std::env::var("FATUM_ENCRYPTION_KEY_FILE")) {
+            (Ok(key), _) => key,
+            (Err(_), Ok(path)) => std::fs::read_to_string(&path).with_context(|| format!("Reading encryption key file {}", path))?,
+            _ => return Ok(None),
+        };
+        Self::new(&decode_key(encoded.trim())?).map(Some)
+    }
+
+    /// Encrypts bytes to `nonce || ciphertext || tag`.
+    pub fn seal_bytes(&self, context: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng.fill(&mut nonce).map_err(|_| anyhow!("System RNG unavailable"))?;
+        let mut buf = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(context.as_bytes()), &mut buf)
+            .map_err(|_| anyhow!("Encryption failed"))?;
+
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&buf);
+        Ok(out)
+    }
+
+    pub fn open_bytes(&self, context: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            bail!("Sealed value is truncated");
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("Bad nonce"))?;
+        let mut buf = ciphertext.to_vec();
+        let plaintext = self.key
+            .open_in_place(nonce, Aad::from(context.as_bytes()), &mut buf)
+            .map_err(|_| anyhow!("Could not decrypt {} (wrong key or corrupted data)", context))?;
+        Ok(plaintext.to_vec())
+    }
+
+    /// Text form of `seal_bytes`, for TEXT columns.
+    pub fn seal(&self, context: &str, plaintext: &str) -> Result<String> {
+        let sealed = self.seal_bytes(context, plaintext.as_bytes())?;
+        Ok(format!("{}{}", SEALED_PREFIX, BASE64.encode(sealed)))
+    }
+
+    /// Decrypts a sealed value; unsealed values are returned unchanged.
+    pub fn open(&self, context: &str, stored: &str) -> Result<String> {
+        let Some(encoded) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let data = BASE64.decode(encoded).context("Sealed value is not valid base64")?;
+        Ok(String::from_utf8(self.open_bytes(context, &data)?)?)
+    }
+}
+
+fn decode_key(encoded: &str) -> Result<Vec<u8>> {
+    if encoded.len() == 64 {
+        if let Ok(bytes) = hex::decode(encoded) {
+            return Ok(bytes);
+        }
+    }
+    BASE64.decode(encoded).context("Encryption key must be 64 hex characters or base64")
+}
+
+/// The server-wide cipher, `None` when encryption is not configured.
+///
+/// Fails (on every call) if a key was configured but could not be loaded, so a
+/// misconfigured server never silently falls back to writing plaintext.
+pub fn cipher() -> Result<Option<&'static FieldCipher>> {
+    match &*CIPHER {
+        Ok(cipher) => Ok(cipher.as_ref()),
+        Err(e) => bail!("Invalid encryption key: {}", e),
+    }
+}
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+/// Seals `plaintext` if encryption is configured, otherwise stores it as is.
+pub fn seal(context: &str, plaintext: &str) -> Result<String> {
+    match cipher()? {
+        Some(c) => c.seal(context, plaintext),
+        None => Ok(plaintext.to_string()),
+    }
+}
+
+/// Reverses `seal`. Sealed values without a configured key are an error.
+pub fn open(context: &str, stored: &str) -> Result<String> {
+    match cipher()? {
+        Some(c) => c.open(context, stored),
+        None if is_sealed(stored) => bail!("{} is encrypted but no FATUM_ENCRYPTION_KEY is configured", context),
+        None => Ok(stored.to_string()),
+    }
+}
+
+#[cfg(test)]
+#[path = "crypto_tests.rs"]
+mod tests;
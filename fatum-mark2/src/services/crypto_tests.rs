@@ -0,0 +1,44 @@
+use super::FieldCipher;
+
+fn cipher() -> FieldCipher {
+    FieldCipher::new(&[7u8; 32]).unwrap()
+}
+
+#[test]
+fn test_seal_roundtrip_uses_fresh_nonces() {
+    let c = cipher();
+    let a = c.seal("profiles.birth_data", "1990-05-17").unwrap();
+    let b = c.seal("profiles.birth_data", "1990-05-17").unwrap();
+    assert!(a.starts_with("enc:v1:"));
+    assert_ne!(a, b, "same plaintext must not produce the same ciphertext");
+    assert!(!a.contains("1990"));
+    assert_eq!(c.open("profiles.birth_data", &a).unwrap(), "1990-05-17");
+}
+
+#[test]
+fn test_open_rejects_wrong_context_and_key() {
+    let sealed = cipher().seal("profiles.birth_data", "secret").unwrap();
+    assert!(cipher().open("quantum_entropy_data.hex_value", &sealed).is_err());
+    assert!(FieldCipher::new(&[8u8; 32]).unwrap().open("profiles.birth_data", &sealed).is_err());
+}
+
+#[test]
+fn test_plaintext_passes_through() {
+    assert_eq!(cipher().open("quantum_entropy_data.hex_value", "deadbeef").unwrap(), "deadbeef");
+}
+
+#[test]
+fn test_key_must_be_32_bytes() {
+    assert!(FieldCipher::new(&[0u8; 16]).is_err());
+    assert_eq!(super::decode_key(&"ab".repeat(32)).unwrap(), vec![0xab; 32]);
+}
+
+#[test]
+fn test_seal_bytes_roundtrip() {
+    let c = cipher();
+    let pulse: Vec<u8> = (0..64).collect();
+    let sealed = c.seal_bytes("quantum_entropy_data.pulse", &pulse).unwrap();
+    assert_eq!(sealed.len(), pulse.len() + 12 + 16, "nonce and tag overhead only");
+    assert_eq!(c.open_bytes("quantum_entropy_data.pulse", &sealed).unwrap(), pulse);
+    assert!(c.open_bytes("quantum_entropy_data.pulse", &sealed[..8]).is_err());
+}
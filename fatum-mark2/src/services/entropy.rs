@@ -1,33 +1,43 @@
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
 use crate::client::CurbyClient;
-use crate::db::Db;
+use crate::db::{Db, User};
+use crate::services::events::{self, EventSender, ServerEvent};
 use std::time::Duration;
-use hex;
+use tracing::Instrument;
 
 lazy_static::lazy_static! {
-    static ref HARVESTER_CONTROL: Arc<Mutex<Option<i64>>> = Arc::new(Mutex::new(None));
+    // (batch, owner) of the running harvest
+    static ref HARVESTER_CONTROL: Arc<Mutex<Option<(i64, i64)>>> = Arc::new(Mutex::new(None));
+    // Cuts the 60 s wait short when the harvester is stopped
+    static ref HARVESTER_WAKE: Notify = Notify::new();
+    static ref HARVESTER_TASK: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
 }
 
-pub async fn start_harvesting(db: Arc<Db>, batch_id: i64) {
+/// Starts harvesting into `batch_id`. Returns false if a harvest is already running.
+pub async fn start_harvesting(db: Arc<Db>, batch_id: i64, owner_id: i64, events: EventSender) -> bool {
     let mut lock = HARVESTER_CONTROL.lock().await;
     if lock.is_some() {
-        println!("Harvester already running for batch {:?}", *lock);
-        return;
+        tracing::warn!(running_batch = ?*lock, "Harvester already running");
+        return false;
     }
-    *lock = Some(batch_id);
+    *lock = Some((batch_id, owner_id));
     drop(lock);
+    // Also resumes batches left `paused` by a shutdown
+    let _ = db.update_batch_status(batch_id, "collecting").await;
+    events::emit(&events, ServerEvent::HarvestStarted { batch_id, owner_id });
 
-    tokio::spawn(async move {
+    let handle = tokio::spawn(async move {
         let mut client = CurbyClient::new();
-        println!("Starting Quantum Harvesting for Batch {}", batch_id);
+        tracing::info!("Starting quantum harvesting");
 
         loop {
             // Check if we should stop
             {
                 let lock = HARVESTER_CONTROL.lock().await;
-                if *lock != Some(batch_id) {
-                    println!("Stopping Harvester for Batch {}", batch_id);
+                if *lock != Some((batch_id, owner_id)) {
+                    tracing::info!("Stopping harvester");
                     break;
                 }
             }
@@ -45,36 +55,76 @@ pub async fn start_harvesting(db: Arc<Db>, batch_id: i64) {
 
             match client.fetch_raw_entropy().await {
                 Ok(bytes) => {
-                    let hex_val = hex::encode(&bytes);
                     // Get round info if possible? Currently client hides it.
                     // For now just save data.
-                    if let Err(e) = db.insert_entropy(batch_id, None, &hex_val).await {
-                         eprintln!("Failed to save entropy: {}", e);
+                    if let Err(e) = db.insert_entropy(batch_id, None, &bytes).await {
+                         tracing::error!(error = %e, "Failed to save entropy");
+                         events::emit(&events, ServerEvent::HarvestError { batch_id, owner_id, error: e.to_string() });
                     } else {
-                        println!("Harvested 512 bits for Batch {}", batch_id);
+                        tracing::info!(bytes = bytes.len(), "Harvested pulse");
+                        events::emit(&events, ServerEvent::HarvestPulse { batch_id, owner_id, bytes: bytes.len() });
                     }
                 },
                 Err(e) => {
-                    eprintln!("Harvest Error: {}", e);
+                    tracing::warn!(error = %e, "Harvest failed");
+                    events::emit(&events, ServerEvent::HarvestError { batch_id, owner_id, error: e.to_string() });
                 }
             }
 
             // Wait 60 seconds (beacon interval)
-            tokio::time::sleep(Duration::from_secs(60)).await;
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(60)) => {}
+                _ = HARVESTER_WAKE.notified() => {}
+            }
         }
-    });
+    }.instrument(tracing::info_span!("harvester", batch_id)));
+    *HARVESTER_TASK.lock().await = Some(handle);
+    true
 }
 
-pub async fn stop_harvesting(db: Arc<Db>) {
+/// Stops the running harvest and marks its batch `completed`, if `user` owns it (or is an admin).
+///
+/// Returns false, leaving any other user's harvest running, if nothing was stopped.
+pub async fn stop_harvesting(db: Arc<Db>, user: &User, events: &EventSender) -> bool {
     let mut lock = HARVESTER_CONTROL.lock().await;
-    if let Some(bid) = *lock {
-        // Update batch status
-        let _ = db.update_batch_status(bid, "completed").await;
+    let Some((bid, owner_id)) = *lock else { return false };
+    if owner_id != user.id && !user.is_admin {
+        return false;
     }
     *lock = None;
+    // Update batch status
+    let _ = db.update_batch_status(bid, "completed").await;
+    events::emit(events, ServerEvent::HarvestStopped { batch_id: bid, owner_id });
+    HARVESTER_WAKE.notify_one();
+    true
+}
+
+/// Stops the harvester for server shutdown.
+///
+/// Lets a pulse that is being fetched or saved finish (up to `timeout`), then marks the
+/// batch `paused` rather than `completed` since collection was cut short.
+pub async fn shutdown_harvesting(db: Arc<Db>, events: &EventSender, timeout: Duration) {
+    let batch_id = HARVESTER_CONTROL.lock().await.take();
+    let Some((bid, owner_id)) = batch_id else { return };
+    HARVESTER_WAKE.notify_one();
+
+    if let Some(handle) = HARVESTER_TASK.lock().await.take() {
+        if tokio::time::timeout(timeout, handle).await.is_err() {
+            tracing::warn!(batch_id = bid, ?timeout, "Harvester did not stop in time");
+        }
+    }
+    if let Err(e) = db.update_batch_status(bid, "paused").await {
+        tracing::error!(batch_id = bid, error = %e, "Failed to mark batch as paused");
+    }
+    events::emit(events, ServerEvent::HarvestStopped { batch_id: bid, owner_id });
 }
 
-pub async fn get_harvest_status() -> Option<i64> {
+/// The batch being harvested, if `owner_id` started it.
+pub async fn get_harvest_status(owner_id: i64) -> Option<i64> {
     let lock = HARVESTER_CONTROL.lock().await;
-    *lock
+    lock.filter(|&(_, owner)| owner == owner_id).map(|(batch_id, _)| batch_id)
 }
+
+#[cfg(test)]
+#[path = "entropy_tests.rs"]
+mod tests;
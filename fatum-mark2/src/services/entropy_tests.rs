@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use super::{get_harvest_status, stop_harvesting, HARVESTER_CONTROL};
+use crate::db::{Db, User};
+use crate::services::events;
+
+fn user(id: i64, is_admin: bool) -> User {
+    User { id, username: format!("user{}", id), is_admin, created_at: None }
+}
+
+#[tokio::test]
+async fn test_only_the_owner_sees_and_stops_a_harvest() {
+    let db = Arc::new(Db::in_memory().await.unwrap());
+    let (alice, bob) = (user(1, false), user(2, false));
+    let batch = db.create_batch("night", None).await.unwrap();
+    let events = events::channel();
+    // Stands in for a running harvester without fetching from the beacon
+    *HARVESTER_CONTROL.lock().await = Some((batch, alice.id));
+
+    assert_eq!(get_harvest_status(alice.id).await, Some(batch));
+    assert_eq!(get_harvest_status(bob.id).await, None);
+    assert!(!stop_harvesting(db.clone(), &bob, &events).await);
+    assert_eq!(*HARVESTER_CONTROL.lock().await, Some((batch, alice.id)));
+    assert_eq!(db.get_batch(batch).await.unwrap().status, "collecting");
+
+    assert!(stop_harvesting(db.clone(), &alice, &events).await);
+    assert_eq!(db.get_batch(batch).await.unwrap().status, "completed");
+
+    // Admins can stop anyone's harvest
+    *HARVESTER_CONTROL.lock().await = Some((batch, alice.id));
+    assert!(stop_harvesting(db, &user(3, true), &events).await);
+    assert!(HARVESTER_CONTROL.lock().await.is_none());
+}
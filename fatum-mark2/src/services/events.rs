@@ -0,0 +1,56 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+use crate::engine::{SimulationReport, TimeStep};
+
+/// How many events a slow `/ws` client may fall behind before it starts missing some.
+const EVENT_BUFFER: usize = 1024;
+/// |Z| at which a finished simulation raises `AnomalyDetected`, whatever its own threshold.
+pub const ANOMALY_ALERT_Z: f64 = 3.0;
+
+/// Server-wide notifications pushed to `/ws` subscribers.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    HarvestStarted { batch_id: i64, owner_id: i64 },
+    HarvestPulse { batch_id: i64, owner_id: i64, bytes: usize },
+    HarvestError { batch_id: i64, owner_id: i64, error: String },
+    HarvestStopped { batch_id: i64, owner_id: i64 },
+    JobProgress { job_id: u64, owner_id: i64, step: TimeStep },
+    JobDone { job_id: u64, owner_id: i64, report: SimulationReport },
+    JobFailed { job_id: u64, owner_id: i64, error: String },
+    HistoryCreated { id: i64, owner_id: i64, profile_id: Option<i64>, tool_type: String, summary: String },
+    HistoryDeleted { id: i64, owner_id: i64 },
+    ScheduleRun { schedule_id: i64, owner_id: i64, name: String, history_id: Option<i64>, error: Option<String> },
+    AnomalyDetected { job_id: u64, owner_id: i64, option: String, z_score: f64 },
+}
+
+impl ServerEvent {
+    /// The user this event belongs to; only they are sent it over `/ws`.
+    pub fn owner_id(&self) -> i64 {
+        match self {
+            ServerEvent::HarvestStarted { owner_id, .. }
+            | ServerEvent::HarvestPulse { owner_id, .. }
+            | ServerEvent::HarvestError { owner_id, .. }
+            | ServerEvent::HarvestStopped { owner_id, .. }
+            | ServerEvent::JobProgress { owner_id, .. }
+            | ServerEvent::JobDone { owner_id, .. }
+            | ServerEvent::JobFailed { owner_id, .. }
+            | ServerEvent::HistoryCreated { owner_id, .. }
+            | ServerEvent::HistoryDeleted { owner_id, .. }
+            | ServerEvent::ScheduleRun { owner_id, .. }
+            | ServerEvent::AnomalyDetected { owner_id, .. } => *owner_id,
+        }
+    }
+}
+
+pub type EventSender = broadcast::Sender<ServerEvent>;
+
+/// Creates the broadcast channel shared through `AppState`.
+pub fn channel() -> EventSender {
+    broadcast::channel(EVENT_BUFFER).0
+}
+
+/// Sends an event, ignoring the error raised when nobody is listening.
+pub fn emit(tx: &EventSender, event: ServerEvent) {
+    let _ = tx.send(event);
+}
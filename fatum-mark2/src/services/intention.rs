@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use crate::engine::{bind_entropy, context_digest};
+use crate::tools::divination::QUESTION_DOMAIN;
+
+/// Binder for Feng Shui intentions ("Wealth", "Love", ...).
+pub const FENG_SHUI: IntentionBinder = IntentionBinder::new("fatum-fengshui-v1");
+/// Binder for divination questions; keeps the domain casts were bound under before the service existed.
+pub const DIVINATION: IntentionBinder = IntentionBinder::new(QUESTION_DOMAIN);
+/// Binder for the question a decision is put to.
+pub const DECISION: IntentionBinder = IntentionBinder::new("fatum-decision-v1");
+/// Binder for the intention a geolocation outing is set with.
+pub const GEOLOCATION: IntentionBinder = IntentionBinder::new("fatum-geolocation-v1");
+
+/// What was bound into a run's entropy, recorded with its result so the run can be replayed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntentionBinding {
+    /// The intention as bound, trimmed.
+    pub intention: String,
+    pub domain: String,
+    /// Hex SHA-256 of the intention under `domain`, the key the entropy was bound with.
+    pub digest: String,
+}
+
+/// Ties a user's stated intention to the quantum entropy before it seeds a session.
+///
+/// Every tool binds under its own domain, so the same intention and beacon bytes give
+/// unrelated draws in different tools.
+#[derive(Debug, Clone, Copy)]
+pub struct IntentionBinder {
+    domain: &'static str,
+}
+
+impl IntentionBinder {
+    pub const fn new(domain: &'static str) -> Self {
+        Self { domain }
+    }
+
+    pub fn domain(&self) -> &'static str {
+        self.domain
+    }
+
+    /// Binds `intention` into `entropy`, returning the bytes with the binding to record.
+    /// Blank or missing intentions leave the entropy untouched.
+    pub fn bind(&self, entropy: Vec<u8>, intention: Option<&str>) -> (Vec<u8>, Option<IntentionBinding>) {
+        match intention.map(str::trim).filter(|i| !i.is_empty()) {
+            Some(i) => (
+                bind_entropy(&entropy, self.domain, i),
+                Some(IntentionBinding {
+                    intention: i.to_string(),
+                    domain: self.domain.to_string(),
+                    digest: hex::encode(context_digest(self.domain, i)),
+                }),
+            ),
+            None => (entropy, None),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "intention_tests.rs"]
+mod tests;
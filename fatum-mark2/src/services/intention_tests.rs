@@ -0,0 +1,40 @@
+use super::{DECISION, DIVINATION, FENG_SHUI, GEOLOCATION};
+use crate::tools::divination::bind_question;
+
+fn entropy() -> Vec<u8> {
+    (0..100u8).collect()
+}
+
+#[test]
+fn test_blank_intentions_leave_entropy_alone() {
+    for intention in [None, Some(""), Some("   ")] {
+        let (bytes, binding) = FENG_SHUI.bind(entropy(), intention);
+        assert_eq!(bytes, entropy());
+        assert!(binding.is_none());
+    }
+}
+
+#[test]
+fn test_binding_is_trimmed_and_domain_separated() {
+    let (bytes, binding) = DECISION.bind(entropy(), Some("  Take the job?  "));
+    let binding = binding.unwrap();
+    assert_eq!(bytes.len(), 100);
+    assert_ne!(bytes, entropy());
+    assert_eq!(binding.intention, "Take the job?");
+    assert_eq!(binding.domain, "fatum-decision-v1");
+    assert_eq!(binding.digest.len(), 64);
+    assert_eq!(DECISION.bind(entropy(), Some("Take the job?")), (bytes.clone(), Some(binding.clone())));
+
+    // Same words, other tools: unrelated bytes and keys
+    for other in [FENG_SHUI, DIVINATION, GEOLOCATION] {
+        let (other_bytes, other_binding) = other.bind(entropy(), Some("Take the job?"));
+        assert_ne!(other_bytes, bytes);
+        assert_ne!(other_binding.unwrap().digest, binding.digest);
+    }
+}
+
+#[test]
+fn test_divination_binding_is_unchanged() {
+    let (bytes, binding) = DIVINATION.bind(entropy(), Some("Should I move?"));
+    assert_eq!(bind_question(entropy(), Some("Should I move?")), (bytes, binding.map(|b| b.digest)));
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use crate::db::User;
+use crate::engine::{SimulationReport, TimeStep};
+
+/// How long a finished job's events stay available for late subscribers.
+const JOB_RETENTION: Duration = Duration::from_secs(600);
+
+/// A progress event emitted by a background simulation job.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum JobEvent {
+    Step(TimeStep),
+    Done(SimulationReport),
+    Failed(String),
+}
+
+impl JobEvent {
+    /// SSE event name for this payload.
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobEvent::Step(_) => "step",
+            JobEvent::Done(_) => "done",
+            JobEvent::Failed(_) => "error",
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        !matches!(self, JobEvent::Step(_))
+    }
+}
+
+struct Job {
+    owner_id: Option<i64>,
+    // The original request, kept so an interrupted job can be persisted and resubmitted.
+    request: serde_json::Value,
+    // Full event log so subscribers that connect late can replay what they missed.
+    events: Vec<JobEvent>,
+    // Dropped once the job finishes, which closes every live subscription.
+    tx: Option<broadcast::Sender<JobEvent>>,
+}
+
+lazy_static::lazy_static! {
+    static ref JOBS: Arc<Mutex<HashMap<u64, Job>>> = Arc::new(Mutex::new(HashMap::new()));
+}
+
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A job that was still running when the server shut down.
+#[derive(Debug, Clone)]
+pub struct InterruptedJob {
+    pub job_id: u64,
+    pub owner_id: Option<i64>,
+    pub request: serde_json::Value,
+}
+
+/// Registers a new job and returns its id.
+pub fn create_job(owner_id: Option<i64>, request: serde_json::Value) -> u64 {
+    let id = NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed);
+    let (tx, _) = broadcast::channel(256);
+    JOBS.lock().unwrap().insert(id, Job { owner_id, request, events: Vec::new(), tx: Some(tx) });
+    id
+}
+
+/// Number of jobs that have not published a terminal event yet.
+pub fn running_count() -> usize {
+    JOBS.lock().unwrap().values().filter(|j| j.tx.is_some()).count()
+}
+
+/// Waits until every job has finished or `timeout` passes; returns true if all finished.
+pub async fn wait_idle(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while running_count() > 0 {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    true
+}
+
+/// Fails every running job with `reason` (closing their subscriptions) and returns them.
+pub fn interrupt_running(reason: &str) -> Vec<InterruptedJob> {
+    let running: Vec<InterruptedJob> = JOBS.lock().unwrap().iter()
+        .filter(|(_, j)| j.tx.is_some())
+        .map(|(id, j)| InterruptedJob { job_id: *id, owner_id: j.owner_id, request: j.request.clone() })
+        .collect();
+    for job in &running {
+        publish(job.job_id, JobEvent::Failed(reason.to_string()));
+    }
+    running
+}
+
+/// Records an event for `job_id` and forwards it to live subscribers.
+///
+/// Safe to call from blocking threads. A terminal event closes the job and
+/// schedules its removal after `JOB_RETENTION`.
+pub fn publish(job_id: u64, event: JobEvent) {
+    let terminal = event.is_terminal();
+    {
+        let mut jobs = JOBS.lock().unwrap();
+        let Some(job) = jobs.get_mut(&job_id) else { return };
+        if job.tx.is_none() {
+            return; // Already finished
+        }
+        job.events.push(event.clone());
+        if let Some(tx) = &job.tx {
+            let _ = tx.send(event);
+        }
+        if terminal {
+            job.tx = None;
+        }
+    }
+
+    if terminal {
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move {
+                tokio::time::sleep(JOB_RETENTION).await;
+                JOBS.lock().unwrap().remove(&job_id);
+            });
+        }
+    }
+}
+
+/// Returns the events recorded so far plus a receiver for the rest.
+///
+/// For finished jobs the receiver is already closed. Returns `None` for unknown ids
+/// and for jobs `user` did not start, so other users' jobs look the same as missing ones.
+pub fn subscribe(job_id: u64, user: &User) -> Option<(Vec<JobEvent>, broadcast::Receiver<JobEvent>)> {
+    let jobs = JOBS.lock().unwrap();
+    let job = jobs.get(&job_id).filter(|j| j.owner_id == Some(user.id))?;
+    let rx = match &job.tx {
+        Some(tx) => tx.subscribe(),
+        None => {
+            let (tx, rx) = broadcast::channel(1);
+            drop(tx);
+            rx
+        }
+    };
+    Some((job.events.clone(), rx))
+}
+
+/// Convenience for engine callbacks: publishes a `TimeStep` as a `Step` event.
+pub fn step_publisher(job_id: u64) -> impl FnMut(&TimeStep) {
+    move |step| publish(job_id, JobEvent::Step(step.clone()))
+}
+
+#[cfg(test)]
+#[path = "jobs_tests.rs"]
+mod tests;
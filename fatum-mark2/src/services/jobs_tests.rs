@@ -0,0 +1,18 @@
+use super::{create_job, publish, subscribe, JobEvent};
+use crate::db::User;
+
+fn user(id: i64) -> User {
+    User { id, username: format!("user{}", id), is_admin: false, created_at: None }
+}
+
+#[test]
+fn test_subscribe_only_for_job_owner() {
+    let job_id = create_job(Some(1), serde_json::json!({}));
+    publish(job_id, JobEvent::Failed("stopped".to_string()));
+
+    let (backlog, _) = subscribe(job_id, &user(1)).unwrap();
+    assert_eq!(backlog.len(), 1);
+    assert!(subscribe(job_id, &user(2)).is_none());
+    // Admins get no exception either
+    assert!(subscribe(job_id, &User { is_admin: true, ..user(3) }).is_none());
+}
@@ -0,0 +1,70 @@
+use crate::engine::stats::{self, OptionStats};
+use crate::engine::{SimulationOptions, SimulationReport, SimulationSession};
+
+/// Nibble values the volatility test counts (each byte gives two).
+const NIBBLES: usize = 16;
+
+/// What a tool's quantum pass found: the option in focus, how unruly the entropy behind it
+/// was, and which options it drew more or less often than their weights predict.
+#[derive(Debug, Clone)]
+pub struct QuantumFocus {
+    /// The most drawn option; `None` when there were no options.
+    pub focus: Option<usize>,
+    /// 0 to 1; see `volatility_index`.
+    pub volatility: f64,
+    /// Options past the anomaly thresholds of the run's `SimulationOptions`, in option order.
+    pub deviations: Vec<OptionStats>,
+    pub report: SimulationReport,
+}
+
+impl QuantumFocus {
+    /// Name of the option in focus, or "None".
+    pub fn focus_name(&self) -> &str {
+        &self.report.winner
+    }
+}
+
+/// Runs a weighted simulation over a tool's options and reads focus, volatility and anomalies off it.
+///
+/// Volatility is measured on the pool bytes the run consumed; a run served entirely by the
+/// fallback stream is measured on the spread of its draws instead.
+pub fn analyze(
+    session: &mut SimulationSession,
+    options: &[String],
+    weights: Option<&[f64]>,
+    simulations: usize,
+    sim_options: &SimulationOptions,
+) -> QuantumFocus {
+    let report = session.simulate_decision(options, weights, simulations, sim_options);
+    let consumed: Vec<u8> = report.entropy_ranges.iter()
+        .flat_map(|r| session.entropy_pool[r.start..r.end].iter().copied())
+        .collect();
+    let volatility = volatility_index(&consumed)
+        .or_else(|| report.chi_square.as_ref().map(|c| 1.0 - c.p_value))
+        .unwrap_or(0.0);
+    QuantumFocus {
+        focus: options.iter().position(|o| *o == report.winner),
+        volatility,
+        deviations: report.option_stats.iter().filter(|s| sim_options.is_anomaly(s)).cloned().collect(),
+        report,
+    }
+}
+
+/// How far `bytes` stray from uniform noise: one minus the p-value of a chi-square test on
+/// their nibbles. Noise lands anywhere in [0, 1] with equal odds (0.5 on average); values
+/// near 1 mean skewed entropy. `None` for no bytes.
+pub fn volatility_index(bytes: &[u8]) -> Option<f64> {
+    if bytes.is_empty() {
+        return None;
+    }
+    let mut counts = [0usize; NIBBLES];
+    for &b in bytes {
+        counts[(b >> 4) as usize] += 1;
+        counts[(b & 0x0f) as usize] += 1;
+    }
+    stats::chi_square_test(&counts, &[1.0 / NIBBLES as f64; NIBBLES]).map(|c| 1.0 - c.p_value)
+}
+
+#[cfg(test)]
+#[path = "quantum_tests.rs"]
+mod tests;
@@ -0,0 +1,34 @@
+use super::{analyze, volatility_index};
+use crate::engine::{SimulationOptions, SimulationSession};
+
+#[test]
+fn test_volatility_of_uniform_and_stuck_bytes() {
+    assert_eq!(volatility_index(&[]), None);
+    // Every nibble equally often: textbook noise
+    let even: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+    assert!(volatility_index(&even).unwrap() < 1e-9);
+    let stuck = vec![0x11u8; 4096];
+    assert!(volatility_index(&stuck).unwrap() > 0.999);
+}
+
+#[test]
+fn test_analyze_picks_focus_and_flags_deviations() {
+    let options: Vec<String> = ["North", "South", "East"].iter().map(|s| s.to_string()).collect();
+    // A stuck pool always draws the first option
+    let mut session = SimulationSession::new(vec![0; 800]);
+    let focus = analyze(&mut session, &options, None, 100, &SimulationOptions::default());
+    assert_eq!(focus.focus, Some(0));
+    assert_eq!(focus.focus_name(), "North");
+    assert!(focus.volatility > 0.999);
+    assert_eq!(focus.deviations.len(), 3);
+    assert_eq!(focus.report.entropy_ranges[0].end, 800);
+
+    // Nothing left in the pool: volatility comes from the spread of the fallback draws
+    let focus = analyze(&mut session, &options, None, 300, &SimulationOptions::default());
+    assert!(focus.report.entropy_ranges.is_empty());
+    assert!((0.0..=1.0).contains(&focus.volatility));
+
+    let empty = analyze(&mut session, &[], None, 10, &SimulationOptions::default());
+    assert_eq!(empty.focus, None);
+    assert_eq!(empty.volatility, 0.0);
+}
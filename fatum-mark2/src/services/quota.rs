@@ -0,0 +1,129 @@
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+use anyhow::Result;
+use crate::db::Db;
+
+/// Daily allowance applied to profiles without an explicit `entropy_quota_bytes` (1 MiB).
+pub const DEFAULT_DAILY_QUOTA_BYTES: i64 = 1024 * 1024;
+
+lazy_static::lazy_static! {
+    // Serializes check-and-record so concurrent requests can't both slip under the limit.
+    static ref QUOTA_LOCK: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
+}
+
+/// Snapshot of a profile's (or, for draws that name no profile, a user's) entropy
+/// allowance for the current UTC day.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i64>,
+    pub usage_date: NaiveDate,
+    pub daily_quota_bytes: i64,
+    pub used_bytes: i64,
+    pub remaining_bytes: i64,
+}
+
+/// Returned (wrapped in `anyhow::Error`) when a draw would exceed the allowance.
+#[derive(Debug, Clone)]
+pub struct QuotaExceeded {
+    pub status: QuotaStatus,
+    pub requested_bytes: i64,
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whose = match (self.status.profile_id, self.status.user_id) {
+            (Some(pid), _) => format!("profile {}", pid),
+            (None, Some(uid)) => format!("user {}", uid),
+            (None, None) => "request".to_string(),
+        };
+        write!(
+            f,
+            "Entropy quota exceeded for {}: requested {} bytes, {} of {} remaining today",
+            whose, self.requested_bytes, self.status.remaining_bytes, self.status.daily_quota_bytes
+        )
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+/// Server-wide default allowance, overridable with `FATUM_DAILY_ENTROPY_QUOTA` (bytes).
+pub fn default_daily_quota() -> i64 {
+    std::env::var("FATUM_DAILY_ENTROPY_QUOTA")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_DAILY_QUOTA_BYTES)
+}
+
+pub async fn get_quota_status(db: &Db, profile_id: i64) -> Result<QuotaStatus> {
+    let today = Utc::now().date_naive();
+    let quota = db.get_profile_quota(profile_id).await?.unwrap_or_else(default_daily_quota);
+    let used = db.get_entropy_usage(profile_id, today).await?;
+    Ok(QuotaStatus {
+        profile_id: Some(profile_id),
+        user_id: None,
+        usage_date: today,
+        daily_quota_bytes: quota,
+        used_bytes: used,
+        remaining_bytes: (quota - used).max(0),
+    })
+}
+
+/// Charges `bytes` against the profile's daily allowance and against its owner's.
+///
+/// The owner's allowance caps every profile they hold, so a raised or re-created
+/// profile can't draw more than the user could directly. Fails with `QuotaExceeded`
+/// (nothing is recorded) if the draw does not fit either allowance.
+pub async fn consume(db: &Db, profile_id: i64, owner_id: i64, bytes: i64) -> Result<QuotaStatus> {
+    let _guard = QUOTA_LOCK.lock().await;
+    let mut status = get_quota_status(db, profile_id).await?;
+    if bytes > status.remaining_bytes {
+        return Err(QuotaExceeded { status, requested_bytes: bytes }.into());
+    }
+    let owner = get_user_quota_status(db, owner_id).await?;
+    if bytes > owner.remaining_bytes {
+        return Err(QuotaExceeded { status: owner, requested_bytes: bytes }.into());
+    }
+    db.add_entropy_usage(profile_id, status.usage_date, bytes).await?;
+    db.add_user_entropy_usage(owner_id, owner.usage_date, bytes).await?;
+    status.used_bytes += bytes;
+    status.remaining_bytes -= bytes;
+    Ok(status)
+}
+
+/// A user's own allowance, which always gets the server default.
+pub async fn get_user_quota_status(db: &Db, user_id: i64) -> Result<QuotaStatus> {
+    let today = Utc::now().date_naive();
+    let quota = default_daily_quota();
+    let used = db.get_user_entropy_usage(user_id, today).await?;
+    Ok(QuotaStatus {
+        profile_id: None,
+        user_id: Some(user_id),
+        usage_date: today,
+        daily_quota_bytes: quota,
+        used_bytes: used,
+        remaining_bytes: (quota - used).max(0),
+    })
+}
+
+/// Charges `bytes` against the user's own daily allowance, for draws that name no profile.
+pub async fn consume_for_user(db: &Db, user_id: i64, bytes: i64) -> Result<QuotaStatus> {
+    let _guard = QUOTA_LOCK.lock().await;
+    let mut status = get_user_quota_status(db, user_id).await?;
+    if bytes > status.remaining_bytes {
+        return Err(QuotaExceeded { status, requested_bytes: bytes }.into());
+    }
+    db.add_user_entropy_usage(user_id, status.usage_date, bytes).await?;
+    status.used_bytes += bytes;
+    status.remaining_bytes -= bytes;
+    Ok(status)
+}
+
+#[cfg(test)]
+#[path = "quota_tests.rs"]
+mod tests;
@@ -0,0 +1,27 @@
+use super::{consume, default_daily_quota, QuotaExceeded};
+use crate::db::{Db, ProfileFields};
+
+#[tokio::test]
+async fn test_owner_allowance_caps_profile_draws() {
+    let db = Db::in_memory().await.unwrap();
+    let alice = db.create_user("alice", false).await.unwrap();
+    let fields = ProfileFields {
+        name: "Unlimited".to_string(),
+        birth_year: 1990,
+        birth_month: 5,
+        birth_day: 17,
+        birth_hour: 8,
+        gender: "F".to_string(),
+        entropy_quota_bytes: Some(i64::MAX),
+    };
+    let first = db.create_profile(alice, &fields).await.unwrap();
+    let daily = default_daily_quota();
+    consume(&db, first, alice, daily).await.unwrap();
+
+    // A fresh profile starts with no usage of its own, but the owner's is spent
+    let second = db.create_profile(alice, &fields).await.unwrap();
+    let err = consume(&db, second, alice, 1).await.unwrap_err();
+    let exceeded = err.downcast_ref::<QuotaExceeded>().unwrap();
+    assert_eq!((exceeded.status.profile_id, exceeded.status.user_id), (None, Some(alice)));
+    assert_eq!(db.get_entropy_usage(second, exceeded.status.usage_date).await.unwrap(), 0);
+}
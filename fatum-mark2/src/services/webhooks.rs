@@ -0,0 +1,268 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use reqwest::header::CONTENT_TYPE;
+use ring::hmac;
+use serde::Serialize;
+use tokio::sync::{broadcast, watch};
+use tracing::Instrument;
+
+use crate::db::{Db, Webhook};
+use crate::services::events::ServerEvent;
+
+pub const HARVEST_COMPLETED: &str = "harvest.completed";
+pub const SCHEDULE_COMPLETED: &str = "schedule.completed";
+pub const SCHEDULE_FAILED: &str = "schedule.failed";
+pub const ANOMALY_DETECTED: &str = "anomaly.detected";
+/// Sent by the test endpoint only.
+pub const PING: &str = "ping";
+/// Events a webhook can subscribe to.
+pub const EVENT_TYPES: &[&str] = &[HARVEST_COMPLETED, SCHEDULE_COMPLETED, SCHEDULE_FAILED, ANOMALY_DETECTED];
+
+pub const EVENT_HEADER: &str = "x-fatum-event";
+pub const DELIVERY_HEADER: &str = "x-fatum-delivery";
+pub const TIMESTAMP_HEADER: &str = "x-fatum-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-fatum-signature";
+
+/// How a delivery is attempted.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled after each one.
+    pub base_delay: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Five attempts over about half a minute (2s, 4s, 8s and 16s apart).
+    fn default() -> Self {
+        Self { max_attempts: 5, base_delay: Duration::from_secs(2), timeout: Duration::from_secs(10) }
+    }
+}
+
+/// The JSON body POSTed to a webhook.
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    /// Unique per delivery (not per attempt), so receivers can drop duplicates.
+    pub id: String,
+    pub event: String,
+    pub created_at: DateTime<Utc>,
+    pub data: serde_json::Value,
+}
+
+impl Delivery {
+    pub fn new(event: &str, data: serde_json::Value) -> Self {
+        let mut id = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut id);
+        Self { id: hex::encode(id), event: event.to_string(), created_at: Utc::now(), data }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryOutcome {
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if it got a response.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// A fresh signing secret for a new webhook.
+pub fn new_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// `sha256=` + hex HMAC-SHA256 of `"{timestamp}.{body}"`.
+///
+/// Covering the timestamp lets receivers reject replays of old deliveries.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let mut ctx = hmac::Context::with_key(&key);
+    ctx.update(timestamp.to_string().as_bytes());
+    ctx.update(b".");
+    ctx.update(body);
+    format!("sha256={}", hex::encode(ctx.sign().as_ref()))
+}
+
+/// Addresses a webhook must not reach: loopback, link-local, private (including shared
+/// and unique local ranges), unspecified and broadcast.
+pub fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_loopback() || v4.is_private() || v4.is_link_local() || v4.is_unspecified() || v4.is_broadcast()
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_internal(v4.into()),
+            None => {
+                let first = v6.segments()[0];
+                v6.is_loopback() || v6.is_unspecified() || first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+/// Resolves a webhook URL's host, refusing it if any address it resolves to is internal,
+/// so webhooks can't be used to probe the server's own network.
+pub async fn resolve_destination(url: &str) -> Result<(String, SocketAddr)> {
+    let parsed = reqwest::Url::parse(url)?;
+    let host = parsed.host_str().ok_or_else(|| anyhow!("URL has no host"))?.to_string();
+    let port = parsed.port_or_known_default().ok_or_else(|| anyhow!("URL has no port"))?;
+    // IPv6 literals come bracketed
+    let name = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name, port)).await?.collect();
+    if let Some(addr) = addrs.iter().find(|a| is_internal(a.ip())) {
+        bail!("{} resolves to internal address {}", host, addr.ip());
+    }
+    let addr = *addrs.first().ok_or_else(|| anyhow!("{} did not resolve", host))?;
+    Ok((host, addr))
+}
+
+/// A client for delivering to `url`: redirects are not followed and the host is pinned to
+/// the address that was checked, so DNS can't swap in an internal one before connecting.
+pub async fn client_for(url: &str) -> Result<reqwest::Client> {
+    let (host, addr) = resolve_destination(url).await?;
+    Ok(reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(&host, addr)
+        .build()?)
+}
+
+/// POSTs a delivery, retrying network errors, 429 and 5xx responses with exponential backoff.
+///
+/// Every attempt is signed with its own timestamp. Without a secret the request is unsigned.
+pub async fn deliver(client: &reqwest::Client, url: &str, secret: Option<&str>, delivery: &Delivery, policy: RetryPolicy) -> DeliveryOutcome {
+    let body = serde_json::to_vec(delivery).unwrap();
+    let mut delay = policy.base_delay;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let timestamp = Utc::now().timestamp();
+        let mut req = client
+            .post(url)
+            .timeout(policy.timeout)
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &delivery.event)
+            .header(DELIVERY_HEADER, &delivery.id)
+            .header(TIMESTAMP_HEADER, timestamp.to_string());
+        if let Some(secret) = secret {
+            req = req.header(SIGNATURE_HEADER, sign(secret, timestamp, &body));
+        }
+
+        let (status, error, retry) = match req.body(body.clone()).send().await {
+            Ok(res) if res.status().is_success() => {
+                return DeliveryOutcome { attempts, status: Some(res.status().as_u16()), error: None };
+            }
+            Ok(res) => {
+                let status = res.status();
+                (Some(status.as_u16()), format!("HTTP {}", status), status.is_server_error() || status.as_u16() == 429)
+            }
+            Err(e) => (None, e.to_string(), true),
+        };
+        if !retry || attempts >= policy.max_attempts {
+            return DeliveryOutcome { attempts, status, error: Some(error) };
+        }
+        tracing::debug!(%url, attempts, %error, retry_in_secs = delay.as_secs_f64(), "Webhook delivery failed, retrying");
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+}
+
+/// Delivers to a registered webhook and records the outcome on it.
+///
+/// The URL is checked again here, since where a host resolves to can change after it was saved.
+pub async fn send(db: &Db, hook: &Webhook, delivery: &Delivery, policy: RetryPolicy) -> DeliveryOutcome {
+    let ready = async { Ok::<_, anyhow::Error>((client_for(&hook.url).await?, hook.signing_secret()?)) };
+    let outcome = match ready.await {
+        Ok((client, secret)) => deliver(&client, &hook.url, Some(&secret), delivery, policy).await,
+        Err(e) => DeliveryOutcome { attempts: 0, status: None, error: Some(format!("{:#}", e)) },
+    };
+    match &outcome.error {
+        None => tracing::info!(webhook_id = hook.id, event = %delivery.event, attempts = outcome.attempts, "Webhook delivered"),
+        Some(error) => tracing::warn!(webhook_id = hook.id, event = %delivery.event, attempts = outcome.attempts, %error, "Webhook delivery failed"),
+    }
+    if let Err(e) = db.record_webhook_delivery(hook.id, outcome.status, outcome.error.as_deref()).await {
+        tracing::error!(webhook_id = hook.id, error = %e, "Could not record webhook delivery");
+    }
+    outcome
+}
+
+/// The webhook event a server event triggers, if any: `(owner, event name, data)`.
+async fn notification(db: &Db, event: ServerEvent) -> Result<Option<(i64, &'static str, serde_json::Value)>> {
+    Ok(match event {
+        ServerEvent::HarvestStopped { batch_id, .. } => {
+            let batch = db.get_batch(batch_id).await?;
+            // Shutdown also stops the harvester but only pauses the batch
+            let (Some(owner_id), "completed") = (batch.owner_id, batch.status.as_str()) else { return Ok(None) };
+            let bytes = db.get_batch_size(batch_id).await?;
+            Some((owner_id, HARVEST_COMPLETED, serde_json::json!({ "batch_id": batch_id, "name": batch.name, "bytes": bytes })))
+        }
+        ServerEvent::ScheduleRun { schedule_id, owner_id, name, history_id, error } => {
+            let event = if error.is_none() { SCHEDULE_COMPLETED } else { SCHEDULE_FAILED };
+            Some((owner_id, event, serde_json::json!({
+                "schedule_id": schedule_id,
+                "name": name,
+                "history_id": history_id,
+                "error": error,
+            })))
+        }
+        ServerEvent::AnomalyDetected { job_id, owner_id, option, z_score } => {
+            Some((owner_id, ANOMALY_DETECTED, serde_json::json!({ "job_id": job_id, "option": option, "z_score": z_score })))
+        }
+        _ => None,
+    })
+}
+
+/// Forwards server events to the owners' webhooks until shutdown.
+///
+/// Each delivery runs in its own task, so a slow receiver never holds up the others.
+pub fn spawn_dispatcher(db: Arc<Db>, mut events: broadcast::Receiver<ServerEvent>, mut shutdown: watch::Receiver<bool>) {
+    tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                event = events.recv() => event,
+                _ = shutdown.changed() => break,
+            };
+            let event = match event {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "Webhook dispatcher fell behind; some events were not delivered");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            let (owner_id, name, data) = match notification(&db, event).await {
+                Ok(Some(notification)) => notification,
+                Ok(None) => continue,
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not prepare webhook notification");
+                    continue;
+                }
+            };
+            let hooks = match db.webhooks_for_event(owner_id, name).await {
+                Ok(hooks) => hooks,
+                Err(e) => {
+                    tracing::error!(error = %e, "Could not load webhooks");
+                    continue;
+                }
+            };
+            for hook in hooks {
+                let (db, delivery) = (db.clone(), Delivery::new(name, data.clone()));
+                tokio::spawn(async move {
+                    send(&db, &hook, &delivery, RetryPolicy::default()).await;
+                }.in_current_span());
+            }
+        }
+    }.instrument(tracing::info_span!("webhook_dispatcher")));
+}
+
+#[cfg(test)]
+#[path = "webhooks_tests.rs"]
+mod tests;
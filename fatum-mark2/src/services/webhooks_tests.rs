@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{http::{HeaderMap, StatusCode}, routing::post, Router};
+
+use super::{deliver, is_internal, resolve_destination, sign, Delivery, RetryPolicy, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+
+fn quick() -> RetryPolicy {
+    RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(10), timeout: Duration::from_secs(5) }
+}
+
+/// Serves `POST /` answering with `statuses` in turn (the last one repeats), recording each request.
+async fn receiver(statuses: Vec<u16>) -> (String, Arc<AtomicU32>, Arc<std::sync::Mutex<Vec<(HeaderMap, String)>>>) {
+    let hits = Arc::new(AtomicU32::new(0));
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (h, s) = (hits.clone(), seen.clone());
+    let app = Router::new().route("/", post(move |headers: HeaderMap, body: String| async move {
+        let n = h.fetch_add(1, Ordering::SeqCst) as usize;
+        s.lock().unwrap().push((headers, body));
+        StatusCode::from_u16(statuses[n.min(statuses.len() - 1)]).unwrap()
+    }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, hits, seen)
+}
+
+#[test]
+fn test_sign_matches_reference_hmac() {
+    // python: hmac.new(b"whsec_test", b'1700000000.{"event":"ping"}', hashlib.sha256).hexdigest()
+    assert_eq!(
+        sign("whsec_test", 1_700_000_000, br#"{"event":"ping"}"#),
+        "sha256=aa8efe37b751e71157c508c5ac4acb1e9fe5225db98355dfc00f4b680afbc447"
+    );
+}
+
+#[tokio::test]
+async fn test_deliver_retries_server_errors_and_signs_each_attempt() {
+    let (url, hits, seen) = receiver(vec![500, 503, 200]).await;
+    let delivery = Delivery::new("ping", serde_json::json!({ "hello": "world" }));
+    let outcome = deliver(&reqwest::Client::new(), &url, Some("s3cret"), &delivery, quick()).await;
+
+    assert_eq!((outcome.attempts, outcome.status, outcome.error), (3, Some(200), None));
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+    for (headers, body) in seen.lock().unwrap().iter() {
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert_eq!(headers[SIGNATURE_HEADER].to_str().unwrap(), sign("s3cret", timestamp, body.as_bytes()));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(body).unwrap()["id"], delivery.id.as_str());
+    }
+}
+
+#[tokio::test]
+async fn test_deliver_gives_up_on_client_errors() {
+    let (url, hits, seen) = receiver(vec![410]).await;
+    let outcome = deliver(&reqwest::Client::new(), &url, None, &Delivery::new("ping", serde_json::json!({})), quick()).await;
+
+    assert_eq!(outcome.attempts, 1, "4xx other than 429 is not retried");
+    assert_eq!(outcome.status, Some(410));
+    assert!(outcome.error.is_some());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    assert!(!seen.lock().unwrap()[0].0.contains_key(SIGNATURE_HEADER), "no secret, no signature");
+}
+
+#[tokio::test]
+async fn test_deliver_stops_after_max_attempts() {
+    let (url, hits, _) = receiver(vec![502]).await;
+    let outcome = deliver(&reqwest::Client::new(), &url, Some("k"), &Delivery::new("ping", serde_json::json!({})), quick()).await;
+    assert_eq!((outcome.attempts, outcome.status), (3, Some(502)));
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_internal_destinations_are_refused() {
+    for url in [
+        "http://127.0.0.1:8080/hook",
+        "http://localhost/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "http://10.1.2.3/",
+        "http://192.168.0.10/",
+        "http://100.64.0.1/",
+        "http://0.0.0.0/",
+        "http://[::1]/",
+        "http://[fe80::1]/",
+        "http://[fd00::1]/",
+        "http://[::ffff:127.0.0.1]/",
+    ] {
+        assert!(resolve_destination(url).await.is_err(), "{}", url);
+    }
+    let (host, addr) = resolve_destination("https://93.184.216.34/hook").await.unwrap();
+    assert_eq!((host.as_str(), addr.port()), ("93.184.216.34", 443));
+    assert!(!is_internal("8.8.8.8".parse().unwrap()));
+}
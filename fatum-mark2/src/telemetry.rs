@@ -0,0 +1,21 @@
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+/// Filter used when `RUST_LOG` is unset. SQL statements are logged by sqlx at `debug`
+/// (slow ones at `warn`), so `RUST_LOG=info,sqlx=debug` shows every query.
+pub const DEFAULT_FILTER: &str = "info";
+
+/// Installs the global tracing subscriber.
+///
+/// * `RUST_LOG`: standard env filter, defaults to `DEFAULT_FILTER`.
+/// * `FATUM_LOG_FORMAT`: `json` (one object per line, with span fields), `pretty`
+///   (multi-line, for local debugging), anything else the compact default.
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match std::env::var("FATUM_LOG_FORMAT").unwrap_or_default().to_lowercase().as_str() {
+        "json" => registry.with(fmt::layer().json().with_current_span(true).with_span_list(true)).init(),
+        "pretty" => registry.with(fmt::layer().pretty()).init(),
+        _ => registry.with(fmt::layer()).init(),
+    }
+}
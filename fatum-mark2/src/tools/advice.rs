@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tools::feng_shui::{AfflictionOverlay, FlyingStarChart, HexagramInfo, KuaProfile, QuantumAnalysis};
+use crate::tools::i18n::{affliction, hexagram, in_sentence, message, sector, Locale};
+use crate::tools::qi_flow::facing_sector;
+use crate::tools::qimen::{QiMenChart, QiMenPalace};
+
+/// How urgently an advice item needs acting on, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// An opportunity or reading; nothing is wrong.
+    Info,
+    Low,
+    Medium,
+    High,
+}
+
+/// One piece of advice, tagged with the rule that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdviceItem {
+    /// Stable id of the rule, e.g. "flying-star.wealth-water", for grouping and filtering.
+    pub rule_id: String,
+    pub severity: Severity,
+    /// Palace or direction the advice is about; `None` if it concerns the whole house.
+    pub sector: Option<String>,
+    /// What to do.
+    pub action: String,
+    /// Why: the finding behind the advice.
+    pub explanation: String,
+}
+
+/// Everything the rules read from a finished report.
+pub struct AdviceContext<'a> {
+    pub chart: &'a FlyingStarChart,
+    pub kua: Option<&'a KuaProfile>,
+    pub quantum: &'a QuantumAnalysis,
+    pub formations: &'a [String],
+    pub hexagram: Option<&'a HexagramInfo>,
+    pub facing_degrees: f64,
+    /// Period of the current year (not the house's).
+    pub current_period: i32,
+    pub qimen: Option<&'a QiMenChart>,
+    pub date_afflictions: &'a [AfflictionOverlay],
+    /// Language of the actions and explanations.
+    pub locale: Locale,
+}
+
+type Rule = fn(&AdviceContext) -> Vec<AdviceItem>;
+
+/// Every rule, in the order its advice is listed.
+const RULES: [Rule; 8] = [
+    wealth_water, wealth_mountain, kua_direction, quantum_focus,
+    special_formations, hexagram_timing, qimen_structures, date_afflictions,
+];
+
+/// Date afflictions at or above this severity (one full year-layer affliction) become advice.
+const MIN_AFFLICTION_SEVERITY: f64 = 1.0;
+
+/// The message `key` in the report's locale.
+fn text(ctx: &AdviceContext, key: &str, args: &[(&str, String)]) -> String {
+    let args: Vec<(&str, &str)> = args.iter().map(|(name, value)| (*name, value.as_str())).collect();
+    message(ctx.locale, key, &args)
+}
+
+fn item(rule_id: &str, severity: Severity, sector: Option<&str>, action: String, explanation: String) -> AdviceItem {
+    AdviceItem { rule_id: rule_id.to_string(), severity, sector: sector.map(str::to_string), action, explanation }
+}
+
+/// Runs every rule over the report, in `RULES` order.
+pub fn generate_advice(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    RULES.iter().flat_map(|rule| rule(ctx)).collect()
+}
+
+/// Period 9 Wealth Star is 9, Period 8 was 8.
+fn wealth_star(chart: &FlyingStarChart) -> i32 {
+    if chart.period == 9 { 9 } else { 8 }
+}
+
+fn wealth_water(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    let star = wealth_star(ctx.chart);
+    ctx.chart.palaces.iter().filter(|p| p.water_star == star).map(|p| {
+        let args = [("sector", sector(&p.sector, ctx.locale)), ("star", star.to_string())];
+        item(
+            "flying-star.wealth-water",
+            Severity::Info,
+            Some(&p.sector),
+            text(ctx, "wealth-water.action", &args),
+            text(ctx, "wealth-water.explanation", &args),
+        )
+    }).collect()
+}
+
+fn wealth_mountain(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    let star = wealth_star(ctx.chart);
+    ctx.chart.palaces.iter().filter(|p| p.mountain_star == star).map(|p| {
+        let args = [("sector", sector(&p.sector, ctx.locale)), ("star", star.to_string())];
+        item(
+            "flying-star.wealth-mountain",
+            Severity::Info,
+            Some(&p.sector),
+            text(ctx, "wealth-mountain.action", &args),
+            text(ctx, "wealth-mountain.explanation", &args),
+        )
+    }).collect()
+}
+
+fn kua_direction(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    let Some(k) = ctx.kua else { return Vec::new() };
+    let Some((direction, _)) = k.lucky_directions.first() else { return Vec::new() };
+    let args = [("direction", sector(direction, ctx.locale)), ("number", k.number.to_string())];
+    vec![item(
+        "kua.best-direction",
+        Severity::Info,
+        Some(direction),
+        text(ctx, "kua.action", &args),
+        text(ctx, "kua.explanation", &args),
+    )]
+}
+
+fn quantum_focus(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    let q = ctx.quantum;
+    let args = [("sector", sector(&q.focus_sector, ctx.locale)), ("volatility", format!("{:.2}", q.volatility_index))];
+    vec![item(
+        "quantum.focus",
+        Severity::Info,
+        Some(&q.focus_sector),
+        text(ctx, "quantum.action", &args),
+        text(ctx, "quantum.explanation", &args),
+    )]
+}
+
+fn special_formations(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    if ctx.formations.is_empty() {
+        return Vec::new();
+    }
+    vec![item(
+        "flying-star.formations",
+        Severity::Info,
+        None,
+        text(ctx, "formations.action", &[]),
+        text(ctx, "formations.explanation", &[]),
+    )]
+}
+
+/// Whether the facing hexagram's Qi is in season: Gua Yun 1-4 belong to the upper
+/// era (Periods 1-4), 6-9 to the lower era (Periods 6-9).
+fn hexagram_timing(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    let Some(h) = ctx.hexagram else { return Vec::new() };
+    let period = [("period", ctx.current_period.to_string())];
+    let (timing, mut severity) = if h.period_number == ctx.current_period {
+        ("hexagram.timely", Severity::Info)
+    } else if (h.period_number < 5) == (ctx.current_period < 5) {
+        ("hexagram.same-era", Severity::Low)
+    } else {
+        ("hexagram.out-of-season", Severity::Medium)
+    };
+    let (name, meaning) = match (ctx.locale, hexagram(h.king_wen)) {
+        (Locale::Zh, Some((term, meaning))) => (term.hanzi.to_string(), meaning.to_string()),
+        _ => (h.name.clone(), h.meaning.to_lowercase()),
+    };
+    let mut explanation = text(ctx, "hexagram.explanation", &[
+        ("name", name),
+        ("yun", h.period_number.to_string()),
+        ("timing", text(ctx, timing, &period)),
+    ]);
+    if !h.auspicious {
+        explanation.push_str(&text(ctx, "hexagram.warning", &[("meaning", meaning)]));
+        severity = severity.max(Severity::Medium);
+    }
+    let action = text(ctx, if severity == Severity::Info { "hexagram.keep" } else { "hexagram.turn" }, &[]);
+    vec![item("hexagram.timing", severity, Some(facing_sector(ctx.facing_degrees)), action, explanation)]
+}
+
+/// The Qi Men palaces with the strongest auspicious and harmful structures, if any.
+fn qimen_structures(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    let Some(chart) = ctx.qimen else { return Vec::new() };
+    let best = chart.palaces.iter().filter(|p| p.structure_score() > 0).max_by_key(|p| p.structure_score());
+    let worst = chart.palaces.iter().filter(|p| p.structure_score() < 0).min_by_key(|p| p.structure_score());
+    let args = |p: &QiMenPalace| [
+        ("direction", sector(&p.position, ctx.locale)),
+        ("structure", match ctx.locale {
+            Locale::En => p.structure.clone(),
+            locale => p.structures.iter().map(|s| in_sentence(&s.name, locale)).collect::<Vec<_>>().join("、"),
+        }),
+        ("time", chart.time_label.clone()),
+    ];
+    let mut items = Vec::new();
+    if let Some(p) = best {
+        items.push(item(
+            "qimen.auspicious-structure",
+            Severity::Info,
+            Some(&p.position),
+            text(ctx, "qimen.auspicious.action", &args(p)),
+            text(ctx, "qimen.auspicious.explanation", &args(p)),
+        ));
+    }
+    if let Some(p) = worst {
+        items.push(item(
+            "qimen.harmful-structure",
+            Severity::Medium,
+            Some(&p.position),
+            text(ctx, "qimen.harmful.action", &args(p)),
+            text(ctx, "qimen.harmful.explanation", &args(p)),
+        ));
+    }
+    items
+}
+
+/// Palaces where the year, month and day afflictions pile up.
+fn date_afflictions(ctx: &AdviceContext) -> Vec<AdviceItem> {
+    ctx.date_afflictions.iter().filter(|o| o.severity >= MIN_AFFLICTION_SEVERITY).map(|o| {
+        // The English warning already says how long to wait; other locales get the template
+        let explanation = match ctx.locale {
+            Locale::En => o.warning.clone(),
+            locale => text(ctx, "date-afflictions.explanation", &[
+                ("sector", sector(&o.sector, locale)),
+                ("afflictions", o.afflictions.iter().map(|a| affliction(a, locale)).collect::<Vec<_>>().join(" + ")),
+            ]),
+        };
+        item(
+            "date.afflictions",
+            if o.severity >= 2.0 * MIN_AFFLICTION_SEVERITY { Severity::High } else { Severity::Medium },
+            Some(&o.sector),
+            text(ctx, "date-afflictions.action", &[]),
+            explanation,
+        )
+    }).collect()
+}
+
+#[cfg(test)]
+#[path = "advice_tests.rs"]
+mod tests;
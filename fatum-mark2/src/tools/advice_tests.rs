@@ -0,0 +1,145 @@
+use super::{generate_advice, AdviceContext, AdviceItem, Severity};
+use crate::tools::i18n::Locale;
+use crate::tools::feng_shui::{calculate_flying_star_chart, AfflictionOverlay, FlyingStarChart, HexagramInfo, KuaProfile, QuantumAnalysis};
+
+fn quantum() -> QuantumAnalysis {
+    QuantumAnalysis {
+        volatility_index: 0.25,
+        focus_sector: "E".to_string(),
+        anomalies: vec![],
+        intention_resonance: None,
+        suggested_cures: vec![],
+        qi_flow: None,
+        qi_heatmap: None,
+        cure_efficacy: None,
+        cure_simulation: None,
+    }
+}
+
+fn hexagram(period_number: i32, auspicious: bool) -> HexagramInfo {
+    HexagramInfo {
+        name: "Test".to_string(),
+        index: 1,
+        meaning: "Obstruction".to_string(),
+        element: "Earth".to_string(),
+        king_wen: 12,
+        element_number: 1,
+        period_number,
+        auspicious,
+    }
+}
+
+fn overlay(sector: &str, severity: f64) -> AfflictionOverlay {
+    AfflictionOverlay {
+        sector: sector.to_string(),
+        afflictions: vec!["Year Tai Sui".to_string()],
+        severity,
+        facing: false,
+        warning: format!("{}: Year Tai Sui.", sector),
+    }
+}
+
+fn advise(chart: &FlyingStarChart, hexagram: Option<&HexagramInfo>, overlays: &[AfflictionOverlay]) -> Vec<AdviceItem> {
+    advise_in(Locale::En, chart, hexagram, overlays)
+}
+
+fn advise_in(locale: Locale, chart: &FlyingStarChart, hexagram: Option<&HexagramInfo>, overlays: &[AfflictionOverlay]) -> Vec<AdviceItem> {
+    let kua = KuaProfile {
+        number: 1,
+        group: "East Group".to_string(),
+        element: "Water".to_string(),
+        lucky_directions: vec![("SE".to_string(), "Sheng Chi".to_string())],
+    };
+    let quantum = quantum();
+    generate_advice(&AdviceContext {
+        chart,
+        kua: Some(&kua),
+        quantum: &quantum,
+        formations: &[],
+        hexagram,
+        facing_degrees: 180.0,
+        current_period: 9,
+        qimen: None,
+        date_afflictions: overlays,
+        locale,
+    })
+}
+
+#[test]
+fn test_rules_tag_their_advice() {
+    let chart = calculate_flying_star_chart(2024, 180.0, 2025, None);
+    let advice = advise(&chart, None, &[]);
+
+    let water: Vec<&AdviceItem> = advice.iter().filter(|a| a.rule_id == "flying-star.wealth-water").collect();
+    assert!(!water.is_empty());
+    for a in &water {
+        let sector = a.sector.as_deref().unwrap();
+        assert!(chart.palaces.iter().any(|p| p.sector == sector && p.water_star == 9));
+        assert_eq!(a.severity, Severity::Info);
+    }
+    let kua = advice.iter().find(|a| a.rule_id == "kua.best-direction").unwrap();
+    assert_eq!(kua.sector.as_deref(), Some("SE"));
+    assert_eq!(kua.explanation, "Your Life Gua is 1. Strongest direction: SE.");
+    assert!(advice.iter().all(|a| a.rule_id != "flying-star.formations"));
+
+    // Rules run in order: the star rules come before the quantum focus
+    let position = |id: &str| advice.iter().position(|a| a.rule_id == id).unwrap();
+    assert!(position("flying-star.wealth-water") < position("quantum.focus"));
+
+    let json = serde_json::to_value(kua).unwrap();
+    assert_eq!(json["severity"], "info");
+}
+
+#[test]
+fn test_hexagram_severity_follows_its_timing() {
+    let chart = calculate_flying_star_chart(2024, 180.0, 2025, None);
+    let timely = hexagram(9, true);
+    let advice = advise(&chart, Some(&timely), &[]);
+    let item = advice.iter().find(|a| a.rule_id == "hexagram.timing").unwrap();
+    assert_eq!((item.severity, item.sector.as_deref()), (Severity::Info, Some("S")));
+    assert_eq!(item.action, "Keep the current facing.");
+
+    let same_era = hexagram(7, true);
+    let advice = advise(&chart, Some(&same_era), &[]);
+    assert_eq!(advice.iter().find(|a| a.rule_id == "hexagram.timing").unwrap().severity, Severity::Low);
+
+    let warned = hexagram(7, false);
+    let advice = advise(&chart, Some(&warned), &[]);
+    let item = advice.iter().find(|a| a.rule_id == "hexagram.timing").unwrap();
+    assert_eq!(item.severity, Severity::Medium);
+    assert!(item.explanation.ends_with("Its classical reading warns: obstruction."));
+}
+
+#[test]
+fn test_only_heavy_date_afflictions_become_advice() {
+    let chart = calculate_flying_star_chart(2024, 180.0, 2025, None);
+    let overlays = [overlay("SE", 2.5), overlay("W", 1.2), overlay("N", 0.6)];
+    let advice = advise(&chart, None, &overlays);
+    let dated: Vec<(&str, Severity)> = advice.iter()
+        .filter(|a| a.rule_id == "date.afflictions")
+        .map(|a| (a.sector.as_deref().unwrap(), a.severity))
+        .collect();
+    assert_eq!(dated, [("SE", Severity::High), ("W", Severity::Medium)]);
+    assert!(Severity::High > Severity::Medium && Severity::Low > Severity::Info);
+}
+
+#[test]
+fn test_advice_follows_locale() {
+    let chart = calculate_flying_star_chart(2024, 180.0, 2025, None);
+    let overlays = [overlay("SE", 2.5)];
+    let en = advise(&chart, Some(&hexagram(7, false)), &overlays);
+    let zh = advise_in(Locale::Zh, &chart, Some(&hexagram(7, false)), &overlays);
+
+    // Same rules, ids, severities and sector codes; only the text changes
+    let tags = |advice: &[AdviceItem]| advice.iter()
+        .map(|a| (a.rule_id.clone(), a.severity, a.sector.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(tags(&en), tags(&zh));
+
+    let kua = zh.iter().find(|a| a.rule_id == "kua.best-direction").unwrap();
+    assert_eq!(kua.explanation, "你的命卦为1，最佳方位：东南方巽宫。");
+    let dated = zh.iter().find(|a| a.rule_id == "date.afflictions").unwrap();
+    assert_eq!(dated.explanation, "东南方巽宫：年太岁。避免在此装修动土。");
+    let hexagram = zh.iter().find(|a| a.rule_id == "hexagram.timing").unwrap();
+    assert_eq!(hexagram.explanation, "向首卦否（卦运7）与9运同属一元。古典卦义提示：闭塞不通。");
+}
@@ -0,0 +1,198 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::tools::astrology_western::{
+    aspect_of, calculate_natal_chart, separation, zodiac_position, Aspect, NatalChart, NatalConfig, ZodiacPosition, ASPECTS,
+};
+use crate::tools::astronomy::{ecliptic_longitude, julian_day_ut, Body, BODIES};
+
+/// Hours between transit samples; short enough that even the Moon cannot skip an aspect.
+const TRANSIT_STEP_HOURS: i64 = 6;
+
+/// Bodies whose contacts weigh most in synastry.
+const PERSONAL: [Body; 5] = [Body::Sun, Body::Moon, Body::Mercury, Body::Venus, Body::Mars];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransitConfig {
+    pub natal: NatalConfig,
+    /// First and last UT day to scan.
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Transiting bodies to follow; every body but the Moon when empty.
+    #[serde(default)]
+    pub bodies: Vec<Body>,
+}
+
+/// A transiting body reaching an exact aspect to a natal point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitHit {
+    pub utc: NaiveDateTime,
+    pub transiting: Body,
+    pub natal_point: String,
+    pub aspect: String,
+    pub position: ZodiacPosition,
+    pub retrograde: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitReport {
+    pub natal: NatalChart,
+    /// In time order.
+    pub hits: Vec<TransitHit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SynastryConfig {
+    pub first: NatalConfig,
+    pub second: NatalConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SynastryReport {
+    pub first: NatalChart,
+    pub second: NatalChart,
+    /// Aspect grid: `first` names the first chart's point, `second` the second's. Natal
+    /// points do not move, so `applying` is always false.
+    pub aspects: Vec<Aspect>,
+    /// 0-100 from the planet-to-planet aspects: trines and sextiles raise it, squares and
+    /// oppositions lower it, tighter orbs and personal planets count more. Angles are left
+    /// out since they need exact birth times and places.
+    pub harmony_score: f64,
+    /// The tightest planet-to-planet contacts, e.g. "Venus Trine Mars (orb 1.2°)".
+    pub factors: Vec<String>,
+}
+
+/// Natal points an aspect can be made to: the ten bodies, then the Ascendant and Midheaven.
+fn natal_points(chart: &NatalChart) -> Vec<(String, f64)> {
+    chart.planets.iter()
+        .map(|p| (format!("{:?}", p.body), p.position.longitude))
+        .chain([
+            ("Ascendant".to_string(), chart.ascendant.longitude),
+            ("Midheaven".to_string(), chart.midheaven.longitude),
+        ])
+        .collect()
+}
+
+/// How far `longitude` is past `target` (negative when short of it), in (-180, 180].
+fn offset(longitude: f64, target: f64) -> f64 {
+    180.0 - (target - longitude + 180.0).rem_euclid(360.0)
+}
+
+/// Scans `start_date..=end_date` for exact aspects from the transiting bodies to the natal chart.
+pub fn scan_transits(config: &TransitConfig) -> Result<TransitReport> {
+    // 1. The natal chart and its sensitive points
+    let natal = calculate_natal_chart(&config.natal)?;
+    let points = natal_points(&natal);
+    let bodies: Vec<Body> = if config.bodies.is_empty() {
+        BODIES.into_iter().filter(|&b| b != Body::Moon).collect()
+    } else {
+        config.bodies.clone()
+    };
+
+    // 2. Every aspect angle on both sides of each point
+    let targets: Vec<(&str, f64, &str)> = points.iter().flat_map(|(name, lon)| {
+        ASPECTS.iter().flat_map(move |&(kind, angle, _)| {
+            let sides: &[f64] = if angle == 0.0 || angle == 180.0 { &[1.0] } else { &[1.0, -1.0] };
+            sides.iter().map(move |s| (name.as_str(), lon + s * angle, kind))
+        })
+    }).collect();
+
+    // 3. Step through the range, bisecting wherever a body crosses a target
+    let start = config.start_date.and_hms_opt(0, 0, 0).unwrap();
+    let steps = (config.end_date - config.start_date).num_hours().max(0) / TRANSIT_STEP_HOURS + 24 / TRANSIT_STEP_HOURS;
+    let jd0 = julian_day_ut(start);
+    let step = TRANSIT_STEP_HOURS as f64 / 24.0;
+    let mut hits = Vec::new();
+    for &body in &bodies {
+        let mut prev = ecliptic_longitude(body, jd0);
+        for i in 1..=steps {
+            let (t0, t1) = (jd0 + (i - 1) as f64 * step, jd0 + i as f64 * step);
+            let next = ecliptic_longitude(body, t1);
+            for &(point, target, kind) in &targets {
+                let (a, b) = (offset(prev, target), offset(next, target));
+                // A sign change far from the target is the wrap at the opposite side
+                if a == 0.0 || a.signum() == b.signum() || a.abs() > 90.0 || b.abs() > 90.0 {
+                    continue;
+                }
+                let (mut lo, mut hi) = (t0, t1);
+                for _ in 0..30 {
+                    let mid = (lo + hi) / 2.0;
+                    if offset(ecliptic_longitude(body, mid), target).signum() == a.signum() { lo = mid } else { hi = mid }
+                }
+                let jd = (lo + hi) / 2.0;
+                hits.push(TransitHit {
+                    utc: start + Duration::seconds(((jd - jd0) * 86400.0).round() as i64),
+                    transiting: body,
+                    natal_point: point.to_string(),
+                    aspect: kind.to_string(),
+                    position: zodiac_position(ecliptic_longitude(body, jd)),
+                    retrograde: b < a,
+                });
+            }
+            prev = next;
+        }
+    }
+    hits.sort_by_key(|h| h.utc);
+
+    Ok(TransitReport { natal, hits })
+}
+
+/// Aspects from each of `first`'s points to each of `second`'s.
+fn aspect_grid(first: &[(String, f64)], second: &[(String, f64)]) -> Vec<Aspect> {
+    let mut aspects: Vec<Aspect> = first.iter().flat_map(|(a, la)| {
+        second.iter().filter_map(move |(b, lb)| {
+            let angle = separation(*la, *lb);
+            aspect_of(angle).map(|(kind, orb)| Aspect {
+                first: a.clone(),
+                second: b.clone(),
+                kind: kind.to_string(),
+                angle,
+                orb,
+                applying: false,
+            })
+        })
+    }).collect();
+    aspects.sort_by(|a, b| a.orb.total_cmp(&b.orb));
+    aspects
+}
+
+/// Harmony weight of an aspect kind before orb and planet weighting.
+fn aspect_weight(kind: &str) -> f64 {
+    match kind {
+        "Trine" => 1.0,
+        "Sextile" => 0.7,
+        "Conjunction" => 0.5,
+        "Opposition" => -0.6,
+        _ => -0.8, // Square
+    }
+}
+
+/// Compares two natal charts: the aspect grid between them and a harmony score.
+pub fn calculate_synastry(config: &SynastryConfig) -> Result<SynastryReport> {
+    let first = calculate_natal_chart(&config.first)?;
+    let second = calculate_natal_chart(&config.second)?;
+    let aspects = aspect_grid(&natal_points(&first), &natal_points(&second));
+
+    // Planet pairs only: angles depend on birth details often only roughly known
+    let personal = |name: &str| PERSONAL.iter().any(|b| format!("{:?}", b) == name);
+    let is_angle = |name: &str| name == "Ascendant" || name == "Midheaven";
+    let planetary: Vec<&Aspect> = aspects.iter().filter(|a| !is_angle(&a.first) && !is_angle(&a.second)).collect();
+    let total: f64 = planetary.iter().map(|a| {
+        let max_orb = ASPECTS.iter().find(|(n, _, _)| *n == a.kind).map_or(8.0, |x| x.2);
+        let tightness = 1.0 - a.orb / max_orb;
+        let weight = 1.0 + 0.5 * (personal(&a.first) as u8 + personal(&a.second) as u8) as f64;
+        aspect_weight(&a.kind) * tightness * weight
+    }).sum();
+    let harmony_score = 50.0 + 50.0 * (total / 6.0).tanh();
+
+    let factors = planetary.iter().take(5)
+        .map(|a| format!("{} {} {} (orb {:.1}°)", a.first, a.kind, a.second, a.orb))
+        .collect();
+
+    Ok(SynastryReport { first, second, aspects, harmony_score, factors })
+}
+
+#[cfg(test)]
+#[path = "astrology_reports_tests.rs"]
+mod tests;
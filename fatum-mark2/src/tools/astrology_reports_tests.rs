@@ -0,0 +1,55 @@
+use chrono::NaiveDate;
+
+use super::{calculate_synastry, offset, scan_transits, SynastryConfig, TransitConfig};
+use crate::tools::astrology_western::{separation, HouseSystem, NatalConfig};
+use crate::tools::astronomy::{ecliptic_longitude, julian_day_ut, Body, Location};
+
+fn natal(y: i32, m: u32, d: u32) -> NatalConfig {
+    NatalConfig {
+        datetime: NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+        location: Location { longitude: -0.13, utc_offset: 0.0 },
+        latitude: 51.5,
+        house_system: HouseSystem::WholeSign,
+    }
+}
+
+#[test]
+fn test_offset_wraps() {
+    assert_eq!(offset(350.0, 10.0), -20.0);
+    assert_eq!(offset(10.0, 350.0), 20.0);
+}
+
+#[test]
+fn test_solar_return_in_transits() {
+    // Over a birthday the transiting Sun conjoins the natal Sun once, within minutes
+    let config = TransitConfig {
+        natal: natal(1990, 6, 15),
+        start_date: NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+        end_date: NaiveDate::from_ymd_opt(2024, 6, 20).unwrap(),
+        bodies: vec![Body::Sun],
+    };
+    let report = scan_transits(&config).unwrap();
+    let returns: Vec<_> = report.hits.iter().filter(|h| h.natal_point == "Sun" && h.aspect == "Conjunction").collect();
+    assert_eq!(returns.len(), 1);
+    let at = julian_day_ut(returns[0].utc);
+    let natal_sun = report.natal.planets[0].position.longitude;
+    assert!(separation(ecliptic_longitude(Body::Sun, at), natal_sun) < 0.01);
+    assert!(report.hits.windows(2).all(|w| w[0].utc <= w[1].utc));
+    assert!(report.hits.iter().all(|h| h.transiting == Body::Sun && !h.retrograde));
+}
+
+#[test]
+fn test_synastry() {
+    let config = SynastryConfig { first: natal(1990, 6, 15), second: natal(1992, 2, 3) };
+    let report = calculate_synastry(&config).unwrap();
+    assert!((0.0..=100.0).contains(&report.harmony_score));
+    assert!(report.aspects.windows(2).all(|w| w[0].orb <= w[1].orb));
+    assert!(report.factors.len() <= 5);
+
+    // A chart against itself: every point conjoins its twin exactly
+    let same = calculate_synastry(&SynastryConfig { first: natal(1990, 6, 15), second: natal(1990, 6, 15) }).unwrap();
+    for name in ["Sun", "Moon", "Ascendant"] {
+        assert!(same.aspects.iter().any(|a| a.first == name && a.second == name && a.kind == "Conjunction" && a.orb < 1e-9));
+    }
+    assert!(same.harmony_score > 50.0);
+}
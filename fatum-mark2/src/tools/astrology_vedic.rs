@@ -0,0 +1,247 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::tools::astrology_western::{angles, body_motion};
+use crate::tools::astronomy::{julian_day_ut, local_sidereal_time, mean_lunar_node, obliquity, Body, Location};
+
+pub const RASHIS: [&str; 12] = [
+    "Mesha", "Vrishabha", "Mithuna", "Karka", "Simha", "Kanya",
+    "Tula", "Vrishchika", "Dhanu", "Makara", "Kumbha", "Meena",
+];
+
+/// Ruling graha of each rashi, from Mesha.
+const RASHI_LORDS: [&str; 12] = [
+    "Mars", "Venus", "Mercury", "Moon", "Sun", "Mercury",
+    "Venus", "Mars", "Jupiter", "Saturn", "Saturn", "Jupiter",
+];
+
+pub const NAKSHATRAS: [&str; 27] = [
+    "Ashwini", "Bharani", "Krittika", "Rohini", "Mrigashira", "Ardra", "Punarvasu", "Pushya", "Ashlesha",
+    "Magha", "Purva Phalguni", "Uttara Phalguni", "Hasta", "Chitra", "Swati", "Vishakha", "Anuradha", "Jyeshtha",
+    "Mula", "Purva Ashadha", "Uttara Ashadha", "Shravana", "Dhanishta", "Shatabhisha", "Purva Bhadrapada", "Uttara Bhadrapada", "Revati",
+];
+
+/// Arc of one nakshatra: 13°20'.
+const NAKSHATRA_SPAN: f64 = 360.0 / 27.0;
+
+/// Vimshottari dasha lords and their years, in sequence from Ashwini's lord; they total 120.
+pub const VIMSHOTTARI: [(&str, f64); 9] = [
+    ("Ketu", 7.0), ("Venus", 20.0), ("Sun", 6.0), ("Moon", 10.0), ("Mars", 7.0),
+    ("Rahu", 18.0), ("Jupiter", 16.0), ("Saturn", 19.0), ("Mercury", 17.0),
+];
+
+const DASHA_YEAR_DAYS: f64 = 365.25;
+
+/// The nine grahas as tropical bodies, with the lunar nodes handled separately.
+const GRAHAS: [(&str, Option<Body>); 9] = [
+    ("Sun", Some(Body::Sun)), ("Moon", Some(Body::Moon)), ("Mars", Some(Body::Mars)),
+    ("Mercury", Some(Body::Mercury)), ("Jupiter", Some(Body::Jupiter)), ("Venus", Some(Body::Venus)),
+    ("Saturn", Some(Body::Saturn)), ("Rahu", None), ("Ketu", None),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ayanamsa {
+    /// Chitrapaksha, the Indian government standard.
+    #[default]
+    Lahiri,
+    Raman,
+    Krishnamurti,
+    FaganBradley,
+}
+
+impl Ayanamsa {
+    /// Offset of the sidereal zodiac from the tropical at Julian Day `jd`, in degrees: the
+    /// J2000 value advanced by general precession (50.29" a year).
+    pub fn degrees(self, jd: f64) -> f64 {
+        let at_j2000 = match self {
+            Ayanamsa::Lahiri => 23.857092,
+            Ayanamsa::Raman => 22.410791,
+            Ayanamsa::Krishnamurti => 23.760240,
+            Ayanamsa::FaganBradley => 24.740300,
+        };
+        at_j2000 + (jd - 2451545.0) / 365.25 * 50.29 / 3600.0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VedicConfig {
+    /// Local clock time of birth.
+    pub datetime: NaiveDateTime,
+    pub location: Location,
+    /// Degrees north of the equator (negative south), for the Lagna.
+    pub latitude: f64,
+    #[serde(default)]
+    pub ayanamsa: Ayanamsa,
+}
+
+/// A sidereal longitude and the rashi it falls in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RashiPosition {
+    pub longitude: f64,
+    pub rashi: String,
+    pub lord: String,
+    /// Degrees into the rashi, 0-30.
+    pub degree: f64,
+    /// e.g. "15°32' Simha".
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Nakshatra {
+    /// 1 = Ashwini.
+    pub number: usize,
+    pub name: String,
+    /// Vimshottari lord.
+    pub lord: String,
+    /// Quarter of the nakshatra, 1-4.
+    pub pada: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Graha {
+    pub name: String,
+    pub position: RashiPosition,
+    pub nakshatra: Nakshatra,
+    /// Whole-sign house counted from the Lagna.
+    pub house: usize,
+    /// Always true for the mean nodes.
+    pub retrograde: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashaPeriod {
+    pub lord: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Antardashas (sub-periods); empty for the sub-periods themselves.
+    #[serde(default)]
+    pub sub_periods: Vec<DashaPeriod>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VedicChart {
+    pub ayanamsa: Ayanamsa,
+    pub ayanamsa_degrees: f64,
+    pub lagna: RashiPosition,
+    pub grahas: Vec<Graha>,
+    /// The Moon's nakshatra, which sets the dasha sequence.
+    pub janma_nakshatra: Nakshatra,
+    /// Vimshottari mahadashas from birth, 120 years in all; the first is the balance left at birth.
+    pub dashas: Vec<DashaPeriod>,
+}
+
+pub fn rashi_position(longitude: f64) -> RashiPosition {
+    let longitude = longitude.rem_euclid(360.0);
+    let idx = (longitude / 30.0) as usize % 12;
+    let degree = longitude - idx as f64 * 30.0;
+    let minutes = (degree.fract() * 60.0) as u32;
+    RashiPosition {
+        longitude,
+        rashi: RASHIS[idx].to_string(),
+        lord: RASHI_LORDS[idx].to_string(),
+        degree,
+        label: format!("{}°{:02}' {}", degree as u32, minutes, RASHIS[idx]),
+    }
+}
+
+pub fn nakshatra_of(longitude: f64) -> Nakshatra {
+    let longitude = longitude.rem_euclid(360.0);
+    let idx = (longitude / NAKSHATRA_SPAN) as usize % 27;
+    let into = longitude - idx as f64 * NAKSHATRA_SPAN;
+    Nakshatra {
+        number: idx + 1,
+        name: NAKSHATRAS[idx].to_string(),
+        lord: VIMSHOTTARI[idx % 9].0.to_string(),
+        pada: (into / (NAKSHATRA_SPAN / 4.0)) as usize % 4 + 1,
+    }
+}
+
+/// Vimshottari mahadashas and antardashas for a Moon at sidereal `moon` born at `birth`.
+///
+/// The cycle is laid out from the moment the Moon entered its nakshatra, then clipped to birth,
+/// so the first mahadasha (and its sub-periods) keeps only the balance still to run.
+pub fn vimshottari(moon: f64, birth: NaiveDateTime) -> Vec<DashaPeriod> {
+    let moon = moon.rem_euclid(360.0);
+    let idx = (moon / NAKSHATRA_SPAN) as usize % 27;
+    let elapsed = (moon - idx as f64 * NAKSHATRA_SPAN) / NAKSHATRA_SPAN;
+    let first = idx % 9;
+    let at = |years: f64| birth + Duration::seconds((years * DASHA_YEAR_DAYS * 86400.0).round() as i64);
+
+    // Years from birth, negative before it
+    let mut start = -elapsed * VIMSHOTTARI[first].1;
+    (0..9).map(|i| {
+        let (lord, years) = VIMSHOTTARI[(first + i) % 9];
+        let mut sub_start = start;
+        let sub_periods = (0..9).filter_map(|j| {
+            let (sub_lord, sub_years) = VIMSHOTTARI[(first + i + j) % 9];
+            let sub_end = sub_start + years * sub_years / 120.0;
+            let period = (sub_end > 0.0).then(|| DashaPeriod {
+                lord: sub_lord.to_string(),
+                start: at(sub_start.max(0.0)).date(),
+                end: at(sub_end).date(),
+                sub_periods: Vec::new(),
+            });
+            sub_start = sub_end;
+            period
+        }).collect();
+        let period = DashaPeriod {
+            lord: lord.to_string(),
+            start: at(start.max(0.0)).date(),
+            end: at(start + years).date(),
+            sub_periods,
+        };
+        start += years;
+        period
+    }).collect()
+}
+
+/// Casts a sidereal rashi chart with whole-sign houses, the Moon's nakshatra and the Vimshottari dashas.
+pub fn calculate_vedic_chart(config: &VedicConfig) -> Result<VedicChart> {
+    // 1. Local clock to UT, the ayanamsa and the sidereal Lagna
+    let utc = config.datetime - Duration::seconds((config.location.utc_offset * 3600.0).round() as i64);
+    let jd = julian_day_ut(utc);
+    let ayanamsa = config.ayanamsa.degrees(jd);
+    let ramc = local_sidereal_time(jd, config.location.longitude);
+    let (_, asc) = angles(ramc, obliquity(jd), config.latitude);
+    let lagna = rashi_position(asc - ayanamsa);
+    let lagna_idx = (lagna.longitude / 30.0) as usize;
+
+    // 2. The nine grahas; Ketu sits opposite Rahu
+    let rahu = mean_lunar_node(jd);
+    let grahas: Vec<Graha> = GRAHAS.iter().map(|&(name, body)| {
+        let (tropical, retrograde) = match (body, name) {
+            (Some(body), _) => {
+                let (lon, speed) = body_motion(body, jd);
+                (lon, speed < 0.0)
+            }
+            (None, "Rahu") => (rahu, true),
+            (None, _) => (rahu + 180.0, true),
+        };
+        let position = rashi_position(tropical - ayanamsa);
+        let idx = (position.longitude / 30.0) as usize;
+        Graha {
+            name: name.to_string(),
+            nakshatra: nakshatra_of(position.longitude),
+            house: (idx + 12 - lagna_idx) % 12 + 1,
+            position,
+            retrograde,
+        }
+    }).collect();
+
+    // 3. Dashas from the Moon's nakshatra, dated in local time
+    let moon = grahas[1].position.longitude;
+    Ok(VedicChart {
+        ayanamsa: config.ayanamsa,
+        ayanamsa_degrees: ayanamsa,
+        lagna,
+        janma_nakshatra: nakshatra_of(moon),
+        dashas: vimshottari(moon, config.datetime),
+        grahas,
+    })
+}
+
+#[cfg(test)]
+#[path = "astrology_vedic_tests.rs"]
+mod tests;
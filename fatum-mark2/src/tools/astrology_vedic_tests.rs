@@ -0,0 +1,58 @@
+use chrono::NaiveDate;
+
+use super::{calculate_vedic_chart, nakshatra_of, Ayanamsa, VedicConfig};
+use crate::tools::astronomy::Location;
+
+fn j2000() -> VedicConfig {
+    VedicConfig {
+        datetime: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+        location: Location { longitude: 0.0, utc_offset: 0.0 },
+        latitude: 51.5,
+        ayanamsa: Ayanamsa::Lahiri,
+    }
+}
+
+#[test]
+fn test_nakshatras() {
+    let ashwini = nakshatra_of(0.0);
+    assert_eq!((ashwini.name.as_str(), ashwini.lord.as_str(), ashwini.pada), ("Ashwini", "Ketu", 1));
+    let revati = nakshatra_of(359.9);
+    assert_eq!((revati.number, revati.lord.as_str(), revati.pada), (27, "Mercury", 4));
+}
+
+#[test]
+fn test_j2000_chart() {
+    let chart = calculate_vedic_chart(&j2000()).unwrap();
+    assert!((chart.ayanamsa_degrees - 23.857).abs() < 0.001);
+
+    // Tropical Moon 223.3° less the ayanamsa: 19° Tula, in Swati
+    let moon = &chart.grahas[1];
+    assert_eq!(moon.position.rashi, "Tula");
+    assert_eq!(chart.janma_nakshatra.name, "Swati");
+    assert_eq!(chart.janma_nakshatra.pada, 4);
+
+    let rahu = chart.grahas.iter().find(|g| g.name == "Rahu").unwrap();
+    let ketu = chart.grahas.iter().find(|g| g.name == "Ketu").unwrap();
+    assert!(((ketu.position.longitude - rahu.position.longitude).rem_euclid(360.0) - 180.0).abs() < 1e-9);
+    assert!(chart.grahas.iter().all(|g| (1..=12).contains(&g.house)));
+}
+
+#[test]
+fn test_vimshottari_sequence() {
+    let chart = calculate_vedic_chart(&j2000()).unwrap();
+    let dashas = &chart.dashas;
+    assert_eq!(dashas.len(), 9);
+    assert_eq!(dashas[0].lord, "Rahu");
+    assert_eq!(dashas[0].start, NaiveDate::from_ymd_opt(2000, 1, 1).unwrap());
+    assert!(dashas.windows(2).all(|w| w[0].end == w[1].start));
+    for dasha in dashas {
+        assert_eq!(dasha.sub_periods.first().unwrap().start, dasha.start);
+        assert_eq!(dasha.sub_periods.last().unwrap().end, dasha.end);
+        assert!(dasha.sub_periods.windows(2).all(|w| w[0].end == w[1].start));
+    }
+    // Full mahadashas after the first run their whole term, and the first sub-period is the lord's own
+    assert_eq!(dashas[1].lord, "Jupiter");
+    assert_eq!(dashas[1].sub_periods.len(), 9);
+    assert_eq!(dashas[1].sub_periods[0].lord, "Jupiter");
+    assert_eq!((dashas[1].end - dashas[1].start).num_days(), (16.0 * 365.25_f64).round() as i64);
+}
@@ -0,0 +1,286 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::tools::astronomy::{ecliptic_longitude, julian_day_ut, local_sidereal_time, obliquity, Body, Location, BODIES};
+
+pub const SIGNS: [&str; 12] = [
+    "Aries", "Taurus", "Gemini", "Cancer", "Leo", "Virgo",
+    "Libra", "Scorpio", "Sagittarius", "Capricorn", "Aquarius", "Pisces",
+];
+
+/// Elements of the signs, repeating Fire, Earth, Air, Water from Aries.
+const SIGN_ELEMENTS: [&str; 4] = ["Fire", "Earth", "Air", "Water"];
+
+/// Modalities of the signs, repeating Cardinal, Fixed, Mutable from Aries.
+const SIGN_MODALITIES: [&str; 3] = ["Cardinal", "Fixed", "Mutable"];
+
+/// Major (Ptolemaic) aspects: name, exact angle and orb in degrees.
+pub const ASPECTS: [(&str, f64, f64); 5] = [
+    ("Conjunction", 0.0, 8.0),
+    ("Sextile", 60.0, 5.0),
+    ("Square", 90.0, 7.0),
+    ("Trine", 120.0, 7.0),
+    ("Opposition", 180.0, 8.0),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HouseSystem {
+    /// Time-based houses trisecting the semi-arcs; undefined beyond the polar circles.
+    #[default]
+    Placidus,
+    /// Each house is one whole sign, starting with the rising sign.
+    WholeSign,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NatalConfig {
+    /// Local clock time of birth.
+    pub datetime: NaiveDateTime,
+    /// Birth place longitude and clock offset; converts `datetime` to UT.
+    pub location: Location,
+    /// Degrees north of the equator (negative south), for the angles and houses.
+    pub latitude: f64,
+    #[serde(default)]
+    pub house_system: HouseSystem,
+}
+
+/// A point on the ecliptic and the sign it falls in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZodiacPosition {
+    pub longitude: f64,
+    pub sign: String,
+    /// Degrees into the sign, 0-30.
+    pub degree: f64,
+    /// e.g. "15°32' Leo".
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanetPosition {
+    pub body: Body,
+    pub position: ZodiacPosition,
+    /// Degrees per day; negative when retrograde.
+    pub speed: f64,
+    pub retrograde: bool,
+    /// House 1-12 the planet occupies.
+    pub house: usize,
+    pub element: String,
+    pub modality: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HouseCusp {
+    pub house: usize,
+    pub position: ZodiacPosition,
+}
+
+/// An aspect between two bodies (or between a body and an angle).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Aspect {
+    pub first: String,
+    pub second: String,
+    /// "Conjunction", "Sextile", "Square", "Trine" or "Opposition".
+    pub kind: String,
+    /// Actual separation in degrees, 0-180.
+    pub angle: f64,
+    /// Distance from exact, in degrees.
+    pub orb: f64,
+    /// True when the orb is closing.
+    pub applying: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NatalChart {
+    /// Birth time converted to UT.
+    pub utc: NaiveDateTime,
+    pub julian_day: f64,
+    pub house_system: HouseSystem,
+    pub ascendant: ZodiacPosition,
+    pub midheaven: ZodiacPosition,
+    pub planets: Vec<PlanetPosition>,
+    pub houses: Vec<HouseCusp>,
+    pub aspects: Vec<Aspect>,
+}
+
+/// Sign index (Aries = 0) of an ecliptic longitude.
+pub fn sign_index(longitude: f64) -> usize {
+    (longitude.rem_euclid(360.0) / 30.0) as usize % 12
+}
+
+pub fn zodiac_position(longitude: f64) -> ZodiacPosition {
+    let longitude = longitude.rem_euclid(360.0);
+    let sign = SIGNS[sign_index(longitude)];
+    let degree = longitude % 30.0;
+    let minutes = ((degree - degree.floor()) * 60.0).floor();
+    ZodiacPosition {
+        longitude,
+        sign: sign.to_string(),
+        degree,
+        label: format!("{}°{:02}' {}", degree.floor(), minutes, sign),
+    }
+}
+
+/// Shortest angular distance between two longitudes, 0-180.
+pub fn separation(a: f64, b: f64) -> f64 {
+    let d = (a - b).rem_euclid(360.0);
+    if d > 180.0 { 360.0 - d } else { d }
+}
+
+/// The major aspect `angle` degrees of separation falls within orb of, with the orb.
+pub fn aspect_of(angle: f64) -> Option<(&'static str, f64)> {
+    ASPECTS.iter()
+        .map(|&(name, exact, orb)| (name, (angle - exact).abs(), orb))
+        .filter(|&(_, off, orb)| off <= orb)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(name, off, _)| (name, off))
+}
+
+/// Longitude and daily motion of `body`, from its positions half a day either side.
+pub fn body_motion(body: Body, jd: f64) -> (f64, f64) {
+    let before = ecliptic_longitude(body, jd - 0.5);
+    let after = ecliptic_longitude(body, jd + 0.5);
+    let speed = (after - before + 540.0).rem_euclid(360.0) - 180.0;
+    (ecliptic_longitude(body, jd), speed)
+}
+
+/// Midheaven and Ascendant longitudes for the local sidereal time `ramc` (degrees).
+pub fn angles(ramc: f64, eps: f64, latitude: f64) -> (f64, f64) {
+    let (r, e, phi) = (ramc.to_radians(), eps.to_radians(), latitude.to_radians());
+    let mc = r.sin().atan2(r.cos() * e.cos()).to_degrees().rem_euclid(360.0);
+    let asc = r.cos().atan2(-(r.sin() * e.cos() + phi.tan() * e.sin())).to_degrees().rem_euclid(360.0);
+    (mc, asc)
+}
+
+/// Ecliptic longitude of the point with right ascension `ra` (degrees).
+fn ra_to_longitude(ra: f64, eps: f64) -> f64 {
+    let (r, e) = (ra.to_radians(), eps.to_radians());
+    r.sin().atan2(r.cos() * e.cos()).to_degrees().rem_euclid(360.0)
+}
+
+/// Placidus cusp that trisects a semi-arc: `fraction` of the diurnal semi-arc east of the
+/// MC when `above` the horizon, otherwise that fraction of the nocturnal semi-arc west of the IC.
+fn placidus_cusp(ramc: f64, eps: f64, latitude: f64, fraction: f64, above: bool) -> Result<f64> {
+    let (e, phi) = (eps.to_radians(), latitude.to_radians());
+    let mut ra = ramc + if above { 90.0 * fraction } else { 180.0 - 90.0 * fraction };
+    for _ in 0..50 {
+        let decl = (e.sin() * ra_to_longitude(ra, eps).to_radians().sin()).asin();
+        let x = phi.tan() * decl.tan();
+        if x.abs() > 1.0 {
+            anyhow::bail!("Placidus houses are undefined at latitude {:.2}; use whole_sign", latitude);
+        }
+        // Ascensional difference: how far the semi-arc differs from 90°
+        let ad = x.asin().to_degrees();
+        let next = if above { ramc + fraction * (90.0 + ad) } else { ramc + 180.0 - fraction * (90.0 - ad) };
+        if (next - ra).abs() < 1e-7 {
+            break;
+        }
+        ra = next;
+    }
+    Ok(ra_to_longitude(ra, eps))
+}
+
+/// Twelve house cusps, house 1 first.
+fn house_cusps(system: HouseSystem, ramc: f64, eps: f64, latitude: f64, mc: f64, asc: f64) -> Result<Vec<f64>> {
+    match system {
+        HouseSystem::WholeSign => {
+            let first = sign_index(asc) as f64 * 30.0;
+            Ok((0..12).map(|h| (first + 30.0 * h as f64) % 360.0).collect())
+        }
+        HouseSystem::Placidus => {
+            let c11 = placidus_cusp(ramc, eps, latitude, 1.0 / 3.0, true)?;
+            let c12 = placidus_cusp(ramc, eps, latitude, 2.0 / 3.0, true)?;
+            let c2 = placidus_cusp(ramc, eps, latitude, 2.0 / 3.0, false)?;
+            let c3 = placidus_cusp(ramc, eps, latitude, 1.0 / 3.0, false)?;
+            let first_half = [asc, c2, c3, mc + 180.0, c11 + 180.0, c12 + 180.0];
+            Ok(first_half.iter().chain(&[asc + 180.0, c2 + 180.0, c3 + 180.0, mc, c11, c12])
+                .map(|c| c.rem_euclid(360.0))
+                .collect())
+        }
+    }
+}
+
+/// House (1-12) holding `longitude` given the cusps.
+pub fn house_of(longitude: f64, cusps: &[f64]) -> usize {
+    (0..12).find(|&h| {
+        let span = (cusps[(h + 1) % 12] - cusps[h]).rem_euclid(360.0);
+        (longitude - cusps[h]).rem_euclid(360.0) < span
+    }).map_or(1, |h| h + 1)
+}
+
+/// Aspects between every pair of `points` (name, longitude, speed).
+pub fn find_aspects(points: &[(String, f64, f64)]) -> Vec<Aspect> {
+    let mut aspects = Vec::new();
+    for (i, (a, la, sa)) in points.iter().enumerate() {
+        for (b, lb, sb) in &points[i + 1..] {
+            let angle = separation(*la, *lb);
+            let Some((kind, orb)) = aspect_of(angle) else { continue };
+            // Step both points forward an hour and see whether the orb shrinks
+            let later = separation(la + sa / 24.0, lb + sb / 24.0);
+            let exact = ASPECTS.iter().find(|(n, _, _)| *n == kind).map_or(0.0, |a| a.1);
+            aspects.push(Aspect {
+                first: a.clone(),
+                second: b.clone(),
+                kind: kind.to_string(),
+                angle,
+                orb,
+                applying: (later - exact).abs() < orb,
+            });
+        }
+    }
+    aspects.sort_by(|a, b| a.orb.total_cmp(&b.orb));
+    aspects
+}
+
+/// Casts a tropical natal chart: planets Sun to Pluto, the angles, houses and major aspects.
+pub fn calculate_natal_chart(config: &NatalConfig) -> Result<NatalChart> {
+    // 1. Local clock to UT, then the sidereal time and angles of the place
+    let utc = config.datetime - Duration::seconds((config.location.utc_offset * 3600.0).round() as i64);
+    let jd = julian_day_ut(utc);
+    let eps = obliquity(jd);
+    let ramc = local_sidereal_time(jd, config.location.longitude);
+    let (mc, asc) = angles(ramc, eps, config.latitude);
+
+    // 2. Houses
+    let cusps = house_cusps(config.house_system, ramc, eps, config.latitude, mc, asc)?;
+
+    // 3. Planets with their motion, signs and houses
+    let planets: Vec<PlanetPosition> = BODIES.iter().map(|&body| {
+        let (longitude, speed) = body_motion(body, jd);
+        let sign = sign_index(longitude);
+        PlanetPosition {
+            body,
+            position: zodiac_position(longitude),
+            speed,
+            retrograde: speed < 0.0,
+            house: house_of(longitude, &cusps),
+            element: SIGN_ELEMENTS[sign % 4].to_string(),
+            modality: SIGN_MODALITIES[sign % 3].to_string(),
+        }
+    }).collect();
+
+    // 4. Aspects among the planets and to the angles (which are taken as fixed)
+    let mut points: Vec<(String, f64, f64)> = planets.iter()
+        .map(|p| (format!("{:?}", p.body), p.position.longitude, p.speed))
+        .collect();
+    points.push(("Ascendant".to_string(), asc, 0.0));
+    points.push(("Midheaven".to_string(), mc, 0.0));
+    let mut aspects = find_aspects(&points);
+    aspects.retain(|a| !(a.first == "Ascendant" && a.second == "Midheaven"));
+
+    Ok(NatalChart {
+        utc,
+        julian_day: jd,
+        house_system: config.house_system,
+        ascendant: zodiac_position(asc),
+        midheaven: zodiac_position(mc),
+        planets,
+        houses: cusps.iter().enumerate().map(|(i, &c)| HouseCusp { house: i + 1, position: zodiac_position(c) }).collect(),
+        aspects,
+    })
+}
+
+#[cfg(test)]
+#[path = "astrology_western_tests.rs"]
+mod tests;
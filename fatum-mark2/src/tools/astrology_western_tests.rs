@@ -0,0 +1,58 @@
+use chrono::NaiveDate;
+
+use super::{angles, aspect_of, calculate_natal_chart, house_of, separation, HouseSystem, NatalConfig};
+use crate::tools::astronomy::{Body, Location};
+
+fn config(house_system: HouseSystem, latitude: f64) -> NatalConfig {
+    NatalConfig {
+        // 2000-01-01 12:00 UT in London
+        datetime: NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+        location: Location { longitude: -0.13, utc_offset: 0.0 },
+        latitude,
+        house_system,
+    }
+}
+
+#[test]
+fn test_angles_on_the_equator() {
+    // With 0° Aries culminating on the equator, 0° Cancer rises
+    let (mc, asc) = angles(0.0, 23.44, 0.0);
+    assert!(mc.abs() < 1e-9);
+    assert!((asc - 90.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_aspects() {
+    assert_eq!(separation(350.0, 10.0), 20.0);
+    assert_eq!(aspect_of(123.0), Some(("Trine", 3.0)));
+    assert_eq!(aspect_of(100.0), None);
+}
+
+#[test]
+fn test_natal_chart() {
+    for system in [HouseSystem::Placidus, HouseSystem::WholeSign] {
+        let chart = calculate_natal_chart(&config(system, 51.5)).unwrap();
+        assert_eq!(chart.planets.len(), 10);
+        let sun = &chart.planets[0];
+        assert_eq!(sun.body, Body::Sun);
+        assert_eq!(sun.position.sign, "Capricorn");
+        assert!((sun.speed - 1.02).abs() < 0.02);
+        let cusps: Vec<f64> = chart.houses.iter().map(|h| h.position.longitude).collect();
+        assert_eq!(house_of(chart.ascendant.longitude, &cusps), 1);
+        // Noon at the winter solstice: the Sun is near the Midheaven
+        assert!(separation(sun.position.longitude, chart.midheaven.longitude) < 5.0);
+        if system == HouseSystem::Placidus {
+            assert!((cusps[0] - chart.ascendant.longitude).abs() < 1e-9);
+            assert!((cusps[9] - chart.midheaven.longitude).abs() < 1e-9);
+            assert_eq!(house_of(chart.midheaven.longitude, &cusps), 10);
+            // Cusps run counter-clockwise through the zodiac
+            assert!((0..12).all(|h| (cusps[(h + 1) % 12] - cusps[h]).rem_euclid(360.0) < 90.0));
+        } else {
+            assert!(cusps.iter().all(|c| c % 30.0 == 0.0));
+        }
+    }
+
+    // Beyond the polar circle Placidus fails but whole sign still works
+    assert!(calculate_natal_chart(&config(HouseSystem::Placidus, 70.0)).is_err());
+    assert!(calculate_natal_chart(&config(HouseSystem::WholeSign, 70.0)).is_ok());
+}
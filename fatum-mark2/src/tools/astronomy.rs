@@ -1,3 +1,6 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
 /// Calculates the Solar Term (0-24) based on the Sun's ecliptic longitude.
 ///
 /// This uses a simplified astronomical algorithm suitable for Feng Shui purposes.
@@ -45,3 +48,191 @@ fn sun_longitude(jd: f64) -> f64 {
     let l = q + 1.915 * g.to_radians().sin() + 0.020 * (2.0 * g).to_radians().sin();
     (l + 360.0) % 360.0
 }
+
+/// Where an event happened, for converting clock time to true solar time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Location {
+    /// Degrees east of Greenwich (negative for west), -180 to 180.
+    pub longitude: f64,
+    /// Offset of the local clock from UTC in hours, daylight saving included (e.g. 8.0, -4.0, 5.5).
+    pub utc_offset: f64,
+}
+
+impl Location {
+    /// Minutes to add to the clock to get true solar time.
+    ///
+    /// Each degree away from the time zone's meridian is four minutes, then the equation of
+    /// time corrects for the Sun running ahead of or behind its mean position.
+    pub fn correction_minutes(&self, date: NaiveDate) -> f64 {
+        (self.longitude / 15.0 - self.utc_offset) * 60.0 + equation_of_time(date)
+    }
+
+    /// True solar time of a local clock time; may fall on the previous or next day.
+    pub fn solar_time(&self, clock: NaiveDateTime) -> NaiveDateTime {
+        let seconds = (self.correction_minutes(clock.date()) * 60.0).round() as i64;
+        clock + Duration::seconds(seconds)
+    }
+}
+
+/// `(year, month, day, hour)` in true solar time for a clock reading on the hour, or the
+/// inputs unchanged without a location. Invalid dates are passed through for the caller to reject.
+pub fn solar_hour(year: i32, month: u32, day: u32, hour: u32, location: Option<&Location>) -> (i32, u32, u32, u32) {
+    let clock = NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(hour, 0, 0));
+    match (location, clock) {
+        (Some(loc), Some(clock)) => {
+            let solar = loc.solar_time(clock);
+            use chrono::Timelike;
+            (solar.year(), solar.month(), solar.day(), solar.hour())
+        }
+        _ => (year, month, day, hour),
+    }
+}
+
+/// Julian Day of a UT date and time.
+pub fn julian_day_ut(dt: NaiveDateTime) -> f64 {
+    use chrono::Timelike;
+    julian_day(dt.year(), dt.month(), dt.day()) + dt.num_seconds_from_midnight() as f64 / 86400.0
+}
+
+/// Julian centuries since J2000.0.
+fn centuries(jd: f64) -> f64 {
+    (jd - 2451545.0) / 36525.0
+}
+
+/// Mean obliquity of the ecliptic in degrees (Meeus 22.2, truncated).
+pub fn obliquity(jd: f64) -> f64 {
+    let t = centuries(jd);
+    23.439291 - 0.0130042 * t - 0.00000016 * t * t
+}
+
+/// Local mean sidereal time in degrees for `longitude` east of Greenwich (Meeus 12.4).
+pub fn local_sidereal_time(jd: f64, longitude: f64) -> f64 {
+    let t = centuries(jd);
+    let gmst = 280.46061837 + 360.98564736629 * (jd - 2451545.0) + 0.000387933 * t * t - t * t * t / 38710000.0;
+    (gmst + longitude).rem_euclid(360.0)
+}
+
+/// Bodies with a geocentric ecliptic longitude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Body {
+    Sun,
+    Moon,
+    Mercury,
+    Venus,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+    Pluto,
+}
+
+pub const BODIES: [Body; 10] = [
+    Body::Sun, Body::Moon, Body::Mercury, Body::Venus, Body::Mars,
+    Body::Jupiter, Body::Saturn, Body::Uranus, Body::Neptune, Body::Pluto,
+];
+
+/// Keplerian elements at J2000 and their rates per century: semi-major axis (AU), eccentricity,
+/// inclination, mean longitude, longitude of perihelion and of the ascending node (degrees).
+/// Standish, "Approximate Positions of the Planets" (JPL), valid 1800-2050 to about an arcminute.
+type Elements = [(f64, f64); 6];
+
+const EARTH: Elements = [(1.00000261, 0.00000562), (0.01671123, -0.00004392), (-0.00001531, -0.01294668), (100.46457166, 35999.37244981), (102.93768193, 0.32327364), (0.0, 0.0)];
+
+fn planet_elements(body: Body) -> Option<Elements> {
+    Some(match body {
+        Body::Mercury => [(0.38709927, 0.00000037), (0.20563593, 0.00001906), (7.00497902, -0.00594749), (252.25032350, 149472.67411175), (77.45779628, 0.16047689), (48.33076593, -0.12534081)],
+        Body::Venus => [(0.72333566, 0.00000390), (0.00677672, -0.00004107), (3.39467605, -0.00078890), (181.97909950, 58517.81538729), (131.60246718, 0.00268329), (76.67984255, -0.27769418)],
+        Body::Mars => [(1.52371034, 0.00001847), (0.09339410, 0.00007882), (1.84969142, -0.00813131), (-4.55343205, 19140.30268499), (-23.94362959, 0.44441088), (49.55953891, -0.29257343)],
+        Body::Jupiter => [(5.20288700, -0.00011607), (0.04838624, -0.00013253), (1.30439695, -0.00183714), (34.39644051, 3034.74612775), (14.72847983, 0.21252668), (100.47390909, 0.20469106)],
+        Body::Saturn => [(9.53667594, -0.00125060), (0.05386179, -0.00050991), (2.48599187, 0.00193609), (49.95424423, 1222.49362201), (92.59887831, -0.41897216), (113.66242448, -0.28867794)],
+        Body::Uranus => [(19.18916464, -0.00196176), (0.04725744, -0.00004397), (0.77263783, -0.00242939), (313.23810451, 428.48202785), (170.95427630, 0.40805281), (74.01692503, 0.04240589)],
+        Body::Neptune => [(30.06992276, 0.00026291), (0.00859048, 0.00005105), (1.77004347, 0.00035372), (-55.12002969, 218.45945325), (44.96476227, -0.32241464), (131.78422574, -0.00508664)],
+        Body::Pluto => [(39.48211675, -0.00031596), (0.24882730, 0.00005170), (17.14001206, 0.00004818), (238.92903833, 145.20780515), (224.06891629, -0.04062942), (110.30393684, -0.01183482)],
+        Body::Sun | Body::Moon => return None,
+    })
+}
+
+/// Heliocentric ecliptic coordinates (AU, J2000 equinox) from Keplerian elements.
+fn heliocentric(elements: &Elements, t: f64) -> [f64; 3] {
+    let [a, e, i, l, peri, node] = elements.map(|(base, rate)| base + rate * t);
+    let (i, node) = (i.to_radians(), node.to_radians());
+    let omega = (peri.to_radians() - node).rem_euclid(2.0 * std::f64::consts::PI);
+    let m = (l - peri).to_radians();
+    // Kepler's equation by Newton iteration
+    let mut ecc = m + e * m.sin();
+    for _ in 0..10 {
+        ecc -= (ecc - e * ecc.sin() - m) / (1.0 - e * ecc.cos());
+    }
+    let (x, y) = (a * (ecc.cos() - e), a * (1.0 - e * e).sqrt() * ecc.sin());
+    let (so, co, sn, cn, si, ci) = (omega.sin(), omega.cos(), node.sin(), node.cos(), i.sin(), i.cos());
+    [
+        (co * cn - so * sn * ci) * x + (-so * cn - co * sn * ci) * y,
+        (co * sn + so * cn * ci) * x + (-so * sn + co * cn * ci) * y,
+        (so * si) * x + (co * si) * y,
+    ]
+}
+
+/// Moon's longitude from the largest periodic terms of Meeus ch. 47 (about 0.01° accuracy).
+fn moon_longitude(t: f64) -> f64 {
+    let l = 218.3164477 + 481267.88123421 * t;
+    let d = (297.8501921 + 445267.1114034 * t).to_radians();
+    let m = (357.5291092 + 35999.0502909 * t).to_radians();
+    let mp = (134.9633964 + 477198.8675055 * t).to_radians();
+    let f = (93.2720950 + 483202.0175233 * t).to_radians();
+    let e = 1.0 - 0.002516 * t;
+    // (coefficient in millionths of a degree, D, M, M', F)
+    const TERMS: [(f64, f64, f64, f64, f64); 22] = [
+        (6288774.0, 0.0, 0.0, 1.0, 0.0), (1274027.0, 2.0, 0.0, -1.0, 0.0), (658314.0, 2.0, 0.0, 0.0, 0.0),
+        (213618.0, 0.0, 0.0, 2.0, 0.0), (-185116.0, 0.0, 1.0, 0.0, 0.0), (-114332.0, 0.0, 0.0, 0.0, 2.0),
+        (58793.0, 2.0, 0.0, -2.0, 0.0), (57066.0, 2.0, -1.0, -1.0, 0.0), (53322.0, 2.0, 0.0, 1.0, 0.0),
+        (45758.0, 2.0, -1.0, 0.0, 0.0), (-40923.0, 0.0, 1.0, -1.0, 0.0), (-34720.0, 1.0, 0.0, 0.0, 0.0),
+        (-30383.0, 0.0, 1.0, 1.0, 0.0), (15327.0, 2.0, 0.0, 0.0, -2.0), (-12528.0, 0.0, 0.0, 1.0, 2.0),
+        (10980.0, 0.0, 0.0, 1.0, -2.0), (10675.0, 4.0, 0.0, -1.0, 0.0), (10034.0, 0.0, 0.0, 3.0, 0.0),
+        (8548.0, 4.0, 0.0, -2.0, 0.0), (-7888.0, 2.0, 1.0, -1.0, 0.0), (-6766.0, 2.0, 1.0, 0.0, 0.0),
+        (-5163.0, 1.0, 0.0, -1.0, 0.0),
+    ];
+    let sum: f64 = TERMS.iter().map(|&(c, td, tm, tmp, tf)| {
+        // Terms involving the Sun's anomaly shrink with the Earth's orbital eccentricity
+        c * e.powf(tm.abs()) * (td * d + tm * m + tmp * mp + tf * f).sin()
+    }).sum();
+    (l + sum / 1_000_000.0).rem_euclid(360.0)
+}
+
+/// Geocentric tropical ecliptic longitude in degrees of `body` at Julian Day `jd`.
+///
+/// Planets come from their Keplerian orbits minus the Earth's, precessed from J2000 to the
+/// equinox of date; nutation and light time are ignored (well under a tenth of a degree).
+pub fn ecliptic_longitude(body: Body, jd: f64) -> f64 {
+    let t = centuries(jd);
+    if body == Body::Moon {
+        return moon_longitude(t);
+    }
+    let earth = heliocentric(&EARTH, t);
+    let [x, y, _] = match planet_elements(body) {
+        Some(elements) => {
+            let p = heliocentric(&elements, t);
+            [p[0] - earth[0], p[1] - earth[1], p[2] - earth[2]]
+        }
+        None => earth.map(|c| -c),
+    };
+    // General precession in longitude since J2000
+    let precession = 1.396971 * t + 0.0003086 * t * t;
+    (y.atan2(x).to_degrees() + precession).rem_euclid(360.0)
+}
+
+/// Tropical longitude of the Moon's mean ascending node in degrees (Meeus 47.7).
+pub fn mean_lunar_node(jd: f64) -> f64 {
+    let t = centuries(jd);
+    (125.0445479 - 1934.1362891 * t + 0.0020754 * t * t + t * t * t / 467441.0).rem_euclid(360.0)
+}
+
+/// Equation of time in minutes (apparent minus mean solar time), accurate to about a minute.
+pub fn equation_of_time(date: NaiveDate) -> f64 {
+    let b = (360.0 / 365.0 * (date.ordinal() as f64 - 81.0)).to_radians();
+    9.87 * (2.0 * b).sin() - 7.53 * b.cos() - 1.5 * b.sin()
+}
+
+#[cfg(test)]
+#[path = "astronomy_tests.rs"]
+mod tests;
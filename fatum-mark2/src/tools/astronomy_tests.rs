@@ -0,0 +1,83 @@
+use chrono::NaiveDate;
+
+use super::{ecliptic_longitude, equation_of_time, julian_day_ut, local_sidereal_time, solar_hour, Body, Location, BODIES};
+use crate::tools::da_liu_ren::{generate_da_liu_ren, DaLiuRenConfig};
+use crate::tools::feng_shui::calculate_bazi;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_equation_of_time_extremes() {
+    // About +16 minutes in early November, -14 in mid February, near zero mid April
+    assert!((equation_of_time(date(2024, 11, 3)) - 16.4).abs() < 1.0);
+    assert!((equation_of_time(date(2024, 2, 11)) + 14.2).abs() < 1.0);
+    assert!(equation_of_time(date(2024, 4, 15)).abs() < 1.0);
+}
+
+#[test]
+fn test_solar_time_far_from_meridian() {
+    // Urumqi runs on Beijing time (UTC+8) at 87.6°E: about 2h10m behind the clock
+    let urumqi = Location { longitude: 87.6, utc_offset: 8.0 };
+    let minutes = urumqi.correction_minutes(date(2024, 4, 15));
+    assert!((minutes + 129.6).abs() < 1.0, "{}", minutes);
+    // 10:00 on the clock is before 8:00 solar time: Chen hour, not Si
+    assert_eq!(solar_hour(2024, 4, 15, 10, Some(&urumqi)), (2024, 4, 15, 7));
+    // Just after midnight the solar date is still the previous day
+    assert_eq!(solar_hour(2024, 4, 15, 1, Some(&urumqi)), (2024, 4, 14, 22));
+    // Without a location nothing changes
+    assert_eq!(solar_hour(2024, 4, 15, 10, None), (2024, 4, 15, 10));
+}
+
+#[test]
+fn test_bazi_hour_pillar_follows_solar_time() {
+    let urumqi = Location { longitude: 87.6, utc_offset: 8.0 };
+    let (y, m, d, h) = solar_hour(1990, 6, 15, 10, Some(&urumqi));
+    let clock = calculate_bazi(1990, 6, 15, 10, None, None, None).unwrap();
+    let solar = calculate_bazi(y, m, d, h, None, None, None).unwrap();
+    assert!(clock.hour_pillar.ends_with("Si (Snake)"));
+    assert!(solar.hour_pillar.ends_with("Chen (Dragon)"));
+    assert_eq!(clock.day_pillar, solar.day_pillar);
+}
+
+#[test]
+fn test_da_liu_ren_derives_indices_from_datetime() {
+    let config = |location| DaLiuRenConfig {
+        day_stem_idx: 0,
+        day_branch_idx: 0,
+        hour_branch_idx: 0,
+        solar_term_idx: 0,
+        datetime: Some(date(2024, 4, 15).and_hms_opt(10, 0, 0).unwrap()),
+        location,
+    };
+    let mut plain = config(None);
+    plain.resolve_datetime();
+    // 2024-04-15 is a Ji You day; 10:00 is the Si hour; the Sun is past Qingming (term 4 from Li Chun)
+    assert_eq!((plain.day_stem_idx, plain.day_branch_idx, plain.hour_branch_idx, plain.solar_term_idx), (5, 9, 5, 4));
+
+    let mut corrected = config(Some(Location { longitude: 87.6, utc_offset: 8.0 }));
+    corrected.resolve_datetime();
+    assert_eq!(corrected.hour_branch_idx, 4);
+    assert!(generate_da_liu_ren(config(None)).is_ok());
+}
+
+#[test]
+fn test_planet_longitudes_at_j2000() {
+    // 2000-01-01 12:00 UT, against published ephemerides (degrees)
+    let jd = julian_day_ut(date(2000, 1, 1).and_hms_opt(12, 0, 0).unwrap());
+    assert_eq!(jd, 2451545.0);
+    let expected = [280.37, 223.32, 271.89, 241.57, 327.96, 25.25, 40.40, 314.81, 303.19, 251.45];
+    for (body, want) in BODIES.into_iter().zip(expected) {
+        let got = ecliptic_longitude(body, jd);
+        assert!((got - want).abs() < 0.3, "{:?}: {} vs {}", body, got, want);
+    }
+}
+
+#[test]
+fn test_moon_and_sidereal_time_meeus_examples() {
+    // Example 47.a: 1992-04-12 0h TD, λ = 133.1627°
+    assert!((ecliptic_longitude(Body::Moon, 2448724.5) - 133.1627).abs() < 0.02);
+    // Example 12.a: 1987-04-10 0h UT, GMST 13h10m46.3668s
+    assert!((local_sidereal_time(2446895.5, 0.0) - 197.693195).abs() < 1e-4);
+}
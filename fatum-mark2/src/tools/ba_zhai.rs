@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tools::feng_shui::FlyingStarChart;
+use crate::tools::floor_plan::{is_afflicted, is_timely, star_name};
+
+/// The eight sectors in compass order.
+pub const SECTORS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// The eight Ba Zhai stars, best to worst: (name, meaning, weight).
+pub const STARS: [(&str, &str, i32); 8] = [
+    ("Sheng Qi", "Vitality and wealth", 4),
+    ("Tian Yi", "Health and heavenly help", 3),
+    ("Yan Nian", "Longevity and relationships", 2),
+    ("Fu Wei", "Stability and personal growth", 1),
+    ("Huo Hai", "Mishaps and setbacks", -1),
+    ("Wu Gui", "Five Ghosts: betrayal and fire", -2),
+    ("Liu Sha", "Six Killings: scandal and legal trouble", -3),
+    ("Jue Ming", "Total Loss: illness and ruin", -4),
+];
+
+/// Sector of each star for a Kua, in `STARS` order (Sheng Qi first).
+fn star_sectors(kua: i32) -> Option<[&'static str; 8]> {
+    Some(match kua {
+        1 => ["SE", "E", "S", "N", "W", "NE", "NW", "SW"],
+        2 => ["NE", "W", "NW", "SW", "E", "SE", "S", "N"],
+        3 => ["S", "N", "SE", "E", "SW", "NW", "NE", "W"],
+        4 => ["N", "S", "E", "SE", "NW", "SW", "W", "NE"],
+        6 => ["W", "NE", "SW", "NW", "SE", "E", "N", "S"],
+        7 => ["NW", "SW", "NE", "W", "N", "S", "SE", "E"],
+        8 => ["SW", "NW", "W", "NE", "S", "N", "E", "SE"],
+        9 => ["E", "SE", "N", "S", "NE", "W", "SW", "NW"],
+        _ => return None,
+    })
+}
+
+/// The Ba Zhai star a Kua places in a sector, as an index into `STARS`.
+pub fn star_in(kua: i32, sector: &str) -> Option<usize> {
+    star_sectors(kua)?.iter().position(|s| *s == sector)
+}
+
+pub fn group(kua: i32) -> &'static str {
+    if [1, 3, 4, 9].contains(&kua) { "East Group" } else { "West Group" }
+}
+
+/// A Ba Zhai star as placed in one sector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MansionStar {
+    pub name: String,
+    pub meaning: String,
+    pub auspicious: bool,
+}
+
+impl MansionStar {
+    fn new(index: usize) -> Self {
+        let (name, meaning, weight) = STARS[index];
+        Self { name: name.to_string(), meaning: meaning.to_string(), auspicious: weight > 0 }
+    }
+}
+
+/// One of the eight sectors, read by both Ba Zhai and the Flying Stars.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MansionSector {
+    pub sector: String,
+    /// The house's star here (from the sitting direction).
+    pub house_star: MansionStar,
+    /// The occupant's star here (from their personal Kua), if known.
+    pub person_star: Option<MansionStar>,
+    pub mountain_star: i32,
+    pub water_star: i32,
+    /// Ba Zhai weights of both stars plus the Flying Star verdict; higher is better.
+    pub score: i32,
+    pub notes: Vec<String>,
+}
+
+/// Eight Mansions reading of the house, and of the occupant if their Kua is known.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaZhaiAnalysis {
+    pub house_kua: i32,
+    pub house_group: String,
+    pub person_kua: Option<i32>,
+    pub person_group: Option<String>,
+    /// True when occupant and house belong to the same (East/West) group.
+    pub compatible: Option<bool>,
+    /// All eight sectors in compass order.
+    pub sectors: Vec<MansionSector>,
+    /// Sectors ranked best first, for placing the bedroom, desk and door.
+    pub ranking: Vec<String>,
+}
+
+/// Flying Star verdict on a sector: +2 per prosperous star, -2 per afflicted one.
+fn flying_score(mountain: i32, water: i32, period: i32) -> i32 {
+    [mountain, water].iter().map(|&s| {
+        if is_timely(s, period) { 2 } else if is_afflicted(s) { -2 } else { 0 }
+    }).sum()
+}
+
+/// Places both Kuas' eight stars on the chart's sectors and scores where they agree.
+///
+/// `house_kua` comes from the sitting direction and is never 5; a person Kua of 5
+/// must already be mapped to 2 or 8.
+pub fn analyze_ba_zhai(house_kua: i32, person_kua: Option<i32>, chart: &FlyingStarChart) -> Option<BaZhaiAnalysis> {
+    star_sectors(house_kua)?;
+    let person_kua = person_kua.filter(|k| star_sectors(*k).is_some());
+
+    let sectors: Vec<MansionSector> = SECTORS.iter().map(|&sector| {
+        let palace = chart.palaces.iter().find(|p| p.sector == sector);
+        let (mountain, water) = palace.map(|p| (p.mountain_star, p.water_star)).unwrap_or((0, 0));
+        let house = star_in(house_kua, sector).unwrap();
+        let person = person_kua.and_then(|k| star_in(k, sector));
+
+        let ba_zhai = STARS[house].2 + person.map(|i| STARS[i].2).unwrap_or(0);
+        let flying = flying_score(mountain, water, chart.period);
+        let mut notes = Vec::new();
+        if person.is_some_and(|p| (STARS[p].2 > 0) != (STARS[house].2 > 0)) {
+            notes.push(format!("House {} meets your {}; the personal star decides how you use it.", STARS[house].0, STARS[person.unwrap()].0));
+        }
+        match (ba_zhai > 0, flying) {
+            (true, f) if f > 0 => notes.push("Ba Zhai and Flying Stars agree: a strong sector to activate.".to_string()),
+            (false, f) if f < 0 => notes.push("Ba Zhai and Flying Stars agree: keep this sector quiet.".to_string()),
+            (true, f) if f < 0 => notes.push(format!(
+                "Good mansion, but {} / {} fly in this period; cure them before using it.",
+                star_name(mountain), star_name(water)
+            )),
+            (false, f) if f > 0 => notes.push("Weak mansion carried by prosperous Flying Stars; usable for now.".to_string()),
+            _ => {}
+        }
+        MansionSector {
+            sector: sector.to_string(),
+            house_star: MansionStar::new(house),
+            person_star: person.map(MansionStar::new),
+            mountain_star: mountain,
+            water_star: water,
+            score: ba_zhai + flying,
+            notes,
+        }
+    }).collect();
+
+    let mut ranking: Vec<&MansionSector> = sectors.iter().collect();
+    // Stable sort keeps compass order among equal scores
+    ranking.sort_by_key(|s| -s.score);
+    let ranking = ranking.into_iter().map(|s| s.sector.clone()).collect();
+
+    Some(BaZhaiAnalysis {
+        house_kua,
+        house_group: group(house_kua).to_string(),
+        person_kua,
+        person_group: person_kua.map(|k| group(k).to_string()),
+        compatible: person_kua.map(|k| group(k) == group(house_kua)),
+        sectors,
+        ranking,
+    })
+}
+
+#[cfg(test)]
+#[path = "ba_zhai_tests.rs"]
+mod tests;
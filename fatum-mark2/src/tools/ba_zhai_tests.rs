@@ -0,0 +1,54 @@
+use super::{analyze_ba_zhai, star_in, SECTORS, STARS};
+use crate::tools::feng_shui::calculate_kua_profile;
+use crate::tools::test_fixtures::chart;
+
+#[test]
+fn test_every_kua_places_all_eight_stars() {
+    for kua in [1, 2, 3, 4, 6, 7, 8, 9] {
+        let mut stars: Vec<usize> = SECTORS.iter().map(|s| star_in(kua, s).unwrap()).collect();
+        stars.sort();
+        assert_eq!(stars, (0..8).collect::<Vec<_>>(), "Kua {}", kua);
+    }
+    assert!(star_in(5, "N").is_none());
+}
+
+#[test]
+fn test_lucky_stars_match_kua_profile() {
+    // Male 1980 -> Kua 2, female 1985 -> Kua 9, female 2008 -> Kua 8
+    for (year, gender) in [(1980, "M"), (1985, "F"), (2008, "F"), (1990, "M")] {
+        let profile = calculate_kua_profile(year, gender);
+        for (i, (sector, _)) in profile.lucky_directions.iter().enumerate() {
+            assert_eq!(star_in(profile.number, sector), Some(i), "Kua {} {}", profile.number, sector);
+        }
+    }
+}
+
+#[test]
+fn test_sectors_are_scored_against_flying_stars() {
+    // House Kua 1 (sitting N): Sheng Qi SE, Jue Ming SW. Person Kua 9: Sheng Qi E, Jue Ming NW.
+    let chart = chart(&[("SE", 9, 1, 6), ("SW", 2, 5, 6), ("E", 5, 2, 6)]);
+    let analysis = analyze_ba_zhai(1, Some(9), &chart).unwrap();
+    assert_eq!(analysis.compatible, Some(true));
+    assert_eq!(analysis.sectors.len(), 8);
+
+    let se = analysis.sectors.iter().find(|s| s.sector == "SE").unwrap();
+    assert_eq!(se.house_star.name, STARS[0].0);
+    // Sheng Qi (4) + person Tian Yi (3) + two prosperous stars (4)
+    assert_eq!(se.score, 11);
+    assert_eq!(analysis.ranking[0], "SE");
+
+    let e = analysis.sectors.iter().find(|s| s.sector == "E").unwrap();
+    assert!(e.notes.iter().any(|n| n.starts_with("Good mansion")), "{:?}", e.notes);
+    assert_eq!(analysis.ranking.last().unwrap(), "SW");
+}
+
+#[test]
+fn test_mismatched_groups_are_flagged() {
+    let analysis = analyze_ba_zhai(1, Some(2), &chart(&[])).unwrap();
+    assert_eq!(analysis.compatible, Some(false));
+    assert_eq!(analysis.person_group.as_deref(), Some("West Group"));
+
+    let house_only = analyze_ba_zhai(1, None, &chart(&[])).unwrap();
+    assert_eq!(house_only.compatible, None);
+    assert!(house_only.sectors.iter().all(|s| s.person_star.is_none()));
+}
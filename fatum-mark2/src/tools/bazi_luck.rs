@@ -0,0 +1,111 @@
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::tools::astronomy::get_solar_term;
+use crate::tools::chinese_meta::{get_branch, get_stem, get_stem_element};
+
+/// Luck pillars listed per chart (80 years from the start age).
+pub const LUCK_PILLAR_COUNT: usize = 8;
+
+/// Three days between birth and the nearest sectional term count as one year of age.
+const DAYS_PER_YEAR_OF_AGE: f64 = 3.0;
+
+/// Longest gap between two sectional terms, with margin; bounds the search.
+const MAX_SEARCH_DAYS: u64 = 40;
+
+/// A 10-year luck (Da Yun) pillar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LuckPillar {
+    pub pillar: String,
+    /// Element of the pillar's stem.
+    pub element: String,
+    pub start_age: f64,
+    pub start_year: i32,
+}
+
+/// The pillar ruling a calendar year (Liu Nian).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnualPillar {
+    pub year: i32,
+    pub pillar: String,
+    pub element: String,
+    /// True when the stem's element is one of the chart's useful elements.
+    pub favorable: bool,
+}
+
+/// Stem and branch index of a year's pillar (1924 is Jia Zi).
+pub fn year_pillar(year: i32) -> (usize, usize) {
+    let offset = (year - 1924).rem_euclid(60);
+    (offset.rem_euclid(10) as usize, offset.rem_euclid(12) as usize)
+}
+
+/// Whether the luck pillars run forward: a yang year for a man or a yin year for a woman.
+///
+/// Returns `None` when the gender is unknown.
+pub fn runs_forward(gender: Option<&str>, year_stem: usize) -> Option<bool> {
+    let yang_year = year_stem.is_multiple_of(2);
+    match gender? {
+        "M" => Some(yang_year),
+        "F" => Some(!yang_year),
+        _ => None,
+    }
+}
+
+/// Whether a sectional term (Jie, the odd 15° terms that start each solar month) falls
+/// during `date`.
+///
+/// `get_solar_term` reads the Sun at midnight, so the term is compared with the next midnight.
+fn is_jie_day(date: NaiveDate) -> bool {
+    use chrono::Datelike;
+    let Some(next) = date.succ_opt() else { return false };
+    let start = get_solar_term(date.year(), date.month(), date.day());
+    let end = get_solar_term(next.year(), next.month(), next.day());
+    end % 2 == 1 && end != start
+}
+
+/// Days from birth to the next sectional term (forward) or back to the previous one.
+pub fn days_to_jie(birth: NaiveDate, forward: bool) -> Option<u64> {
+    (if forward { 1..=MAX_SEARCH_DAYS } else { 0..=MAX_SEARCH_DAYS }).find(|&d| {
+        let date = if forward { birth.checked_add_days(Days::new(d)) } else { birth.checked_sub_days(Days::new(d)) };
+        date.is_some_and(is_jie_day)
+    })
+}
+
+/// Age at which the first luck pillar starts, to one decimal.
+pub fn luck_start_age(birth: NaiveDate, forward: bool) -> Option<f64> {
+    let days = days_to_jie(birth, forward)? as f64;
+    Some((days / DAYS_PER_YEAR_OF_AGE * 10.0).round() / 10.0)
+}
+
+/// Luck pillars step from the month pillar through the sixty Jia Zi, forward or backward.
+pub fn luck_pillars(month_stem: usize, month_branch: usize, forward: bool, start_age: f64, birth_year: i32) -> Vec<LuckPillar> {
+    (1..=LUCK_PILLAR_COUNT).map(|i| {
+        // Stepping back one is stepping forward 9 stems and 11 branches
+        let (stem, branch) = if forward { (month_stem + i, month_branch + i) } else { (month_stem + 9 * i, month_branch + 11 * i) };
+        let age = start_age + 10.0 * (i - 1) as f64;
+        LuckPillar {
+            pillar: format!("{} {}", get_stem(stem), get_branch(branch)),
+            element: get_stem_element(stem).to_string(),
+            start_age: age,
+            start_year: birth_year + age.round() as i32,
+        }
+    }).collect()
+}
+
+/// Annual pillars for `first..=last`, marked against the chart's useful elements.
+pub fn annual_pillars(first: i32, last: i32, useful: &[String]) -> Vec<AnnualPillar> {
+    (first..=last).map(|year| {
+        let (stem, branch) = year_pillar(year);
+        let element = get_stem_element(stem);
+        AnnualPillar {
+            year,
+            pillar: format!("{} {}", get_stem(stem), get_branch(branch)),
+            element: element.to_string(),
+            favorable: useful.iter().any(|e| e == element),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+#[path = "bazi_luck_tests.rs"]
+mod tests;
@@ -0,0 +1,88 @@
+use chrono::NaiveDate;
+
+use super::{annual_pillars, days_to_jie, luck_pillars, luck_start_age, runs_forward, year_pillar};
+use crate::tools::feng_shui::calculate_bazi;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_year_pillars_and_direction() {
+    assert_eq!(year_pillar(1984), (0, 0)); // Jia Zi
+    assert_eq!(year_pillar(2024), (0, 4)); // Jia Chen
+    assert_eq!(year_pillar(1923), (9, 11)); // Gui Hai
+    // Yang year: forward for men, backward for women; yin years the other way round
+    assert_eq!(runs_forward(Some("M"), 0), Some(true));
+    assert_eq!(runs_forward(Some("F"), 0), Some(false));
+    assert_eq!(runs_forward(Some("M"), 1), Some(false));
+    assert_eq!(runs_forward(Some("F"), 9), Some(true));
+    assert_eq!(runs_forward(None, 0), None);
+}
+
+#[test]
+fn test_start_age_counts_days_to_sectional_term() {
+    // In UT, Li Chun 2024 fell on 4 February and Xiao Han on 5 January
+    assert_eq!(days_to_jie(date(2024, 2, 1), true), Some(3));
+    assert_eq!(luck_start_age(date(2024, 2, 1), true), Some(1.0));
+    assert_eq!(days_to_jie(date(2024, 2, 1), false), Some(27));
+    assert_eq!(luck_start_age(date(2024, 2, 1), false), Some(9.0));
+    // Born on the term day itself: counting back gives zero
+    assert_eq!(days_to_jie(date(2024, 2, 4), false), Some(0));
+}
+
+#[test]
+fn test_luck_pillars_step_from_month_pillar() {
+    // Month pillar Bing Yin
+    let forward = luck_pillars(2, 2, true, 3.3, 2000);
+    let names: Vec<&str> = forward.iter().take(3).map(|p| p.pillar.as_str()).collect();
+    assert_eq!(names, vec!["Ding Mao (Rabbit)", "Wu Chen (Dragon)", "Ji Si (Snake)"]);
+    assert_eq!((forward[0].start_age, forward[0].start_year), (3.3, 2003));
+    assert_eq!((forward[1].start_age, forward[1].start_year), (13.3, 2013));
+    assert_eq!(forward[0].element, "Fire");
+
+    let backward = luck_pillars(2, 2, false, 5.0, 2000);
+    let names: Vec<&str> = backward.iter().take(3).map(|p| p.pillar.as_str()).collect();
+    assert_eq!(names, vec!["Yi Chou (Ox)", "Jia Zi (Rat)", "Gui Hai (Pig)"]);
+}
+
+#[test]
+fn test_annual_pillars_marked_against_useful_elements() {
+    let years = annual_pillars(2024, 2026, &["Fire".to_string()]);
+    let summary: Vec<(i32, &str, bool)> = years.iter().map(|y| (y.year, y.pillar.as_str(), y.favorable)).collect();
+    assert_eq!(summary, vec![
+        (2024, "Jia Chen (Dragon)", false),
+        (2025, "Yi Si (Snake)", false),
+        (2026, "Bing Wu (Horse)", true),
+    ]);
+}
+
+#[test]
+fn test_calculate_bazi_adds_luck_and_annual_pillars() {
+    // Geng Wu year (yang), man: forward from the Ren Wu month to Xiao Shu (7 July)
+    let profile = calculate_bazi(1990, 6, 15, 10, Some("M"), Some((2024, 2033)), None).unwrap();
+    assert_eq!(profile.month_pillar, "Ren Wu (Horse)");
+    assert_eq!(profile.luck_direction.as_deref(), Some("forward"));
+    assert_eq!(profile.luck_start_age, Some(7.3));
+    assert_eq!(profile.luck_pillars[0].pillar, "Gui Wei (Goat)");
+    assert_eq!(profile.luck_pillars.len(), 8);
+    assert_eq!(profile.annual_pillars.len(), 10);
+    assert_eq!(profile.annual_pillars[0].pillar, "Jia Chen (Dragon)");
+
+    // A woman born the same day runs backward from Grain in Ear (6 June)
+    let profile = calculate_bazi(1990, 6, 15, 10, Some("F"), None, None).unwrap();
+    assert_eq!(profile.luck_direction.as_deref(), Some("backward"));
+    assert_eq!(profile.luck_pillars[0].pillar, "Xin Si (Snake)");
+    assert!(profile.annual_pillars.is_empty());
+
+    // Without a gender there is no direction to run in
+    let profile = calculate_bazi(1990, 6, 15, 10, None, None, None).unwrap();
+    assert!(profile.luck_direction.is_none() && profile.luck_pillars.is_empty());
+}
+
+#[test]
+fn test_month_pillar_changes_at_sectional_terms() {
+    // Qingming (4 April 2024) opens the Dragon month; the Rabbit month runs before it
+    assert_eq!(calculate_bazi(2024, 4, 10, 12, None, None, None).unwrap().month_pillar, "Wu Chen (Dragon)");
+    assert_eq!(calculate_bazi(2024, 3, 25, 12, None, None, None).unwrap().month_pillar, "Ding Mao (Rabbit)");
+}
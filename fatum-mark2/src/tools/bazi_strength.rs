@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::timeline::WU_XING;
+use crate::tools::chinese_meta::{get_branch, get_stem, is_six_clash, is_six_combination, stem_combination};
+
+/// Hidden stems of each branch, main Qi first.
+pub(crate) const HIDDEN_STEMS: [&[usize]; 12] = [
+    &[9],       // Zi: Gui
+    &[5, 9, 7], // Chou: Ji, Gui, Xin
+    &[0, 2, 4], // Yin: Jia, Bing, Wu
+    &[1],       // Mao: Yi
+    &[4, 1, 9], // Chen: Wu, Yi, Gui
+    &[2, 4, 6], // Si: Bing, Wu, Geng
+    &[3, 5],    // Wu: Ding, Ji
+    &[5, 3, 1], // Wei: Ji, Ding, Yi
+    &[6, 8, 4], // Shen: Geng, Ren, Wu
+    &[7],       // You: Xin
+    &[4, 7, 3], // Xu: Wu, Xin, Ding
+    &[8, 0],    // Hai: Ren, Jia
+];
+
+/// Weight of a hidden stem by its place in the branch (main, middle, residual Qi).
+pub(crate) const HIDDEN_WEIGHTS: [f64; 3] = [1.0, 0.5, 0.3];
+
+/// The month branch commands the season, so its Qi counts double.
+const MONTH_FACTOR: f64 = 2.0;
+
+/// Clashing branches scatter each other's Qi.
+const CLASH_FACTOR: f64 = 0.7;
+
+/// A stem combination that cannot transform binds both stems and weakens them.
+const BOUND_FACTOR: f64 = 0.5;
+
+/// Support shares (percent) at or above which the day master is strong, or at or below which it is weak.
+const STRONG_AT: f64 = 55.0;
+const WEAK_AT: f64 = 45.0;
+
+pub(crate) const PILLARS: [&str; 4] = ["year", "month", "day", "hour"];
+
+/// Element index (into `WU_XING`) of a stem.
+pub(crate) fn stem_element(stem: usize) -> usize {
+    stem % 10 / 2
+}
+
+/// Day master strength and the elements that balance it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayMasterStrength {
+    pub day_master_element: String,
+    /// Phase of the day master in the birth month, e.g. "Prosperous (Wang)".
+    pub season: String,
+    /// Share of the chart's Qi that supports the day master (companions and resource), 0-100.
+    pub score: f64,
+    /// "strong", "balanced" or "weak".
+    pub verdict: String,
+    /// Weighted Qi of each element across stems and hidden stems.
+    pub element_scores: BTreeMap<String, f64>,
+    /// Elements that bring the chart back toward balance, most needed first.
+    pub useful_elements: Vec<String>,
+    pub unfavorable_elements: Vec<String>,
+    /// Seasonal, root and interaction findings behind the score.
+    pub notes: Vec<String>,
+}
+
+/// Analyses the day master (the day stem) of a chart given as `[year, month, day, hour]`
+/// stem and branch indices.
+///
+/// Visible stems count 1.0 each; hidden stems 1.0, 0.5 and 0.3 by position, doubled in the
+/// month branch. Clashes scatter both branches; adjacent combining stems transform when the
+/// month supports the new element and are otherwise bound.
+pub fn analyze(stems: [usize; 4], branches: [usize; 4]) -> DayMasterStrength {
+    let dm = stem_element(stems[2]);
+    let mut notes = Vec::new();
+
+    // 1. Interactions that change how much each stem and branch contributes
+    let month_element = stem_element(HIDDEN_STEMS[branches[1] % 12][0]);
+    let mut stem_weights = [1.0; 4];
+    let mut stem_elements = stems.map(stem_element);
+    for i in 0..3 {
+        let Some(element) = stem_combination(stems[i], stems[i + 1]) else { continue };
+        let pair = format!("{} and {} stems ({} + {})", PILLARS[i], PILLARS[i + 1], get_stem(stems[i]), get_stem(stems[i + 1]));
+        if element == month_element {
+            stem_elements[i] = element;
+            stem_elements[i + 1] = element;
+            notes.push(format!("The {} combine and transform into {} in a {} month.", pair, WU_XING[element], WU_XING[month_element]));
+        } else {
+            stem_weights[i] *= BOUND_FACTOR;
+            stem_weights[i + 1] *= BOUND_FACTOR;
+            notes.push(format!("The {} combine but cannot transform; both are bound and weakened.", pair));
+        }
+    }
+
+    let mut branch_weights = [1.0, MONTH_FACTOR, 1.0, 1.0];
+    for i in 0..4 {
+        for j in i + 1..4 {
+            let (a, b) = (branches[i] % 12, branches[j] % 12);
+            if is_six_clash(a, b) {
+                branch_weights[i] *= CLASH_FACTOR;
+                branch_weights[j] *= CLASH_FACTOR;
+                notes.push(format!("The {} and {} branches clash ({} / {}), scattering both.", PILLARS[i], PILLARS[j], get_branch(a), get_branch(b)));
+            } else if is_six_combination(a, b) {
+                notes.push(format!("The {} and {} branches combine ({} / {}).", PILLARS[i], PILLARS[j], get_branch(a), get_branch(b)));
+            }
+        }
+    }
+
+    // 2. Weigh every element; the day stem counts toward its own element
+    let mut scores = [0.0; 5];
+    for i in 0..4 {
+        scores[stem_elements[i]] += stem_weights[i];
+    }
+    let mut roots = Vec::new();
+    for (i, &branch) in branches.iter().enumerate() {
+        for (&hidden, weight) in HIDDEN_STEMS[branch % 12].iter().zip(HIDDEN_WEIGHTS) {
+            scores[stem_element(hidden)] += weight * branch_weights[i];
+            if stem_element(hidden) == dm && !roots.contains(&PILLARS[i]) {
+                roots.push(PILLARS[i]);
+            }
+        }
+    }
+
+    // 3. Season and roots
+    let season = match (month_element + 5 - dm) % 5 {
+        0 => "Prosperous (Wang)",
+        4 => "Strengthening (Xiang)",
+        1 => "Resting (Xiu)",
+        2 => "Trapped (Qiu)",
+        _ => "Dead (Si)",
+    };
+    notes.insert(0, format!("{} day master is {} in a {} month.", WU_XING[dm], season, WU_XING[month_element]));
+    if roots.is_empty() {
+        notes.push("The day master has no root in any branch.".to_string());
+    } else {
+        notes.push(format!("The day master is rooted in the {} branch(es).", roots.join(", ")));
+    }
+
+    // 4. Support (companion + resource) against everything that drains or controls it
+    let resource = (dm + 4) % 5;
+    let total: f64 = scores.iter().sum();
+    let score = ((scores[dm] + scores[resource]) / total * 1000.0).round() / 10.0;
+    let verdict = if score >= STRONG_AT { "strong" } else if score <= WEAK_AT { "weak" } else { "balanced" };
+
+    // 5. A strong day master wants draining, a weak one support; weakest candidates first
+    let mut useful: Vec<usize> = match verdict {
+        "strong" => vec![(dm + 1) % 5, (dm + 2) % 5, (dm + 3) % 5],
+        "weak" => vec![resource, dm],
+        _ => (0..5).filter(|&e| scores[e] < total / 5.0).collect(),
+    };
+    useful.sort_by(|a, b| scores[*a].total_cmp(&scores[*b]));
+    let unfavorable = (0..5).filter(|e| !useful.contains(e)).map(|e| WU_XING[e].to_string()).collect();
+
+    DayMasterStrength {
+        day_master_element: WU_XING[dm].to_string(),
+        season: season.to_string(),
+        score,
+        verdict: verdict.to_string(),
+        element_scores: WU_XING.iter().zip(scores).map(|(e, s)| (e.to_string(), (s * 100.0).round() / 100.0)).collect(),
+        useful_elements: useful.into_iter().map(|e| WU_XING[e].to_string()).collect(),
+        unfavorable_elements: unfavorable,
+        notes,
+    }
+}
+
+#[cfg(test)]
+#[path = "bazi_strength_tests.rs"]
+mod tests;
@@ -0,0 +1,83 @@
+use super::analyze;
+use crate::engine::timeline::WU_XING;
+use crate::tools::feng_shui::calculate_bazi;
+
+#[test]
+fn test_wood_in_spring_is_strong() {
+    // Jia Yin / Yi Mao / Jia Yin / Ren Hai
+    let s = analyze([0, 1, 0, 8], [2, 3, 2, 11]);
+    assert_eq!(s.day_master_element, "Wood");
+    assert_eq!(s.season, "Prosperous (Wang)");
+    assert_eq!(s.verdict, "strong");
+    assert!(s.score > 80.0, "{}", s.score);
+    assert_eq!(s.unfavorable_elements, vec!["Wood", "Water"]);
+    let mut useful = s.useful_elements.clone();
+    useful.sort();
+    assert_eq!(useful, vec!["Earth", "Fire", "Metal"]);
+    assert!(s.notes.iter().any(|n| n.contains("rooted in the year, month, day")));
+    assert!(s.notes.iter().any(|n| n.contains("Yin (Tiger) / Hai (Pig)") && n.contains("combine")));
+}
+
+#[test]
+fn test_unrooted_wood_among_metal_is_weak() {
+    // Geng Shen / Xin You / Jia Shen / Geng You
+    let s = analyze([6, 7, 0, 6], [8, 9, 8, 9]);
+    assert_eq!(s.season, "Dead (Si)");
+    assert_eq!(s.verdict, "weak");
+    assert!(s.score < 20.0, "{}", s.score);
+    // Wood (the day stem) and Water (hidden in both Shen) tie at 1.0; resource comes first
+    assert_eq!(s.element_scores["Wood"], s.element_scores["Water"]);
+    assert_eq!(s.useful_elements, vec!["Water", "Wood"]);
+    assert_eq!(s.unfavorable_elements, vec!["Fire", "Earth", "Metal"]);
+    assert!(s.notes.iter().any(|n| n.contains("no root")));
+}
+
+#[test]
+fn test_stem_combinations_transform_or_bind() {
+    // Ji (month) + Jia (day) transform into Earth in a Chen month
+    let s = analyze([4, 5, 0, 2], [4, 4, 0, 2]);
+    assert!(s.notes.iter().any(|n| n.contains("transform into Earth")), "{:?}", s.notes);
+    // Wu, Ji, Jia stems + Chen main Qi (year, doubled month) + Wu residual in Yin
+    assert_eq!(s.element_scores["Earth"], 6.3);
+
+    // In a Zi (Water) month they stay bound instead
+    let s = analyze([4, 5, 0, 2], [4, 0, 0, 2]);
+    assert!(s.notes.iter().any(|n| n.contains("cannot transform")), "{:?}", s.notes);
+    assert_eq!(s.element_scores["Earth"], 1.0 + 0.5 + 1.0 + 0.3);
+}
+
+#[test]
+fn test_clashes_scatter_branches() {
+    let calm = analyze([0, 0, 0, 0], [0, 0, 0, 0]);
+    let clashing = analyze([0, 0, 0, 0], [0, 6, 0, 0]);
+    assert!(clashing.notes.iter().any(|n| n.contains("year and month branches clash")));
+    // The Zi month now clashes three times, so Water (resource) loses weight
+    assert!(clashing.element_scores["Water"] < calm.element_scores["Water"]);
+}
+
+#[test]
+fn test_useful_and_unfavorable_cover_all_elements() {
+    let charts = [
+        ([0, 1, 0, 8], [2, 3, 2, 11]),
+        ([6, 7, 0, 6], [8, 9, 8, 9]),
+        ([2, 4, 6, 8], [1, 4, 7, 10]),
+    ];
+    for (stems, branches) in charts {
+        let s = analyze(stems, branches);
+        assert!(!s.useful_elements.is_empty());
+        let mut all: Vec<&str> = s.useful_elements.iter().chain(&s.unfavorable_elements).map(|e| e.as_str()).collect();
+        all.sort();
+        let mut expected = WU_XING.to_vec();
+        expected.sort();
+        assert_eq!(all, expected, "{:?}", s);
+    }
+}
+
+#[test]
+fn test_calculate_bazi_uses_strength_analysis() {
+    let profile = calculate_bazi(1990, 6, 15, 10, None, None, None).unwrap();
+    let strength = profile.strength.unwrap();
+    assert_eq!(profile.favorable_elements, strength.useful_elements);
+    assert!(profile.favorable_elements.iter().all(|e| WU_XING.contains(&e.as_str())));
+    assert!(["strong", "balanced", "weak"].contains(&strength.verdict.as_str()));
+}
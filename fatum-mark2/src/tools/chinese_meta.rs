@@ -8,6 +8,12 @@ pub const EARTHLY_BRANCHES: [&str; 12] = [
     "Wu (Horse)", "Wei (Goat)", "Shen (Monkey)", "You (Rooster)", "Xu (Dog)", "Hai (Pig)"
 ];
 
+/// Stem and branch index of a day's pillar, counted from 2000-01-01 (Wu Wu).
+pub fn day_pillar(date: chrono::NaiveDate) -> (usize, usize) {
+    let days = (date - chrono::NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()).num_days();
+    ((4 + days).rem_euclid(10) as usize, (6 + days).rem_euclid(12) as usize)
+}
+
 pub fn get_stem(idx: usize) -> &'static str {
     HEAVENLY_STEMS[idx % 10]
 }
@@ -39,6 +45,26 @@ pub fn is_six_combination(b1_idx: usize, b2_idx: usize) -> bool {
     pairs.contains(&(min, max))
 }
 
+/// The element (index into `WU_XING`) two combining stems transform into, if they combine
+/// (the Five Combinations: Jia + Ji = Earth, and so on).
+pub fn stem_combination(a: usize, b: usize) -> Option<usize> {
+    let (a, b) = (a % 10, b % 10);
+    if a.abs_diff(b) != 5 {
+        return None;
+    }
+    // Jia-Ji Earth, Yi-Geng Metal, Bing-Xin Water, Ding-Ren Wood, Wu-Gui Fire
+    Some((a.min(b) + 2) % 5)
+}
+
+/// Stem and branch indices of a pillar written as "Jia Zi (Rat)".
+pub fn parse_pillar(pillar: &str) -> Option<(usize, usize)> {
+    let (stem, branch) = pillar.split_once(' ')?;
+    Some((
+        HEAVENLY_STEMS.iter().position(|s| *s == stem)?,
+        EARTHLY_BRANCHES.iter().position(|b| *b == branch)?,
+    ))
+}
+
 /// Returns the element associated with a Stem.
 pub fn get_stem_element(idx: usize) -> &'static str {
     match idx % 10 {
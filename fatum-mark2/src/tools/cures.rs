@@ -0,0 +1,183 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::stats::Z_95;
+use crate::engine::timeline::WU_XING;
+use crate::engine::SimulationSession;
+use crate::tools::feng_shui::{FlyingStarChart, VirtualCure};
+use crate::tools::floor_plan::GRID;
+
+/// Simulated years per cure comparison.
+pub const CURE_TRIALS: usize = 1000;
+
+/// Afflictive stars: (star, affliction, element, severity, chance of striking in a year).
+const AFFLICTIONS: [(i32, &str, &str, f64, f64); 4] = [
+    (2, "Sickness", "Earth", 0.8, 0.4),
+    (3, "Quarrels", "Wood", 0.5, 0.3),
+    (5, "Misfortune", "Earth", 1.0, 0.5),
+    (7, "Robbery", "Metal", 0.5, 0.3),
+];
+
+/// A visiting star is a guest for the year and strikes at this fraction of its chance.
+const VISITING_SHARE: f64 = 0.5;
+
+/// Words in a cure's name that give away its element, checked in order ("salt water" is a Metal cure).
+const CURE_ELEMENTS: [(&str, &str); 20] = [
+    ("wu lou", "Metal"), ("salt", "Metal"), ("metal", "Metal"), ("brass", "Metal"), ("bell", "Metal"),
+    ("coin", "Metal"), ("chime", "Metal"), ("red", "Fire"), ("fire", "Fire"), ("candle", "Fire"),
+    ("lamp", "Fire"), ("water", "Water"), ("fountain", "Water"), ("aquarium", "Water"), ("plant", "Wood"),
+    ("bamboo", "Wood"), ("wood", "Wood"), ("crystal", "Earth"), ("stone", "Earth"), ("ceramic", "Earth"),
+];
+
+/// One virtual cure's simulated effect on the chart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CureEffect {
+    pub name: String,
+    /// Palace the cure stands in; `None` if it is off the grid.
+    pub sector: Option<String>,
+    /// Element read from the name; `None` if the name does not give one away (no effect).
+    pub element: Option<String>,
+    /// Mean fall in the affliction score from adding this cure to the others; negative if it feeds the afflictions.
+    pub marginal_efficacy: f64,
+    /// 95% confidence interval of `marginal_efficacy`.
+    pub ci_low: f64,
+    pub ci_high: f64,
+}
+
+/// The chart's afflictions simulated year by year with and without the virtual cures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CureSimulation {
+    pub trials: usize,
+    /// Mean yearly affliction score with no cures, and with all of them.
+    pub baseline_score: f64,
+    pub cured_score: f64,
+    /// Share of the baseline score the cures remove together (0 when nothing afflicts the chart).
+    pub efficacy: f64,
+    pub cures: Vec<CureEffect>,
+}
+
+/// An afflictive star in a palace: where it sits, how bad it is and how likely it strikes.
+struct Affliction {
+    sector: usize,
+    element: usize,
+    severity: f64,
+    chance: f64,
+}
+
+/// The element of the cure named `name`, as an index into `WU_XING`.
+pub fn cure_element(name: &str) -> Option<usize> {
+    let name = name.to_lowercase();
+    let (_, element) = CURE_ELEMENTS.iter().find(|(word, _)| name.contains(word))?;
+    WU_XING.iter().position(|e| e == element)
+}
+
+/// What a cure of element `cure` does to the chance of a star of element `star` striking.
+///
+/// Draining the star (the star produces the cure) works best, controlling it next; feeding
+/// it (the cure produces the star) or doubling it makes it worse.
+fn element_factor(cure: usize, star: usize) -> f64 {
+    if cure == (star + 1) % 5 {
+        0.4
+    } else if star == (cure + 2) % 5 {
+        0.6
+    } else if cure == (star + 2) % 5 {
+        0.85
+    } else if cure == star {
+        1.15
+    } else {
+        1.3
+    }
+}
+
+/// The palace (index into the flattened `GRID`) a cure stands in.
+fn cure_sector(cure: &VirtualCure) -> Option<usize> {
+    let (x, y) = (cure.x.floor(), cure.y.floor());
+    ((0.0..3.0).contains(&x) && (0.0..3.0).contains(&y)).then(|| y as usize * 3 + x as usize)
+}
+
+/// Compares the chart's yearly affliction score with and without each virtual cure.
+///
+/// Each simulated year, every afflictive mountain, water or visiting star strikes with its
+/// chance, scaled by the cures in its palace, and adds its severity to the score. All cure
+/// sets are scored on the same draws, so a cure's marginal efficacy is the paired difference
+/// between the year with every cure and the year without it. The draws come from a generator
+/// seeded with 32 bytes of session entropy.
+pub fn simulate_cures(session: &mut SimulationSession, chart: &FlyingStarChart, cures: &[VirtualCure]) -> CureSimulation {
+    let mut afflictions = Vec::new();
+    for palace in &chart.palaces {
+        let Some(sector) = GRID.iter().flatten().position(|s| *s == palace.sector) else { continue };
+        let stars = [(palace.mountain_star, 1.0), (palace.water_star, 1.0), (palace.visiting_star, VISITING_SHARE)];
+        for (star, share) in stars {
+            if let Some(&(_, _, element, severity, chance)) = AFFLICTIONS.iter().find(|a| a.0 == star) {
+                let element = WU_XING.iter().position(|e| *e == element).unwrap_or(0);
+                afflictions.push(Affliction { sector, element, severity, chance: chance * share });
+            }
+        }
+    }
+    let placed: Vec<(Option<usize>, Option<usize>)> = cures.iter().map(|c| (cure_sector(c), cure_element(&c.name))).collect();
+
+    // Chance of each affliction striking with the cures `on` in place
+    let chances = |on: &dyn Fn(usize) -> bool| -> Vec<f64> {
+        afflictions.iter().map(|a| {
+            let factor: f64 = placed.iter().enumerate()
+                .filter(|&(i, &(sector, _))| on(i) && sector == Some(a.sector))
+                .filter_map(|(_, &(_, element))| element)
+                .map(|e| element_factor(e, a.element))
+                .product();
+            (a.chance * factor).min(1.0)
+        }).collect()
+    };
+    let baseline = chances(&|_| false);
+    let cured = chances(&|_| true);
+    let without: Vec<Vec<f64>> = (0..cures.len()).map(|skip| chances(&|i| i != skip)).collect();
+
+    let mut rng = ChaCha20Rng::from_seed(session.next_seed());
+    let (mut baseline_sum, mut cured_sum) = (0.0, 0.0);
+    // Per cure: sum and sum of squares of the paired differences
+    let mut diffs = vec![(0.0, 0.0); cures.len()];
+    let mut draws = vec![0.0; afflictions.len()];
+    for _ in 0..CURE_TRIALS {
+        for d in draws.iter_mut() {
+            *d = rng.gen::<f64>();
+        }
+        let score = |chances: &[f64]| -> f64 {
+            afflictions.iter().zip(chances).zip(&draws).filter(|((_, c), d)| *d < *c).map(|((a, _), _)| a.severity).sum()
+        };
+        let with_all = score(&cured);
+        baseline_sum += score(&baseline);
+        cured_sum += with_all;
+        for (sums, chances) in diffs.iter_mut().zip(&without) {
+            let d = score(chances) - with_all;
+            sums.0 += d;
+            sums.1 += d * d;
+        }
+    }
+
+    let n = CURE_TRIALS as f64;
+    let effects = cures.iter().zip(&placed).zip(&diffs).map(|((cure, &(sector, element)), &(sum, sum_sq))| {
+        let mean = sum / n;
+        let sd = ((sum_sq / n - mean * mean).max(0.0) * n / (n - 1.0)).sqrt();
+        let half = Z_95 * sd / n.sqrt();
+        CureEffect {
+            name: cure.name.clone(),
+            sector: sector.map(|s| GRID[s / 3][s % 3].to_string()),
+            element: element.map(|e| WU_XING[e].to_string()),
+            marginal_efficacy: mean,
+            ci_low: mean - half,
+            ci_high: mean + half,
+        }
+    }).collect();
+    let (baseline_score, cured_score) = (baseline_sum / n, cured_sum / n);
+    CureSimulation {
+        trials: CURE_TRIALS,
+        baseline_score,
+        cured_score,
+        efficacy: if baseline_score > 0.0 { (baseline_score - cured_score) / baseline_score } else { 0.0 },
+        cures: effects,
+    }
+}
+
+#[cfg(test)]
+#[path = "cures_tests.rs"]
+mod tests;
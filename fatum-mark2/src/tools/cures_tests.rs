@@ -0,0 +1,53 @@
+use super::{cure_element, simulate_cures, CURE_TRIALS};
+use crate::engine::timeline::WU_XING;
+use crate::engine::SimulationSession;
+use crate::tools::feng_shui::VirtualCure;
+use crate::tools::test_fixtures::chart;
+
+fn cure(name: &str, x: f64, y: f64) -> VirtualCure {
+    VirtualCure { name: name.to_string(), x, y }
+}
+
+#[test]
+fn test_cure_elements_from_names() {
+    let element = |name: &str| cure_element(name).map(|e| WU_XING[e]);
+    assert_eq!(element("Brass Wu Lou"), Some("Metal"));
+    assert_eq!(element("Salt water cure"), Some("Metal"));
+    assert_eq!(element("Small fountain"), Some("Water"));
+    assert_eq!(element("Red candle"), Some("Fire"));
+    assert_eq!(element("Lucky bamboo"), Some("Wood"));
+    assert_eq!(element("Mystery object"), None);
+}
+
+#[test]
+fn test_metal_drains_and_fire_feeds_earth_stars() {
+    // 2 and 5 (Earth) sit in the SE palace, the top-left grid cell
+    let chart = chart(&[("SE", 2, 5, 6)]);
+    let cures = [cure("Wu Lou", 0.5, 0.5), cure("Red lamp", 0.2, 0.8), cure("Mystery object", 0.5, 0.5), cure("Wu Lou", 5.0, 5.0)];
+    let mut session = SimulationSession::new((0..=255u8).cycle().take(1024).collect());
+    let sim = simulate_cures(&mut session, &chart, &cures);
+
+    assert_eq!(sim.trials, CURE_TRIALS);
+    // 0.8 * 0.4 + 1.0 * 0.5 expected without cures
+    assert!((sim.baseline_score - 0.82).abs() < 0.1, "{}", sim.baseline_score);
+    let [metal, fire, unknown, outside] = &sim.cures[..] else { panic!("one effect per cure") };
+    assert_eq!(metal.sector.as_deref(), Some("SE"));
+    assert_eq!(metal.element.as_deref(), Some("Metal"));
+    assert!(metal.ci_low > 0.0, "{:?}", metal);
+    assert!(fire.ci_high < 0.0, "{:?}", fire);
+    assert!(metal.ci_low <= metal.marginal_efficacy && metal.marginal_efficacy <= metal.ci_high);
+    for idle in [unknown, outside] {
+        assert_eq!((idle.marginal_efficacy, idle.ci_low, idle.ci_high), (0.0, 0.0, 0.0));
+    }
+    assert_eq!(outside.sector, None);
+    assert!(sim.efficacy > 0.0 && sim.cured_score < sim.baseline_score);
+    assert_eq!(session.pool_index, 32);
+}
+
+#[test]
+fn test_quiet_chart_has_nothing_to_cure() {
+    let mut session = SimulationSession::new(vec![9; 64]);
+    let sim = simulate_cures(&mut session, &chart(&[]), &[cure("Wu Lou", 1.5, 1.5)]);
+    assert_eq!((sim.baseline_score, sim.cured_score, sim.efficacy), (0.0, 0.0, 0.0));
+    assert_eq!(sim.cures[0].sector.as_deref(), Some("Center"));
+}
@@ -0,0 +1,168 @@
+/// Degrees covered by one hexagram of the 64-hexagram ring.
+pub const SEGMENT_DEGREES: f64 = 360.0 / 64.0;
+
+/// Trigrams in Fu Xi (Early Heaven) order, with their Early Heaven Luo Shu numbers.
+///
+/// Lines are bottom first, `true` for yang.
+pub const TRIGRAMS: [(&str, [bool; 3], i32); 8] = [
+    ("Qian", [true, true, true], 9),
+    ("Dui", [true, true, false], 4),
+    ("Li", [true, false, true], 3),
+    ("Zhen", [true, false, false], 8),
+    ("Xun", [false, true, true], 2),
+    ("Kan", [false, true, false], 7),
+    ("Gen", [false, false, true], 6),
+    ("Kun", [false, false, false], 1),
+];
+
+/// King Wen number, name and keyword of each hexagram, in Fu Xi order
+/// (lower trigram in `TRIGRAMS` order, then upper trigram likewise).
+const HEXAGRAMS: [(u8, &str, &str); 64] = [
+    (1, "Qian (The Creative)", "Strength and initiative"),
+    (43, "Guai (Breakthrough)", "Resolution, decisive action"),
+    (14, "Da You (Great Possession)", "Abundance held with grace"),
+    (34, "Da Zhuang (Great Power)", "Power that must stay correct"),
+    (9, "Xiao Chu (Small Taming)", "Gradual gains, restraint"),
+    (5, "Xu (Waiting)", "Patience before success"),
+    (26, "Da Chu (Great Taming)", "Stored strength, accumulation"),
+    (11, "Tai (Peace)", "Harmony, prosperity"),
+    (10, "Lu (Treading)", "Careful conduct"),
+    (58, "Dui (The Joyous)", "Joy, communication"),
+    (38, "Kui (Opposition)", "Estrangement, small matters only"),
+    (54, "Gui Mei (The Marrying Maiden)", "Subordinate position, caution"),
+    (61, "Zhong Fu (Inner Truth)", "Sincerity, trust"),
+    (60, "Jie (Limitation)", "Moderation, boundaries"),
+    (41, "Sun (Decrease)", "Simplification, sacrifice"),
+    (19, "Lin (Approach)", "Rising influence"),
+    (13, "Tong Ren (Fellowship)", "Community, cooperation"),
+    (49, "Ge (Revolution)", "Timely change"),
+    (30, "Li (The Clinging)", "Clarity, brightness"),
+    (55, "Feng (Abundance)", "Peak fullness"),
+    (37, "Jia Ren (The Family)", "Household harmony"),
+    (63, "Ji Ji (After Completion)", "Order achieved, guard it"),
+    (22, "Bi (Grace)", "Beauty, adornment"),
+    (36, "Ming Yi (Darkening of the Light)", "Hidden talent, adversity"),
+    (25, "Wu Wang (Innocence)", "Sincerity without guile"),
+    (17, "Sui (Following)", "Adaptation"),
+    (21, "Shi He (Biting Through)", "Justice, removing obstacles"),
+    (51, "Zhen (The Arousing)", "Shock that awakens"),
+    (42, "Yi (Increase)", "Gain, growth"),
+    (3, "Zhun (Difficulty at the Beginning)", "Birth pains, perseverance"),
+    (27, "Yi (Nourishment)", "Care, sustenance"),
+    (24, "Fu (Return)", "Renewal, turning point"),
+    (44, "Gou (Coming to Meet)", "Temptation, hidden danger"),
+    (28, "Da Guo (Great Exceeding)", "Overload, strain"),
+    (50, "Ding (The Cauldron)", "Refinement, nourishing talent"),
+    (32, "Heng (Duration)", "Endurance, constancy"),
+    (57, "Xun (The Gentle)", "Gentle penetration"),
+    (48, "Jing (The Well)", "Inexhaustible source"),
+    (18, "Gu (Work on the Decayed)", "Repairing what was spoiled"),
+    (46, "Sheng (Pushing Upward)", "Steady ascent"),
+    (6, "Song (Conflict)", "Disputes, litigation"),
+    (47, "Kun (Oppression)", "Exhaustion, adversity"),
+    (64, "Wei Ji (Before Completion)", "Transition, care needed"),
+    (40, "Xie (Deliverance)", "Release, relief"),
+    (59, "Huan (Dispersion)", "Dissolving rigidity"),
+    (29, "Kan (The Abysmal)", "Repeated danger"),
+    (4, "Meng (Youthful Folly)", "Learning, inexperience"),
+    (7, "Shi (The Army)", "Discipline, leadership"),
+    (33, "Dun (Retreat)", "Strategic withdrawal"),
+    (31, "Xian (Influence)", "Attraction, courtship"),
+    (56, "Lu (The Wanderer)", "Travel, impermanence"),
+    (62, "Xiao Guo (Small Exceeding)", "Attention to small things"),
+    (53, "Jian (Development)", "Gradual progress"),
+    (39, "Jian (Obstruction)", "Obstacles, seek help"),
+    (52, "Gen (Keeping Still)", "Stillness, rest"),
+    (15, "Qian (Modesty)", "Humility rewarded"),
+    (12, "Pi (Standstill)", "Stagnation, blocked flow"),
+    (45, "Cui (Gathering Together)", "Assembly, unity"),
+    (35, "Jin (Progress)", "Rapid advancement"),
+    (16, "Yu (Enthusiasm)", "Readiness, delight"),
+    (20, "Guan (Contemplation)", "Observation, example"),
+    (8, "Bi (Holding Together)", "Union, alliance"),
+    (23, "Bo (Splitting Apart)", "Decay, erosion"),
+    (2, "Kun (The Receptive)", "Devotion, support"),
+];
+
+/// Hexagrams whose classical judgment warns rather than promises (by King Wen number).
+const INAUSPICIOUS: [u8; 14] = [3, 6, 12, 18, 23, 28, 29, 33, 36, 38, 39, 44, 47, 54];
+
+/// One hexagram of the Xuan Kong Da Gua ring.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaGua {
+    /// Position on the ring, 0 (Kun, centred on North) to 63, clockwise.
+    pub position: usize,
+    pub king_wen: u8,
+    pub name: &'static str,
+    pub meaning: &'static str,
+    pub upper: &'static str,
+    pub lower: &'static str,
+    /// Element (Gua Qi) number: the lower trigram's Early Heaven number.
+    pub element_number: i32,
+    /// Period (Gua Yun) number, from how the upper and lower trigrams differ.
+    pub period_number: i32,
+    pub auspicious: bool,
+    /// Compass span `[start, end)`; Kun's start is negative (it straddles 0°).
+    pub start_degrees: f64,
+    pub end_degrees: f64,
+}
+
+impl DaGua {
+    /// Element of the He Tu pair the element number belongs to.
+    pub fn element(&self) -> &'static str {
+        match self.element_number {
+            1 | 6 => "Water",
+            2 | 7 => "Fire",
+            3 | 8 => "Wood",
+            _ => "Metal",
+        }
+    }
+}
+
+/// Fu Xi sequence index of the hexagram at a ring position.
+///
+/// Clockwise from North: Kun, then the yang half from Fu round the east to Qian at South,
+/// then the yin half from Gou round the west to Bo.
+fn fu_xi_index(position: usize) -> usize {
+    match position {
+        0 => 63,
+        1..=32 => 32 - position,
+        _ => position - 1,
+    }
+}
+
+/// The hexagram at a ring position (taken modulo 64).
+pub fn at_position(position: usize) -> DaGua {
+    let position = position % 64;
+    let fu_xi = fu_xi_index(position);
+    let (lower, lower_lines, element_number) = TRIGRAMS[fu_xi / 8];
+    let (upper, upper_lines, _) = TRIGRAMS[fu_xi % 8];
+    // The lines where the trigrams differ form a trigram whose number is the period
+    let differing = [0, 1, 2].map(|i| lower_lines[i] != upper_lines[i]);
+    let period_number = TRIGRAMS.iter().find(|t| t.1 == differing).map(|t| t.2).unwrap();
+    let (king_wen, name, meaning) = HEXAGRAMS[fu_xi];
+    let start = (position as f64 - 0.5) * SEGMENT_DEGREES;
+    DaGua {
+        position,
+        king_wen,
+        name,
+        meaning,
+        upper,
+        lower,
+        element_number,
+        period_number,
+        auspicious: !INAUSPICIOUS.contains(&king_wen),
+        start_degrees: start,
+        end_degrees: start + SEGMENT_DEGREES,
+    }
+}
+
+/// The hexagram a compass bearing falls in.
+pub fn lookup(degrees: f64) -> DaGua {
+    let d = degrees.rem_euclid(360.0);
+    at_position(((d + SEGMENT_DEGREES / 2.0) / SEGMENT_DEGREES).floor() as usize)
+}
+
+#[cfg(test)]
+#[path = "da_gua_tests.rs"]
+mod tests;
@@ -0,0 +1,60 @@
+use super::{at_position, lookup, SEGMENT_DEGREES};
+
+#[test]
+fn test_reference_facings() {
+    // Kun straddles North and Qian is centred on South
+    assert_eq!(lookup(0.0).king_wen, 2);
+    assert_eq!(lookup(359.0).king_wen, 2);
+    assert_eq!(lookup(180.0).king_wen, 1);
+    // Return (Fu) follows Kun clockwise; Coming to Meet (Gou) follows Qian
+    assert_eq!(lookup(3.0).king_wen, 24);
+    assert_eq!(lookup(183.0).king_wen, 44);
+    // Li (Fire) lies in the east, Kan (Water) in the west, as in the Early Heaven sequence
+    assert_eq!(lookup(78.75).king_wen, 30);
+    assert_eq!(lookup(258.75).king_wen, 29);
+    // Peace and Standstill sit either side of the east-west axis
+    assert_eq!(lookup(140.625).king_wen, 11);
+    assert_eq!(lookup(320.625).king_wen, 12);
+}
+
+#[test]
+fn test_ring_covers_every_hexagram_once() {
+    let mut seen: Vec<u8> = (0..64).map(|p| at_position(p).king_wen).collect();
+    seen.sort();
+    assert_eq!(seen, (1..=64).collect::<Vec<u8>>());
+    for p in 0..64 {
+        let gua = at_position(p);
+        assert!((gua.end_degrees - gua.start_degrees - SEGMENT_DEGREES).abs() < 1e-9);
+        assert_eq!(lookup(gua.start_degrees + 0.1).position, p);
+        assert_eq!(lookup(gua.end_degrees - 0.1).position, p);
+    }
+}
+
+#[test]
+fn test_element_and_period_numbers() {
+    let qian = lookup(180.0);
+    assert_eq!((qian.element_number, qian.period_number, qian.element()), (9, 1, "Metal"));
+    let kun = lookup(0.0);
+    assert_eq!((kun.element_number, kun.period_number, kun.element()), (1, 1, "Water"));
+    // Peace: Kun over Qian, every line differs
+    let tai = lookup(140.625);
+    assert_eq!((tai.upper, tai.lower, tai.element_number, tai.period_number), ("Kun", "Qian", 9, 9));
+    // Return: only the bottom line differs
+    let fu = lookup(3.0);
+    assert_eq!((fu.element_number, fu.period_number), (8, 8));
+    // After Completion: Kan over Li
+    let ji_ji = (0..64).map(at_position).find(|g| g.king_wen == 63).unwrap();
+    assert_eq!((ji_ji.element_number, ji_ji.period_number), (3, 9));
+    // Opposite hexagrams on the ring have complementary numbers
+    for p in 0..32 {
+        let (a, b) = (at_position(p), at_position(p + 32));
+        assert_eq!(a.element_number + b.element_number, 10, "{} / {}", a.name, b.name);
+        assert_eq!(a.period_number, b.period_number, "{} / {}", a.name, b.name);
+    }
+}
+
+#[test]
+fn test_auspiciousness_follows_the_judgment() {
+    assert!(lookup(140.625).auspicious, "Peace");
+    assert!(!lookup(320.625).auspicious, "Standstill");
+}
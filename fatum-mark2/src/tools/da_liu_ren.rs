@@ -1,12 +1,39 @@
+use chrono::{Datelike, NaiveDateTime, Timelike};
 use serde::{Deserialize, Serialize};
-use crate::tools::chinese_meta::{get_branch};
+use crate::tools::astronomy::{get_solar_term, Location};
+use crate::tools::chinese_meta::{day_pillar, get_branch, get_stem};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DaLiuRenConfig {
+    #[serde(default)]
     pub day_stem_idx: usize, // 0-9
+    #[serde(default)]
     pub day_branch_idx: usize, // 0-11
+    #[serde(default)]
     pub hour_branch_idx: usize, // 0-11
-    pub solar_term_idx: usize, // 0-23
+    #[serde(default)]
+    pub solar_term_idx: usize, // 0-23 (0 = Li Chun)
+    /// Local clock time of the question; when set, the four indices above are derived from it.
+    #[serde(default)]
+    pub datetime: Option<NaiveDateTime>,
+    /// Where the question was asked; converts `datetime` to true solar time.
+    #[serde(default)]
+    pub location: Option<Location>,
+}
+
+impl DaLiuRenConfig {
+    /// Fills the day, hour and solar term indices from `datetime`, if one was given.
+    pub fn resolve_datetime(&mut self) {
+        let Some(clock) = self.datetime else { return };
+        let time = match &self.location {
+            Some(loc) => loc.solar_time(clock),
+            None => clock,
+        };
+        (self.day_stem_idx, self.day_branch_idx) = day_pillar(time.date());
+        self.hour_branch_idx = (time.hour() as usize).div_ceil(2) % 12;
+        // The astronomy module counts from the vernal equinox, three terms after Li Chun
+        self.solar_term_idx = (get_solar_term(time.year(), time.month(), time.day()) as usize + 3) % 24;
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,6 +43,27 @@ pub struct DaLiuRenChart {
     pub four_lessons: Vec<Lesson>,
     pub three_transmissions: Vec<String>, // The 3 Branches
     pub description: String,
+    /// The San Chuan method applied, e.g. "Zei Ke (Chong Shen)", "She Hai", "Fu Yin".
+    #[serde(default)]
+    pub method: String,
+    /// Heavenly General (Tian Jiang) riding each heaven-plate position, indexed like `heaven_plate`.
+    #[serde(default)]
+    pub generals: Vec<String>,
+    /// Generals riding the three transmissions.
+    #[serde(default)]
+    pub transmission_generals: Vec<String>,
+    /// Whether the daytime Noble was used (hours Mao to Shen).
+    #[serde(default)]
+    pub daytime: bool,
+    /// Day pillar the chart was cast for, e.g. "Jia Zi (Rat)".
+    #[serde(default)]
+    pub day_pillar: String,
+    /// Hour branch the Monthly General was placed on.
+    #[serde(default)]
+    pub hour_branch: String,
+    /// Solar term index used (0 = Li Chun), derived from `datetime` when one was given.
+    #[serde(default)]
+    pub solar_term_idx: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -24,9 +72,14 @@ pub struct Lesson {
     pub top: String, // Heaven Branch
     pub bottom_idx: usize,
     pub top_idx: usize,
+    /// Heavenly General riding the top branch.
+    #[serde(default)]
+    pub general: String,
 }
 
-pub fn generate_da_liu_ren(config: DaLiuRenConfig) -> Result<DaLiuRenChart, String> {
+pub fn generate_da_liu_ren(mut config: DaLiuRenConfig) -> Result<DaLiuRenChart, String> {
+    config.resolve_datetime();
+
     // 1. Determine Monthly General (Yue Jiang)
     // Formula: In Term T, Jiang is J.
     // Standard Mapping:
@@ -85,11 +138,13 @@ pub fn generate_da_liu_ren(config: DaLiuRenConfig) -> Result<DaLiuRenChart, Stri
 
     let earth_plate: Vec<String> = (0..12).map(|i| get_branch(i).to_string()).collect();
 
+    // 2b. Heavenly Generals (Tian Jiang), placed from the Noble on the heaven plate
+    let (daytime, general_map) = place_generals(config.day_stem_idx, config.hour_branch_idx, &heaven_map);
+    let generals: Vec<String> = general_map.iter().map(|g| GENERALS[*g].to_string()).collect();
+
     // 3. Four Lessons (Si Ke)
     // Determine Parasitic Branch for Day Stem (Gan Ji)
-    // Jia(0)->Yin(2), Yi(1)->Chen(4), Bing(2)->Si(5), Ding(3)->Wei(7), Wu(4)->Si(5), Ji(5)->Wei(7), Geng(6)->Shen(8), Xin(7)->Xu(10), Ren(8)->Hai(11), Gui(9)->Chou(1)
-    let gan_ji_map = [2, 4, 5, 7, 5, 7, 8, 10, 11, 1];
-    let gan_ji = gan_ji_map[config.day_stem_idx];
+    let gan_ji = GAN_JI[config.day_stem_idx];
 
     // Lesson 1: Day Stem (Gan) -> Heaven of Parasite
     // Bottom is conceptually the Stem, but we look at the Earth position of its parasite.
@@ -121,126 +176,272 @@ pub fn generate_da_liu_ren(config: DaLiuRenConfig) -> Result<DaLiuRenChart, Stri
             top_idx: *t,
             bottom: get_branch(*b).to_string(),
             top: get_branch(*t).to_string(),
+            general: generals[*b].clone(),
         }
     }).collect();
 
-    // 4. Three Transmissions (San Chuan) - Algorithm "Ze Ke" (Simplified)
-    // Determine Element relationships (0=Water, 1=Earth, 2=Wood, 3=Wood, 4=Earth, 5=Fire, 6=Fire, 7=Earth, 8=Metal, 9=Metal, 10=Earth, 11=Water)
-    // Actually, let's use a helper for element index (0=Wood, 1=Fire, 2=Earth, 3=Metal, 4=Water) to simplify comparisons.
-    // Branch Elements:
-    // Hai(11), Zi(0) -> Water (4)
-    // Yin(2), Mao(3) -> Wood (0)
-    // Si(5), Wu(6) -> Fire (1)
-    // Shen(8), You(9) -> Metal (3)
-    // Chen(4), Xu(10), Chou(1), Wei(7) -> Earth (2)
-
-    let get_el = |b: usize| -> usize {
-        match b {
-            11 | 0 => 4, // Water
-            2 | 3 => 0, // Wood
-            5 | 6 => 1, // Fire
-            8 | 9 => 3, // Metal
-            _ => 2, // Earth
-        }
-    };
+    // 4. Three Transmissions (San Chuan) - the Nine Methods (Jiu Zong Men)
+    let (method, [t1, t2, t3]) = three_transmissions(&config, &heaven_map, &lessons);
 
-    // Overcomes? (A overcomes B) -> Metal(3)>Wood(0), Wood(0)>Earth(2), Earth(2)>Water(4), Water(4)>Fire(1), Fire(1)>Metal(3)
-    let overcomes = |a: usize, b: usize| -> bool {
-        let ea = get_el(a);
-        let eb = get_el(b);
-        match (ea, eb) {
-            (3, 0) => true,
-            (0, 2) => true,
-            (2, 4) => true,
-            (4, 1) => true,
-            (1, 3) => true,
-            _ => false
-        }
-    };
+    let transmissions = vec![
+        get_branch(t1).to_string(),
+        get_branch(t2).to_string(),
+        get_branch(t3).to_string()
+    ];
+    // A transmission's general is the one riding that branch on the heaven plate
+    let transmission_generals = [t1, t2, t3].iter().map(|t| {
+        let pos = heaven_map.iter().position(|h| h == t).unwrap_or(*t);
+        generals[pos].clone()
+    }).collect();
+
+    Ok(DaLiuRenChart {
+        earth_plate,
+        heaven_plate,
+        four_lessons: lessons,
+        three_transmissions: transmissions,
+        description: method_description(method).to_string(),
+        method: method.to_string(),
+        generals,
+        transmission_generals,
+        daytime,
+        day_pillar: format!("{} {}", get_stem(config.day_stem_idx), get_branch(config.day_branch_idx)),
+        hour_branch: get_branch(config.hour_branch_idx).to_string(),
+        solar_term_idx: config.solar_term_idx,
+    })
+}
+
+// Branch elements use the Wu Xing order: 0=Wood, 1=Fire, 2=Earth, 3=Metal, 4=Water.
+// Hai(11), Zi(0) -> Water. Yin(2), Mao(3) -> Wood. Si(5), Wu(6) -> Fire.
+// Shen(8), You(9) -> Metal. Chen(4), Xu(10), Chou(1), Wei(7) -> Earth.
+const BRANCH_ELEMENTS: [usize; 12] = [4, 2, 0, 0, 2, 1, 1, 2, 3, 3, 2, 4];
+
+// Three Punishments (San Xing): the branch each branch punishes.
+// Zi-Mao punish each other, Yin -> Si -> Shen -> Yin, Chou -> Xu -> Wei -> Chou,
+// and Chen, Wu, You, Hai punish themselves.
+const PUNISHMENTS: [usize; 12] = [3, 10, 5, 0, 4, 8, 6, 1, 2, 9, 7, 11];
+
+// Parasitic Branch (Gan Ji) of each Day Stem
+// Jia(0)->Yin(2), Yi(1)->Chen(4), Bing(2)->Si(5), Ding(3)->Wei(7), Wu(4)->Si(5), Ji(5)->Wei(7), Geng(6)->Shen(8), Xin(7)->Xu(10), Ren(8)->Hai(11), Gui(9)->Chou(1)
+const GAN_JI: [usize; 10] = [2, 4, 5, 7, 5, 7, 8, 10, 11, 1];
+
+// The twelve Heavenly Generals in ring order, starting from the Noble.
+const GENERALS: [&str; 12] = [
+    "Gui Ren (Noble)", "Teng She (Soaring Snake)", "Zhu Que (Vermilion Bird)", "Liu He (Six Harmony)",
+    "Gou Chen (Hook)", "Qing Long (Green Dragon)", "Tian Kong (Heavenly Void)", "Bai Hu (White Tiger)",
+    "Tai Chang (Great Constancy)", "Xuan Wu (Dark Warrior)", "Tai Yin (Great Yin)", "Tian Hou (Empress)",
+];
+
+// Noble (Gui Ren) branch per Day Stem, (daytime, nighttime):
+// Jia/Wu/Geng -> Chou/Wei, Yi/Ji -> Zi/Shen, Bing/Ding -> Hai/You, Xin -> Wu/Yin, Ren/Gui -> Si/Mao
+pub(crate) const NOBLE: [(usize, usize); 10] = [(1, 7), (0, 8), (11, 9), (11, 9), (1, 7), (0, 8), (1, 7), (6, 2), (5, 3), (5, 3)];
+
+/// Places the twelve generals: returns whether the daytime Noble applies and, for each
+/// earth position, the index into `GENERALS` of the general riding its heaven branch.
+fn place_generals(day_stem: usize, hour_branch: usize, heaven_map: &[usize; 12]) -> (bool, [usize; 12]) {
+    // Hours Mao (5-7am) through Shen (3-5pm) take the daytime Noble
+    let daytime = (3..=8).contains(&hour_branch);
+    let (day, night) = NOBLE[day_stem];
+    let noble = if daytime { day } else { night };
+    let noble_pos = heaven_map.iter().position(|h| *h == noble).unwrap_or(noble);
+
+    // Noble over Hai..Chen of the earth plate runs clockwise, over Si..Xu counter-clockwise
+    let clockwise = !(5..=10).contains(&noble_pos);
+    let mut map = [0usize; 12];
+    for (pos, general) in map.iter_mut().enumerate() {
+        *general = if clockwise { (pos + 12 - noble_pos) % 12 } else { (noble_pos + 12 - pos) % 12 };
+    }
+    (daytime, map)
+}
+
+// Overcomes? (A overcomes B) -> Wood > Earth > Water > Fire > Metal > Wood
+fn overcomes(a: usize, b: usize) -> bool {
+    b == (a + 2) % 5
+}
+
+fn stem_element(stem: usize) -> usize {
+    stem / 2
+}
+
+/// Post Horse (Yi Ma): the branch clashing the first branch of the day branch's triad.
+fn post_horse(branch: usize) -> usize {
+    [2, 11, 8, 5][branch % 4]
+}
+
+/// Element of a lesson's lower half: the Day Stem itself for Lesson 1, else the branch.
+fn lower_element(config: &DaLiuRenConfig, i: usize, lesson: &Lesson) -> usize {
+    if i == 0 { stem_element(config.day_stem_idx) } else { BRANCH_ELEMENTS[lesson.bottom_idx] }
+}
 
-    let mut candidates_lower_destroys_upper = Vec::new(); // Ze (Rebellion) - Priority
-    let mut candidates_upper_destroys_lower = Vec::new(); // Ke (Control)
+/// Picks the method and the three transmissions (first, middle, last).
+///
+/// Order of precedence: the Fu Yin and Fan Yin plates have their own rules; otherwise
+/// Zei Ke, Bi Yong, She Hai, Yao Ke, then Ang Xing, Bie Ze or Ba Zhuan depending on how
+/// many distinct lessons the chart has.
+fn three_transmissions(config: &DaLiuRenConfig, heaven_map: &[usize; 12], lessons: &[Lesson]) -> (&'static str, [usize; 3]) {
+    let day_is_yang = config.day_stem_idx.is_multiple_of(2); // Jia(0) is Yang
+    let stem_top = lessons[0].top_idx; // Heaven over the Day Stem (Gan Shang)
+    let branch_top = lessons[2].top_idx; // Heaven over the Day Branch (Zhi Shang)
+    let regular = |t1: usize| [t1, heaven_map[t1], heaven_map[heaven_map[t1]]];
 
+    // Lessons with a lower-overcomes-upper (Zei) or upper-overcomes-lower (Ke) relation
+    let mut zei = Vec::new();
+    let mut ke = Vec::new();
     for (i, lesson) in lessons.iter().enumerate() {
-        if overcomes(lesson.bottom_idx, lesson.top_idx) {
-            candidates_lower_destroys_upper.push((i, lesson.top_idx));
-        }
-        if overcomes(lesson.top_idx, lesson.bottom_idx) {
-            candidates_upper_destroys_lower.push((i, lesson.top_idx));
+        let (lower, upper) = (lower_element(config, i, lesson), BRANCH_ELEMENTS[lesson.top_idx]);
+        let list = if overcomes(lower, upper) { &mut zei } else if overcomes(upper, lower) { &mut ke } else { continue };
+        // Identical tops count as one candidate
+        if !list.iter().any(|&(_, t): &(usize, usize)| t == lesson.top_idx) {
+            list.push((i, lesson.top_idx));
         }
     }
+    let ke_choice = if !zei.is_empty() {
+        Some(choose_ke(config, lessons, &zei, true))
+    } else if !ke.is_empty() {
+        Some(choose_ke(config, lessons, &ke, false))
+    } else {
+        None
+    };
 
-    let mut first_transmission = None;
-    let day_is_yang = config.day_stem_idx % 2 == 0; // Jia(0) is Yang
-
-    // Rule 1: Ze (Lower > Upper)
-    if !candidates_lower_destroys_upper.is_empty() {
-        if candidates_lower_destroys_upper.len() == 1 {
-            first_transmission = Some(candidates_lower_destroys_upper[0].1);
+    // Fu Yin: Heaven Plate sits on itself
+    if heaven_map[0] == 0 {
+        // The only possible Ke is the Day Stem's against its own lodging
+        let t1 = if ke_choice.is_some() || day_is_yang { stem_top } else { branch_top };
+        let t2 = if PUNISHMENTS[t1] == t1 {
+            if day_is_yang { branch_top } else { stem_top }
         } else {
-            // Bi Yong (Compare with Day)
-            // Yang Day -> Pick Yang Branch (Top).
-            // Yin Day -> Pick Yin Branch.
-            // Branch Yin/Yang:
-            // Yang: Zi(0), Yin(2), Chen(4), Wu(6), Shen(8), Xu(10) ??
-            // Standard: Odd indices in list? No.
-            // Zi(0) is Yang. Chou(1) is Yin.
-            // So Even Index = Yang, Odd Index = Yin.
-            for (_, branch_idx) in &candidates_lower_destroys_upper {
-                let branch_is_yang = branch_idx % 2 == 0;
-                if branch_is_yang == day_is_yang {
-                    first_transmission = Some(*branch_idx);
-                    break;
-                }
-            }
-            // If still none (e.g. Day Yang, but all candidates Yin), pick first?
-            if first_transmission.is_none() {
-                first_transmission = Some(candidates_lower_destroys_upper[0].1);
+            PUNISHMENTS[t1]
+        };
+        let t3 = if PUNISHMENTS[t2] == t2 || PUNISHMENTS[t2] == t1 { (t2 + 6) % 12 } else { PUNISHMENTS[t2] };
+        return ("Fu Yin", [t1, t2, t3]);
+    }
+
+    // Fan Yin: every Heaven branch clashes the Earth branch below it
+    if heaven_map[0] == 6 {
+        return match ke_choice {
+            Some((_, t1)) => ("Fan Yin", regular(t1)),
+            // Wu Yi (no support): Post Horse of the Day Branch, then Zhi Shang, then Gan Shang
+            None => ("Fan Yin (Wu Yi)", [post_horse(config.day_branch_idx), branch_top, stem_top]),
+        };
+    }
+
+    if let Some((method, t1)) = ke_choice {
+        return (method, regular(t1));
+    }
+
+    // Yao Ke: no lesson overcomes within itself; compare the upper branches with the Day Stem
+    let stem_el = stem_element(config.day_stem_idx);
+    let remote = |f: &dyn Fn(usize) -> bool| -> Vec<usize> {
+        let mut tops = Vec::new();
+        for t in lessons[1..].iter().map(|l| l.top_idx) {
+            if f(BRANCH_ELEMENTS[t]) && !tops.contains(&t) {
+                tops.push(t);
             }
         }
+        tops
+    };
+    for (method, tops) in [
+        ("Yao Ke (Hao Shi)", remote(&|el| overcomes(el, stem_el))),
+        ("Yao Ke (Dan She)", remote(&|el| overcomes(stem_el, el))),
+    ] {
+        if let Some(&first) = tops.first() {
+            // Bi Yong: prefer the branch sharing the Day Stem's polarity
+            let t1 = tops.iter().copied().find(|t| t.is_multiple_of(2) == day_is_yang).unwrap_or(first);
+            return (method, regular(t1));
+        }
+    }
+
+    let mut distinct: Vec<(usize, usize)> = lessons.iter().map(|l| (l.bottom_idx, l.top_idx)).collect();
+    distinct.sort();
+    distinct.dedup();
+    // Lesson 1's bottom is the stem's lodging; when it is the Day Branch the lessons repeat
+    let ba_zhuan = GAN_JI[config.day_stem_idx] == config.day_branch_idx;
+
+    if ba_zhuan {
+        // Ba Zhuan: Yang day counts three forward from Gan Shang, Yin day three back from Lesson 4's top
+        let t1 = if day_is_yang { (stem_top + 2) % 12 } else { (lessons[3].top_idx + 10) % 12 };
+        return ("Ba Zhuan", [t1, stem_top, stem_top]);
     }
-    // Rule 2: Ke (Upper > Lower)
-    else if !candidates_upper_destroys_lower.is_empty() {
-        if candidates_upper_destroys_lower.len() == 1 {
-            first_transmission = Some(candidates_upper_destroys_lower[0].1);
+
+    if distinct.len() < 4 {
+        // Bie Ze: Yang day takes the Heaven over the combining stem's lodging,
+        // Yin day the branch after the Day Branch in its Three Harmony triad
+        let t1 = if day_is_yang {
+            heaven_map[GAN_JI[(config.day_stem_idx + 5) % 10]]
         } else {
-            // Bi Yong
-            for (_, branch_idx) in &candidates_upper_destroys_lower {
-                let branch_is_yang = branch_idx % 2 == 0;
-                if branch_is_yang == day_is_yang {
-                    first_transmission = Some(*branch_idx);
-                    break;
-                }
-            }
-            if first_transmission.is_none() {
-                first_transmission = Some(candidates_upper_destroys_lower[0].1);
-            }
-        }
+            (config.day_branch_idx + 4) % 12
+        };
+        return ("Bie Ze", [t1, stem_top, stem_top]);
     }
 
-    // Rule 3: Yao Ke (Remote) - Simplified Fallback
-    // If no direct clashes, check Day Stem vs Heaven Plates of lessons.
-    if first_transmission.is_none() {
-        // Fallback: Just pick Lesson 1 Top (Yuan Shou / Chief)
-        // This is a gross simplification but ensures a result for MVP.
-        first_transmission = Some(lessons[0].top_idx);
+    // Ang Xing: look to You (the Rooster star Mao Xing)
+    if day_is_yang {
+        ("Ang Xing", [heaven_map[9], branch_top, stem_top])
+    } else {
+        let under_you = (0..12).find(|&p| heaven_map[p] == 9).unwrap_or(9);
+        ("Ang Xing", [under_you, stem_top, branch_top])
     }
+}
 
-    let t1 = first_transmission.unwrap();
-    let t2 = heaven_map[t1]; // Heaven atop T1
-    let t3 = heaven_map[t2]; // Heaven atop T2
+/// Zei Ke with one candidate; Bi Yong when polarity decides; She Hai otherwise.
+///
+/// `candidates` are `(lesson, upper branch)` pairs; `zei` means lower overcomes upper.
+fn choose_ke(config: &DaLiuRenConfig, lessons: &[Lesson], candidates: &[(usize, usize)], zei: bool) -> (&'static str, usize) {
+    if candidates.len() == 1 {
+        return (if zei { "Zei Ke (Chong Shen)" } else { "Zei Ke (Yuan Shou)" }, candidates[0].1);
+    }
+    let day_is_yang = config.day_stem_idx.is_multiple_of(2);
+    let same_polarity: Vec<(usize, usize)> = candidates.iter().copied().filter(|(_, t)| t.is_multiple_of(2) == day_is_yang).collect();
+    if same_polarity.len() == 1 {
+        return ("Bi Yong", same_polarity[0].1);
+    }
+    let pool = if same_polarity.is_empty() { candidates.to_vec() } else { same_polarity };
 
-    let transmissions = vec![
-        get_branch(t1).to_string(),
-        get_branch(t2).to_string(),
-        get_branch(t3).to_string()
-    ];
+    // She Hai: the branch that meets the most hardship on its way home wins.
+    // Walking from the Earth position it sits on back to its own, count every branch
+    // and lodged stem that overcomes it (Zei) or that it overcomes (Ke).
+    let depth = |(lesson, top): (usize, usize)| -> usize {
+        let el = BRANCH_ELEMENTS[top];
+        let hurts = |other: usize| if zei { overcomes(other, el) } else { overcomes(el, other) };
+        let mut p = lessons[lesson].bottom_idx;
+        let mut count = 0;
+        while p != top {
+            count += hurts(BRANCH_ELEMENTS[p]) as usize;
+            count += (0..10).filter(|&s| GAN_JI[s] == p && hurts(stem_element(s))).count();
+            p = (p + 1) % 12;
+        }
+        count
+    };
+    // Ties: Meng (Yin, Si, Shen, Hai) before Zhong (Zi, Wu, Mao, You) before Ji positions,
+    // then the stem-side lessons on a Yang day and the branch-side lessons on a Yin day
+    let seat_rank = |(lesson, _): (usize, usize)| match lessons[lesson].bottom_idx % 3 {
+        2 => 0,
+        0 => 1,
+        _ => 2,
+    };
+    let side_rank = |(lesson, _): (usize, usize)| if (lesson < 2) == day_is_yang { 0 } else { 1 };
+    let best = pool.iter().copied()
+        .min_by_key(|&c| (std::cmp::Reverse(depth(c)), seat_rank(c), side_rank(c), c.0))
+        .unwrap();
+    ("She Hai", best.1)
+}
 
-    Ok(DaLiuRenChart {
-        earth_plate,
-        heaven_plate,
-        four_lessons: lessons,
-        three_transmissions: transmissions,
-        description: "Standard Yuan Shou / Ze Ke Calculation".to_string(),
-    })
+fn method_description(method: &str) -> &'static str {
+    match method {
+        "Zei Ke (Chong Shen)" => "A single lesson whose lower branch overcomes its upper gives the first transmission.",
+        "Zei Ke (Yuan Shou)" => "A single lesson whose upper branch overcomes its lower gives the first transmission.",
+        "Bi Yong" => "Several lessons overcome; the one sharing the Day Stem's polarity is used.",
+        "She Hai" => "Several lessons overcome alike; the one meeting the most hardship on its way home is used.",
+        "Yao Ke (Hao Shi)" => "No lesson overcomes within itself; an upper branch remotely overcomes the Day Stem.",
+        "Yao Ke (Dan She)" => "No lesson overcomes within itself; the Day Stem remotely overcomes an upper branch.",
+        "Ang Xing" => "No overcoming at all; the transmissions are read from You, the Rooster.",
+        "Bie Ze" => "Only three distinct lessons and no overcoming; the first transmission is borrowed.",
+        "Ba Zhuan" => "The Day Stem lodges on the Day Branch, leaving two lessons; the first is counted three places away.",
+        "Fu Yin" => "The Heaven Plate rests on itself; the transmissions follow the Three Punishments.",
+        "Fan Yin" => "The Heaven Plate clashes the Earth Plate; the overcoming lesson still gives the first transmission.",
+        _ => "The Heaven Plate clashes the Earth Plate with no overcoming; the Post Horse leads.",
+    }
 }
+
+#[cfg(test)]
+#[path = "da_liu_ren_tests.rs"]
+mod tests;
@@ -0,0 +1,134 @@
+use chrono::NaiveDate;
+
+use super::{generate_da_liu_ren, DaLiuRenChart, DaLiuRenConfig};
+
+/// Chart for a day pillar and hour in the Yin month (Monthly General Hai).
+fn chart(stem: usize, branch: usize, hour: usize) -> DaLiuRenChart {
+    generate_da_liu_ren(DaLiuRenConfig {
+        day_stem_idx: stem,
+        day_branch_idx: branch,
+        hour_branch_idx: hour,
+        solar_term_idx: 0,
+        datetime: None,
+        location: None,
+    }).unwrap()
+}
+
+fn names(chart: &DaLiuRenChart) -> Vec<&str> {
+    chart.three_transmissions.iter().map(|t| t.split(' ').next().unwrap()).collect()
+}
+
+#[test]
+fn test_zei_ke_and_yuan_shou() {
+    // Jia Zi, Mao hour: only Lesson 1 rebels, the Jia Wood stem overcoming Xu above it
+    let c = chart(0, 0, 3);
+    assert_eq!(c.method, "Zei Ke (Chong Shen)");
+    assert_eq!(names(&c), vec!["Xu", "Wu", "Yin"]);
+    // Chou hour: Xu overcomes the Zi Water below it, and nothing rebels
+    let c = chart(0, 0, 1);
+    assert_eq!(c.method, "Zei Ke (Yuan Shou)");
+    assert_eq!(names(&c)[0], "Xu");
+}
+
+#[test]
+fn test_she_hai_counts_hardship() {
+    // Ding Mao, Chou hour: Chou (on Mao) and Hai (on Chou) both rebel; Hai meets more Earth on its way home
+    let c = chart(3, 3, 1);
+    assert_eq!(c.method, "She Hai");
+    assert_eq!(names(&c)[0], "Hai");
+}
+
+#[test]
+fn test_yao_ke() {
+    // Bing Yin, Yin hour: no lesson overcomes itself; Hai remotely overcomes the Bing Fire stem
+    let c = chart(2, 2, 2);
+    assert_eq!(c.method, "Yao Ke (Hao Shi)");
+    assert_eq!(names(&c), vec!["Hai", "Shen", "Si"]);
+}
+
+#[test]
+fn test_fu_yin_follows_punishments() {
+    // Jia Zi Fu Yin: Gan Shang Yin, which punishes Si, which punishes Shen
+    let c = chart(0, 0, 11);
+    assert_eq!(c.method, "Fu Yin");
+    assert_eq!(names(&c), vec!["Yin", "Si", "Shen"]);
+}
+
+#[test]
+fn test_fan_yin() {
+    let c = chart(0, 0, 5);
+    assert_eq!(c.method, "Fan Yin");
+    assert_eq!(names(&c), vec!["Yin", "Shen", "Yin"]);
+    // Xin Wei Fan Yin has no overcoming: Post Horse Si, then Zhi Shang, then Gan Shang
+    let c = chart(7, 7, 5);
+    assert_eq!(c.method, "Fan Yin (Wu Yi)");
+    assert_eq!(names(&c), vec!["Si", "Chou", "Chen"]);
+}
+
+#[test]
+fn test_incomplete_lessons() {
+    // Ding Wei: the stem lodges on the Day Branch (Ba Zhuan); Yin day counts back from Lesson 4
+    let c = chart(3, 7, 0);
+    assert_eq!(c.method, "Ba Zhuan");
+    assert_eq!(names(&c), vec!["Mao", "Wu", "Wu"]);
+    // Wu Chen, Xu hour: three distinct lessons; Yang day borrows Gui's lodging Chou
+    let c = chart(4, 4, 10);
+    assert_eq!(c.method, "Bie Ze");
+    assert_eq!(names(&c), vec!["Yin", "Wu", "Wu"]);
+}
+
+#[test]
+fn test_ang_xing() {
+    // Ji Si, Xu hour: no overcoming, near or remote; Yin day takes the branch under You
+    let c = chart(5, 5, 10);
+    assert_eq!(c.method, "Ang Xing");
+    assert_eq!(names(&c), vec!["Shen", "Shen", "Wu"]);
+}
+
+#[test]
+fn test_every_chart_names_its_method() {
+    for day in 0..60 {
+        for hour in 0..12 {
+            let c = chart(day % 10, day % 12, hour);
+            assert!(!c.method.is_empty() && !c.description.is_empty(), "day {} hour {}", day, hour);
+            assert_eq!(c.three_transmissions.len(), 3);
+        }
+    }
+}
+
+#[test]
+fn test_heavenly_generals() {
+    // Jia day at Zi hour: night Noble Wei rides Shen (Si..Xu), so the ring runs counter-clockwise
+    let c = chart(0, 0, 0);
+    assert!(!c.daytime);
+    assert_eq!(c.generals[8], "Gui Ren (Noble)");
+    assert_eq!(c.generals[7], "Teng She (Soaring Snake)");
+    assert_eq!(c.generals[9], "Tian Hou (Empress)");
+    // Bing day at Chen hour: day Noble Hai rides Chen (Hai..Chen), so the ring runs clockwise
+    let c = chart(2, 2, 4);
+    assert!(c.daytime);
+    assert_eq!(c.generals[4], "Gui Ren (Noble)");
+    assert_eq!(c.generals[5], "Teng She (Soaring Snake)");
+    assert_eq!(c.generals[3], "Tian Hou (Empress)");
+    for lesson in &c.four_lessons {
+        assert_eq!(lesson.general, c.generals[lesson.bottom_idx]);
+    }
+    assert_eq!(c.transmission_generals.len(), 3);
+}
+
+#[test]
+fn test_indices_from_datetime() {
+    // Chinese New Year 2024 fell on a Jia Chen day, six days after Li Chun
+    let c = generate_da_liu_ren(DaLiuRenConfig {
+        day_stem_idx: 0,
+        day_branch_idx: 0,
+        hour_branch_idx: 0,
+        solar_term_idx: 0,
+        datetime: Some(NaiveDate::from_ymd_opt(2024, 2, 10).unwrap().and_hms_opt(12, 0, 0).unwrap()),
+        location: None,
+    }).unwrap();
+    assert_eq!(c.day_pillar, "Jia Chen (Dragon)");
+    assert_eq!(c.hour_branch, "Wu (Horse)");
+    assert_eq!(c.solar_term_idx, 0);
+    assert_eq!(c.three_transmissions, chart(0, 4, 6).three_transmissions);
+}
@@ -0,0 +1,743 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::alias::AliasTable;
+use crate::engine::stats::OptionStats;
+use crate::engine::tournament::{TournamentConfig, TournamentReport};
+use crate::engine::{EntropyRange, SimulationOptions, SimulationReport, SimulationSession, TimeStep};
+
+/// Iterations when the request does not say.
+pub const DEFAULT_SIMULATIONS: usize = 10_000;
+/// Most branches one tree path may take before it is cut off.
+pub const MAX_TREE_DEPTH: usize = 100;
+/// Joins the branch labels of a tree path.
+pub const PATH_SEPARATOR: &str = " > ";
+/// Largest relative change to each branch weight in the sensitivity analysis, when not given.
+pub const DEFAULT_PERTURBATION: f64 = 0.2;
+/// Perturbed copies of the tree the sensitivity analysis re-solves.
+pub const SENSITIVITY_RUNS: usize = 200;
+/// Most paths scored against their exact probability; wider trees only score the paths walked.
+pub const MAX_SCORED_PATHS: usize = 10_000;
+
+/// A decision to put to the quantum engine: either a flat list of `options`
+/// (optionally weighted), played off head to head if `tournament` is set, or a `tree` of choices.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DecisionInput {
+    /// What is being decided; recorded with the result.
+    #[serde(default)]
+    pub question: Option<String>,
+    #[serde(default)]
+    pub options: Vec<String>,
+    /// One non-negative weight per option; equal odds when omitted.
+    #[serde(default)]
+    pub weights: Option<Vec<f64>>,
+    /// Rank the options in head-to-head matches instead of one categorical draw;
+    /// `weights` then act as strengths.
+    #[serde(default)]
+    pub tournament: Option<TournamentConfig>,
+    #[serde(default)]
+    pub tree: Option<DecisionTree>,
+    /// Largest relative change to each branch weight when testing how robust a tree's
+    /// outcome is (0.2 = up to ±20%); only used for trees with utilities.
+    #[serde(default)]
+    pub perturbation: Option<f64>,
+    pub simulations: Option<usize>,
+    #[serde(default)]
+    pub simulation_options: Option<SimulationOptions>,
+    #[serde(default)]
+    pub profile_id: Option<i64>,
+    /// Draw from a stored entropy batch instead of the live beacon.
+    #[serde(default)]
+    pub entropy_batch_id: Option<i64>,
+}
+
+/// Nodes of choices, walked from `root_id` until a branch leads nowhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionTree {
+    pub root_id: String,
+    pub nodes: Vec<DecisionNode>,
+}
+
+/// A point of choice; a node without branches is a leaf.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionNode {
+    pub id: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub branches: Vec<DecisionBranch>,
+    /// What ending here is worth; only read on leaves.
+    #[serde(default)]
+    pub utility: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionBranch {
+    pub label: String,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// The node this branch leads to; `None` ends the path.
+    #[serde(default)]
+    pub next_node_id: Option<String>,
+    /// What ending on this branch is worth, when it ends the path (or leads to a leaf
+    /// without a utility of its own).
+    #[serde(default)]
+    pub utility: Option<f64>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// Something wrong with a tree's structure, found before it is walked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeIssue {
+    /// `root_id` names no node.
+    MissingRoot,
+    /// A second node with an id already used.
+    DuplicateNode,
+    /// A branch's `next_node_id` names no node.
+    DanglingBranch,
+    /// A branch leads back to a node already on its path.
+    Cycle,
+    /// A node has branches but none can be taken.
+    DeadEnd,
+    /// No path from the root reaches the node.
+    Unreachable,
+    /// A branch that can never be taken.
+    ZeroWeightBranch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The tree cannot be simulated.
+    Error,
+    /// The tree runs, but probably not as intended.
+    Warning,
+}
+
+impl TreeIssue {
+    pub fn severity(self) -> Severity {
+        match self {
+            TreeIssue::Unreachable | TreeIssue::ZeroWeightBranch => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeDiagnostic {
+    pub issue: TreeIssue,
+    pub severity: Severity,
+    /// Index into `nodes`, when the issue belongs to a node.
+    pub node: Option<usize>,
+    /// Index into that node's `branches`, when the issue belongs to a branch.
+    pub branch: Option<usize>,
+    pub message: String,
+}
+
+impl TreeDiagnostic {
+    fn new(issue: TreeIssue, node: Option<usize>, branch: Option<usize>, message: String) -> Self {
+        Self { issue, severity: issue.severity(), node, branch, message }
+    }
+}
+
+/// How often each path through a tree was taken.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeReport {
+    pub total_simulations: usize,
+    /// The most travelled path, as branch labels joined by `PATH_SEPARATOR`.
+    pub winner: String,
+    /// Walks per path.
+    pub distribution: HashMap<String, usize>,
+    /// Paths walked significantly more or less often than their probability implies.
+    pub anomalies: Vec<String>,
+    /// Walks per path so far, at `time_series_points` steps through the run.
+    pub time_series: Vec<TimeStep>,
+    /// Each path's walks against its probability from the weights, most walked first.
+    #[serde(default)]
+    pub path_stats: Vec<OptionStats>,
+    /// Walks cut off at `MAX_TREE_DEPTH` branches.
+    pub truncated: usize,
+    /// Warnings from `check_tree`; a tree with errors is not walked.
+    #[serde(default)]
+    pub diagnostics: Vec<TreeDiagnostic>,
+    /// Expected utilities and their sensitivity, when the tree has utilities.
+    #[serde(default)]
+    pub utility: Option<UtilityAnalysis>,
+    pub entropy_ranges: Vec<EntropyRange>,
+    pub fallback_draws: usize,
+}
+
+/// One branch's odds and worth, exact and as walked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchUtility {
+    pub node_id: String,
+    pub branch: String,
+    /// Chance of taking the branch once at its node.
+    pub probability: f64,
+    /// Expected utility of the walk from this branch on.
+    pub expected_utility: f64,
+    pub walks: usize,
+    /// Mean utility of the walks that took the branch; `None` if none did.
+    pub observed_utility: Option<f64>,
+}
+
+/// How robust the outcome is to the branch weights being somewhat off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sensitivity {
+    pub perturbation: f64,
+    pub runs: usize,
+    /// The most probable path under the given weights, and its probability.
+    pub likeliest_path: String,
+    pub likeliest_probability: f64,
+    /// Share of perturbed trees whose most probable path is unchanged.
+    pub path_stable: f64,
+    /// Share of perturbed trees whose best choice is unchanged.
+    pub choice_stable: f64,
+    /// How often each root branch was the best choice across the perturbed trees.
+    pub best_choice_counts: HashMap<String, usize>,
+    /// Mean absolute change in the tree's expected utility.
+    pub mean_utility_shift: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtilityAnalysis {
+    /// Expected utility of a walk from the root, from the weights.
+    pub expected_utility: f64,
+    /// Mean utility of the simulated walks (cut-off walks count as 0).
+    pub observed_utility: f64,
+    /// The root branch with the highest expected utility: the pick if the first step were yours.
+    pub best_choice: String,
+    /// Every branch, node by node.
+    pub branches: Vec<BranchUtility>,
+    pub sensitivity: Sensitivity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DecisionReport {
+    Simple(SimulationReport),
+    Tournament(TournamentReport),
+    Tree(TreeReport),
+}
+
+impl DecisionReport {
+    pub fn winner(&self) -> &str {
+        match self {
+            DecisionReport::Simple(r) => &r.winner,
+            DecisionReport::Tournament(r) => &r.winner,
+            DecisionReport::Tree(r) => &r.winner,
+        }
+    }
+}
+
+pub struct DecisionTool;
+
+impl DecisionTool {
+    /// Runs the decision on the session's entropy.
+    pub fn decide(session: &mut SimulationSession, input: &DecisionInput) -> Result<DecisionReport> {
+        let simulations = input.simulations.unwrap_or(DEFAULT_SIMULATIONS);
+        let sim_options = input.simulation_options.clone().unwrap_or_default();
+        match (&input.tree, &input.tournament) {
+            (Some(tree), _) => {
+                let perturbation = input.perturbation.unwrap_or(DEFAULT_PERTURBATION);
+                Ok(DecisionReport::Tree(simulate_tree(session, tree, simulations, perturbation, &sim_options)?))
+            }
+            _ if input.options.is_empty() => bail!("A decision needs options or a tree"),
+            (None, Some(config)) => Ok(DecisionReport::Tournament(session.simulate_tournament(&input.options, input.weights.as_deref(), config))),
+            (None, None) => Ok(DecisionReport::Simple(session.simulate_decision(
+                &input.options,
+                input.weights.as_deref(),
+                simulations,
+                &sim_options,
+            ))),
+        }
+    }
+}
+
+fn live(branch: &DecisionBranch) -> bool {
+    branch.weight.is_finite() && branch.weight > 0.0
+}
+
+/// Node indices by id; the first node wins a duplicated id.
+fn node_index(tree: &DecisionTree) -> HashMap<&str, usize> {
+    let mut index = HashMap::with_capacity(tree.nodes.len());
+    for (i, node) in tree.nodes.iter().enumerate() {
+        index.entry(node.id.as_str()).or_insert(i);
+    }
+    index
+}
+
+/// Finds dangling branches, cycles, unreachable nodes and zero-weight branches, errors first.
+pub fn check_tree(tree: &DecisionTree) -> Vec<TreeDiagnostic> {
+    let index = node_index(tree);
+    let mut found = Vec::new();
+
+    // 1. Ids, targets and weights, node by node
+    for (i, node) in tree.nodes.iter().enumerate() {
+        if index[node.id.as_str()] != i {
+            found.push(TreeDiagnostic::new(TreeIssue::DuplicateNode, Some(i), None, format!("Node id {:?} is used more than once", node.id)));
+        }
+        for (j, branch) in node.branches.iter().enumerate() {
+            if let Some(next) = branch.next_node_id.as_deref().filter(|id| !index.contains_key(id)) {
+                found.push(TreeDiagnostic::new(
+                    TreeIssue::DanglingBranch,
+                    Some(i),
+                    Some(j),
+                    format!("Branch {:?} of {:?} leads to unknown node {:?}", branch.label, node.id, next),
+                ));
+            }
+            if !live(branch) {
+                found.push(TreeDiagnostic::new(
+                    TreeIssue::ZeroWeightBranch,
+                    Some(i),
+                    Some(j),
+                    format!("Branch {:?} of {:?} has no weight and is never taken", branch.label, node.id),
+                ));
+            }
+        }
+        if !node.branches.is_empty() && !node.branches.iter().any(live) {
+            found.push(TreeDiagnostic::new(TreeIssue::DeadEnd, Some(i), None, format!("Node {:?} has no branch with positive weight", node.id)));
+        }
+    }
+    let edges = |at: usize| {
+        tree.nodes[at].branches.iter().enumerate()
+            .filter(|(_, b)| live(b))
+            .filter_map(|(j, b)| Some((j, *index.get(b.next_node_id.as_deref()?)?)))
+    };
+
+    // 2. Cycles, by depth-first search over takeable branches (0 unseen, 1 on the path, 2 done)
+    let mut state = vec![0u8; tree.nodes.len()];
+    for start in 0..tree.nodes.len() {
+        if state[start] != 0 {
+            continue;
+        }
+        state[start] = 1;
+        let mut stack = vec![(start, edges(start))];
+        while let Some((at, next)) = stack.last_mut() {
+            let at = *at;
+            let Some((j, to)) = next.next() else {
+                state[at] = 2;
+                stack.pop();
+                continue;
+            };
+            match state[to] {
+                0 => {
+                    state[to] = 1;
+                    stack.push((to, edges(to)));
+                }
+                1 => {
+                    let from = stack.iter().position(|(n, _)| *n == to).unwrap_or(0);
+                    let ids: Vec<&str> = stack[from..].iter().map(|(n, _)| tree.nodes[*n].id.as_str()).chain([tree.nodes[to].id.as_str()]).collect();
+                    found.push(TreeDiagnostic::new(
+                        TreeIssue::Cycle,
+                        Some(at),
+                        Some(j),
+                        format!("Branch {:?} of {:?} loops back: {}", tree.nodes[at].branches[j].label, tree.nodes[at].id, ids.join(PATH_SEPARATOR)),
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // 3. Reachability from the root
+    match index.get(tree.root_id.as_str()) {
+        None => found.push(TreeDiagnostic::new(TreeIssue::MissingRoot, None, None, format!("Root node {:?} is not in the tree", tree.root_id))),
+        Some(&root) => {
+            let mut seen = vec![false; tree.nodes.len()];
+            seen[root] = true;
+            let mut queue = vec![root];
+            while let Some(at) = queue.pop() {
+                for (_, to) in edges(at) {
+                    if !std::mem::replace(&mut seen[to], true) {
+                        queue.push(to);
+                    }
+                }
+            }
+            for (i, node) in tree.nodes.iter().enumerate().filter(|(i, _)| !seen[*i] && index[tree.nodes[*i].id.as_str()] == *i) {
+                found.push(TreeDiagnostic::new(TreeIssue::Unreachable, Some(i), None, format!("Node {:?} is never reached from the root", node.id)));
+            }
+        }
+    }
+
+    found.sort_by_key(|d| d.severity != Severity::Error);
+    found
+}
+
+/// A checked tree's branches numbered flat (node by node), with a children-first node order.
+struct Layout {
+    root: usize,
+    /// Index of each node's first branch in the flat numbering.
+    offsets: Vec<usize>,
+    /// Flat branch number to the node it leads to.
+    next: Vec<Option<usize>>,
+    /// Every node after all the nodes its branches lead to.
+    order: Vec<usize>,
+}
+
+impl Layout {
+    fn new(tree: &DecisionTree) -> Self {
+        let index = node_index(tree);
+        let mut offsets = Vec::with_capacity(tree.nodes.len());
+        let mut next = Vec::new();
+        for node in &tree.nodes {
+            offsets.push(next.len());
+            next.extend(node.branches.iter().map(|b| b.next_node_id.as_deref().map(|id| index[id])));
+        }
+        // Post-order DFS; the tree is acyclic once `check_tree` passes
+        let mut done = vec![false; tree.nodes.len()];
+        let mut order = Vec::with_capacity(tree.nodes.len());
+        for start in 0..tree.nodes.len() {
+            if done[start] {
+                continue;
+            }
+            done[start] = true;
+            let mut stack = vec![(start, 0)];
+            while let Some((at, j)) = stack.last_mut() {
+                let at = *at;
+                if *j == tree.nodes[at].branches.len() {
+                    order.push(at);
+                    stack.pop();
+                    continue;
+                }
+                let to = next[offsets[at] + *j];
+                *j += 1;
+                if let Some(to) = to.filter(|&to| !done[to]) {
+                    done[to] = true;
+                    stack.push((to, 0));
+                }
+            }
+        }
+        Self { root: index[tree.root_id.as_str()], offsets, next, order }
+    }
+
+    /// What a walk ending on flat branch `b` is worth.
+    fn end_utility(&self, tree: &DecisionTree, node: usize, b: usize) -> f64 {
+        let branch = &tree.nodes[node].branches[b - self.offsets[node]];
+        match self.next[b] {
+            Some(leaf) => tree.nodes[leaf].utility.or(branch.utility).unwrap_or(0.0),
+            None => branch.utility.unwrap_or(0.0),
+        }
+    }
+
+    /// Each branch's chance at its node under `weights` (flat, one per branch).
+    fn probabilities(&self, tree: &DecisionTree, weights: &[f64]) -> Vec<f64> {
+        let mut probs = vec![0.0; weights.len()];
+        for (i, node) in tree.nodes.iter().enumerate() {
+            let range = self.offsets[i]..self.offsets[i] + node.branches.len();
+            let live = |w: f64| if w.is_finite() && w > 0.0 { w } else { 0.0 };
+            let total: f64 = weights[range.clone()].iter().map(|&w| live(w)).sum();
+            if total > 0.0 {
+                for (p, &w) in probs[range.clone()].iter_mut().zip(&weights[range]) {
+                    *p = live(w) / total;
+                }
+            }
+        }
+        probs
+    }
+
+    /// Expected utility of each branch (flat) and of the root.
+    fn expected_utilities(&self, tree: &DecisionTree, probs: &[f64]) -> (Vec<f64>, f64) {
+        let mut node_eu = vec![0.0; tree.nodes.len()];
+        let mut branch_eu = vec![0.0; probs.len()];
+        for &at in &self.order {
+            let node = &tree.nodes[at];
+            if node.branches.is_empty() {
+                node_eu[at] = node.utility.unwrap_or(0.0);
+                continue;
+            }
+            for b in self.offsets[at]..self.offsets[at] + node.branches.len() {
+                branch_eu[b] = match self.next[b] {
+                    Some(to) if !tree.nodes[to].branches.is_empty() => node_eu[to],
+                    _ => self.end_utility(tree, at, b),
+                };
+                node_eu[at] += probs[b] * branch_eu[b];
+            }
+        }
+        (branch_eu, node_eu[self.root])
+    }
+
+    /// The single most probable path (flat branch numbers) and its probability.
+    fn likeliest_path(&self, tree: &DecisionTree, probs: &[f64]) -> (Vec<usize>, f64) {
+        let mut best = vec![(1.0, None); tree.nodes.len()];
+        for &at in &self.order {
+            let count = tree.nodes[at].branches.len();
+            if count == 0 {
+                continue;
+            }
+            let mut pick = (0.0, None);
+            for (b, p) in probs.iter().enumerate().skip(self.offsets[at]).take(count) {
+                let p = p * self.next[b].map_or(1.0, |to| best[to].0);
+                if p > pick.0 {
+                    pick = (p, Some(b));
+                }
+            }
+            best[at] = pick;
+        }
+        let mut path = Vec::new();
+        let mut at = Some(self.root);
+        while let Some(b) = at.and_then(|n| best[n].1) {
+            path.push(b);
+            at = self.next[b];
+        }
+        (path, best[self.root].0)
+    }
+
+    /// The root branch with the highest expected utility (first wins a tie).
+    fn best_choice(&self, tree: &DecisionTree, probs: &[f64], branch_eu: &[f64]) -> Option<usize> {
+        let root = self.offsets[self.root]..self.offsets[self.root] + tree.nodes[self.root].branches.len();
+        root.filter(|&b| probs[b] > 0.0)
+            .fold(None, |best: Option<usize>, b| match best {
+                Some(a) if branch_eu[a] >= branch_eu[b] => Some(a),
+                _ => Some(b),
+            })
+    }
+
+    /// Every path from the root (flat branches) with its probability, or `None` past `limit` paths.
+    ///
+    /// Paths end where a walk would: on a branch to nowhere or to a leaf, or after `MAX_TREE_DEPTH` branches.
+    fn paths(&self, tree: &DecisionTree, probs: &[f64], limit: usize) -> Option<Vec<(Vec<usize>, f64)>> {
+        if tree.nodes[self.root].branches.is_empty() {
+            return Some(vec![(vec![], 1.0)]);
+        }
+        let mut found = Vec::new();
+        let mut stack = vec![(self.root, Vec::new(), 1.0)];
+        while let Some((at, prefix, p)) = stack.pop() {
+            for (b, &q) in probs.iter().enumerate().skip(self.offsets[at]).take(tree.nodes[at].branches.len()) {
+                if q <= 0.0 {
+                    continue;
+                }
+                let mut path: Vec<usize> = prefix.clone();
+                path.push(b);
+                let p = p * q;
+                match self.next[b] {
+                    Some(to) if !tree.nodes[to].branches.is_empty() && path.len() < MAX_TREE_DEPTH => stack.push((to, path, p)),
+                    _ if found.len() == limit => return None,
+                    _ => found.push((path, p)),
+                }
+            }
+        }
+        Some(found)
+    }
+
+    fn path_label(&self, tree: &DecisionTree, path: &[usize]) -> String {
+        path.iter().map(|&b| self.label(tree, b)).collect::<Vec<_>>().join(PATH_SEPARATOR)
+    }
+
+    fn label<'t>(&self, tree: &'t DecisionTree, b: usize) -> &'t str {
+        let node = self.offsets.partition_point(|&o| o <= b) - 1;
+        &tree.nodes[node].branches[b - self.offsets[node]].label
+    }
+}
+
+fn has_utilities(tree: &DecisionTree) -> bool {
+    tree.nodes.iter().any(|n| n.utility.is_some() || n.branches.iter().any(|b| b.utility.is_some()))
+}
+
+/// Walks `tree` from its root `simulations` times, one draw per branch taken.
+///
+/// Fails with every `check_tree` error if the tree is malformed; its warnings go in the report.
+/// Path frequencies are tracked over the run and scored against the path probabilities with
+/// the anomaly thresholds of `sim_options`. Trees with utilities also get a `UtilityAnalysis`,
+/// whose sensitivity runs perturb each weight by up to `perturbation` of itself.
+pub fn simulate_tree(
+    session: &mut SimulationSession,
+    tree: &DecisionTree,
+    simulations: usize,
+    perturbation: f64,
+    sim_options: &SimulationOptions,
+) -> Result<TreeReport> {
+    let (errors, diagnostics): (Vec<_>, Vec<_>) = check_tree(tree).into_iter().partition(|d| d.severity == Severity::Error);
+    if !errors.is_empty() {
+        bail!("Invalid decision tree: {}", errors.iter().map(|d| d.message.as_str()).collect::<Vec<_>>().join("; "));
+    }
+    let layout = Layout::new(tree);
+    let samplers: Vec<Option<AliasTable>> = tree.nodes.iter().map(|n| {
+        let weights: Vec<f64> = n.branches.iter().map(|b| b.weight).collect();
+        (!weights.is_empty()).then(|| AliasTable::new(&weights))
+    }).collect();
+
+    let pool_start = session.pool_index;
+    let fallback_start = session.fallback_draws;
+    // Walks per path, keyed by flat branches so paths with the same labels stay apart
+    let mut walked: HashMap<Vec<usize>, usize> = HashMap::new();
+    let tally = |walked: &HashMap<Vec<usize>, usize>| {
+        let mut distribution: HashMap<String, usize> = HashMap::new();
+        for (path, &count) in walked {
+            *distribution.entry(layout.path_label(tree, path)).or_insert(0) += count;
+        }
+        distribution
+    };
+    let step_size = (simulations / sim_options.time_series_points.max(1)).max(1);
+    let mut time_series = Vec::new();
+    let mut truncated = 0;
+    // Per flat branch: walks through it and the sum of their utilities
+    let mut walks = vec![0usize; layout.next.len()];
+    let mut utility_sums = vec![0.0; layout.next.len()];
+    let mut total_utility = 0.0;
+    let mut taken: Vec<usize> = Vec::new();
+    for i in 1..=simulations {
+        taken.clear();
+        let mut node = Some(layout.root);
+        let mut end = None;
+        while let Some(at) = node {
+            let Some(sampler) = &samplers[at] else { break };
+            if taken.len() == MAX_TREE_DEPTH {
+                truncated += 1;
+                end = None;
+                break;
+            }
+            let b = layout.offsets[at] + sampler.sample(session.next_f64());
+            taken.push(b);
+            end = Some((at, b));
+            node = layout.next[b];
+        }
+        let utility = end.map_or(0.0, |(at, b)| layout.end_utility(tree, at, b));
+        total_utility += utility;
+        for &b in &taken {
+            walks[b] += 1;
+            utility_sums[b] += utility;
+        }
+        match walked.get_mut(taken.as_slice()) {
+            Some(count) => *count += 1,
+            None => {
+                walked.insert(taken.clone(), 1);
+            }
+        }
+        if i % step_size == 0 || i == simulations {
+            time_series.push(TimeStep { step_index: i, distribution: tally(&walked) });
+        }
+    }
+    let distribution = tally(&walked);
+
+    // Most walks wins; ties go to the path that sorts first, so reruns agree
+    let winner = distribution.iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(path, _)| path.clone())
+        .unwrap_or_else(|| "None".to_string());
+    let pool_end = session.pool_index;
+    let fallback_draws = session.fallback_draws - fallback_start;
+
+    let weights: Vec<f64> = tree.nodes.iter().flat_map(|n| n.branches.iter().map(|b| b.weight)).collect();
+    let probs = layout.probabilities(tree, &weights);
+
+    // Path anomalies: Z-scores of walks per path against the exact path probabilities
+    let mut expected: HashMap<String, f64> = HashMap::new();
+    match layout.paths(tree, &probs, MAX_SCORED_PATHS) {
+        Some(paths) => {
+            for (path, p) in paths {
+                *expected.entry(layout.path_label(tree, &path)).or_insert(0.0) += p;
+            }
+        }
+        None => {
+            for path in walked.keys() {
+                *expected.entry(layout.path_label(tree, path)).or_insert(0.0) += path.iter().map(|&b| probs[b]).product::<f64>();
+            }
+        }
+    }
+    let mut path_stats: Vec<OptionStats> = expected.into_iter()
+        .map(|(path, p)| {
+            let count = distribution.get(&path).copied().unwrap_or(0);
+            OptionStats::new(path, count, simulations, p)
+        })
+        .collect();
+    path_stats.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.option.cmp(&b.option)));
+    let anomalies = path_stats.iter().filter(|s| sim_options.is_anomaly(s)).map(|s| {
+        let direction = if s.z_score > 0.0 { "high" } else { "low" };
+        format!("Path '{}' is significant {} (Z={:.2})", s.option, direction, s.z_score)
+    }).collect();
+
+    let utility = has_utilities(tree).then(|| {
+        let (branch_eu, expected_utility) = layout.expected_utilities(tree, &probs);
+        let branches = tree.nodes.iter().enumerate().flat_map(|(i, node)| {
+            let (layout, probs, branch_eu, walks, utility_sums) = (&layout, &probs, &branch_eu, &walks, &utility_sums);
+            node.branches.iter().enumerate().map(move |(j, branch)| {
+                let b = layout.offsets[i] + j;
+                BranchUtility {
+                    node_id: node.id.clone(),
+                    branch: branch.label.clone(),
+                    probability: probs[b],
+                    expected_utility: branch_eu[b],
+                    walks: walks[b],
+                    observed_utility: (walks[b] > 0).then(|| utility_sums[b] / walks[b] as f64),
+                }
+            })
+        }).collect();
+        let best = layout.best_choice(tree, &probs, &branch_eu);
+        UtilityAnalysis {
+            expected_utility,
+            observed_utility: if simulations > 0 { total_utility / simulations as f64 } else { 0.0 },
+            best_choice: best.map_or("None", |b| layout.label(tree, b)).to_string(),
+            branches,
+            sensitivity: sensitivity(session, tree, &layout, &weights, perturbation),
+        }
+    });
+
+    Ok(TreeReport {
+        total_simulations: simulations,
+        winner,
+        distribution,
+        anomalies,
+        time_series,
+        path_stats,
+        truncated,
+        diagnostics,
+        utility,
+        entropy_ranges: if pool_end > pool_start { vec![EntropyRange { start: pool_start, end: pool_end }] } else { vec![] },
+        fallback_draws,
+    })
+}
+
+/// Re-solves the tree with every weight scaled by a random factor in `1 ± perturbation`.
+///
+/// The factors come from a generator seeded with 32 bytes of session entropy, so the
+/// analysis replays with the rest of the run.
+fn sensitivity(session: &mut SimulationSession, tree: &DecisionTree, layout: &Layout, weights: &[f64], perturbation: f64) -> Sensitivity {
+    let probs = layout.probabilities(tree, weights);
+    let (base_path, likeliest_probability) = layout.likeliest_path(tree, &probs);
+    let (base_eu, base_utility) = layout.expected_utilities(tree, &probs);
+    let base_choice = layout.best_choice(tree, &probs, &base_eu);
+
+    let mut rng = ChaCha20Rng::from_seed(session.next_seed());
+    let (mut path_same, mut choice_same, mut shift) = (0, 0, 0.0);
+    let mut best_choice_counts: HashMap<String, usize> = HashMap::new();
+    let mut scaled = weights.to_vec();
+    for _ in 0..SENSITIVITY_RUNS {
+        for (w, &base) in scaled.iter_mut().zip(weights) {
+            *w = base * (1.0 + perturbation * rng.gen_range(-1.0..=1.0));
+        }
+        let probs = layout.probabilities(tree, &scaled);
+        let (branch_eu, utility) = layout.expected_utilities(tree, &probs);
+        let choice = layout.best_choice(tree, &probs, &branch_eu);
+        path_same += usize::from(layout.likeliest_path(tree, &probs).0 == base_path);
+        choice_same += usize::from(choice == base_choice);
+        shift += (utility - base_utility).abs();
+        if let Some(b) = choice {
+            *best_choice_counts.entry(layout.label(tree, b).to_string()).or_insert(0) += 1;
+        }
+    }
+
+    let runs = SENSITIVITY_RUNS as f64;
+    Sensitivity {
+        perturbation,
+        runs: SENSITIVITY_RUNS,
+        likeliest_path: base_path.iter().map(|&b| layout.label(tree, b)).collect::<Vec<_>>().join(PATH_SEPARATOR),
+        likeliest_probability,
+        path_stable: path_same as f64 / runs,
+        choice_stable: choice_same as f64 / runs,
+        best_choice_counts,
+        mean_utility_shift: shift / runs,
+    }
+}
+
+#[cfg(test)]
+#[path = "decision_tests.rs"]
+mod tests;
@@ -0,0 +1,211 @@
+use super::{
+    check_tree, simulate_tree, DecisionBranch, DecisionInput, DecisionNode, DecisionReport, DecisionTool, DecisionTree, Severity,
+    TreeIssue, MAX_TREE_DEPTH,
+};
+use crate::engine::{SimulationOptions, SimulationSession};
+use crate::tools::test_fixtures::entropy;
+
+fn branch(label: &str, weight: f64, next: Option<&str>) -> DecisionBranch {
+    DecisionBranch { label: label.to_string(), weight, next_node_id: next.map(str::to_string), utility: None }
+}
+
+fn node(id: &str, branches: Vec<DecisionBranch>) -> DecisionNode {
+    DecisionNode { id: id.to_string(), label: id.to_string(), branches, utility: None }
+}
+
+/// Move or stay; moving then picks a city.
+fn relocation() -> DecisionTree {
+    DecisionTree {
+        root_id: "start".to_string(),
+        nodes: vec![
+            node("start", vec![branch("Move", 3.0, Some("city")), branch("Stay", 1.0, None)]),
+            node("city", vec![branch("Lisbon", 1.0, Some("done")), branch("Oslo", 1.0, Some("done"))]),
+            node("done", vec![]),
+        ],
+    }
+}
+
+#[test]
+fn test_tree_paths_follow_weights() {
+    let mut session = SimulationSession::new((0..=255u8).cycle().take(4096).collect());
+    let report = simulate_tree(&mut session, &relocation(), 8000, 0.2, &SimulationOptions::default()).unwrap();
+    assert_eq!(report.distribution.values().sum::<usize>(), 8000);
+    assert_eq!(report.distribution.len(), 3);
+    let share = |path: &str| report.distribution[path] as f64 / 8000.0;
+    assert!((share("Stay") - 0.25).abs() < 0.03);
+    assert!((share("Move > Lisbon") - 0.375).abs() < 0.03);
+    assert!(report.winner.starts_with("Move > "));
+    assert_eq!(report.truncated, 0);
+    assert_eq!(report.entropy_ranges[0].end, 4096);
+    assert!(report.fallback_draws > 0);
+    assert!(report.utility.is_none());
+}
+
+#[test]
+fn test_tree_time_series_and_path_anomalies() {
+    let options = SimulationOptions { time_series_points: 10, ..Default::default() };
+    let mut session = SimulationSession::new(entropy(8000 * 16));
+    let report = simulate_tree(&mut session, &relocation(), 8000, 0.2, &options).unwrap();
+    let steps: Vec<usize> = report.time_series.iter().map(|s| s.step_index).collect();
+    assert_eq!(steps, (1..=10).map(|i| i * 800).collect::<Vec<_>>());
+    assert_eq!(report.time_series.last().unwrap().distribution, report.distribution);
+    assert_eq!(report.path_stats.len(), 3);
+    assert_eq!(report.path_stats.iter().map(|s| s.count).sum::<usize>(), 8000);
+    let stay = report.path_stats.iter().find(|s| s.option == "Stay").unwrap();
+    assert!((stay.expected_probability - 0.25).abs() < 1e-12);
+    assert!(report.anomalies.is_empty(), "{:?}", report.anomalies);
+
+    // A stuck source always takes the first branch, so every other path is missing
+    let mut stuck = SimulationSession::new(vec![0; 1000 * 16]);
+    let report = simulate_tree(&mut stuck, &relocation(), 1000, 0.2, &options).unwrap();
+    assert_eq!(report.distribution.len(), 1);
+    assert_eq!(report.path_stats.len(), 3);
+    assert_eq!(report.path_stats[0].option, "Move > Lisbon");
+    assert!(report.path_stats.iter().find(|s| s.option == "Move > Oslo").is_some_and(|s| s.count == 0 && s.z_score < -3.0));
+    assert_eq!(report.anomalies.len(), 3);
+    assert!(report.anomalies.iter().any(|a| a.starts_with("Path 'Stay' is significant low")));
+}
+
+#[test]
+fn test_deep_paths_are_cut_off() {
+    // A chain one node longer than the depth limit
+    let nodes = (0..=MAX_TREE_DEPTH).map(|i| {
+        let next = (i < MAX_TREE_DEPTH).then(|| (i + 1).to_string());
+        node(&i.to_string(), vec![branch("on", 1.0, next.as_deref())])
+    }).collect();
+    let tree = DecisionTree { root_id: "0".to_string(), nodes };
+    let mut session = SimulationSession::new(vec![7; 64]);
+    let report = simulate_tree(&mut session, &tree, 3, 0.2, &SimulationOptions::default()).unwrap();
+    assert_eq!(report.truncated, 3);
+    assert_eq!(report.winner.matches("on").count(), MAX_TREE_DEPTH);
+}
+
+#[test]
+fn test_check_tree_finds_every_issue() {
+    assert!(check_tree(&relocation()).is_empty());
+
+    let tree = DecisionTree {
+        root_id: "start".to_string(),
+        nodes: vec![
+            node("start", vec![branch("Go", 1.0, Some("loop")), branch("Never", 0.0, Some("orphan")), branch("Lost", 1.0, Some("gone"))]),
+            node("loop", vec![branch("Again", 1.0, Some("back")), branch("Out", 1.0, None)]),
+            node("back", vec![branch("Return", 1.0, Some("loop"))]),
+            node("orphan", vec![]),
+            node("stuck", vec![branch("Off", 0.0, None)]),
+            node("loop", vec![]),
+        ],
+    };
+    let found = check_tree(&tree);
+    let issues: Vec<_> = found.iter().map(|d| (d.issue, d.node, d.branch)).collect();
+    assert!(issues.contains(&(TreeIssue::DuplicateNode, Some(5), None)));
+    assert!(issues.contains(&(TreeIssue::DanglingBranch, Some(0), Some(2))));
+    assert!(issues.contains(&(TreeIssue::Cycle, Some(2), Some(0))));
+    assert!(issues.contains(&(TreeIssue::DeadEnd, Some(4), None)));
+    assert!(issues.contains(&(TreeIssue::ZeroWeightBranch, Some(0), Some(1))));
+    // Only a zero-weight branch leads to the orphan; the duplicate is not reported twice
+    assert!(issues.contains(&(TreeIssue::Unreachable, Some(3), None)));
+    assert!(issues.contains(&(TreeIssue::Unreachable, Some(4), None)));
+    assert!(!issues.contains(&(TreeIssue::Unreachable, Some(5), None)));
+    assert_eq!(found.iter().filter(|d| d.issue == TreeIssue::Cycle).count(), 1);
+    let cycle = found.iter().find(|d| d.issue == TreeIssue::Cycle).unwrap();
+    assert!(cycle.message.contains("loop > back > loop"), "{}", cycle.message);
+    // Errors come first
+    let first_warning = found.iter().position(|d| d.severity == Severity::Warning).unwrap();
+    assert!(found[first_warning..].iter().all(|d| d.severity == Severity::Warning));
+
+    let mut session = SimulationSession::new(vec![7; 64]);
+    let err = simulate_tree(&mut session, &tree, 3, 0.2, &SimulationOptions::default()).unwrap_err().to_string();
+    assert!(err.contains("unknown node \"gone\""), "{}", err);
+    let missing = DecisionTree { root_id: "nowhere".to_string(), ..relocation() };
+    assert_eq!(check_tree(&missing)[0].issue, TreeIssue::MissingRoot);
+    assert!(simulate_tree(&mut session, &missing, 3, 0.2, &SimulationOptions::default()).is_err());
+}
+
+#[test]
+fn test_tree_warnings_reach_the_report() {
+    let mut tree = relocation();
+    tree.nodes[1].branches.push(branch("Paris", 0.0, Some("done")));
+    let mut session = SimulationSession::new(vec![7; 64]);
+    let report = simulate_tree(&mut session, &tree, 50, 0.2, &SimulationOptions::default()).unwrap();
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].issue, TreeIssue::ZeroWeightBranch);
+    assert!(!report.distribution.contains_key("Move > Paris"));
+}
+
+/// Accept a job (which may go well or badly) or decline it.
+fn job_offer() -> DecisionTree {
+    let worth = |mut b: DecisionBranch, utility: f64| {
+        b.utility = Some(utility);
+        b
+    };
+    DecisionTree {
+        root_id: "offer".to_string(),
+        nodes: vec![
+            node("offer", vec![branch("Accept", 1.0, Some("job")), worth(branch("Decline", 1.0, None), 0.0)]),
+            node("job", vec![worth(branch("Thrive", 3.0, None), 10.0), worth(branch("Struggle", 1.0, None), -20.0)]),
+        ],
+    }
+}
+
+#[test]
+fn test_tree_expected_utility() {
+    let mut session = SimulationSession::new(entropy(20_000 * 16));
+    let report = simulate_tree(&mut session, &job_offer(), 20_000, 0.0, &SimulationOptions::default()).unwrap();
+    let utility = report.utility.unwrap();
+    assert!((utility.expected_utility - 1.25).abs() < 1e-12);
+    assert!((utility.observed_utility - 1.25).abs() < 0.3, "observed {}", utility.observed_utility);
+    assert_eq!(utility.best_choice, "Accept");
+    let accept = utility.branches.iter().find(|b| b.branch == "Accept").unwrap();
+    assert_eq!((accept.node_id.as_str(), accept.probability), ("offer", 0.5));
+    assert!((accept.expected_utility - 2.5).abs() < 1e-12);
+    assert!((accept.walks as f64 / 20_000.0 - 0.5).abs() < 0.02);
+    let struggle = utility.branches.iter().find(|b| b.branch == "Struggle").unwrap();
+    assert_eq!(struggle.observed_utility, Some(-20.0));
+
+    // Without perturbation nothing moves
+    let s = &utility.sensitivity;
+    assert_eq!((s.path_stable, s.choice_stable, s.mean_utility_shift), (1.0, 1.0, 0.0));
+    assert_eq!(s.likeliest_path, "Decline");
+    assert_eq!(s.likeliest_probability, 0.5);
+    assert_eq!(s.best_choice_counts["Accept"], s.runs);
+}
+
+#[test]
+fn test_tree_sensitivity_to_weights() {
+    let mut session = SimulationSession::new(entropy(1000 * 16));
+    let report = simulate_tree(&mut session, &job_offer(), 1000, 0.5, &SimulationOptions::default()).unwrap();
+    let s = report.utility.unwrap().sensitivity;
+    // Accepting only pays while Thrive keeps at least two thirds of the job's odds
+    assert!(s.choice_stable > 0.0 && s.choice_stable < 1.0, "choice stable {}", s.choice_stable);
+    assert!(s.best_choice_counts.contains_key("Decline"));
+    assert_eq!(s.best_choice_counts.values().sum::<usize>(), s.runs);
+    assert!(s.mean_utility_shift > 0.0);
+    assert!(s.path_stable < 1.0);
+
+    // The sensitivity runs replay with the entropy
+    let mut again = SimulationSession::new(entropy(1000 * 16));
+    let replay = simulate_tree(&mut again, &job_offer(), 1000, 0.5, &SimulationOptions::default()).unwrap();
+    assert_eq!(replay.utility.unwrap().sensitivity.choice_stable, s.choice_stable);
+}
+
+#[test]
+fn test_decide_picks_mode() {
+    let mut session = SimulationSession::new(vec![1; 256]);
+    let simple = DecisionInput { options: vec!["Yes".into(), "No".into()], simulations: Some(100), ..Default::default() };
+    let report = DecisionTool::decide(&mut session, &simple).unwrap();
+    assert!(matches!(report, DecisionReport::Simple(ref r) if r.total_simulations == 100));
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["mode"], "simple");
+    assert!(json["distribution"].is_object());
+
+    let tree = DecisionInput { tree: Some(relocation()), simulations: Some(10), ..Default::default() };
+    let report = DecisionTool::decide(&mut session, &tree).unwrap();
+    assert_eq!(serde_json::to_value(&report).unwrap()["mode"], "tree");
+
+    let tournament = DecisionInput { tournament: Some(Default::default()), ..simple };
+    let report = DecisionTool::decide(&mut session, &tournament).unwrap();
+    assert!(matches!(report, DecisionReport::Tournament(ref r) if r.matches == 1));
+    assert_eq!(serde_json::to_value(&report).unwrap()["mode"], "tournament");
+
+    assert!(DecisionTool::decide(&mut session, &DecisionInput::default()).is_err());
+}
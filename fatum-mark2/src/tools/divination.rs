@@ -1,7 +1,8 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use crate::engine::SimulationSession;
-use std::fs;
+use crate::services::intention;
+use crate::engine::drift::{DriftScanOptions, EntropyHotspot};
 
 /// Represents the metadata for a single Hexagram from `iching.json`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,8 +11,79 @@ pub struct HexagramData {
     pub name: String,
     pub judgment: String,
     pub image: String,
+    /// Line statements, bottom to top.
+    #[serde(default)]
+    pub lines: Vec<String>,
 }
 
+lazy_static::lazy_static! {
+    // Compiled in, so casting does not depend on the working directory
+    static ref HEXAGRAMS: Vec<HexagramData> = serde_json::from_str(include_str!("../../static/iching.json"))
+        .expect("static/iching.json is valid");
+}
+
+/// Traditional names of the six line positions, bottom to top.
+const LINE_PLACES: [&str; 6] = [
+    "at the beginning", "in the second place", "in the third place",
+    "in the fourth place", "in the fifth place", "at the top",
+];
+
+/// Texts of the hexagram with King Wen `number`.
+pub fn hexagram_data(number: u32) -> Option<&'static HexagramData> {
+    HEXAGRAMS.iter().find(|h| h.number == number)
+}
+
+/// How each line is cast.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CastMethod {
+    /// Three coins: moving lines 1/8 each, static lines 3/8 each.
+    #[default]
+    Coin,
+    /// Yarrow stalks: Old Yin 1/16, Young Yang 5/16, Young Yin 7/16, Old Yang 3/16.
+    Yarrow,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DivinationConfig {
+    #[serde(default)]
+    pub method: CastMethod,
+    /// What is being asked; bound into the entropy so the cast answers this question only.
+    #[serde(default)]
+    pub question: Option<String>,
+    /// Stored batch to draw the lines from instead of a fresh beacon fetch.
+    #[serde(default)]
+    pub entropy_batch_id: Option<i64>,
+    /// Fail rather than top up with the PRNG when the entropy runs short.
+    #[serde(default)]
+    pub strict_quantum: bool,
+}
+
+/// Domain separating divination question bindings from any other use of the entropy.
+pub const QUESTION_DOMAIN: &str = "fatum-divination-v1";
+
+/// One of the eight trigrams making up half a hexagram.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trigram {
+    pub name: String, // e.g. "Zhen (Thunder)"
+    pub element: String,
+}
+
+/// A hexagram derived from the cast one, with its texts but no further derivations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedHexagram {
+    pub number: u32,
+    pub name: String,
+    pub lines: Vec<u8>,
+    pub judgment: String,
+}
+
+/// Trigrams by their lines packed bottom-first (bit 0 = bottom line), with their elements.
+pub(crate) const TRIGRAMS: [(&str, &str); 8] = [
+    ("Kun (Earth)", "Earth"), ("Zhen (Thunder)", "Wood"), ("Kan (Water)", "Water"), ("Dui (Lake)", "Metal"),
+    ("Gen (Mountain)", "Earth"), ("Li (Fire)", "Fire"), ("Xun (Wind)", "Wood"), ("Qian (Heaven)", "Metal"),
+];
+
 /// Represents the result of a Divination cast.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Hexagram {
@@ -22,37 +94,54 @@ pub struct Hexagram {
     pub transformed_hexagram: Option<Box<Hexagram>>, // The result after changing lines flip
     pub judgment: String,
     pub image: String,
+    /// Line statements of the `changing_lines`, in the same order, e.g. "Nine at the beginning: ...".
+    #[serde(default)]
+    pub changing_line_texts: Vec<String>,
+    #[serde(default)]
+    pub upper_trigram: Option<Trigram>,
+    #[serde(default)]
+    pub lower_trigram: Option<Trigram>,
+    /// Hu Gua: lines 2-4 as the lower trigram and 3-5 as the upper, the hidden core.
+    #[serde(default)]
+    pub nuclear_hexagram: Option<RelatedHexagram>,
+    /// Cuo Gua: every line inverted.
+    #[serde(default)]
+    pub opposite_hexagram: Option<RelatedHexagram>,
+    /// Zong Gua: the hexagram turned upside down.
+    #[serde(default)]
+    pub reversed_hexagram: Option<RelatedHexagram>,
+    #[serde(default)]
+    pub method: CastMethod,
+    #[serde(default)]
+    pub question: Option<String>,
+    /// Hex SHA-256 of the question under `QUESTION_DOMAIN`, the key the entropy was bound with.
+    #[serde(default)]
+    pub question_digest: Option<String>,
+    /// Regions of the raw entropy that deviated from noise during the cast.
+    #[serde(default)]
+    pub entropy_hotspots: Vec<EntropyHotspot>,
 }
 
 pub struct DivinationTool;
 
 impl DivinationTool {
-    /// Casts a Hexagram using the Quantum Coin Method.
+    /// Casts a Hexagram with the Quantum Coin or Yarrow Stalk Method.
     ///
-    /// Simulates tossing 3 coins 6 times.
+    /// Coin: simulates tossing 3 coins 6 times.
     /// - 3 Heads (3+3+3=9) -> Old Yang (Changes to Yin)
     /// - 3 Tails (2+2+2=6) -> Old Yin (Changes to Yang)
     /// - 2 Heads + 1 Tail (3+3+2=8) -> Young Yin (Static)
     /// - 1 Head + 2 Tails (3+2+2=7) -> Young Yang (Static)
-    pub fn cast_hexagram(session: &SimulationSession) -> Result<Hexagram> {
-        // Load JSON data
-        // Ideally cached, but reading here for stateless simplicity.
-        let data_str = fs::read_to_string("static/iching.json").unwrap_or_else(|_| "[]".to_string());
-        let hex_db: Vec<HexagramData> = serde_json::from_str(&data_str).unwrap_or_default();
-
+    ///
+    /// Yarrow: draws each line straight from the pool with the stalk odds, so Old Yang
+    /// moves three times as often as Old Yin.
+    pub fn cast_hexagram(session: &mut SimulationSession, method: CastMethod) -> Result<Hexagram> {
         let mut lines = Vec::new();
         let mut changing = Vec::new();
         let mut trans_lines = Vec::new();
 
         // Build 6 lines (Bottom to Top)
-        for i in 0..6 {
-            let mut sum = 0;
-            for _ in 0..3 {
-                // Quantum simulation of a coin toss
-                let toss = session.simulate_decision(&vec!["Head".to_string(), "Tail".to_string()], None, 10).winner;
-                sum += if toss == "Head" { 3 } else { 2 };
-            }
-
+        for (i, sum) in cast_lines(session, method).into_iter().enumerate() {
             let is_yang = sum % 2 != 0; // 7 or 9 is Yang
             let is_changing = sum == 6 || sum == 9;
 
@@ -67,19 +156,24 @@ impl DivinationTool {
 
         // Identify Primary Hexagram
         let (orig_num, orig_name) = lookup_hexagram_meta(&lines);
-        let orig_data = hex_db.iter().find(|h| h.number == orig_num);
+        let orig_data = hexagram_data(orig_num);
         let judgment = orig_data.map(|d| d.judgment.clone()).unwrap_or_else(|| "Unknown Judgment".to_string());
         let image = orig_data.map(|d| d.image.clone()).unwrap_or_else(|| "Unknown Image".to_string());
         let name_full = orig_data.map(|d| d.name.clone()).unwrap_or(orig_name);
+        let changing_line_texts = changing.iter().map(|&i| {
+            let text = orig_data.and_then(|d| d.lines.get(i)).map_or("", |t| t.as_str());
+            format!("{} {}: {}", if lines[i] == 1 { "Nine" } else { "Six" }, LINE_PLACES[i], text)
+        }).collect();
 
         // Identify Transformed Hexagram (if any lines changed)
         let transformed = if !changing.is_empty() {
             let (t_num, t_name) = lookup_hexagram_meta(&trans_lines);
-            let t_data = hex_db.iter().find(|h| h.number == t_num);
+            let t_data = hexagram_data(t_num);
             let t_judgment = t_data.map(|d| d.judgment.clone()).unwrap_or_else(|| "Unknown Judgment".to_string());
             let t_image = t_data.map(|d| d.image.clone()).unwrap_or_else(|| "Unknown Image".to_string());
             let t_name_full = t_data.map(|d| d.name.clone()).unwrap_or(t_name);
 
+            let (t_upper, t_lower) = trigrams(&trans_lines);
             Some(Box::new(Hexagram {
                 number: t_num,
                 name: t_name_full,
@@ -88,11 +182,30 @@ impl DivinationTool {
                 transformed_hexagram: None,
                 judgment: t_judgment,
                 image: t_image,
+                changing_line_texts: vec![],
+                upper_trigram: Some(t_upper),
+                lower_trigram: Some(t_lower),
+                nuclear_hexagram: None,
+                opposite_hexagram: None,
+                reversed_hexagram: None,
+                method,
+                question: None,
+                question_digest: None,
+                entropy_hotspots: vec![],
             }))
         } else {
             None
         };
 
+        // Trigrams and the derived hexagrams interpreters consult
+        let (upper, lower) = trigrams(&lines);
+        let nuclear = vec![lines[1], lines[2], lines[3], lines[2], lines[3], lines[4]];
+        let opposite: Vec<u8> = lines.iter().map(|l| 1 - l).collect();
+        let reversed: Vec<u8> = lines.iter().rev().copied().collect();
+
+        // Surface meaningful deviations in the entropy behind this cast
+        let entropy_hotspots = session.scan_entropy_drift(&DriftScanOptions::default());
+
         Ok(Hexagram {
             number: orig_num,
             name: name_full,
@@ -101,12 +214,76 @@ impl DivinationTool {
             transformed_hexagram: transformed,
             judgment,
             image,
+            changing_line_texts,
+            upper_trigram: Some(upper),
+            lower_trigram: Some(lower),
+            nuclear_hexagram: Some(related(nuclear)),
+            opposite_hexagram: Some(related(opposite)),
+            reversed_hexagram: Some(related(reversed)),
+            method,
+            question: None,
+            question_digest: None,
+            entropy_hotspots,
         })
     }
 }
 
+/// Binds `question` into the beacon bytes, returning them with the question's hex digest.
+/// Blank or missing questions leave the entropy untouched.
+pub fn bind_question(entropy: Vec<u8>, question: Option<&str>) -> (Vec<u8>, Option<String>) {
+    let (entropy, binding) = intention::DIVINATION.bind(entropy, question);
+    (entropy, binding.map(|b| b.digest))
+}
+
+/// Casts six line values (6-9), bottom to top.
+pub fn cast_lines(session: &mut SimulationSession, method: CastMethod) -> [u8; 6] {
+    std::array::from_fn(|_| match method {
+        CastMethod::Coin => (0..3)
+            // Quantum simulation of a coin toss
+            .map(|_| if session.next_bool(0.5) { 3 } else { 2 })
+            .sum(),
+        CastMethod::Yarrow => yarrow_line(session.next_u64_in_range(0, 16)),
+    })
+}
+
+/// Index into `TRIGRAMS` of three lines (bottom to top, 1 = Yang).
+pub(crate) fn trigram_index(lines: &[u8]) -> usize {
+    (lines[0] | lines[1] << 1 | lines[2] << 2) as usize
+}
+
+/// Upper and lower trigram of six lines (bottom to top).
+fn trigrams(lines: &[u8]) -> (Trigram, Trigram) {
+    let trigram = |l: &[u8]| {
+        let (name, element) = TRIGRAMS[trigram_index(l)];
+        Trigram { name: name.to_string(), element: element.to_string() }
+    };
+    (trigram(&lines[3..6]), trigram(&lines[0..3]))
+}
+
+fn related(lines: Vec<u8>) -> RelatedHexagram {
+    let (number, name) = lookup_hexagram_meta(&lines);
+    let data = hexagram_data(number);
+    RelatedHexagram {
+        number,
+        name: data.map(|d| d.name.clone()).unwrap_or(name),
+        judgment: data.map(|d| d.judgment.clone()).unwrap_or_else(|| "Unknown Judgment".to_string()),
+        lines,
+    }
+}
+
+/// Line value for one of 16 equally likely yarrow outcomes: 6 once, 7 five times,
+/// 8 seven times, 9 three times.
+fn yarrow_line(r: u64) -> u8 {
+    match r {
+        0 => 6,
+        1..=5 => 7,
+        6..=12 => 8,
+        _ => 9,
+    }
+}
+
 /// Converts a 6-bit array (Bottom->Top) to King Wen Hexagram Number.
-fn lookup_hexagram_meta(lines: &[u8]) -> (u32, String) {
+pub(crate) fn lookup_hexagram_meta(lines: &[u8]) -> (u32, String) {
     let mut val = 0;
     // Pack bits into integer
     for (i, &bit) in lines.iter().enumerate() {
@@ -129,3 +306,7 @@ fn lookup_hexagram_meta(lines: &[u8]) -> (u32, String) {
     let number = if val < 64 { king_wen_map[val] } else { 0 };
     (number, format!("Hexagram {}", number))
 }
+
+#[cfg(test)]
+#[path = "divination_tests.rs"]
+mod tests;
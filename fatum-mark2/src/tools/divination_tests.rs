@@ -0,0 +1,89 @@
+use super::{bind_question, hexagram_data, related, trigrams, yarrow_line, CastMethod, DivinationTool};
+use crate::engine::SimulationSession;
+
+#[test]
+fn test_yarrow_line_odds() {
+    let mut counts = [0; 4];
+    for r in 0..16 {
+        counts[(yarrow_line(r) - 6) as usize] += 1;
+    }
+    assert_eq!(counts, [1, 5, 7, 3]);
+}
+
+#[test]
+fn test_cast_methods() {
+    for method in [CastMethod::Coin, CastMethod::Yarrow] {
+        let mut session = SimulationSession::new((0..=255).cycle().take(4096).collect());
+        let hex = DivinationTool::cast_hexagram(&mut session, method).unwrap();
+        assert_eq!(hex.method, method);
+        assert_eq!(hex.lines.len(), 6);
+        assert!((1..=64).contains(&hex.number));
+        let opposite = hex.opposite_hexagram.as_ref().unwrap();
+        assert!(hex.lines.iter().zip(&opposite.lines).all(|(a, b)| a != b));
+        assert_eq!(hex.changing_line_texts.len(), hex.changing_lines.len());
+        assert!(hex.changing_line_texts.iter().all(|t| t.starts_with("Nine ") || t.starts_with("Six ")));
+        if let Some(t) = &hex.transformed_hexagram {
+            for (i, (a, b)) in hex.lines.iter().zip(&t.lines).enumerate() {
+                assert_eq!(a != b, hex.changing_lines.contains(&i));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_embedded_hexagram_texts() {
+    for number in 1..=64 {
+        let data = hexagram_data(number).unwrap();
+        assert_eq!(data.lines.len(), 6, "hexagram {}", number);
+        assert!(data.lines.iter().all(|l| !l.is_empty()));
+    }
+    assert!(hexagram_data(1).unwrap().lines[0].contains("dragon"));
+    assert!(hexagram_data(65).is_none());
+}
+
+#[test]
+fn test_trigrams_and_derived_hexagrams() {
+    // Hexagram 3 (Zhun): Water over Thunder
+    let zhun = [1, 0, 0, 0, 1, 0];
+    let (upper, lower) = trigrams(&zhun);
+    assert_eq!((upper.name.as_str(), upper.element.as_str()), ("Kan (Water)", "Water"));
+    assert_eq!((lower.name.as_str(), lower.element.as_str()), ("Zhen (Thunder)", "Wood"));
+    let (upper, lower) = trigrams(&[1, 1, 0, 0, 1, 1]);
+    assert_eq!((upper.name.as_str(), lower.name.as_str()), ("Xun (Wind)", "Dui (Lake)"));
+
+    // Reversed, Zhun becomes Meng (4); its opposite is Ding (50); its core is Bo (23)
+    let reversed: Vec<u8> = zhun.iter().rev().copied().collect();
+    assert_eq!(related(reversed).number, 4);
+    assert_eq!(related(zhun.iter().map(|l| 1 - l).collect()).number, 50);
+    assert_eq!(related(vec![zhun[1], zhun[2], zhun[3], zhun[2], zhun[3], zhun[4]]).number, 23);
+}
+
+#[test]
+fn test_question_binding() {
+    let entropy: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let (a, digest_a) = bind_question(entropy.clone(), Some("Should I move?"));
+    let (again, _) = bind_question(entropy.clone(), Some("Should I move?"));
+    let (b, digest_b) = bind_question(entropy.clone(), Some("Should I stay?"));
+    assert_eq!(a.len(), entropy.len());
+    assert_eq!(a, again);
+    assert_ne!(a, b);
+    assert_ne!(a, entropy);
+    assert_eq!(digest_a.unwrap().len(), 64);
+    assert_ne!(digest_b, None);
+
+    // No question leaves the beacon bytes as they were
+    assert_eq!(bind_question(entropy.clone(), None), (entropy.clone(), None));
+    assert_eq!(bind_question(entropy.clone(), Some("  ")), (entropy, None));
+}
+
+#[test]
+fn test_short_batch_falls_back() {
+    // Six coin lines need 18 draws (144 bytes); strict mode rejects casts that used the fallback
+    let mut session = SimulationSession::new(vec![7; 144]);
+    DivinationTool::cast_hexagram(&mut session, CastMethod::Coin).unwrap();
+    assert_eq!(session.fallback_draws, 0);
+
+    let mut session = SimulationSession::new(vec![7; 100]);
+    DivinationTool::cast_hexagram(&mut session, CastMethod::Coin).unwrap();
+    assert!(session.fallback_draws > 0);
+}
@@ -1,15 +1,80 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::Write;
-// use crate::tools::chinese_meta::{is_six_clash, is_six_combination, get_stem_element};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 
-#[derive(Deserialize)]
+use crate::engine::timeline::WU_XING;
+use crate::engine::SimulationSession;
+use crate::tools::astrology_reports::{calculate_synastry, SynastryConfig};
+use crate::tools::bazi_strength::PILLARS;
+use crate::tools::chinese_meta::{get_branch, get_stem, is_six_clash, is_six_combination, parse_pillar, stem_combination};
+use crate::tools::feng_shui::{calculate_bazi, BaZiProfile};
+
+#[derive(Serialize, Deserialize)]
 pub struct EntanglementRequest {
+    #[serde(default)]
     pub profile1_data: String, // e.g., JSON string or raw text
+    #[serde(default)]
     pub profile2_data: String,
     pub mode: EntanglementMode,
+    /// Group analysis: each member's profile data (three or more). Every pair is scored as
+    /// profile1/profile2 would be, and the pair fields and per-pair inputs are ignored.
+    #[serde(default)]
+    pub group: Vec<String>,
+    /// Birth charts for both profiles; when given, their synastry is blended into the score.
+    #[serde(default)]
+    pub charts: Option<SynastryConfig>,
+    /// Birth moments for BaZi synastry in Seed Hash mode. When left out, the profile data is
+    /// read as a saved profile's JSON (`birth_year`, `birth_month`, ...) if it parses as one.
+    #[serde(default)]
+    pub birth1: Option<BirthMoment>,
+    #[serde(default)]
+    pub birth2: Option<BirthMoment>,
+    /// Entropy Stream mode: stored batch to draw the stream's seed from instead of a live fetch.
+    #[serde(default)]
+    pub entropy_batch_id: Option<i64>,
+    /// Entropy Stream mode: hex seed from a previous report's `seed`, to replay that run.
+    #[serde(default)]
+    pub seed: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BirthMoment {
+    /// Local clock time of birth.
+    pub datetime: NaiveDateTime,
+    /// "M" or "F".
+    #[serde(default)]
+    pub gender: Option<String>,
+}
+
+/// The birth fields of a saved profile, as the frontend passes it.
+#[derive(Deserialize)]
+struct ProfileBirth {
+    birth_year: i32,
+    birth_month: u32,
+    birth_day: u32,
+    birth_hour: Option<u32>,
+    gender: Option<String>,
+}
+
+impl BirthMoment {
+    fn from_profile_data(data: &str) -> Option<Self> {
+        let p: ProfileBirth = serde_json::from_str(data).ok()?;
+        Some(BirthMoment {
+            datetime: chrono::NaiveDate::from_ymd_opt(p.birth_year, p.birth_month, p.birth_day)?
+                .and_hms_opt(p.birth_hour.unwrap_or(12), 0, 0)?,
+            gender: p.gender,
+        })
+    }
+
+    fn bazi(&self) -> anyhow::Result<BaZiProfile> {
+        let dt = self.datetime;
+        calculate_bazi(dt.year(), dt.month(), dt.day(), dt.hour(), self.gender.as_deref(), None, None)
+    }
 }
 
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum EntanglementMode {
     SeedHash,
     EntropyStream,
@@ -22,19 +87,164 @@ pub struct EntanglementReport {
     pub compatibility_factors: Vec<String>,
     pub narrative: String,
     pub shared_hexagram: Option<u8>, // 1-64
+    pub astrological_harmony: Option<f64>, // 0-100, when charts were given
+    pub bazi: Option<BaZiSynastry>, // Seed Hash mode, when both birth moments are known
+    pub seed: Option<String>, // Entropy Stream mode: hex seed that replays the run
+    pub group: Option<GroupResonance>, // when a group was given; resonance_score is its coherence
+}
+
+/// Pairwise resonance across a group.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupResonance {
+    /// Profile names where the data is a saved profile, else "Member N".
+    pub members: Vec<String>,
+    /// `matrix[i][j]`: resonance of members i and j, each pair scored once in list order
+    /// and mirrored; 100 on the diagonal.
+    pub matrix: Vec<Vec<f64>>,
+    /// Mean pair resonance less half its standard deviation, so one bad pairing drags a
+    /// harmonious group down.
+    pub coherence: f64,
+    pub strongest_pair: (String, String, f64),
+    pub weakest_pair: (String, String, f64),
+}
+
+/// How two BaZi charts interact, each finding with its effect on the 0-100 score.
+#[derive(Debug, Clone, Serialize)]
+pub struct BaZiSynastry {
+    pub first_pillars: [String; 4],
+    pub second_pillars: [String; 4],
+    /// e.g. "Day stems combine (Wu + Gui -> Fire)".
+    pub stem_combinations: Vec<String>,
+    pub branch_combinations: Vec<String>,
+    pub branch_clashes: Vec<String>,
+    /// How the day masters' elements relate, e.g. "Wood feeds Fire".
+    pub day_master_relation: String,
+    /// How much of each chart's Qi lies in the other's useful elements, relative to an even
+    /// spread: 1.0 is neutral, above 1 the charts supply what each other lacks.
+    pub element_complementarity: f64,
+    /// Before the hash tie-breaker.
+    pub score: f64,
 }
 
-pub fn calculate_entanglement(req: &EntanglementRequest) -> anyhow::Result<EntanglementReport> {
-    match req.mode {
-        EntanglementMode::SeedHash => calculate_seed_hash(req),
-        EntanglementMode::EntropyStream => calculate_entropy_stream(req),
+/// `session` seeds Entropy Stream mode unless the request replays a `seed`; Seed Hash ignores it.
+pub fn calculate_entanglement(req: &EntanglementRequest, session: Option<&mut SimulationSession>) -> anyhow::Result<EntanglementReport> {
+    if !req.group.is_empty() {
+        return calculate_group(req, session);
     }
+    let mut report = match req.mode {
+        EntanglementMode::SeedHash => calculate_seed_hash(req)?,
+        EntanglementMode::EntropyStream => calculate_entropy_stream(req, stream_seed(req, session)?)?,
+    };
+
+    // Real chart contacts carry half the weight when both charts are known
+    if let Some(charts) = &req.charts {
+        let synastry = calculate_synastry(charts)?;
+        report.resonance_score = (report.resonance_score + synastry.harmony_score) / 2.0;
+        report.compatibility_factors.push(format!("Astrological Harmony: {:.1}%", synastry.harmony_score));
+        report.compatibility_factors.extend(synastry.factors);
+        write!(report.narrative, " Synastry between the birth charts rates {:.0}% harmonious.", synastry.harmony_score)?;
+        report.astrological_harmony = Some(synastry.harmony_score);
+    }
+    Ok(report)
 }
 
 #[cfg(test)]
 #[path = "entanglement_tests.rs"]
 mod tests;
 
+/// The replayed `seed` if the request has one, else a fresh one from the session.
+fn stream_seed(req: &EntanglementRequest, session: Option<&mut SimulationSession>) -> anyhow::Result<[u8; 32]> {
+    Ok(match (&req.seed, session) {
+        (Some(hex_seed), _) => hex::decode(hex_seed).ok()
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| anyhow::anyhow!("seed must be 64 hex characters"))?,
+        (None, Some(session)) => session.next_seed(),
+        (None, None) => anyhow::bail!("Entropy Stream mode needs quantum entropy or a seed"),
+    })
+}
+
+// === GROUP RESONANCE ===
+// Scores every pair in the group; in Entropy Stream mode the whole group rides one stream.
+fn calculate_group(req: &EntanglementRequest, session: Option<&mut SimulationSession>) -> anyhow::Result<EntanglementReport> {
+    let n = req.group.len();
+    if n < 3 {
+        anyhow::bail!("A group needs at least 3 profiles (got {})", n);
+    }
+    let seed = match req.mode {
+        EntanglementMode::EntropyStream => Some(hex::encode(stream_seed(req, session)?)),
+        EntanglementMode::SeedHash => None,
+    };
+
+    // 1. Members and the pair matrix
+    let members: Vec<String> = req.group.iter().enumerate().map(|(i, data)| {
+        serde_json::from_str::<serde_json::Value>(data).ok()
+            .and_then(|v| v.get("name")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("Member {}", i + 1))
+    }).collect();
+    let mut pairs = Vec::new();
+    for (i, first) in req.group.iter().enumerate() {
+        for (j, second) in req.group.iter().enumerate().skip(i + 1) {
+            let pair = EntanglementRequest {
+                profile1_data: first.clone(),
+                profile2_data: second.clone(),
+                mode: req.mode,
+                group: Vec::new(),
+                charts: None,
+                birth1: None,
+                birth2: None,
+                entropy_batch_id: None,
+                seed: seed.clone(),
+            };
+            let score = calculate_entanglement(&pair, None)?.resonance_score;
+            pairs.push((i, j, score));
+        }
+    }
+    let mut matrix = vec![vec![100.0; n]; n];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        for &(a, b, score) in pairs.iter().filter(|p| p.0 == i || p.1 == i) {
+            row[if a == i { b } else { a }] = score;
+        }
+    }
+
+    // 2. Coherence: the mean, marked down for uneven pairings
+    let mean = pairs.iter().map(|p| p.2).sum::<f64>() / pairs.len() as f64;
+    let variance = pairs.iter().map(|p| (p.2 - mean).powi(2)).sum::<f64>() / pairs.len() as f64;
+    let coherence = (mean - variance.sqrt() / 2.0).clamp(0.0, 100.0);
+    let named = |&(i, j, score): &(usize, usize, f64)| (members[i].clone(), members[j].clone(), score);
+    let strongest_pair = pairs.iter().max_by(|a, b| a.2.total_cmp(&b.2)).map(named).unwrap();
+    let weakest_pair = pairs.iter().min_by(|a, b| a.2.total_cmp(&b.2)).map(named).unwrap();
+
+    let factors = vec![
+        format!("Group Coherence: {:.1}%", coherence),
+        format!("Mean Pair Resonance: {:.1}%", mean),
+        format!("Strongest Bond: {} & {} ({:.1}%)", strongest_pair.0, strongest_pair.1, strongest_pair.2),
+        format!("Weakest Bond: {} & {} ({:.1}%)", weakest_pair.0, weakest_pair.1, weakest_pair.2),
+    ];
+    let mut narrative = format!("Group analysis of {} members across {} pairings. ", n, pairs.len());
+    if coherence > 70.0 {
+        write!(narrative, "The group is strongly coherent: its members resonate as one field.")?;
+    } else if coherence > 45.0 {
+        write!(narrative, "The group holds together, though some pairings need conscious tending.")?;
+    } else {
+        write!(narrative, "The group is fragmented; weak bonds interfere with the whole.")?;
+    }
+
+    Ok(EntanglementReport {
+        mode: match req.mode {
+            EntanglementMode::SeedHash => "Seed Hash",
+            EntanglementMode::EntropyStream => "Entropy Stream",
+        }.to_string(),
+        resonance_score: coherence,
+        compatibility_factors: factors,
+        narrative,
+        shared_hexagram: None,
+        astrological_harmony: None,
+        bazi: None,
+        seed,
+        group: Some(GroupResonance { members, matrix, coherence, strongest_pair, weakest_pair }),
+    })
+}
+
 // === MODE A: SEED HASH (Deterministic) ===
 // Combines birth data to form a seed, then derives compatibility.
 // This is like "Synastry" where the chart interaction is fixed, but we add a crypto-flavor.
@@ -49,32 +259,46 @@ fn calculate_seed_hash(req: &EntanglementRequest) -> anyhow::Result<Entanglement
     hasher.update(combined.as_bytes());
     let result = hasher.finalize(); // 32 bytes
 
-    // 3. Extract Metrics
-    // Byte 0: Base Resonance (0-255)
-    // Byte 1: Karma Link (0-255)
-    // Byte 2: Friction (0-255)
     // Byte 3: Hexagram (0-63)
-
-    let base_res = result[0] as f64 / 2.55; // 0-100
-    let karma = result[1] as f64 / 2.55;
-    let friction = result[2] as f64 / 2.55;
     let hex_idx = (result[3] % 64) + 1;
 
-    // Adjust score based on "Metaphysical" logic if we can parse the profiles
-    // For MVP, we assume profiles are passed as JSON strings that might contain {birth_year, etc.}
-    // But since the request just takes strings to be generic, we'll try to parse them if possible.
-    // If parsing fails, we rely purely on the hash.
-
-    let mut factors = Vec::new();
-    factors.push(format!("Base Resonance: {:.1}%", base_res));
-    factors.push(format!("Karmic Link: {:.1}%", karma));
-    factors.push(format!("Friction Potential: {:.1}%", friction));
-
-    // Calculate final score
-    let score = (base_res + karma - (friction * 0.5)).clamp(0.0, 100.0);
+    // 3. Score the charts when both births are known; otherwise fall back to the hash metrics
+    let births = (
+        req.birth1.clone().or_else(|| BirthMoment::from_profile_data(&req.profile1_data)),
+        req.birth2.clone().or_else(|| BirthMoment::from_profile_data(&req.profile2_data)),
+    );
+    let (score, factors, bazi) = match births {
+        (Some(a), Some(b)) => {
+            let bazi = bazi_synastry(&a, &b)?;
+            // Byte 0 only breaks ties: +/- half a point
+            let score = (bazi.score + (result[0] as f64 / 255.0 - 0.5)).clamp(0.0, 100.0);
+            let mut factors = vec![format!("Day Masters: {}", bazi.day_master_relation)];
+            factors.extend(bazi.stem_combinations.iter().cloned());
+            factors.extend(bazi.branch_combinations.iter().cloned());
+            factors.extend(bazi.branch_clashes.iter().cloned());
+            factors.push(format!("Element Complementarity: {:.2}x", bazi.element_complementarity));
+            (score, factors, Some(bazi))
+        }
+        _ => {
+            // Byte 0: Base Resonance, Byte 1: Karma Link, Byte 2: Friction (0-255)
+            let base_res = result[0] as f64 / 2.55; // 0-100
+            let karma = result[1] as f64 / 2.55;
+            let friction = result[2] as f64 / 2.55;
+            let factors = vec![
+                format!("Base Resonance: {:.1}%", base_res),
+                format!("Karmic Link: {:.1}%", karma),
+                format!("Friction Potential: {:.1}%", friction),
+            ];
+            ((base_res + karma - (friction * 0.5)).clamp(0.0, 100.0), factors, None)
+        }
+    };
 
     let mut narrative = String::new();
-    write!(narrative, "Deterministic Seed Analysis complete. The combined waveform of these two entities generates a stable resonance pattern. ")?;
+    if bazi.is_some() {
+        write!(narrative, "BaZi Synastry complete. The four pillars of both charts were compared stem by stem and branch by branch. ")?;
+    } else {
+        write!(narrative, "Deterministic Seed Analysis complete. The combined waveform of these two entities generates a stable resonance pattern. ")?;
+    }
     if score > 80.0 {
         write!(narrative, "Extremely high compatibility detected. A 'Soul Bond' configuration.")?;
     } else if score > 50.0 {
@@ -89,33 +313,104 @@ fn calculate_seed_hash(req: &EntanglementRequest) -> anyhow::Result<Entanglement
         compatibility_factors: factors,
         narrative,
         shared_hexagram: Some(hex_idx),
+        astrological_harmony: None,
+        bazi,
+        seed: None,
+        group: None,
     })
 }
 
+/// Compares two BaZi charts from 50 (neutral): stem combinations and branch combinations add,
+/// clashes subtract, the day pillars count double, and the day masters' elements and the
+/// charts' mutual supply of useful elements shift the score further.
+pub fn bazi_synastry(first: &BirthMoment, second: &BirthMoment) -> anyhow::Result<BaZiSynastry> {
+    let (a, b) = (first.bazi()?, second.bazi()?);
+    let pillars = |p: &BaZiProfile| [p.year_pillar.clone(), p.month_pillar.clone(), p.day_pillar.clone(), p.hour_pillar.clone()];
+    let (pa, pb) = (pillars(&a), pillars(&b));
+    let indices = |ps: &[String; 4]| ps.clone().map(|p| parse_pillar(&p).expect("calculate_bazi writes parsable pillars"));
+    let (ia, ib) = (indices(&pa), indices(&pb));
+    let mut score = 50.0;
+
+    // 1. Stems and branches across the charts, pillar by pillar
+    let (mut stem_combinations, mut branch_combinations, mut branch_clashes) = (Vec::new(), Vec::new(), Vec::new());
+    for i in 0..4 {
+        for j in 0..4 {
+            let weight = if i == 2 && j == 2 { 2.0 } else { 1.0 };
+            let what = if i == j { format!("{} pillars", capitalize(PILLARS[i])) } else { format!("{} / {} pillars", capitalize(PILLARS[i]), PILLARS[j]) };
+            let ((sa, ba), (sb, bb)) = (ia[i], ib[j]);
+            if let Some(element) = stem_combination(sa, sb) {
+                score += 4.0 * weight;
+                stem_combinations.push(format!("{}: stems combine ({} + {} -> {})", what, get_stem(sa), get_stem(sb), WU_XING[element]));
+            }
+            if is_six_combination(ba, bb) {
+                score += 4.0 * weight;
+                branch_combinations.push(format!("{}: branches combine ({} / {})", what, get_branch(ba), get_branch(bb)));
+            } else if is_six_clash(ba, bb) {
+                score -= 4.0 * weight;
+                branch_clashes.push(format!("{}: branches clash ({} / {})", what, get_branch(ba), get_branch(bb)));
+            }
+        }
+    }
+
+    // 2. Day master elements: companions and the generating cycle help, control strains
+    let (ea, eb) = (ia[2].0 % 10 / 2, ib[2].0 % 10 / 2);
+    let (day_master_relation, effect) = match (eb + 5 - ea) % 5 {
+        0 => (format!("both {}: companions", WU_XING[ea]), 5.0),
+        1 => (format!("{} feeds {}", WU_XING[ea], WU_XING[eb]), 8.0),
+        4 => (format!("{} feeds {}", WU_XING[eb], WU_XING[ea]), 8.0),
+        2 => (format!("{} controls {}", WU_XING[ea], WU_XING[eb]), -6.0),
+        _ => (format!("{} controls {}", WU_XING[eb], WU_XING[ea]), -6.0),
+    };
+    score += effect;
+
+    // 3. Does each chart hold what the other needs?
+    let supply = |needs: &BaZiProfile, giver: &BaZiProfile| {
+        let scores = &giver.strength.as_ref().expect("calculate_bazi analyses strength").element_scores;
+        let total: f64 = scores.values().sum();
+        let useful: f64 = needs.favorable_elements.iter().filter_map(|e| scores.get(e)).sum();
+        let even = needs.favorable_elements.len() as f64 / 5.0;
+        if total > 0.0 && even > 0.0 { useful / total / even } else { 1.0 }
+    };
+    let element_complementarity = (supply(&a, &b) + supply(&b, &a)) / 2.0;
+    score += ((element_complementarity - 1.0) * 20.0).clamp(-10.0, 10.0);
+
+    Ok(BaZiSynastry {
+        first_pillars: pa,
+        second_pillars: pb,
+        stem_combinations,
+        branch_combinations,
+        branch_clashes,
+        day_master_relation,
+        element_complementarity,
+        score: score.clamp(0.0, 100.0),
+    })
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars.next().map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+}
+
+/// Events in one Entropy Stream run.
+pub const STREAM_EVENTS: usize = 100;
+
 // === MODE B: ENTROPY STREAM (Probabilistic) ===
-// Fetches entropy and simulates how two entities 'ride the wave' together.
+// Drives a stream of entropy events and simulates how two entities 'ride the wave' together.
 // Does their luck correlate?
-fn calculate_entropy_stream(req: &EntanglementRequest) -> anyhow::Result<EntanglementReport> {
-    // For simulation, we ideally need the actual BaZi charts to see if they like the same elements.
-    // Since we don't have full BaZi logic exposed easily here without full profile parsing,
-    // we will simulate "Abstract Resonance" using the hash of their data as a "seed" for their
-    // individual reaction functions.
+fn calculate_entropy_stream(req: &EntanglementRequest, seed: [u8; 32]) -> anyhow::Result<EntanglementReport> {
+    // We simulate "Abstract Resonance" using the hash of each profile's data as a "seed" for
+    // its individual reaction function.
 
     // 1. Derive a "Reaction Seed" for each profile
     let seed1 = derive_reaction_seed(&req.profile1_data);
     let seed2 = derive_reaction_seed(&req.profile2_data);
 
-    // 2. Simulate 100 "Time Steps" of Entropy
-    // In a real scenario, we'd fetch from CURBy. Here we use a local RNG seeded by system time for the "Stream"
-    // to simulate a live flux if we don't have a batch passed.
-    // (Ideally the controller passes entropy, but for this tool we'll self-generate for now).
-
-    let mut rng = rand::thread_rng();
-    use rand::Rng;
-
+    // 2. Simulate the "Time Steps" on a ChaCha20 stream keyed by the quantum seed, so the
+    // same seed replays the same events
+    let mut rng = ChaCha20Rng::from_seed(seed);
     let mut correlation_sum: f64 = 0.0;
 
-    for _ in 0..100 {
+    for _ in 0..STREAM_EVENTS {
         // "Event" is a value -1.0 to 1.0 representing some energy shift
         let event_val: f64 = rng.gen_range(-1.0..1.0);
 
@@ -138,7 +433,7 @@ fn calculate_entropy_stream(req: &EntanglementRequest) -> anyhow::Result<Entangl
 
     let factors = vec![
         format!("Quantum Synchronization: {:.1}%", score),
-        "Simulated 100 Entropy Events".to_string()
+        format!("Simulated {} Entropy Events", STREAM_EVENTS),
     ];
 
     let mut narrative = String::new();
@@ -155,13 +450,16 @@ fn calculate_entropy_stream(req: &EntanglementRequest) -> anyhow::Result<Entangl
         compatibility_factors: factors,
         narrative,
         shared_hexagram: None,
+        astrological_harmony: None,
+        bazi: None,
+        seed: Some(hex::encode(seed)),
+        group: None,
     })
 }
 
+/// First eight bytes of the data's SHA-256, so reaction seeds are stable across builds.
 fn derive_reaction_seed(data: &str) -> u64 {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    let mut s = DefaultHasher::new();
-    data.hash(&mut s);
-    s.finish()
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(data.as_bytes());
+    u64::from_le_bytes(digest[..8].try_into().unwrap())
 }
@@ -9,17 +9,29 @@ mod tests {
             profile1_data: "UserA".to_string(),
             profile2_data: "UserB".to_string(),
             mode: EntanglementMode::SeedHash,
+            group: Vec::new(),
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
         };
 
-        let report1 = calculate_entanglement(&req1).unwrap();
+        let report1 = calculate_entanglement(&req1, None).unwrap();
 
         // Run again
         let req2 = EntanglementRequest {
             profile1_data: "UserA".to_string(),
             profile2_data: "UserB".to_string(),
             mode: EntanglementMode::SeedHash,
+            group: Vec::new(),
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
         };
-        let report2 = calculate_entanglement(&req2).unwrap();
+        let report2 = calculate_entanglement(&req2, None).unwrap();
 
         assert_eq!(report1.resonance_score, report2.resonance_score);
         assert_eq!(report1.compatibility_factors.len(), 3);
@@ -35,15 +47,27 @@ mod tests {
             profile1_data: "UserA".to_string(),
             profile2_data: "UserB".to_string(),
             mode: EntanglementMode::SeedHash,
+            group: Vec::new(),
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
         };
-        let r1 = calculate_entanglement(&req1).unwrap();
+        let r1 = calculate_entanglement(&req1, None).unwrap();
 
         let req2 = EntanglementRequest {
             profile1_data: "UserB".to_string(),
             profile2_data: "UserA".to_string(),
             mode: EntanglementMode::SeedHash,
+            group: Vec::new(),
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
         };
-        let r2 = calculate_entanglement(&req2).unwrap();
+        let r2 = calculate_entanglement(&req2, None).unwrap();
 
         assert_ne!(r1.resonance_score, r2.resonance_score);
     }
@@ -54,9 +78,154 @@ mod tests {
             profile1_data: "UserA".to_string(),
             profile2_data: "UserB".to_string(),
             mode: EntanglementMode::EntropyStream,
+            group: Vec::new(),
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
         };
-        let r = calculate_entanglement(&req).unwrap();
+        let mut session = crate::engine::SimulationSession::new((0..=255).collect());
+        let r = calculate_entanglement(&req, Some(&mut session)).unwrap();
         // Just check it returns a score 0-100
         assert!(r.resonance_score >= 0.0 && r.resonance_score <= 100.0);
+        assert_eq!(session.fallback_draws, 0);
+
+        // The reported seed replays the run without any entropy
+        let seed = r.seed.clone().unwrap();
+        let replay = calculate_entanglement(&EntanglementRequest { seed: Some(seed.clone()), ..req }, None).unwrap();
+        assert_eq!(replay.resonance_score, r.resonance_score);
+        assert_eq!(replay.seed, Some(seed));
+    }
+
+    #[test]
+    fn test_entropy_stream_needs_entropy() {
+        let req = EntanglementRequest {
+            profile1_data: "UserA".to_string(),
+            profile2_data: "UserB".to_string(),
+            mode: EntanglementMode::EntropyStream,
+            group: Vec::new(),
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
+        };
+        assert!(calculate_entanglement(&req, None).is_err());
+        let bad_seed = EntanglementRequest { seed: Some("abcd".to_string()), ..req };
+        assert!(calculate_entanglement(&bad_seed, None).is_err());
+    }
+
+    #[test]
+    fn test_charts_blend_synastry() {
+        use crate::tools::astrology_reports::SynastryConfig;
+        use crate::tools::astrology_western::{HouseSystem, NatalConfig};
+        use crate::tools::astronomy::Location;
+
+        let chart = |date: &str| NatalConfig {
+            datetime: chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap(),
+            location: Location { longitude: 0.0, utc_offset: 0.0 },
+            latitude: 51.5,
+            house_system: HouseSystem::WholeSign,
+        };
+        let mut req = EntanglementRequest {
+            profile1_data: "UserA".to_string(),
+            profile2_data: "UserB".to_string(),
+            mode: EntanglementMode::SeedHash,
+            group: Vec::new(),
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
+        };
+        let plain = calculate_entanglement(&req, None).unwrap();
+        req.charts = Some(SynastryConfig { first: chart("1990-06-15 12:00"), second: chart("1992-02-03 08:30") });
+        let blended = calculate_entanglement(&req, None).unwrap();
+
+        let harmony = blended.astrological_harmony.unwrap();
+        assert!((blended.resonance_score - (plain.resonance_score + harmony) / 2.0).abs() < 1e-9);
+        assert!(blended.compatibility_factors.len() > 3);
+    }
+
+    #[test]
+    fn test_bazi_synastry_factors() {
+        use crate::tools::entanglement::BirthMoment;
+
+        // Day pillars Wu Wu (2000-01-01) and Gui Hai (2000-01-06): Wu + Gui combine into Fire
+        let birth = |date: &str| BirthMoment {
+            datetime: chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap(),
+            gender: None,
+        };
+        let req = EntanglementRequest {
+            profile1_data: "UserA".to_string(),
+            profile2_data: "UserB".to_string(),
+            mode: EntanglementMode::SeedHash,
+            group: Vec::new(),
+            charts: None,
+            birth1: Some(birth("2000-01-01 12:00")),
+            birth2: Some(birth("2000-01-06 12:00")),
+            entropy_batch_id: None,
+            seed: None,
+        };
+        let report = calculate_entanglement(&req, None).unwrap();
+        let bazi = report.bazi.as_ref().unwrap();
+        assert!(bazi.stem_combinations.iter().any(|f| f.starts_with("Day pillars: stems combine (Wu + Gui -> Fire)")));
+        // The hash only breaks ties
+        assert!((report.resonance_score - bazi.score).abs() <= 0.5);
+
+        // Saved profiles passed as JSON are read the same way
+        let profile = |y, m, d| format!(r#"{{"id":1,"name":"A","birth_year":{},"birth_month":{},"birth_day":{},"birth_hour":12}}"#, y, m, d);
+        let from_profiles = calculate_entanglement(&EntanglementRequest {
+            profile1_data: profile(2000, 1, 1),
+            profile2_data: profile(2000, 1, 6),
+            mode: EntanglementMode::SeedHash,
+            group: Vec::new(),
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
+        }, None).unwrap();
+        assert_eq!(from_profiles.bazi.unwrap().score, bazi.score);
+    }
+
+    #[test]
+    fn test_group_resonance() {
+        let profile = |name: &str, d| format!(r#"{{"name":"{}","birth_year":2000,"birth_month":1,"birth_day":{},"birth_hour":12}}"#, name, d);
+        let mut req = EntanglementRequest {
+            profile1_data: String::new(),
+            profile2_data: String::new(),
+            mode: EntanglementMode::SeedHash,
+            group: vec![profile("Ann", 1), profile("Bo", 6), profile("Cy", 9), "raw text".to_string()],
+            charts: None,
+            birth1: None,
+            birth2: None,
+            entropy_batch_id: None,
+            seed: None,
+        };
+        let report = calculate_entanglement(&req, None).unwrap();
+        let group = report.group.unwrap();
+        assert_eq!(group.members, ["Ann", "Bo", "Cy", "Member 4"]);
+        assert_eq!(group.matrix.len(), 4);
+        for i in 0..4 {
+            assert_eq!(group.matrix[i][i], 100.0);
+            for j in 0..4 {
+                assert_eq!(group.matrix[i][j], group.matrix[j][i]);
+            }
+        }
+        assert!(group.weakest_pair.2 <= group.strongest_pair.2 && group.coherence <= group.strongest_pair.2);
+        assert_eq!(report.resonance_score, group.coherence);
+
+        // A stream group shares one seed, which replays every pairing
+        req.mode = EntanglementMode::EntropyStream;
+        let mut session = crate::engine::SimulationSession::new(vec![7; 64]);
+        let stream = calculate_entanglement(&req, Some(&mut session)).unwrap();
+        req.seed = stream.seed.clone();
+        let replay = calculate_entanglement(&req, None).unwrap();
+        assert_eq!(replay.group.unwrap().matrix, stream.group.unwrap().matrix);
+
+        req.group.truncate(2);
+        assert!(calculate_entanglement(&req, None).is_err());
     }
 }
@@ -2,14 +2,26 @@ use anyhow::Result;
 use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
 use crate::client::CurbyClient;
-use crate::engine::SimulationSession;
-use crate::tools::astronomy::get_solar_term;
+use crate::engine::{EntropyRange, SimulationOptions, SimulationSession};
+use crate::tools::astronomy::{get_solar_term, solar_hour, Location};
 use crate::tools::san_he::{analyze_san_he, SanHeAnalysis};
 use crate::tools::qimen::{calculate_qimen, QiMenChart};
-use crate::tools::chinese_meta::{get_stem, get_branch};
+use crate::tools::chinese_meta::{get_stem, get_branch, day_pillar};
+use crate::tools::da_gua;
+use crate::tools::bazi_strength::{self, DayMasterStrength};
+use crate::tools::bazi_luck::{self, AnnualPillar, LuckPillar};
+use crate::tools::ten_gods::{self, TenGodsAnalysis};
+use crate::tools::ba_zhai::{analyze_ba_zhai, BaZhaiAnalysis};
+use crate::tools::floor_plan::{self, analyze_rooms, Room, RoomAnalysis};
+use crate::tools::cures::{simulate_cures, CureSimulation};
+use crate::tools::qi_flow::{self, analyze_qi_flow, QiFlowAnalysis};
 use std::sync::Arc;
 use crate::db::Db;
-use hex;
+use crate::services::intention::{self, IntentionBinding};
+use crate::services::quantum;
+use crate::tools::advice::{generate_advice, AdviceContext, AdviceItem};
+use crate::tools::i18n::{glossary, Glossary, Locale};
+use crate::tools::period_transition::{plan_transition, TransitionPlan, TARGET_PERIOD};
 
 /// Configuration for a Feng Shui analysis session.
 ///
@@ -27,10 +39,19 @@ pub struct FengShuiConfig {
     pub birth_hour: Option<u32>,
     /// Gender ("M" or "F") for Kua number calculation.
     pub gender: Option<String>,
+    /// Birth place; converts the birth hour (and the Qi Men hour) to true solar time.
+    #[serde(default)]
+    pub location: Option<Location>,
     /// Year the building was constructed (determines the Period).
     pub construction_year: i32,
     /// Magnetic compass reading of the house facing direction (0.0 - 359.9).
     pub facing_degrees: f64,
+    /// Compass degree where water leaves the site, for the San He water method.
+    #[serde(default)]
+    pub water_exit_degrees: Option<f64>,
+    /// Compass degrees of other water on the site (ponds, pools, incoming streams).
+    #[serde(default)]
+    pub water_features: Option<Vec<f64>>,
     /// Year for the Annual Star chart (defaults to current system year).
     pub current_year: Option<i32>,
     /// Month for the Monthly Star chart.
@@ -45,6 +66,18 @@ pub struct FengShuiConfig {
     pub virtual_cures: Option<Vec<VirtualCure>>,
     /// ID of the entropy batch to use for simulation. If None, falls back to legacy/live mode.
     pub entropy_batch_id: Option<i64>,
+    /// Anomaly thresholds and resolution for the sector volatility simulation.
+    #[serde(default)]
+    pub simulation_options: Option<SimulationOptions>,
+    /// Floor plan rooms to map onto the palaces for per-room advice.
+    #[serde(default)]
+    pub rooms: Option<Vec<Room>>,
+    /// First and last year of BaZi annual pillars (defaults to the current year and the nine after it).
+    #[serde(default)]
+    pub annual_years: Option<[i32; 2]>,
+    /// Language of the advice text and glossary (defaults to English).
+    #[serde(default)]
+    pub locale: Option<Locale>,
 }
 
 /// Represents a "Virtual Cure" placed on the frontend grid.
@@ -70,33 +103,89 @@ pub struct FengShuiReport {
     pub annual_chart: FlyingStarChart,
     pub replacement_chart: Option<FlyingStarChart>,
     pub yearly_afflictions: Vec<String>,
+    /// Year, month and day afflictions and visiting 5s and 2s overlaid by palace, worst first.
+    #[serde(default)]
+    pub date_afflictions: Vec<AfflictionOverlay>,
     pub monthly_chart: Option<FlyingStarChart>,
     pub daily_chart: Option<FlyingStarChart>,
     pub formations: Vec<String>,
     pub quantum: QuantumAnalysis,
-    pub advice: Vec<String>,
+    /// Advice from every rule, tagged with its rule id, severity and sector.
+    pub advice: Vec<AdviceItem>,
     pub san_he: Option<SanHeAnalysis>,
     pub qimen: Option<QiMenChart>,
     pub period_9_compliance: Vec<String>,
+    /// Keep, re-roof or re-face options for a pre-Period 9 house, scored for Period 9.
+    #[serde(default)]
+    pub period_transition: Option<TransitionPlan>,
+    /// Eight Mansions reading of the house and occupant, scored against the annual chart.
+    #[serde(default)]
+    pub ba_zhai: Option<BaZhaiAnalysis>,
+    /// Per-room advice for the floor plan, if rooms were given.
+    #[serde(default)]
+    pub rooms: Vec<RoomAnalysis>,
+    /// Stored batch the entropy was read from; `None` when it was fetched live.
+    #[serde(default)]
+    pub entropy_batch_id: Option<i64>,
+    /// Pool bytes the report drew, so it can be replayed against the batch.
+    #[serde(default)]
+    pub entropy_ranges: Vec<EntropyRange>,
+    /// Beacon rounds of the batch pulses those bytes came from.
+    #[serde(default)]
+    pub entropy_rounds: Vec<i64>,
+    /// The intention bound into the entropy before any draw, if one was given.
+    #[serde(default)]
+    pub intention: Option<IntentionBinding>,
+    /// Palace and star names in the requested locale, for labelling the charts.
+    #[serde(default)]
+    pub glossary: Glossary,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HexagramInfo {
     pub name: String,
+    /// Position on the 64-hexagram ring (1 = Kun at North, clockwise).
     pub index: usize,
     pub meaning: String,
     pub element: String,
+    #[serde(default)]
+    pub king_wen: u8,
+    /// Xuan Kong Da Gua element (Gua Qi) number.
+    #[serde(default)]
+    pub element_number: i32,
+    /// Xuan Kong Da Gua period (Gua Yun) number.
+    #[serde(default)]
+    pub period_number: i32,
+    #[serde(default)]
+    pub auspicious: bool,
 }
 
 /// Represents the Four Pillars of Destiny (BaZi) for the user.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BaZiProfile {
     pub year_pillar: String,
     pub month_pillar: String,
     pub day_pillar: String,
     pub hour_pillar: String,
     pub day_master: String,
+    /// Useful elements from the day master strength analysis.
     pub favorable_elements: Vec<String>,
+    #[serde(default)]
+    pub strength: Option<DayMasterStrength>,
+    /// Ten Gods of every stem and hidden stem, with relationship, wealth and career readings.
+    #[serde(default)]
+    pub ten_gods: Option<TenGodsAnalysis>,
+    /// "forward" or "backward"; unknown without a gender.
+    #[serde(default)]
+    pub luck_direction: Option<String>,
+    /// Age the first luck pillar starts at (three days to the sectional term per year).
+    #[serde(default)]
+    pub luck_start_age: Option<f64>,
+    #[serde(default)]
+    pub luck_pillars: Vec<LuckPillar>,
+    /// Annual pillars for the requested years.
+    #[serde(default)]
+    pub annual_pillars: Vec<AnnualPillar>,
     pub quantum_flux: Option<String>, // Real-time elemental strength amplified by quantum noise.
     pub alternate_pillars: Option<Vec<String>>, // Probabilistic "alternate timeline" pillars.
 }
@@ -133,14 +222,17 @@ pub struct Palace {
 /// Analysis derived from Quantum Entropy simulations.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuantumAnalysis {
-    pub volatility_index: f64, // 0.0 - 1.0 Measure of entropy chaos
+    pub volatility_index: f64, // 0.0 - 1.0 Measure of entropy chaos (see `quantum::volatility_index`)
     pub focus_sector: String, // The sector "chosen" by the quantum decision tree
     pub anomalies: Vec<String>, // Statistical outliers found in simulation
     pub intention_resonance: Option<String>,
     pub suggested_cures: Vec<CureSuggestion>,
     pub qi_flow: Option<QiFlowAnalysis>,
     pub qi_heatmap: Option<Vec<Vec<f64>>>, // 3x3 Heatmap for frontend visualization
-    pub cure_efficacy: Option<f64>, // Simulated impact of virtual cures
+    pub cure_efficacy: Option<f64>, // Share of the affliction score the virtual cures remove
+    /// Per-cure Monte Carlo comparison behind `cure_efficacy`, when cures were placed.
+    #[serde(default)]
+    pub cure_simulation: Option<CureSimulation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -152,12 +244,6 @@ pub struct CureSuggestion {
     pub success_probability: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct QiFlowAnalysis {
-    pub flow_path: Vec<String>,
-    pub blockages: Vec<String>,
-}
-
 // === MAIN ENTRY POINTS ===
 
 /// CLI Entry Point (Deprecated, now defaults to Server)
@@ -175,40 +261,40 @@ pub async fn run_feng_shui_cli() -> Result<()> {
 /// 4. Aggregates results into a comprehensive report.
 pub async fn generate_report(config: FengShuiConfig, db: Option<Arc<Db>>) -> Result<FengShuiReport> {
     // 1. Initialize Quantum Source
-    let entropy: Vec<u8>;
-
-    if let (Some(db_ref), Some(batch_id)) = (&db, config.entropy_batch_id) {
-         // Load from DB
-         println!("Loading entropy from Batch {}", batch_id);
-         let rows = db_ref.get_batch_entropy(batch_id).await?;
-         let mut buffer = Vec::new();
-         for row in rows {
-             if let Ok(bytes) = hex::decode(row.hex_value) {
-                 buffer.extend(bytes);
-             }
-         }
-         if buffer.is_empty() {
-             // Fallback if batch empty
-             println!("Batch empty, fetching live.");
-             let mut client = CurbyClient::new();
-             entropy = client.fetch_bulk_randomness(4096).await?;
-         } else {
-             entropy = buffer;
-         }
-    } else {
-         let mut client = CurbyClient::new();
-         // Fetch 4KB of true randomness to seed simulations
-         entropy = client.fetch_bulk_randomness(4096).await?;
-    }
+    // A stored batch is replayed from its first pulse; `pulse_starts` maps pool offsets back to rounds
+    let mut pulse_starts: Vec<(usize, Option<i64>)> = Vec::new();
+    let mut source_batch = None;
+    let entropy: Vec<u8> = match (&db, config.entropy_batch_id) {
+        (Some(db_ref), Some(batch_id)) => {
+            tracing::info!(batch_id, "Loading entropy from batch");
+            let mut buffer = Vec::new();
+            for row in db_ref.get_batch_entropy(batch_id).await? {
+                pulse_starts.push((buffer.len(), row.pulse_round));
+                buffer.extend(row.pulse);
+            }
+            if buffer.is_empty() {
+                anyhow::bail!("Entropy batch {} has no pulses yet", batch_id);
+            }
+            source_batch = Some(batch_id);
+            buffer
+        }
+        _ => {
+            let mut client = CurbyClient::new();
+            // Fetch 4KB of true randomness to seed simulations
+            client.fetch_bulk_randomness(4096).await?
+        }
+    };
 
-    let session = SimulationSession::new(entropy);
+    // Bound bytes keep their positions, so `pulse_starts` still applies
+    let (entropy, intention) = intention::FENG_SHUI.bind(entropy, config.intention.as_deref());
+    let mut session = SimulationSession::new(entropy);
 
     // 2. BaZi Calculation (with Solar Terms and Quantum Mode)
     let bazi_profile = if let (Some(y), Some(m), Some(d)) = (config.birth_year, config.birth_month, config.birth_day) {
-        match calculate_bazi(y, m, d, config.birth_hour.unwrap_or(12), if config.quantum_mode { Some(&session) } else { None }) {
-            Ok(profile) => Some(profile),
-            Err(_) => None,
-        }
+        let this_year = config.current_year.unwrap_or_else(|| chrono::Local::now().year());
+        let annual_years = config.annual_years.map(|[first, last]| (first, last)).unwrap_or((this_year, this_year + 9));
+        let (y, m, d, h) = solar_hour(y, m, d, config.birth_hour.unwrap_or(12), config.location.as_ref());
+        calculate_bazi(y, m, d, h, config.gender.as_deref(), Some(annual_years), config.quantum_mode.then_some(&mut session)).ok()
     } else { None };
 
     // 3. Personal Kua Calculation
@@ -228,25 +314,50 @@ pub async fn generate_report(config: FengShuiConfig, db: Option<Arc<Db>>) -> Res
 
     // 6. Flying Star Chart Generation
     // If quantum_mode is on, stars may "mutate" (flip polarity) based on entropy.
-    let mutation_source = if config.quantum_mode { Some(&session) } else { None };
+    let quantum = config.quantum_mode;
 
-    let annual_chart = calculate_flying_star_chart(config.construction_year, config.facing_degrees, current_year, mutation_source);
-    let replacement_chart = calculate_replacement_chart(config.construction_year, config.facing_degrees, current_year, mutation_source);
+    let annual_chart = calculate_flying_star_chart(config.construction_year, config.facing_degrees, current_year, quantum.then_some(&mut session));
+    let replacement_chart = calculate_replacement_chart(config.construction_year, config.facing_degrees, current_year, quantum.then_some(&mut session));
     let yearly_afflictions = calculate_yearly_afflictions(current_year, config.facing_degrees);
-    let monthly_chart = calculate_monthly_chart(current_year, current_month, mutation_source);
-    let daily_chart = calculate_daily_chart(current_year, current_month, current_day, mutation_source);
+    let monthly_chart = calculate_monthly_chart(current_year, current_month, quantum.then_some(&mut session));
+    let daily_chart = calculate_daily_chart(current_year, current_month, current_day, quantum.then_some(&mut session));
+
+    let date_afflictions = calculate_date_afflictions(
+        current_year, current_month, current_day, config.facing_degrees,
+        &annual_chart, monthly_chart.as_ref(), daily_chart.as_ref(),
+    );
 
     // 7. Analysis & Pattern Detection
     let formations = analyze_formations(&annual_chart);
 
     // 8. Quantum Simulation (Qi Flow, Heatmaps, Cures)
-    let quantum = run_quantum_analysis(&session, &annual_chart, monthly_chart.as_ref(), config.intention.as_deref(), config.virtual_cures.as_ref());
-
-    let advice = generate_advice(&annual_chart, &kua_profile, &quantum, &formations);
+    let sim_options = config.simulation_options.clone().unwrap_or_default();
+    let qi_flow = analyze_qi_flow(&annual_chart, config.facing_degrees, config.rooms.as_deref());
+    let quantum = run_quantum_analysis(&mut session, &annual_chart, monthly_chart.as_ref(), config.intention.as_deref(), config.virtual_cures.as_ref(), qi_flow, &sim_options);
+
+    let ba_zhai = house_kua.as_ref().and_then(|h| analyze_ba_zhai(h.number, kua_profile.as_ref().map(|k| k.number), &annual_chart));
+    let rooms = match &config.rooms {
+        Some(rooms) => analyze_rooms(rooms, &annual_chart, kua_profile.as_ref())?,
+        None => Vec::new(),
+    };
 
     // 9. Advanced Schools (San He, Qi Men Dun Jia)
-    let san_he = Some(analyze_san_he(config.facing_degrees, None));
-    let qimen = Some(calculate_qimen(current_year, current_month, current_day, config.birth_hour.unwrap_or(12)));
+    let san_he = Some(analyze_san_he(config.facing_degrees, config.water_exit_degrees, config.water_features.as_deref().unwrap_or_default()));
+    let (qy, qm, qd, qh) = solar_hour(current_year, current_month, current_day, config.birth_hour.unwrap_or(12), config.location.as_ref());
+    let qimen = Some(calculate_qimen(qy, qm, qd, qh));
+    let locale = config.locale.unwrap_or_default();
+    let advice = generate_advice(&AdviceContext {
+        chart: &annual_chart,
+        kua: kua_profile.as_ref(),
+        quantum: &quantum,
+        formations: &formations,
+        hexagram: hexagram.as_ref(),
+        facing_degrees: config.facing_degrees,
+        current_period: get_period(current_year),
+        qimen: qimen.as_ref(),
+        date_afflictions: &date_afflictions,
+        locale,
+    });
 
     // 10. Period 9 Compliance Check
     let mut p9_compliance = Vec::new();
@@ -261,6 +372,26 @@ pub async fn generate_report(config: FengShuiConfig, db: Option<Arc<Db>>) -> Res
     } else {
         p9_compliance.push(format!("Current Period: {}. Prepare for Period 9 transition.", annual_chart.period));
     }
+    let period_transition = (get_period(config.construction_year) < TARGET_PERIOD)
+        .then(|| plan_transition(config.construction_year, config.facing_degrees, current_year));
+    if let Some(plan) = &period_transition {
+        p9_compliance.push(plan.summary.clone());
+    }
+
+    // 11. Entropy Provenance
+    // A batch that ran dry would silently finish on the PRNG, so the report is refused instead
+    if let (Some(batch_id), true) = (source_batch, session.fallback_draws > 0) {
+        anyhow::bail!(
+            "Entropy batch {} is exhausted: its {} bytes ran out before the report was complete; harvest more pulses into it",
+            batch_id, session.entropy_pool.len()
+        );
+    }
+    let consumed = session.pool_index;
+    let entropy_ranges = if consumed > 0 { vec![EntropyRange { start: 0, end: consumed }] } else { vec![] };
+    let entropy_rounds = pulse_starts.iter()
+        .take_while(|(start, _)| *start < consumed)
+        .filter_map(|(_, round)| *round)
+        .collect();
 
     Ok(FengShuiReport {
         bazi: bazi_profile,
@@ -270,6 +401,7 @@ pub async fn generate_report(config: FengShuiConfig, db: Option<Arc<Db>>) -> Res
         annual_chart,
         replacement_chart,
         yearly_afflictions,
+        date_afflictions,
         monthly_chart,
         daily_chart,
         formations,
@@ -278,6 +410,14 @@ pub async fn generate_report(config: FengShuiConfig, db: Option<Arc<Db>>) -> Res
         san_he,
         qimen,
         period_9_compliance: p9_compliance,
+        period_transition,
+        ba_zhai,
+        rooms,
+        entropy_batch_id: source_batch,
+        entropy_ranges,
+        entropy_rounds,
+        intention,
+        glossary: glossary(locale),
     })
 }
 
@@ -286,8 +426,18 @@ pub async fn generate_report(config: FengShuiConfig, db: Option<Arc<Db>>) -> Res
 /// Calculates the Four Pillars of Destiny (BaZi).
 ///
 /// Uses astronomical solar terms to determine the exact boundaries of months.
+/// With a `gender` ("M"/"F") the 10-year luck pillars are added, and `annual_years`
+/// (first, last) adds the annual pillars of that range.
 /// If `session` is provided, adds "Quantum Flux" analysis.
-pub fn calculate_bazi(year: i32, month: u32, day: u32, hour: u32, session: Option<&SimulationSession>) -> Result<BaZiProfile> {
+pub fn calculate_bazi(
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    gender: Option<&str>,
+    annual_years: Option<(i32, i32)>,
+    session: Option<&mut SimulationSession>,
+) -> Result<BaZiProfile> {
     if month < 1 || month > 12 { anyhow::bail!("Invalid month: {}", month); }
     if day < 1 || day > 31 { anyhow::bail!("Invalid Day"); }
     // Check NaiveDate validity
@@ -295,15 +445,14 @@ pub fn calculate_bazi(year: i32, month: u32, day: u32, hour: u32, session: Optio
 
     // Calculate Solar Term to find true Month Branch
     let term_idx = get_solar_term(year, month, day);
-    // Rough mapping: Term 0 (Spring Equinox) ~ Month 2 (Rabbit).
+    // Months start at the sectional terms (odd indices): Term 1 (Qingming, 15°) opens
+    // the Dragon month, so Terms 23 and 0 (Jingzhe to Qingming) are the Rabbit month.
     // This formula aligns term index to branch index (0=Rat, 1=Ox...)
-    let month_branch_idx = ((term_idx + 2) / 2 + 2) % 12;
+    let month_branch_idx = (term_idx.div_ceil(2) + 3) % 12;
 
     // Year Pillar Calculation
     // Base year 1924 is Jia Zi (Wood Rat)
-    let year_offset = (year - 1924).rem_euclid(60);
-    let year_stem_idx = year_offset.rem_euclid(10) as usize;
-    let year_branch_idx = year_offset.rem_euclid(12) as usize;
+    let (year_stem_idx, year_branch_idx) = bazi_luck::year_pillar(year);
     let year_pillar = format!("{} {}", get_stem(year_stem_idx), get_branch(year_branch_idx));
 
     // Month Pillar Calculation
@@ -316,11 +465,7 @@ pub fn calculate_bazi(year: i32, month: u32, day: u32, hour: u32, session: Optio
 
     // Day Pillar Calculation
     // Requires counting days from a reference point (Jan 1 2000)
-    let base2000 = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
-    let days = (NaiveDate::from_ymd_opt(year, month, day).unwrap() - base2000).num_days();
-    // Reference offset for 2000-01-01
-    let day_stem_idx = (4 + days).rem_euclid(10) as usize;
-    let day_branch_idx = (6 + days).rem_euclid(12) as usize;
+    let (day_stem_idx, day_branch_idx) = day_pillar(NaiveDate::from_ymd_opt(year, month, day).unwrap());
     let day_pillar = format!("{} {}", get_stem(day_stem_idx), get_branch(day_branch_idx));
 
     // Hour Pillar Calculation
@@ -337,12 +482,12 @@ pub fn calculate_bazi(year: i32, month: u32, day: u32, hour: u32, session: Optio
     if let Some(sess) = session {
         // Real-time Flux: Simulate current energy boosting specific elements
         let elements = vec!["Wood", "Fire", "Earth", "Metal", "Water"];
-        let flux_element = sess.simulate_decision(&elements.iter().map(|s| s.to_string()).collect::<Vec<_>>(), None, 20).winner;
+        let flux_element = elements[sess.next_u64_in_range(0, elements.len() as u64) as usize];
         quantum_flux = Some(format!("Quantum Field is currently amplifying: {}.", flux_element));
 
         // Probabilistic Birth: Simulate 'what if' the user was born +/- 1 hour (changing the pillar)
         // Simplified: Just randomize one alternate hour pillar
-        let alt_hour_offset = if sess.simulate_decision(&vec!["+".to_string(), "-".to_string()], None, 5).winner == "+" { 1 } else { -1 };
+        let alt_hour_offset = if sess.next_bool(0.5) { 1 } else { -1 };
         // Recalc hour with offset
         let alt_hour_idx = (hour_branch_idx as i32 + alt_hour_offset).rem_euclid(12) as usize;
         let alt_h_stem_idx = (hour_start_stem + alt_hour_idx as u32) % 10;
@@ -350,10 +495,41 @@ pub fn calculate_bazi(year: i32, month: u32, day: u32, hour: u32, session: Optio
         alternate_pillars = Some(vec![format!("Alternate Timeline (Hour {}): {}", if alt_hour_offset > 0 { "+2h" } else { "-2h" }, alt_pillar)]);
     }
 
+    // Day master strength decides which elements are useful
+    let strength = bazi_strength::analyze(
+        [year_stem_idx, month_stem_idx as usize, day_stem_idx, hour_stem_idx as usize],
+        [year_branch_idx, month_branch_idx as usize, day_branch_idx, hour_branch_idx],
+    );
+
+    let ten_gods = ten_gods::analyze(
+        [year_stem_idx, month_stem_idx as usize, day_stem_idx, hour_stem_idx as usize],
+        [year_branch_idx, month_branch_idx as usize, day_branch_idx, hour_branch_idx],
+        gender,
+        match strength.verdict.as_str() { "strong" => Some(true), "weak" => Some(false), _ => None },
+    );
+
+    // Luck pillars run from the month pillar; direction needs the gender
+    let birth = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    let forward = bazi_luck::runs_forward(gender, year_stem_idx);
+    let luck_start_age = forward.and_then(|f| bazi_luck::luck_start_age(birth, f));
+    let luck_pillars = match (forward, luck_start_age) {
+        (Some(f), Some(age)) => bazi_luck::luck_pillars(month_stem_idx as usize, month_branch_idx as usize, f, age, year),
+        _ => vec![],
+    };
+    let annual_pillars = annual_years
+        .map(|(first, last)| bazi_luck::annual_pillars(first, last, &strength.useful_elements))
+        .unwrap_or_default();
+
     Ok(BaZiProfile {
         year_pillar, month_pillar, day_pillar, hour_pillar,
         day_master: get_stem(day_stem_idx).to_string(),
-        favorable_elements: vec!["Solar Term Adjusted".to_string()],
+        favorable_elements: strength.useful_elements.clone(),
+        strength: Some(strength),
+        ten_gods: Some(ten_gods),
+        luck_direction: forward.map(|f| if f { "forward" } else { "backward" }.to_string()),
+        luck_start_age,
+        luck_pillars,
+        annual_pillars,
         quantum_flux,
         alternate_pillars,
     })
@@ -362,18 +538,19 @@ pub fn calculate_bazi(year: i32, month: u32, day: u32, hour: u32, session: Optio
 /// Runs the Quantum Simulation part of the report.
 ///
 /// Generates the Qi Heatmap, checks for resonance with user intention,
-/// and calculates the efficacy of placed virtual cures.
+/// and calculates the efficacy of placed virtual cures. `qi_flow` is reported as traced.
 fn run_quantum_analysis(
-    session: &SimulationSession,
+    session: &mut SimulationSession,
     chart: &FlyingStarChart,
     _monthly: Option<&FlyingStarChart>,
     intention: Option<&str>,
     virtual_cures: Option<&Vec<VirtualCure>>,
+    qi_flow: QiFlowAnalysis,
+    sim_options: &SimulationOptions,
 ) -> QuantumAnalysis {
     // 1. Sector Volatility Simulation
-    let sectors = vec!["North", "NE", "East", "SE", "South", "SW", "West", "NW", "Center"];
-    let report = session.simulate_decision(&sectors.iter().map(|s| s.to_string()).collect::<Vec<_>>(), None, 100);
-    let volatility = 0.5; // Placeholder for calculated entropy variance
+    let sectors: Vec<String> = ["North", "NE", "East", "SE", "South", "SW", "West", "NW", "Center"].iter().map(|s| s.to_string()).collect();
+    let pass = quantum::analyze(session, &sectors, None, 100, sim_options);
 
     // 2. Automated Cure Suggestions
     let mut cures = Vec::new();
@@ -405,29 +582,24 @@ fn run_quantum_analysis(
         heatmap[coords.0][coords.1] = val;
     }
 
-    // 4. Virtual Cure Impact
-    let mut cure_efficacy = 0.0;
-    if let Some(vc_list) = virtual_cures {
-        for vc in vc_list {
-            let r = vc.y.floor() as usize;
-            let c = vc.x.floor() as usize;
-            if r < 3 && c < 3 {
-                 // Cures boost the local sector energy
-                 heatmap[r][c] += 0.5;
-                 cure_efficacy += 0.1;
-            }
+    // 4. Virtual Cure Impact: each cure lifts its sector by the affliction it simulates away
+    let cure_simulation = virtual_cures.filter(|c| !c.is_empty()).map(|vc_list| simulate_cures(session, chart, vc_list));
+    for effect in cure_simulation.iter().flat_map(|s| &s.cures) {
+        if let Some((r, c)) = effect.sector.as_deref().and_then(floor_plan::sector_cell) {
+            heatmap[r][c] = (heatmap[r][c] + effect.marginal_efficacy).max(0.0);
         }
     }
 
     QuantumAnalysis {
-        volatility_index: volatility,
-        focus_sector: report.winner,
-        anomalies: vec![],
+        volatility_index: pass.volatility,
+        focus_sector: pass.report.winner,
+        anomalies: pass.report.anomalies,
         intention_resonance: intention.map(|s| s.to_string()),
         suggested_cures: cures,
-        qi_flow: None,
+        qi_flow: Some(qi_flow),
         qi_heatmap: Some(heatmap),
-        cure_efficacy: Some(cure_efficacy),
+        cure_efficacy: cure_simulation.as_ref().map(|s| s.efficacy),
+        cure_simulation,
     }
 }
 
@@ -512,21 +684,25 @@ pub fn calculate_house_kua(sitting_deg: f64) -> KuaProfile {
 }
 
 /// Calculates the Hexagram based on specific degree (Xuan Kong Da Gua).
-/// Map 360 degrees to 64 Hexagrams.
+/// Map 360 degrees to 64 Hexagrams of 5.625° each, Kun centred on North.
 pub fn calculate_hexagram(degrees: f64) -> HexagramInfo {
-    let idx = (degrees / 5.625).floor() as usize % 64;
+    let gua = da_gua::lookup(degrees);
     HexagramInfo {
-        name: "Hexagram".to_string(), // Full list omitted for brevity
-        index: idx + 1,
-        meaning: "Auspicious alignment".to_string(),
-        element: "Unknown".to_string(),
+        name: gua.name.to_string(),
+        index: gua.position + 1,
+        meaning: gua.meaning.to_string(),
+        element: gua.element().to_string(),
+        king_wen: gua.king_wen,
+        element_number: gua.element_number,
+        period_number: gua.period_number,
+        auspicious: gua.auspicious,
     }
 }
 
 /// Core Flying Star Logic.
 ///
 /// Determines the Time Star (Period), Mountain Star (Sitting), and Water Star (Facing).
-pub fn calculate_flying_star_chart(construction_year: i32, degrees: f64, current_year: i32, mutation: Option<&SimulationSession>) -> FlyingStarChart {
+pub fn calculate_flying_star_chart(construction_year: i32, degrees: f64, current_year: i32, mut mutation: Option<&mut SimulationSession>) -> FlyingStarChart {
     let period = get_period(construction_year);
     // Determine 24 Mountain for Facing and Sitting
     let (facing_sector, facing_mountain_idx, _) = get_24_mountain(degrees);
@@ -536,7 +712,7 @@ pub fn calculate_flying_star_chart(construction_year: i32, degrees: f64, current
     let sitting_label = format!("{} ({})", sitting_sector, get_mountain_name(&sitting_sector, sitting_mountain_idx));
 
     // 1. Fly Base Star (Period Star)
-    let base_chart = fly_stars(period, true, mutation);
+    let base_chart = fly_stars(period, true, mutation.as_deref_mut());
 
     let sector_map = |s: &str| match s {
         "Center" => 0, "NW" => 1, "W" => 2, "NE" => 3, "S" => 4,
@@ -548,11 +724,11 @@ pub fn calculate_flying_star_chart(construction_year: i32, degrees: f64, current
 
     // 2. Fly Mountain Star (Health)
     let mtn_flight_pol = get_flight_polarity(sit_base_star, sitting_mountain_idx);
-    let mtn_chart = fly_stars(sit_base_star, mtn_flight_pol, mutation);
+    let mtn_chart = fly_stars(sit_base_star, mtn_flight_pol, mutation.as_deref_mut());
 
     // 3. Fly Water Star (Wealth)
     let wtr_flight_pol = get_flight_polarity(face_base_star, facing_mountain_idx);
-    let wtr_chart = fly_stars(face_base_star, wtr_flight_pol, mutation);
+    let wtr_chart = fly_stars(face_base_star, wtr_flight_pol, mutation.as_deref_mut());
 
     // 4. Fly Annual Star (Time)
     let annual_star = calculate_annual_star(current_year);
@@ -580,7 +756,7 @@ pub fn calculate_flying_star_chart(construction_year: i32, degrees: f64, current
 }
 
 /// Calculates "Ti Gua" (Replacement Stars) if degrees are near a Void Line (Kung Wang).
-pub fn calculate_replacement_chart(construction_year: i32, degrees: f64, current_year: i32, mutation: Option<&SimulationSession>) -> Option<FlyingStarChart> {
+pub fn calculate_replacement_chart(construction_year: i32, degrees: f64, current_year: i32, mutation: Option<&mut SimulationSession>) -> Option<FlyingStarChart> {
     let d = degrees % 360.0;
     let mut needs_replacement = false;
     // Check boundaries of 24 Mountains
@@ -613,15 +789,23 @@ pub fn calculate_replacement_chart(construction_year: i32, degrees: f64, current
     None
 }
 
+/// Sector San Sha (Three Killings) occupies for a branch, by its San He frame (branch % 4), with its full name.
+const SAN_SHA: [(&str, &str); 4] = [("S", "South"), ("E", "East"), ("N", "North"), ("W", "West")];
+
+/// Weight of an affliction in each time layer: the year's lasts longest, the day's passes quickest.
+const LAYER_WEIGHTS: [(&str, f64); 3] = [("Year", 1.0), ("Month", 0.6), ("Day", 0.3)];
+
+/// Compass degree of a branch (Zi = North = 0°), where its Tai Sui sits.
+fn branch_degrees(branch: usize) -> f64 {
+    (branch % 12) as f64 * 30.0
+}
+
 /// Calculates annual afflictions: Tai Sui, Sui Po, San Sha.
 pub fn calculate_yearly_afflictions(year: i32, facing_deg: f64) -> Vec<String> {
     let mut afflictions = Vec::new();
-    let zodiac_idx = (year - 1900).rem_euclid(12);
+    let zodiac_idx = (year - 1900).rem_euclid(12) as usize;
     // Tai Sui (Grand Duke Jupiter) occupies the zodiac direction of the year
-    let tai_sui_deg = match zodiac_idx {
-        0 => 0.0, 1 => 30.0, 2 => 60.0, 3 => 90.0, 4 => 120.0, 5 => 150.0,
-        6 => 180.0, 7 => 210.0, 8 => 240.0, 9 => 270.0, 10 => 300.0, 11 => 330.0, _ => 0.0,
-    };
+    let tai_sui_deg = branch_degrees(zodiac_idx);
     let diff = (facing_deg - tai_sui_deg).abs();
     if diff < 15.0 || diff > 345.0 {
         afflictions.push(format!("Facing Tai Sui ({} deg): Avoid renovation.", tai_sui_deg));
@@ -633,15 +817,102 @@ pub fn calculate_yearly_afflictions(year: i32, facing_deg: f64) -> Vec<String> {
         afflictions.push("Facing Sui Po (Year Breaker): High risk if disturbed.".to_string());
     }
     // San Sha (Three Killings) depends on the Trinity (San He) frame
-    let san_sha_dir = match zodiac_idx % 4 {
-        0 => "South", 1 => "East", 2 => "North", 3 => "West", _ => "None",
-    };
+    let san_sha_dir = SAN_SHA[zodiac_idx % 4].1;
     afflictions.push(format!("San Sha (Three Killings) is in the {} this year.", san_sha_dir));
     afflictions
 }
 
+/// The year, month and day afflictions that share one palace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AfflictionOverlay {
+    pub sector: String,
+    /// e.g. "Year Tai Sui", "Month Five Yellow", "Day 2 Black".
+    pub afflictions: Vec<String>,
+    /// Sum of the afflictions, each weighted by its layer (year 1.0, month 0.6, day 0.3).
+    pub severity: f64,
+    /// True if the palace is the one the house faces.
+    pub facing: bool,
+    pub warning: String,
+}
+
+/// Overlays the Tai Sui, Sui Po, San Sha, Five Yellow and 2 Black of the year, month and day.
+///
+/// The branch afflictions follow the year branch, the solar month's branch and the day
+/// pillar's branch; the 5 and 2 are where each layer's visiting stars put them (`None`
+/// charts are skipped). Palaces are listed worst first; a palace hit by both the 5 and
+/// the 2 from different layers counts half again.
+pub fn calculate_date_afflictions(
+    year: i32,
+    month: u32,
+    day: u32,
+    facing_deg: f64,
+    annual: &FlyingStarChart,
+    monthly: Option<&FlyingStarChart>,
+    daily: Option<&FlyingStarChart>,
+) -> Vec<AfflictionOverlay> {
+    // Solar months begin early in the Gregorian month; February is the Tiger (Yin) month
+    let month_branch = month as usize % 12;
+    let day_branch = NaiveDate::from_ymd_opt(year, month, day).map(|d| day_pillar(d).1);
+    let layers = [
+        (Some((year - 1900).rem_euclid(12) as usize), Some(annual)),
+        (Some(month_branch), monthly),
+        (day_branch, daily),
+    ];
+
+    let mut hits: Vec<(String, String, f64, usize)> = Vec::new();
+    for (layer, ((branch, chart), (name, weight))) in layers.into_iter().zip(LAYER_WEIGHTS).enumerate() {
+        if let Some(branch) = branch {
+            let tai_sui = branch_degrees(branch);
+            hits.push((qi_flow::facing_sector(tai_sui).to_string(), format!("{} Tai Sui", name), weight, layer));
+            hits.push((qi_flow::facing_sector(tai_sui + 180.0).to_string(), format!("{} Sui Po", name), weight, layer));
+            hits.push((SAN_SHA[branch % 4].0.to_string(), format!("{} San Sha", name), weight, layer));
+        }
+        for p in chart.map(|c| c.palaces.as_slice()).unwrap_or_default() {
+            match p.visiting_star {
+                5 => hits.push((p.sector.clone(), format!("{} Five Yellow", name), weight, layer)),
+                2 => hits.push((p.sector.clone(), format!("{} 2 Black", name), 0.7 * weight, layer)),
+                _ => {}
+            }
+        }
+    }
+
+    let facing = qi_flow::facing_sector(facing_deg);
+    let mut overlays: Vec<AfflictionOverlay> = Vec::new();
+    for sector in floor_plan::GRID.iter().flatten() {
+        let here: Vec<_> = hits.iter().filter(|h| h.0 == *sector).collect();
+        if here.is_empty() {
+            continue;
+        }
+        let mut severity: f64 = here.iter().map(|h| h.2).sum();
+        let five = here.iter().find(|h| h.1.ends_with("Five Yellow")).map(|h| h.3);
+        let two = here.iter().find(|h| h.1.ends_with("2 Black")).map(|h| h.3);
+        let five_meets_two = matches!((five, two), (Some(a), Some(b)) if a != b);
+        if five_meets_two {
+            severity *= 1.5;
+        }
+        // Warn for as long as every layer involved lasts
+        let until = match here.iter().map(|h| h.3).max() {
+            Some(2) => "today",
+            Some(1) => "this month",
+            _ => "this year",
+        };
+        let afflictions: Vec<String> = here.iter().map(|h| h.1.clone()).collect();
+        let mut warning = format!("{}: {}.", sector, afflictions.join(" + "));
+        if five_meets_two {
+            warning.push_str(" The 5 Yellow and 2 Black meet here.");
+        }
+        if *sector == facing {
+            warning.push_str(" It is the facing palace; keep the main door calm and use another entrance if you can.");
+        }
+        warning.push_str(&format!(" Avoid renovation and groundbreaking here {}.", until));
+        overlays.push(AfflictionOverlay { sector: sector.to_string(), afflictions, severity, facing: *sector == facing, warning });
+    }
+    overlays.sort_by(|a, b| b.severity.total_cmp(&a.severity));
+    overlays
+}
+
 /// Calculates the Monthly Flying Star chart.
-pub fn calculate_monthly_chart(year: i32, month: u32, mutation: Option<&SimulationSession>) -> Option<FlyingStarChart> {
+pub fn calculate_monthly_chart(year: i32, month: u32, mutation: Option<&mut SimulationSession>) -> Option<FlyingStarChart> {
     let offset = (year - 1900).rem_euclid(12);
     // Base stars pattern for months (Tiger/Monkey/Snake/Pig years etc)
     let start_star = if [0, 6, 3, 9].contains(&offset) { 8 }
@@ -670,7 +941,7 @@ pub fn calculate_monthly_chart(year: i32, month: u32, mutation: Option<&Simulati
 /// Calculates the Daily Flying Star chart.
 ///
 /// Accounts for Yin/Yang cycles based on Winter/Summer Solstices.
-pub fn calculate_daily_chart(year: i32, month: u32, day: u32, mutation: Option<&SimulationSession>) -> Option<FlyingStarChart> {
+pub fn calculate_daily_chart(year: i32, month: u32, day: u32, mutation: Option<&mut SimulationSession>) -> Option<FlyingStarChart> {
     let d = NaiveDate::from_ymd_opt(year, month, day)?;
     let winter_solstice = NaiveDate::from_ymd_opt(year, 12, 21)?;
     let summer_solstice = NaiveDate::from_ymd_opt(year, 6, 21)?;
@@ -728,21 +999,6 @@ pub fn analyze_formations(chart: &FlyingStarChart) -> Vec<String> {
     formations
 }
 
-/// Generates actionable advice based on Stars + Kua + Quantum analysis.
-pub fn generate_advice(chart: &FlyingStarChart, kua: &Option<KuaProfile>, quantum: &QuantumAnalysis, formations: &Vec<String>) -> Vec<String> {
-    let mut advice = Vec::new();
-    // Period 9 Wealth Star is 9, Period 8 was 8
-    let wealth_star = if chart.period == 9 { 9 } else { 8 };
-    for p in &chart.palaces {
-        if p.water_star == wealth_star { advice.push(format!("Sector {} contains the Water Star {}, activating Wealth Luck.", p.sector, wealth_star)); }
-        if p.mountain_star == wealth_star { advice.push(format!("Sector {} contains the Mountain Star {}, good for Health/Relations.", p.sector, wealth_star)); }
-    }
-    if let Some(k) = kua { advice.push(format!("Your Life Gua is {}. Strongest direction: {}.", k.number, k.lucky_directions[0].0)); }
-    advice.push(format!("Quantum Focus: {}. Volatility: {:.2}", quantum.focus_sector, quantum.volatility_index));
-    if !formations.is_empty() { advice.push("Special Auspicious Formations detected! See report details.".to_string()); }
-    advice
-}
-
 // === UTILS ===
 
 /// Determines the Feng Shui Period (1-9) based on year.
@@ -772,18 +1028,17 @@ fn calculate_annual_star(year: i32) -> i32 {
 ///
 /// Moves numbers through the 9 sectors in a specific order: Center -> NW -> W -> NE -> S -> N -> SW -> E -> SE.
 /// If `mutation` is active, entropy can flip the flight direction or value.
-fn fly_stars(center_star: i32, forward: bool, mutation: Option<&SimulationSession>) -> Vec<i32> {
+fn fly_stars(center_star: i32, forward: bool, mut mutation: Option<&mut SimulationSession>) -> Vec<i32> {
     let mut chart = vec![0; 9];
     let mut current = center_star;
     let path = vec![0, 1, 2, 3, 4, 5, 6, 7, 8]; // Lo Shu path indices
     for &idx in &path {
         let mut val = current;
-        if let Some(session) = mutation {
+        if let Some(session) = mutation.as_deref_mut() {
              // Quantum check: does this star "mutate"?
-             let outcome = session.simulate_decision(&vec!["Normal".to_string(), "Mutate".to_string()], None, 10);
-             if outcome.winner == "Mutate" {
+             if session.next_bool(0.5) {
                  // Flip value up or down
-                 if session.simulate_decision(&vec!["+".to_string(), "-".to_string()], None, 5).winner == "+" { val += 1; } else { val -= 1; }
+                 if session.next_bool(0.5) { val += 1; } else { val -= 1; }
                  if val > 9 { val = 1; } if val < 1 { val = 9; }
              }
         }
@@ -2,9 +2,11 @@
 mod tests {
     use crate::tools::feng_shui::{
         calculate_kua_profile, calculate_flying_star_chart,
-        calculate_monthly_chart, calculate_daily_chart, analyze_formations
+        calculate_monthly_chart, calculate_daily_chart, analyze_formations,
+        calculate_date_afflictions,
     };
-    use crate::tools::feng_shui::FlyingStarChart;
+    use crate::tools::feng_shui::{generate_report, FlyingStarChart};
+    use crate::engine::EntropyRange;
 
     #[test]
     fn test_calculate_kua() {
@@ -56,6 +58,40 @@ mod tests {
         assert_eq!(chart_mar.period, 4);
     }
 
+    #[test]
+    fn test_date_afflictions_overlay_the_layers() {
+        // 2024 is the Dragon year (Tai Sui SE, San Sha S) with the 5 in W and the 2 in SE.
+        // March is the Rabbit month (Tai Sui E, Sui Po W, San Sha W) with the 5 in NW and the 2 in E.
+        let annual = calculate_flying_star_chart(2004, 270.0, 2024, None);
+        let monthly = calculate_monthly_chart(2024, 3, None).unwrap();
+        let overlays = calculate_date_afflictions(2024, 3, 10, 270.0, &annual, Some(&monthly), None);
+        let at = |sector: &str| overlays.iter().find(|o| o.sector == sector).unwrap();
+        let has = |sector: &str, affliction: &str| at(sector).afflictions.iter().any(|a| a == affliction);
+
+        assert!(has("SE", "Year Tai Sui") && has("SE", "Year 2 Black"));
+        assert!(has("NW", "Year Sui Po") && has("NW", "Month Five Yellow"));
+        assert!(has("E", "Month Tai Sui") && has("E", "Month 2 Black"));
+        assert!(has("W", "Year Five Yellow") && has("W", "Month Sui Po") && has("W", "Month San Sha"));
+        assert!(has("S", "Year San Sha"));
+        assert!(overlays.iter().all(|o| !o.afflictions.iter().any(|a| a.ends_with("Five Yellow") && a.starts_with("Day"))));
+
+        assert!(at("W").facing && at("W").warning.contains("facing palace"));
+        assert!(at("S").warning.contains("Avoid renovation and groundbreaking here"));
+        assert!(overlays.windows(2).all(|w| w[0].severity >= w[1].severity));
+    }
+
+    #[test]
+    fn test_five_yellow_meeting_two_black_is_worse() {
+        // October 2024 flies the month's 5 onto the year's 2 in the SE
+        let annual = calculate_flying_star_chart(2004, 0.0, 2024, None);
+        let monthly = calculate_monthly_chart(2024, 10, None).unwrap();
+        let overlays = calculate_date_afflictions(2024, 10, 1, 0.0, &annual, Some(&monthly), None);
+        let se = overlays.iter().find(|o| o.sector == "SE").unwrap();
+        assert!(se.warning.contains("The 5 Yellow and 2 Black meet here."));
+        let plain: f64 = 1.0 + 0.7 + 0.6; // Year Tai Sui, Year 2 Black, Month Five Yellow
+        assert!(se.severity >= plain * 1.5 - 1e-9);
+    }
+
     #[test]
     fn test_daily_chart_solstice() {
         // Winter Solstice 2023: Dec 22.
@@ -85,12 +121,12 @@ mod tests {
         use crate::tools::feng_shui::calculate_bazi;
 
          // Month 13 should return Err
-         let res = calculate_bazi(2024, 13, 1, 12, None);
+         let res = calculate_bazi(2024, 13, 1, 12, None, None, None);
          assert!(res.is_err());
          assert_eq!(res.unwrap_err().to_string(), "Invalid month: 13");
 
          // Feb 30 should return Err
-         let res2 = calculate_bazi(2024, 2, 30, 12, None);
+         let res2 = calculate_bazi(2024, 2, 30, 12, None, None, None);
          assert!(res2.is_err());
          assert_eq!(res2.unwrap_err().to_string(), "Invalid date: 2024-2-30");
     }
@@ -129,4 +165,65 @@ mod tests {
         // Base+Mountain = 3+2=5 != 10.
         assert!(!forms.iter().any(|f| f.contains("Sum of Ten (Mountain)")));
     }
+
+    fn batch_config(batch_id: i64) -> crate::tools::feng_shui::FengShuiConfig {
+        crate::tools::feng_shui::FengShuiConfig {
+            birth_year: Some(1985),
+            birth_month: Some(6),
+            birth_day: Some(15),
+            birth_hour: Some(10),
+            gender: Some("F".to_string()),
+            location: None,
+            construction_year: 2004,
+            facing_degrees: 180.0,
+            water_exit_degrees: None,
+            water_features: None,
+            current_year: Some(2024),
+            current_month: Some(3),
+            current_day: Some(1),
+            intention: None,
+            quantum_mode: true,
+            virtual_cures: None,
+            entropy_batch_id: Some(batch_id),
+            simulation_options: None,
+            rooms: None,
+            annual_years: None,
+            locale: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_replays_batch_and_records_rounds() {
+        let db = std::sync::Arc::new(crate::db::Db::in_memory().await.unwrap());
+        let batch = db.create_batch("stored", None).await.unwrap();
+        // 512-byte pulses, far more than one report draws
+        for round in 100..110u64 {
+            db.insert_entropy(batch, Some(round), &[round as u8; 512]).await.unwrap();
+        }
+
+        let first = generate_report(batch_config(batch), Some(db.clone())).await.unwrap();
+        assert_eq!(first.entropy_batch_id, Some(batch));
+        let consumed = first.entropy_ranges[0].end;
+        assert_eq!(first.entropy_ranges, vec![EntropyRange { start: 0, end: consumed }]);
+        let pulses_used = consumed.div_ceil(512) as i64;
+        assert_eq!(first.entropy_rounds, (100..100 + pulses_used).collect::<Vec<_>>());
+
+        // The same batch replays to the same reading
+        let second = generate_report(batch_config(batch), Some(db)).await.unwrap();
+        assert_eq!(second.quantum.focus_sector, first.quantum.focus_sector);
+        assert_eq!(second.entropy_rounds, first.entropy_rounds);
+    }
+
+    #[tokio::test]
+    async fn test_report_rejects_empty_or_exhausted_batch() {
+        let db = std::sync::Arc::new(crate::db::Db::in_memory().await.unwrap());
+        let empty = db.create_batch("empty", None).await.unwrap();
+        let err = generate_report(batch_config(empty), Some(db.clone())).await.unwrap_err();
+        assert!(err.to_string().contains("has no pulses"), "{}", err);
+
+        let small = db.create_batch("small", None).await.unwrap();
+        db.insert_entropy(small, Some(1), &[7u8; 64]).await.unwrap();
+        let err = generate_report(batch_config(small), Some(db)).await.unwrap_err();
+        assert!(err.to_string().contains("exhausted"), "{}", err);
+    }
 }
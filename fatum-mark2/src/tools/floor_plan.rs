@@ -0,0 +1,296 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::tools::feng_shui::{FlyingStarChart, KuaProfile, Palace};
+
+/// The 3x3 palace grid as `[row][col]`, South at the top.
+///
+/// Same orientation as the virtual cure and heatmap coordinates: `x` is the column and
+/// `y` the row, both 0.0-3.0.
+pub const GRID: [[&str; 3]; 3] = [
+    ["SE", "S", "SW"],
+    ["E", "Center", "W"],
+    ["NE", "N", "NW"],
+];
+
+/// A palace holding less of a room than this only gets advice if it is the room's largest.
+const MIN_SHARE: f64 = 0.15;
+
+/// What a room is used for; decides which stars matter there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoomKind {
+    Bedroom,
+    Kitchen,
+    /// The main entrance.
+    Door,
+    Bathroom,
+    Office,
+    Living,
+    #[serde(other)]
+    Other,
+}
+
+/// A room on the floor plan, drawn as a polygon or listed by the palaces it occupies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Room {
+    pub name: String,
+    pub kind: RoomKind,
+    /// Outline as `[x, y]` grid coordinates (0.0-3.0). Takes precedence over `sectors`.
+    #[serde(default)]
+    pub polygon: Option<Vec<[f64; 2]>>,
+    /// Palaces the room fills, e.g. `["N", "NE"]`, split evenly between them.
+    #[serde(default)]
+    pub sectors: Option<Vec<String>>,
+}
+
+/// How much of a room lies in one palace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PalaceShare {
+    pub sector: String,
+    /// Fraction of the room's area (0.0-1.0).
+    pub share: f64,
+}
+
+/// Room-specific reading of the palaces a room sits in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomAnalysis {
+    pub name: String,
+    pub kind: RoomKind,
+    /// Largest share first.
+    pub palaces: Vec<PalaceShare>,
+    /// Supportive findings minus harmful ones.
+    pub score: i32,
+    /// "favorable", "neutral" or "unfavorable".
+    pub rating: String,
+    pub advice: Vec<String>,
+}
+
+/// Grid cell `(row, col)` of a palace.
+pub fn sector_cell(sector: &str) -> Option<(usize, usize)> {
+    (0..3).flat_map(|r| (0..3).map(move |c| (r, c))).find(|&(r, c)| GRID[r][c] == sector)
+}
+
+/// Splits a room between the palaces it covers, largest share first.
+///
+/// Polygons are clipped to each cell, so parts drawn outside the grid are ignored.
+pub fn palace_shares(room: &Room) -> Result<Vec<PalaceShare>> {
+    let mut shares = Vec::new();
+    if let Some(polygon) = &room.polygon {
+        if polygon.len() < 3 {
+            bail!("needs at least 3 points");
+        }
+        for (r, row) in GRID.iter().enumerate() {
+            for (c, sector) in row.iter().enumerate() {
+                let area = area(&clip_to_cell(polygon, r as f64, c as f64));
+                if area > 1e-9 {
+                    shares.push(PalaceShare { sector: sector.to_string(), share: area });
+                }
+            }
+        }
+    } else if let Some(sectors) = &room.sectors {
+        for sector in sectors {
+            if sector_cell(sector).is_none() {
+                bail!("unknown sector {:?}", sector);
+            }
+            if !shares.iter().any(|s: &PalaceShare| &s.sector == sector) {
+                shares.push(PalaceShare { sector: sector.clone(), share: 1.0 });
+            }
+        }
+    } else {
+        bail!("needs a polygon or sectors");
+    }
+
+    let total: f64 = shares.iter().map(|s| s.share).sum();
+    if total <= 1e-9 {
+        bail!("does not cover any part of the grid");
+    }
+    for s in &mut shares {
+        s.share /= total;
+    }
+    shares.sort_by(|a, b| b.share.total_cmp(&a.share));
+    Ok(shares)
+}
+
+/// Sutherland-Hodgman clip of a polygon to the unit cell at `(row, col)`.
+fn clip_to_cell(polygon: &[[f64; 2]], row: f64, col: f64) -> Vec<[f64; 2]> {
+    // Each edge keeps the points where `inside` holds: (axis, bound, keep values above it)
+    let edges = [(0, col, true), (0, col + 1.0, false), (1, row, true), (1, row + 1.0, false)];
+    let mut points = polygon.to_vec();
+    for (axis, bound, above) in edges {
+        let inside = |p: &[f64; 2]| if above { p[axis] >= bound } else { p[axis] <= bound };
+        let input = std::mem::take(&mut points);
+        for (i, cur) in input.iter().enumerate() {
+            let prev = &input[(i + input.len() - 1) % input.len()];
+            if inside(cur) != inside(prev) {
+                let t = (bound - prev[axis]) / (cur[axis] - prev[axis]);
+                points.push([prev[0] + t * (cur[0] - prev[0]), prev[1] + t * (cur[1] - prev[1])]);
+            }
+            if inside(cur) {
+                points.push(*cur);
+            }
+        }
+        if points.is_empty() {
+            break;
+        }
+    }
+    points
+}
+
+/// Shoelace area (always positive).
+fn area(points: &[[f64; 2]]) -> f64 {
+    let n = points.len();
+    if n < 3 {
+        return 0.0;
+    }
+    let twice: f64 = (0..n).map(|i| {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        a[0] * b[1] - b[0] * a[1]
+    }).sum();
+    twice.abs() / 2.0
+}
+
+pub(crate) fn star_name(star: i32) -> &'static str {
+    match star {
+        1 => "1 White (Career)",
+        2 => "2 Black (Sickness)",
+        3 => "3 Jade (Quarrels)",
+        4 => "4 Green (Study)",
+        5 => "5 Yellow (Misfortune)",
+        6 => "6 White (Authority)",
+        7 => "7 Red (Loss)",
+        8 => "8 White (Wealth)",
+        _ => "9 Purple (Celebration)",
+    }
+}
+
+/// The period star and the one after it carry prosperous Qi (8 and 9 in Period 8, 9 and 1 in Period 9).
+pub(crate) fn is_timely(star: i32, period: i32) -> bool {
+    star == period || star == period % 9 + 1
+}
+
+pub(crate) fn is_afflicted(star: i32) -> bool {
+    star == 2 || star == 5
+}
+
+/// `(score, advice)` findings for one kind of room in one palace.
+fn palace_rules(kind: RoomKind, p: &Palace, period: i32, kua: Option<&KuaProfile>) -> Vec<(i32, String)> {
+    let (m, w, v) = (p.mountain_star, p.water_star, p.visiting_star);
+    let lucky = kua.and_then(|k| k.lucky_directions.iter().find(|(dir, _)| *dir == p.sector)).map(|(_, name)| name.as_str());
+    let mut rules = Vec::new();
+
+    match kind {
+        RoomKind::Bedroom => {
+            // Sleep is governed by the Mountain (health/relationships) star
+            if is_timely(m, period) {
+                rules.push((1, format!("Mountain Star {} supports rest, health and relationships.", star_name(m))));
+            }
+            if is_afflicted(m) {
+                rules.push((-1, format!("Mountain Star {} drains health here; hang a metal Wu Lou or move the bed.", star_name(m))));
+            }
+            if m == 3 {
+                rules.push((-1, "Mountain Star 3 stirs arguments between partners; calm it with red or fire tones.".to_string()));
+            }
+            if let Some(name) = lucky {
+                rules.push((1, format!("Lies in your {} direction; sleep with your head pointing {}.", name, p.sector)));
+            }
+        }
+        RoomKind::Kitchen => {
+            // The stove burns whatever Qi sits under it
+            if is_timely(w, period) {
+                rules.push((-1, format!("The stove burns the wealth of Water Star {}; keep fire away from this palace.", star_name(w))));
+            }
+            if is_afflicted(w) || is_afflicted(m) {
+                rules.push((-1, "Fire feeds the Earth stars 2 and 5; add metal (brass, copper) near the stove.".to_string()));
+            }
+            match lucky {
+                Some(name) => rules.push((-1, format!("A kitchen suppresses your {} direction; it is better placed in an inauspicious sector.", name))),
+                None if kua.is_some() => rules.push((1, "Sits in one of your inauspicious directions, where a stove presses bad luck down.".to_string())),
+                None => {}
+            }
+        }
+        RoomKind::Door => {
+            // Qi enters through the door, so the Water star rules here
+            if is_timely(w, period) {
+                rules.push((1, format!("Water Star {} at the door draws in prosperous Qi; keep the entrance clear and bright.", star_name(w))));
+            }
+            if is_afflicted(w) {
+                rules.push((-1, format!("Water Star {} at the door lets in misfortune; place a metal cure by the entrance.", star_name(w))));
+            }
+            if w == 3 {
+                rules.push((-1, "Water Star 3 at the door brings disputes and legal trouble; use red accents.".to_string()));
+            }
+            if w == 7 && period >= 8 {
+                rules.push((-1, "Water Star 7 at the door risks theft and loss; place still water nearby.".to_string()));
+            }
+        }
+        RoomKind::Bathroom => {
+            // Drains flush away whatever Qi is there, good or bad
+            if is_timely(w, period) {
+                rules.push((-1, format!("Drains flush away the wealth of Water Star {}; keep the lid and door closed.", star_name(w))));
+            }
+            if is_afflicted(w) || is_afflicted(m) {
+                rules.push((1, "A good use of an afflicted palace: the drains carry its sickness and misfortune away.".to_string()));
+            }
+        }
+        RoomKind::Office => {
+            if m == 4 || w == 4 {
+                rules.push((1, "Star 4 favours study, writing and exams.".to_string()));
+            }
+            if is_timely(w, period) {
+                rules.push((1, format!("Water Star {} supports career and income; face the desk toward {}.", star_name(w), p.sector)));
+            }
+            if w == 3 {
+                rules.push((-1, "Water Star 3 brings office politics and disputes; avoid meetings here.".to_string()));
+            }
+            if is_afflicted(w) || is_afflicted(m) {
+                rules.push((-1, "An afflicted star saps focus; place a metal cure by the desk.".to_string()));
+            }
+        }
+        RoomKind::Living | RoomKind::Other => {
+            if is_timely(w, period) {
+                rules.push((1, format!("Water Star {} is prosperous; activity here stirs up wealth.", star_name(w))));
+            }
+            if is_afflicted(w) || is_afflicted(m) {
+                rules.push((-1, "An afflicted star sits here; keep it quiet and place a metal cure.".to_string()));
+            }
+        }
+    }
+
+    // The annual star passes through every room, but drains and stoves already deal with it
+    if is_afflicted(v) && !matches!(kind, RoomKind::Bathroom | RoomKind::Kitchen) {
+        rules.push((-1, format!("This year's visiting {} calls for no renovation or digging here.", star_name(v))));
+    }
+    rules
+}
+
+/// Maps each room to its palaces and applies that room type's rules to their stars.
+pub fn analyze_rooms(rooms: &[Room], chart: &FlyingStarChart, kua: Option<&KuaProfile>) -> Result<Vec<RoomAnalysis>> {
+    rooms.iter().map(|room| {
+        let palaces = palace_shares(room).map_err(|e| anyhow::anyhow!("Room {:?} {}", room.name, e))?;
+        // The largest palace always counts; small slivers of others do not
+        let considered: Vec<&PalaceShare> = palaces.iter().enumerate()
+            .filter(|(i, s)| *i == 0 || s.share >= MIN_SHARE)
+            .map(|(_, s)| s)
+            .collect();
+
+        let (mut score, mut advice) = (0, Vec::new());
+        for share in &considered {
+            let Some(palace) = chart.palaces.iter().find(|p| p.sector == share.sector) else { continue };
+            for (delta, text) in palace_rules(room.kind, palace, chart.period, kua) {
+                score += delta;
+                advice.push(if considered.len() > 1 { format!("{}: {}", palace.sector, text) } else { text });
+            }
+        }
+        let rating = match score {
+            s if s > 0 => "favorable",
+            0 => "neutral",
+            _ => "unfavorable",
+        };
+        Ok(RoomAnalysis { name: room.name.clone(), kind: room.kind, palaces, score, rating: rating.to_string(), advice })
+    }).collect()
+}
+
+#[cfg(test)]
+#[path = "floor_plan_tests.rs"]
+mod tests;
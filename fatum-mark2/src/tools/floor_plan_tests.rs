@@ -0,0 +1,68 @@
+use super::{analyze_rooms, palace_shares, Room, RoomKind};
+use crate::tools::test_fixtures::chart;
+
+fn room(kind: RoomKind, polygon: Option<Vec<[f64; 2]>>, sectors: Option<&[&str]>) -> Room {
+    Room {
+        name: format!("{:?}", kind),
+        kind,
+        polygon,
+        sectors: sectors.map(|s| s.iter().map(|s| s.to_string()).collect()),
+    }
+}
+
+#[test]
+fn test_polygon_is_split_by_area() {
+    // 1.5 x 1 rectangle across the top-left: 2/3 in SE, 1/3 in S
+    let r = room(RoomKind::Living, Some(vec![[0.0, 0.0], [1.5, 0.0], [1.5, 1.0], [0.0, 1.0]]), None);
+    let shares = palace_shares(&r).unwrap();
+    assert_eq!(shares.iter().map(|s| s.sector.as_str()).collect::<Vec<_>>(), vec!["SE", "S"]);
+    assert!((shares[0].share - 2.0 / 3.0).abs() < 1e-9);
+    assert!((shares[1].share - 1.0 / 3.0).abs() < 1e-9);
+
+    // A diamond around the corner SE/S/E/Center meet at, listed clockwise: a quarter each
+    let r = room(RoomKind::Living, Some(vec![[1.0, 0.5], [0.5, 1.0], [1.0, 1.5], [1.5, 1.0]]), None);
+    let shares = palace_shares(&r).unwrap();
+    let mut sectors: Vec<&str> = shares.iter().map(|s| s.sector.as_str()).collect();
+    sectors.sort();
+    assert_eq!(sectors, vec!["Center", "E", "S", "SE"]);
+    assert!(shares.iter().all(|s| (s.share - 0.25).abs() < 1e-9));
+}
+
+#[test]
+fn test_sector_lists_split_evenly_and_reject_bad_input() {
+    let shares = palace_shares(&room(RoomKind::Office, None, Some(&["N", "NE", "N"]))).unwrap();
+    assert_eq!(shares.len(), 2, "duplicates are merged");
+    assert!(shares.iter().all(|s| (s.share - 0.5).abs() < 1e-9));
+
+    assert!(palace_shares(&room(RoomKind::Office, None, Some(&["North"]))).is_err());
+    assert!(palace_shares(&room(RoomKind::Office, None, None)).is_err());
+    assert!(palace_shares(&room(RoomKind::Office, Some(vec![[0.0, 0.0], [1.0, 1.0]]), None)).is_err());
+    // Entirely off the grid
+    assert!(palace_shares(&room(RoomKind::Office, Some(vec![[4.0, 4.0], [5.0, 4.0], [5.0, 5.0]]), None)).is_err());
+}
+
+#[test]
+fn test_room_rules_depend_on_room_kind() {
+    // N holds the prosperous Water Star 9; SW holds Mountain Star 2
+    let chart = chart(&[("N", 6, 9, 6), ("SW", 2, 6, 6)]);
+    let rooms = vec![
+        room(RoomKind::Door, None, Some(&["N"])),
+        room(RoomKind::Kitchen, None, Some(&["N"])),
+        room(RoomKind::Bedroom, None, Some(&["SW"])),
+        room(RoomKind::Bathroom, None, Some(&["SW"])),
+    ];
+    let analysis = analyze_rooms(&rooms, &chart, None).unwrap();
+    let ratings: Vec<&str> = analysis.iter().map(|a| a.rating.as_str()).collect();
+    assert_eq!(ratings, vec!["favorable", "unfavorable", "unfavorable", "favorable"]);
+    assert!(analysis[1].advice[0].contains("burns the wealth"));
+    assert!(analysis[2].advice.iter().any(|a| a.contains("Wu Lou")));
+}
+
+#[test]
+fn test_multi_palace_rooms_label_each_palace() {
+    let chart = chart(&[("N", 6, 9, 6), ("NE", 6, 5, 6)]);
+    let analysis = analyze_rooms(&[room(RoomKind::Door, None, Some(&["N", "NE"]))], &chart, None).unwrap();
+    assert_eq!(analysis[0].score, 0);
+    assert!(analysis[0].advice.iter().any(|a| a.starts_with("N: ")));
+    assert!(analysis[0].advice.iter().any(|a| a.starts_with("NE: ")));
+}
@@ -0,0 +1,633 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::f64::consts::TAU;
+
+use crate::engine::{EntropyRange, SimulationSession};
+use crate::engine::stats::chi_square_sf;
+use crate::services::intention::IntentionBinding;
+
+/// Mean Earth radius (IUGG), in metres.
+pub const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+pub const DEFAULT_POINTS: usize = 2000;
+
+/// Pool bytes behind each point: an 8-byte draw for the distance and one for the bearing.
+pub const BYTES_PER_POINT: usize = 16;
+
+/// Attractors and voids reported per scan.
+const HOTSPOTS: usize = 3;
+
+/// Radius of the circle hotspots are ranked over, as a fraction of the scan radius.
+const SEARCH_FRACTION: f64 = 0.1;
+
+/// Circle radii tried for each hotspot's extent, in quarters of the search radius.
+const RADIUS_STEPS: usize = 12;
+
+/// Grid cells per search radius; the grid both buckets the points and samples the density surface.
+const CELLS_PER_SEARCH: f64 = 2.0;
+
+/// Mean-shift steps that walk an attractor from its cell centre onto the local peak.
+const MEAN_SHIFT_STEPS: usize = 8;
+
+/// Redraws allowed per point before the exclusion zones are judged to cover the scan area.
+const MAX_REDRAWS: usize = 256;
+
+/// Lattice samples per side of the disc for measuring the area left by exclusion zones.
+const ALLOWED_LATTICE: usize = 200;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GeolocationConfig {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Scan radius in metres.
+    pub radius_m: f64,
+    /// Points to scatter; `DEFAULT_POINTS` if omitted.
+    #[serde(default)]
+    pub points: Option<usize>,
+    #[serde(default)]
+    pub entropy_batch_id: Option<i64>,
+    /// Refuse the scan when the pool cannot cover every point, instead of finishing on the PRNG.
+    #[serde(default)]
+    pub strict_quantum: bool,
+    /// No-go zones (lakes, highways, private land) no point may land in.
+    #[serde(default)]
+    pub exclusions: Vec<ExclusionZone>,
+    /// What the outing is for; bound into the entropy before the scatter.
+    #[serde(default)]
+    pub intention: Option<String>,
+}
+
+/// A polygon points must stay out of, widened by `buffer_m`. Two vertices with a buffer make
+/// a corridor, e.g. along a road.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExclusionZone {
+    #[serde(default)]
+    pub label: Option<String>,
+    pub polygon: Vec<GeoPoint>,
+    #[serde(default)]
+    pub buffer_m: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// A densest (attractor) or emptiest (void) spot in the point cloud.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hotspot {
+    pub point: GeoPoint,
+    /// Extent of the spot: the circle with the most points in excess of (or, for a void,
+    /// short of) a uniform scatter.
+    pub radius_m: f64,
+    /// Generated points within `radius_m`.
+    pub count: usize,
+    /// Points a uniform scatter would put within `radius_m`.
+    pub expected: f64,
+    /// Z-score of `count` against `expected`: positive for attractors, negative for voids.
+    pub power: f64,
+    pub distance_m: f64,
+    /// Degrees clockwise from north, seen from the centre.
+    pub bearing_deg: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeolocationReport {
+    pub center: GeoPoint,
+    pub radius_m: f64,
+    /// Radius of the circle the hotspots are ranked over.
+    pub search_radius_m: f64,
+    /// Densest first.
+    pub attractors: Vec<Hotspot>,
+    /// Emptiest first.
+    pub voids: Vec<Hotspot>,
+    /// Side of a density surface cell, in metres.
+    pub cell_m: f64,
+    /// The density surface for a heatmap: one sample per grid cell centred inside the disc.
+    pub density: Vec<DensityCell>,
+    pub points: Vec<GeoPoint>,
+    /// Pool bytes the scatter consumed.
+    #[serde(default)]
+    pub entropy_ranges: Vec<EntropyRange>,
+    /// Draws served by the fallback PRNG after the pool ran out.
+    #[serde(default)]
+    pub fallback_draws: usize,
+    #[serde(default)]
+    pub exclusions: Vec<ExclusionZone>,
+    /// Points that landed in an exclusion zone and were drawn again.
+    #[serde(default)]
+    pub redrawn: usize,
+    /// The intention bound into the entropy; set by the caller that bound it.
+    #[serde(default)]
+    pub intention: Option<IntentionBinding>,
+}
+
+/// One heatmap sample: the points within the search radius of a grid cell's centre.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DensityCell {
+    pub point: GeoPoint,
+    pub count: usize,
+    /// Z-score against a uniform scatter, as for `Hotspot::power`.
+    pub power: f64,
+}
+
+/// Great-circle destination from `from` after `distance_m` on `bearing` (radians from north).
+pub fn destination(from: GeoPoint, bearing: f64, distance_m: f64) -> GeoPoint {
+    let delta = distance_m / EARTH_RADIUS_M;
+    let (lat1, lon1) = (from.latitude.to_radians(), from.longitude.to_radians());
+    let lat2 = (lat1.sin() * delta.cos() + lat1.cos() * delta.sin() * bearing.cos()).asin();
+    let lon2 = lon1 + (bearing.sin() * delta.sin() * lat1.cos()).atan2(delta.cos() - lat1.sin() * lat2.sin());
+    GeoPoint {
+        latitude: lat2.to_degrees(),
+        longitude: (lon2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0,
+    }
+}
+
+/// Haversine distance in metres.
+pub fn distance_m(a: GeoPoint, b: GeoPoint) -> f64 {
+    let (lat1, lat2) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.longitude - a.longitude).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * h.sqrt().min(1.0).asin()
+}
+
+/// Initial great-circle bearing from `a` to `b`, radians from north.
+fn bearing(a: GeoPoint, b: GeoPoint) -> f64 {
+    let (lat1, lat2) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let dlon = (b.longitude - a.longitude).to_radians();
+    (dlon.sin() * lat2.cos()).atan2(lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos())
+}
+
+/// Offset from the centre in metres, east and north.
+type Offset = (f64, f64);
+
+/// Where `p` falls in the scan's offset plane: the inverse of `destination` from `center`.
+fn offset_of(center: GeoPoint, p: GeoPoint) -> Offset {
+    let (d, b) = (distance_m(center, p), bearing(center, p));
+    (d * b.sin(), d * b.cos())
+}
+
+/// An exclusion zone moved into the offset plane.
+struct Mask {
+    vertices: Vec<Offset>,
+    buffer: f64,
+}
+
+impl Mask {
+    fn new(center: GeoPoint, zone: &ExclusionZone) -> Self {
+        Mask { vertices: zone.polygon.iter().map(|&p| offset_of(center, p)).collect(), buffer: zone.buffer_m.max(0.0) }
+    }
+
+    fn covers(&self, p: Offset) -> bool {
+        let n = self.vertices.len();
+        // A closed ring for a polygon; a single segment (or point) otherwise
+        let edges = self.vertices.iter().zip(self.vertices.iter().cycle().skip(1))
+            .take(if n >= 3 { n } else { 1 });
+        // Even-odd ray cast to the east
+        let inside = n >= 3 && edges.clone()
+            .filter(|(a, b)| (a.1 > p.1) != (b.1 > p.1) && p.0 < a.0 + (p.1 - a.1) * (b.0 - a.0) / (b.1 - a.1))
+            .count() % 2 == 1;
+        inside || (self.buffer > 0.0 && edges.into_iter().any(|(&a, &b)| segment_distance(p, a, b) <= self.buffer))
+    }
+}
+
+fn segment_distance(p: Offset, a: Offset, b: Offset) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0.0 { (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0) } else { 0.0 };
+    (p.0 - a.0 - t * dx).hypot(p.1 - a.1 - t * dy)
+}
+
+fn within(a: Offset, b: Offset, r: f64) -> bool {
+    (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) <= r * r
+}
+
+/// Share of the scan disc (radius `big_r`) covered by a circle of radius `r` whose centre is
+/// `d` from the disc's centre: the circle-circle lens over the disc's area.
+pub fn coverage(d: f64, r: f64, big_r: f64) -> f64 {
+    if d + r <= big_r {
+        return (r / big_r).powi(2);
+    }
+    if d >= big_r + r {
+        return 0.0;
+    }
+    if d + big_r <= r {
+        return 1.0;
+    }
+    let a = r * r * ((d * d + r * r - big_r * big_r) / (2.0 * d * r)).clamp(-1.0, 1.0).acos();
+    let b = big_r * big_r * ((d * d + big_r * big_r - r * r) / (2.0 * d * big_r)).clamp(-1.0, 1.0).acos();
+    let c = 0.5 * ((-d + r + big_r) * (d + r - big_r) * (d - r + big_r) * (d + r + big_r)).max(0.0).sqrt();
+    (a + b - c) / (std::f64::consts::PI * big_r * big_r)
+}
+
+/// The points bucketed on a square grid over the disc, so a circle count only visits the
+/// cells the circle overlaps.
+struct PointGrid {
+    radius: f64,
+    cell: f64,
+    cols: usize,
+    /// Where each cell's run starts in `points`, row-major from the south-west, plus an end marker.
+    starts: Vec<usize>,
+    points: Vec<Offset>,
+    /// A lattice over the part of the disc outside the exclusion zones, when there are any;
+    /// uniform expectations are then shares of this lattice rather than of the whole disc.
+    allowed: Option<Box<PointGrid>>,
+}
+
+impl PointGrid {
+    fn new(offsets: &[Offset], radius: f64, cell: f64) -> Self {
+        let cols = ((2.0 * radius / cell).ceil() as usize).max(1);
+        let mut grid = PointGrid {
+            radius,
+            cell: 2.0 * radius / cols as f64,
+            cols,
+            starts: vec![0; cols * cols + 1],
+            points: Vec::new(),
+            allowed: None,
+        };
+
+        // Counting sort by cell
+        let cells: Vec<usize> = offsets.iter().map(|p| grid.col(p.1) * cols + grid.col(p.0)).collect();
+        for &c in &cells {
+            grid.starts[c + 1] += 1;
+        }
+        for i in 0..cols * cols {
+            grid.starts[i + 1] += grid.starts[i];
+        }
+        let mut next = grid.starts.clone();
+        grid.points = vec![(0.0, 0.0); offsets.len()];
+        for (&c, &p) in cells.iter().zip(offsets) {
+            grid.points[next[c]] = p;
+            next[c] += 1;
+        }
+        grid
+    }
+
+    /// Column (or row) holding coordinate `v`, clamped to the grid.
+    fn col(&self, v: f64) -> usize {
+        (((v + self.radius) / self.cell).floor().max(0.0) as usize).min(self.cols - 1)
+    }
+
+    fn centre(&self, col: usize, row: usize) -> Offset {
+        (-self.radius + (col as f64 + 0.5) * self.cell, -self.radius + (row as f64 + 0.5) * self.cell)
+    }
+
+    /// Points in the cells overlapping the square around a circle; callers filter to the circle.
+    fn near(&self, at: Offset, r: f64) -> impl Iterator<Item = &Offset> {
+        let (c0, c1) = (self.col(at.0 - r), self.col(at.0 + r));
+        (self.col(at.1 - r)..=self.col(at.1 + r)).flat_map(move |row| {
+            // A row's cells are contiguous, so the span is one slice
+            &self.points[self.starts[row * self.cols + c0]..self.starts[row * self.cols + c1 + 1]]
+        })
+    }
+
+    fn count(&self, at: Offset, r: f64) -> usize {
+        self.near(at, r).filter(|&&p| within(p, at, r)).count()
+    }
+
+    fn nearest(&self, at: Offset) -> Option<Offset> {
+        let dist = |p: &Offset| (p.0 - at.0).hypot(p.1 - at.1);
+        self.points.iter().copied().min_by(|a, b| dist(a).total_cmp(&dist(b)))
+    }
+
+    /// Moves `at` to the centroid of the points within `r`, repeatedly, until it settles on a
+    /// local density peak.
+    fn mean_shift(&self, mut at: Offset, r: f64) -> Offset {
+        for _ in 0..MEAN_SHIFT_STEPS {
+            let (x, y, k) = self.near(at, r)
+                .filter(|&&p| within(p, at, r))
+                .fold((0.0, 0.0, 0usize), |(x, y, k), p| (x + p.0, y + p.1, k + 1));
+            if k == 0 {
+                break;
+            }
+            let next = (x / k as f64, y / k as f64);
+            let settled = within(next, at, r * 1e-3);
+            at = next;
+            if settled {
+                break;
+            }
+        }
+        at
+    }
+
+    /// A circle's point count against a uniform scatter over the disc: (count, expected, z-score).
+    ///
+    /// A circle centred on a generated point (`on_point`) always holds that point, so only the
+    /// other points are binomial draws.
+    fn density(&self, at: Offset, r: f64, on_point: bool) -> (usize, f64, f64) {
+        let f = match &self.allowed {
+            Some(lattice) => lattice.count(at, r) as f64 / lattice.points.len() as f64,
+            None => coverage(at.0.hypot(at.1), r, self.radius),
+        };
+        let others = (self.points.len() - on_point as usize) as f64;
+        let count = self.count(at, r);
+        let expected = on_point as u8 as f64 + others * f;
+        let sd = (others * f * (1.0 - f)).sqrt();
+        let z = if sd > 0.0 { (count as f64 - expected) / sd } else { 0.0 };
+        (count, expected, z)
+    }
+}
+
+/// Grows the circle around a picked spot in quarter steps of the search radius and keeps the
+/// one with the largest surplus of points (`sign` 1) or shortfall (`sign` -1).
+fn hotspot(center: GeoPoint, grid: &PointGrid, search: f64, at: Offset, on_point: bool, sign: f64) -> Hotspot {
+    let (r, (count, expected, power)) = (1..=RADIUS_STEPS)
+        .map(|k| {
+            let r = search * k as f64 / 4.0;
+            (r, grid.density(at, r, on_point))
+        })
+        .max_by(|a, b| (sign * (a.1.0 as f64 - a.1.1)).total_cmp(&(sign * (b.1.0 as f64 - b.1.1))))
+        .unwrap();
+    let distance = at.0.hypot(at.1);
+    let bearing = at.0.atan2(at.1);
+    Hotspot {
+        point: destination(center, bearing, distance),
+        radius_m: r,
+        count,
+        expected,
+        power,
+        distance_m: distance,
+        bearing_deg: bearing.to_degrees().rem_euclid(360.0),
+    }
+}
+
+/// Takes candidates in order, skipping any within `2 * search` of one already taken.
+fn pick_separated(ranked: Vec<(Offset, f64)>, search: f64) -> Vec<Offset> {
+    let mut picked: Vec<Offset> = Vec::new();
+    for (at, _) in ranked {
+        if picked.len() == HOTSPOTS {
+            break;
+        }
+        if picked.iter().all(|&p| !within(p, at, 2.0 * search)) {
+            picked.push(at);
+        }
+    }
+    picked
+}
+
+fn to_point(center: GeoPoint, at: Offset) -> GeoPoint {
+    destination(center, at.0.atan2(at.1), at.0.hypot(at.1))
+}
+
+pub struct GeolocationTool;
+
+impl GeolocationTool {
+    /// Scatters points uniformly over the disc around the centre and finds where they bunch up
+    /// (attractors) and where they leave gaps (voids), each with its extent and its power
+    /// against what a uniform scatter would give.
+    ///
+    /// Densities are sampled on a grid of cells half the search radius across rather than at
+    /// every point, so the cost grows linearly with the point count.
+    pub fn generate_location(session: &mut SimulationSession, config: &GeolocationConfig) -> Result<GeolocationReport> {
+        let n = config.points.unwrap_or(DEFAULT_POINTS);
+        if n == 0 {
+            bail!("At least one point is required");
+        }
+        if !config.radius_m.is_finite() || config.radius_m <= 0.0 {
+            bail!("Radius must be positive");
+        }
+        if config.strict_quantum && session.remaining_pool_bytes() < n * BYTES_PER_POINT {
+            bail!(
+                "{} points need {} entropy bytes but only {} are left; strict quantum mode does not fall back to the PRNG",
+                n, n * BYTES_PER_POINT, session.remaining_pool_bytes()
+            );
+        }
+        let center = GeoPoint { latitude: config.latitude, longitude: config.longitude };
+        let radius = config.radius_m;
+        let search = radius * SEARCH_FRACTION;
+
+        let masks: Vec<Mask> = config.exclusions.iter().map(|zone| Mask::new(center, zone)).collect();
+        let masked = |p: Offset| masks.iter().any(|m| m.covers(p));
+
+        // 1. Uniform over the disc straight from the pool: the square root keeps the outer rings
+        // as dense as the middle. A point in an exclusion zone is drawn again from the next bytes,
+        // so the same pool always gives the same scatter.
+        let (start, fallbacks) = (session.pool_index, session.fallback_draws);
+        let mut redrawn = 0;
+        let mut offsets: Vec<Offset> = Vec::with_capacity(n);
+        for _ in 0..n {
+            let mut attempts = 0;
+            let point = loop {
+                let distance = radius * session.next_f64().sqrt();
+                let bearing = TAU * session.next_f64();
+                let point = (distance * bearing.sin(), distance * bearing.cos());
+                if !masked(point) {
+                    break point;
+                }
+                attempts += 1;
+                if attempts == MAX_REDRAWS {
+                    bail!("Exclusion zones cover almost all of the scan area; {} draws in a row were masked", MAX_REDRAWS);
+                }
+            };
+            redrawn += attempts;
+            offsets.push(point);
+        }
+        if config.strict_quantum && session.fallback_draws > fallbacks {
+            bail!(
+                "Entropy ran out after {} bytes while redrawing masked points; strict quantum mode does not fall back to the PRNG",
+                session.pool_index - start
+            );
+        }
+        let entropy_ranges = if session.pool_index > start {
+            vec![EntropyRange { start, end: session.pool_index }]
+        } else {
+            vec![]
+        };
+        let mut grid = PointGrid::new(&offsets, radius, search / CELLS_PER_SEARCH);
+        if !masks.is_empty() {
+            let step = 2.0 * radius / ALLOWED_LATTICE as f64;
+            let lattice: Vec<Offset> = (0..ALLOWED_LATTICE * ALLOWED_LATTICE)
+                .map(|i| (-radius + ((i % ALLOWED_LATTICE) as f64 + 0.5) * step, -radius + ((i / ALLOWED_LATTICE) as f64 + 0.5) * step))
+                .filter(|&p| within(p, (0.0, 0.0), radius) && !masked(p))
+                .collect();
+            if lattice.is_empty() {
+                bail!("Exclusion zones cover the whole scan area");
+            }
+            grid.allowed = Some(Box::new(PointGrid::new(&lattice, radius, grid.cell)));
+        }
+
+        // 2. Density surface at every cell centre inside the disc
+        let surface: Vec<(Offset, usize, f64)> = (0..grid.cols * grid.cols)
+            .map(|i| grid.centre(i % grid.cols, i / grid.cols))
+            .filter(|&at| within(at, (0.0, 0.0), radius))
+            .map(|at| {
+                let (count, _, z) = grid.density(at, search, false);
+                (at, count, z)
+            })
+            .collect();
+
+        // 3. Attractors: the strongest cells, each walked onto the peak of its cluster; a peak
+        // that settles in an exclusion zone moves to the nearest point, which never does
+        let mut dense: Vec<(Offset, f64)> = surface.iter().map(|&(at, _, z)| (at, z)).collect();
+        dense.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let mut attractors: Vec<Hotspot> = pick_separated(dense, search).into_iter().map(|cell| {
+            let peak = grid.mean_shift(cell, search);
+            let peak = if masked(peak) { grid.nearest(peak).unwrap_or(peak) } else { peak };
+            hotspot(center, &grid, search, peak, false, 1.0)
+        }).collect();
+        attractors.sort_by(|a, b| b.power.total_cmp(&a.power));
+
+        // 4. Voids: open cells whose whole search circle lies inside the disc, emptiest first
+        let mut sparse: Vec<(Offset, f64)> = surface.iter()
+            .filter(|&&(at, _, _)| within(at, (0.0, 0.0), radius - search) && !masked(at))
+            .map(|&(at, _, z)| (at, z))
+            .collect();
+        sparse.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let mut voids: Vec<Hotspot> = pick_separated(sparse, search).into_iter()
+            .map(|at| hotspot(center, &grid, search, at, false, -1.0))
+            .collect();
+        voids.sort_by(|a, b| a.power.total_cmp(&b.power));
+
+        Ok(GeolocationReport {
+            center,
+            radius_m: radius,
+            search_radius_m: search,
+            attractors,
+            voids,
+            cell_m: grid.cell,
+            density: surface.into_iter()
+                .map(|(at, count, power)| DensityCell { point: to_point(center, at), count, power })
+                .collect(),
+            points: offsets.into_iter().map(|at| to_point(center, at)).collect(),
+            entropy_ranges,
+            fallback_draws: session.fallback_draws - fallbacks,
+            exclusions: config.exclusions.clone(),
+            redrawn,
+            intention: None,
+        })
+    }
+}
+
+fn feature(point: GeoPoint, properties: serde_json::Value) -> serde_json::Value {
+    json!({
+        "type": "Feature",
+        "geometry": { "type": "Point", "coordinates": [point.longitude, point.latitude] },
+        "properties": properties,
+    })
+}
+
+/// The report as a GeoJSON FeatureCollection: the centre, ranked attractors and voids, the
+/// exclusion zones, the density surface as weighted heatmap samples, then every generated point, each tagged with a
+/// `kind` property for styling. The centre carries the entropy provenance.
+pub fn to_geojson(report: &GeolocationReport) -> serde_json::Value {
+    let mut features = vec![feature(report.center, json!({
+        "kind": "center",
+        "radius_m": report.radius_m,
+        "entropy_ranges": report.entropy_ranges,
+        "fallback_draws": report.fallback_draws,
+        "redrawn": report.redrawn,
+        "intention": report.intention,
+    }))];
+    for (kind, spots) in [("attractor", &report.attractors), ("void", &report.voids)] {
+        features.extend(spots.iter().enumerate().map(|(i, s)| feature(s.point, json!({
+            "kind": kind,
+            "rank": i + 1,
+            "radius_m": s.radius_m,
+            "count": s.count,
+            "expected": s.expected,
+            "power": s.power,
+            "distance_m": s.distance_m,
+            "bearing_deg": s.bearing_deg,
+        }))));
+    }
+    features.extend(report.exclusions.iter().map(|zone| {
+        let mut ring: Vec<[f64; 2]> = zone.polygon.iter().map(|p| [p.longitude, p.latitude]).collect();
+        let geometry = if ring.len() >= 3 {
+            ring.push(ring[0]);
+            json!({ "type": "Polygon", "coordinates": [ring] })
+        } else {
+            json!({ "type": "LineString", "coordinates": ring })
+        };
+        json!({
+            "type": "Feature",
+            "geometry": geometry,
+            "properties": { "kind": "exclusion", "label": zone.label, "buffer_m": zone.buffer_m },
+        })
+    }));
+    features.extend(report.density.iter().map(|c| feature(c.point, json!({
+        "kind": "density",
+        "count": c.count,
+        "power": c.power,
+        "cell_m": report.cell_m,
+    }))));
+    features.extend(report.points.iter().map(|&p| feature(p, json!({ "kind": "point" }))));
+    json!({ "type": "FeatureCollection", "features": features })
+}
+
+/// A visited attractor or void and how the visit went, for `outcome_stats`.
+#[derive(Debug, Clone)]
+pub struct TripOutcome {
+    pub kind: String,
+    pub power: f64,
+    pub rating: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KindOutcome {
+    pub kind: String,
+    pub trips: usize,
+    pub mean_rating: f64,
+    pub mean_power: f64,
+}
+
+/// Whether stronger targets made for better trips.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutcomeStats {
+    pub trips: usize,
+    pub by_kind: Vec<KindOutcome>,
+    /// Pearson correlation of the target's strength (|power|) with the rating; `None` below
+    /// four trips or when either has no spread.
+    pub strength_correlation: Option<f64>,
+    /// Two-sided p-value of the correlation (Fisher z-transform).
+    pub p_value: Option<f64>,
+    /// Least-squares rating gained per unit of strength.
+    pub slope: Option<f64>,
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, n) = values.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+    if n == 0 { 0.0 } else { sum / n as f64 }
+}
+
+/// Rating against attractor and void strength across logged trips.
+pub fn outcome_stats(trips: &[TripOutcome]) -> OutcomeStats {
+    let mut kinds: Vec<&str> = trips.iter().map(|t| t.kind.as_str()).collect();
+    kinds.sort_unstable();
+    kinds.dedup();
+    let by_kind = kinds.into_iter().map(|kind| {
+        let of_kind = || trips.iter().filter(move |t| t.kind == kind);
+        KindOutcome {
+            kind: kind.to_string(),
+            trips: of_kind().count(),
+            mean_rating: mean(of_kind().map(|t| t.rating)),
+            mean_power: mean(of_kind().map(|t| t.power)),
+        }
+    }).collect();
+
+    let n = trips.len();
+    let (mx, my) = (mean(trips.iter().map(|t| t.power.abs())), mean(trips.iter().map(|t| t.rating)));
+    let (sxy, sxx, syy) = trips.iter().fold((0.0, 0.0, 0.0), |(sxy, sxx, syy), t| {
+        let (dx, dy) = (t.power.abs() - mx, t.rating - my);
+        (sxy + dx * dy, sxx + dx * dx, syy + dy * dy)
+    });
+    let r = (n >= 4 && sxx > 0.0 && syy > 0.0).then(|| sxy / (sxx * syy).sqrt());
+    let p_value = r.map(|r| {
+        let z = r.clamp(-0.999_999, 0.999_999).atanh() * ((n - 3) as f64).sqrt();
+        chi_square_sf(z * z, 1)
+    });
+
+    OutcomeStats {
+        trips: n,
+        by_kind,
+        strength_correlation: r,
+        p_value,
+        slope: r.map(|_| sxy / sxx),
+    }
+}
+
+#[cfg(test)]
+#[path = "geolocation_tests.rs"]
+mod tests;
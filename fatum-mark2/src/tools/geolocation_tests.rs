@@ -0,0 +1,249 @@
+use super::{
+    coverage, destination, distance_m, offset_of, outcome_stats, to_geojson, within, ExclusionZone, GeoPoint, GeolocationConfig,
+    GeolocationTool, Mask, PointGrid, TripOutcome,
+};
+use crate::engine::SimulationSession;
+use crate::tools::test_fixtures::entropy;
+
+fn config(points: usize) -> GeolocationConfig {
+    GeolocationConfig { latitude: 51.5007, longitude: -0.1246, radius_m: 1000.0, points: Some(points), ..Default::default() }
+}
+
+/// Eight pool bytes that `next_f64` reads back as `x`.
+fn draw(x: f64) -> [u8; 8] {
+    (((x * (1u64 << 53) as f64) as u64) << 11).to_le_bytes()
+}
+
+#[test]
+fn test_destination_round_trips_distance() {
+    let from = GeoPoint { latitude: 40.0, longitude: 179.9 };
+    let to = destination(from, 90f64.to_radians(), 20_000.0);
+    assert!((distance_m(from, to) - 20_000.0).abs() < 0.01);
+    // Crossing the antimeridian wraps into the western hemisphere
+    assert!(to.longitude < -179.0);
+}
+
+#[test]
+fn test_points_stay_inside_radius() {
+    let mut session = SimulationSession::new(entropy(300 * 16));
+    let report = GeolocationTool::generate_location(&mut session, &config(300)).unwrap();
+    assert_eq!(report.points.len(), 300);
+    assert_eq!(session.fallback_draws, 0);
+    assert!(report.points.iter().all(|&p| distance_m(report.center, p) <= 1000.0 + 0.01));
+    assert!(!report.attractors.is_empty() && !report.voids.is_empty());
+    for spot in report.voids.iter() {
+        assert!(spot.distance_m + report.search_radius_m <= 1000.0 + 0.01);
+    }
+}
+
+#[test]
+fn test_coverage_edges() {
+    assert!((coverage(0.0, 100.0, 1000.0) - 0.01).abs() < 1e-12);
+    assert_eq!(coverage(2000.0, 100.0, 1000.0), 0.0);
+    assert_eq!(coverage(0.0, 2000.0, 1000.0), 1.0);
+    // A small circle on the rim is about half inside
+    assert!((coverage(1000.0, 10.0, 1000.0) - 0.5 * 0.0001).abs() < 1e-6);
+}
+
+#[test]
+fn test_planted_cluster_is_strongest_attractor() {
+    // 60 of 400 points dropped within a few metres of 500 m due east, the rest scattered
+    let mut pool = entropy(400 * 16);
+    for i in 0..60 {
+        let jitter = i as f64 * 1e-5;
+        pool[i * 16..i * 16 + 8].copy_from_slice(&draw(0.25 + jitter));
+        pool[i * 16 + 8..i * 16 + 16].copy_from_slice(&draw(0.25 + jitter));
+    }
+    let mut session = SimulationSession::new(pool);
+    let report = GeolocationTool::generate_location(&mut session, &config(400)).unwrap();
+    let top = &report.attractors[0];
+    assert!((top.distance_m - 500.0).abs() < 10.0, "attractor at {} m", top.distance_m);
+    assert!((top.bearing_deg - 90.0).abs() < 10.0);
+    assert!(top.power > 5.0 && top.count as f64 > top.expected);
+    assert!(top.radius_m > 0.0 && top.radius_m <= 3.0 * report.search_radius_m);
+    assert!(report.voids.iter().all(|v| v.power < 0.0 && (v.count as f64) < v.expected));
+}
+
+#[test]
+fn test_grid_counts_match_brute_force() {
+    let mut session = SimulationSession::new(entropy(2000 * 16));
+    let offsets: Vec<(f64, f64)> = (0..2000).map(|_| {
+        let (d, b) = (1000.0 * session.next_f64().sqrt(), std::f64::consts::TAU * session.next_f64());
+        (d * b.sin(), d * b.cos())
+    }).collect();
+    let grid = PointGrid::new(&offsets, 1000.0, 50.0);
+    for at in [(0.0, 0.0), (-990.0, 5.0), (400.0, -700.0), (1000.0, 1000.0)] {
+        for r in [25.0, 100.0, 300.0] {
+            let brute = offsets.iter().filter(|&&p| within(p, at, r)).count();
+            assert_eq!(grid.count(at, r), brute, "at {:?} r {}", at, r);
+        }
+    }
+}
+
+#[test]
+fn test_large_scan_surface() {
+    let mut session = SimulationSession::new(entropy(64));
+    let report = GeolocationTool::generate_location(&mut session, &config(100_000)).unwrap();
+    assert_eq!(report.points.len(), 100_000);
+    // 40 x 40 cells of 50 m, about pi/4 of them centred inside the disc
+    assert_eq!(report.cell_m, 50.0);
+    assert!(report.density.len() > 1200 && report.density.len() < 1300);
+    assert!(report.attractors.iter().all(|a| a.power > 0.0));
+}
+
+#[test]
+fn test_points_come_from_the_pool() {
+    let mut session = SimulationSession::new(entropy(100 * 16 + 8));
+    session.pool_index = 8;
+    let report = GeolocationTool::generate_location(&mut session, &config(100)).unwrap();
+    assert_eq!(report.entropy_ranges[0].start, 8);
+    assert_eq!(report.entropy_ranges[0].end, 8 + 100 * 16);
+    assert_eq!(report.fallback_draws, 0);
+
+    // Short pool: the last points finish on the PRNG unless strict mode refuses
+    let mut session = SimulationSession::new(entropy(90 * 16));
+    let report = GeolocationTool::generate_location(&mut session, &config(100)).unwrap();
+    assert_eq!(report.fallback_draws, 20);
+    let strict = GeolocationConfig { strict_quantum: true, ..config(100) };
+    let mut session = SimulationSession::new(entropy(90 * 16));
+    let err = GeolocationTool::generate_location(&mut session, &strict).unwrap_err();
+    assert!(err.to_string().contains("strict quantum"));
+    assert_eq!(session.pool_index, 0);
+}
+
+/// The square of half-side `half` metres centred `east` metres east of the test centre.
+fn square_zone(east: f64, half: f64) -> ExclusionZone {
+    let c = config(1);
+    let centre = GeoPoint { latitude: c.latitude, longitude: c.longitude };
+    let corner = |x: f64, y: f64| destination(centre, x.atan2(y), x.hypot(y));
+    ExclusionZone {
+        label: Some("Lake".to_string()),
+        polygon: vec![corner(east - half, -half), corner(east + half, -half), corner(east + half, half), corner(east - half, half)],
+        buffer_m: 0.0,
+    }
+}
+
+#[test]
+fn test_mask_polygon_and_corridor() {
+    let c = config(1);
+    let centre = GeoPoint { latitude: c.latitude, longitude: c.longitude };
+    let lake = Mask::new(centre, &square_zone(500.0, 200.0));
+    assert!(lake.covers((500.0, 0.0)));
+    assert!(lake.covers((650.0, 150.0)));
+    assert!(!lake.covers((250.0, 0.0)));
+    assert!(!lake.covers((0.0, 0.0)));
+
+    // A road running north-south through the centre, 20 m either side
+    let road = ExclusionZone {
+        label: None,
+        polygon: vec![destination(centre, 0.0, 900.0), destination(centre, std::f64::consts::PI, 900.0)],
+        buffer_m: 20.0,
+    };
+    let road = Mask::new(centre, &road);
+    assert!(road.covers((15.0, 300.0)));
+    assert!(!road.covers((25.0, 300.0)));
+    assert!(!road.covers((0.0, 930.0)));
+
+    // The offset plane round-trips through `destination`
+    let p = offset_of(centre, destination(centre, 1.0, 700.0));
+    assert!((p.0 - 700.0 * 1f64.sin()).abs() < 1e-6 && (p.1 - 700.0 * 1f64.cos()).abs() < 1e-6);
+}
+
+#[test]
+fn test_exclusions_redraw_from_the_pool() {
+    let masked = GeolocationConfig { exclusions: vec![square_zone(500.0, 300.0)], ..config(300) };
+    let run = || {
+        let mut session = SimulationSession::new(entropy(600 * 16));
+        GeolocationTool::generate_location(&mut session, &masked).unwrap()
+    };
+    let report = run();
+    let lake = Mask::new(report.center, &masked.exclusions[0]);
+    assert!(report.redrawn > 0);
+    assert_eq!(report.fallback_draws, 0);
+    assert_eq!(report.entropy_ranges[0].end, (300 + report.redrawn) * 16);
+    assert!(report.points.iter().all(|&p| !lake.covers(offset_of(report.center, p))));
+    assert!(report.attractors.iter().chain(&report.voids).all(|h| !lake.covers(offset_of(report.center, h.point))));
+    // Cells well inside the lake expect nothing, so they are neither strong nor empty
+    let lake_cell = report.density.iter().find(|c| {
+        let at = offset_of(report.center, c.point);
+        (at.0 - 500.0).abs() < 150.0 && at.1.abs() < 150.0
+    }).unwrap();
+    assert_eq!((lake_cell.count, lake_cell.power), (0, 0.0));
+    assert_eq!(run().points, report.points);
+
+    let geojson = to_geojson(&report);
+    let zone = geojson["features"].as_array().unwrap().iter().find(|f| f["properties"]["kind"] == "exclusion").unwrap();
+    assert_eq!(zone["geometry"]["type"], "Polygon");
+    assert_eq!(zone["geometry"]["coordinates"][0].as_array().unwrap().len(), 5);
+}
+
+#[test]
+fn test_exclusions_covering_everything() {
+    let everything = GeolocationConfig { exclusions: vec![square_zone(0.0, 2000.0)], ..config(10) };
+    let mut session = SimulationSession::new(entropy(64));
+    assert!(GeolocationTool::generate_location(&mut session, &everything).is_err());
+}
+
+#[test]
+fn test_same_entropy_same_scan() {
+    let run = || {
+        let mut session = SimulationSession::new(entropy(200 * 16));
+        GeolocationTool::generate_location(&mut session, &config(200)).unwrap()
+    };
+    let (a, b) = (run(), run());
+    assert_eq!(a.points, b.points);
+    assert_eq!(a.attractors[0].point, b.attractors[0].point);
+}
+
+#[test]
+fn test_rejects_bad_config() {
+    let mut session = SimulationSession::new(entropy(64));
+    assert!(GeolocationTool::generate_location(&mut session, &config(0)).is_err());
+    let flat = GeolocationConfig { radius_m: 0.0, ..config(10) };
+    assert!(GeolocationTool::generate_location(&mut session, &flat).is_err());
+}
+
+#[test]
+fn test_geojson_feature_collection() {
+    let mut session = SimulationSession::new(entropy(50 * 16));
+    let report = GeolocationTool::generate_location(&mut session, &config(50)).unwrap();
+    let geojson = to_geojson(&report);
+    assert_eq!(geojson["type"], "FeatureCollection");
+    let features = geojson["features"].as_array().unwrap();
+    assert_eq!(features.len(), 1 + report.attractors.len() + report.voids.len() + report.density.len() + 50);
+    assert_eq!(features[0]["properties"]["kind"], "center");
+    assert_eq!(features[1]["properties"]["kind"], "attractor");
+    assert_eq!(features[1]["properties"]["rank"], 1);
+    assert!(features[1]["properties"]["power"].as_f64().unwrap() > 0.0);
+    // GeoJSON orders coordinates longitude first
+    assert_eq!(features[0]["geometry"]["coordinates"][0], report.center.longitude);
+}
+
+#[test]
+fn test_outcome_stats() {
+    let trip = |kind: &str, power: f64, rating: f64| TripOutcome { kind: kind.to_string(), power, rating };
+    // Too few trips to correlate
+    let few = outcome_stats(&[trip("attractor", 3.0, 4.0), trip("void", -2.0, 2.0)]);
+    assert_eq!(few.trips, 2);
+    assert!(few.strength_correlation.is_none() && few.p_value.is_none());
+
+    // Ratings rise with strength regardless of sign
+    let trips: Vec<_> = (1..=10).map(|i| {
+        let kind = if i % 2 == 0 { "attractor" } else { "void" };
+        let power = if i % 2 == 0 { i as f64 } else { -(i as f64) };
+        trip(kind, power, 1.0 + 0.4 * i as f64)
+    }).collect();
+    let stats = outcome_stats(&trips);
+    assert!((stats.strength_correlation.unwrap() - 1.0).abs() < 1e-9);
+    assert!((stats.slope.unwrap() - 0.4).abs() < 1e-9);
+    assert!(stats.p_value.unwrap() < 1e-6);
+    assert_eq!(stats.by_kind.len(), 2);
+    assert_eq!(stats.by_kind[0].kind, "attractor");
+    assert_eq!(stats.by_kind[0].trips, 5);
+    assert!((stats.by_kind[0].mean_power - 6.0).abs() < 1e-9);
+    assert!((stats.by_kind[1].mean_rating - 3.0).abs() < 1e-9);
+
+    // Flat ratings have no spread to correlate
+    let flat: Vec<_> = (1..=5).map(|i| trip("attractor", i as f64, 3.0)).collect();
+    assert!(outcome_stats(&flat).strength_correlation.is_none());
+}
@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Language of a tool's output text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    /// Simplified Chinese, with pinyin beside glossary terms.
+    Zh,
+}
+
+/// A domain term in English and in Chinese.
+pub struct Term {
+    /// The name the tools already print, e.g. "Jian (Establish)".
+    pub en: &'static str,
+    pub hanzi: &'static str,
+    pub pinyin: &'static str,
+}
+
+impl Term {
+    /// The English name, or "hanzi (pinyin)".
+    pub fn label(&self, locale: Locale) -> String {
+        match locale {
+            Locale::En => self.en.to_string(),
+            Locale::Zh => format!("{} ({})", self.hanzi, self.pinyin),
+        }
+    }
+}
+
+const fn term(en: &'static str, hanzi: &'static str, pinyin: &'static str) -> Term {
+    Term { en, hanzi, pinyin }
+}
+
+/// Flying Stars 1-9 with their classical names, by `floor_plan::star_name`.
+pub const STARS: [Term; 9] = [
+    term("1 White (Career)", "一白贪狼", "yī bái tān láng"),
+    term("2 Black (Sickness)", "二黑巨门", "èr hēi jù mén"),
+    term("3 Jade (Quarrels)", "三碧禄存", "sān bì lù cún"),
+    term("4 Green (Study)", "四绿文曲", "sì lǜ wén qū"),
+    term("5 Yellow (Misfortune)", "五黄廉贞", "wǔ huáng lián zhēn"),
+    term("6 White (Authority)", "六白武曲", "liù bái wǔ qǔ"),
+    term("7 Red (Loss)", "七赤破军", "qī chì pò jūn"),
+    term("8 White (Wealth)", "八白左辅", "bā bái zuǒ fǔ"),
+    term("9 Purple (Celebration)", "九紫右弼", "jiǔ zǐ yòu bì"),
+];
+
+/// The nine palaces by sector code, named by direction and trigram.
+pub const PALACES: [(&str, Term); 9] = [
+    ("N", term("North", "北方坎宫", "běi fāng kǎn gōng")),
+    ("NE", term("Northeast", "东北方艮宫", "dōng běi fāng gèn gōng")),
+    ("E", term("East", "东方震宫", "dōng fāng zhèn gōng")),
+    ("SE", term("Southeast", "东南方巽宫", "dōng nán fāng xùn gōng")),
+    ("S", term("South", "南方离宫", "nán fāng lí gōng")),
+    ("SW", term("Southwest", "西南方坤宫", "xī nán fāng kūn gōng")),
+    ("W", term("West", "西方兑宫", "xī fāng duì gōng")),
+    ("NW", term("Northwest", "西北方乾宫", "xī běi fāng qián gōng")),
+    ("Center", term("Center", "中宫", "zhōng gōng")),
+];
+
+/// The 12 Day Officers (Jian Chu), as `ze_ri` names them.
+pub const OFFICERS: [Term; 12] = [
+    term("Jian (Establish)", "建", "jiàn"), term("Chu (Remove)", "除", "chú"),
+    term("Man (Full)", "满", "mǎn"), term("Ping (Balance)", "平", "píng"),
+    term("Ding (Stable)", "定", "dìng"), term("Zhi (Initiate)", "执", "zhí"),
+    term("Po (Destruction)", "破", "pò"), term("Wei (Danger)", "危", "wēi"),
+    term("Cheng (Success)", "成", "chéng"), term("Shou (Receive)", "收", "shōu"),
+    term("Kai (Open)", "开", "kāi"), term("Bi (Close)", "闭", "bì"),
+];
+
+/// The 12 Hour Spirits of the Yellow and Black Paths.
+pub const HOUR_OFFICERS: [Term; 12] = [
+    term("Qing Long (Green Dragon)", "青龙", "qīng lóng"), term("Ming Tang (Bright Hall)", "明堂", "míng táng"),
+    term("Tian Xing (Heavenly Punishment)", "天刑", "tiān xíng"), term("Zhu Que (Vermilion Bird)", "朱雀", "zhū què"),
+    term("Jin Kui (Golden Coffer)", "金匮", "jīn kuì"), term("Tian De (Heavenly Virtue)", "天德", "tiān dé"),
+    term("Bai Hu (White Tiger)", "白虎", "bái hǔ"), term("Yu Tang (Jade Hall)", "玉堂", "yù táng"),
+    term("Tian Lao (Heavenly Prison)", "天牢", "tiān láo"), term("Xuan Wu (Black Tortoise)", "玄武", "xuán wǔ"),
+    term("Si Ming (Life Controller)", "司命", "sī mìng"), term("Gou Chen (Hook)", "勾陈", "gōu chén"),
+];
+
+/// Date afflictions and the time layers they belong to, as in "Year Tai Sui".
+pub const AFFLICTIONS: [Term; 8] = [
+    term("Tai Sui", "太岁", "tài suì"), term("Sui Po", "岁破", "suì pò"),
+    term("San Sha", "三煞", "sān shà"), term("Five Yellow", "五黄", "wǔ huáng"),
+    term("2 Black", "二黑", "èr hēi"), term("Year", "年", "nián"),
+    term("Month", "月", "yuè"), term("Day", "日", "rì"),
+];
+
+/// Classic Qi Men structures (Ge Ju), as `qimen` names them.
+pub const QIMEN_STRUCTURES: [Term; 6] = [
+    term("Green Dragon Returns", "青龙返首", "qīng lóng fǎn shǒu"),
+    term("Birds Falling into Cave", "飞鸟跌穴", "fēi niǎo diē xué"),
+    term("Fu Yin", "伏吟", "fú yín"), term("Fan Yin", "反吟", "fǎn yín"),
+    term("Door Clash", "门迫", "mén pò"), term("Empty Death", "空亡", "kōng wáng"),
+];
+
+/// The 64 hexagrams in King Wen order, as `da_gua` names them, with their classical keyword in Chinese.
+pub const HEXAGRAMS: [(Term, &str); 64] = [
+    (term("Qian (The Creative)", "乾", "qián"), "刚健进取"),
+    (term("Kun (The Receptive)", "坤", "kūn"), "柔顺承载"),
+    (term("Zhun (Difficulty at the Beginning)", "屯", "zhūn"), "初创艰难"),
+    (term("Meng (Youthful Folly)", "蒙", "méng"), "启蒙求学"),
+    (term("Xu (Waiting)", "需", "xū"), "守正待时"),
+    (term("Song (Conflict)", "讼", "sòng"), "争讼是非"),
+    (term("Shi (The Army)", "师", "shī"), "统众用兵"),
+    (term("Bi (Holding Together)", "比", "bǐ"), "亲比团结"),
+    (term("Xiao Chu (Small Taming)", "小畜", "xiǎo xù"), "积小蓄势"),
+    (term("Lu (Treading)", "履", "lǚ"), "谨慎行事"),
+    (term("Tai (Peace)", "泰", "tài"), "通泰和顺"),
+    (term("Pi (Standstill)", "否", "pǐ"), "闭塞不通"),
+    (term("Tong Ren (Fellowship)", "同人", "tóng rén"), "和同于人"),
+    (term("Da You (Great Possession)", "大有", "dà yǒu"), "丰盛大有"),
+    (term("Qian (Modesty)", "谦", "qiān"), "谦逊受益"),
+    (term("Yu (Enthusiasm)", "豫", "yù"), "安乐和悦"),
+    (term("Sui (Following)", "随", "suí"), "随时变通"),
+    (term("Gu (Work on the Decayed)", "蛊", "gǔ"), "整治积弊"),
+    (term("Lin (Approach)", "临", "lín"), "势力渐长"),
+    (term("Guan (Contemplation)", "观", "guān"), "观察示范"),
+    (term("Shi He (Biting Through)", "噬嗑", "shì kè"), "除障明法"),
+    (term("Bi (Grace)", "贲", "bì"), "文饰修美"),
+    (term("Bo (Splitting Apart)", "剥", "bō"), "剥落衰败"),
+    (term("Fu (Return)", "复", "fù"), "一阳来复"),
+    (term("Wu Wang (Innocence)", "无妄", "wú wàng"), "真诚无妄"),
+    (term("Da Chu (Great Taming)", "大畜", "dà xù"), "厚积蓄德"),
+    (term("Yi (Nourishment)", "颐", "yí"), "颐养守正"),
+    (term("Da Guo (Great Exceeding)", "大过", "dà guò"), "负重过度"),
+    (term("Kan (The Abysmal)", "坎", "kǎn"), "重重险陷"),
+    (term("Li (The Clinging)", "离", "lí"), "光明附丽"),
+    (term("Xian (Influence)", "咸", "xián"), "感应相交"),
+    (term("Heng (Duration)", "恒", "héng"), "恒久不变"),
+    (term("Dun (Retreat)", "遁", "dùn"), "退避保身"),
+    (term("Da Zhuang (Great Power)", "大壮", "dà zhuàng"), "强盛守正"),
+    (term("Jin (Progress)", "晋", "jìn"), "迅速晋升"),
+    (term("Ming Yi (Darkening of the Light)", "明夷", "míng yí"), "光明受伤"),
+    (term("Jia Ren (The Family)", "家人", "jiā rén"), "家道和睦"),
+    (term("Kui (Opposition)", "睽", "kuí"), "乖离相背"),
+    (term("Jian (Obstruction)", "蹇", "jiǎn"), "险阻难行"),
+    (term("Xie (Deliverance)", "解", "xiè"), "解脱舒缓"),
+    (term("Sun (Decrease)", "损", "sǔn"), "减损奉献"),
+    (term("Yi (Increase)", "益", "yì"), "增益成长"),
+    (term("Guai (Breakthrough)", "夬", "guài"), "果决决断"),
+    (term("Gou (Coming to Meet)", "姤", "gòu"), "暗藏隐患"),
+    (term("Cui (Gathering Together)", "萃", "cuì"), "聚合团结"),
+    (term("Sheng (Pushing Upward)", "升", "shēng"), "稳步上升"),
+    (term("Kun (Oppression)", "困", "kùn"), "困穷受阻"),
+    (term("Jing (The Well)", "井", "jǐng"), "源源不竭"),
+    (term("Ge (Revolution)", "革", "gé"), "顺时变革"),
+    (term("Ding (The Cauldron)", "鼎", "dǐng"), "革新养贤"),
+    (term("Zhen (The Arousing)", "震", "zhèn"), "震动警醒"),
+    (term("Gen (Keeping Still)", "艮", "gèn"), "止而安静"),
+    (term("Jian (Development)", "渐", "jiàn"), "循序渐进"),
+    (term("Gui Mei (The Marrying Maiden)", "归妹", "guī mèi"), "名位不正"),
+    (term("Feng (Abundance)", "丰", "fēng"), "盛大丰满"),
+    (term("Lu (The Wanderer)", "旅", "lǚ"), "羁旅无常"),
+    (term("Xun (The Gentle)", "巽", "xùn"), "柔顺渗入"),
+    (term("Dui (The Joyous)", "兑", "duì"), "喜悦沟通"),
+    (term("Huan (Dispersion)", "涣", "huàn"), "涣散化解"),
+    (term("Jie (Limitation)", "节", "jié"), "节制有度"),
+    (term("Zhong Fu (Inner Truth)", "中孚", "zhōng fú"), "诚信中正"),
+    (term("Xiao Guo (Small Exceeding)", "小过", "xiǎo guò"), "小事可为"),
+    (term("Ji Ji (After Completion)", "既济", "jì jì"), "功成守成"),
+    (term("Wei Ji (Before Completion)", "未济", "wèi jì"), "事未完成"),
+];
+
+/// Zi Wei Dou Shu stars by the pinyin names `zi_wei` gives them.
+pub const ZIWEI_STARS: [Term; 36] = [
+    term("Zi Wei", "紫微", "zǐ wēi"), term("Tian Ji", "天机", "tiān jī"),
+    term("Tai Yang", "太阳", "tài yáng"), term("Wu Qu", "武曲", "wǔ qǔ"),
+    term("Tian Tong", "天同", "tiān tóng"), term("Lian Zhen", "廉贞", "lián zhēn"),
+    term("Tian Fu", "天府", "tiān fǔ"), term("Tai Yin", "太阴", "tài yīn"),
+    term("Tan Lang", "贪狼", "tān láng"), term("Ju Men", "巨门", "jù mén"),
+    term("Tian Xiang", "天相", "tiān xiàng"), term("Tian Liang", "天梁", "tiān liáng"),
+    term("Qi Sha", "七杀", "qī shā"), term("Po Jun", "破军", "pò jūn"),
+    term("Wen Chang", "文昌", "wén chāng"), term("Wen Qu", "文曲", "wén qǔ"),
+    term("Zuo Fu", "左辅", "zuǒ fǔ"), term("You Bi", "右弼", "yòu bì"),
+    term("Tian Kui", "天魁", "tiān kuí"), term("Tian Yue", "天钺", "tiān yuè"),
+    term("Lu Cun", "禄存", "lù cún"), term("Qing Yang", "擎羊", "qíng yáng"),
+    term("Tuo Luo", "陀罗", "tuó luó"), term("Huo Xing", "火星", "huǒ xīng"),
+    term("Ling Xing", "铃星", "líng xīng"), term("Di Kong", "地空", "dì kōng"),
+    term("Di Jie", "地劫", "dì jié"), term("Tian Xing", "天刑", "tiān xíng"),
+    term("Tian Ma", "天马", "tiān mǎ"), term("Tai Fu", "台辅", "tái fǔ"),
+    term("Feng Gao", "封诰", "fēng gào"), term("Hong Luan", "红鸾", "hóng luán"),
+    term("Tian Xi", "天喜", "tiān xǐ"), term("Tian Yao", "天姚", "tiān yáo"),
+    term("Xian Chi", "咸池", "xián chí"), term("Tian Kong", "天空", "tiān kōng"),
+];
+
+/// The 12 Zi Wei palaces in `zi_wei::PALACE_NAMES` order.
+pub const ZIWEI_PALACES: [Term; 12] = [
+    term("Life", "命宫", "mìng gōng"), term("Siblings", "兄弟宫", "xiōng dì gōng"),
+    term("Spouse", "夫妻宫", "fū qī gōng"), term("Children", "子女宫", "zǐ nǚ gōng"),
+    term("Wealth", "财帛宫", "cái bó gōng"), term("Health", "疾厄宫", "jí è gōng"),
+    term("Travel", "迁移宫", "qiān yí gōng"), term("Friends", "交友宫", "jiāo yǒu gōng"),
+    term("Career", "官禄宫", "guān lù gōng"), term("Property", "田宅宫", "tián zhái gōng"),
+    term("Mental", "福德宫", "fú dé gōng"), term("Parents", "父母宫", "fù mǔ gōng"),
+];
+
+/// Output templates by key: (key, English, Chinese). `{name}` is filled in by `message`.
+const MESSAGES: [(&str, &str, &str); 23] = [
+    ("wealth-water.action",
+        "Keep the {sector} open and active; water or movement here draws wealth.",
+        "保持{sector}开阔、多走动；在此放置流水可催财。"),
+    ("wealth-water.explanation",
+        "Sector {sector} contains the Water Star {star}, activating Wealth Luck.",
+        "{sector}有向星{star}飞临，催旺财运。"),
+    ("wealth-mountain.action",
+        "Keep the {sector} quiet and solid; a bedroom or tall furniture here supports health.",
+        "{sector}宜静宜实；在此设卧室或摆放高大家具有利健康。"),
+    ("wealth-mountain.explanation",
+        "Sector {sector} contains the Mountain Star {star}, good for Health/Relations.",
+        "{sector}有山星{star}飞临，利健康与人缘。"),
+    ("kua.action",
+        "Face {direction} when working or sleeping where you can.",
+        "工作或睡觉时尽量朝向{direction}。"),
+    ("kua.explanation",
+        "Your Life Gua is {number}. Strongest direction: {direction}.",
+        "你的命卦为{number}，最佳方位：{direction}。"),
+    ("quantum.action",
+        "Give the {sector} sector extra attention.",
+        "多留意{sector}。"),
+    ("quantum.explanation",
+        "Quantum Focus: {sector}. Volatility: {volatility}",
+        "量子焦点：{sector}。波动度：{volatility}"),
+    ("formations.action",
+        "Review the formations in the report and keep the palaces they rely on unobstructed.",
+        "查看报告中的格局，并保持相关宫位通畅无阻。"),
+    ("formations.explanation",
+        "Special Auspicious Formations detected! See report details.",
+        "发现特殊吉格！详见报告。"),
+    ("hexagram.timely", "is fully timely in Period {period}", "在{period}运完全当令"),
+    ("hexagram.same-era", "shares the era of Period {period}", "与{period}运同属一元"),
+    ("hexagram.out-of-season",
+        "is out of season in Period {period}; a facing adjustment of a few degrees may help",
+        "在{period}运失令；微调朝向数度或有帮助"),
+    ("hexagram.explanation",
+        "Facing hexagram {name} (Gua Yun {yun}) {timing}.",
+        "向首卦{name}（卦运{yun}）{timing}。"),
+    ("hexagram.warning", " Its classical reading warns: {meaning}.", "古典卦义提示：{meaning}。"),
+    ("hexagram.keep", "Keep the current facing.", "保持现有朝向。"),
+    ("hexagram.turn",
+        "Consider turning the main facing a few degrees into a timely hexagram.",
+        "可考虑将大门朝向微调数度，转入当令之卦。"),
+    ("qimen.auspicious.action", "Favour activity toward the {direction}.", "宜向{direction}行事。"),
+    ("qimen.auspicious.explanation",
+        "Qi Men ({time}): {structure} in {direction} favours activity toward that direction.",
+        "奇门（{time}）：{direction}见{structure}，宜向该方行事。"),
+    ("qimen.harmful.action",
+        "Avoid starting anything important toward the {direction} while this chart holds.",
+        "此局期间，避免向{direction}开展要事。"),
+    ("qimen.harmful.explanation",
+        "Qi Men ({time}): {structure} in {direction} calls for caution there.",
+        "奇门（{time}）：{direction}见{structure}，需谨慎。"),
+    ("date-afflictions.action",
+        "Leave the palace undisturbed and keep metal cures (a Wu Lou or chime) there.",
+        "此宫宜静不宜动，可放置葫芦或铜铃等金属化煞物。"),
+    ("date-afflictions.explanation",
+        "{sector}: {afflictions}.",
+        "{sector}：{afflictions}。避免在此装修动土。"),
+];
+
+/// Fills in the template `key` for `locale`; an unknown key comes back as itself.
+pub fn message(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    let Some(&(_, en, zh)) = MESSAGES.iter().find(|m| m.0 == key) else { return key.to_string() };
+    let mut text = match locale {
+        Locale::En => en,
+        Locale::Zh => zh,
+    }.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// The glossary term the tools print in English as `text`.
+fn find(text: &str) -> Option<&'static Term> {
+    STARS.iter().chain(&OFFICERS).chain(&HOUR_OFFICERS).chain(&AFFLICTIONS).chain(&QIMEN_STRUCTURES)
+        .find(|t| t.en == text)
+}
+
+/// Translates a star, officer, affliction or Qi Men structure name the tools print in English.
+///
+/// English and names outside the glossary are returned unchanged.
+pub fn localize(text: &str, locale: Locale) -> String {
+    match (locale, find(text)) {
+        (Locale::Zh, Some(t)) => t.label(locale),
+        _ => text.to_string(),
+    }
+}
+
+/// A glossary term as it reads inside a sentence: unchanged in English, the hanzi alone in Chinese.
+pub fn in_sentence(text: &str, locale: Locale) -> String {
+    match (locale, find(text)) {
+        (Locale::Zh, Some(t)) => t.hanzi.to_string(),
+        _ => text.to_string(),
+    }
+}
+
+/// A hexagram's term and Chinese keyword by King Wen number (1-64).
+pub fn hexagram(king_wen: u8) -> Option<&'static (Term, &'static str)> {
+    HEXAGRAMS.get((king_wen as usize).checked_sub(1)?)
+}
+
+/// A sector code as it reads inside a sentence: the code itself in English, the palace in Chinese.
+pub fn sector(code: &str, locale: Locale) -> String {
+    match (locale, PALACES.iter().find(|(c, _)| *c == code)) {
+        (Locale::Zh, Some((_, palace))) => palace.hanzi.to_string(),
+        _ => code.to_string(),
+    }
+}
+
+/// A date affliction such as "Month Five Yellow" in `locale` ("月五黄" in Chinese).
+pub fn affliction(text: &str, locale: Locale) -> String {
+    let hanzi = |en: &str| AFFLICTIONS.iter().find(|t| t.en == en).map(|t| t.hanzi);
+    match (locale, text.split_once(' ')) {
+        (Locale::Zh, Some((layer, kind))) => match (hanzi(layer), hanzi(kind)) {
+            (Some(layer), Some(kind)) => format!("{}{}", layer, kind),
+            _ => text.to_string(),
+        },
+        _ => text.to_string(),
+    }
+}
+
+/// Display names for a report's sector codes and star numbers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Glossary {
+    pub locale: Locale,
+    /// Sector code ("N", "Center") to palace name.
+    pub palaces: BTreeMap<String, String>,
+    /// Star number ("1"-"9") to star name.
+    pub stars: BTreeMap<String, String>,
+}
+
+pub fn glossary(locale: Locale) -> Glossary {
+    Glossary {
+        locale,
+        palaces: PALACES.iter().map(|(code, t)| (code.to_string(), t.label(locale))).collect(),
+        stars: STARS.iter().enumerate().map(|(i, t)| ((i + 1).to_string(), t.label(locale))).collect(),
+    }
+}
+
+#[cfg(test)]
+#[path = "i18n_tests.rs"]
+mod tests;
@@ -0,0 +1,68 @@
+use super::{
+    affliction, glossary, hexagram, in_sentence, localize, message, sector, Locale, HOUR_OFFICERS, OFFICERS, STARS,
+    ZIWEI_PALACES,
+};
+use crate::tools::{da_gua, floor_plan::star_name, zi_wei};
+
+#[test]
+fn test_english_is_unchanged() {
+    for star in 1..=9 {
+        assert_eq!(localize(star_name(star), Locale::En), star_name(star));
+    }
+    assert_eq!(sector("SE", Locale::En), "SE");
+    assert_eq!(affliction("Month Five Yellow", Locale::En), "Month Five Yellow");
+    assert_eq!(
+        message(Locale::En, "kua.explanation", &[("number", "1"), ("direction", "SE")]),
+        "Your Life Gua is 1. Strongest direction: SE."
+    );
+}
+
+#[test]
+fn test_chinese_terms() {
+    // The English names must match what the tools print, or nothing gets translated
+    for star in 1..=9 {
+        assert_eq!(STARS[star as usize - 1].en, star_name(star));
+    }
+    assert_eq!(localize("8 White (Wealth)", Locale::Zh), "八白左辅 (bā bái zuǒ fǔ)");
+    assert_eq!(localize(OFFICERS[0].en, Locale::Zh), "建 (jiàn)");
+    assert_eq!(localize(HOUR_OFFICERS[0].en, Locale::Zh), "青龙 (qīng lóng)");
+    assert_eq!(localize("Something else", Locale::Zh), "Something else");
+
+    assert_eq!(sector("Center", Locale::Zh), "中宫");
+    assert_eq!(sector("X", Locale::Zh), "X");
+    assert_eq!(affliction("Month Five Yellow", Locale::Zh), "月五黄");
+    assert_eq!(affliction("Year Tai Sui", Locale::Zh), "年太岁");
+    assert_eq!(message(Locale::Zh, "no.such.key", &[]), "no.such.key");
+}
+
+#[test]
+fn test_hexagrams_structures_and_zi_wei() {
+    // Every hexagram on the ring must find its own entry by King Wen number
+    for position in 0..64 {
+        let gua = da_gua::at_position(position);
+        assert_eq!(hexagram(gua.king_wen).unwrap().0.en, gua.name);
+    }
+    assert_eq!(hexagram(12).map(|(t, meaning)| (t.hanzi, *meaning)), Some(("否", "闭塞不通")));
+    assert!(hexagram(0).is_none() && hexagram(65).is_none());
+
+    assert_eq!(localize("Green Dragon Returns", Locale::Zh), "青龙返首 (qīng lóng fǎn shǒu)");
+    assert_eq!(in_sentence("Door Clash", Locale::Zh), "门迫");
+    assert_eq!(in_sentence("Door Clash", Locale::En), "Door Clash");
+
+    for (term, name) in ZIWEI_PALACES.iter().zip(zi_wei::PALACE_NAMES) {
+        assert_eq!(term.en, name);
+    }
+}
+
+#[test]
+fn test_glossary_covers_palaces_and_stars() {
+    let zh = glossary(Locale::Zh);
+    assert_eq!((zh.palaces.len(), zh.stars.len()), (9, 9));
+    assert_eq!(zh.palaces["N"], "北方坎宫 (běi fāng kǎn gōng)");
+    assert_eq!(zh.stars["5"], "五黄廉贞 (wǔ huáng lián zhēn)");
+
+    let en = glossary(Locale::En);
+    assert_eq!(en.palaces["NW"], "Northwest");
+    assert_eq!(en.stars["9"], star_name(9));
+    assert_eq!(serde_json::to_value(&en).unwrap()["locale"], "en");
+}
@@ -0,0 +1,307 @@
+use chrono::{Datelike, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::timeline::WU_XING;
+use crate::engine::SimulationSession;
+use crate::tools::astronomy::{get_solar_term, Location};
+use crate::tools::bazi_strength::{stem_element, HIDDEN_STEMS};
+use crate::tools::chinese_meta::{day_pillar, get_branch, get_stem, is_six_clash, is_six_combination};
+use crate::tools::divination::{cast_lines, hexagram_data, lookup_hexagram_meta, trigram_index, CastMethod, TRIGRAMS};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LiuYaoConfig {
+    /// Local clock time of the question (defaults to now); sets the day and month pillars.
+    #[serde(default)]
+    pub datetime: Option<NaiveDateTime>,
+    /// Where the question was asked; converts `datetime` to true solar time.
+    #[serde(default)]
+    pub location: Option<Location>,
+    #[serde(default)]
+    pub method: CastMethod,
+}
+
+/// A six-line (Wen Wang Gua) chart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiuYaoChart {
+    pub number: u32,
+    pub name: String,
+    /// Hexagram the moving lines change into.
+    pub changed_number: Option<u32>,
+    pub changed_name: Option<String>,
+    /// Palace the hexagram belongs to, e.g. "Qian (Heaven)", and its element.
+    pub palace: String,
+    pub palace_element: String,
+    /// Place in the palace, e.g. "1st Generation" or "Wandering Soul (You Hun)".
+    pub generation: String,
+    /// Day and month pillars the lines are judged against, e.g. "Jia Zi (Rat)".
+    pub day_pillar: String,
+    pub month_branch: String,
+    /// Void branches (Xun Kong) of the day's decade.
+    pub void_branches: Vec<String>,
+    /// Bottom to top.
+    pub lines: Vec<LiuYaoLine>,
+    /// Relatives that appear on no line.
+    pub missing_relatives: Vec<String>,
+}
+
+/// One line with its Najia stem and branch and its readings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiuYaoLine {
+    /// 1 = bottom.
+    pub position: usize,
+    /// Cast value: 6 Old Yin, 7 Young Yang, 8 Young Yin, 9 Old Yang.
+    pub value: u8,
+    pub yang: bool,
+    pub moving: bool,
+    pub stem: String,
+    pub branch: String,
+    pub element: String,
+    /// Six Relatives (Liu Qin) against the palace element, e.g. "Wealth".
+    pub relative: String,
+    /// Six Spirits (Liu Shen) by the day stem.
+    pub spirit: String,
+    /// Self (Shi) and Other (Ying) lines.
+    pub shi: bool,
+    pub ying: bool,
+    pub void: bool,
+    pub month_break: bool,
+    /// Seasonal strength from the month branch, e.g. "Prosperous (Wang)".
+    pub month_strength: String,
+    /// What a moving line turns into.
+    pub changed: Option<ChangedLine>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedLine {
+    pub stem: String,
+    pub branch: String,
+    pub element: String,
+    /// Still counted against the original palace.
+    pub relative: String,
+}
+
+/// Najia by trigram (indexed like `TRIGRAMS`): inner and outer stems, then the branches of the
+/// inner (lines 1-3) and outer (lines 4-6) positions.
+const NAJIA: [(usize, usize, [usize; 3], [usize; 3]); 8] = [
+    (1, 9, [7, 5, 3], [1, 11, 9]),  // Kun: Yi / Gui
+    (6, 6, [0, 2, 4], [6, 8, 10]),  // Zhen: Geng
+    (4, 4, [2, 4, 6], [8, 10, 0]),  // Kan: Wu
+    (3, 3, [5, 3, 1], [11, 9, 7]),  // Dui: Ding
+    (2, 2, [4, 6, 8], [10, 0, 2]),  // Gen: Bing
+    (5, 5, [3, 1, 11], [9, 7, 5]),  // Li: Ji
+    (7, 7, [1, 11, 9], [7, 5, 3]),  // Xun: Xin
+    (0, 8, [0, 2, 4], [6, 8, 10]),  // Qian: Jia / Ren
+];
+
+// Relative of a line by its element's distance from the palace element along the generating cycle
+const RELATIVES: [&str; 5] = ["Brothers", "Offspring", "Wealth", "Officer", "Parents"];
+
+// Seasonal strength by the line element's distance from the month element
+const MONTH_STRENGTH: [&str; 5] = ["Prosperous (Wang)", "Strong (Xiang)", "Dead (Si)", "Trapped (Qiu)", "Resting (Xiu)"];
+
+const SPIRITS: [&str; 6] = [
+    "Qing Long (Green Dragon)", "Zhu Que (Vermilion Bird)", "Gou Chen (Hook)",
+    "Teng She (Soaring Snake)", "Bai Hu (White Tiger)", "Xuan Wu (Black Tortoise)",
+];
+
+const GENERATIONS: [&str; 8] = [
+    "Pure (Ben Gong)", "1st Generation", "2nd Generation", "3rd Generation",
+    "4th Generation", "5th Generation", "Wandering Soul (You Hun)", "Returning Soul (Gui Hun)",
+];
+
+/// Advancing Spirit (Jin Shen) pairs: a branch changing into the next of its element.
+const JIN_SHEN: [(usize, usize); 8] = [(11, 0), (2, 3), (5, 6), (8, 9), (1, 4), (4, 7), (7, 10), (10, 1)];
+
+fn branch_element(branch: usize) -> usize {
+    stem_element(HIDDEN_STEMS[branch][0])
+}
+
+/// Palace trigram, generation and Shi line (1-6) of a hexagram, by the Jing Fang eight palaces.
+fn palace_of(lines: &[u8; 6]) -> (usize, usize, usize) {
+    for palace in 0..8 {
+        let base: [u8; 6] = std::array::from_fn(|i| (palace as u8 >> (i % 3)) & 1);
+        // Generations 1-5 flip lines from the bottom; the souls then turn lines 4, then 1-3, back
+        let flipped = |n: usize, back: &[usize]| -> [u8; 6] {
+            std::array::from_fn(|i| if i < n && !back.contains(&i) { 1 - base[i] } else { base[i] })
+        };
+        let generations = [
+            (flipped(0, &[]), 6), (flipped(1, &[]), 1), (flipped(2, &[]), 2), (flipped(3, &[]), 3),
+            (flipped(4, &[]), 4), (flipped(5, &[]), 5), (flipped(5, &[3]), 4), (flipped(5, &[0, 1, 2, 3]), 3),
+        ];
+        if let Some(g) = generations.iter().position(|(pattern, _)| pattern == lines) {
+            return (palace, g, generations[g].1);
+        }
+    }
+    unreachable!("every hexagram belongs to a palace")
+}
+
+/// Najia stem and branch of each line, bottom to top.
+fn najia(lines: &[u8; 6]) -> [(usize, usize); 6] {
+    let inner = NAJIA[trigram_index(&lines[0..3])];
+    let outer = NAJIA[trigram_index(&lines[3..6])];
+    std::array::from_fn(|i| if i < 3 { (inner.0, inner.2[i]) } else { (outer.1, outer.3[i - 3]) })
+}
+
+fn relative(palace_element: usize, element: usize) -> &'static str {
+    RELATIVES[(element + 5 - palace_element) % 5]
+}
+
+/// What a moving line's transformation does to it.
+fn change_notes(from: usize, to: usize, void: &[usize; 2]) -> Vec<String> {
+    let (fe, te) = (branch_element(from), branch_element(to));
+    let mut notes = Vec::new();
+    if (te + 1) % 5 == fe {
+        notes.push("Transforms into its own support (Hui Tou Sheng)".to_string());
+    } else if (te + 2) % 5 == fe {
+        notes.push("Transforms into its own attacker (Hui Tou Ke)".to_string());
+    } else if JIN_SHEN.contains(&(from, to)) {
+        notes.push("Advancing Spirit (Jin Shen): grows stronger".to_string());
+    } else if JIN_SHEN.contains(&(to, from)) {
+        notes.push("Retreating Spirit (Tui Shen): fades".to_string());
+    } else if (fe + 1) % 5 == te {
+        notes.push("Drains into its transformation (Hua Xie)".to_string());
+    }
+    if is_six_clash(from, to) {
+        notes.push("Transforms into a clash (Hua Chong): the matter scatters".to_string());
+    } else if is_six_combination(from, to) {
+        notes.push("Transforms into a combination (Hua He): the matter binds".to_string());
+    }
+    if void.contains(&to) {
+        notes.push("Transforms into a void".to_string());
+    }
+    notes
+}
+
+/// Builds the chart for cast line values (6-9, bottom to top) at a solar time.
+pub fn build_chart(values: [u8; 6], time: NaiveDateTime) -> LiuYaoChart {
+    // 1. Original and changed hexagrams
+    let lines: [u8; 6] = values.map(|v| v % 2);
+    let changed: [u8; 6] = std::array::from_fn(|i| if values[i] == 6 || values[i] == 9 { 1 - lines[i] } else { lines[i] });
+    let moving = lines != changed;
+
+    // 2. Palace, generation and Shi/Ying
+    let (palace, generation, shi) = palace_of(&lines);
+    let ying = (shi + 2) % 6 + 1;
+    let (palace_name, palace_el_name) = TRIGRAMS[palace];
+    let palace_element = WU_XING.iter().position(|e| *e == palace_el_name).unwrap_or(0);
+
+    // 3. Day and month
+    let (day_stem, day_branch) = day_pillar(time.date());
+    let month_branch = ((get_solar_term(time.year(), time.month(), time.day()) as usize).div_ceil(2) + 3) % 12;
+    let month_element = branch_element(month_branch);
+    let void = [(day_branch + 22 - day_stem) % 12, (day_branch + 23 - day_stem) % 12];
+    let spirit_start = [0, 0, 1, 1, 2, 3, 4, 4, 5, 5][day_stem];
+
+    // 4. Lines
+    let original = najia(&lines);
+    let transformed = najia(&changed);
+    let chart_lines: Vec<LiuYaoLine> = (0..6).map(|i| {
+        let (stem, branch) = original[i];
+        let element = branch_element(branch);
+        let is_moving = lines[i] != changed[i];
+        let strength = MONTH_STRENGTH[(element + 5 - month_element) % 5];
+        let month_break = is_six_clash(month_branch, branch);
+        let mut notes = Vec::new();
+
+        if month_break {
+            notes.push("Month Break (Yue Po): broken by the month".to_string());
+        }
+        if void.contains(&branch) {
+            notes.push("Void (Xun Kong): empty until the void is filled".to_string());
+        }
+        let day_element = branch_element(day_branch);
+        if is_six_clash(day_branch, branch) {
+            let strong = strength.starts_with("Prosperous") || strength.starts_with("Strong");
+            notes.push(match (is_moving, strong) {
+                (true, _) => "Clashed by the day (Ri Chong): the movement scatters",
+                (false, true) => "Hidden Movement (An Dong): the day clash stirs it",
+                (false, false) => "Day Break (Ri Po): the day clash breaks it",
+            }.to_string());
+        } else if is_six_combination(day_branch, branch) {
+            notes.push("Combined by the day (Ri He)".to_string());
+        }
+        if (day_element + 1) % 5 == element {
+            notes.push("Supported by the day".to_string());
+        } else if (day_element + 2) % 5 == element {
+            notes.push("Restrained by the day".to_string());
+        }
+
+        let changed_line = is_moving.then(|| {
+            let (c_stem, c_branch) = transformed[i];
+            notes.extend(change_notes(branch, c_branch, &void));
+            ChangedLine {
+                stem: get_stem(c_stem).to_string(),
+                branch: get_branch(c_branch).to_string(),
+                element: WU_XING[branch_element(c_branch)].to_string(),
+                relative: relative(palace_element, branch_element(c_branch)).to_string(),
+            }
+        });
+
+        LiuYaoLine {
+            position: i + 1,
+            value: values[i],
+            yang: lines[i] == 1,
+            moving: is_moving,
+            stem: get_stem(stem).to_string(),
+            branch: get_branch(branch).to_string(),
+            element: WU_XING[element].to_string(),
+            relative: relative(palace_element, element).to_string(),
+            spirit: SPIRITS[(spirit_start + i) % 6].to_string(),
+            shi: i + 1 == shi,
+            ying: i + 1 == ying,
+            void: void.contains(&branch),
+            month_break,
+            month_strength: strength.to_string(),
+            changed: changed_line,
+            notes,
+        }
+    }).collect();
+
+    let missing_relatives = RELATIVES.iter()
+        .filter(|r| !chart_lines.iter().any(|l| l.relative == **r))
+        .map(|r| r.to_string())
+        .collect();
+
+    // 5. Names
+    let name_of = |l: &[u8; 6]| {
+        let (number, fallback) = lookup_hexagram_meta(l);
+        (number, hexagram_data(number).map(|d| d.name.clone()).unwrap_or(fallback))
+    };
+    let (number, name) = name_of(&lines);
+    let (changed_number, changed_name) = if moving {
+        let (n, name) = name_of(&changed);
+        (Some(n), Some(name))
+    } else {
+        (None, None)
+    };
+
+    LiuYaoChart {
+        number,
+        name,
+        changed_number,
+        changed_name,
+        palace: palace_name.to_string(),
+        palace_element: palace_el_name.to_string(),
+        generation: GENERATIONS[generation].to_string(),
+        day_pillar: format!("{} {}", get_stem(day_stem), get_branch(day_branch)),
+        month_branch: get_branch(month_branch).to_string(),
+        void_branches: void.iter().map(|b| get_branch(*b).to_string()).collect(),
+        lines: chart_lines,
+        missing_relatives,
+    }
+}
+
+/// Casts six lines from the session and charts them for the question's time.
+pub fn cast_liu_yao(session: &mut SimulationSession, config: &LiuYaoConfig) -> LiuYaoChart {
+    let clock = config.datetime.unwrap_or_else(|| chrono::Local::now().naive_local());
+    let time = match &config.location {
+        Some(loc) => loc.solar_time(clock),
+        None => clock,
+    };
+    build_chart(cast_lines(session, config.method), time)
+}
+
+#[cfg(test)]
+#[path = "liu_yao_tests.rs"]
+mod tests;
@@ -0,0 +1,61 @@
+use super::{build_chart, palace_of};
+use chrono::NaiveDate;
+
+fn noon(y: i32, m: u32, d: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(12, 0, 0).unwrap()
+}
+
+#[test]
+fn test_every_hexagram_has_a_palace() {
+    let mut per_palace = [0; 8];
+    for bits in 0..64u8 {
+        let lines: [u8; 6] = std::array::from_fn(|i| (bits >> i) & 1);
+        let (palace, _, shi) = palace_of(&lines);
+        assert!((1..=6).contains(&shi));
+        per_palace[palace] += 1;
+    }
+    assert_eq!(per_palace, [8; 8]);
+}
+
+#[test]
+fn test_gou_najia_and_relatives() {
+    // Gou (44): Heaven over Wind, first generation of the Qian palace, Shi on the first line
+    let chart = build_chart([8, 7, 7, 7, 7, 7], noon(2000, 1, 1));
+    assert_eq!(chart.number, 44);
+    assert_eq!((chart.palace.as_str(), chart.palace_element.as_str()), ("Qian (Heaven)", "Metal"));
+    assert_eq!(chart.generation, "1st Generation");
+    assert!(chart.lines[0].shi && chart.lines[3].ying);
+
+    let najia: Vec<(&str, &str, &str)> = chart.lines.iter()
+        .map(|l| (l.stem.as_str(), l.branch.as_str(), l.relative.as_str()))
+        .collect();
+    assert_eq!(najia, vec![
+        ("Xin", "Chou (Ox)", "Parents"), ("Xin", "Hai (Pig)", "Offspring"), ("Xin", "You (Rooster)", "Brothers"),
+        ("Ren", "Wu (Horse)", "Officer"), ("Ren", "Shen (Monkey)", "Brothers"), ("Ren", "Xu (Dog)", "Parents"),
+    ]);
+    assert_eq!(chart.missing_relatives, vec!["Wealth"]);
+    assert!(chart.changed_number.is_none());
+
+    // 2000-01-01 is Wu Wu: the Jia Yin decade leaves Zi and Chou void, and the spirits start at Gou Chen
+    assert_eq!(chart.day_pillar, "Wu Wu (Horse)");
+    assert_eq!(chart.void_branches, vec!["Zi (Rat)", "Chou (Ox)"]);
+    assert!(chart.lines[0].void);
+    assert_eq!(chart.lines[0].spirit, "Gou Chen (Hook)");
+    // The Zi month clashes the Horse line
+    assert_eq!(chart.month_branch, "Zi (Rat)");
+    assert!(chart.lines[3].month_break);
+}
+
+#[test]
+fn test_moving_line_analysis() {
+    // Old Yin at the bottom turns Gou into Qian (1): Xin Chou becomes Jia Zi, a six combination
+    let chart = build_chart([6, 7, 7, 7, 7, 7], noon(2000, 1, 1));
+    assert_eq!(chart.changed_number, Some(1));
+    let first = &chart.lines[0];
+    assert!(first.moving);
+    let changed = first.changed.as_ref().unwrap();
+    assert_eq!((changed.stem.as_str(), changed.branch.as_str(), changed.relative.as_str()), ("Jia", "Zi (Rat)", "Offspring"));
+    assert!(first.notes.iter().any(|n| n.contains("Hua He")), "{:?}", first.notes);
+    assert!(first.notes.iter().any(|n| n == "Transforms into a void"), "{:?}", first.notes);
+    assert!(chart.lines[1..].iter().all(|l| l.changed.is_none()));
+}
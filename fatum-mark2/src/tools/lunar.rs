@@ -0,0 +1,193 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// The Chinese calendar is reckoned at the 120°E meridian (UTC+8).
+const TIME_ZONE: f64 = 8.0;
+
+/// Mean length of a lunation in days.
+const SYNODIC_MONTH: f64 = 29.530588853;
+
+/// Julian Day of the new moon numbered 0 (1900-01-01 13:52 UT), the epoch of `new_moon`.
+const NEW_MOON_EPOCH: f64 = 2415021.076998695;
+
+/// Offset from chrono's days-since-CE to the Julian Day Number.
+const JDN_CE_OFFSET: i64 = 1721425;
+
+/// A date in the Chinese lunisolar calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LunarDate {
+    /// Lunar year, which starts at Chinese New Year (not Li Chun).
+    pub year: i32,
+    /// 1-12; a leap month repeats the number of the month before it.
+    pub month: u32,
+    /// 1-30.
+    pub day: u32,
+    pub leap: bool,
+}
+
+impl LunarDate {
+    /// Converts a Gregorian date (China Standard Time).
+    pub fn from_solar(date: NaiveDate) -> LunarDate {
+        let day_number = jdn(date);
+        let k = ((day_number as f64 - NEW_MOON_EPOCH) / SYNODIC_MONTH).floor() as i64;
+        let mut month_start = new_moon_day(k + 1);
+        if month_start > day_number {
+            month_start = new_moon_day(k);
+        }
+
+        // Months are counted from the 11th month (the one holding the winter solstice)
+        let mut a11 = lunar_month_11(date.year());
+        let mut b11 = a11;
+        let mut year;
+        if a11 >= month_start {
+            year = date.year();
+            a11 = lunar_month_11(date.year() - 1);
+        } else {
+            year = date.year() + 1;
+            b11 = lunar_month_11(date.year() + 1);
+        }
+
+        let day = (day_number - month_start + 1) as u32;
+        let diff = ((month_start - a11) as f64 / 29.0).floor() as i64;
+        let mut leap = false;
+        let mut month = diff + 11;
+        // Thirteen new moons between the solstice months: one of them is a leap month
+        if b11 - a11 > 365 {
+            let leap_offset = leap_month_offset(a11);
+            if diff >= leap_offset {
+                month = diff + 10;
+                leap = diff == leap_offset;
+            }
+        }
+        if month > 12 {
+            month -= 12;
+        }
+        if month >= 11 && diff < 4 {
+            year -= 1;
+        }
+        LunarDate { year, month: month as u32, day, leap }
+    }
+
+    /// The Gregorian date, or `None` if this lunar date does not exist
+    /// (a leap month the year does not have, or a day past the month's end).
+    pub fn to_solar(&self) -> Option<NaiveDate> {
+        if !(1..=12).contains(&self.month) || !(1..=30).contains(&self.day) {
+            return None;
+        }
+        let (a11, b11) = if self.month < 11 {
+            (lunar_month_11(self.year - 1), lunar_month_11(self.year))
+        } else {
+            (lunar_month_11(self.year), lunar_month_11(self.year + 1))
+        };
+        let k = (0.5 + (a11 as f64 - NEW_MOON_EPOCH) / SYNODIC_MONTH).floor() as i64;
+        let mut offset = (self.month as i64 - 11).rem_euclid(12);
+        if b11 - a11 > 365 {
+            let leap_offset = leap_month_offset(a11);
+            let leap_month = (leap_offset - 2).rem_euclid(12);
+            if self.leap && self.month as i64 != leap_month {
+                return None;
+            }
+            if self.leap || offset >= leap_offset {
+                offset += 1;
+            }
+        } else if self.leap {
+            return None;
+        }
+        let month_start = new_moon_day(k + offset);
+        let date = from_jdn(month_start + self.day as i64 - 1)?;
+        // Day 30 of a 29-day month lands on the next month
+        (LunarDate::from_solar(date) == *self).then_some(date)
+    }
+
+    /// e.g. "Lunar 2023, Leap Month 2, Day 1".
+    pub fn label(&self) -> String {
+        format!("Lunar {}, {}Month {}, Day {}", self.year, if self.leap { "Leap " } else { "" }, self.month, self.day)
+    }
+}
+
+fn jdn(date: NaiveDate) -> i64 {
+    date.num_days_from_ce() as i64 + JDN_CE_OFFSET
+}
+
+fn from_jdn(jdn: i64) -> Option<NaiveDate> {
+    NaiveDate::from_num_days_from_ce_opt(i32::try_from(jdn - JDN_CE_OFFSET).ok()?)
+}
+
+/// Julian Day (UT) of the `k`th new moon after 1900-01-01 (Meeus, "Astronomical Algorithms", ch. 49).
+pub fn new_moon(k: i64) -> f64 {
+    let k = k as f64;
+    let t = k / 1236.85;
+    let (t2, t3) = (t * t, t * t * t);
+    let dr = PI / 180.0;
+    let mut jd = 2415020.75933 + 29.53058868 * k + 0.0001178 * t2 - 0.000000155 * t3;
+    jd += 0.00033 * ((166.56 + 132.87 * t - 0.009173 * t2) * dr).sin();
+    // Mean anomalies of the Sun and Moon, and the Moon's argument of latitude
+    let m = (359.2242 + 29.10535608 * k - 0.0000333 * t2 - 0.00000347 * t3) * dr;
+    let mpr = (306.0253 + 385.81691806 * k + 0.0107306 * t2 + 0.00001236 * t3) * dr;
+    let f = (21.2964 + 390.67050646 * k - 0.0016528 * t2 - 0.00000239 * t3) * dr;
+    let c1 = (0.1734 - 0.000393 * t) * m.sin() + 0.0021 * (2.0 * m).sin() - 0.4068 * mpr.sin()
+        + 0.0161 * (2.0 * mpr).sin() - 0.0004 * (3.0 * mpr).sin() + 0.0104 * (2.0 * f).sin()
+        - 0.0051 * (m + mpr).sin() - 0.0074 * (m - mpr).sin() + 0.0004 * (2.0 * f + m).sin()
+        - 0.0004 * (2.0 * f - m).sin() - 0.0006 * (2.0 * f + mpr).sin()
+        + 0.0010 * (2.0 * f - mpr).sin() + 0.0005 * (2.0 * mpr + m).sin();
+    // Delta T (terrestrial minus universal time), in days
+    let delta_t = if t < -11.0 {
+        0.001 + 0.000839 * t + 0.0002261 * t2 - 0.00000845 * t3 - 0.000000081 * t * t3
+    } else {
+        -0.000278 + 0.000265 * t + 0.000262 * t2
+    };
+    jd + c1 - delta_t
+}
+
+/// Day number (local midnight-based JDN) the `k`th new moon falls on.
+fn new_moon_day(k: i64) -> i64 {
+    (new_moon(k) + 0.5 + TIME_ZONE / 24.0).floor() as i64
+}
+
+/// Sun's apparent longitude in radians at Julian Day `jd`.
+fn sun_longitude(jd: f64) -> f64 {
+    let t = (jd - 2451545.0) / 36525.0;
+    let t2 = t * t;
+    let dr = PI / 180.0;
+    let m = 357.52910 + 35999.05030 * t - 0.0001559 * t2 - 0.00000048 * t * t2;
+    let l0 = 280.46645 + 36000.76983 * t + 0.0003032 * t2;
+    let dl = (1.914600 - 0.004817 * t - 0.000014 * t2) * (dr * m).sin()
+        + (0.019993 - 0.000101 * t) * (dr * 2.0 * m).sin()
+        + 0.000290 * (dr * 3.0 * m).sin();
+    ((l0 + dl) * dr).rem_euclid(2.0 * PI)
+}
+
+/// Principal term (Zhong Qi) segment, 0-11, the Sun is in at the start of a local day.
+fn sun_segment(day_number: i64) -> i64 {
+    (sun_longitude(day_number as f64 - 0.5 - TIME_ZONE / 24.0) / PI * 6.0).floor() as i64
+}
+
+/// Start day of the 11th lunar month of a Gregorian year (the month holding the winter solstice).
+fn lunar_month_11(year: i32) -> i64 {
+    let off = jdn(NaiveDate::from_ymd_opt(year, 12, 31).unwrap()) - 2415021;
+    let k = (off as f64 / SYNODIC_MONTH).floor() as i64;
+    let nm = new_moon_day(k);
+    // Past 270° already: that new moon starts the 12th month
+    if sun_segment(nm) >= 9 { new_moon_day(k - 1) } else { nm }
+}
+
+/// Months after the 11th month at which the leap month falls: the first month with no principal term.
+fn leap_month_offset(a11: i64) -> i64 {
+    let k = ((a11 as f64 - NEW_MOON_EPOCH) / SYNODIC_MONTH + 0.5).floor() as i64;
+    let mut i = 1;
+    let mut arc = sun_segment(new_moon_day(k + i));
+    loop {
+        let last = arc;
+        i += 1;
+        arc = sun_segment(new_moon_day(k + i));
+        if arc == last || i >= 14 {
+            break;
+        }
+    }
+    i - 1
+}
+
+#[cfg(test)]
+#[path = "lunar_tests.rs"]
+mod tests;
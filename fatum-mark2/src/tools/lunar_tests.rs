@@ -0,0 +1,55 @@
+use chrono::NaiveDate;
+
+use super::LunarDate;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn lunar(year: i32, month: u32, day: u32, leap: bool) -> LunarDate {
+    LunarDate { year, month, day, leap }
+}
+
+#[test]
+fn test_new_years_and_festivals() {
+    assert_eq!(LunarDate::from_solar(date(2024, 2, 10)), lunar(2024, 1, 1, false));
+    assert_eq!(LunarDate::from_solar(date(2025, 1, 29)), lunar(2025, 1, 1, false));
+    assert_eq!(LunarDate::from_solar(date(1985, 2, 20)), lunar(1985, 1, 1, false));
+    // The day before New Year still belongs to the previous lunar year
+    assert_eq!(LunarDate::from_solar(date(2025, 1, 28)), lunar(2024, 12, 29, false));
+    // Mid-Autumn Festival 2024
+    assert_eq!(LunarDate::from_solar(date(2024, 9, 17)), lunar(2024, 8, 15, false));
+}
+
+#[test]
+fn test_leap_months() {
+    // 2023 repeated the 2nd month, 2020 the 4th and 2017 the 6th
+    assert_eq!(LunarDate::from_solar(date(2023, 3, 21)), lunar(2023, 2, 30, false));
+    assert_eq!(LunarDate::from_solar(date(2023, 3, 22)), lunar(2023, 2, 1, true));
+    assert_eq!(LunarDate::from_solar(date(2023, 4, 20)), lunar(2023, 3, 1, false));
+    assert_eq!(LunarDate::from_solar(date(2020, 5, 23)), lunar(2020, 4, 1, true));
+    assert_eq!(LunarDate::from_solar(date(2017, 6, 24)), lunar(2017, 6, 1, false));
+    assert_eq!(LunarDate::from_solar(date(2017, 7, 23)), lunar(2017, 6, 1, true));
+    assert_eq!(lunar(2023, 2, 1, true).label(), "Lunar 2023, Leap Month 2, Day 1");
+}
+
+#[test]
+fn test_round_trip() {
+    let mut day = date(1950, 1, 1);
+    while day < date(2050, 1, 1) {
+        let l = LunarDate::from_solar(day);
+        assert_eq!(l.to_solar(), Some(day), "{} -> {:?}", day, l);
+        day += chrono::Duration::days(13);
+    }
+}
+
+#[test]
+fn test_nonexistent_lunar_dates() {
+    // 2024 has no leap month, and no leap 3rd month in 2023
+    assert_eq!(lunar(2024, 5, 1, true).to_solar(), None);
+    assert_eq!(lunar(2023, 3, 1, true).to_solar(), None);
+    assert_eq!(lunar(2024, 13, 1, false).to_solar(), None);
+    // The 12th month of 2024 had 29 days
+    assert_eq!(lunar(2024, 12, 30, false).to_solar(), None);
+    assert_eq!(lunar(2023, 2, 1, true).to_solar(), Some(date(2023, 3, 22)));
+}
@@ -1,10 +1,34 @@
 pub mod feng_shui;
+pub mod ba_zhai;
+pub mod bazi_strength;
+pub mod bazi_luck;
+pub mod ten_gods;
+pub mod lunar;
+pub mod da_gua;
+pub mod floor_plan;
+pub mod cures;
+pub mod qi_flow;
+pub mod period_transition;
+pub mod advice;
+pub mod property_compare;
+pub mod i18n;
 pub mod astronomy;
+pub mod astrology_western;
+pub mod astrology_reports;
+pub mod astrology_vedic;
 pub mod san_he;
 pub mod qimen;
+pub mod qimen_directions;
 pub mod divination;
+pub mod liu_yao;
+pub mod tarot;
+pub mod runes;
+pub mod numerology;
+pub mod geolocation;
+pub mod decision;
 pub mod pdf_generator;
 pub mod zi_wei;
+pub mod zi_wei_limits;
 pub mod ze_ri;
 pub mod da_liu_ren;
 pub mod chinese_meta;
@@ -12,3 +36,5 @@ pub mod entanglement;
 
 #[cfg(test)]
 mod feng_shui_tests;
+#[cfg(test)]
+mod test_fixtures;
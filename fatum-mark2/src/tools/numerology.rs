@@ -0,0 +1,199 @@
+use anyhow::{bail, Result};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::SimulationSession;
+
+/// Master numbers, which are not reduced further.
+pub const MASTER_NUMBERS: [u32; 3] = [11, 22, 33];
+
+/// Entropy draws in a resonance simulation.
+pub const RESONANCE_TRIALS: usize = 999;
+
+/// Keyword meanings for 1-9 and the master numbers.
+const MEANINGS: [(u32, &str); 12] = [
+    (1, "Leadership, independence, new beginnings"),
+    (2, "Cooperation, diplomacy, sensitivity"),
+    (3, "Expression, creativity, joy"),
+    (4, "Stability, discipline, hard work"),
+    (5, "Freedom, change, adventure"),
+    (6, "Responsibility, nurture, harmony"),
+    (7, "Introspection, wisdom, spirituality"),
+    (8, "Ambition, power, material success"),
+    (9, "Compassion, completion, humanitarianism"),
+    (11, "Master Intuitive: inspiration and illumination"),
+    (22, "Master Builder: vision made real"),
+    (33, "Master Teacher: selfless guidance"),
+];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NumerologyConfig {
+    /// Full birth name; falls back to the profile's name.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Falls back to the profile's birth date.
+    #[serde(default)]
+    pub birth_date: Option<NaiveDate>,
+    #[serde(default)]
+    pub profile_id: Option<i64>,
+    /// Day the Personal Year and Month are for; today if omitted.
+    #[serde(default)]
+    pub target_date: Option<NaiveDate>,
+    /// Run the quantum resonance simulation (draws entropy from the beacon).
+    #[serde(default)]
+    pub resonance: bool,
+}
+
+/// A core number and what it stands for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoreNumber {
+    pub value: u32,
+    pub master: bool,
+    pub meaning: String,
+}
+
+/// How often the entropy stream landed on each core number's root digit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resonance {
+    pub trials: usize,
+    /// Hits per digit 1-9.
+    pub digit_counts: [usize; 9],
+    /// Hits on the core numbers' roots relative to chance; 1.0 is neutral.
+    pub score: f64,
+    /// The core number whose root digit came up most often.
+    pub strongest: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumerologyReport {
+    pub name: String,
+    pub birth_date: NaiveDate,
+    pub target_date: NaiveDate,
+    pub life_path: CoreNumber,
+    pub expression: CoreNumber,
+    pub soul_urge: CoreNumber,
+    pub personal_year: CoreNumber,
+    pub personal_month: CoreNumber,
+    pub resonance: Option<Resonance>,
+}
+
+/// Sums digits until one digit or a master number is left.
+pub fn reduce(mut n: u32) -> u32 {
+    while n > 9 && !MASTER_NUMBERS.contains(&n) {
+        n = digit_sum(n);
+    }
+    n
+}
+
+fn digit_sum(mut n: u32) -> u32 {
+    let mut sum = 0;
+    while n > 0 {
+        sum += n % 10;
+        n /= 10;
+    }
+    sum
+}
+
+/// Pythagorean value of a letter (A=1 ... I=9, J=1 ...); `None` for anything else.
+pub fn letter_value(c: char) -> Option<u32> {
+    let c = c.to_ascii_uppercase();
+    c.is_ascii_uppercase().then(|| (c as u32 - 'A' as u32) % 9 + 1)
+}
+
+/// Y counts as a consonant.
+fn is_vowel(c: char) -> bool {
+    matches!(c.to_ascii_uppercase(), 'A' | 'E' | 'I' | 'O' | 'U')
+}
+
+fn core(value: u32) -> CoreNumber {
+    CoreNumber {
+        value,
+        master: MASTER_NUMBERS.contains(&value),
+        meaning: MEANINGS.iter().find(|(n, _)| *n == value).map_or("", |m| m.1).to_string(),
+    }
+}
+
+/// Month, day and year are reduced separately before being added, so master numbers survive.
+pub fn life_path(birth: NaiveDate) -> u32 {
+    reduce(reduce(birth.month()) + reduce(birth.day()) + reduce(birth.year() as u32))
+}
+
+/// Sum of every letter in the name.
+pub fn expression(name: &str) -> u32 {
+    reduce(name.chars().filter_map(letter_value).sum())
+}
+
+/// Sum of the vowels in the name.
+pub fn soul_urge(name: &str) -> u32 {
+    reduce(name.chars().filter(|&c| is_vowel(c)).filter_map(letter_value).sum())
+}
+
+/// Birth month and day with the calendar year; the cycle turns over on 1 January.
+pub fn personal_year(birth: NaiveDate, on: NaiveDate) -> u32 {
+    reduce(reduce(birth.month()) + reduce(birth.day()) + reduce(on.year() as u32))
+}
+
+pub fn personal_month(birth: NaiveDate, on: NaiveDate) -> u32 {
+    reduce(personal_year(birth, on) + on.month())
+}
+
+/// Roots of master numbers: 11 -> 2, 22 -> 4, 33 -> 6.
+fn root(n: u32) -> u32 {
+    if n > 9 { digit_sum(n) } else { n }
+}
+
+/// Draws `RESONANCE_TRIALS` digits 1-9 and measures how strongly they favour the chart's numbers.
+pub fn simulate_resonance(session: &mut SimulationSession, numbers: &[(&str, u32)]) -> Resonance {
+    let mut digit_counts = [0usize; 9];
+    for _ in 0..RESONANCE_TRIALS {
+        digit_counts[session.next_u64_in_range(0, 9) as usize] += 1;
+    }
+
+    let mut roots: Vec<u32> = numbers.iter().map(|&(_, n)| root(n)).collect();
+    roots.sort_unstable();
+    roots.dedup();
+    let hits: usize = roots.iter().map(|&r| digit_counts[r as usize - 1]).sum();
+    let expected = RESONANCE_TRIALS as f64 * roots.len() as f64 / 9.0;
+    let strongest = numbers.iter()
+        .max_by_key(|&&(_, n)| digit_counts[root(n) as usize - 1])
+        .map_or(String::new(), |&(label, n)| format!("{} {}", label, n));
+
+    Resonance { trials: RESONANCE_TRIALS, digit_counts, score: hits as f64 / expected, strongest }
+}
+
+/// Computes the core numbers; `session` runs the resonance simulation when given.
+pub fn calculate_numerology(
+    name: &str,
+    birth_date: NaiveDate,
+    target_date: NaiveDate,
+    session: Option<&mut SimulationSession>,
+) -> Result<NumerologyReport> {
+    if !name.chars().any(|c| letter_value(c).is_some()) {
+        bail!("Name must contain at least one letter A-Z");
+    }
+
+    let numbers = [
+        ("Life Path", life_path(birth_date)),
+        ("Expression", expression(name)),
+        ("Soul Urge", soul_urge(name)),
+        ("Personal Year", personal_year(birth_date, target_date)),
+        ("Personal Month", personal_month(birth_date, target_date)),
+    ];
+    let resonance = session.map(|s| simulate_resonance(s, &numbers));
+
+    Ok(NumerologyReport {
+        name: name.to_string(),
+        birth_date,
+        target_date,
+        life_path: core(numbers[0].1),
+        expression: core(numbers[1].1),
+        soul_urge: core(numbers[2].1),
+        personal_year: core(numbers[3].1),
+        personal_month: core(numbers[4].1),
+        resonance,
+    })
+}
+
+#[cfg(test)]
+#[path = "numerology_tests.rs"]
+mod tests;
@@ -0,0 +1,42 @@
+use chrono::NaiveDate;
+
+use super::{calculate_numerology, expression, life_path, personal_month, personal_year, reduce, soul_urge, RESONANCE_TRIALS};
+use crate::engine::SimulationSession;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_reduce_keeps_master_numbers() {
+    assert_eq!(reduce(44), 8);
+    assert_eq!(reduce(29), 11);
+    assert_eq!(reduce(1987), 7);
+    assert_eq!(reduce(33), 33);
+}
+
+#[test]
+fn test_core_numbers() {
+    // 7 + (2+6) + (1+9+8+7 -> 7) = 22
+    assert_eq!(life_path(date(1987, 7, 26)), 22);
+    // JOHN = 1+6+8+5, SMITH = 1+4+9+2+8: 44 -> 8; vowels O + I = 15 -> 6
+    assert_eq!(expression("John Smith"), 8);
+    assert_eq!(soul_urge("John Smith"), 6);
+    // 7 + 8 + (2024 -> 8) = 23 -> 5, then March: 5 + 3 = 8
+    assert_eq!(personal_year(date(1987, 7, 26), date(2024, 3, 1)), 5);
+    assert_eq!(personal_month(date(1987, 7, 26), date(2024, 3, 1)), 8);
+}
+
+#[test]
+fn test_report_and_resonance() {
+    let mut session = SimulationSession::new((0..=255).cycle().take(4096).collect());
+    let report = calculate_numerology("John Smith", date(1987, 7, 26), date(2024, 3, 1), Some(&mut session)).unwrap();
+    assert!(report.life_path.master);
+    assert!(!report.expression.meaning.is_empty());
+
+    let resonance = report.resonance.unwrap();
+    assert_eq!(resonance.digit_counts.iter().sum::<usize>(), RESONANCE_TRIALS);
+    assert!(resonance.score >= 0.0);
+
+    assert!(calculate_numerology("1234", date(1987, 7, 26), date(2024, 3, 1), None).is_err());
+}
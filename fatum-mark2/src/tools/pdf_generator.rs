@@ -32,6 +32,45 @@ pub fn generate_pdf(report: &FengShuiReport) -> Result<Vec<u8>> {
              .element(elements::Paragraph::new(&bazi.hour_pillar))
              .push().expect("Invalid table");
         doc.push(table);
+        if let Some(strength) = &bazi.strength {
+            doc.push(elements::Paragraph::new(format!(
+                "Day Master {} ({}): {} at {:.1}% support, {}",
+                bazi.day_master, strength.day_master_element, strength.verdict, strength.score, strength.season
+            )));
+            doc.push(elements::Paragraph::new(format!(
+                "Useful: {} | Unfavorable: {}",
+                strength.useful_elements.join(", "), strength.unfavorable_elements.join(", ")
+            )));
+        }
+        if let Some(gods) = &bazi.ten_gods {
+            let mut table = elements::TableLayout::new(vec![1, 2, 3]);
+            table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+            table.row().element(elements::Paragraph::new("Pillar")).element(elements::Paragraph::new("Stem God"))
+                 .element(elements::Paragraph::new("Hidden Stems")).push().expect("Invalid table");
+            for pillar in ["year", "month", "day", "hour"] {
+                let of = |hidden: bool| -> Vec<String> {
+                    gods.placements.iter().filter(|p| p.pillar == pillar && p.hidden == hidden)
+                        .map(|p| format!("{} {}", p.stem, p.god)).collect()
+                };
+                let stem = of(false).pop().unwrap_or_else(|| "Day Master".to_string());
+                table.row().element(elements::Paragraph::new(pillar))
+                     .element(elements::Paragraph::new(stem))
+                     .element(elements::Paragraph::new(of(true).join(", ")))
+                     .push().expect("Invalid table");
+            }
+            doc.push(table);
+            for area in &gods.areas {
+                doc.push(elements::Paragraph::new(format!("{}: {}", area.area.to_uppercase(), area.reading)));
+            }
+        }
+        if !bazi.luck_pillars.is_empty() {
+            let luck: Vec<String> = bazi.luck_pillars.iter().map(|p| format!("{} ({})", p.pillar, p.start_age)).collect();
+            doc.push(elements::Paragraph::new(format!("Luck Pillars ({}): {}", bazi.luck_direction.as_deref().unwrap_or(""), luck.join(", "))));
+        }
+        if !bazi.annual_pillars.is_empty() {
+            let years: Vec<String> = bazi.annual_pillars.iter().map(|p| format!("{} {}", p.year, p.pillar)).collect();
+            doc.push(elements::Paragraph::new(format!("Annual Pillars: {}", years.join(", "))));
+        }
         doc.push(elements::Break::new(1.0));
     }
 
@@ -64,13 +103,69 @@ pub fn generate_pdf(report: &FengShuiReport) -> Result<Vec<u8>> {
     if let Some(sh) = &report.san_he {
         doc.push(elements::Break::new(1.0));
         doc.push(elements::Paragraph::new("SAN HE WATER METHOD").styled(style::Style::new().bold()));
-        doc.push(elements::Paragraph::new(format!("Method: {}", sh.water_method)));
-        doc.push(elements::Paragraph::new("Warnings:"));
+        doc.push(elements::Paragraph::new(format!("Method: {} - sitting {}, {}", sh.water_method, sh.sitting_mountain, sh.frame)));
+        if let Some(exit) = &sh.water_exit {
+            doc.push(elements::Paragraph::new(format!("Water exit {:.1}° ({}, {}): {}", exit.degrees, exit.mountain, exit.phase, exit.note)));
+        }
+        for w in &sh.warnings {
+            doc.push(elements::Paragraph::new(format!("WARNING {} in {} ({:.1}°): {}", w.kind, w.mountain, w.degrees, w.message)));
+        }
+        doc.push(elements::Paragraph::new("Auspicious water exits:"));
         for w in &sh.lucky_water_exit {
             doc.push(elements::Paragraph::new(format!("- {}", w)));
         }
     }
 
+    // Ba Zhai
+    if let Some(bz) = &report.ba_zhai {
+        doc.push(elements::Break::new(1.0));
+        doc.push(elements::Paragraph::new("EIGHT MANSIONS (BA ZHAI)").styled(style::Style::new().bold()));
+        let mut heading = format!("House Kua {} ({})", bz.house_kua, bz.house_group);
+        if let (Some(kua), Some(group)) = (bz.person_kua, &bz.person_group) {
+            heading.push_str(&format!(" | Personal Kua {} ({})", kua, group));
+        }
+        doc.push(elements::Paragraph::new(heading));
+        let mut table = elements::TableLayout::new(vec![1, 2, 2, 1]);
+        table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+        table.row().element(elements::Paragraph::new("Sector")).element(elements::Paragraph::new("House"))
+             .element(elements::Paragraph::new("Personal")).element(elements::Paragraph::new("Score")).push().expect("Invalid table");
+        for sec in &bz.sectors {
+            table.row().element(elements::Paragraph::new(&sec.sector))
+                 .element(elements::Paragraph::new(&sec.house_star.name))
+                 .element(elements::Paragraph::new(sec.person_star.as_ref().map(|s| s.name.as_str()).unwrap_or("-")))
+                 .element(elements::Paragraph::new(sec.score.to_string()))
+                 .push().expect("Invalid table");
+        }
+        doc.push(table);
+    }
+
+    // Advice
+    if !report.advice.is_empty() {
+        doc.push(elements::Break::new(1.0));
+        doc.push(elements::Paragraph::new("ADVICE").styled(style::Style::new().bold()));
+        let mut advice: Vec<_> = report.advice.iter().collect();
+        advice.sort_by_key(|a| std::cmp::Reverse(a.severity));
+        for a in advice {
+            let severity = format!("{:?}", a.severity).to_uppercase();
+            let sector = a.sector.as_deref().map(|s| format!(" {}", s)).unwrap_or_default();
+            doc.push(elements::Paragraph::new(format!("[{}]{} {}", severity, sector, a.explanation)));
+            doc.push(elements::Paragraph::new(format!("    {}", a.action)));
+        }
+    }
+
+    // Rooms
+    if !report.rooms.is_empty() {
+        doc.push(elements::Break::new(1.0));
+        doc.push(elements::Paragraph::new("ROOMS").styled(style::Style::new().bold()));
+        for room in &report.rooms {
+            let sectors: Vec<&str> = room.palaces.iter().map(|p| p.sector.as_str()).collect();
+            doc.push(elements::Paragraph::new(format!("{} ({}): {}", room.name, sectors.join(", "), room.rating)));
+            for a in &room.advice {
+                doc.push(elements::Paragraph::new(format!("- {}", a)));
+            }
+        }
+    }
+
     let mut buffer = Vec::new();
     doc.render(&mut buffer)?;
     Ok(buffer)
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tools::ba_zhai::SECTORS;
+use crate::tools::feng_shui::{calculate_flying_star_chart, FlyingStarChart};
+use crate::tools::floor_plan::{is_afflicted, is_timely, star_name};
+use crate::tools::qi_flow::facing_sector;
+
+/// The period the transition moves into, and the first year a house is built (or re-roofed) in it.
+pub const TARGET_PERIOD: i32 = 9;
+pub const TARGET_PERIOD_START: i32 = 2024;
+
+/// Score a change has to gain over keeping the house as it is to be worth the work.
+const MIN_GAIN: f64 = 2.0;
+
+/// Points for a timely or afflicted star in its own half of the house: water at the front, mountain at the back.
+const WATER_WEIGHT: f64 = 2.0;
+const MOUNTAIN_WEIGHT: f64 = 1.0;
+/// Points for timely stars in their own palaces (water at the facing, mountain at the sitting).
+const KEY_PALACE_WEIGHT: f64 = 3.0;
+
+/// One way of carrying the house into Period 9, scored against the Period 9 stars.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionOption {
+    /// "keep", "re-roof" or "change facing".
+    pub action: String,
+    pub facing_degrees: f64,
+    pub chart: FlyingStarChart,
+    pub score: f64,
+    /// Score gained over keeping the natal chart; 0 for "keep".
+    pub differential: f64,
+    pub notes: Vec<String>,
+}
+
+/// Whether a Period 7/8 house should be re-roofed, re-faced or left alone for Period 9.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitionPlan {
+    pub natal_period: i32,
+    pub target_period: i32,
+    /// "keep" first, then "re-roof", then the other three facings.
+    pub options: Vec<TransitionOption>,
+    /// Action of the recommended option.
+    pub recommendation: String,
+    pub summary: String,
+}
+
+/// How much a star is worth in Period 9: the 9 in full, the coming 1 by half, the 2 and 5 against.
+fn timeliness(star: i32) -> f64 {
+    match star {
+        9 => 1.0,
+        1 => 0.5,
+        2 | 5 => -1.0,
+        _ => 0.0,
+    }
+}
+
+/// Where a palace lies relative to the facing: 1.0 at the front (the facing palace and the
+/// two beside it), 0.5 on the sides and in the Center, and -0.5 at the back.
+fn frontness(sector: &str, facing: &str) -> f64 {
+    let index = |s: &str| SECTORS.iter().position(|x| *x == s);
+    let (Some(at), Some(front)) = (index(sector), index(facing)) else { return 0.5 };
+    match (at + 8 - front) % 8 {
+        0 | 1 | 7 => 1.0,
+        2 | 6 => 0.5,
+        _ => -0.5,
+    }
+}
+
+/// How a chart's natal stars serve the house in Period 9, with the findings behind the score.
+///
+/// Water stars count at the front of the house, where the door and open space activate them,
+/// and mountain stars at the back (double weight for water). A timely water star at the facing
+/// or mountain star at the sitting palace adds a bonus, and the two swapped ("up the mountain,
+/// down the water") lose it. Visiting stars are left out, as they are the same for every option.
+pub fn score_chart(chart: &FlyingStarChart, facing_degrees: f64) -> (f64, Vec<String>) {
+    let facing = facing_sector(facing_degrees);
+    let sitting = facing_sector(facing_degrees + 180.0);
+    let mut score = 0.0;
+    let mut notes = Vec::new();
+    for p in &chart.palaces {
+        let front = frontness(&p.sector, facing);
+        score += WATER_WEIGHT * timeliness(p.water_star) * front + MOUNTAIN_WEIGHT * timeliness(p.mountain_star) * (0.5 - front);
+        if p.sector == facing {
+            if is_timely(p.water_star, TARGET_PERIOD) {
+                score += KEY_PALACE_WEIGHT;
+                notes.push(format!("Timely {} at the facing palace ({}) brings wealth.", star_name(p.water_star), p.sector));
+            } else if is_timely(p.mountain_star, TARGET_PERIOD) {
+                score -= KEY_PALACE_WEIGHT;
+                notes.push(format!("Timely Mountain Star {} is stuck at the facing palace ({}).", p.mountain_star, p.sector));
+            }
+            if is_afflicted(p.water_star) {
+                notes.push(format!("{} as the facing Water Star drains the entrance.", star_name(p.water_star)));
+            }
+        }
+        if p.sector == sitting {
+            if is_timely(p.mountain_star, TARGET_PERIOD) {
+                score += KEY_PALACE_WEIGHT;
+                notes.push(format!("Timely {} at the sitting palace ({}) supports health.", star_name(p.mountain_star), p.sector));
+            } else if is_timely(p.water_star, TARGET_PERIOD) {
+                score -= KEY_PALACE_WEIGHT;
+                notes.push(format!("Timely Water Star {} is stuck at the sitting palace ({}).", p.water_star, p.sector));
+            }
+        }
+    }
+    (score, notes)
+}
+
+/// Compares keeping the natal chart with re-roofing (a Period 9 chart on the same facing)
+/// and with moving the main facing to each of the other three sides of the house.
+///
+/// A change is only recommended if it gains at least `MIN_GAIN` over keeping the house;
+/// the best change wins, with re-roofing preferred on a tie as it keeps the door.
+pub fn plan_transition(construction_year: i32, facing_degrees: f64, current_year: i32) -> TransitionPlan {
+    let natal = calculate_flying_star_chart(construction_year, facing_degrees, current_year, None);
+    let natal_period = natal.period;
+    let mut candidates = vec![("keep", facing_degrees, natal)];
+    candidates.push(("re-roof", facing_degrees, calculate_flying_star_chart(TARGET_PERIOD_START, facing_degrees, current_year, None)));
+    for turn in [90.0, 180.0, 270.0] {
+        let degrees = (facing_degrees + turn).rem_euclid(360.0);
+        candidates.push(("change facing", degrees, calculate_flying_star_chart(TARGET_PERIOD_START, degrees, current_year, None)));
+    }
+
+    let mut options: Vec<TransitionOption> = candidates.into_iter().map(|(action, degrees, chart)| {
+        let (score, notes) = score_chart(&chart, degrees);
+        TransitionOption { action: action.to_string(), facing_degrees: degrees, chart, score, differential: 0.0, notes }
+    }).collect();
+    let base = options[0].score;
+    for option in &mut options {
+        option.differential = option.score - base;
+    }
+
+    let best = options.iter().enumerate().skip(1)
+        .filter(|(_, o)| o.differential >= MIN_GAIN)
+        .max_by(|(i, a), (j, b)| a.differential.total_cmp(&b.differential).then(j.cmp(i)))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let chosen = &options[best];
+    let summary = match chosen.action.as_str() {
+        "keep" if natal_period == TARGET_PERIOD => "The house is already a Period 9 house; keep it as it is.".to_string(),
+        "keep" => format!(
+            "Keep the Period {} chart: no change gains {:.0} points or more over it in Period 9.",
+            natal_period, MIN_GAIN
+        ),
+        "re-roof" => format!(
+            "Re-roof to a Period 9 chart on the same facing ({:+.1} points over the Period {} chart).",
+            chosen.differential, natal_period
+        ),
+        _ => format!(
+            "Move the main facing to {} ({:.1}°) and rebuild in Period 9 ({:+.1} points over the Period {} chart).",
+            facing_sector(chosen.facing_degrees), chosen.facing_degrees, chosen.differential, natal_period
+        ),
+    };
+    TransitionPlan {
+        natal_period,
+        target_period: TARGET_PERIOD,
+        recommendation: chosen.action.clone(),
+        summary,
+        options,
+    }
+}
+
+#[cfg(test)]
+#[path = "period_transition_tests.rs"]
+mod tests;
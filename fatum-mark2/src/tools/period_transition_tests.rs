@@ -0,0 +1,55 @@
+use super::{plan_transition, score_chart, MIN_GAIN};
+use crate::tools::feng_shui::FlyingStarChart;
+use crate::tools::test_fixtures::period_chart;
+
+/// A Period 8 chart with quiet 6s everywhere except the 9s placed as `(water sector, mountain sector)`.
+fn chart(water_9: &str, mountain_9: &str) -> FlyingStarChart {
+    period_chart(8, &[(water_9, 6, 9, 6), (mountain_9, 9, 6, 6)])
+}
+
+#[test]
+fn test_timely_stars_in_their_own_palaces_score_highest() {
+    // Facing South: water 9 at the facing, mountain 9 at the sitting palace
+    let (score, notes) = score_chart(&chart("S", "N"), 180.0);
+    assert!((score - 9.0).abs() < 1e-9);
+    assert_eq!(notes.len(), 2);
+
+    // Up the mountain, down the water
+    let (reversed, notes) = score_chart(&chart("N", "S"), 180.0);
+    assert!((reversed + 7.5).abs() < 1e-9);
+    assert!(notes.iter().all(|n| n.contains("is stuck")));
+}
+
+#[test]
+fn test_plan_compares_every_option_with_keeping_the_house() {
+    let plan = plan_transition(2010, 90.0, 2025);
+    assert_eq!((plan.natal_period, plan.target_period), (8, 9));
+    let actions: Vec<&str> = plan.options.iter().map(|o| o.action.as_str()).collect();
+    assert_eq!(actions, ["keep", "re-roof", "change facing", "change facing", "change facing"]);
+    assert_eq!(plan.options[0].differential, 0.0);
+    for option in &plan.options {
+        assert!((option.differential - (option.score - plan.options[0].score)).abs() < 1e-9);
+    }
+    let facings: Vec<f64> = plan.options.iter().map(|o| o.facing_degrees).collect();
+    assert_eq!(facings, [90.0, 90.0, 180.0, 270.0, 0.0]);
+
+    // The Period 9 chart on the same facing is the best change here
+    assert_eq!(plan.recommendation, "re-roof");
+    assert!(plan.options[1].differential >= MIN_GAIN);
+    assert!(plan.summary.starts_with("Re-roof"));
+}
+
+#[test]
+fn test_small_gains_keep_the_house() {
+    let plan = plan_transition(1995, 45.0, 2025);
+    assert_eq!(plan.natal_period, 7);
+    assert!(plan.options.iter().all(|o| o.differential < MIN_GAIN));
+    assert_eq!(plan.recommendation, "keep");
+    assert!(plan.summary.starts_with("Keep the Period 7 chart"));
+
+    // A Period 9 house re-roofed is the same house
+    let plan = plan_transition(2024, 90.0, 2025);
+    assert_eq!(plan.options[1].differential, 0.0);
+    assert_eq!(plan.recommendation, "keep");
+    assert!(plan.summary.contains("already a Period 9 house"));
+}
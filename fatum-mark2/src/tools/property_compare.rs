@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::SimulationSession;
+use crate::tools::astronomy::{solar_hour, Location};
+use crate::tools::ba_zhai::{analyze_ba_zhai, star_in, STARS};
+use crate::tools::divination::TRIGRAMS;
+use crate::tools::feng_shui::{calculate_bazi, calculate_flying_star_chart, calculate_house_kua, calculate_kua_profile};
+use crate::tools::floor_plan::{analyze_rooms, Room};
+use crate::tools::period_transition::{score_chart, TARGET_PERIOD};
+use crate::tools::qi_flow::facing_sector;
+
+/// Entropy drawn per candidate for its tie-break.
+pub const BYTES_PER_PROPERTY: usize = 8;
+
+/// Points for the occupant's Kua group matching the house's, or not.
+const GROUP_WEIGHT: f64 = 4.0;
+/// Points for the occupant's Ba Zhai star at the facing (the main door) being good or bad.
+const DOOR_WEIGHT: f64 = 2.0;
+/// Points for the house's trigram element being useful or unfavorable to the Day Master.
+const ELEMENT_WEIGHT: f64 = 2.0;
+
+/// One candidate home.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyCandidate {
+    pub name: String,
+    pub construction_year: i32,
+    /// Compass reading of the facing direction (0.0 - 359.9).
+    pub facing_degrees: f64,
+    /// Floor plan, if known; each room's score counts toward the property.
+    #[serde(default)]
+    pub rooms: Option<Vec<Room>>,
+}
+
+/// Several candidate homes for one occupant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyComparisonConfig {
+    #[serde(default)]
+    pub profile_id: Option<i64>,
+    pub birth_year: Option<i32>,
+    pub birth_month: Option<u32>,
+    pub birth_day: Option<u32>,
+    pub birth_hour: Option<u32>,
+    /// "M" or "F", for the Kua number.
+    pub gender: Option<String>,
+    #[serde(default)]
+    pub location: Option<Location>,
+    pub properties: Vec<PropertyCandidate>,
+    /// Year whose stars the homes are judged by (defaults to the current year).
+    #[serde(default)]
+    pub current_year: Option<i32>,
+    /// ID of the entropy batch for the tie-breaks; fetched live if `None`.
+    #[serde(default)]
+    pub entropy_batch_id: Option<i64>,
+}
+
+/// How one candidate scores for the occupant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyScore {
+    pub name: String,
+    /// 1 for the best home.
+    pub rank: usize,
+    pub total: f64,
+    /// The natal chart against the Period 9 stars (see `period_transition::score_chart`).
+    pub period_score: f64,
+    /// Kua group match and the occupant's star at the main door.
+    pub ba_zhai_score: f64,
+    /// The house's element against the Day Master's useful and unfavorable elements.
+    pub bazi_score: f64,
+    /// Sum of the room scores, if a floor plan was given.
+    pub room_score: f64,
+    pub house_kua: i32,
+    /// Whether occupant and house share a Kua group; `None` without the occupant's Kua.
+    pub compatible: Option<bool>,
+    /// Quantum draw (0.0-1.0) that orders homes with the same total, higher first.
+    pub tie_break: f64,
+    /// True if another home has the same total, so the tie-break decided between them.
+    pub tied: bool,
+    pub notes: Vec<String>,
+}
+
+/// Candidate homes ranked for one occupant, best first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyComparison {
+    /// Period the homes are judged in.
+    pub current_period: i32,
+    pub occupant_kua: Option<i32>,
+    /// The Day Master's useful elements, if the birth date was given.
+    pub useful_elements: Vec<String>,
+    pub properties: Vec<PropertyScore>,
+}
+
+/// Scores each home against the occupant's Kua and BaZi and the current period, and ranks them.
+///
+/// Homes are ranked by total; equal totals are ordered by one quantum draw per home, taken in
+/// the order the homes were given.
+pub fn compare_properties(session: &mut SimulationSession, config: &PropertyComparisonConfig) -> Result<PropertyComparison> {
+    let current_year = config.current_year.unwrap_or_else(|| {
+        use chrono::Datelike;
+        chrono::Local::now().year()
+    });
+    let kua = match (config.birth_year, &config.gender) {
+        (Some(y), Some(g)) => Some(calculate_kua_profile(y, g)),
+        _ => None,
+    };
+    let strength = match (config.birth_year, config.birth_month, config.birth_day) {
+        (Some(y), Some(m), Some(d)) => {
+            let (y, m, d, h) = solar_hour(y, m, d, config.birth_hour.unwrap_or(12), config.location.as_ref());
+            calculate_bazi(y, m, d, h, config.gender.as_deref(), None, None).ok().and_then(|b| b.strength)
+        }
+        _ => None,
+    };
+    let person_kua = kua.as_ref().map(|k| k.number);
+
+    let mut scores = Vec::new();
+    for property in &config.properties {
+        let chart = calculate_flying_star_chart(property.construction_year, property.facing_degrees, current_year, None);
+        let (period_score, mut notes) = score_chart(&chart, property.facing_degrees);
+
+        let house = calculate_house_kua(property.facing_degrees + 180.0);
+        let ba_zhai = analyze_ba_zhai(house.number, person_kua, &chart);
+        let compatible = ba_zhai.as_ref().and_then(|b| b.compatible);
+        let mut ba_zhai_score = match compatible {
+            Some(true) => GROUP_WEIGHT,
+            Some(false) => -GROUP_WEIGHT,
+            None => 0.0,
+        };
+        let door = facing_sector(property.facing_degrees);
+        if let Some(star) = person_kua.and_then(|k| star_in(k, door)) {
+            let (name, _, weight) = STARS[star];
+            ba_zhai_score += if weight > 0 { DOOR_WEIGHT } else { -DOOR_WEIGHT };
+            notes.push(format!("The main door faces your {} direction ({}).", name, door));
+        }
+
+        let element = TRIGRAMS.iter().find(|(name, _)| *name == house.element).map(|(_, e)| *e);
+        let bazi_score = match (&strength, element) {
+            (Some(s), Some(e)) if s.useful_elements.iter().any(|u| u == e) => {
+                notes.push(format!("The house's {} supports your Day Master.", e));
+                ELEMENT_WEIGHT
+            }
+            (Some(s), Some(e)) if s.unfavorable_elements.iter().any(|u| u == e) => {
+                notes.push(format!("The house's {} works against your Day Master.", e));
+                -ELEMENT_WEIGHT
+            }
+            _ => 0.0,
+        };
+
+        let room_score = match &property.rooms {
+            Some(rooms) => analyze_rooms(rooms, &chart, kua.as_ref())
+                .with_context(|| format!("property {:?}", property.name))?
+                .iter().map(|r| r.score as f64).sum(),
+            None => 0.0,
+        };
+
+        scores.push(PropertyScore {
+            name: property.name.clone(),
+            rank: 0,
+            total: period_score + ba_zhai_score + bazi_score + room_score,
+            period_score,
+            ba_zhai_score,
+            bazi_score,
+            room_score,
+            house_kua: house.number,
+            compatible,
+            tie_break: session.next_f64(),
+            tied: false,
+            notes,
+        });
+    }
+
+    scores.sort_by(|a, b| b.total.total_cmp(&a.total).then(b.tie_break.total_cmp(&a.tie_break)));
+    let totals: Vec<f64> = scores.iter().map(|s| s.total).collect();
+    for (i, score) in scores.iter_mut().enumerate() {
+        score.rank = i + 1;
+        score.tied = totals.iter().filter(|t| **t == score.total).count() > 1;
+    }
+
+    Ok(PropertyComparison {
+        current_period: TARGET_PERIOD,
+        occupant_kua: person_kua,
+        useful_elements: strength.map(|s| s.useful_elements).unwrap_or_default(),
+        properties: scores,
+    })
+}
+
+#[cfg(test)]
+#[path = "property_compare_tests.rs"]
+mod tests;
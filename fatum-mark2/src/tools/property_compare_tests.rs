@@ -0,0 +1,66 @@
+use super::{compare_properties, PropertyCandidate, PropertyComparisonConfig, BYTES_PER_PROPERTY};
+use crate::engine::SimulationSession;
+use crate::tools::floor_plan::{Room, RoomKind};
+
+fn home(name: &str, facing_degrees: f64) -> PropertyCandidate {
+    PropertyCandidate { name: name.to_string(), construction_year: 2010, facing_degrees, rooms: None }
+}
+
+fn config(properties: Vec<PropertyCandidate>) -> PropertyComparisonConfig {
+    // Female, 1985: Kua 9 (East Group)
+    PropertyComparisonConfig {
+        profile_id: None,
+        birth_year: Some(1985),
+        birth_month: Some(6),
+        birth_day: Some(15),
+        birth_hour: Some(10),
+        gender: Some("F".to_string()),
+        location: None,
+        properties,
+        current_year: Some(2025),
+        entropy_batch_id: None,
+    }
+}
+
+fn session(homes: usize) -> SimulationSession {
+    SimulationSession::new((0..homes * BYTES_PER_PROPERTY).map(|i| (i * 37 % 251) as u8).collect())
+}
+
+#[test]
+fn test_homes_in_the_occupants_group_rank_higher() {
+    // Facing South sits North (Kan, East Group); facing East sits West (Dui, West Group)
+    let report = compare_properties(&mut session(2), &config(vec![home("West flat", 90.0), home("South flat", 180.0)])).unwrap();
+    assert_eq!(report.occupant_kua, Some(9));
+    assert_eq!(report.current_period, 9);
+    assert!(!report.useful_elements.is_empty());
+
+    let south = report.properties.iter().find(|p| p.name == "South flat").unwrap();
+    let west = report.properties.iter().find(|p| p.name == "West flat").unwrap();
+    assert_eq!((south.house_kua, south.compatible), (1, Some(true)));
+    assert_eq!((west.house_kua, west.compatible), (7, Some(false)));
+    assert!(south.ba_zhai_score > west.ba_zhai_score);
+    for p in &report.properties {
+        assert!((p.total - (p.period_score + p.ba_zhai_score + p.bazi_score + p.room_score)).abs() < 1e-9);
+    }
+    assert!(report.properties.windows(2).all(|w| w[0].total >= w[1].total));
+    assert_eq!(report.properties.iter().map(|p| p.rank).collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn test_equal_homes_are_split_by_the_quantum_draw() {
+    let mut session = session(2);
+    let report = compare_properties(&mut session, &config(vec![home("A", 180.0), home("B", 180.0)])).unwrap();
+    assert_eq!(session.pool_index, 2 * BYTES_PER_PROPERTY);
+    let [first, second] = &report.properties[..] else { panic!("expected two homes") };
+    assert_eq!(first.total, second.total);
+    assert!(first.tied && second.tied);
+    assert!(first.tie_break > second.tie_break);
+}
+
+#[test]
+fn test_a_bad_floor_plan_names_the_home() {
+    let mut bad = home("Loft", 180.0);
+    bad.rooms = Some(vec![Room { name: "Hall".to_string(), kind: RoomKind::Living, polygon: None, sectors: None }]);
+    let err = compare_properties(&mut session(1), &config(vec![bad])).unwrap_err();
+    assert!(format!("{:#}", err).contains("Loft"));
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tools::feng_shui::{FlyingStarChart, Palace};
+use crate::tools::floor_plan::{is_timely, palace_shares, sector_cell, star_name, Room, RoomKind, GRID};
+
+/// Compass sectors clockwise from North, 45° each.
+const COMPASS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// Weight of each star in a palace's conductance: Qi moves with the Water star.
+const WATER_WEIGHT: f64 = 1.0;
+const MOUNTAIN_WEIGHT: f64 = 0.5;
+const VISITING_WEIGHT: f64 = 0.5;
+
+/// Conductance at or above which a palace holds prosperous Qi worth drawing the flow into.
+const PROSPEROUS: f64 = 1.0;
+
+/// The palaces as a graph Qi flows through: how well each conducts it, the path it takes
+/// from the entrance and where it stalls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QiFlowAnalysis {
+    /// Palaces from the entrance to the far side of the house, along the path of least resistance.
+    pub flow_path: Vec<String>,
+    /// Palaces whose stars stall the Qi, worst first.
+    pub blockages: Vec<String>,
+    /// Where Qi enters: the main door's palace, or the facing palace without one.
+    #[serde(default)]
+    pub entry: String,
+    /// The palace opposite the entrance the flow is traced to.
+    #[serde(default)]
+    pub destination: String,
+    /// Each palace's star auspiciousness as `[row][col]`, South at the top; negative stalls Qi.
+    #[serde(default)]
+    pub conductance: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub hints: Vec<QiFlowHint>,
+}
+
+/// What to do about one palace's part in the flow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QiFlowHint {
+    pub sector: String,
+    /// True if the main flow passes through the palace.
+    pub on_path: bool,
+    pub issue: String,
+    pub remedy: String,
+}
+
+/// One star's pull on passing Qi in `period`: timely stars draw it, afflictive ones stall it.
+fn star_value(star: i32, period: i32) -> f64 {
+    if is_timely(star, period) {
+        return 1.0;
+    }
+    match star {
+        5 => -1.0,
+        2 => -0.8,
+        3 | 7 => -0.5,
+        _ => 0.2,
+    }
+}
+
+/// How readily a palace passes Qi on, from its water, mountain and visiting stars.
+pub fn conductance(palace: &Palace, period: i32) -> f64 {
+    WATER_WEIGHT * star_value(palace.water_star, period)
+        + MOUNTAIN_WEIGHT * star_value(palace.mountain_star, period)
+        + VISITING_WEIGHT * star_value(palace.visiting_star, period)
+}
+
+/// The compass palace a facing direction in degrees points to.
+pub fn facing_sector(facing_degrees: f64) -> &'static str {
+    COMPASS[((facing_degrees.rem_euclid(360.0) + 22.5) / 45.0) as usize % 8]
+}
+
+/// A classical cure for the afflictive star stalling a palace.
+fn remedy(star: i32) -> String {
+    match star {
+        5 => "Hang a brass Wu Lou or six-rod metal chime to drain the 5 Yellow's Earth.".to_string(),
+        2 => "Place a brass Wu Lou or salt water cure to drain the 2 Black's Earth.".to_string(),
+        3 => "Use red or fire accents to burn off the 3 Jade's Wood.".to_string(),
+        7 => "Keep still water here to drain the 7 Red's Metal.".to_string(),
+        _ => "Keep the sector tidy and well lit so Qi does not stagnate.".to_string(),
+    }
+}
+
+/// Traces the flow of Qi through the palaces of `chart`.
+///
+/// The palaces form a 3x3 grid graph where Qi may step to any of the (up to eight)
+/// neighbouring palaces; entering a palace costs `exp(-conductance)`, so Qi favours timely
+/// stars and avoids afflicted ones. The flow enters at the main door (the largest palace of
+/// the first `Door` room) or the facing palace, and is traced along the cheapest route to the
+/// palace opposite (the sitting palace, when the door is central).
+pub fn analyze_qi_flow(chart: &FlyingStarChart, facing_degrees: f64, rooms: Option<&[Room]>) -> QiFlowAnalysis {
+    let period = chart.period;
+    let mut values = [[0.0; 3]; 3];
+    for palace in &chart.palaces {
+        if let Some((r, c)) = sector_cell(&palace.sector) {
+            values[r][c] = conductance(palace, period);
+        }
+    }
+
+    let facing = sector_cell(facing_sector(facing_degrees)).unwrap_or((0, 1));
+    let door = rooms.unwrap_or_default().iter()
+        .find(|room| room.kind == RoomKind::Door)
+        .and_then(|room| palace_shares(room).ok()?.first().and_then(|s| sector_cell(&s.sector)));
+    let entry = door.unwrap_or(facing);
+    let destination = match entry {
+        (1, 1) => (2 - facing.0, 2 - facing.1),
+        (r, c) => (2 - r, 2 - c),
+    };
+
+    // Dijkstra over the nine palaces (index r * 3 + c)
+    let cost = |at: usize| (-values[at / 3][at % 3]).exp();
+    let mut dist = [f64::INFINITY; 9];
+    let mut prev = [None; 9];
+    let mut done = [false; 9];
+    dist[entry.0 * 3 + entry.1] = 0.0;
+    while let Some(at) = (0..9).filter(|&i| !done[i] && dist[i].is_finite()).min_by(|&a, &b| dist[a].total_cmp(&dist[b])) {
+        done[at] = true;
+        let (r, c) = ((at / 3) as i32, (at % 3) as i32);
+        for (dr, dc) in (-1..=1).flat_map(|dr| (-1..=1).map(move |dc| (dr, dc))).filter(|&d| d != (0, 0)) {
+            let (nr, nc) = (r + dr, c + dc);
+            if !(0..3).contains(&nr) || !(0..3).contains(&nc) {
+                continue;
+            }
+            let next = (nr * 3 + nc) as usize;
+            let through = dist[at] + cost(next);
+            if !done[next] && through < dist[next] {
+                dist[next] = through;
+                prev[next] = Some(at);
+            }
+        }
+    }
+    let mut path = vec![destination.0 * 3 + destination.1];
+    while let Some(p) = prev[*path.last().unwrap_or(&0)] {
+        path.push(p);
+    }
+    path.reverse();
+    let name = |at: usize| GRID[at / 3][at % 3].to_string();
+
+    let mut stalled: Vec<usize> = (0..9).filter(|&i| values[i / 3][i % 3] < 0.0).collect();
+    stalled.sort_by(|&a, &b| values[a / 3][a % 3].total_cmp(&values[b / 3][b % 3]).then(a.cmp(&b)));
+    let palace_at = |at: usize| chart.palaces.iter().find(|p| sector_cell(&p.sector) == Some((at / 3, at % 3)));
+
+    let mut hints = Vec::new();
+    for &at in &stalled {
+        let Some(palace) = palace_at(at) else { continue };
+        let worst = [palace.water_star, palace.mountain_star, palace.visiting_star].into_iter()
+            .min_by(|&a, &b| star_value(a, period).total_cmp(&star_value(b, period)))
+            .unwrap_or(palace.water_star);
+        let on_path = path.contains(&at);
+        hints.push(QiFlowHint {
+            sector: name(at),
+            on_path,
+            issue: if on_path {
+                format!("{} on the main flow stalls Qi for the rest of the house.", star_name(worst))
+            } else {
+                format!("{} lets Qi stagnate here.", star_name(worst))
+            },
+            remedy: remedy(worst),
+        });
+    }
+    for at in (0..9).filter(|&i| values[i / 3][i % 3] >= PROSPEROUS && !path.contains(&i)) {
+        let Some(palace) = palace_at(at) else { continue };
+        hints.push(QiFlowHint {
+            sector: name(at),
+            on_path: false,
+            issue: format!("Prosperous {} sits off the main flow.", star_name(palace.water_star)),
+            remedy: "Keep the way from the main flow open and add movement (a water feature, fan or lively use) to draw Qi in.".to_string(),
+        });
+    }
+
+    QiFlowAnalysis {
+        flow_path: path.iter().map(|&at| name(at)).collect(),
+        blockages: stalled.iter().map(|&at| name(at)).collect(),
+        entry: name(entry.0 * 3 + entry.1),
+        destination: name(destination.0 * 3 + destination.1),
+        conductance: values.iter().map(|row| row.to_vec()).collect(),
+        hints,
+    }
+}
+
+#[cfg(test)]
+#[path = "qi_flow_tests.rs"]
+mod tests;
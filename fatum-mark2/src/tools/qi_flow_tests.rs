@@ -0,0 +1,39 @@
+use super::{analyze_qi_flow, facing_sector};
+use crate::tools::floor_plan::{Room, RoomKind};
+use crate::tools::test_fixtures::chart;
+
+#[test]
+fn test_facing_sectors() {
+    assert_eq!(facing_sector(180.0), "S");
+    assert_eq!(facing_sector(0.0), "N");
+    assert_eq!(facing_sector(350.0), "N");
+    assert_eq!(facing_sector(22.5), "NE");
+    assert_eq!(facing_sector(-90.0), "W");
+}
+
+#[test]
+fn test_flow_skirts_an_afflicted_centre() {
+    // Facing South with the 5 Yellow in the Center and a timely 9 in the East
+    let chart = chart(&[("Center", 5, 5, 5), ("E", 6, 9, 6)]);
+    let flow = analyze_qi_flow(&chart, 180.0, None);
+    assert_eq!((flow.entry.as_str(), flow.destination.as_str()), ("S", "N"));
+    assert_eq!(flow.flow_path, vec!["S", "E", "N"]);
+    assert_eq!(flow.blockages, vec!["Center"]);
+    assert!((flow.conductance[1][1] + 2.0).abs() < 1e-9);
+    assert_eq!(flow.hints.len(), 1);
+    assert!(!flow.hints[0].on_path);
+    assert!(flow.hints[0].remedy.contains("Wu Lou"));
+}
+
+#[test]
+fn test_door_sets_the_entry_and_prosperous_sectors_off_the_path_get_hints() {
+    let chart = chart(&[("Center", 5, 5, 5), ("S", 6, 9, 6), ("NW", 6, 1, 6), ("W", 3, 3, 6)]);
+    let door = Room { name: "Front door".to_string(), kind: RoomKind::Door, polygon: None, sectors: Some(vec!["E".to_string()]) };
+    let flow = analyze_qi_flow(&chart, 180.0, Some(&[door]));
+    assert_eq!((flow.entry.as_str(), flow.destination.as_str()), ("E", "W"));
+    assert_eq!(flow.flow_path, vec!["E", "S", "W"]);
+    assert_eq!(flow.blockages, vec!["Center", "W"]);
+    let hint = |sector: &str| flow.hints.iter().find(|h| h.sector == sector).unwrap();
+    assert!(hint("W").on_path && hint("W").remedy.contains("red"));
+    assert!(!hint("NW").on_path && hint("NW").issue.starts_with("Prosperous 1 White"));
+}
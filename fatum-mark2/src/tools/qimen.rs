@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
 use crate::tools::astronomy::get_solar_term;
+use crate::tools::chinese_meta::day_pillar;
 
 /// Represents a full Qi Men Dun Jia Chart (Hour School).
 ///
@@ -13,6 +15,9 @@ pub struct QiMenChart {
     pub duty_star: String, // Zhi Fu (The lead star)
     pub duty_door: String, // Zhi Shi (The lead door)
     pub palaces: Vec<QiMenPalace>,
+    /// Xun Shou of the hour: the Jia leading its decade and the stem it hides under, e.g. "Jia Zi (Wu)".
+    #[serde(default)]
+    pub xun_shou: String,
 }
 
 /// A single sector (Palace) in the Qi Men grid.
@@ -21,11 +26,30 @@ pub struct QiMenPalace {
     pub index: usize, // 1-9
     pub position: String, // "SE", "S", etc.
     pub earth_plate: String, // Earth Stem (Di Pan)
-    pub heaven_plate: String, // Heaven Stem (Tian Pan)
+    pub heaven_plate: String, // Heaven Stem (Tian Pan); "Ji/Wu" when Tian Qin's stem rides along
     pub door: String, // Men (Door)
     pub star: String, // Xing (Star)
     pub deity: String, // Shen (Deity)
-    pub structure: String, // Special structures (e.g. "Green Dragon Returns")
+    pub structure: String, // Special structures (e.g. "Green Dragon Returns"), or "Normal"
+    /// Structures (Ge Ju) found in the palace, with their severity.
+    #[serde(default)]
+    pub structures: Vec<QiMenStructure>,
+}
+
+/// A classic Qi Men structure (Ge Ju) formed in a palace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QiMenStructure {
+    pub name: String,
+    /// Positive when auspicious, negative when harmful; magnitude 1 (mild) to 3 (strong).
+    pub severity: i32,
+    pub description: String,
+}
+
+impl QiMenPalace {
+    /// Net severity of the palace's structures.
+    pub fn structure_score(&self) -> i32 {
+        self.structures.iter().map(|s| s.severity).sum()
+    }
 }
 
 // Stems: 0=Jia, 1=Yi, ... 9=Gui
@@ -38,7 +62,8 @@ pub struct QiMenPalace {
 pub fn calculate_qimen(year: i32, month: u32, day: u32, hour: u32) -> QiMenChart {
     // 1. Determine Solar Term
     // The solar term dictates the Ju (Bureau) Number.
-    let term_idx = get_solar_term(year, month, day); // 0-23
+    // The astronomy module counts from the Spring Equinox; the tables here start at Little Cold.
+    let term_idx = (get_solar_term(year, month, day) + 5) % 24;
     let term_name = get_term_name(term_idx as usize);
 
     // 2. Determine Yin/Yang Dun and Ju Number
@@ -48,8 +73,7 @@ pub fn calculate_qimen(year: i32, month: u32, day: u32, hour: u32) -> QiMenChart
 
     let day_idx = get_day_gan_zhi_idx(year, month, day);
     // Cycle repeats every 15 days (5 Upper + 5 Middle + 5 Lower)
-    let yuan_mod = day_idx % 15;
-    let yuan = if yuan_mod < 5 { 0 } else if yuan_mod < 10 { 1 } else { 2 }; // 0=Upper, 1=Middle, 2=Lower
+    let yuan = get_yuan(day_idx); // 0=Upper, 1=Middle, 2=Lower
 
     let (dun_type, ju_num) = get_ju_number(term_idx as usize, yuan);
 
@@ -58,18 +82,19 @@ pub fn calculate_qimen(year: i32, month: u32, day: u32, hour: u32) -> QiMenChart
     let earth_plate = layout_earth_plate(dun_type, ju_num);
 
     // 4. Find Duty Star (Zhi Fu) and Duty Door (Zhi Shi)
-    // Determined by the Hour Stem location on the Earth Plate.
+    // Both sit in the palace of the hour's Xun Shou and follow the hour stem around the plate.
     let h_idx = get_gan_zhi_idx_hour(day_stem, hour);
-    let palaces = generate_palaces(dun_type, ju_num, h_idx, &earth_plate);
+    let rotation = generate_palaces(dun_type, h_idx, &earth_plate);
 
     QiMenChart {
         time_label: format!("Hour: {} {}", hour_stem, hour_branch),
         solar_term: term_name.to_string(),
         dun_type: if dun_type { "Yang Dun".to_string() } else { "Yin Dun".to_string() },
         ju_number: ju_num,
-        duty_star: palaces[0].star.clone(), // Simplified: Just taking first sector's star as representative
-        duty_door: palaces[0].door.clone(),
-        palaces,
+        duty_star: rotation.duty_star.to_string(),
+        duty_door: rotation.duty_door.to_string(),
+        palaces: rotation.palaces,
+        xun_shou: format!("{} ({})", ["Jia Zi", "Jia Xu", "Jia Shen", "Jia Wu", "Jia Chen", "Jia Yin"][h_idx / 10], LEAD_STEMS[h_idx / 10]),
     }
 }
 
@@ -91,17 +116,15 @@ fn get_ju_number(term: usize, yuan: usize) -> (bool, i32) {
     // Format: [Upper, Middle, Lower]
 
     // Winter Solstice to Summer Solstice is Yang.
-    let is_yang = match term {
-        22 | 23 | 0..=9 => true,
-        _ => false,
-    };
+    // Winter Solstice (23) through Grain in Ear (10) is Yang.
+    let is_yang = matches!(term, 23 | 0..=10);
 
     // Specific Ju numbers for each solar term
     let nums = match term {
          0 => [2,8,5],  1 => [3,9,6],  2 => [8,5,2],  3 => [9,6,3],  4 => [1,7,4],  5 => [3,9,6],
-         6 => [4,1,7],  7 => [5,2,8],  8 => [4,1,7],  9 => [5,2,8], 10 => [9,3,6], 11 => [9,3,6],
+         6 => [4,1,7],  7 => [5,2,8],  8 => [4,1,7],  9 => [5,2,8], 10 => [6,3,9], 11 => [9,3,6],
         12 => [8,2,5], 13 => [7,1,4], 14 => [2,5,8], 15 => [1,4,7], 16 => [9,3,6], 17 => [7,1,4],
-        18 => [6,9,3], 19 => [5,8,2], 20 => [4,7,1], 21 => [3,6,9], 22 => [1,7,4], 23 => [2,8,5],
+        18 => [6,9,3], 19 => [5,8,2], 20 => [6,9,3], 21 => [5,8,2], 22 => [4,7,1], 23 => [1,7,4],
         _ => [1,1,1]
     };
 
@@ -138,53 +161,162 @@ fn layout_earth_plate(yang: bool, ju: i32) -> [String; 9] {
     arr
 }
 
+// Outer palaces in clockwise order from Kan (Luo Shu numbers); stars, doors and deities turn around this ring.
+const RING: [usize; 8] = [1, 8, 3, 4, 9, 2, 7, 6];
+
+// Home palace of each star and door, indexed by Luo Shu number - 1. The center has no door.
+const STARS: [&str; 9] = ["Peng", "Rui", "Chong", "Fu", "Qin", "Xin", "Zhu", "Ren", "Ying"];
+const DOORS: [&str; 9] = ["Rest", "Death", "Harm", "Du", "", "Open", "Fear", "Life", "Jing"];
+
+// The eight deities, laid clockwise in Yang Dun and counter-clockwise in Yin Dun from the Chief.
+const DEITIES: [&str; 8] = ["Chief", "Snake", "Moon", "Harmony", "Tiger", "Tortoise", "Earth", "Heaven"];
+
+// Stem each Jia hides under (Liu Yi), by decade of the 60 cycle: Jia Zi -> Wu, Jia Xu -> Ji, ...
+const LEAD_STEMS: [&str; 6] = ["Wu", "Ji", "Geng", "Xin", "Ren", "Gui"];
+
+// Element of each palace (and of the door whose home it is), Wu Xing order: 0=Wood, 1=Fire, 2=Earth, 3=Metal, 4=Water.
+const PALACE_ELEMENTS: [usize; 9] = [4, 2, 0, 0, 2, 3, 3, 2, 1];
+
+// Palace holding each branch: Zi in Kan, Chou and Yin in Gen, Mao in Zhen, ...
+const BRANCH_PALACES: [usize; 12] = [1, 8, 8, 3, 4, 4, 9, 2, 2, 7, 6, 6];
+
+const SECTORS: [&str; 9] = ["Kan (N)", "Kun (SW)", "Zhen (E)", "Xun (SE)", "Center", "Qian (NW)", "Dui (W)", "Gen (NE)", "Li (S)"];
+
+/// The rotated plates with the Zhi Fu star and Zhi Shi door they were turned by.
+struct Rotation {
+    palaces: Vec<QiMenPalace>,
+    duty_star: &'static str,
+    duty_door: &'static str,
+}
+
+/// Tian Qin has no place on the ring; it lodges in Kun (2) with the Death door.
+fn lodge(palace: usize) -> usize {
+    if palace == 5 { 2 } else { palace }
+}
+
+fn ring_pos(palace: usize) -> usize {
+    RING.iter().position(|&p| p == palace).unwrap_or(0)
+}
+
 /// Generates the full palace content (Heaven, Star, Door, Deity).
-fn generate_palaces(yang: bool, _ju: i32, h_idx: usize, earth: &[String; 9]) -> Vec<QiMenPalace> {
-    let doors = ["Rest", "Life", "Harm", "Du", "Jing", "Death", "Fear", "Open"];
-    let stars = ["Peng", "Ren", "Chong", "Fu", "Ying", "Rui", "Zhu", "Xin", "Qin"];
-    let deities = ["Chief", "Snake", "Moon", "Harmony", "Tiger", "Tortoise", "Phoenix", "Earth", "Heaven"];
-
-    // Rotation logic based on Hour Stem index
-    let shift = h_idx % 9;
-
-    let mut palaces = Vec::new();
-    let sectors = ["Kan (N)", "Kun (SW)", "Zhen (E)", "Xun (SE)", "Center", "Qian (NW)", "Dui (W)", "Gen (NE)", "Li (S)"];
-
-    for i in 0..9 {
-        let star_idx = (i + shift) % 9;
-        let door_idx = (i + shift) % 8; // Doors skip center usually, simplified here
-        let deity_idx = if yang { (i + (h_idx % 9)) % 8 } else { (i + 8 - (h_idx % 9)) % 8 };
-
-        // Heaven stem rotates with the stars
-        let heaven_stem = earth[(i + shift) % 9].clone();
-
-        palaces.push(QiMenPalace {
-            index: i + 1,
-            position: sectors[i].to_string(),
-            earth_plate: earth[i].clone(),
-            heaven_plate: heaven_stem,
-            door: doors[door_idx].to_string(),
-            star: stars[star_idx].to_string(),
-            deity: deities[deity_idx].to_string(),
+///
+/// The Zhi Fu star (home palace of the Xun Shou stem) flies to the palace of the hour stem on
+/// the Earth Plate, turning the other stars and their stems with it. The Zhi Shi door counts one
+/// palace per hour from its home through the Luo Shu sequence, turning the other doors with it.
+fn generate_palaces(yang: bool, h_idx: usize, earth: &[String; 9]) -> Rotation {
+    let stem_palace = |stem: &str| earth.iter().position(|e| e == stem).map_or(5, |i| i + 1);
+
+    // 1. Xun Shou: the Jia heading the hour's decade hides under its Liu Yi stem
+    let lead_palace = stem_palace(LEAD_STEMS[h_idx / 10]);
+    let duty_star = STARS[lead_palace - 1];
+    let duty_door = DOORS[lodge(lead_palace) - 1];
+
+    // 2. Stars: the Zhi Fu lands on the hour stem (a Jia hour uses its lead stem)
+    let hour_stem = ["Jia", "Yi", "Bing", "Ding", "Wu", "Ji", "Geng", "Xin", "Ren", "Gui"][h_idx % 10];
+    let star_target = lodge(if hour_stem == "Jia" { lead_palace } else { stem_palace(hour_stem) });
+    let star_shift = (ring_pos(star_target) + 8 - ring_pos(lodge(lead_palace))) % 8;
+
+    // 3. Doors: the Zhi Shi steps once per hour since the Jia hour, forward in Yang Dun
+    let steps = h_idx % 10;
+    let counted = if yang { (lead_palace - 1 + steps) % 9 + 1 } else { (lead_palace - 1 + 9 - steps) % 9 + 1 };
+    let door_shift = (ring_pos(lodge(counted)) + 8 - ring_pos(lodge(lead_palace))) % 8;
+
+    // Empty Death (Xun Kong): the two branches the hour's decade leaves out
+    let void = [(10 + 12 - 2 * (h_idx / 10)) % 12, (11 + 12 - 2 * (h_idx / 10)) % 12];
+    let void_palaces = void.map(|b| BRANCH_PALACES[b]);
+
+    // 4. Lay the plates out palace by palace
+    let palaces = (1..=9).map(|palace| {
+        let mut p = QiMenPalace {
+            index: palace,
+            position: SECTORS[palace - 1].to_string(),
+            earth_plate: earth[palace - 1].clone(),
+            heaven_plate: String::new(),
+            door: String::new(),
+            star: String::new(),
+            deity: String::new(),
             structure: "Normal".to_string(),
-        });
+            structures: Vec::new(),
+        };
+        if palace == 5 {
+            // The center keeps only its Earth stem; Tian Qin travels with Tian Rui
+            return p;
+        }
+        let r = ring_pos(palace);
+        let star_home = RING[(r + 8 - star_shift) % 8];
+        let door_home = RING[(r + 8 - door_shift) % 8];
+        let deity = if yang { (r + 8 - ring_pos(star_target)) % 8 } else { (ring_pos(star_target) + 8 - r) % 8 };
+
+        p.star = STARS[star_home - 1].to_string();
+        p.heaven_plate = earth[star_home - 1].clone();
+        if star_home == 2 {
+            p.star.push_str("/Qin");
+            p.heaven_plate = format!("{}/{}", earth[1], earth[4]);
+        }
+        p.door = DOORS[door_home - 1].to_string();
+        p.deity = DEITIES[deity].to_string();
+        p.structures = detect_structures(palace, star_home, door_home, &p.heaven_plate, &p.earth_plate, &void_palaces);
+        if !p.structures.is_empty() {
+            p.structure = p.structures.iter().map(|s| s.name.as_str()).collect::<Vec<_>>().join(", ");
+        }
+        p
+    }).collect();
+
+    Rotation { palaces, duty_star, duty_door }
+}
+
+/// Structures formed in an outer palace by the stems, star and door that landed on it.
+fn detect_structures(palace: usize, star_home: usize, door_home: usize, heaven: &str, earth: &str, void_palaces: &[usize; 2]) -> Vec<QiMenStructure> {
+    let mut found = Vec::new();
+    let mut add = |name: &str, severity: i32, description: &str| {
+        found.push(QiMenStructure { name: name.to_string(), severity, description: description.to_string() });
+    };
+    let heaven_has = |stem: &str| heaven.split('/').any(|h| h == stem);
+
+    // 1. Stem combinations
+    if heaven_has("Wu") && earth == "Bing" {
+        add("Green Dragon Returns", 3, "Wu over Bing: bold moves and new ventures are rewarded.");
+    }
+    if heaven_has("Bing") && earth == "Wu" {
+        add("Birds Falling into Cave", 3, "Bing over Wu: opportunities arrive without being chased.");
+    }
+
+    // 2. Stars resting at home or thrown to the opposite palace
+    if star_home == palace {
+        add("Fu Yin", -2, "The star sits in its own palace: affairs stall; wait rather than act.");
+    } else if star_home == 10 - palace {
+        add("Fan Yin", -3, "The star faces its home across the plate: reversals and repeated setbacks.");
+    }
+
+    // 3. Door Clash (Men Po): the door's element overcomes the palace's
+    let (door, home) = (PALACE_ELEMENTS[door_home - 1], PALACE_ELEMENTS[palace - 1]);
+    if home == (door + 2) % 5 {
+        add("Door Clash", -2, "The door overcomes its palace: its good effects are blocked and its bad ones sharpened.");
     }
 
-    palaces
+    // 4. Empty Death (Kong Wang)
+    if void_palaces.contains(&palace) {
+        add("Empty Death", -1, "The palace holds a void branch of the hour: what it promises is hollow until the void is filled.");
+    }
+    found
 }
 
 // === DATE UTILS ===
 
+/// Position of the day in the 60-day cycle (0 = Jia Zi), from the same day count Ze Ri uses.
 fn get_day_gan_zhi_idx(y: i32, m: u32, d: u32) -> usize {
-    let offset = (y * 365 + m as i32 * 30 + d as i32) as usize;
-    offset % 60
+    let Some(date) = NaiveDate::from_ymd_opt(y, m, d) else { return 0 };
+    let (stem, branch) = day_pillar(date);
+    // The index that is `stem` mod 10 and `branch` mod 12
+    (6 * stem + 55 * branch) % 60
 }
 
+/// Position of the hour in the 60 cycle (0 = Jia Zi).
 fn get_gan_zhi_idx_hour(day_stem: &str, hour: u32) -> usize {
     let h_branch = (hour as usize + 1) / 2 % 12;
     let d_stem_idx = get_stem_idx(day_stem);
     let h_stem_idx = (d_stem_idx % 5 * 2 + h_branch) % 10;
-    (h_stem_idx * 10 + h_branch) % 60
+    (6 * h_stem_idx + 55 * h_branch) % 60
 }
 
 fn get_gan_zhi_day(y: i32, m: u32, d: u32) -> (&'static str, usize) {
@@ -206,9 +338,19 @@ fn get_gan_zhi_hour(day_stem: &str, hour: u32) -> (&'static str, &'static str) {
     (stems[h_stem_idx], branches[h_branch_idx])
 }
 
+/// Yuan (0 = Upper, 1 = Middle, 2 = Lower) of a day, from its place in the 15-day cycle
+/// headed by a Jia or Ji day (Fu Tou): Zi/Wu/Mao/You heads open the Upper Yuan.
+fn get_yuan(day_idx: usize) -> usize {
+    day_idx % 15 / 5
+}
+
 fn get_stem_idx(s: &str) -> usize {
     match s {
         "Jia" => 0, "Yi" => 1, "Bing" => 2, "Ding" => 3, "Wu" => 4,
         "Ji" => 5, "Geng" => 6, "Xin" => 7, "Ren" => 8, "Gui" => 9, _ => 0
     }
 }
+
+#[cfg(test)]
+#[path = "qimen_tests.rs"]
+mod tests;
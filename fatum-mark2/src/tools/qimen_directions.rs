@@ -0,0 +1,172 @@
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::tools::astronomy::{solar_hour, Location};
+use crate::tools::qimen::{calculate_qimen, QiMenChart, QiMenPalace};
+
+/// Most double-hours after the queried one a forecast covers (one full day).
+pub const MAX_HOURS_AHEAD: usize = 12;
+
+/// What the direction is wanted for; each favours its own door, star and deity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QiMenIntention {
+    #[default]
+    General,
+    /// Seeking money or deals: the Life door and Tian Ren.
+    Wealth,
+    /// Meetings, contracts, partnerships: the Open door, Tian Xin and Liu He (Harmony).
+    Negotiation,
+    /// Journeys: the Open and Rest doors, Tian Chong and Jiu Tian (Heaven).
+    Travel,
+}
+
+impl QiMenIntention {
+    fn favoured(self) -> (&'static [&'static str], &'static str, &'static str) {
+        match self {
+            QiMenIntention::General => (&[], "", ""),
+            QiMenIntention::Wealth => (&["Life"], "Ren", "Harmony"),
+            QiMenIntention::Negotiation => (&["Open"], "Xin", "Harmony"),
+            QiMenIntention::Travel => (&["Open", "Rest"], "Chong", "Heaven"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QiMenConfig {
+    /// Local clock time of the question.
+    pub datetime: NaiveDateTime,
+    /// Where the question was asked; converts `datetime` to true solar time.
+    #[serde(default)]
+    pub location: Option<Location>,
+    #[serde(default)]
+    pub intention: QiMenIntention,
+    /// Double-hours after the queried one to rank as well (0-12).
+    #[serde(default)]
+    pub hours_ahead: usize,
+}
+
+/// One direction's score for the intention, with what earned it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectionScore {
+    pub palace: usize,
+    pub direction: String,
+    pub score: i32,
+    pub reasons: Vec<String>,
+}
+
+/// Directions ranked best first for one double-hour.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourDirections {
+    /// Clock time the double-hour was cast for.
+    pub datetime: NaiveDateTime,
+    pub time_label: String,
+    pub ranking: Vec<DirectionScore>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QiMenReading {
+    pub chart: QiMenChart,
+    pub intention: QiMenIntention,
+    /// The queried hour first, then each following double-hour.
+    pub forecast: Vec<HourDirections>,
+}
+
+// Door, star and deity weights before the intention is considered.
+fn door_weight(door: &str) -> i32 {
+    match door {
+        "Open" | "Rest" | "Life" => 2,
+        "Jing" => 0,
+        "Du" => -1,
+        "Harm" | "Fear" => -2,
+        "Death" => -3,
+        _ => 0,
+    }
+}
+
+fn star_weight(star: &str) -> i32 {
+    match star {
+        "Fu" | "Qin" | "Xin" | "Ren" => 1,
+        "Peng" | "Rui" | "Zhu" => -1,
+        _ => 0,
+    }
+}
+
+fn deity_weight(deity: &str) -> i32 {
+    match deity {
+        "Chief" | "Moon" | "Harmony" | "Earth" | "Heaven" => 1,
+        "Snake" | "Tiger" | "Tortoise" => -1,
+        _ => 0,
+    }
+}
+
+/// Scores an outer palace for `intention`: door, star and deity, then the structures doubled.
+pub fn score_palace(palace: &QiMenPalace, intention: QiMenIntention) -> DirectionScore {
+    let (doors, star, deity) = intention.favoured();
+    let lead_star = palace.star.split('/').next().unwrap_or("");
+    let mut reasons = Vec::new();
+
+    let mut score = door_weight(&palace.door) + star_weight(lead_star) + deity_weight(&palace.deity);
+    if doors.contains(&palace.door.as_str()) {
+        score += 3;
+        reasons.push(format!("{} door suits the intention", palace.door));
+    }
+    if palace.star.split('/').any(|s| s == star) {
+        score += 2;
+        reasons.push(format!("Tian {} supports the intention", star));
+    }
+    if palace.deity == deity {
+        score += 2;
+        reasons.push(format!("{} deity supports the intention", palace.deity));
+    }
+    for s in &palace.structures {
+        score += 2 * s.severity;
+        reasons.push(format!("{} ({:+})", s.name, s.severity));
+    }
+
+    DirectionScore {
+        palace: palace.index,
+        direction: palace.position.clone(),
+        score,
+        reasons,
+    }
+}
+
+/// Ranks the eight outer palaces, best first. The center has no door and is never a direction.
+pub fn rank_directions(chart: &QiMenChart, intention: QiMenIntention) -> Vec<DirectionScore> {
+    let mut ranking: Vec<DirectionScore> = chart.palaces.iter()
+        .filter(|p| p.index != 5)
+        .map(|p| score_palace(p, intention))
+        .collect();
+    ranking.sort_by(|a, b| b.score.cmp(&a.score).then(a.palace.cmp(&b.palace)));
+    ranking
+}
+
+fn chart_at(clock: NaiveDateTime, location: Option<&Location>) -> QiMenChart {
+    let (y, m, d, h) = solar_hour(clock.year(), clock.month(), clock.day(), clock.hour(), location);
+    calculate_qimen(y, m, d, h)
+}
+
+/// Casts the queried hour and ranks its directions, then the next `hours_ahead` double-hours.
+pub fn recommend_directions(config: &QiMenConfig) -> QiMenReading {
+    let chart = chart_at(config.datetime, config.location.as_ref());
+    let forecast = (0..=config.hours_ahead.min(MAX_HOURS_AHEAD)).map(|i| {
+        let clock = config.datetime + Duration::hours(2 * i as i64);
+        let hour_chart = if i == 0 { chart.clone() } else { chart_at(clock, config.location.as_ref()) };
+        HourDirections {
+            datetime: clock,
+            time_label: hour_chart.time_label.clone(),
+            ranking: rank_directions(&hour_chart, config.intention),
+        }
+    }).collect();
+
+    QiMenReading {
+        chart,
+        intention: config.intention,
+        forecast,
+    }
+}
+
+#[cfg(test)]
+#[path = "qimen_directions_tests.rs"]
+mod tests;
@@ -0,0 +1,45 @@
+use super::{rank_directions, recommend_directions, score_palace, QiMenConfig, QiMenIntention, MAX_HOURS_AHEAD};
+use crate::tools::qimen::calculate_qimen;
+use chrono::NaiveDate;
+
+fn config(intention: QiMenIntention, hours_ahead: usize) -> QiMenConfig {
+    QiMenConfig {
+        datetime: NaiveDate::from_ymd_opt(2024, 2, 10).unwrap().and_hms_opt(12, 0, 0).unwrap(),
+        location: None,
+        intention,
+        hours_ahead,
+    }
+}
+
+#[test]
+fn test_ranking_covers_eight_directions_best_first() {
+    let chart = calculate_qimen(2024, 2, 10, 12);
+    let ranking = rank_directions(&chart, QiMenIntention::General);
+    assert_eq!(ranking.len(), 8);
+    assert!(ranking.iter().all(|d| d.palace != 5));
+    assert!(ranking.windows(2).all(|w| w[0].score >= w[1].score));
+}
+
+#[test]
+fn test_intention_favours_its_door() {
+    let chart = calculate_qimen(2024, 2, 10, 12);
+    let life = chart.palaces.iter().find(|p| p.door == "Life").unwrap();
+    let general = score_palace(life, QiMenIntention::General);
+    let wealth = score_palace(life, QiMenIntention::Wealth);
+    assert!(wealth.score > general.score);
+    assert!(wealth.reasons.iter().any(|r| r.contains("Life door")));
+    // The Death door is never a good direction on its own
+    let death = chart.palaces.iter().find(|p| p.door == "Death").unwrap();
+    assert!(score_palace(death, QiMenIntention::Travel).reasons.iter().all(|r| !r.contains("door suits")));
+}
+
+#[test]
+fn test_forecast_steps_through_double_hours() {
+    let reading = recommend_directions(&config(QiMenIntention::Negotiation, 3));
+    assert_eq!(reading.forecast.len(), 4);
+    assert_eq!(reading.forecast[0].time_label, reading.chart.time_label);
+    let labels: Vec<&str> = reading.forecast.iter().map(|h| h.time_label.as_str()).collect();
+    assert_eq!(labels, vec!["Hour: Geng Wu", "Hour: Xin Wei", "Hour: Ren Shen", "Hour: Gui You"]);
+    // Requests past a day are capped
+    assert_eq!(recommend_directions(&config(QiMenIntention::Travel, 100)).forecast.len(), MAX_HOURS_AHEAD + 1);
+}
@@ -0,0 +1,111 @@
+use super::{calculate_qimen, generate_palaces, get_day_gan_zhi_idx, get_gan_zhi_day, get_yuan, layout_earth_plate};
+
+#[test]
+fn test_day_pillar_matches_almanac() {
+    // 1949-10-01 was a Jia Zi day, 2000-01-01 Wu Wu, 2008-08-08 Geng Chen, 2024-02-10 Jia Chen
+    assert_eq!(get_day_gan_zhi_idx(1949, 10, 1), 0);
+    assert_eq!(get_day_gan_zhi_idx(2000, 1, 1), 54);
+    assert_eq!(get_day_gan_zhi_idx(2008, 8, 8), 16);
+    assert_eq!(get_day_gan_zhi_idx(2024, 2, 10), 40);
+    assert_eq!(get_gan_zhi_day(2024, 2, 10), ("Jia", 4));
+    // Consecutive days step through the cycle, across month and year ends
+    assert_eq!(get_day_gan_zhi_idx(2024, 1, 1), (get_day_gan_zhi_idx(2023, 12, 31) + 1) % 60);
+    assert_eq!(get_day_gan_zhi_idx(2024, 3, 1), (get_day_gan_zhi_idx(2024, 2, 29) + 1) % 60);
+}
+
+#[test]
+fn test_yuan_follows_fu_tou() {
+    // Jia Zi, Ji Mao heads open the Upper Yuan; Ji Si the Middle; Jia Xu the Lower
+    assert_eq!(get_yuan(0), 0);
+    assert_eq!(get_yuan(15), 0);
+    assert_eq!(get_yuan(5), 1);
+    assert_eq!(get_yuan(10), 2);
+    assert_eq!(get_yuan(14), 2);
+}
+
+#[test]
+fn test_hour_stem_follows_day() {
+    // A Jia day starts its hours at Jia Zi, so noon is Geng Wu
+    assert_eq!(calculate_qimen(2024, 2, 10, 12).time_label, "Hour: Geng Wu");
+    // 2008-08-08 (Geng day) noon is Ren Wu
+    assert_eq!(calculate_qimen(2008, 8, 8, 12).time_label, "Hour: Ren Wu");
+}
+
+#[test]
+fn test_plates_rotate_from_xun_shou() {
+    // 2024-02-10 noon: Start of Spring, Lower Yuan -> Yang Dun 2. Geng Wu hour in the Jia Zi decade:
+    // Wu sits in Kun, so Tian Rui and the Death door lead; Rui flies to Geng in Xun, and
+    // Death counts six palaces from Kun to Gen.
+    let chart = calculate_qimen(2024, 2, 10, 12);
+    assert_eq!(chart.solar_term, "Start of Spring");
+    assert_eq!((chart.dun_type.as_str(), chart.ju_number), ("Yang Dun", 2));
+    assert_eq!(chart.xun_shou, "Jia Zi (Wu)");
+    assert_eq!((chart.duty_star.as_str(), chart.duty_door.as_str()), ("Rui", "Death"));
+    let xun = &chart.palaces[3];
+    assert_eq!(xun.star, "Rui/Qin");
+    assert_eq!(xun.heaven_plate, "Wu/Xin");
+    assert_eq!(xun.deity, "Chief");
+    assert_eq!(chart.palaces[7].door, "Death");
+    // Every star and door appears exactly once on the ring
+    for names in [chart.palaces.iter().map(|p| p.star.as_str()).collect::<Vec<_>>(), chart.palaces.iter().map(|p| p.door.as_str()).collect()] {
+        let mut sorted: Vec<_> = names.into_iter().filter(|n| !n.is_empty()).collect();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 8);
+    }
+}
+
+#[test]
+fn test_tian_qin_lead_lodges_in_kun() {
+    // Yang Dun 1 puts Ren in the center, so the Jia Chen decade is led by Tian Qin and the Death door
+    let earth = layout_earth_plate(true, 1);
+    let jia_chen = generate_palaces(true, 40, &earth);
+    assert_eq!((jia_chen.duty_star, jia_chen.duty_door), ("Qin", "Death"));
+    // The Jia hour itself leaves every star and door at home (Fu Yin)
+    assert_eq!(jia_chen.palaces[1].star, "Rui/Qin");
+    assert_eq!(jia_chen.palaces[1].door, "Death");
+    assert_eq!(jia_chen.palaces[4].star, "");
+    // Yi Si: Rui and Qin fly to Yi in Li, and Death counts from the center to Qian
+    let yi_si = generate_palaces(true, 41, &earth);
+    assert_eq!(yi_si.palaces[8].star, "Rui/Qin");
+    assert_eq!(yi_si.palaces[5].door, "Death");
+}
+
+#[test]
+fn test_yin_dun_runs_backwards() {
+    // Yin Dun 9, Yi Chou hour: Wu sits in Li, so Tian Ying and the Jing door lead
+    let earth = layout_earth_plate(false, 9);
+    let rotation = generate_palaces(false, 1, &earth);
+    assert_eq!((rotation.duty_star, rotation.duty_door), ("Ying", "Jing"));
+    // Ying flies to Yi in Kan; Jing steps back one palace to Gen
+    assert_eq!(rotation.palaces[0].star, "Ying");
+    assert_eq!(rotation.palaces[7].door, "Jing");
+    // Deities run counter-clockwise from the Chief
+    assert_eq!(rotation.palaces[0].deity, "Chief");
+    assert_eq!(rotation.palaces[5].deity, "Snake");
+}
+
+#[test]
+fn test_structures_detected_per_palace() {
+    // Yang Dun 1, Bing Yin hour: Tian Peng carries Wu onto Bing in Gen
+    let rotation = generate_palaces(true, 2, &layout_earth_plate(true, 1));
+    let gen = &rotation.palaces[7];
+    assert!(gen.structures.iter().any(|s| s.name == "Green Dragon Returns" && s.severity > 0));
+    assert!(gen.structure.contains("Green Dragon Returns"));
+    // The Du door (Wood) lands in Kun (Earth)
+    assert!(rotation.palaces[1].structures.iter().any(|s| s.name == "Door Clash"));
+    // The Jia Zi decade leaves Xu and Hai void, both in Qian
+    assert!(rotation.palaces[5].structures.iter().any(|s| s.name == "Empty Death"));
+    assert_eq!(rotation.palaces[4].structure, "Normal");
+}
+
+#[test]
+fn test_fu_yin_and_fan_yin() {
+    // A Jia hour leaves every star at home
+    let fu_yin = generate_palaces(true, 40, &layout_earth_plate(true, 1));
+    assert!(fu_yin.palaces.iter().filter(|p| p.index != 5).all(|p| p.structures.iter().any(|s| s.name == "Fu Yin")));
+    // Yang Dun 1, Yi Chou hour: Tian Peng leaves Kan for Yi in Li, throwing every star across the plate
+    let fan_yin = generate_palaces(true, 1, &layout_earth_plate(true, 1));
+    assert!(fan_yin.palaces.iter().filter(|p| p.index != 5).all(|p| p.structures.iter().any(|s| s.name == "Fan Yin")));
+    assert!(fan_yin.palaces.iter().all(|p| p.structure_score() <= 0));
+}
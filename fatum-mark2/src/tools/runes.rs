@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::SimulationSession;
+
+/// Runes in the Elder Futhark.
+pub const FUTHARK_SIZE: usize = 24;
+
+/// A rune and its meanings from `runes.json`, in Futhark order (Fehu = 0).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rune {
+    pub number: usize,
+    pub name: String,
+    pub letter: String,
+    pub sound: String,
+    /// Freyr's, Heimdall's or Tyr's Aett.
+    pub aett: String,
+    pub keywords: String,
+    pub upright: String,
+    /// `None` for the symmetric runes (Gebo, Isa, Dagaz...), which read the same either way up.
+    pub reversed: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    // Compiled in, so casting does not depend on the working directory
+    static ref FUTHARK: Vec<Rune> = serde_json::from_str(include_str!("../../static/runes.json"))
+        .expect("static/runes.json is valid");
+}
+
+/// The whole Futhark in order.
+pub fn futhark() -> &'static [Rune] {
+    &FUTHARK
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuneCast {
+    /// Odin's Rune: one rune for the matter at hand.
+    #[default]
+    Single,
+    /// The three Norns: what was, what is becoming, what shall be.
+    ThreeRune,
+    /// One rune for each of the Nine Worlds.
+    NineRune,
+}
+
+impl RuneCast {
+    /// Position names and what each position speaks to, in drawing order.
+    pub fn positions(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            RuneCast::Single => &[("Odin's Rune", "The heart of the matter")],
+            RuneCast::ThreeRune => &[
+                ("Urd", "What was"),
+                ("Verdandi", "What is becoming"),
+                ("Skuld", "What shall be"),
+            ],
+            RuneCast::NineRune => &[
+                ("Asgard", "Higher aims"),
+                ("Alfheim", "Inspiration"),
+                ("Vanaheim", "Harmony and relationships"),
+                ("Midgard", "The present self"),
+                ("Jotunheim", "Obstacles"),
+                ("Muspelheim", "Drive and passion"),
+                ("Niflheim", "Fears and what holds you back"),
+                ("Svartalfheim", "Skills and resources"),
+                ("Helheim", "The past and what is ending"),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RuneConfig {
+    #[serde(default)]
+    pub cast: RuneCast,
+    /// Read every rune upright instead of letting the entropy reverse (merkstave) them.
+    #[serde(default)]
+    pub upright_only: bool,
+}
+
+/// A rune drawn into one position of a cast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawnRune {
+    /// 1 = first rune drawn.
+    pub position: usize,
+    pub position_name: String,
+    pub position_meaning: String,
+    pub number: usize,
+    pub name: String,
+    pub letter: String,
+    pub aett: String,
+    /// Merkstave. Always false for the symmetric runes.
+    pub reversed: bool,
+    pub keywords: String,
+    pub meaning: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuneReading {
+    pub cast: RuneCast,
+    pub runes: Vec<DrawnRune>,
+    pub reversed_count: usize,
+}
+
+/// Draws the cast's runes from the bag with the session's permutation sampler.
+///
+/// A rune with a reversed reading falls merkstave with probability 1/2 unless
+/// `upright_only` is set; symmetric runes draw no reversal.
+pub fn cast_runes(session: &mut SimulationSession, config: &RuneConfig) -> RuneReading {
+    // 1. Shake the bag
+    let mut order: Vec<usize> = (0..FUTHARK.len()).collect();
+    session.shuffle(&mut order);
+
+    // 2. Draw one rune per position, upright or merkstave
+    let runes: Vec<DrawnRune> = config.cast.positions().iter().zip(order).enumerate().map(|(i, (&(name, meaning), idx))| {
+        let rune = &FUTHARK[idx];
+        let reversed_text = rune.reversed.as_ref().filter(|_| !config.upright_only && session.next_bool(0.5));
+        DrawnRune {
+            position: i + 1,
+            position_name: name.to_string(),
+            position_meaning: meaning.to_string(),
+            number: rune.number,
+            name: rune.name.clone(),
+            letter: rune.letter.clone(),
+            aett: rune.aett.clone(),
+            reversed: reversed_text.is_some(),
+            keywords: rune.keywords.clone(),
+            meaning: reversed_text.unwrap_or(&rune.upright).clone(),
+        }
+    }).collect();
+
+    RuneReading {
+        cast: config.cast,
+        reversed_count: runes.iter().filter(|r| r.reversed).count(),
+        runes,
+    }
+}
+
+#[cfg(test)]
+#[path = "runes_tests.rs"]
+mod tests;
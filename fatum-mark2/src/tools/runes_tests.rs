@@ -0,0 +1,32 @@
+use std::collections::HashSet;
+
+use super::{cast_runes, futhark, RuneCast, RuneConfig, FUTHARK_SIZE};
+use crate::engine::SimulationSession;
+
+#[test]
+fn test_futhark() {
+    let futhark = futhark();
+    assert_eq!(futhark.len(), FUTHARK_SIZE);
+    assert!(futhark.iter().enumerate().all(|(i, r)| r.number == i));
+    assert_eq!(futhark[0].name, "Fehu");
+    assert_eq!(futhark[23].name, "Dagaz");
+    assert!(futhark[10].reversed.is_none(), "Isa reads the same either way up");
+}
+
+#[test]
+fn test_casts() {
+    for (cast, size) in [(RuneCast::Single, 1), (RuneCast::ThreeRune, 3), (RuneCast::NineRune, 9)] {
+        let mut session = SimulationSession::new((0..=255).cycle().take(1024).collect());
+        let reading = cast_runes(&mut session, &RuneConfig { cast, upright_only: false });
+        assert_eq!(reading.runes.len(), size);
+        let distinct: HashSet<usize> = reading.runes.iter().map(|r| r.number).collect();
+        assert_eq!(distinct.len(), size);
+        // Symmetric runes never fall merkstave
+        assert!(reading.runes.iter().all(|r| !r.reversed || futhark()[r.number].reversed.is_some()));
+        assert_eq!(session.fallback_draws, 0);
+    }
+
+    let mut session = SimulationSession::new(vec![0xff; 1024]);
+    let reading = cast_runes(&mut session, &RuneConfig { cast: RuneCast::NineRune, upright_only: true });
+    assert_eq!(reading.reversed_count, 0);
+}
@@ -1,5 +1,29 @@
 use serde::{Deserialize, Serialize};
 
+/// The twelve Double Mountains, each a stem or trigram paired with the branch it precedes,
+/// indexed by branch (Zi = 0). Ren-Zi is centred on north.
+const DOUBLE_MOUNTAINS: [&str; 12] = [
+    "Ren-Zi", "Gui-Chou", "Gen-Yin", "Jia-Mao", "Yi-Chen", "Xun-Si",
+    "Bing-Wu", "Ding-Wei", "Kun-Shen", "Geng-You", "Xin-Xu", "Qian-Hai",
+];
+
+/// The 24 Mountains clockwise from Ren (337.5°), 15° each.
+const MOUNTAINS: [&str; 24] = [
+    "Ren", "Zi", "Gui", "Chou", "Gen", "Yin", "Jia", "Mao", "Yi", "Chen", "Xun", "Si",
+    "Bing", "Wu", "Ding", "Wei", "Kun", "Shen", "Geng", "You", "Xin", "Xu", "Qian", "Hai",
+];
+
+/// Eight Killings (Ba Sha Huang Quan): the branch that attacks each sitting trigram, by trigram
+/// in the order Kan, Gen, Zhen, Xun, Li, Kun, Dui, Qian. Kan dreads Chen, Kun Mao, Zhen Shen,
+/// Xun You, Qian Wu, Dui Si, Gen Yin and Li Hai.
+const EIGHT_KILLINGS: [usize; 8] = [9, 5, 17, 19, 23, 7, 11, 13];
+
+/// The twelve growth phases (Chang Sheng Shi Er Gong), from Birth.
+const GROWTH_PHASES: [&str; 12] = [
+    "Birth", "Bath", "Crown", "Officer", "Prosperity", "Weakening",
+    "Sickness", "Death", "Grave", "Extinction", "Conception", "Nurture",
+];
+
 /// Analysis report for the San He (Three Harmony) Water Method.
 ///
 /// San He focuses on the relationship between the Mountain (Sitting), Water (Facing/Exit),
@@ -9,46 +33,204 @@ pub struct SanHeAnalysis {
     pub water_method: String, // e.g. "Double Mountain San He"
     pub growth_phase: String, // Current phase of the water exit (e.g. "Death", "Grave")
     pub lucky_water_exit: Vec<String>, // Recommended exit directions
+    /// Double Mountain the house sits in, e.g. "Ren-Zi".
+    #[serde(default)]
+    pub sitting_mountain: String,
+    /// Three Harmony frame of the sitting, e.g. "Water Frame (Shen-Zi-Chen)".
+    #[serde(default)]
+    pub frame: String,
+    /// The growth-phase ring of the frame laid over the twelve Double Mountains.
+    #[serde(default)]
+    pub phases: Vec<GrowthPhase>,
+    #[serde(default)]
+    pub water_exit: Option<WaterExit>,
+    /// Directions where water is harmful for this house, whether or not any is there.
+    #[serde(default)]
+    pub sha_directions: Vec<ShaDirection>,
+    /// Water features that fall in one of the `sha_directions`.
+    #[serde(default)]
+    pub warnings: Vec<SanHeWarning>,
+}
+
+/// A direction water should keep away from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaDirection {
+    /// "Eight Killings", "Eight Roads Yellow Springs" or "Peach Blossom Sha".
+    pub kind: String,
+    pub mountain: String,
+    pub start_deg: f64,
+    pub end_deg: f64,
+}
+
+/// A water feature found in a Sha direction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SanHeWarning {
+    pub kind: String,
+    pub mountain: String,
+    /// Where the offending water is.
+    pub degrees: f64,
+    pub message: String,
+}
+
+/// One Double Mountain and the phase the frame's Qi is in there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrowthPhase {
+    pub mountain: String,
+    pub phase: String,
+    /// Compass span of the mountain in degrees; `start` is greater than `end` across north.
+    pub start_deg: f64,
+    pub end_deg: f64,
+    /// Water leaving here carries spent Qi away.
+    pub auspicious_exit: bool,
+}
+
+/// Reading of the actual water exit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaterExit {
+    pub degrees: f64,
+    pub mountain: String,
+    pub phase: String,
+    pub auspicious: bool,
+    pub note: String,
+}
+
+/// Branch (0 = Zi) of the Double Mountain holding `deg`.
+fn double_mountain(deg: f64) -> usize {
+    ((deg - 337.5).rem_euclid(360.0) / 30.0) as usize % 12
+}
+
+/// Index into `MOUNTAINS` of the mountain holding `deg`.
+fn mountain(deg: f64) -> usize {
+    ((deg - 337.5).rem_euclid(360.0) / 15.0) as usize % 24
+}
+
+fn sha_direction(kind: &str, idx: usize) -> ShaDirection {
+    ShaDirection {
+        kind: kind.to_string(),
+        mountain: MOUNTAINS[idx].to_string(),
+        start_deg: (337.5 + 15.0 * idx as f64) % 360.0,
+        end_deg: (352.5 + 15.0 * idx as f64) % 360.0,
+    }
+}
+
+/// Eight Roads Yellow Springs (Ba Lu Huang Quan): the trigram a stem facing must not release
+/// water toward. Geng and Ding fear Kun, Yi and Bing Xun, Jia and Gui Gen, Xin and Ren Qian.
+fn eight_roads(facing: usize) -> Option<usize> {
+    match MOUNTAINS[facing] {
+        "Geng" | "Ding" => Some(16),
+        "Yi" | "Bing" => Some(10),
+        "Jia" | "Gui" => Some(4),
+        "Xin" | "Ren" => Some(22),
+        _ => None,
+    }
+}
+
+fn sha_message(kind: &str) -> &'static str {
+    match kind {
+        "Eight Killings" => "Water here attacks the sitting trigram: accidents, lawsuits and sudden losses.",
+        "Eight Roads Yellow Springs" => "Water here drains the facing's wealth along the Yellow Springs road.",
+        _ => "Water at the frame's Bath position stirs scandal and affairs.",
+    }
+}
+
+/// Frame name and Birth branch for the frame a branch belongs to.
+fn frame_of(branch: usize) -> (&'static str, usize) {
+    match branch % 4 {
+        0 => ("Water Frame (Shen-Zi-Chen)", 8),
+        1 => ("Metal Frame (Si-You-Chou)", 5),
+        2 => ("Fire Frame (Yin-Wu-Xu)", 2),
+        _ => ("Wood Frame (Hai-Mao-Wei)", 11),
+    }
+}
+
+/// Water should leave through the declining phases, Weakening to Extinction.
+fn auspicious_exit(phase: usize) -> bool {
+    (5..=9).contains(&phase)
+}
+
+fn exit_note(phase: usize) -> &'static str {
+    match phase {
+        0 => "Water leaves at Birth: new Qi drains away before it can grow (Killing Birth).",
+        1 => "Water leaves at Bath: unsettled, but tolerated when the exit is narrow.",
+        2 | 3 => "Water leaves at Crown or Officer: talent and position drain away.",
+        4 => "Water leaves at Prosperity: the strongest Qi of the frame is lost.",
+        5..=9 => "Water leaves through a declining phase, carrying spent Qi away.",
+        _ => "Water leaves at Conception or Nurture: weak but not harmful.",
+    }
 }
 
 /// Analyzes the environment using San He Logic.
 ///
 /// Requires the Facing Degree (to determine the Sitting/Mountain) and optionally
 /// the degree where water exits the property.
-pub fn analyze_san_he(facing_deg: f64, _water_exit_deg: Option<f64>) -> SanHeAnalysis {
-    // 24 Mountains for San He
-    // Simplified Logic: Determine "Frame" based on Facing (Water Frame, Wood Frame, etc.)
-    // Then check Growth Phases.
-
-    // Frame Determination (Double Mountain)
-    // Water Frame: Shen-Zi-Chen (Monkey-Rat-Dragon) -> North
-    // Wood Frame: Hai-Mao-Wei (Pig-Rabbit-Goat) -> East
-    // Fire Frame: Yin-Wu-Xu (Tiger-Horse-Dog) -> South
-    // Metal Frame: Si-You-Chou (Snake-Rooster-Ox) -> West
-
-    // Map facing to Frame?
-    // Usually Sitting determines the Mountain (Dragon).
-    // Let's assume Sitting = Facing + 180.
-    let sitting = (facing_deg + 180.0) % 360.0;
-
-    // Simplified: Check typical "Killing Forces"
-    // Yellow Springs (Huang Quan) are specific directions that are harmful to specific mountains.
-    // Eight Killings (Ba Sha) are similar conflict points.
-    let warnings = if sitting >= 337.5 || sitting < 22.5 {
-        "Water Frame (North). Avoid Water exit at Dragon (SE) - Yellow Springs."
-    } else if sitting >= 67.5 && sitting < 112.5 {
-        "Wood Frame (East). Avoid Water exit at Goat (SW) - Yellow Springs."
-    } else if sitting >= 157.5 && sitting < 202.5 {
-        "Fire Frame (South). Avoid Water exit at Dog (NW) - Yellow Springs."
-    } else if sitting >= 247.5 && sitting < 292.5 {
-        "Metal Frame (West). Avoid Water exit at Ox (NE) - Yellow Springs."
-    } else {
-        "Mixed/Earth Frame. Check individual mountain affiliations."
-    };
+pub fn analyze_san_he(facing_deg: f64, water_exit_deg: Option<f64>, water_features: &[f64]) -> SanHeAnalysis {
+    // 1. Sitting Double Mountain and its frame
+    // Water Frame: Shen-Zi-Chen (Monkey-Rat-Dragon)
+    // Wood Frame: Hai-Mao-Wei (Pig-Rabbit-Goat)
+    // Fire Frame: Yin-Wu-Xu (Tiger-Horse-Dog)
+    // Metal Frame: Si-You-Chou (Snake-Rooster-Ox)
+    let sitting = (facing_deg + 180.0).rem_euclid(360.0);
+    let sitting_branch = double_mountain(sitting);
+    let (frame, birth) = frame_of(sitting_branch);
+
+    // 2. Growth-phase ring: Birth on the frame's Birth branch, running clockwise
+    let phase_of = |branch: usize| (branch + 12 - birth) % 12;
+    let phases: Vec<GrowthPhase> = (0..12).map(|b| GrowthPhase {
+        mountain: DOUBLE_MOUNTAINS[b].to_string(),
+        phase: GROWTH_PHASES[phase_of(b)].to_string(),
+        start_deg: (337.5 + 30.0 * b as f64) % 360.0,
+        end_deg: (7.5 + 30.0 * b as f64) % 360.0,
+        auspicious_exit: auspicious_exit(phase_of(b)),
+    }).collect();
+
+    let lucky_water_exit = phases.iter().filter(|p| p.auspicious_exit)
+        .map(|p| format!("{} ({}, {:.1}°-{:.1}°)", p.mountain, p.phase, p.start_deg, p.end_deg))
+        .collect();
+
+    // 3. Classify the actual exit
+    let water_exit = water_exit_deg.map(|deg| {
+        let branch = double_mountain(deg);
+        let phase = phase_of(branch);
+        WaterExit {
+            degrees: deg,
+            mountain: DOUBLE_MOUNTAINS[branch].to_string(),
+            phase: GROWTH_PHASES[phase].to_string(),
+            auspicious: auspicious_exit(phase),
+            note: exit_note(phase).to_string(),
+        }
+    });
+
+    // 4. Sha directions: Eight Killings by the sitting trigram, Eight Roads by the facing stem,
+    // and Peach Blossom at the frame's Bath
+    let mut sha_directions = vec![sha_direction("Eight Killings", EIGHT_KILLINGS[mountain(sitting) / 3])];
+    if let Some(idx) = eight_roads(mountain(facing_deg)) {
+        sha_directions.push(sha_direction("Eight Roads Yellow Springs", idx));
+    }
+    sha_directions.push(sha_direction("Peach Blossom Sha", 2 * ((birth + 1) % 12) + 1));
+
+    let warnings = water_exit_deg.iter().chain(water_features).flat_map(|&deg| {
+        let hit = MOUNTAINS[mountain(deg)];
+        sha_directions.iter().filter(move |s| s.mountain == hit).map(move |s| SanHeWarning {
+            kind: s.kind.clone(),
+            mountain: s.mountain.clone(),
+            degrees: deg,
+            message: sha_message(&s.kind).to_string(),
+        })
+    }).collect();
 
     SanHeAnalysis {
         water_method: "Double Mountain San He".to_string(),
-        growth_phase: "Analysis Requires Topography (Water Exit Degree)".to_string(),
-        lucky_water_exit: vec![warnings.to_string()],
+        growth_phase: water_exit.as_ref().map_or_else(|| "Water exit not given".to_string(), |w| w.phase.clone()),
+        lucky_water_exit,
+        sitting_mountain: DOUBLE_MOUNTAINS[sitting_branch].to_string(),
+        frame: frame.to_string(),
+        phases,
+        water_exit,
+        sha_directions,
+        warnings,
     }
 }
+
+#[cfg(test)]
+#[path = "san_he_tests.rs"]
+mod tests;
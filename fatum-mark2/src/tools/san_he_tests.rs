@@ -0,0 +1,54 @@
+use super::analyze_san_he;
+
+#[test]
+fn test_sitting_frame_and_ring() {
+    // Facing south sits Ren-Zi in the Water Frame: Birth at Shen, Prosperity at Zi, Grave at Chen
+    let sh = analyze_san_he(180.0, None, &[]);
+    assert_eq!(sh.sitting_mountain, "Ren-Zi");
+    assert_eq!(sh.frame, "Water Frame (Shen-Zi-Chen)");
+    assert_eq!(sh.phases[8].phase, "Birth");
+    assert_eq!(sh.phases[0].phase, "Prosperity");
+    assert_eq!(sh.phases[4].phase, "Grave");
+    assert_eq!((sh.phases[0].start_deg, sh.phases[0].end_deg), (337.5, 7.5));
+    assert!(sh.water_exit.is_none());
+    // Exits from Weakening (Chou) through Extinction (Si)
+    assert_eq!(sh.lucky_water_exit.len(), 5);
+    assert!(sh.lucky_water_exit[0].starts_with("Gui-Chou (Weakening"));
+}
+
+#[test]
+fn test_water_exit_phase() {
+    // Water Frame leaving through Yi-Chen is the classic Grave exit
+    let grave = analyze_san_he(180.0, Some(120.0), &[]).water_exit.unwrap();
+    assert_eq!((grave.mountain.as_str(), grave.phase.as_str()), ("Yi-Chen", "Grave"));
+    assert!(grave.auspicious);
+    // Leaving through the Birth mountain drains new Qi
+    let birth = analyze_san_he(180.0, Some(240.0), &[]);
+    assert_eq!(birth.growth_phase, "Birth");
+    assert!(!birth.water_exit.unwrap().auspicious);
+    // Facing north sits Bing-Wu in the Fire Frame, whose Grave is Xin-Xu
+    let fire = analyze_san_he(0.0, Some(300.0), &[]);
+    assert_eq!(fire.frame, "Fire Frame (Yin-Wu-Xu)");
+    assert_eq!(fire.growth_phase, "Grave");
+}
+
+#[test]
+fn test_sha_directions_and_warnings() {
+    // Sitting Zi (Kan) dreads Chen; the Water Frame's Bath is You; a Wu facing has no Eight Roads
+    let sh = analyze_san_he(180.0, Some(120.0), &[270.0, 45.0]);
+    let kinds: Vec<(&str, &str)> = sh.sha_directions.iter().map(|s| (s.kind.as_str(), s.mountain.as_str())).collect();
+    assert_eq!(kinds, vec![("Eight Killings", "Chen"), ("Peach Blossom Sha", "You")]);
+    assert_eq!((sh.sha_directions[0].start_deg, sh.sha_directions[0].end_deg), (112.5, 127.5));
+    // The Chen exit and the pond in You are flagged; the one in Gen is not
+    let hits: Vec<(&str, f64)> = sh.warnings.iter().map(|w| (w.kind.as_str(), w.degrees)).collect();
+    assert_eq!(hits, vec![("Eight Killings", 120.0), ("Peach Blossom Sha", 270.0)]);
+}
+
+#[test]
+fn test_eight_roads_follows_facing_stem() {
+    // Facing Bing must not release water toward Xun
+    let sh = analyze_san_he(165.0, Some(135.0), &[]);
+    let road = sh.sha_directions.iter().find(|s| s.kind == "Eight Roads Yellow Springs").unwrap();
+    assert_eq!(road.mountain, "Xun");
+    assert!(sh.warnings.iter().any(|w| w.kind == "Eight Roads Yellow Springs" && w.degrees == 135.0));
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use crate::engine::SimulationSession;
+
+/// Cards in a full Rider-Waite-Smith deck.
+pub const DECK_SIZE: usize = 78;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Arcana {
+    Major,
+    Minor,
+}
+
+/// A card and its meanings from `tarot.json`. Majors are numbered 0-21 (The Fool to The World),
+/// then each suit runs Ace to King: Wands 22-35, Cups 36-49, Swords 50-63, Pentacles 64-77.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TarotCard {
+    pub number: usize,
+    pub name: String,
+    pub arcana: Arcana,
+    pub suit: Option<String>,
+    pub element: Option<String>,
+    pub keywords: String,
+    pub upright: String,
+    pub reversed: String,
+}
+
+lazy_static::lazy_static! {
+    // Compiled in, so drawing does not depend on the working directory
+    static ref DECK: Vec<TarotCard> = serde_json::from_str(include_str!("../../static/tarot.json"))
+        .expect("static/tarot.json is valid");
+}
+
+/// The whole deck in order.
+pub fn deck() -> &'static [TarotCard] {
+    &DECK
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Spread {
+    /// One card for the heart of the matter.
+    #[default]
+    Single,
+    /// Past, present and future.
+    ThreeCard,
+    CelticCross,
+}
+
+impl Spread {
+    /// Position names and what each position speaks to, in laying order.
+    pub fn positions(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Spread::Single => &[("Focus", "The heart of the matter")],
+            Spread::ThreeCard => &[
+                ("Past", "What led here"),
+                ("Present", "Where things stand"),
+                ("Future", "Where things are heading"),
+            ],
+            Spread::CelticCross => &[
+                ("Present", "The situation as it is"),
+                ("Challenge", "What crosses you"),
+                ("Foundation", "The root beneath the matter"),
+                ("Recent Past", "What is passing away"),
+                ("Crown", "The conscious aim or best outcome"),
+                ("Near Future", "What is coming next"),
+                ("Self", "Your own stance"),
+                ("Environment", "Other people and surroundings"),
+                ("Hopes and Fears", "What you hope for or dread"),
+                ("Outcome", "Where this leads"),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TarotConfig {
+    #[serde(default)]
+    pub spread: Spread,
+    /// Deal every card upright instead of letting the entropy reverse them.
+    #[serde(default)]
+    pub upright_only: bool,
+}
+
+/// A card dealt into one position of a spread.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawnCard {
+    /// 1 = first card laid.
+    pub position: usize,
+    pub position_name: String,
+    pub position_meaning: String,
+    pub number: usize,
+    pub name: String,
+    pub arcana: Arcana,
+    pub suit: Option<String>,
+    pub reversed: bool,
+    pub keywords: String,
+    /// The upright or reversed meaning, whichever way the card fell.
+    pub meaning: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TarotReading {
+    pub spread: Spread,
+    pub cards: Vec<DrawnCard>,
+    pub major_count: usize,
+    pub reversed_count: usize,
+    /// Suit appearing most often among the minor cards, when one leads outright.
+    pub dominant_suit: Option<String>,
+}
+
+/// Shuffles the deck with the session's permutation sampler and deals the spread.
+///
+/// Each card is reversed with probability 1/2 unless `upright_only` is set.
+pub fn draw_spread(session: &mut SimulationSession, config: &TarotConfig) -> TarotReading {
+    // 1. Shuffle the whole deck, then deal from the top
+    let mut order: Vec<usize> = (0..DECK.len()).collect();
+    session.shuffle(&mut order);
+
+    // 2. Lay the cards into the spread positions, each upright or reversed
+    let cards: Vec<DrawnCard> = config.spread.positions().iter().zip(order).enumerate().map(|(i, (&(name, meaning), idx))| {
+        let card = &DECK[idx];
+        let reversed = !config.upright_only && session.next_bool(0.5);
+        DrawnCard {
+            position: i + 1,
+            position_name: name.to_string(),
+            position_meaning: meaning.to_string(),
+            number: card.number,
+            name: card.name.clone(),
+            arcana: card.arcana,
+            suit: card.suit.clone(),
+            reversed,
+            keywords: card.keywords.clone(),
+            meaning: if reversed { card.reversed.clone() } else { card.upright.clone() },
+        }
+    }).collect();
+
+    // 3. Overall tallies
+    let mut suit_counts: Vec<(&str, usize)> = Vec::new();
+    for suit in cards.iter().filter_map(|c| c.suit.as_deref()) {
+        match suit_counts.iter_mut().find(|(s, _)| *s == suit) {
+            Some((_, n)) => *n += 1,
+            None => suit_counts.push((suit, 1)),
+        }
+    }
+    suit_counts.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+    let dominant_suit = match suit_counts.as_slice() {
+        [(suit, n), rest @ ..] if rest.first().is_none_or(|(_, m)| m < n) => Some(suit.to_string()),
+        _ => None,
+    };
+
+    TarotReading {
+        spread: config.spread,
+        major_count: cards.iter().filter(|c| c.arcana == Arcana::Major).count(),
+        reversed_count: cards.iter().filter(|c| c.reversed).count(),
+        dominant_suit,
+        cards,
+    }
+}
+
+#[cfg(test)]
+#[path = "tarot_tests.rs"]
+mod tests;
@@ -0,0 +1,38 @@
+use std::collections::HashSet;
+
+use super::{deck, draw_spread, Arcana, Spread, TarotConfig, DECK_SIZE};
+use crate::engine::SimulationSession;
+
+#[test]
+fn test_deck() {
+    let deck = deck();
+    assert_eq!(deck.len(), DECK_SIZE);
+    assert!(deck.iter().enumerate().all(|(i, c)| c.number == i));
+    assert_eq!(deck.iter().filter(|c| c.arcana == Arcana::Major).count(), 22);
+    for suit in ["Wands", "Cups", "Swords", "Pentacles"] {
+        assert_eq!(deck.iter().filter(|c| c.suit.as_deref() == Some(suit)).count(), 14);
+    }
+    assert_eq!(deck[22].name, "Ace of Wands");
+    assert_eq!(deck[77].name, "King of Pentacles");
+}
+
+#[test]
+fn test_spreads() {
+    for (spread, size) in [(Spread::Single, 1), (Spread::ThreeCard, 3), (Spread::CelticCross, 10)] {
+        let mut session = SimulationSession::new((0..=255).cycle().take(2048).collect());
+        let reading = draw_spread(&mut session, &TarotConfig { spread, upright_only: false });
+        assert_eq!(reading.cards.len(), size);
+        let distinct: HashSet<usize> = reading.cards.iter().map(|c| c.number).collect();
+        assert_eq!(distinct.len(), size);
+        assert!(reading.cards.iter().enumerate().all(|(i, c)| c.position == i + 1));
+        assert_eq!(session.fallback_draws, 0);
+    }
+}
+
+#[test]
+fn test_upright_only() {
+    let mut session = SimulationSession::new(vec![0xff; 2048]);
+    let reading = draw_spread(&mut session, &TarotConfig { spread: Spread::CelticCross, upright_only: true });
+    assert_eq!(reading.reversed_count, 0);
+    assert!(reading.cards.iter().all(|c| c.meaning == deck()[c.number].upright));
+}
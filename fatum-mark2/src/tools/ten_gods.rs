@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::tools::bazi_strength::{stem_element, HIDDEN_STEMS, HIDDEN_WEIGHTS, PILLARS};
+use crate::tools::chinese_meta::get_stem;
+
+/// The Ten Gods, indexed by `2 * relation + (1 if polarity differs)` where the relation
+/// counts generating-cycle steps from the day master's element.
+pub const TEN_GODS: [&str; 10] = [
+    "Friend (Bi Jian)",
+    "Rob Wealth (Jie Cai)",
+    "Eating God (Shi Shen)",
+    "Hurting Officer (Shang Guan)",
+    "Indirect Wealth (Pian Cai)",
+    "Direct Wealth (Zheng Cai)",
+    "Seven Killings (Qi Sha)",
+    "Direct Officer (Zheng Guan)",
+    "Indirect Resource (Pian Yin)",
+    "Direct Resource (Zheng Yin)",
+];
+
+/// The Ten God a stem is to the day master.
+pub fn ten_god(day_master: usize, stem: usize) -> &'static str {
+    let relation = (stem_element(stem) + 5 - stem_element(day_master)) % 5;
+    let differs = (stem % 2 != day_master % 2) as usize;
+    TEN_GODS[2 * relation + differs]
+}
+
+/// One stem of the chart and the god it plays.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenGodPlacement {
+    /// "year", "month", "day" or "hour".
+    pub pillar: String,
+    /// False for the visible stem, true for a stem hidden in the branch.
+    pub hidden: bool,
+    pub stem: String,
+    pub god: String,
+    /// 1.0 for visible stems; 1.0, 0.5 or 0.3 for main, middle and residual hidden Qi.
+    pub weight: f64,
+}
+
+/// How strongly the chart speaks to one area of life.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifeArea {
+    /// "relationship", "wealth" or "career".
+    pub area: String,
+    /// Gods that stand for this area in the chart.
+    pub gods: Vec<String>,
+    /// Summed weight of those gods across the chart.
+    pub weight: f64,
+    pub reading: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenGodsAnalysis {
+    /// Every stem except the day master itself, pillar by pillar.
+    pub placements: Vec<TenGodPlacement>,
+    /// Summed weight of each god present.
+    pub totals: BTreeMap<String, f64>,
+    pub areas: Vec<LifeArea>,
+}
+
+/// Summed weight of the gods, rounded to two decimals.
+fn weight_of(totals: &BTreeMap<String, f64>, gods: &[&str]) -> f64 {
+    let sum: f64 = gods.iter().filter_map(|g| totals.get(*g)).sum();
+    (sum * 100.0).round() / 100.0
+}
+
+fn presence(weight: f64) -> &'static str {
+    if weight == 0.0 {
+        "absent"
+    } else if weight < 1.5 {
+        "light"
+    } else if weight < 3.0 {
+        "clear"
+    } else {
+        "dominant"
+    }
+}
+
+/// Classifies every stem and hidden stem of a `[year, month, day, hour]` chart against the
+/// day stem and reads relationship, wealth and career from the result.
+///
+/// The spouse star is Wealth for a man and Officer for a woman; without a gender both count.
+/// `day_master_strong` (from the strength analysis) decides whether heavy Wealth and Officer
+/// stars are carried or are a burden.
+pub fn analyze(stems: [usize; 4], branches: [usize; 4], gender: Option<&str>, day_master_strong: Option<bool>) -> TenGodsAnalysis {
+    let dm = stems[2];
+    let mut placements = Vec::new();
+    for i in 0..4 {
+        if i != 2 {
+            placements.push(TenGodPlacement {
+                pillar: PILLARS[i].to_string(),
+                hidden: false,
+                stem: get_stem(stems[i]).to_string(),
+                god: ten_god(dm, stems[i]).to_string(),
+                weight: 1.0,
+            });
+        }
+        for (&hidden, weight) in HIDDEN_STEMS[branches[i] % 12].iter().zip(HIDDEN_WEIGHTS) {
+            placements.push(TenGodPlacement {
+                pillar: PILLARS[i].to_string(),
+                hidden: true,
+                stem: get_stem(hidden).to_string(),
+                god: ten_god(dm, hidden).to_string(),
+                weight,
+            });
+        }
+    }
+    let mut totals = BTreeMap::new();
+    for p in &placements {
+        *totals.entry(p.god.clone()).or_insert(0.0) += p.weight;
+    }
+
+    let wealth_gods = [TEN_GODS[4], TEN_GODS[5]];
+    let officer_gods = [TEN_GODS[6], TEN_GODS[7]];
+    let wealth = weight_of(&totals, &wealth_gods);
+    let officer = weight_of(&totals, &officer_gods);
+
+    // Wealth: how much there is, and whether the day master can hold it
+    let wealth_reading = match (presence(wealth), day_master_strong) {
+        ("absent", _) => "No Wealth star: income comes through skill and effort rather than windfalls.".to_string(),
+        (p, Some(false)) if wealth >= 1.5 => format!("Wealth is {} but the day master is weak; partners or Resource years help hold on to it.", p),
+        (p, _) if totals.get(TEN_GODS[4]).copied().unwrap_or(0.0) > totals.get(TEN_GODS[5]).copied().unwrap_or(0.0) => {
+            format!("Wealth is {}, mostly Indirect: gains from ventures, trading and side income.", p)
+        }
+        (p, _) => format!("Wealth is {}, mostly Direct: steady salary and saved assets.", p),
+    };
+
+    // Career: Officer brings rank and structure, Seven Killings pressure and competition
+    let career_reading = match (presence(officer), day_master_strong) {
+        ("absent", _) => "No Officer star: independent or self-directed work suits better than hierarchy.".to_string(),
+        (p, Some(false)) if officer >= 1.5 => format!("Authority is {} and presses on a weak day master; pace ambitions and lean on mentors.", p),
+        (p, _) if totals.get(TEN_GODS[6]).copied().unwrap_or(0.0) > totals.get(TEN_GODS[7]).copied().unwrap_or(0.0) => {
+            format!("Authority is {}, led by Seven Killings: drive and competition, suited to leadership under pressure.", p)
+        }
+        (p, _) => format!("Authority is {}, led by Direct Officer: recognition within organisations and steady promotion.", p),
+    };
+
+    // Relationship: the spouse star, plus what sits in the spouse palace (day branch main Qi)
+    let spouse_gods: Vec<&str> = match gender {
+        Some("M") => wealth_gods.to_vec(),
+        Some("F") => officer_gods.to_vec(),
+        _ => wealth_gods.iter().chain(&officer_gods).copied().collect(),
+    };
+    let spouse = weight_of(&totals, &spouse_gods);
+    let palace = ten_god(dm, HIDDEN_STEMS[branches[2] % 12][0]);
+    let mut relationship_reading = match presence(spouse) {
+        "absent" => "The spouse star is absent; relationships tend to come later or through friends.".to_string(),
+        "dominant" => "The spouse star is dominant: many prospects, commitment takes deliberate choice.".to_string(),
+        p => format!("The spouse star is {}.", p),
+    };
+    relationship_reading.push_str(&format!(" The spouse palace holds {}.", palace));
+    if [TEN_GODS[1], TEN_GODS[3], TEN_GODS[6]].contains(&palace) {
+        relationship_reading.push_str(" This god brings friction into partnerships; patience is needed.");
+    }
+
+    let areas = vec![
+        LifeArea { area: "relationship".to_string(), gods: spouse_gods.iter().map(|g| g.to_string()).collect(), weight: spouse, reading: relationship_reading },
+        LifeArea { area: "wealth".to_string(), gods: wealth_gods.iter().map(|g| g.to_string()).collect(), weight: wealth, reading: wealth_reading },
+        LifeArea { area: "career".to_string(), gods: officer_gods.iter().map(|g| g.to_string()).collect(), weight: officer, reading: career_reading },
+    ];
+
+    TenGodsAnalysis { placements, totals, areas }
+}
+
+#[cfg(test)]
+#[path = "ten_gods_tests.rs"]
+mod tests;
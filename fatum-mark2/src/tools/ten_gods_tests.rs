@@ -0,0 +1,60 @@
+use super::{analyze, ten_god, TEN_GODS};
+
+#[test]
+fn test_ten_gods_of_jia_and_yi() {
+    // Jia day master against all ten stems, in order
+    let gods: Vec<&str> = (0..10).map(|s| ten_god(0, s)).collect();
+    assert_eq!(gods, TEN_GODS.to_vec());
+    // For Yi, Geng (yang Metal) is the Direct Officer and Xin the Seven Killings
+    assert_eq!(ten_god(1, 6), "Direct Officer (Zheng Guan)");
+    assert_eq!(ten_god(1, 7), "Seven Killings (Qi Sha)");
+    assert_eq!(ten_god(1, 0), "Rob Wealth (Jie Cai)");
+    // Ren (yang Water) is produced by Geng: Indirect Resource for Geng
+    assert_eq!(ten_god(6, 8), "Eating God (Shi Shen)");
+    assert_eq!(ten_god(6, 4), "Indirect Resource (Pian Yin)");
+}
+
+#[test]
+fn test_placements_cover_stems_and_hidden_stems() {
+    // Wu Chen / Ji Wei / Jia Zi / Geng Shen
+    let a = analyze([4, 5, 0, 6], [4, 7, 0, 8], None, None);
+    // Three visible stems (not the day master) plus 3 + 3 + 1 + 3 hidden stems
+    assert_eq!(a.placements.len(), 13);
+    assert!(!a.placements.iter().any(|p| p.pillar == "day" && !p.hidden));
+    let day_branch: Vec<&str> = a.placements.iter().filter(|p| p.pillar == "day").map(|p| p.god.as_str()).collect();
+    assert_eq!(day_branch, vec!["Direct Resource (Zheng Yin)"]);
+    // Wu (1.0) + Chen's Wu (1.0) + Shen's Wu (0.3)
+    assert_eq!(a.totals["Indirect Wealth (Pian Cai)"], 2.3);
+}
+
+#[test]
+fn test_spouse_star_follows_gender() {
+    let stems = [4, 5, 0, 6];
+    let branches = [4, 7, 0, 8];
+    let man = analyze(stems, branches, Some("M"), None);
+    let woman = analyze(stems, branches, Some("F"), None);
+    let unknown = analyze(stems, branches, None, None);
+    let relationship = |a: &super::TenGodsAnalysis| a.areas.iter().find(|x| x.area == "relationship").unwrap().clone();
+    assert_eq!(relationship(&man).gods, vec!["Indirect Wealth (Pian Cai)", "Direct Wealth (Zheng Cai)"]);
+    assert_eq!(relationship(&woman).gods, vec!["Seven Killings (Qi Sha)", "Direct Officer (Zheng Guan)"]);
+    assert_eq!(relationship(&unknown).gods.len(), 4);
+    assert!(relationship(&man).weight > relationship(&woman).weight);
+    assert!(relationship(&man).reading.contains("spouse palace holds Direct Resource"));
+}
+
+#[test]
+fn test_area_readings() {
+    // All Wood and Fire: no Officer or Wealth stars at all
+    let a = analyze([0, 2, 0, 2], [2, 3, 3, 2], Some("M"), Some(true));
+    let area = |name: &str| a.areas.iter().find(|x| x.area == name).unwrap();
+    // Yin hides Wu (residual Earth), so Wealth is only light
+    assert_eq!(area("wealth").weight, 0.6);
+    assert_eq!(area("career").weight, 0.0);
+    assert!(area("career").reading.starts_with("No Officer star"));
+
+    // Heavy Earth against a weak Jia day master
+    let a = analyze([4, 5, 0, 4], [4, 10, 6, 1], Some("M"), Some(false));
+    let wealth = a.areas.iter().find(|x| x.area == "wealth").unwrap();
+    assert!(wealth.weight >= 3.0);
+    assert!(wealth.reading.contains("dominant but the day master is weak"), "{}", wealth.reading);
+}
@@ -0,0 +1,26 @@
+use crate::tools::feng_shui::{FlyingStarChart, Palace};
+use crate::tools::floor_plan::GRID;
+
+/// A Period 9 chart with every palace quiet except the ones given as `(sector, mountain, water, visiting)`.
+pub fn chart(stars: &[(&str, i32, i32, i32)]) -> FlyingStarChart {
+    period_chart(9, stars)
+}
+
+/// Like `chart`, for a house built in `period`.
+pub fn period_chart(period: i32, stars: &[(&str, i32, i32, i32)]) -> FlyingStarChart {
+    let palaces = GRID.iter().flatten().map(|sector| {
+        let (m, w, v) = stars.iter().find(|s| s.0 == *sector).map(|s| (s.1, s.2, s.3)).unwrap_or((6, 6, 6));
+        Palace { sector: sector.to_string(), base_star: period, mountain_star: m, water_star: w, visiting_star: v }
+    }).collect();
+    FlyingStarChart { period, label: "Test".to_string(), facing_mountain: "Wu".to_string(), sitting_mountain: "Zi".to_string(), palaces }
+}
+
+/// A well-mixed stand-in for beacon bytes (splitmix64).
+pub fn entropy(len: usize) -> Vec<u8> {
+    (0..len.div_ceil(8) as u64).flat_map(|i| {
+        let mut z = i.wrapping_add(1).wrapping_mul(0x9E3779B97F4A7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        (z ^ (z >> 31)).to_le_bytes()
+    }).take(len).collect()
+}
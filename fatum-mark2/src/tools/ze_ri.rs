@@ -1,6 +1,15 @@
+use std::collections::HashMap;
 use chrono::{NaiveDate, Datelike};
-use crate::tools::chinese_meta::{is_six_clash, is_six_combination, get_branch};
+use crate::engine::{SimulationOptions, SimulationSession};
+use crate::services::quantum;
+use crate::engine::timeline::WU_XING;
+use crate::tools::bazi_strength::{stem_element, HIDDEN_STEMS};
+use crate::tools::da_liu_ren::NOBLE;
+use crate::tools::chinese_meta::{day_pillar, is_six_clash, is_six_combination, get_branch, get_stem};
 use crate::tools::astronomy::get_solar_term;
+use crate::tools::feng_shui::calculate_bazi;
+use crate::tools::lunar::LunarDate;
+use crate::tools::i18n::{localize, Locale};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -10,6 +19,26 @@ pub struct DateSelectionConfig {
     pub intention: Option<String>,
     pub activities: Option<Vec<String>>, // List of desired activities
     pub user_birth_year: Option<i32>, // Personalized Mode
+    /// Full birth date; days are also scored against the day master and its useful elements.
+    #[serde(default)]
+    pub user_birth_date: Option<NaiveDate>,
+    /// Birth hour (0-23) for the hour pillar; noon when unknown.
+    #[serde(default)]
+    pub user_birth_hour: Option<u32>,
+    /// Also returns days scoring below zero, so callers can see why they are bad.
+    #[serde(default)]
+    pub include_inauspicious: bool,
+    /// Lowest score returned (default 0); negative values need `include_inauspicious`.
+    #[serde(default)]
+    pub min_score: Option<i32>,
+    /// Breaks ties between qualifying dates with a weighted simulation on quantum entropy.
+    #[serde(default)]
+    pub quantum_mode: bool,
+    #[serde(default)]
+    pub simulation_options: Option<SimulationOptions>,
+    /// Language of the day and hour officer names (defaults to English).
+    #[serde(default)]
+    pub locale: Option<Locale>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,6 +49,110 @@ pub struct AuspiciousDate {
     pub officer: String, // 12 Day Officer
     pub suitable_activities: Vec<String>,
     pub collision: Option<String>, // e.g. "Year Breaker"
+    #[serde(default)]
+    pub lunar_date: Option<String>, // e.g. "Lunar 2024, Month 3, Day 7"
+    #[serde(default)]
+    pub mansion: Option<String>, // 28 Lunar Mansions (Xiu), e.g. "Jiao (Horn)"
+    #[serde(default)]
+    pub hours: Vec<HourSlot>, // Filled for the top-ranked days only
+}
+
+/// Date favoured by a weighted simulation over the qualifying dates (quantum mode only).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZeRiQuantumPick {
+    pub favored_date: NaiveDate,
+    /// Classical score of the favoured date.
+    pub favored_score: i32,
+    /// How far the entropy behind the pick strayed from noise (0 to 1).
+    #[serde(default)]
+    pub volatility_index: f64,
+    /// Draws per date, keyed by ISO date.
+    pub distribution: HashMap<String, usize>,
+    pub anomalies: Vec<String>,
+    pub narrative: Vec<String>,
+}
+
+/// Simulated draws per quantum selection: one 8-byte draw each, 4 KB of entropy in all.
+pub const DATE_SIMULATIONS: usize = 512;
+
+/// One double-hour of a chosen day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourSlot {
+    pub branch: String, // e.g. "Zi (Rat)"
+    pub pillar: String, // e.g. "Jia Zi"
+    pub time_range: String, // e.g. "23:00-01:00"
+    pub officer: String, // Yellow/Black Path hour spirit, e.g. "Qing Long (Green Dragon)"
+    pub yellow_path: bool,
+    pub noble: bool, // Tian Yi Noble (Gui Ren) hour of the day stem
+    pub clash: Option<String>,
+    pub score: i32,
+    pub summary: String,
+}
+
+// Yang Gong's thirteen taboo days (lunar month, day)
+const YANG_GONG_TABOO: [(u32, u32); 13] = [
+    (1, 13), (2, 11), (3, 9), (4, 7), (5, 5), (6, 3), (7, 1),
+    (7, 29), (8, 27), (9, 25), (10, 23), (11, 21), (12, 19)
+];
+
+// Yue Ji (Month Taboo) days of every lunar month
+const YUE_JI: [u32; 3] = [5, 14, 23];
+
+/// One of the 28 Lunar Mansions (Er Shi Ba Xiu) and what its day favours.
+pub struct Mansion {
+    pub name: &'static str,
+    pub english: &'static str,
+    pub auspicious: bool,
+    pub good_for: &'static [&'static str],
+    pub avoid: &'static [&'static str],
+}
+
+impl Mansion {
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.name, self.english)
+    }
+}
+
+const fn mansion(name: &'static str, english: &'static str, auspicious: bool, good_for: &'static [&'static str], avoid: &'static [&'static str]) -> Mansion {
+    Mansion { name, english, auspicious, good_for, avoid }
+}
+
+// 28 Lunar Mansions from Jiao; each keeps its weekday (Jiao falls on Thursdays)
+const MANSIONS: [Mansion; 28] = [
+    mansion("Jiao", "Horn", true, &["Marriage", "Construction", "Travel"], &["Burial"]),
+    mansion("Kang", "Neck", false, &[], &["Marriage", "Construction"]),
+    mansion("Di", "Root", false, &[], &["Marriage", "Opening Business"]),
+    mansion("Fang", "Room", true, &["Marriage", "Moving House", "Opening Business"], &[]),
+    mansion("Xin", "Heart", false, &["Worship"], &["Marriage", "Construction", "Travel"]),
+    mansion("Wei", "Tail", true, &["Marriage", "Construction", "Burial"], &[]),
+    mansion("Ji", "Winnowing Basket", true, &["Construction", "Opening Business", "Burial"], &["Marriage"]),
+    mansion("Dou", "Dipper", true, &["Construction", "Travel", "Opening Business"], &[]),
+    mansion("Niu", "Ox", false, &[], &["Marriage", "Opening Business"]),
+    mansion("Nu", "Girl", false, &[], &["Marriage", "Construction"]),
+    mansion("Xu", "Emptiness", false, &[], &["Construction", "Burial"]),
+    mansion("Wei", "Rooftop", false, &[], &["Travel", "Construction"]),
+    mansion("Shi", "Encampment", true, &["Marriage", "Construction", "Moving House"], &[]),
+    mansion("Bi", "Wall", true, &["Marriage", "Construction", "Opening Business"], &[]),
+    mansion("Kui", "Legs", false, &["Construction"], &["Opening Business"]),
+    mansion("Lou", "Bond", true, &["Marriage", "Construction"], &[]),
+    mansion("Wei", "Stomach", true, &["Marriage", "Opening Business"], &[]),
+    mansion("Mao", "Hairy Head", false, &[], &["Marriage", "Burial"]),
+    mansion("Bi", "Net", true, &["Construction", "Burial", "Marriage"], &[]),
+    mansion("Zi", "Turtle Beak", false, &[], &["Burial"]),
+    mansion("Shen", "Three Stars", true, &["Construction", "Travel"], &["Marriage"]),
+    mansion("Jing", "Well", true, &["Construction", "Travel"], &["Burial"]),
+    mansion("Gui", "Ghost", false, &["Burial"], &["Marriage", "Construction"]),
+    mansion("Liu", "Willow", false, &[], &["Construction", "Burial"]),
+    mansion("Xing", "Star", false, &[], &["Construction"]),
+    mansion("Zhang", "Extended Net", true, &["Marriage", "Opening Business", "Worship"], &[]),
+    mansion("Yi", "Wings", false, &[], &["Construction", "Marriage"]),
+    mansion("Zhen", "Chariot", true, &["Travel", "Marriage", "Moving House"], &[]),
+];
+
+/// The Lunar Mansion ruling `date`. The cycle runs unbroken; 2024-02-12 (a Monday) was Xin.
+pub fn lunar_mansion(date: NaiveDate) -> &'static Mansion {
+    let days = (date - NaiveDate::from_ymd_opt(2024, 2, 12).unwrap()).num_days();
+    &MANSIONS[(4 + days).rem_euclid(28) as usize]
 }
 
 // 12 Day Officers (Jian Chu)
@@ -29,21 +162,173 @@ const OFFICERS: [&str; 12] = [
     "Cheng (Success)", "Shou (Receive)", "Kai (Open)", "Bi (Close)"
 ];
 
+// Days in a result set that get an hour-by-hour breakdown
+const TOP_DAYS_WITH_HOURS: usize = 3;
+
+// 12 Hour Spirits (Huang Dao / Hei Dao), counted from Qing Long; Yellow Path ones are auspicious
+const HOUR_OFFICERS: [(&str, bool); 12] = [
+    ("Qing Long (Green Dragon)", true), ("Ming Tang (Bright Hall)", true),
+    ("Tian Xing (Heavenly Punishment)", false), ("Zhu Que (Vermilion Bird)", false),
+    ("Jin Kui (Golden Coffer)", true), ("Tian De (Heavenly Virtue)", true),
+    ("Bai Hu (White Tiger)", false), ("Yu Tang (Jade Hall)", true),
+    ("Tian Lao (Heavenly Prison)", false), ("Xuan Wu (Black Tortoise)", false),
+    ("Si Ming (Life Controller)", true), ("Gou Chen (Hook)", false),
+];
+
+// Ten God family of an element, by its distance from the day master's along the generating cycle
+const RELATIONS: [&str; 5] = ["Companion", "Output", "Wealth", "Officer", "Resource"];
+
+/// The parts of a birth chart personal scoring needs.
+struct PersonalChart {
+    day_stem: usize,
+    day_branch: usize,
+    useful: Vec<usize>,
+    unfavorable: Vec<usize>,
+}
+
+impl PersonalChart {
+    fn from_birth(date: NaiveDate, hour: u32) -> Result<Self, String> {
+        let bazi = calculate_bazi(date.year(), date.month(), date.day(), hour, None, None, None).map_err(|e| e.to_string())?;
+        let strength = bazi.strength.ok_or("Day master strength unavailable")?;
+        let indices = |names: &[String]| names.iter().filter_map(|n| WU_XING.iter().position(|e| e == n)).collect();
+        let (day_stem, day_branch) = day_pillar(date);
+        Ok(PersonalChart {
+            day_stem,
+            day_branch,
+            useful: indices(&strength.useful_elements),
+            unfavorable: indices(&strength.unfavorable_elements),
+        })
+    }
+}
+
+/// Scores a day against a birth chart: clashes with the natal day pillar and whether the
+/// day's elements are ones the day master needs. Resource and Wealth days earn extra when useful.
+fn personal_score(date: NaiveDate, chart: &PersonalChart) -> (i32, Vec<String>) {
+    let (stem, branch) = day_pillar(date);
+    let dm = stem_element(chart.day_stem);
+    let element = stem_element(stem);
+    let relation = RELATIONS[(element + 5 - dm) % 5];
+    let mut score = 0;
+    let mut notes = Vec::new();
+
+    // 1. Day pillar against the natal day pillar
+    if is_six_clash(chart.day_branch, branch) {
+        score -= 30;
+        notes.push(format!("Clashes your day pillar ({} vs {})", get_branch(chart.day_branch), get_branch(branch)));
+        if relation == "Officer" {
+            score -= 10;
+            notes.push("Heaven strikes, earth clashes (Tian Ke Di Chong)".to_string());
+        }
+    } else if is_six_combination(chart.day_branch, branch) {
+        score += 10;
+        notes.push(format!("Combines with your day branch ({} + {})", get_branch(chart.day_branch), get_branch(branch)));
+    }
+
+    // 2. The day stem's element for the day master
+    if chart.useful.contains(&element) {
+        score += if matches!(relation, "Resource" | "Wealth") { 20 } else { 15 };
+        notes.push(format!("{} day ({} {}) is useful to you", relation, get_stem(stem), WU_XING[element]));
+    } else if chart.unfavorable.contains(&element) {
+        score -= 10;
+        notes.push(format!("{} day ({} {}) works against you", relation, get_stem(stem), WU_XING[element]));
+    }
+
+    // 3. Main Qi of the day branch
+    let branch_element = stem_element(HIDDEN_STEMS[branch][0]);
+    if chart.useful.contains(&branch_element) {
+        score += 5;
+    } else if chart.unfavorable.contains(&branch_element) {
+        score -= 5;
+    }
+    (score, notes)
+}
+
+/// The twelve double-hours of `date`: hour spirit, Noble hours of the day stem, and hours that
+/// clash the day, the user's year branch or natal day branch.
+fn hour_breakdown(date: NaiveDate, user_branch: Option<usize>, natal_day_branch: Option<usize>) -> Vec<HourSlot> {
+    let (day_stem, day_branch) = day_pillar(date);
+    // Qing Long starts on Shen for Zi/Wu days and moves two branches per day pair
+    let qing_long = (8 + 2 * (day_branch % 6)) % 12;
+    let (yang_noble, yin_noble) = NOBLE[day_stem];
+
+    (0..12).map(|branch| {
+        // Five Rats rule: Jia/Ji days start the Zi hour on Jia
+        let stem = (day_stem % 5 * 2 + branch) % 10;
+        let (officer, yellow_path) = HOUR_OFFICERS[(branch + 12 - qing_long) % 12];
+        let noble = branch == yang_noble || branch == yin_noble;
+        let mut score = if yellow_path { 10 } else { -10 };
+        let mut notes = vec![format!("{} {} Path", officer, if yellow_path { "Yellow" } else { "Black" })];
+        let mut clash = None;
+
+        if noble {
+            score += 10;
+            notes.push(format!("{} Noble hour", if branch == yang_noble { "Yang" } else { "Yin" }));
+        }
+        if is_six_clash(day_branch, branch) {
+            score -= 20;
+            notes.push("Clashes the day branch (Day Breaker hour)".to_string());
+            clash = Some("Day Breaker hour".to_string());
+        }
+        if let Some(ub) = user_branch.filter(|&b| is_six_clash(b, branch)) {
+            score -= 15;
+            let msg = format!("Clash with your Year ({} vs {})", get_branch(ub), get_branch(branch));
+            notes.push(msg.clone());
+            clash.get_or_insert(msg);
+        }
+        if let Some(nb) = natal_day_branch.filter(|&b| is_six_clash(b, branch)) {
+            score -= 15;
+            let msg = format!("Clashes your day pillar ({} vs {})", get_branch(nb), get_branch(branch));
+            notes.push(msg.clone());
+            clash.get_or_insert(msg);
+        }
+
+        let start = (23 + 2 * branch) % 24;
+        HourSlot {
+            branch: get_branch(branch).to_string(),
+            pillar: format!("{} {}", get_stem(stem), get_branch(branch).split(' ').next().unwrap_or("")),
+            time_range: format!("{:02}:00-{:02}:00", start, (start + 2) % 24),
+            officer: officer.to_string(),
+            yellow_path,
+            noble,
+            clash,
+            score,
+            summary: notes.join(", "),
+        }
+    }).collect()
+}
+
 pub fn calculate_auspiciousness(config: DateSelectionConfig) -> Result<Vec<AuspiciousDate>, String> {
     let mut results = Vec::new();
     let mut current = config.start_date;
+    let personal = config.user_birth_date
+        .map(|d| PersonalChart::from_birth(d, config.user_birth_hour.unwrap_or(12)))
+        .transpose()?;
+    let user_year = config.user_birth_year.or(config.user_birth_date.map(|d| d.year()));
+    let min_score = match config.min_score {
+        Some(m) if config.include_inauspicious => m,
+        Some(m) => m.max(0),
+        None if config.include_inauspicious => i32::MIN,
+        None => 0,
+    };
 
     while current <= config.end_date {
-        let (score, summary, collision, officer, suitable) = evaluate_day(
+        let (mut score, mut summary, collision, officer, suitable) = evaluate_day(
             current,
             &config.intention,
             &config.activities,
-            config.user_birth_year
+            user_year
         );
+        if let Some(chart) = &personal {
+            let (extra, notes) = personal_score(current, chart);
+            score += extra;
+            for note in notes {
+                if !summary.is_empty() { summary.push_str(", "); }
+                summary.push_str(&note);
+            }
+        }
 
-        // Filter: only show days with neutral or positive score, unless it's a critical clash
-        // OR if the user asked for a specific activity that is suitable
-        if score >= 0 {
+        // Filter: only show days at or above the minimum score
+        if score >= min_score {
             results.push(AuspiciousDate {
                 date: current,
                 score,
@@ -51,15 +336,79 @@ pub fn calculate_auspiciousness(config: DateSelectionConfig) -> Result<Vec<Auspi
                 officer,
                 suitable_activities: suitable,
                 collision,
+                lunar_date: Some(LunarDate::from_solar(current).label()),
+                mansion: Some(lunar_mansion(current).label()),
+                hours: Vec::new(),
             });
         }
 
         current = current.succ_opt().ok_or("Date out of range")?;
     }
 
+    // Hour-level selection for the best days
+    let mut ranked: Vec<usize> = (0..results.len()).collect();
+    ranked.sort_by(|&a, &b| results[b].score.cmp(&results[a].score));
+    for i in ranked.into_iter().take(TOP_DAYS_WITH_HOURS) {
+        results[i].hours = hour_breakdown(
+            results[i].date,
+            user_year.map(get_year_branch_idx),
+            personal.as_ref().map(|p| p.day_branch),
+        );
+    }
+
+    if let Some(locale) = config.locale {
+        for day in &mut results {
+            day.officer = localize(&day.officer, locale);
+            for hour in &mut day.hours {
+                hour.officer = localize(&hour.officer, locale);
+            }
+        }
+    }
+
     Ok(results)
 }
 
+/// Runs a weighted simulation over the qualifying dates, each weighted by its classical score.
+///
+/// Inauspicious days (below zero) never take part; a zero-score day keeps a small weight so it
+/// can still surface. Returns `None` when no date qualified.
+pub fn run_quantum_selection(session: &mut SimulationSession, dates: &[AuspiciousDate], sim_options: &SimulationOptions) -> Option<ZeRiQuantumPick> {
+    // 1. Weights from the classical score
+    let dates: Vec<&AuspiciousDate> = dates.iter().filter(|d| d.score >= 0).collect();
+    let names: Vec<String> = dates.iter().map(|d| d.date.to_string()).collect();
+    let weights: Vec<f64> = dates.iter().map(|d| d.score.max(0) as f64 + 1.0).collect();
+
+    // 2. Simulation
+    let pass = quantum::analyze(session, &names, Some(&weights), DATE_SIMULATIONS, sim_options);
+    let favored = dates[pass.focus?];
+
+    // 3. Narrative
+    let best = dates.iter().map(|d| d.score).max().unwrap_or(favored.score);
+    let mut narrative = vec![if favored.score == best {
+        format!("The simulation confirms {}, the top-scoring date ({}).", favored.date, favored.score)
+    } else {
+        format!("The simulation favours {} ({}) over the top classical score of {}.", favored.date, favored.score, best)
+    }];
+    for stats in &pass.deviations {
+        narrative.push(format!(
+            "{} surfaced {} often than its score predicts ({:.1}% vs {:.1}%).",
+            stats.option,
+            if stats.z_score > 0.0 { "more" } else { "less" },
+            stats.observed_probability * 100.0,
+            stats.expected_probability * 100.0
+        ));
+    }
+
+    Some(ZeRiQuantumPick {
+        favored_date: favored.date,
+        favored_score: favored.score,
+        volatility_index: pass.volatility,
+        distribution: pass.report.distribution,
+        anomalies: pass.report.anomalies,
+        narrative,
+    })
+}
+
 fn evaluate_day(
     date: NaiveDate,
     _intention: &Option<String>,
@@ -185,9 +534,46 @@ fn evaluate_day(
         _ => {}
     }
 
+    // 4. Lunar-date rules
+    let lunar = LunarDate::from_solar(date);
+    if YANG_GONG_TABOO.contains(&(lunar.month, lunar.day)) {
+        score -= 30;
+        notes.push("Yang Gong Taboo Day".to_string());
+        if collision.is_none() { collision = Some("Yang Gong Taboo Day".to_string()); }
+    }
+    if YUE_JI.contains(&lunar.day) {
+        score -= 10;
+        notes.push("Yue Ji: avoid travel and major ventures".to_string());
+    }
+    match lunar.day {
+        1 => notes.push("New Moon (Shuo)".to_string()),
+        15 => notes.push("Full Moon (Wang)".to_string()),
+        _ => {}
+    }
+
+    // 5. 28 Lunar Mansions, layered over the Officers
+    let xiu = lunar_mansion(date);
+    if xiu.auspicious {
+        score += 10;
+        notes.push(format!("Mansion {}: auspicious", xiu.label()));
+    } else {
+        score -= 10;
+        notes.push(format!("Mansion {}: inauspicious", xiu.label()));
+    }
+    for act in xiu.good_for {
+        if !suitable_acts.iter().any(|s| s == act) {
+            suitable_acts.push(act.to_string());
+        }
+    }
+
     // Intention/Activity Matching
     if let Some(user_acts) = activities {
         for act in user_acts {
+            if let Some(avoided) = xiu.avoid.iter().find(|a| a.to_lowercase().contains(&act.to_lowercase())) {
+                score -= 15;
+                notes.push(format!("Mansion {} forbids {}", xiu.name, avoided));
+                continue;
+            }
             if suitable_acts.iter().any(|s| s.to_lowercase().contains(&act.to_lowercase())) {
                 score += 15;
                 notes.push(format!("Good for {}", act));
@@ -239,3 +625,7 @@ fn get_month_branch_idx(date: NaiveDate) -> usize {
     let idx = ((term + 1) / 2 + 3) % 12;
     idx as usize
 }
+
+#[cfg(test)]
+#[path = "ze_ri_tests.rs"]
+mod tests;
@@ -0,0 +1,246 @@
+use super::{calculate_auspiciousness, evaluate_day, hour_breakdown, lunar_mansion, personal_score, run_quantum_selection, DateSelectionConfig, PersonalChart};
+use crate::engine::{SimulationOptions, SimulationSession};
+use chrono::{Datelike, Weekday};
+use chrono::NaiveDate;
+use crate::tools::i18n::{Locale, HOUR_OFFICERS, OFFICERS};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_yang_gong_taboo_day() {
+    // 2024-02-22 is the 13th day of the 1st lunar month
+    let (_, notes, _, _, _) = evaluate_day(date(2024, 2, 22), &None, &None, None);
+    assert!(notes.contains("Yang Gong Taboo Day"), "{}", notes);
+    let (_, notes, _, _, _) = evaluate_day(date(2024, 2, 21), &None, &None, None);
+    assert!(!notes.contains("Yang Gong"), "{}", notes);
+}
+
+#[test]
+fn test_yue_ji_and_moon_phases() {
+    let (_, notes, _, _, _) = evaluate_day(date(2024, 2, 14), &None, &None, None);
+    assert!(notes.contains("Yue Ji"), "{}", notes);
+    let (_, notes, _, _, _) = evaluate_day(date(2024, 2, 10), &None, &None, None);
+    assert!(notes.contains("New Moon"), "{}", notes);
+    let (_, notes, _, _, _) = evaluate_day(date(2024, 2, 24), &None, &None, None);
+    assert!(notes.contains("Full Moon"), "{}", notes);
+}
+
+#[test]
+fn test_results_carry_lunar_date() {
+    let config = DateSelectionConfig {
+        start_date: date(2024, 2, 1),
+        end_date: date(2024, 2, 29),
+        intention: None,
+        activities: None,
+        user_birth_year: None,
+        user_birth_date: None,
+        user_birth_hour: None,
+        include_inauspicious: false,
+        min_score: None,
+        quantum_mode: false,
+        simulation_options: None,
+        locale: None,
+    };
+    let results = calculate_auspiciousness(config).unwrap();
+    assert!(!results.is_empty());
+    let first = &results[0];
+    let expected = crate::tools::lunar::LunarDate::from_solar(first.date).label();
+    assert_eq!(first.lunar_date.as_deref(), Some(expected.as_str()));
+}
+
+#[test]
+fn test_lunar_mansion_cycle() {
+    assert_eq!(lunar_mansion(date(2024, 2, 12)).label(), "Xin (Heart)");
+    assert_eq!(lunar_mansion(date(2024, 3, 11)).label(), "Xin (Heart)");
+    // Every mansion keeps its weekday: Jiao always falls on a Thursday
+    let mut day = date(2024, 1, 1);
+    while day < date(2025, 1, 1) {
+        if lunar_mansion(day).name == "Jiao" {
+            assert_eq!(day.weekday(), Weekday::Thu, "{}", day);
+        }
+        day = day.succ_opt().unwrap();
+    }
+}
+
+#[test]
+fn test_mansion_layers_over_officers() {
+    // Xin (Heart) forbids marriage whatever the Officer says
+    let marriage = Some(vec!["Marriage".to_string()]);
+    let (score, notes, _, _, _) = evaluate_day(date(2024, 2, 12), &None, &marriage, None);
+    assert!(notes.contains("Mansion Xin forbids Marriage"), "{}", notes);
+    let (plain, _, _, _, _) = evaluate_day(date(2024, 2, 12), &None, &None, None);
+    assert_eq!(score, plain - 15);
+    // Fang (Room), the day before, favours it
+    let (_, notes, _, _, suitable) = evaluate_day(date(2024, 2, 11), &None, &marriage, None);
+    assert!(notes.contains("Mansion Fang (Room): auspicious"), "{}", notes);
+    assert!(suitable.iter().any(|s| s == "Marriage"));
+}
+
+#[test]
+fn test_personal_day_pillar_clash() {
+    // Born 2000-01-01, a Wu Wu day: Earth day master on a Horse branch
+    let chart = PersonalChart::from_birth(date(2000, 1, 1), 12).unwrap();
+    assert_eq!((chart.day_stem, chart.day_branch), (4, 6));
+    // Jia Zi (2000-01-07) clashes the Horse and its Wood stem attacks the Earth day master
+    let (score, notes) = personal_score(date(2000, 1, 7), &chart);
+    assert!(notes.iter().any(|n| n.starts_with("Clashes your day pillar")), "{:?}", notes);
+    assert!(notes.iter().any(|n| n.contains("Tian Ke Di Chong")), "{:?}", notes);
+    assert!(score < 0);
+    // Every day is either useful or against the day master by its stem
+    let (_, notes) = personal_score(date(2000, 1, 2), &chart);
+    assert!(notes.iter().any(|n| n.contains("useful to you") || n.contains("works against you")), "{:?}", notes);
+}
+
+#[test]
+fn test_birth_date_personalizes_results() {
+    let config = |birth: Option<NaiveDate>| DateSelectionConfig {
+        start_date: date(2024, 3, 1),
+        end_date: date(2024, 3, 31),
+        intention: None,
+        activities: None,
+        user_birth_year: None,
+        user_birth_date: birth,
+        user_birth_hour: Some(8),
+        include_inauspicious: false,
+        min_score: None,
+        quantum_mode: false,
+        simulation_options: None,
+        locale: None,
+    };
+    let generic = calculate_auspiciousness(config(None)).unwrap();
+    let personal = calculate_auspiciousness(config(Some(date(1985, 6, 15)))).unwrap();
+    assert!(generic.iter().all(|d| !d.summary.contains("useful to you")));
+    assert!(personal.iter().any(|d| d.summary.contains("useful to you")));
+}
+
+#[test]
+fn test_hour_breakdown() {
+    // 2000-01-01 is a Wu Wu day: Qing Long sits on Shen, the Noble on Chou and Wei
+    let hours = hour_breakdown(date(2000, 1, 1), None, None);
+    assert_eq!(hours.len(), 12);
+    assert_eq!(hours[8].officer, "Qing Long (Green Dragon)");
+    assert_eq!(hours[0].officer, "Jin Kui (Golden Coffer)");
+    assert_eq!(hours[0].pillar, "Ren Zi");
+    assert_eq!(hours[0].time_range, "23:00-01:00");
+    assert!(hours[1].noble && hours[7].noble);
+    // The Zi hour breaks the Horse day
+    assert_eq!(hours[0].clash.as_deref(), Some("Day Breaker hour"));
+    // A Rat-year user also clashes the Horse hour
+    let personal = hour_breakdown(date(2000, 1, 1), Some(0), None);
+    assert!(personal[6].clash.as_deref().unwrap().starts_with("Clash with your Year"));
+    assert_eq!(personal[6].score, hours[6].score - 15);
+}
+
+#[test]
+fn test_top_days_get_hours() {
+    let config = DateSelectionConfig {
+        start_date: date(2024, 3, 1),
+        end_date: date(2024, 3, 31),
+        intention: None,
+        activities: None,
+        user_birth_year: None,
+        user_birth_date: None,
+        user_birth_hour: None,
+        include_inauspicious: false,
+        min_score: None,
+        quantum_mode: false,
+        simulation_options: None,
+        locale: None,
+    };
+    let results = calculate_auspiciousness(config).unwrap();
+    let with_hours: Vec<_> = results.iter().filter(|d| !d.hours.is_empty()).collect();
+    assert_eq!(with_hours.len(), 3);
+    let best = results.iter().map(|d| d.score).max().unwrap();
+    assert!(with_hours.iter().any(|d| d.score == best));
+}
+
+#[test]
+fn test_quantum_selection_follows_scores() {
+    let config = DateSelectionConfig {
+        start_date: date(2024, 3, 1),
+        end_date: date(2024, 3, 31),
+        intention: None,
+        activities: None,
+        user_birth_year: None,
+        user_birth_date: None,
+        user_birth_hour: None,
+        include_inauspicious: false,
+        min_score: None,
+        quantum_mode: true,
+        simulation_options: None,
+        locale: None,
+    };
+    let results = calculate_auspiciousness(config).unwrap();
+    let mut session = SimulationSession::new((0..=255).cycle().take(8192).collect());
+    let pick = run_quantum_selection(&mut session, &results, &SimulationOptions::default()).unwrap();
+    assert!(results.iter().any(|d| d.date == pick.favored_date && d.score == pick.favored_score));
+    assert_eq!(pick.distribution.values().sum::<usize>(), super::DATE_SIMULATIONS);
+    assert_eq!(pick.distribution.len(), results.len());
+    assert!(!pick.narrative.is_empty());
+
+    let mut session = SimulationSession::new(vec![1, 2, 3]);
+    assert!(run_quantum_selection(&mut session, &[], &SimulationOptions::default()).is_none());
+}
+
+#[test]
+fn test_include_inauspicious_days() {
+    let config = |include: bool, min: Option<i32>| DateSelectionConfig {
+        start_date: date(2024, 3, 1),
+        end_date: date(2024, 3, 31),
+        intention: None,
+        activities: None,
+        user_birth_year: None,
+        user_birth_date: None,
+        user_birth_hour: None,
+        include_inauspicious: include,
+        min_score: min,
+        quantum_mode: false,
+        simulation_options: None,
+        locale: None,
+    };
+    let all = calculate_auspiciousness(config(true, None)).unwrap();
+    assert_eq!(all.len(), 31);
+    let bad: Vec<_> = all.iter().filter(|d| d.score < 0).collect();
+    assert!(!bad.is_empty());
+    assert!(bad.iter().all(|d| !d.summary.is_empty()));
+
+    let default = calculate_auspiciousness(config(false, None)).unwrap();
+    assert_eq!(default.len(), all.len() - bad.len());
+    // A negative minimum is ignored without the flag
+    assert_eq!(calculate_auspiciousness(config(false, Some(-100))).unwrap().len(), default.len());
+    let above = calculate_auspiciousness(config(true, Some(60))).unwrap();
+    assert!(above.iter().all(|d| d.score >= 60));
+    assert_eq!(above.len(), all.iter().filter(|d| d.score >= 60).count());
+}
+
+#[test]
+fn test_officers_follow_locale() {
+    let config = |locale| DateSelectionConfig {
+        start_date: date(2024, 2, 1),
+        end_date: date(2024, 2, 29),
+        intention: None,
+        activities: None,
+        user_birth_year: None,
+        user_birth_date: None,
+        user_birth_hour: None,
+        include_inauspicious: false,
+        min_score: None,
+        quantum_mode: false,
+        simulation_options: None,
+        locale,
+    };
+    let en = calculate_auspiciousness(config(None)).unwrap();
+    let zh = calculate_auspiciousness(config(Some(Locale::Zh))).unwrap();
+    assert_eq!(en.len(), zh.len());
+    for (e, z) in en.iter().zip(&zh) {
+        let officer = OFFICERS.iter().find(|t| t.en == e.officer).unwrap();
+        assert_eq!(z.officer, officer.label(Locale::Zh));
+        for (eh, zh) in e.hours.iter().zip(&z.hours) {
+            assert_ne!(eh.officer, zh.officer);
+            assert!(HOUR_OFFICERS.iter().any(|t| t.en == eh.officer && zh.officer.starts_with(t.hanzi)));
+        }
+    }
+    assert!(zh.iter().any(|d| !d.hours.is_empty()));
+}
@@ -1,13 +1,78 @@
+use chrono::{Datelike, NaiveDateTime, Timelike};
 use serde::{Serialize, Deserialize};
 use crate::tools::chinese_meta::{get_branch};
+use crate::tools::astronomy::{solar_hour, Location};
+use crate::engine::{SimulationOptions, SimulationSession};
+use crate::services::quantum;
+use crate::tools::bazi_luck::runs_forward;
+use crate::tools::i18n::{Locale, ZIWEI_PALACES, ZIWEI_STARS};
+use crate::tools::lunar::LunarDate;
+use crate::tools::zi_wei_limits::{annual_limits, decade_limits, palace_stem, AnnualLimit, DecadeLimit};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ZiWeiConfig {
+    #[serde(default)]
     pub birth_year: i32,
+    #[serde(default)]
     pub birth_month: u32,
+    #[serde(default)]
     pub birth_day: u32,
+    #[serde(default)]
     pub birth_hour: u32,
     pub gender: String, // "M" or "F"
+    /// Gregorian birth date and clock time; replaces the four fields above when given.
+    #[serde(default)]
+    pub birth_datetime: Option<NaiveDateTime>,
+    /// How a birth in a leap month is counted.
+    #[serde(default)]
+    pub leap_month: LeapMonthRule,
+    /// First and last year of the flowing years (defaults to the current year and the next).
+    #[serde(default)]
+    pub annual_years: Option<[i32; 2]>,
+    /// Runs a palace relevance simulation on quantum entropy.
+    #[serde(default)]
+    pub quantum_mode: bool,
+    #[serde(default)]
+    pub simulation_options: Option<SimulationOptions>,
+    /// Birth place; converts the birth hour to true solar time.
+    #[serde(default)]
+    pub location: Option<Location>,
+    /// Language of the palace and star names (English and pinyin when not given).
+    #[serde(default)]
+    pub locale: Option<Locale>,
+}
+
+impl ZiWeiConfig {
+    /// Fills the birth fields from `birth_datetime`, if one was given.
+    pub fn resolve_datetime(&mut self) {
+        let Some(dt) = self.birth_datetime else { return };
+        (self.birth_year, self.birth_month, self.birth_day, self.birth_hour) = (dt.year(), dt.month(), dt.day(), dt.hour());
+    }
+}
+
+/// Schools differ on which month a leap month counts as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeapMonthRule {
+    /// Days 1-15 belong to the month it repeats, days 16 onward to the next month.
+    #[default]
+    Split,
+    /// The whole leap month counts as the month it repeats.
+    Same,
+    /// The whole leap month counts as the next month.
+    Next,
+}
+
+impl LeapMonthRule {
+    /// Month number (1-12) a lunar date is read as.
+    pub fn month_of(self, lunar: &LunarDate) -> u32 {
+        let next = lunar.leap && match self {
+            LeapMonthRule::Split => lunar.day > 15,
+            LeapMonthRule::Same => false,
+            LeapMonthRule::Next => true,
+        };
+        if next { lunar.month % 12 + 1 } else { lunar.month }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,15 +81,159 @@ pub struct ZiWeiChart {
     pub life_palace_idx: usize,
     pub body_palace_idx: usize,
     pub element_phase: String, // Five Element Phase
+    /// Lunar birth date the chart was cast from.
+    #[serde(default)]
+    pub lunar_birth: Option<LunarDate>,
+    /// 10-year limits (Da Xian) with the Si Hua flown by each palace stem.
+    #[serde(default)]
+    pub decade_limits: Vec<DecadeLimit>,
+    /// Flowing years (Liu Nian) with the Si Hua of each year stem.
+    #[serde(default)]
+    pub annual_limits: Vec<AnnualLimit>,
+    /// Palace relevance simulation (quantum mode only).
+    #[serde(default)]
+    pub quantum: Option<ZiWeiQuantumAnalysis>,
+}
+
+/// Analysis derived from Quantum Entropy simulations over the 12 palaces.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZiWeiQuantumAnalysis {
+    pub focus_palace: String, // The palace "chosen" by the simulation
+    pub focus_palace_idx: usize,
+    /// Relevance weight of each palace (by branch index) from its stars and Si Hua.
+    pub weights: Vec<f64>,
+    /// How far the entropy behind the analysis strayed from noise (0 to 1).
+    #[serde(default)]
+    pub volatility_index: f64,
+    pub distribution: std::collections::HashMap<String, usize>,
+    pub anomalies: Vec<String>, // Statistical outliers found in simulation
+    pub narrative: Vec<String>,
 }
 
+/// Simulated draws per analysis: one 8-byte draw each, 4 KB of entropy in all.
+pub const PALACE_SIMULATIONS: usize = 512;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Palace {
     pub index: usize, // 0..11 (0=Zi/Rat, 1=Chou/Ox...)
     pub branch_name: String, // "Zi", "Chou"
     pub name: String, // "Life", "Siblings", etc.
-    pub major_stars: Vec<String>,
-    pub minor_stars: Vec<String>,
+    pub major_stars: Vec<Star>,
+    pub minor_stars: Vec<Star>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StarCategory {
+    /// One of the fourteen main stars.
+    Major,
+    /// Helpful minor stars (the Noblemen, the literary and assisting pairs, Lu Cun).
+    Auxiliary,
+    /// Harmful minor stars (the Six Malefics and Tian Xing).
+    Malefic,
+    /// Romance and attraction stars (Hong Luan, Tian Xi, Tian Yao, Xian Chi).
+    PeachBlossom,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Star {
+    pub id: String, // "zi_wei", "wen_chang"
+    pub name_en: String, // "Emperor"
+    pub name_pinyin: String, // "Zi Wei"
+    /// Chinese name ("紫微"), when the chart was cast in Chinese.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_zh: Option<String>,
+    pub category: StarCategory,
+    /// Natal Si Hua: "Hua Lu", "Hua Quan", "Hua Ke" or "Hua Ji".
+    pub transformation: Option<String>,
+    /// Miao, Wang, De, Li, Ping, Bu or Xian in this palace (major stars only).
+    pub brightness: Option<String>,
+}
+
+// (id, pinyin, English, category)
+const STARS: [(&str, &str, &str, StarCategory); 36] = [
+    ("zi_wei", "Zi Wei", "Emperor", StarCategory::Major),
+    ("tian_ji", "Tian Ji", "Advisor", StarCategory::Major),
+    ("tai_yang", "Tai Yang", "Sun", StarCategory::Major),
+    ("wu_qu", "Wu Qu", "General", StarCategory::Major),
+    ("tian_tong", "Tian Tong", "Lucky", StarCategory::Major),
+    ("lian_zhen", "Lian Zhen", "Passion", StarCategory::Major),
+    ("tian_fu", "Tian Fu", "Vault", StarCategory::Major),
+    ("tai_yin", "Tai Yin", "Moon", StarCategory::Major),
+    ("tan_lang", "Tan Lang", "Wolf", StarCategory::Major),
+    ("ju_men", "Ju Men", "Orator", StarCategory::Major),
+    ("tian_xiang", "Tian Xiang", "Minister", StarCategory::Major),
+    ("tian_liang", "Tian Liang", "Sage", StarCategory::Major),
+    ("qi_sha", "Qi Sha", "General", StarCategory::Major),
+    ("po_jun", "Po Jun", "Pioneer", StarCategory::Major),
+    ("wen_chang", "Wen Chang", "Arts", StarCategory::Auxiliary),
+    ("wen_qu", "Wen Qu", "Eloquence", StarCategory::Auxiliary),
+    ("zuo_fu", "Zuo Fu", "Aid", StarCategory::Auxiliary),
+    ("you_bi", "You Bi", "Support", StarCategory::Auxiliary),
+    ("tian_kui", "Tian Kui", "Noble", StarCategory::Auxiliary),
+    ("tian_yue", "Tian Yue", "Noble", StarCategory::Auxiliary),
+    ("lu_cun", "Lu Cun", "Wealth", StarCategory::Auxiliary),
+    ("qing_yang", "Qing Yang", "Sheep", StarCategory::Malefic),
+    ("tuo_luo", "Tuo Luo", "Gyro", StarCategory::Malefic),
+    ("huo_xing", "Huo Xing", "Fire", StarCategory::Malefic),
+    ("ling_xing", "Ling Xing", "Bell", StarCategory::Malefic),
+    ("di_kong", "Di Kong", "Void", StarCategory::Malefic),
+    ("di_jie", "Di Jie", "Robbery", StarCategory::Malefic),
+    ("tian_xing", "Tian Xing", "Punishment", StarCategory::Malefic),
+    ("tian_ma", "Tian Ma", "Horse", StarCategory::Auxiliary),
+    ("tai_fu", "Tai Fu", "Honour", StarCategory::Auxiliary),
+    ("feng_gao", "Feng Gao", "Decree", StarCategory::Auxiliary),
+    ("hong_luan", "Hong Luan", "Red Phoenix", StarCategory::PeachBlossom),
+    ("tian_xi", "Tian Xi", "Joy", StarCategory::PeachBlossom),
+    ("tian_yao", "Tian Yao", "Charm", StarCategory::PeachBlossom),
+    ("xian_chi", "Xian Chi", "Salty Pool", StarCategory::PeachBlossom),
+    ("tian_kong", "Tian Kong", "Heavenly Void", StarCategory::Malefic),
+];
+
+// Brightness of the major stars (in `STARS` order) from Zi to Hai.
+// M = Miao (Temple), W = Wang (Prosperous), D = De (Gaining), L = Li (Beneficial),
+// P = Ping (Neutral), B = Bu (Dim), X = Xian (Fallen)
+const BRIGHTNESS: [&str; 14] = [
+    "PMMWDWMMWPDW", // Zi Wei
+    "MXDWLPMXDWLP", // Tian Ji
+    "XBWMWWWDDXBX", // Tai Yang
+    "WMDLMPWMDLMP", // Wu Qu
+    "WBLPPMXBWPPM", // Tian Tong
+    "PLMPLXPLMPLX", // Lian Zhen
+    "MMMDMDWMDWMD", // Tian Fu
+    "MMWXXXBBLBWM", // Tai Yin
+    "WMPLMXWMPLMX", // Tan Lang
+    "WBMMXWWBMMWW", // Ju Men
+    "MMMXDDMDMXDD", // Tian Xiang
+    "MWMMWXMWXDMX", // Tian Liang
+    "WMMXWPWWMMMP", // Qi Sha
+    "MWDXWPMWDXWP", // Po Jun
+];
+
+impl Star {
+    /// The star `id` as placed in the palace on `branch`.
+    pub fn new(id: &str, branch: usize) -> Star {
+        let i = STARS.iter().position(|s| s.0 == id).unwrap_or_else(|| panic!("unknown star {}", id));
+        let (id, pinyin, english, category) = STARS[i];
+        let brightness = BRIGHTNESS.get(i).map(|row| match row.as_bytes()[branch % 12] {
+            b'M' => "Miao",
+            b'W' => "Wang",
+            b'D' => "De",
+            b'L' => "Li",
+            b'P' => "Ping",
+            b'B' => "Bu",
+            _ => "Xian",
+        }.to_string());
+        Star {
+            id: id.to_string(),
+            name_en: english.to_string(),
+            name_pinyin: pinyin.to_string(),
+            name_zh: None,
+            category,
+            transformation: None,
+            brightness,
+        }
+    }
 }
 
 pub const PALACE_NAMES: [&str; 12] = [
@@ -33,10 +242,19 @@ pub const PALACE_NAMES: [&str; 12] = [
     "Career", "Property", "Mental", "Parents"
 ];
 
-pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
+pub fn generate_ziwei_chart(mut config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
+    // 0. Clock time to true solar time (the hour may roll into another day)
+    config.resolve_datetime();
+    (config.birth_year, config.birth_month, config.birth_day, config.birth_hour) =
+        solar_hour(config.birth_year, config.birth_month, config.birth_day, config.birth_hour, config.location.as_ref());
+
     // 1. Basic Calculations
+    // Zi Wei works in lunar months and days; the year stem is the lunar year's
+    let birth_date = chrono::NaiveDate::from_ymd_opt(config.birth_year, config.birth_month, config.birth_day)
+        .ok_or_else(|| format!("Invalid date: {}-{}-{}", config.birth_year, config.birth_month, config.birth_day))?;
+    let lunar = LunarDate::from_solar(birth_date);
     let hour_idx = ((config.birth_hour + 1) / 2) % 12; // 0=Zi, 1=Chou...
-    let month_num = config.birth_month as i32; // 1-12
+    let month_num = config.leap_month.month_of(&lunar) as i32; // 1-12
     let hour_num = hour_idx as i32;
 
     // 2. Determine Life and Body Palaces
@@ -52,7 +270,7 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     // Formula based on Life Palace Branch and Birth Year Stem.
     // Stems: Jia(0)..Gui(9). Year ends in 4 -> 0(Jia).
     // offset = (year - 4) % 10.
-    let year_stem_idx = (config.birth_year - 4).rem_euclid(10) as usize;
+    let year_stem_idx = (lunar.year - 4).rem_euclid(10) as usize;
     // Life Palace Branch: life_idx (0=Zi .. 11=Hai).
     // Formula: (LifeBranch / 2) ?
     // Complex Lookup. Let's use a simplified Mock or standard table.
@@ -67,16 +285,8 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     //    Year Bing/Xin -> Geng (6).
     //    Year Ding/Ren -> Ren (8).
     //    Year Wu/Gui -> Jia (0).
-    let tiger_stem = match year_stem_idx % 5 {
-        0 => 2, // Jia/Ji -> Bing
-        1 => 4, // Yi/Geng -> Wu
-        2 => 6, // Bing/Xin -> Geng
-        3 => 8, // Ding/Ren -> Ren
-        4 => 0, // Wu/Gui -> Jia
-        _ => 0
-    };
     // Life Stem = (TigerStem + (LifeBranch - 2)) % 10.
-    let life_stem_idx = (tiger_stem + (life_idx as i32 - 2).rem_euclid(12) as i32).rem_euclid(10) as usize;
+    let life_stem_idx = palace_stem(year_stem_idx, life_idx);
 
     // 2. Determine Phase from Stem/Branch of Life Palace (Na Yin Element).
     // Water 2, Wood 3, Gold 4, Earth 5, Fire 6.
@@ -104,7 +314,7 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     // 4. Place Zi Wei Star
     // Algorithm: Day / Phase.
     // Returns the Palace Index for Zi Wei.
-    let zi_wei_idx = place_zi_wei(config.birth_day as u32, phase_num);
+    let zi_wei_idx = place_zi_wei(lunar.day, phase_num);
 
     // 5. Place Tian Fu Star
     // Algorithm: Mirror Zi Wei across the Yin-Shen axis (Tiger-Monkey).
@@ -123,8 +333,8 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     let tian_fu_idx = (4i32 - zi_wei_idx as i32).rem_euclid(12) as usize;
 
     // 6. Initialize Palaces
-    let mut palace_stars: Vec<Vec<String>> = vec![Vec::new(); 12]; // Major
-    let mut palace_minor: Vec<Vec<String>> = vec![Vec::new(); 12]; // Minor
+    let mut palace_stars: Vec<Vec<Star>> = vec![Vec::new(); 12]; // Major
+    let mut palace_minor: Vec<Vec<Star>> = vec![Vec::new(); 12]; // Minor
 
     // 7. Distribute Major Stars
     // Zi Wei Series (Counter-Clockwise):
@@ -138,16 +348,16 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     // -7: -
     // -8: Lian Zhen
     let zw_offsets = [
-        (0, "Zi Wei (Emperor)"),
-        (11, "Tian Ji (Advisor)"), // -1
-        (9, "Tai Yang (Sun)"),    // -3
-        (8, "Wu Qu (General)"),   // -4
-        (7, "Tian Tong (Lucky)"), // -5
-        (4, "Lian Zhen (Passion)") // -8 = -8+12=4
+        (0, "zi_wei"),
+        (11, "tian_ji"), // -1
+        (9, "tai_yang"),    // -3
+        (8, "wu_qu"),   // -4
+        (7, "tian_tong"), // -5
+        (4, "lian_zhen") // -8 = -8+12=4
     ];
-    for (off, name) in zw_offsets.iter() {
+    for (off, id) in zw_offsets.iter() {
         let idx = (zi_wei_idx + off) % 12;
-        palace_stars[idx].push(name.to_string());
+        palace_stars[idx].push(Star::new(id, idx));
     }
 
     // Tian Fu Series (Clockwise):
@@ -163,18 +373,18 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     // 9: -
     // 10: Po Jun
     let tf_offsets = [
-        (0, "Tian Fu (Vault)"),
-        (1, "Tai Yin (Moon)"),
-        (2, "Tan Lang (Wolf)"),
-        (3, "Ju Men (Orator)"),
-        (4, "Tian Xiang (Minister)"),
-        (5, "Tian Liang (Sage)"),
-        (6, "Qi Sha (General)"),
-        (10, "Po Jun (Pioneer)")
+        (0, "tian_fu"),
+        (1, "tai_yin"),
+        (2, "tan_lang"),
+        (3, "ju_men"),
+        (4, "tian_xiang"),
+        (5, "tian_liang"),
+        (6, "qi_sha"),
+        (10, "po_jun")
     ];
-    for (off, name) in tf_offsets.iter() {
+    for (off, id) in tf_offsets.iter() {
         let idx = (tian_fu_idx + off) % 12;
-        palace_stars[idx].push(name.to_string());
+        palace_stars[idx].push(Star::new(id, idx));
     }
 
     // 8. Distribute Auxiliary Stars (Month/Hour Based)
@@ -182,16 +392,16 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     // Wen Qu (Hour): Chen (4) + Hour
     let wen_chang_idx = (10i32 - hour_num).rem_euclid(12) as usize;
     let wen_qu_idx = (4 + hour_num).rem_euclid(12) as usize;
-    palace_minor[wen_chang_idx].push("Wen Chang (Arts)".to_string());
-    palace_minor[wen_qu_idx].push("Wen Qu (Eloquence)".to_string());
+    palace_minor[wen_chang_idx].push(Star::new("wen_chang", wen_chang_idx));
+    palace_minor[wen_qu_idx].push(Star::new("wen_qu", wen_qu_idx));
 
     // Zuo Fu (Month): Chen (4) + Month
     // You Bi (Month): Xu (10) - Month
     let zuo_fu_idx = (4 + month_num - 1).rem_euclid(12) as usize; // Month 1 at Chen?
     // Rule: Month 1 at Chen, Month 2 at Si...
     let you_bi_idx = (10i32 - (month_num - 1)).rem_euclid(12) as usize;
-    palace_minor[zuo_fu_idx].push("Zuo Fu (Aid)".to_string());
-    palace_minor[you_bi_idx].push("You Bi (Support)".to_string());
+    palace_minor[zuo_fu_idx].push(Star::new("zuo_fu", zuo_fu_idx));
+    palace_minor[you_bi_idx].push(Star::new("you_bi", you_bi_idx));
 
     // Tian Kui / Tian Yue (Year Stem)
     // Stem: 0(Jia)..9(Gui)
@@ -220,8 +430,8 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
         8 | 9 => (5, 3),     // Ren, Gui -> Si, Mao
         _ => (1, 7)
     };
-    palace_minor[kui].push("Tian Kui (Noble)".to_string());
-    palace_minor[yue].push("Tian Yue (Noble)".to_string());
+    palace_minor[kui].push(Star::new("tian_kui", kui));
+    palace_minor[yue].push(Star::new("tian_yue", yue));
 
     // 9. Bad Stars (Year Stem / Branch)
     // Lu Cun (Wealth) & Qing Yang / Tuo Luo (Sheep/Dala)
@@ -245,9 +455,66 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     let qy_idx = (lu_cun_idx + 1) % 12;
     let tl_idx = (lu_cun_idx as i32 - 1).rem_euclid(12) as usize;
 
-    palace_minor[lu_cun_idx].push("Lu Cun (Wealth)".to_string());
-    palace_minor[qy_idx].push("Qing Yang (Sheep)".to_string());
-    palace_minor[tl_idx].push("Tuo Luo (Gyro)".to_string());
+    palace_minor[lu_cun_idx].push(Star::new("lu_cun", lu_cun_idx));
+    palace_minor[qy_idx].push(Star::new("qing_yang", qy_idx));
+    palace_minor[tl_idx].push(Star::new("tuo_luo", tl_idx));
+
+    // 9b. Remaining Minor Stars (Year Branch / Month / Hour)
+    let year_branch_idx = (lunar.year - 4).rem_euclid(12) as usize;
+    // Year branch triad: Shen-Zi-Chen, Si-You-Chou, Yin-Wu-Xu, Hai-Mao-Wei
+    let triad = year_branch_idx % 4;
+
+    // Huo Xing / Ling Xing: start by triad, then count the hour forward
+    // Yin-Wu-Xu: Chou/Mao. Shen-Zi-Chen: Yin/Xu. Si-You-Chou: Mao/Xu. Hai-Mao-Wei: You/Xu.
+    let (huo_start, ling_start) = match triad {
+        0 => (2, 10), // Shen, Zi, Chen
+        1 => (3, 10), // Si, You, Chou
+        2 => (1, 3),  // Yin, Wu, Xu
+        _ => (9, 10), // Hai, Mao, Wei
+    };
+    let huo_idx = (huo_start + hour_num).rem_euclid(12) as usize;
+    let ling_idx = (ling_start + hour_num).rem_euclid(12) as usize;
+    palace_minor[huo_idx].push(Star::new("huo_xing", huo_idx));
+    palace_minor[ling_idx].push(Star::new("ling_xing", ling_idx));
+
+    // Di Kong (Hai - Hour) / Di Jie (Hai + Hour)
+    let di_kong_idx = (11 - hour_num).rem_euclid(12) as usize;
+    let di_jie_idx = (11 + hour_num).rem_euclid(12) as usize;
+    palace_minor[di_kong_idx].push(Star::new("di_kong", di_kong_idx));
+    palace_minor[di_jie_idx].push(Star::new("di_jie", di_jie_idx));
+
+    // Tai Fu (Wu + Hour) / Feng Gao (Yin + Hour)
+    let tai_fu_idx = (6 + hour_num).rem_euclid(12) as usize;
+    let feng_gao_idx = (2 + hour_num).rem_euclid(12) as usize;
+    palace_minor[tai_fu_idx].push(Star::new("tai_fu", tai_fu_idx));
+    palace_minor[feng_gao_idx].push(Star::new("feng_gao", feng_gao_idx));
+
+    // Tian Xing (Month 1 at You) / Tian Yao (Month 1 at Chou)
+    let tian_xing_idx = (9 + month_num - 1).rem_euclid(12) as usize;
+    let tian_yao_idx = (1 + month_num - 1).rem_euclid(12) as usize;
+    palace_minor[tian_xing_idx].push(Star::new("tian_xing", tian_xing_idx));
+    palace_minor[tian_yao_idx].push(Star::new("tian_yao", tian_yao_idx));
+
+    // Tian Ma: the branch clashing the triad's first branch (Shen-Zi-Chen -> Yin, ...)
+    // Xian Chi: the triad's Peach Blossom (Shen-Zi-Chen -> You, Si-You-Chou -> Wu, ...)
+    let (ma_idx, xian_chi_idx) = match triad {
+        0 => (2, 9),  // Shen, Zi, Chen -> Yin / You
+        1 => (11, 6), // Si, You, Chou -> Hai / Wu
+        2 => (8, 3),  // Yin, Wu, Xu -> Shen / Mao
+        _ => (5, 0),  // Hai, Mao, Wei -> Si / Zi
+    };
+    palace_minor[ma_idx].push(Star::new("tian_ma", ma_idx));
+    palace_minor[xian_chi_idx].push(Star::new("xian_chi", xian_chi_idx));
+
+    // Hong Luan (Mao - Year Branch), Tian Xi opposite it
+    let hong_luan_idx = (3 - year_branch_idx as i32).rem_euclid(12) as usize;
+    let tian_xi_idx = (hong_luan_idx + 6) % 12;
+    palace_minor[hong_luan_idx].push(Star::new("hong_luan", hong_luan_idx));
+    palace_minor[tian_xi_idx].push(Star::new("tian_xi", tian_xi_idx));
+
+    // Tian Kong: the branch after the year branch
+    let tian_kong_idx = (year_branch_idx + 1) % 12;
+    palace_minor[tian_kong_idx].push(Star::new("tian_kong", tian_kong_idx));
 
     // 10. Four Transformations (Si Hua) - Attach to Major Stars
     // Based on Year Stem.
@@ -262,12 +529,10 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
     // Xin: Ju, Yang (Tai Yang), Qu, Chang
     // Ren: Liang, Zi, Zuo, Wu
     // Gui: Po, Ju, Yin (Tai Yin), Tan
-    // Note: This is complex string matching.
-    // I will append "(Hua Lu)" etc to the star string in the palaces.
     let si_hua_map = get_si_hua(year_stem_idx);
 
     // Apply Si Hua
-    // Loop through all palaces and stars. If star starts with Key, append Status.
+    // Loop through all palaces and stars; mark the stars named in the map.
     for p in 0..12 {
         for star in &mut palace_stars[p] {
             apply_si_hua(star, &si_hua_map);
@@ -277,6 +542,14 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
         }
     }
 
+    // Chinese star names, looked up by pinyin
+    let locale = config.locale.unwrap_or_default();
+    if locale == Locale::Zh {
+        for star in palace_stars.iter_mut().chain(palace_minor.iter_mut()).flatten() {
+            star.name_zh = ZIWEI_STARS.iter().find(|t| t.en == star.name_pinyin).map(|t| t.hanzi.to_string());
+        }
+    }
+
     // 11. Final Assembly
     let mut palaces = Vec::new();
     for i in 0..12 {
@@ -297,7 +570,7 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
 
         // Removed unused variable `p_name` assignment here.
         let role_idx = (life_idx as i32 - i as i32).rem_euclid(12) as usize;
-        let p_name = PALACE_NAMES[role_idx].to_string();
+        let p_name = ZIWEI_PALACES[role_idx].label(locale);
 
         palaces.push(Palace {
             index: i,
@@ -308,14 +581,105 @@ pub fn generate_ziwei_chart(config: ZiWeiConfig) -> Result<ZiWeiChart, String> {
         });
     }
 
+    // 12. Decade and flowing-year limits
+    // Yang-year men and yin-year women run clockwise
+    let forward = runs_forward(Some(&config.gender), year_stem_idx).unwrap_or(true);
+    let decades = decade_limits(life_idx, phase_num, forward, year_stem_idx, lunar.year, &palaces);
+    let this_year = chrono::Local::now().year();
+    let [first, last] = config.annual_years.unwrap_or([this_year, this_year + 1]);
+    let annuals = annual_limits(first, last, lunar.year, &decades, &palaces);
+
     Ok(ZiWeiChart {
         palaces,
         life_palace_idx: life_idx,
         body_palace_idx: body_idx,
         element_phase: phase_str.to_string(),
+        lunar_birth: Some(lunar),
+        decade_limits: decades,
+        annual_limits: annuals,
+        quantum: None,
     })
 }
 
+/// How strongly a star draws attention to its palace.
+fn star_relevance(star: &Star) -> f64 {
+    let base = match star.category {
+        StarCategory::Major => match star.brightness.as_deref() {
+            Some("Miao") => 1.0,
+            Some("Wang") => 0.8,
+            Some("De") => 0.6,
+            Some("Li") => 0.5,
+            Some("Ping") => 0.4,
+            _ => 0.2, // Bu / Xian: dim, but still present
+        },
+        StarCategory::Malefic => 0.3,
+        StarCategory::Auxiliary => 0.2,
+        StarCategory::PeachBlossom => 0.1,
+    };
+    // A transformation activates the star; Hua Ji draws attention as much as Hua Lu
+    let activation = match star.transformation.as_deref() {
+        Some("Hua Lu") | Some("Hua Ji") => 1.0,
+        Some("Hua Quan") => 0.8,
+        Some("Hua Ke") => 0.6,
+        _ => 0.0,
+    };
+    base + activation
+}
+
+/// Runs a weighted relevance simulation over the 12 palaces.
+///
+/// Each palace is weighted by the brightness and transformations of its stars, so the
+/// simulation favours active palaces; the drawn "palace in focus" and any palace the
+/// entropy over- or under-selects against those weights become the narrative.
+pub fn run_quantum_analysis(session: &mut SimulationSession, chart: &ZiWeiChart, sim_options: &SimulationOptions) -> ZiWeiQuantumAnalysis {
+    // 1. Palace weights (a palace with no stars still carries a base weight)
+    let weights: Vec<f64> = chart.palaces.iter()
+        .map(|p| 1.0 + p.major_stars.iter().chain(&p.minor_stars).map(star_relevance).sum::<f64>())
+        .collect();
+    let names: Vec<String> = chart.palaces.iter().map(|p| p.name.clone()).collect();
+
+    // 2. Simulation
+    let pass = quantum::analyze(session, &names, Some(&weights), PALACE_SIMULATIONS, sim_options);
+    let focus_idx = pass.focus.unwrap_or(chart.life_palace_idx);
+    let focus = &chart.palaces[focus_idx];
+
+    // 3. Narrative
+    let mut narrative = Vec::new();
+    let stars: Vec<String> = focus.major_stars.iter().map(|s| s.name_pinyin.clone()).collect();
+    narrative.push(format!(
+        "Focus falls on the {} palace ({}){}.",
+        focus.name,
+        focus.branch_name,
+        if stars.is_empty() { ", an empty palace borrowing its opposite's stars".to_string() } else { format!(", held by {}", stars.join(" and ")) }
+    ));
+    for star in focus.major_stars.iter().chain(&focus.minor_stars) {
+        match star.transformation.as_deref() {
+            Some("Hua Ji") => narrative.push(format!("{} carries Hua Ji here: obstacles and attachment to work through.", star.name_pinyin)),
+            Some(t) => narrative.push(format!("{} carries {} here: a supportive opening.", star.name_pinyin, t)),
+            None => {}
+        }
+    }
+    for stats in &pass.deviations {
+        narrative.push(format!(
+            "The {} palace surfaced {} often than its stars predict ({:.1}% vs {:.1}%).",
+            stats.option,
+            if stats.z_score > 0.0 { "more" } else { "less" },
+            stats.observed_probability * 100.0,
+            stats.expected_probability * 100.0
+        ));
+    }
+
+    ZiWeiQuantumAnalysis {
+        focus_palace: focus.name.clone(),
+        focus_palace_idx: focus_idx,
+        weights: weights.iter().map(|w| (w * 100.0).round() / 100.0).collect(),
+        volatility_index: pass.volatility,
+        distribution: pass.report.distribution,
+        anomalies: pass.report.anomalies,
+        narrative,
+    }
+}
+
 fn get_na_yin_number(stem: usize, branch: usize) -> u32 {
     // Simplified lookup or calculation
     // This is complex. For MVP, I'll use a hashing heuristic to distribute phases 2-6
@@ -467,7 +831,7 @@ fn place_zi_wei(day: u32, phase: u32) -> usize {
 }
 
 // CHANGED: Returned tuple instead of array of tuples.
-fn get_si_hua(year_stem_idx: usize) -> (&'static str, &'static str, &'static str, &'static str) {
+pub(crate) fn get_si_hua(year_stem_idx: usize) -> (&'static str, &'static str, &'static str, &'static str) {
     // (Lu, Quan, Ke, Ji)
     match year_stem_idx {
         0 => ("Lian Zhen", "Po Jun", "Wu Qu", "Tai Yang"), // Jia
@@ -484,14 +848,14 @@ fn get_si_hua(year_stem_idx: usize) -> (&'static str, &'static str, &'static str
     }
 }
 
-// CHANGED: Input parameter type to match
-fn apply_si_hua(star: &mut String, map: &(&str, &str, &str, &str)) {
-    // Check if star contains the key name
-    let labels = ["(Hua Lu)", "(Hua Quan)", "(Hua Ke)", "(Hua Ji)"];
-
-    // Tuple unpacking for iteration is clumsy, so just do it manually
-    if star.starts_with(map.0) { star.push_str(" "); star.push_str(labels[0]); }
-    else if star.starts_with(map.1) { star.push_str(" "); star.push_str(labels[1]); }
-    else if star.starts_with(map.2) { star.push_str(" "); star.push_str(labels[2]); }
-    else if star.starts_with(map.3) { star.push_str(" "); star.push_str(labels[3]); }
+fn apply_si_hua(star: &mut Star, map: &(&str, &str, &str, &str)) {
+    let names = [map.0, map.1, map.2, map.3];
+    let labels = ["Hua Lu", "Hua Quan", "Hua Ke", "Hua Ji"];
+    if let Some(i) = names.iter().position(|n| *n == star.name_pinyin) {
+        star.transformation = Some(labels[i].to_string());
+    }
 }
+
+#[cfg(test)]
+#[path = "zi_wei_tests.rs"]
+mod tests;
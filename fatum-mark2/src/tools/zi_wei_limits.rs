@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tools::bazi_luck::year_pillar;
+use crate::tools::chinese_meta::{get_branch, get_stem};
+use crate::tools::zi_wei::{get_si_hua, Palace};
+
+/// Decade limits listed per chart: one per palace, 120 years in all.
+pub const DECADE_COUNT: usize = 12;
+
+const TRANSFORMATIONS: [&str; 4] = ["Hua Lu", "Hua Quan", "Hua Ke", "Hua Ji"];
+
+/// A Si Hua transformation flown by a decade or year stem onto a natal star.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlyingTransformation {
+    /// "Hua Lu", "Hua Quan", "Hua Ke" or "Hua Ji".
+    pub transformation: String,
+    pub star_id: String,
+    pub star: String,
+    /// Natal palace holding the star.
+    pub palace_idx: usize,
+    pub palace: String,
+}
+
+/// A 10-year limit (Da Xian) and the palace that rules it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecadeLimit {
+    /// Nominal (Chinese) ages, counting 1 at birth.
+    pub start_age: i32,
+    pub end_age: i32,
+    pub start_year: i32,
+    pub palace_idx: usize,
+    /// Natal palace the decade's Life palace falls on.
+    pub palace: String,
+    /// Stem and branch of the palace, e.g. "Bing Yin (Tiger)".
+    pub pillar: String,
+    pub transformations: Vec<FlyingTransformation>,
+}
+
+/// The flowing year (Liu Nian): its Life palace sits on the year's branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnualLimit {
+    pub year: i32,
+    /// Nominal age in the year.
+    pub age: i32,
+    pub pillar: String,
+    pub palace_idx: usize,
+    pub palace: String,
+    /// Palace of the decade the year falls in; `None` before the first decade starts.
+    pub decade_palace_idx: Option<usize>,
+    pub transformations: Vec<FlyingTransformation>,
+}
+
+/// Stem of the palace on `branch`, counted from the Tiger (Yin) palace by the Five Tigers rule.
+pub fn palace_stem(year_stem: usize, branch: usize) -> usize {
+    // Jia/Ji years start Yin on Bing, Yi/Geng on Wu, Bing/Xin on Geng, Ding/Ren on Ren, Wu/Gui on Jia
+    let tiger_stem = (year_stem % 5 * 2 + 2) % 10;
+    (tiger_stem + (branch + 10) % 12) % 10
+}
+
+/// Where the four transformations of `stem` land among the natal stars.
+pub fn flying_transformations(stem: usize, palaces: &[Palace]) -> Vec<FlyingTransformation> {
+    let (lu, quan, ke, ji) = get_si_hua(stem);
+    [lu, quan, ke, ji].iter().zip(TRANSFORMATIONS).filter_map(|(star, transformation)| {
+        let (palace, found) = palaces.iter().find_map(|p| {
+            p.major_stars.iter().chain(&p.minor_stars).find(|s| s.name_pinyin == *star).map(|s| (p, s))
+        })?;
+        Some(FlyingTransformation {
+            transformation: transformation.to_string(),
+            star_id: found.id.clone(),
+            star: star.to_string(),
+            palace_idx: palace.index,
+            palace: palace.name.clone(),
+        })
+    }).collect()
+}
+
+/// Decade limits from the Life palace: the first starts at the phase number's age and they
+/// run clockwise through the branches when `forward`, counter-clockwise otherwise.
+pub fn decade_limits(life_idx: usize, phase: u32, forward: bool, year_stem: usize, birth_year: i32, palaces: &[Palace]) -> Vec<DecadeLimit> {
+    (0..DECADE_COUNT).map(|i| {
+        let idx = if forward { (life_idx + i) % 12 } else { (life_idx + 12 - i) % 12 };
+        let stem = palace_stem(year_stem, idx);
+        let start_age = phase as i32 + 10 * i as i32;
+        DecadeLimit {
+            start_age,
+            end_age: start_age + 9,
+            start_year: birth_year + start_age - 1,
+            palace_idx: idx,
+            palace: palaces[idx].name.clone(),
+            pillar: format!("{} {}", get_stem(stem), get_branch(idx)),
+            transformations: flying_transformations(stem, palaces),
+        }
+    }).collect()
+}
+
+/// Flowing years `first..=last` for someone born in lunar year `birth_year`.
+pub fn annual_limits(first: i32, last: i32, birth_year: i32, decades: &[DecadeLimit], palaces: &[Palace]) -> Vec<AnnualLimit> {
+    (first..=last).map(|year| {
+        let (stem, branch) = year_pillar(year);
+        let age = year - birth_year + 1;
+        AnnualLimit {
+            year,
+            age,
+            pillar: format!("{} {}", get_stem(stem), get_branch(branch)),
+            palace_idx: branch,
+            palace: palaces[branch].name.clone(),
+            decade_palace_idx: decades.iter().find(|d| (d.start_age..=d.end_age).contains(&age)).map(|d| d.palace_idx),
+            transformations: flying_transformations(stem, palaces),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+#[path = "zi_wei_limits_tests.rs"]
+mod tests;
@@ -0,0 +1,68 @@
+use super::{palace_stem, DECADE_COUNT};
+use crate::tools::zi_wei::{generate_ziwei_chart, LeapMonthRule, ZiWeiChart, ZiWeiConfig};
+
+/// Lunar New Year 2024 (Jia Chen), noon: Life palace on Shen, Metal 4 phase.
+fn chart(gender: &str) -> ZiWeiChart {
+    generate_ziwei_chart(ZiWeiConfig {
+        birth_year: 2024,
+        birth_month: 2,
+        birth_day: 10,
+        birth_hour: 12,
+        gender: gender.to_string(),
+        birth_datetime: None,
+        leap_month: LeapMonthRule::Split,
+        annual_years: Some([2025, 2028]),
+        quantum_mode: false,
+        simulation_options: None,
+        location: None,
+        locale: None,
+    }).unwrap()
+}
+
+#[test]
+fn test_palace_stems_follow_five_tigers() {
+    assert_eq!(palace_stem(0, 2), 2); // Jia year: Bing Yin
+    assert_eq!(palace_stem(4, 2), 0); // Wu year: Jia Yin
+    assert_eq!(palace_stem(1, 3), 5); // Yi year: Ji Mao
+    assert_eq!(palace_stem(0, 1), 3); // Jia year: Ding Chou closes the cycle
+}
+
+#[test]
+fn test_decades_start_at_phase_and_follow_direction() {
+    let male = chart("M");
+    assert_eq!(male.element_phase, "Metal 4");
+    assert_eq!(male.decade_limits.len(), DECADE_COUNT);
+    let first = &male.decade_limits[0];
+    assert_eq!((first.start_age, first.end_age, first.start_year), (4, 13, 2027));
+    assert_eq!(first.palace_idx, male.life_palace_idx);
+    // Yang year, man: clockwise to You, whose stem is Gui
+    assert_eq!(male.decade_limits[1].palace_idx, 9);
+    assert_eq!(male.decade_limits[1].pillar, "Gui You (Rooster)");
+    // Yang year, woman: counter-clockwise to Wei
+    assert_eq!(chart("F").decade_limits[1].palace_idx, 7);
+}
+
+#[test]
+fn test_annual_limits_sit_on_year_branch() {
+    let chart = chart("M");
+    let years: Vec<i32> = chart.annual_limits.iter().map(|a| a.year).collect();
+    assert_eq!(years, vec![2025, 2026, 2027, 2028]);
+    let y2025 = &chart.annual_limits[0];
+    assert_eq!((y2025.pillar.as_str(), y2025.palace_idx, y2025.age), ("Yi Si (Snake)", 5, 2));
+    assert_eq!(y2025.decade_palace_idx, None);
+    assert_eq!(chart.annual_limits[3].decade_palace_idx, Some(8));
+}
+
+#[test]
+fn test_flying_transformations_land_on_natal_stars() {
+    let chart = chart("M");
+    // Yi year: Tian Ji Lu, Tian Liang Quan, Zi Wei Ke, Tai Yin Ji
+    let flown = &chart.annual_limits[0].transformations;
+    let stars: Vec<&str> = flown.iter().map(|t| t.star.as_str()).collect();
+    assert_eq!(stars, vec!["Tian Ji", "Tian Liang", "Zi Wei", "Tai Yin"]);
+    for t in flown {
+        let palace = &chart.palaces[t.palace_idx];
+        assert!(palace.major_stars.iter().chain(&palace.minor_stars).any(|s| s.id == t.star_id), "{:?}", t);
+        assert_eq!(t.palace, palace.name);
+    }
+}
@@ -0,0 +1,143 @@
+use super::{generate_ziwei_chart, run_quantum_analysis, LeapMonthRule, Star, StarCategory, ZiWeiConfig, PALACE_SIMULATIONS};
+use crate::engine::{SimulationOptions, SimulationSession};
+use crate::tools::i18n::Locale;
+use crate::tools::lunar::LunarDate;
+
+fn config(y: i32, m: u32, d: u32, h: u32, leap_month: LeapMonthRule) -> ZiWeiConfig {
+    ZiWeiConfig {
+        birth_year: y,
+        birth_month: m,
+        birth_day: d,
+        birth_hour: h,
+        gender: "M".to_string(),
+        birth_datetime: None,
+        leap_month,
+        annual_years: None,
+        quantum_mode: false,
+        simulation_options: None,
+        location: None,
+        locale: None,
+    }
+}
+
+#[test]
+fn test_leap_month_rules() {
+    let early = LunarDate { year: 2023, month: 2, day: 10, leap: true };
+    let late = LunarDate { year: 2023, month: 2, day: 20, leap: true };
+    assert_eq!(LeapMonthRule::Split.month_of(&early), 2);
+    assert_eq!(LeapMonthRule::Split.month_of(&late), 3);
+    assert_eq!(LeapMonthRule::Same.month_of(&late), 2);
+    assert_eq!(LeapMonthRule::Next.month_of(&early), 3);
+    // An ordinary month is never shifted; a leap 12th month wraps to the 1st
+    assert_eq!(LeapMonthRule::Next.month_of(&LunarDate { year: 2023, month: 2, day: 20, leap: false }), 2);
+    assert_eq!(LeapMonthRule::Next.month_of(&LunarDate { year: 2033, month: 12, day: 1, leap: true }), 1);
+}
+
+#[test]
+fn test_chart_uses_lunar_date() {
+    // 2024-02-10 is lunar New Year: month 1, day 1 of the Jia Chen year
+    let chart = generate_ziwei_chart(config(2024, 2, 10, 12, LeapMonthRule::Split)).unwrap();
+    assert_eq!(chart.lunar_birth, Some(LunarDate { year: 2024, month: 1, day: 1, leap: false }));
+    // Life palace: Yin + (month - 1) - hour = 2 + 0 - 6 -> Shen (8)
+    assert_eq!(chart.life_palace_idx, 8);
+}
+
+#[test]
+fn test_leap_rule_moves_life_palace() {
+    // 2023-04-10 is day 20 of the leap 2nd month
+    let same = generate_ziwei_chart(config(2023, 4, 10, 12, LeapMonthRule::Same)).unwrap();
+    let next = generate_ziwei_chart(config(2023, 4, 10, 12, LeapMonthRule::Next)).unwrap();
+    assert_eq!(same.lunar_birth.map(|l| l.leap), Some(true));
+    assert_eq!((same.life_palace_idx + 1) % 12, next.life_palace_idx);
+}
+
+#[test]
+fn test_birth_datetime_replaces_fields() {
+    let mut from_datetime = config(0, 0, 0, 0, LeapMonthRule::Split);
+    from_datetime.birth_datetime = Some(chrono::NaiveDate::from_ymd_opt(1990, 7, 15).unwrap().and_hms_opt(9, 30, 0).unwrap());
+    let a = generate_ziwei_chart(from_datetime).unwrap();
+    let b = generate_ziwei_chart(config(1990, 7, 15, 9, LeapMonthRule::Split)).unwrap();
+    assert_eq!(a.lunar_birth, b.lunar_birth);
+    assert_eq!(a.life_palace_idx, b.life_palace_idx);
+    assert_eq!(a.element_phase, b.element_phase);
+}
+
+#[test]
+fn test_stars_are_structured() {
+    // Jia Chen year: Lian Zhen Hua Lu, Po Jun Hua Quan, Wu Qu Hua Ke, Tai Yang Hua Ji
+    let chart = generate_ziwei_chart(config(2024, 2, 10, 12, LeapMonthRule::Split)).unwrap();
+    let stars: Vec<&Star> = chart.palaces.iter().flat_map(|p| p.major_stars.iter().chain(&p.minor_stars)).collect();
+    assert_eq!(stars.iter().filter(|s| s.category == StarCategory::Major).count(), 14);
+    let find = |id: &str| stars.iter().find(|s| s.id == id).unwrap();
+    assert_eq!(find("lian_zhen").transformation.as_deref(), Some("Hua Lu"));
+    assert_eq!(find("tai_yang").transformation.as_deref(), Some("Hua Ji"));
+    assert_eq!(find("tian_ji").transformation, None);
+    assert_eq!(find("qing_yang").category, StarCategory::Malefic);
+    assert!(stars.iter().all(|s| s.brightness.is_some() == (s.category == StarCategory::Major)));
+}
+
+#[test]
+fn test_chinese_names() {
+    let mut zh = config(2024, 2, 10, 12, LeapMonthRule::Split);
+    zh.locale = Some(Locale::Zh);
+    let chart = generate_ziwei_chart(zh).unwrap();
+    let life = &chart.palaces[chart.life_palace_idx];
+    assert_eq!(life.name, "命宫 (mìng gōng)");
+    let stars: Vec<&Star> = chart.palaces.iter().flat_map(|p| p.major_stars.iter().chain(&p.minor_stars)).collect();
+    assert!(stars.iter().all(|s| s.name_zh.is_some()));
+    assert_eq!(stars.iter().find(|s| s.id == "zi_wei").unwrap().name_zh.as_deref(), Some("紫微"));
+    // Limits name the palaces the same way; Si Hua still finds its stars by pinyin
+    assert_eq!(chart.decade_limits[0].palace, life.name);
+    assert_eq!(chart.decade_limits[0].transformations.len(), 4);
+
+    let en = generate_ziwei_chart(config(2024, 2, 10, 12, LeapMonthRule::Split)).unwrap();
+    assert_eq!(en.palaces[en.life_palace_idx].name, "Life");
+    assert!(en.palaces.iter().flat_map(|p| &p.major_stars).all(|s| s.name_zh.is_none()));
+}
+
+#[test]
+fn test_star_brightness() {
+    assert_eq!(Star::new("zi_wei", 6).brightness.as_deref(), Some("Miao"));
+    assert_eq!(Star::new("tai_yang", 0).brightness.as_deref(), Some("Xian"));
+    assert_eq!(Star::new("tai_yin", 11).brightness.as_deref(), Some("Miao"));
+    assert_eq!(Star::new("wen_chang", 0).brightness, None);
+    for id in ["zi_wei", "tian_ji", "tai_yang", "wu_qu", "tian_tong", "lian_zhen", "tian_fu", "tai_yin", "tan_lang", "ju_men", "tian_xiang", "tian_liang", "qi_sha", "po_jun"] {
+        for branch in 0..12 {
+            assert!(Star::new(id, branch).brightness.is_some(), "{} at {}", id, branch);
+        }
+    }
+}
+
+#[test]
+fn test_remaining_minor_stars() {
+    // Jia Chen year (Shen-Zi-Chen triad), 1st lunar month, Wu hour
+    let chart = generate_ziwei_chart(config(2024, 2, 10, 12, LeapMonthRule::Split)).unwrap();
+    let palace_of = |id: &str| chart.palaces.iter().find(|p| p.minor_stars.iter().any(|s| s.id == id)).map(|p| p.index);
+    let expected = [
+        ("huo_xing", 8), ("ling_xing", 4), ("di_kong", 5), ("di_jie", 5),
+        ("tai_fu", 0), ("feng_gao", 8), ("tian_xing", 9), ("tian_yao", 1),
+        ("tian_ma", 2), ("xian_chi", 9), ("hong_luan", 11), ("tian_xi", 5), ("tian_kong", 5),
+    ];
+    for (id, idx) in expected {
+        assert_eq!(palace_of(id), Some(idx), "{}", id);
+    }
+    let hong_luan = chart.palaces[11].minor_stars.iter().find(|s| s.id == "hong_luan").unwrap();
+    assert_eq!(hong_luan.category, StarCategory::PeachBlossom);
+}
+
+#[test]
+fn test_quantum_palace_focus() {
+    let chart = generate_ziwei_chart(config(2024, 2, 10, 12, LeapMonthRule::Split)).unwrap();
+    let mut session = SimulationSession::new((0..=255u8).cycle().take(PALACE_SIMULATIONS * 8).collect());
+    let analysis = run_quantum_analysis(&mut session, &chart, &SimulationOptions::default());
+
+    assert_eq!(analysis.weights.len(), 12);
+    assert!(analysis.weights.iter().all(|w| *w >= 1.0));
+    // The Lian Zhen (Hua Lu) palace outweighs a palace holding no stars at all
+    let lu_palace = chart.palaces.iter().position(|p| p.major_stars.iter().any(|s| s.id == "lian_zhen")).unwrap();
+    assert!(analysis.weights[lu_palace] > 2.0);
+    assert_eq!(analysis.distribution.values().sum::<usize>(), PALACE_SIMULATIONS);
+    assert_eq!(chart.palaces[analysis.focus_palace_idx].name, analysis.focus_palace);
+    assert!(analysis.narrative[0].starts_with(&format!("Focus falls on the {} palace", analysis.focus_palace)));
+    assert_eq!(session.fallback_draws, 0);
+}
@@ -0,0 +1,44 @@
+// === API KEY HANDLING ===
+// Every /api call and the /ws socket need an API key. The key is kept in
+// localStorage and attached to requests; on a 401 the user is asked for one.
+const API_KEY_STORAGE = "fatum_api_key";
+
+function getApiKey() {
+    return localStorage.getItem(API_KEY_STORAGE);
+}
+
+function promptApiKey() {
+    const key = prompt("API key required (create the first user with POST /api/v1/users):");
+    if (key) localStorage.setItem(API_KEY_STORAGE, key.trim());
+    return key;
+}
+
+function apiSocketUrl(path) {
+    const proto = location.protocol === 'https:' ? 'wss' : 'ws';
+    const key = getApiKey();
+    const query = key ? `?api_key=${encodeURIComponent(key)}` : '';
+    return `${proto}://${location.host}${path}${query}`;
+}
+
+(function wrapFetch() {
+    const rawFetch = window.fetch.bind(window);
+
+    function withKey(init) {
+        const key = getApiKey();
+        if (!key) return init;
+        const headers = new Headers((init && init.headers) || {});
+        headers.set("Authorization", `Bearer ${key}`);
+        return { ...(init || {}), headers };
+    }
+
+    window.fetch = async (input, init) => {
+        const url = typeof input === 'string' ? input : input.url;
+        if (!url.startsWith('/api')) return rawFetch(input, init);
+
+        let res = await rawFetch(input, withKey(init));
+        if (res.status === 401 && promptApiKey()) {
+            res = await rawFetch(input, withKey(init));
+        }
+        return res;
+    };
+})();
@@ -0,0 +1,151 @@
+
+// Reads a birth chart form; `prefix` is 'astro' for the main chart, 'syn' for the partner
+function readNatalConfig(prefix) {
+    const datetime = document.getElementById(`${prefix}-datetime`).value;
+    return {
+        datetime: datetime.length === 16 ? `${datetime}:00` : datetime,
+        latitude: parseFloat(document.getElementById(`${prefix}-latitude`).value),
+        location: {
+            longitude: parseFloat(document.getElementById(`${prefix}-longitude`).value),
+            utc_offset: parseFloat(document.getElementById(`${prefix}-utc-offset`).value)
+        },
+        house_system: document.getElementById('astro-houses').value
+    };
+}
+
+async function runNatalChart() {
+    const req = readNatalConfig('astro');
+
+    const res = await fetch('/api/v1/tools/natal', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(req)
+    });
+
+    const chart = await res.json();
+    renderNatalChart(chart);
+}
+
+function renderNatalChart(chart) {
+    const out = document.getElementById('astro-output');
+
+    if (chart.error) {
+        out.innerHTML = `<span style="color:var(--fire)">Error: ${chart.error}</span>`;
+        return;
+    }
+
+    const planets = chart.planets.map(p => `
+        <tr><td>${p.body}</td><td>${p.position.label}${p.retrograde ? ' ℞' : ''}</td><td>${p.house}</td><td>${p.element} / ${p.modality}</td></tr>`).join('');
+    const houses = chart.houses.map(h => `<tr><td>${h.house}</td><td>${h.position.label}</td></tr>`).join('');
+    const aspects = chart.aspects.map(a => `
+        <tr><td>${a.first}</td><td>${a.kind}</td><td>${a.second}</td><td>${a.orb.toFixed(1)}° ${a.applying ? 'applying' : 'separating'}</td></tr>`).join('');
+
+    out.innerHTML = `
+        <h3>Ascendant ${chart.ascendant.label} · Midheaven ${chart.midheaven.label}</h3>
+        <table><tr><th>Planet</th><th>Position</th><th>House</th><th>Element</th></tr>${planets}</table>
+        <h3>Houses (${chart.house_system === 'whole_sign' ? 'Whole Sign' : 'Placidus'})</h3>
+        <table><tr><th>House</th><th>Cusp</th></tr>${houses}</table>
+        <h3>Aspects</h3>
+        <table><tr><th></th><th>Aspect</th><th></th><th>Orb</th></tr>${aspects}</table>
+    `;
+}
+
+async function runTransits() {
+    const req = {
+        natal: readNatalConfig('astro'),
+        start_date: document.getElementById('transit-start').value,
+        end_date: document.getElementById('transit-end').value,
+        bodies: document.getElementById('transit-moon').checked
+            ? ['Sun', 'Moon', 'Mercury', 'Venus', 'Mars', 'Jupiter', 'Saturn', 'Uranus', 'Neptune', 'Pluto']
+            : []
+    };
+
+    const res = await fetch('/api/v1/tools/transits', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(req)
+    });
+
+    const report = await res.json();
+    const out = document.getElementById('astro-output');
+    if (report.error) {
+        out.innerHTML = `<span style="color:var(--fire)">Error: ${report.error}</span>`;
+        return;
+    }
+
+    const rows = report.hits.map(h => `
+        <tr><td>${h.utc.replace('T', ' ').slice(0, 16)} UT</td><td>${h.transiting}${h.retrograde ? ' ℞' : ''}</td><td>${h.aspect}</td><td>natal ${h.natal_point}</td><td>${h.position.label}</td></tr>`).join('');
+    out.innerHTML = `
+        <h3>Transits ${req.start_date} to ${req.end_date} (${report.hits.length} exact)</h3>
+        <table><tr><th>Exact</th><th>Transiting</th><th>Aspect</th><th>To</th><th>Position</th></tr>${rows}</table>
+    `;
+}
+
+async function runSynastry() {
+    const req = { first: readNatalConfig('astro'), second: readNatalConfig('syn') };
+
+    const res = await fetch('/api/v1/tools/synastry', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(req)
+    });
+
+    const report = await res.json();
+    const out = document.getElementById('astro-output');
+    if (report.error) {
+        out.innerHTML = `<span style="color:var(--fire)">Error: ${report.error}</span>`;
+        return;
+    }
+
+    // Grid: first chart's points down the side, second chart's across the top
+    const names = report.first.planets.map(p => p.body).concat(['Ascendant', 'Midheaven']);
+    const glyphs = { Conjunction: '☌', Sextile: '⚹', Square: '□', Trine: '△', Opposition: '☍' };
+    const cell = (a, b) => {
+        const aspect = report.aspects.find(x => x.first === a && x.second === b);
+        return aspect ? `<td title="${aspect.kind} ${aspect.orb.toFixed(1)}°">${glyphs[aspect.kind]}</td>` : '<td></td>';
+    };
+    const header = names.map(n => `<th>${n.slice(0, 3)}</th>`).join('');
+    const grid = names.map(a => `<tr><th>${a}</th>${names.map(b => cell(a, b)).join('')}</tr>`).join('');
+
+    out.innerHTML = `
+        <h3>Synastry Harmony: ${report.harmony_score.toFixed(1)}%</h3>
+        <ul>${report.factors.map(f => `<li>${f}</li>`).join('')}</ul>
+        <table><tr><th></th>${header}</tr>${grid}</table>
+    `;
+}
+
+async function runVedicChart() {
+    const natal = readNatalConfig('astro');
+    const req = {
+        datetime: natal.datetime,
+        latitude: natal.latitude,
+        location: natal.location,
+        ayanamsa: document.getElementById('vedic-ayanamsa').value
+    };
+
+    const res = await fetch('/api/v1/tools/vedic', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(req)
+    });
+
+    const chart = await res.json();
+    const out = document.getElementById('astro-output');
+    if (chart.error) {
+        out.innerHTML = `<span style="color:var(--fire)">Error: ${chart.error}</span>`;
+        return;
+    }
+
+    const grahas = chart.grahas.map(g => `
+        <tr><td>${g.name}${g.retrograde ? ' ℞' : ''}</td><td>${g.position.label}</td><td>${g.house}</td><td>${g.nakshatra.name} ${g.nakshatra.pada}</td></tr>`).join('');
+    const dashas = chart.dashas.map(d => `
+        <tr><td>${d.lord}</td><td>${d.start}</td><td>${d.end}</td><td><small>${d.sub_periods.map(s => `${s.lord} from ${s.start}`).join(', ')}</small></td></tr>`).join('');
+
+    out.innerHTML = `
+        <h3>Lagna ${chart.lagna.label} · Ayanamsa ${chart.ayanamsa_degrees.toFixed(3)}°</h3>
+        <p>Janma Nakshatra: ${chart.janma_nakshatra.name} (pada ${chart.janma_nakshatra.pada}, lord ${chart.janma_nakshatra.lord})</p>
+        <table><tr><th>Graha</th><th>Rashi</th><th>House</th><th>Nakshatra</th></tr>${grahas}</table>
+        <h3>Vimshottari Dasha</h3>
+        <table><tr><th>Mahadasha</th><th>From</th><th>To</th><th>Antardashas</th></tr>${dashas}</table>
+    `;
+}
@@ -12,7 +12,16 @@ async function runDaLiuRen() {
         solar_term_idx: term
     };
 
-    const res = await fetch('/api/tools/daliuren', {
+    // A datetime takes precedence; the server derives the indices from it
+    const datetime = document.getElementById('dlr-datetime').value;
+    if (datetime) {
+        req.datetime = datetime.length === 16 ? `${datetime}:00` : datetime;
+        const longitude = parseFloat(document.getElementById('dlr-longitude').value);
+        const utcOffset = parseFloat(document.getElementById('dlr-utc-offset').value);
+        if (!isNaN(longitude) && !isNaN(utcOffset)) req.location = { longitude, utc_offset: utcOffset };
+    }
+
+    const res = await fetch('/api/v1/tools/daliuren', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(req)
@@ -30,15 +39,17 @@ function renderDaLiuRen(chart) {
         return;
     }
 
-    let html = `<h3>${chart.description}</h3>`;
+    let html = `<h3>${chart.method || 'San Chuan'}</h3><p>${chart.description}</p>`;
+    if (chart.day_pillar) html += `<p>Day ${chart.day_pillar}, Hour ${chart.hour_branch}, Solar Term ${chart.solar_term_idx}</p>`;
+    const general = (list, i) => (list && list[i]) ? ` <em>(${list[i]})</em>` : '';
 
     // Three Transmissions
     html += `<div class="dlr-section">
         <h4>Three Transmissions (San Chuan)</h4>
         <div class="dlr-transmissions">
-            <div>1. Chu Chuan: ${chart.three_transmissions[0]}</div>
-            <div>2. Zhong Chuan: ${chart.three_transmissions[1]}</div>
-            <div>3. Mo Chuan: ${chart.three_transmissions[2]}</div>
+            <div>1. Chu Chuan: ${chart.three_transmissions[0]}${general(chart.transmission_generals, 0)}</div>
+            <div>2. Zhong Chuan: ${chart.three_transmissions[1]}${general(chart.transmission_generals, 1)}</div>
+            <div>3. Mo Chuan: ${chart.three_transmissions[2]}${general(chart.transmission_generals, 2)}</div>
         </div>
     </div>`;
 
@@ -50,7 +61,7 @@ function renderDaLiuRen(chart) {
                 <div class="dlr-lesson">
                     <strong>Lesson ${i+1}</strong><br>
                     Top: ${l.top}<br>
-                    Bottom: ${l.bottom}
+                    Bottom: ${l.bottom}${l.general ? `<br>General: ${l.general}` : ''}
                 </div>
             `).join('')}
         </div>
@@ -61,6 +72,7 @@ function renderDaLiuRen(chart) {
         <h4>Heaven Plate (On top of Earth 12)</h4>
         <p>Earth (Fixed): Rat, Ox, Tiger, Rabbit, Dragon, Snake, Horse, Goat, Monkey, Rooster, Dog, Pig</p>
         <p>Heaven (Rotated): ${chart.heaven_plate.join(', ')}</p>
+        ${chart.generals && chart.generals.length ? `<p>Generals (${chart.daytime ? 'Day' : 'Night'} Noble): ${chart.generals.join(', ')}</p>` : ''}
     </div>`;
 
     out.innerHTML = html;
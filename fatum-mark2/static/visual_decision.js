@@ -0,0 +1,108 @@
+// Visualizer for the Quantum Decision engine (flat options or a decision tree)
+
+async function runDecision() {
+    const req = {
+        simulations: parseInt(document.getElementById('dec-simulations').value) || 10000
+    };
+    const question = document.getElementById('dec-question').value.trim();
+    if (question) req.question = question;
+    const tree = document.getElementById('dec-tree').value.trim();
+    if (tree) {
+        try {
+            req.tree = JSON.parse(tree);
+        } catch (e) {
+            alert("The decision tree must be valid JSON.");
+            return;
+        }
+    } else {
+        req.options = document.getElementById('dec-options').value.split('\n').map(o => o.trim()).filter(o => o);
+        const weights = document.getElementById('dec-weights').value.trim();
+        if (weights) req.weights = weights.split(',').map(w => parseFloat(w));
+    }
+    const batchId = document.getElementById('dec-entropy-source').value;
+    if (batchId) req.entropy_batch_id = parseInt(batchId);
+
+    const out = document.getElementById('dec-output');
+    out.innerHTML = 'Collapsing Quantum Possibilities...';
+    try {
+        const res = await fetch('/api/v1/tools/decision', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(req)
+        });
+        renderDecision(await res.json());
+    } catch (e) {
+        console.error(e);
+        out.innerHTML = '<span style="color:var(--fire)">Network Error.</span>';
+    }
+}
+
+function renderDecision(report) {
+    const out = document.getElementById('dec-output');
+    if (report.error) {
+        out.innerHTML = `<span style="color:var(--fire)">Error: ${report.error}</span>`;
+        return;
+    }
+
+    const total = report.total_simulations || 1;
+    const rows = Object.entries(report.distribution).sort((a, b) => b[1] - a[1]);
+    let html = `<h3>${report.mode === 'tree' ? 'Path' : 'Choice'}: <span style="color:var(--accent)">${report.winner}</span></h3>`;
+    html += `<p>${report.total_simulations} simulations${report.stopped_early ? ' (stopped early)' : ''}`;
+    if (report.truncated) html += `, ${report.truncated} paths cut off at the depth limit`;
+    if (report.fallback_draws) html += `, ${report.fallback_draws} PRNG fallback draws`;
+    html += '</p><table><tr><th>' + (report.mode === 'tree' ? 'Path' : 'Option') + '</th><th>Count</th><th>Share</th></tr>';
+    rows.forEach(([name, count]) => {
+        const pct = (100 * count / total).toFixed(2);
+        html += `<tr><td>${name}</td><td>${count}</td><td><div style="background:var(--accent); height:10px; width:${pct}%"></div>${pct}%</td></tr>`;
+    });
+    html += '</table>';
+    (report.anomalies || []).forEach(a => { html += `<p style="color:var(--fire)">${a}</p>`; });
+    (report.diagnostics || []).forEach(d => { html += `<p style="color:#fa0">Warning: ${d.message}</p>`; });
+    if (report.utility) html += renderDecisionUtility(report.utility);
+    out.innerHTML = html;
+}
+
+// Expected utility per branch and how the outcome holds up when the weights are perturbed
+function renderDecisionUtility(u) {
+    const s = u.sensitivity;
+    let html = `<h3>Expected Utility: ${u.expected_utility.toFixed(2)} (walks averaged ${u.observed_utility.toFixed(2)})</h3>`;
+    html += `<p>Best choice: <span style="color:var(--accent)">${u.best_choice}</span>. ` +
+        `Likeliest path: ${s.likeliest_path} (${(100 * s.likeliest_probability).toFixed(1)}%).</p>`;
+    html += `<p>With weights varied by ±${Math.round(100 * s.perturbation)}% over ${s.runs} runs, the best choice held ` +
+        `${(100 * s.choice_stable).toFixed(0)}% of the time and the likeliest path ${(100 * s.path_stable).toFixed(0)}%; ` +
+        `expected utility moved by ${s.mean_utility_shift.toFixed(2)} on average.</p>`;
+    html += '<table><tr><th>Node</th><th>Branch</th><th>Chance</th><th>Expected</th><th>Walks</th><th>Observed</th></tr>';
+    u.branches.forEach(b => {
+        const observed = b.observed_utility === null ? '-' : b.observed_utility.toFixed(2);
+        html += `<tr><td>${b.node_id}</td><td>${b.branch}</td><td>${(100 * b.probability).toFixed(1)}%</td>` +
+            `<td>${b.expected_utility.toFixed(2)}</td><td>${b.walks}</td><td>${observed}</td></tr>`;
+    });
+    return html + '</table>';
+}
+
+// Lists what is wrong with the tree (dangling branches, cycles, unreachable nodes) without running it
+async function checkDecisionTree() {
+    const out = document.getElementById('dec-output');
+    let tree;
+    try {
+        tree = JSON.parse(document.getElementById('dec-tree').value);
+    } catch (e) {
+        alert("The decision tree must be valid JSON.");
+        return;
+    }
+    const res = await fetch('/api/v1/tools/decision/check', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(tree)
+    });
+    const result = await res.json();
+    if (result.error) {
+        out.innerHTML = `<span style="color:var(--fire)">Error: ${result.error}</span>`;
+        return;
+    }
+    let html = result.valid ? '<p style="color:var(--accent)">The tree is valid.</p>' : '<p style="color:var(--fire)">The tree cannot be simulated.</p>';
+    result.diagnostics.forEach(d => {
+        html += `<p style="color:${d.severity === 'error' ? 'var(--fire)' : '#fa0'}">${d.severity}: ${d.message}</p>`;
+    });
+    out.innerHTML = html;
+}
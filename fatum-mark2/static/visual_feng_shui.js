@@ -6,8 +6,36 @@ let floorplanImage = null;
 // INIT
 document.addEventListener("DOMContentLoaded", () => {
     initTooltips();
+    connectEventSocket();
 });
 
+// === SERVER PUSH ===
+// Refreshes harvest status, batch list and history when the server reports changes.
+function connectEventSocket() {
+    const socket = new WebSocket(apiSocketUrl('/ws'));
+
+    socket.onmessage = (msg) => {
+        const ev = JSON.parse(msg.data);
+        switch (ev.type) {
+            case 'harvest_started':
+            case 'harvest_stopped':
+                checkHarvestStatus();
+                loadEntropyBatches();
+                break;
+            case 'harvest_pulse':
+                loadEntropyBatches();
+                break;
+            case 'history_created':
+            case 'history_deleted':
+                loadHistory();
+                break;
+        }
+    };
+
+    // Reconnect after server restarts
+    socket.onclose = () => setTimeout(connectEventSocket, 5000);
+}
+
 function initTooltips() {
     const box = document.getElementById('tooltip-box');
 
@@ -44,13 +72,16 @@ function showTab(tabId) {
     if (tabId === 'profiles') loadProfiles();
     if (tabId === 'history') loadHistory();
     if (tabId === 'entropy') loadEntropyBatches();
-    if (tabId === 'fengshui') updateEntropyDropdown();
-    if (tabId === 'entanglement') loadEntanglementProfiles();
+    if (tabId === 'fengshui') { updateEntropyDropdown(); loadPresets(); }
+    if (tabId === 'divination') { updateEntropyDropdown(); loadNumerologyProfiles(); }
+    if (tabId === 'entanglement') { loadEntanglementProfiles(); updateEntropyDropdown(); }
     if (tabId === 'manyworlds') loadProfilesForManyWorlds();
+    if (tabId === 'geolocation') updateEntropyDropdown();
+    if (tabId === 'decision') updateEntropyDropdown();
 }
 
 async function loadProfilesForManyWorlds() {
-    const res = await fetch('/api/profiles');
+    const res = await fetch('/api/v1/profiles');
     const profiles = await res.json();
     const select = document.getElementById('mw-profile');
     select.innerHTML = '<option value="">-- Select Subject --</option>';
@@ -68,7 +99,7 @@ async function createEntropyBatch() {
     const name = document.getElementById('entropy-batch-name').value;
     if (!name) return alert("Enter a name");
 
-    const res = await fetch('/api/entropy/batches', {
+    const res = await fetch('/api/v1/entropy/batches', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ name })
@@ -80,7 +111,7 @@ async function createEntropyBatch() {
 }
 
 async function loadEntropyBatches() {
-    const res = await fetch('/api/entropy/batches');
+    const res = await fetch('/api/v1/entropy/batches');
     const batches = await res.json();
     const list = document.getElementById('entropy-batch-list');
     list.innerHTML = '';
@@ -105,7 +136,7 @@ async function loadEntropyBatches() {
 }
 
 async function startHarvest(batchId) {
-    await fetch('/api/entropy/harvest/start', {
+    await fetch('/api/v1/entropy/harvest/start', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify({ batch_id: batchId })
@@ -114,13 +145,13 @@ async function startHarvest(batchId) {
 }
 
 async function stopHarvest() {
-    await fetch('/api/entropy/harvest/stop', { method: 'POST' });
+    await fetch('/api/v1/entropy/harvest/stop', { method: 'POST' });
     checkHarvestStatus();
     loadEntropyBatches();
 }
 
 async function checkHarvestStatus() {
-    const res = await fetch('/api/entropy/harvest/status');
+    const res = await fetch('/api/v1/entropy/harvest/status');
     const data = await res.json();
     const panel = document.getElementById('active-harvest-panel');
     if (data.active_batch_id) {
@@ -132,21 +163,23 @@ async function checkHarvestStatus() {
 }
 
 async function updateEntropyDropdown() {
-    const res = await fetch('/api/entropy/batches');
+    const res = await fetch('/api/v1/entropy/batches');
     const batches = await res.json();
-    const select = document.getElementById('fs-entropy-source');
-    // Keep first option
-    select.innerHTML = '<option value="">Live Quantum Stream (Default)</option>';
-
-    batches.forEach(b => {
-        if (b.count > 0) {
-            const mb = (b.size_bytes / 1024).toFixed(2);
-            const opt = document.createElement('option');
-            opt.value = b.id;
-            opt.innerText = `Batch: ${b.name} (${mb} KB)`;
-            select.appendChild(opt);
-        }
-    });
+    for (const id of ['fs-entropy-source', 'div-entropy-source', 'ent-entropy-source', 'geo-entropy-source', 'dec-entropy-source']) {
+        const select = document.getElementById(id);
+        // Keep first option
+        select.innerHTML = '<option value="">Live Quantum Stream (Default)</option>';
+
+        batches.forEach(b => {
+            if (b.count > 0) {
+                const mb = (b.size_bytes / 1024).toFixed(2);
+                const opt = document.createElement('option');
+                opt.value = b.id;
+                opt.innerText = `Batch: ${b.name} (${mb} KB)`;
+                select.appendChild(opt);
+            }
+        });
+    }
 }
 
 // === PROFILES ===
@@ -160,7 +193,7 @@ async function createProfile() {
         gender: document.getElementById('p-gender').value
     };
 
-    const res = await fetch('/api/profiles', {
+    const res = await fetch('/api/v1/profiles', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(data)
@@ -175,7 +208,7 @@ async function createProfile() {
 }
 
 async function loadProfiles() {
-    const res = await fetch('/api/profiles');
+    const res = await fetch('/api/v1/profiles');
     const profiles = await res.json();
     const list = document.getElementById('profile-list');
     const select = document.getElementById('fs-profile-select');
@@ -187,7 +220,8 @@ async function loadProfiles() {
         // Add to list
         const card = document.createElement('div');
         card.className = 'card';
-        card.innerHTML = `<h4>${p.name}</h4><p>${p.gender} | ${p.birth_year}-${p.birth_month}-${p.birth_day}</p>`;
+        card.innerHTML = `<h4>${p.name}</h4><p>${p.gender} | ${p.birth_year}-${p.birth_month}-${p.birth_day}</p>
+        <button class="cyber-btn small" onclick="deleteProfile(${p.id})">DELETE</button>`;
         list.appendChild(card);
 
         // Add to select
@@ -198,14 +232,26 @@ async function loadProfiles() {
     });
 }
 
+// Removes a profile; its saved reports are kept unless the user opts to delete them too
+async function deleteProfile(id) {
+    if (!confirm('Delete this entity?')) return;
+    const history = confirm('Also delete its saved reports? (Cancel keeps them, unlinked)') ? 'cascade' : 'orphan';
+    const res = await fetch(`/api/v1/profiles/${id}?history=${history}`, { method: 'DELETE' });
+    if (!res.ok) return alert((await res.json()).error);
+    loadProfiles();
+    loadHistory();
+}
+
 async function loadEntanglementProfiles() {
-    const res = await fetch('/api/profiles');
+    const res = await fetch('/api/v1/profiles');
     const profiles = await res.json();
     const sel1 = document.getElementById('ent-profile1');
     const sel2 = document.getElementById('ent-profile2');
+    const group = document.getElementById('ent-group');
 
     sel1.innerHTML = '';
     sel2.innerHTML = '';
+    group.innerHTML = '';
 
     profiles.forEach(p => {
         const val = JSON.stringify(p);
@@ -218,6 +264,11 @@ async function loadEntanglementProfiles() {
         opt2.value = val;
         opt2.textContent = p.name;
         sel2.appendChild(opt2);
+
+        const opt3 = document.createElement('option');
+        opt3.value = val;
+        opt3.textContent = p.name;
+        group.appendChild(opt3);
     });
 }
 
@@ -225,8 +276,13 @@ async function runEntanglement() {
     const p1 = document.getElementById('ent-profile1').value;
     const p2 = document.getElementById('ent-profile2').value;
     const mode = document.getElementById('ent-mode').value;
+    const group = Array.from(document.getElementById('ent-group').selectedOptions).map(o => o.value);
 
-    if (!p1 || !p2) {
+    if (group.length > 0 && group.length < 3) {
+        alert("Select at least three profiles for a group, or none.");
+        return;
+    }
+    if (group.length === 0 && (!p1 || !p2)) {
         alert("Select two profiles.");
         return;
     }
@@ -237,8 +293,16 @@ async function runEntanglement() {
         profile2_data: p2,
         mode: mode
     };
+    if (group.length >= 3) req.group = group;
+    const batchId = document.getElementById('ent-entropy-source').value;
+    const seed = document.getElementById('ent-seed').value.trim();
+    if (batchId) req.entropy_batch_id = parseInt(batchId);
+    if (seed) req.seed = seed;
+    if (document.getElementById('ent-charts').checked) {
+        req.charts = { first: readNatalConfig('astro'), second: readNatalConfig('syn') };
+    }
 
-    const res = await fetch('/api/tools/entanglement', {
+    const res = await fetch('/api/v1/tools/entanglement', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(req)
@@ -250,6 +314,10 @@ async function runEntanglement() {
 
 function renderEntanglementReport(report) {
     const out = document.getElementById('ent-output');
+    if (report.error) {
+        out.innerHTML = `<span style="color:var(--fire)">Error: ${report.error}</span>`;
+        return;
+    }
 
     let color = "var(--primary)";
     if (report.resonance_score > 80) color = "var(--accent)";
@@ -267,6 +335,16 @@ function renderEntanglementReport(report) {
     if (report.shared_hexagram) {
         html += `<p style="margin-top:20px;"><strong>Shared Hexagram:</strong> ${report.shared_hexagram}</p>`;
     }
+    if (report.group) {
+        const g = report.group;
+        const esc = n => { const d = document.createElement('span'); d.textContent = n; return d.innerHTML; };
+        const header = g.members.map(m => `<th>${esc(m)}</th>`).join('');
+        const rows = g.matrix.map((row, i) => `<tr><th>${esc(g.members[i])}</th>${row.map((v, j) => `<td>${i === j ? '-' : v.toFixed(1)}</td>`).join('')}</tr>`).join('');
+        html += `<table><tr><th></th>${header}</tr>${rows}</table>`;
+    }
+    if (report.seed) {
+        html += `<p><small>Replay seed: <code>${report.seed}</code></small></p>`;
+    }
 
     out.innerHTML = html;
 }
@@ -352,6 +430,26 @@ function getElementColor(char) {
 
 // === FENG SHUI ===
 
+// Birth place for true solar time; null unless both fields are filled
+function readLocation() {
+    const longitude = parseFloat(document.getElementById('fs-longitude').value);
+    const utcOffset = parseFloat(document.getElementById('fs-utc-offset').value);
+    if (isNaN(longitude) || isNaN(utcOffset)) return null;
+    return { longitude, utc_offset: utcOffset };
+}
+
+// Compass degree of the water exit; null when left blank
+function readWaterExit() {
+    const deg = parseFloat(document.getElementById('fs-water-exit').value);
+    return isNaN(deg) ? null : deg;
+}
+
+// Other water on the site as comma-separated degrees
+function readWaterFeatures() {
+    return document.getElementById('fs-water-features').value
+        .split(',').map(v => parseFloat(v)).filter(v => !isNaN(v));
+}
+
 async function runFengShui() {
     const profileVal = document.getElementById('fs-profile-select').value;
     const profile = profileVal ? JSON.parse(profileVal) : null;
@@ -360,10 +458,14 @@ async function runFengShui() {
     const req = {
         construction_year: parseInt(document.getElementById('fs-year').value),
         facing_degrees: parseFloat(document.getElementById('fs-facing').value),
+        water_exit_degrees: readWaterExit(),
+        water_features: readWaterFeatures(),
         intention: document.getElementById('fs-intention').value,
         quantum_mode: document.getElementById('fs-quantum').checked,
         virtual_cures: window.virtualCures || [],
-        entropy_batch_id: entropyBatch ? parseInt(entropyBatch) : null
+        entropy_batch_id: entropyBatch ? parseInt(entropyBatch) : null,
+        rooms: parseRooms(document.getElementById('fs-rooms').value),
+        location: readLocation()
     };
 
     if (profile) {
@@ -374,7 +476,7 @@ async function runFengShui() {
         req.gender = profile.gender;
     }
 
-    const res = await fetch('/api/tools/fengshui', {
+    const res = await fetch('/api/v1/tools/fengshui', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(req)
@@ -384,6 +486,14 @@ async function runFengShui() {
     renderFengShuiOutput(currentReport);
 }
 
+// "Kitchen | kitchen | E SE" per line -> [{ name, kind, sectors }]
+function parseRooms(text) {
+    return text.split('\n')
+        .map(line => line.split('|').map(part => part.trim()))
+        .filter(parts => parts.length === 3 && parts[0])
+        .map(([name, kind, sectors]) => ({ name, kind: kind.toLowerCase(), sectors: sectors.split(/[\s,]+/).filter(Boolean) }));
+}
+
 function updateGridTransform() {
     if (currentReport) {
         renderFengShuiSVG(currentReport);
@@ -400,6 +510,30 @@ function renderFengShuiOutput(report) {
     if (report.bazi) {
         // Render SVG BaZi
         renderBaZiSVG(report.bazi);
+        const st = report.bazi.strength;
+        if (st) {
+            txt += `\n[DAY MASTER]\n>> ${report.bazi.day_master} ${st.day_master_element}: ${st.verdict.toUpperCase()} (${st.score}% support), ${st.season}\n`;
+            txt += `>> Useful: ${st.useful_elements.join(', ')} | Unfavorable: ${st.unfavorable_elements.join(', ')}\n`;
+            st.notes.forEach(n => txt += `   - ${n}\n`);
+        }
+        const gods = report.bazi.ten_gods;
+        if (gods) {
+            txt += `\n[TEN GODS]\n`;
+            ['year', 'month', 'day', 'hour'].forEach(pillar => {
+                const of = hidden => gods.placements.filter(p => p.pillar === pillar && p.hidden === hidden).map(p => `${p.stem} ${p.god}`);
+                const stem = of(false)[0] || 'Day Master';
+                txt += `>> ${pillar.toUpperCase()}: ${stem} | hidden: ${of(true).join(', ')}\n`;
+            });
+            gods.areas.forEach(a => txt += `   ${a.area.toUpperCase()} (${a.weight}): ${a.reading}\n`);
+        }
+        if (report.bazi.luck_pillars && report.bazi.luck_pillars.length) {
+            txt += `\n[LUCK PILLARS] (${report.bazi.luck_direction}, from age ${report.bazi.luck_start_age})\n`;
+            report.bazi.luck_pillars.forEach(p => txt += `>> Age ${p.start_age} (${p.start_year}): ${p.pillar} [${p.element}]\n`);
+        }
+        if (report.bazi.annual_pillars && report.bazi.annual_pillars.length) {
+            txt += `\n[ANNUAL PILLARS]\n`;
+            report.bazi.annual_pillars.forEach(p => txt += `>> ${p.year}: ${p.pillar} [${p.element}]${p.favorable ? ' *' : ''}\n`);
+        }
         if (report.bazi.quantum_flux) txt += `\n[BAZI QUANTUM FLUX]\n>> ${report.bazi.quantum_flux}\n`;
         if (report.bazi.alternate_pillars) txt += `>> ${report.bazi.alternate_pillars.join('\n>> ')}\n`;
     } else {
@@ -409,12 +543,55 @@ function renderFengShuiOutput(report) {
     if (report.quantum) {
         txt += `\n[QUANTUM FIELD]\nFocus: ${report.quantum.focus_sector}\nVolatility: ${report.quantum.volatility_index}\n`;
         if (report.quantum.cure_efficacy) txt += `Cure Efficacy: ${(report.quantum.cure_efficacy * 100).toFixed(1)}%\n`;
+        if (report.entropy_batch_id) {
+            const rounds = report.entropy_rounds || [];
+            txt += `Entropy: batch #${report.entropy_batch_id}` + (rounds.length ? `, rounds ${rounds[0]}-${rounds[rounds.length - 1]}` : '') + '\n';
+        }
+    }
+
+    if (report.ba_zhai) {
+        const bz = report.ba_zhai;
+        txt += `\n[BA ZHAI]\nHouse Kua ${bz.house_kua} (${bz.house_group})`;
+        if (bz.person_kua) txt += ` | Your Kua ${bz.person_kua} (${bz.person_group})${bz.compatible ? '' : ' - MISMATCHED GROUPS'}`;
+        txt += `\n`;
+        bz.sectors.forEach(sec => {
+            const person = sec.person_star ? ` / you: ${sec.person_star.name}` : '';
+            txt += `${sec.sector.padEnd(3)} ${sec.house_star.name}${person} [${sec.score > 0 ? '+' : ''}${sec.score}]\n`;
+        });
+        txt += `Best: ${bz.ranking.slice(0, 3).join(', ')}\n`;
+    }
+
+    if (report.rooms && report.rooms.length) {
+        txt += `\n[ROOMS]\n`;
+        report.rooms.forEach(room => {
+            txt += `${room.name} (${room.palaces.map(p => p.sector).join('/')}): ${room.rating.toUpperCase()}\n`;
+            room.advice.forEach(a => txt += `  - ${a}\n`);
+        });
+    }
+
+    if (report.san_he && report.san_he.frame) {
+        const sh = report.san_he;
+        txt += `\n[SAN HE]\nSitting ${sh.sitting_mountain}: ${sh.frame}\n`;
+        if (sh.water_exit) {
+            txt += `Water exit ${sh.water_exit.mountain} (${sh.water_exit.phase}) ${sh.water_exit.auspicious ? 'GOOD' : 'BAD'}: ${sh.water_exit.note}\n`;
+        }
+        txt += `Auspicious exits: ${sh.lucky_water_exit.join(', ')}\n`;
+        (sh.warnings || []).forEach(w => txt += `WARNING ${w.kind} (${w.mountain}, ${w.degrees}°): ${w.message}\n`);
+        (sh.sha_directions || []).forEach(d => txt += `Keep water out of ${d.mountain} ${d.start_deg}°-${d.end_deg}° (${d.kind})\n`);
     }
 
     if (report.qimen) {
         const qm = report.qimen;
         txt += `\n[QI MEN DUN JIA]\nTerm: ${qm.solar_term} (${qm.dun_type} Ju ${qm.ju_number})\n`;
-        txt += `Structure: ${qm.palaces[0].structure}\n`; // Just grab first for summary
+        txt += `${qm.time_label}${qm.xun_shou ? ` (Xun Shou ${qm.xun_shou})` : ''}\nZhi Fu: ${qm.duty_star} | Zhi Shi: ${qm.duty_door}\n`;
+        const structured = qm.palaces.filter(p => p.structures && p.structures.length);
+        if (structured.length) {
+            structured.forEach(p => {
+                txt += `${p.position}: ${p.structures.map(s => `${s.name} (${s.severity > 0 ? '+' : ''}${s.severity})`).join(', ')}\n`;
+            });
+        } else {
+            txt += `Structure: Normal\n`;
+        }
     }
 
     out.innerText = txt;
@@ -652,12 +829,19 @@ async function runZeRi() {
     }
 
     let userYear = null;
+    let birthDate = null;
+    let birthHour = null;
     if (usePersonal) {
         // Try to get from FS Profile Select
         const pVal = document.getElementById('fs-profile-select').value;
         if (pVal) {
             const p = JSON.parse(pVal);
             userYear = p.birth_year;
+            if (p.birth_month && p.birth_day) {
+                const pad = n => String(n).padStart(2, '0');
+                birthDate = `${p.birth_year}-${pad(p.birth_month)}-${pad(p.birth_day)}`;
+                birthHour = p.birth_hour ?? null;
+            }
         } else {
             // Or manual input? For now, warn if no profile selected
             if (!confirm("No profile selected in 'Feng Shui' tab. Proceed with Generic Mode?")) {
@@ -670,10 +854,14 @@ async function runZeRi() {
         start_date: start,
         end_date: end,
         intention: intention || null,
-        user_birth_year: userYear
+        user_birth_year: userYear,
+        user_birth_date: birthDate,
+        user_birth_hour: birthHour,
+        include_inauspicious: document.getElementById('zr-inauspicious').checked,
+        quantum_mode: document.getElementById('zr-quantum').checked
     };
 
-    const res = await fetch('/api/tools/zeri', {
+    const res = await fetch('/api/v1/tools/zeri', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(req)
@@ -683,15 +871,29 @@ async function runZeRi() {
     renderZeRiOutput(dates);
 }
 
-function renderZeRiOutput(dates) {
+function renderZeRiOutput(data) {
     const out = document.getElementById('zr-output');
     out.innerHTML = '';
 
-    if (dates.error) {
-        out.innerHTML = `<p class="error">${dates.error}</p>`;
+    if (data.error) {
+        out.innerHTML = `<p class="error">${data.error}</p>`;
         return;
     }
 
+    // Quantum mode wraps the dates with the simulation's pick
+    const dates = Array.isArray(data) ? data : data.dates;
+    if (data.quantum) {
+        const q = data.quantum;
+        const card = document.createElement('div');
+        card.className = 'card';
+        card.innerHTML = `
+            <h4 style="color:var(--accent)">Quantum Pick: ${q.favored_date} (Score: ${q.favored_score})</h4>
+            ${q.narrative.map(line => `<p>${line}</p>`).join('')}
+            ${q.anomalies.length ? `<p style="color:var(--fire)">Anomalies: ${q.anomalies.join('; ')}</p>` : ''}
+        `;
+        out.appendChild(card);
+    }
+
     if (dates.length === 0) {
         out.innerHTML = `<p>No auspicious dates found in this range.</p>`;
         return;
@@ -707,32 +909,215 @@ function renderZeRiOutput(dates) {
 
         card.innerHTML = `
             <h4 style="color:${color}">${d.date} (Score: ${d.score})</h4>
+            ${d.lunar_date ? `<p><small>${d.lunar_date}${d.mansion ? ` · Mansion ${d.mansion}` : ''}</small></p>` : ''}
             <p>${d.summary}</p>
             ${d.collision ? `<p style="color:var(--fire)">⚠️ ${d.collision}</p>` : ''}
+            ${renderZeRiHours(d.hours)}
         `;
         out.appendChild(card);
     });
 }
 
+function renderZeRiHours(hours) {
+    if (!hours || hours.length === 0) return '';
+    const rows = hours.map(h => `
+        <tr style="color:${h.score > 0 ? 'var(--accent)' : (h.clash ? 'var(--fire)' : 'inherit')}">
+            <td>${h.time_range}</td><td>${h.pillar}</td><td>${h.officer}</td>
+            <td>${h.noble ? '★' : ''}</td><td>${h.score}</td><td><small>${h.clash || ''}</small></td>
+        </tr>`).join('');
+    return `<details><summary>Hours</summary>
+        <table><tr><th>Time</th><th>Pillar</th><th>Spirit</th><th>Noble</th><th>Score</th><th>Clash</th></tr>${rows}</table>
+    </details>`;
+}
+
 // === DIVINATION ===
 
 async function castHexagram() {
-    const res = await fetch('/api/tools/divination', { method: 'POST' });
-    currentHexagram = await res.json();
-
+    const question = document.getElementById('div-question').value.trim();
+    const batch = document.getElementById('div-entropy-source').value;
+    const res = await fetch('/api/v1/tools/divination', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+            method: document.getElementById('div-method').value,
+            question: question || null,
+            entropy_batch_id: batch ? parseInt(batch) : null,
+            strict_quantum: document.getElementById('div-strict').checked
+        })
+    });
+    const data = await res.json();
     const out = document.getElementById('divination-text');
+    if (data.error) {
+        out.innerHTML = `<p class="error">${data.error}</p>`;
+        return;
+    }
+    currentHexagram = data;
+
     out.innerHTML = `<h3>Hexagram ${currentHexagram.number}: ${currentHexagram.name}</h3>
     <p><strong>Judgment:</strong> ${currentHexagram.judgment}</p>
     <p><strong>Image:</strong> ${currentHexagram.image}</p>`;
 
+    if (currentHexagram.upper_trigram) {
+        const h = currentHexagram;
+        out.innerHTML += `<p><strong>Trigrams:</strong> ${h.upper_trigram.name} (${h.upper_trigram.element}) over ${h.lower_trigram.name} (${h.lower_trigram.element})</p>`;
+        const derived = [['Nuclear (Hu Gua)', h.nuclear_hexagram], ['Opposite (Cuo Gua)', h.opposite_hexagram], ['Reversed (Zong Gua)', h.reversed_hexagram]]
+            .filter(([, d]) => d)
+            .map(([label, d]) => `${label}: ${d.number} ${d.name}`);
+        if (derived.length) out.innerHTML += `<p><small>${derived.join(' · ')}</small></p>`;
+    }
+
+    if (currentHexagram.changing_line_texts && currentHexagram.changing_line_texts.length) {
+        out.innerHTML += `<p><strong>Changing Lines:</strong></p>` +
+            currentHexagram.changing_line_texts.map(t => `<p>${t}</p>`).join('');
+    }
+
     if (currentHexagram.transformed_hexagram) {
         out.innerHTML += `<hr><h3>Transformed to: Hexagram ${currentHexagram.transformed_hexagram.number}: ${currentHexagram.transformed_hexagram.name}</h3>
         <p><strong>Judgment:</strong> ${currentHexagram.transformed_hexagram.judgment}</p>`;
     }
 
+    if (currentHexagram.question) {
+        // textContent: the question is user text
+        const asked = document.createElement('p');
+        asked.textContent = `“${currentHexagram.question}” · digest ${currentHexagram.question_digest.slice(0, 16)}…`;
+        out.prepend(asked);
+    }
+
     renderHexagramSVG(currentHexagram);
 }
 
+async function castLiuYao() {
+    const res = await fetch('/api/v1/tools/liuyao', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ method: document.getElementById('div-method').value })
+    });
+    const chart = await res.json();
+    const out = document.getElementById('liuyao-output');
+    if (chart.error) {
+        out.innerHTML = `<p class="error">${chart.error}</p>`;
+        return;
+    }
+
+    const rows = chart.lines.slice().reverse().map(l => `
+        <tr${l.moving ? ' style="color:var(--accent)"' : ''}>
+            <td>${l.position}</td><td>${l.yang ? '⚊' : '⚋'}${l.moving ? (l.yang ? ' ○' : ' ×') : ''}</td>
+            <td>${l.spirit}</td><td>${l.relative}</td><td>${l.stem} ${l.branch} ${l.element}</td>
+            <td>${l.shi ? 'Shi' : (l.ying ? 'Ying' : '')}</td>
+            <td>${l.changed ? `${l.changed.relative} ${l.changed.stem} ${l.changed.branch}` : ''}</td>
+            <td><small>${[l.month_strength, ...l.notes].join('; ')}</small></td>
+        </tr>`).join('');
+    out.innerHTML = `
+        <h3>${chart.name}${chart.changed_name ? ` → ${chart.changed_name}` : ''}</h3>
+        <p>${chart.palace} palace (${chart.palace_element}), ${chart.generation} · Day ${chart.day_pillar} · Month ${chart.month_branch} · Void ${chart.void_branches.join(', ')}</p>
+        <table><tr><th>#</th><th>Line</th><th>Spirit</th><th>Relative</th><th>Najia</th><th></th><th>Changes To</th><th>Notes</th></tr>${rows}</table>
+        ${chart.missing_relatives.length ? `<p><small>Missing: ${chart.missing_relatives.join(', ')}</small></p>` : ''}
+    `;
+}
+
+async function drawTarot() {
+    const res = await fetch('/api/v1/tools/tarot', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+            spread: document.getElementById('tarot-spread').value,
+            upright_only: document.getElementById('tarot-upright').checked
+        })
+    });
+    const reading = await res.json();
+    const out = document.getElementById('tarot-output');
+    if (reading.error) {
+        out.innerHTML = `<p class="error">${reading.error}</p>`;
+        return;
+    }
+
+    const rows = reading.cards.map(c => `
+        <tr>
+            <td>${c.position}. ${c.position_name}<br><small>${c.position_meaning}</small></td>
+            <td>${c.name}${c.reversed ? ' (Reversed)' : ''}<br><small>${c.keywords}</small></td>
+            <td>${c.meaning}</td>
+        </tr>`).join('');
+    out.innerHTML = `
+        <table><tr><th>Position</th><th>Card</th><th>Meaning</th></tr>${rows}</table>
+        <p><small>Major Arcana: ${reading.major_count} · Reversed: ${reading.reversed_count}${reading.dominant_suit ? ` · Dominant suit: ${reading.dominant_suit}` : ''}</small></p>
+    `;
+}
+
+async function castRunes() {
+    const res = await fetch('/api/v1/tools/runes', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({
+            cast: document.getElementById('rune-cast').value,
+            upright_only: document.getElementById('rune-upright').checked
+        })
+    });
+    const reading = await res.json();
+    const out = document.getElementById('runes-output');
+    if (reading.error) {
+        out.innerHTML = `<p class="error">${reading.error}</p>`;
+        return;
+    }
+
+    const rows = reading.runes.map(r => `
+        <tr>
+            <td>${r.position}. ${r.position_name}<br><small>${r.position_meaning}</small></td>
+            <td><span style="font-size:1.6em">${r.letter}</span> ${r.name}${r.reversed ? ' (Merkstave)' : ''}<br><small>${r.keywords}</small></td>
+            <td>${r.meaning}</td>
+        </tr>`).join('');
+    out.innerHTML = `<table><tr><th>Position</th><th>Rune</th><th>Meaning</th></tr>${rows}</table>`;
+}
+
+async function loadNumerologyProfiles() {
+    const res = await fetch('/api/v1/profiles');
+    const profiles = await res.json();
+    const select = document.getElementById('num-profile');
+    select.innerHTML = '<option value="">-- No Profile --</option>';
+
+    profiles.forEach(p => {
+        const opt = document.createElement('option');
+        opt.value = p.id;
+        opt.textContent = p.name;
+        select.appendChild(opt);
+    });
+}
+
+async function runNumerology() {
+    const profileId = document.getElementById('num-profile').value;
+    const req = {
+        name: document.getElementById('num-name').value.trim() || null,
+        birth_date: document.getElementById('num-birth-date').value || null,
+        profile_id: profileId ? parseInt(profileId) : null,
+        resonance: document.getElementById('num-resonance').checked
+    };
+    const res = await fetch('/api/v1/tools/numerology', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(req)
+    });
+    const report = await res.json();
+    const out = document.getElementById('numerology-output');
+    if (report.error) {
+        out.innerHTML = `<p class="error">${report.error}</p>`;
+        return;
+    }
+
+    const rows = [
+        ['Life Path', report.life_path],
+        ['Expression', report.expression],
+        ['Soul Urge', report.soul_urge],
+        [`Personal Year ${report.target_date.slice(0, 4)}`, report.personal_year],
+        ['Personal Month', report.personal_month]
+    ].map(([label, n]) => `<tr><td>${label}</td><td>${n.value}${n.master ? ' (Master)' : ''}</td><td>${n.meaning}</td></tr>`).join('');
+    let html = `<h3></h3><table><tr><th>Number</th><th>Value</th><th>Meaning</th></tr>${rows}</table>`;
+    if (report.resonance) {
+        const r = report.resonance;
+        html += `<p>Resonance ${r.score.toFixed(2)}× chance over ${r.trials} draws · strongest: ${r.strongest}</p>`;
+    }
+    out.innerHTML = html;
+    out.querySelector('h3').textContent = report.name;
+}
+
 function renderHexagramSVG(hex) {
     const container = document.getElementById('hex-svg-container');
     container.innerHTML = '';
@@ -787,6 +1172,11 @@ async function saveReport(type) {
         alert("No active report to save.");
         return;
     }
+    // Tool runs are archived by the server unless it has auto-archiving turned off
+    if (data.history_id) {
+        alert(`Already archived (entry #${data.history_id}).`);
+        return;
+    }
 
     const req = {
         profile_id: pid,
@@ -795,7 +1185,7 @@ async function saveReport(type) {
         full_report: data
     };
 
-    const res = await fetch('/api/history', {
+    const res = await fetch('/api/v1/history', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(req)
@@ -804,8 +1194,72 @@ async function saveReport(type) {
     if (res.ok) alert("Archived successfully.");
 }
 
+// PRESETS (saved Feng Shui house setups)
+let fsPresets = [];
+
+async function loadPresets() {
+    const res = await fetch('/api/v1/presets?tool=fengshui');
+    if (!res.ok) return;
+    fsPresets = await res.json();
+    const select = document.getElementById('fs-preset-select');
+    select.innerHTML = '<option value="">-- None --</option>';
+    fsPresets.forEach(p => {
+        const opt = document.createElement('option');
+        opt.value = p.id;
+        opt.textContent = p.name;
+        select.appendChild(opt);
+    });
+}
+
+function applyPreset() {
+    const preset = fsPresets.find(p => p.id == document.getElementById('fs-preset-select').value);
+    if (!preset) return;
+    const c = preset.config;
+    if (c.construction_year !== undefined) document.getElementById('fs-year').value = c.construction_year;
+    if (c.facing_degrees !== undefined) document.getElementById('fs-facing').value = c.facing_degrees;
+    if (c.water_features !== undefined) document.getElementById('fs-water-features').value = (c.water_features || []).join(', ');
+    if (c.water_exit_degrees !== undefined) document.getElementById('fs-water-exit').value = c.water_exit_degrees ?? '';
+    if (c.intention !== undefined) document.getElementById('fs-intention').value = c.intention;
+    if (c.quantum_mode !== undefined) document.getElementById('fs-quantum').checked = c.quantum_mode;
+    if (c.rooms !== undefined) {
+        document.getElementById('fs-rooms').value = c.rooms
+            .filter(r => r.sectors)
+            .map(r => `${r.name} | ${r.kind} | ${r.sectors.join(' ')}`).join('\n');
+    }
+}
+
+// Saves the current house setup; an existing preset of the same name is overwritten
+async function savePreset() {
+    const name = prompt('Preset name:');
+    if (!name) return;
+    const config = {
+        construction_year: parseInt(document.getElementById('fs-year').value),
+        facing_degrees: parseFloat(document.getElementById('fs-facing').value),
+        water_exit_degrees: readWaterExit(),
+        water_features: readWaterFeatures(),
+        intention: document.getElementById('fs-intention').value,
+        quantum_mode: document.getElementById('fs-quantum').checked,
+        rooms: parseRooms(document.getElementById('fs-rooms').value),
+    };
+    const existing = fsPresets.find(p => p.name === name.trim());
+    const res = existing
+        ? await fetch(`/api/v1/presets/${existing.id}`, {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ name, config })
+        })
+        : await fetch('/api/v1/presets', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ tool_type: 'fengshui', name, config })
+        });
+    if (!res.ok) return alert((await res.json()).error);
+    await loadPresets();
+    document.getElementById('fs-preset-select').value = existing ? existing.id : (await res.json()).id;
+}
+
 async function loadHistory() {
-    const res = await fetch('/api/history');
+    const res = await fetch('/api/v1/history');
     const items = await res.json();
     const list = document.getElementById('history-list');
     list.innerHTML = '';
@@ -815,20 +1269,43 @@ async function loadHistory() {
         card.className = 'card';
         card.innerHTML = `<h4>${h.tool_type.toUpperCase()}</h4>
         <p>${h.summary}</p>
-        <small>${h.created_at} | ${h.profile_name || 'Anonymous'}</small>`;
+        <small>${h.created_at} | ${h.profile_name || 'Anonymous'}</small>
+        <div>
+            <button class="cyber-btn small" onclick="viewHistory(${h.id})">VIEW</button>
+            <button class="cyber-btn small" onclick="deleteHistory(${h.id})">DELETE</button>
+        </div>`;
         list.appendChild(card);
     });
 }
 
+// Opens the stored report of a history entry as JSON in a new tab
+async function viewHistory(id) {
+    const res = await fetch(`/api/v1/history/${id}`);
+    const entry = await res.json();
+    if (!res.ok) return alert(entry.error);
+    const blob = new Blob([JSON.stringify(entry.full_report, null, 2)], { type: 'application/json' });
+    window.open(URL.createObjectURL(blob), '_blank');
+}
+
+async function deleteHistory(id) {
+    if (!confirm('Delete this report?')) return;
+    const res = await fetch(`/api/v1/history/${id}`, { method: 'DELETE' });
+    if (!res.ok) return alert((await res.json()).error);
+    loadHistory();
+}
+
 async function downloadPdf() {
     // Re-send request to PDF endpoint
     // Reuse current params
     const req = {
         construction_year: parseInt(document.getElementById('fs-year').value),
         facing_degrees: parseFloat(document.getElementById('fs-facing').value),
+        water_exit_degrees: readWaterExit(),
+        water_features: readWaterFeatures(),
         intention: document.getElementById('fs-intention').value,
         quantum_mode: document.getElementById('fs-quantum').checked,
-        virtual_cures: window.virtualCures || []
+        virtual_cures: window.virtualCures || [],
+        location: readLocation()
     };
     const profileVal = document.getElementById('fs-profile-select').value;
     if (profileVal) {
@@ -840,7 +1317,7 @@ async function downloadPdf() {
         req.gender = p.gender;
     }
 
-    const res = await fetch('/api/tools/fengshui/pdf', {
+    const res = await fetch('/api/v1/tools/fengshui/pdf', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(req)
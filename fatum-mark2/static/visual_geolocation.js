@@ -0,0 +1,152 @@
+// Visualizer for Quantum Geolocation (GeoJSON point cloud on SVG)
+
+async function runGeolocation() {
+    const req = {
+        latitude: parseFloat(document.getElementById('geo-lat').value),
+        longitude: parseFloat(document.getElementById('geo-lon').value),
+        radius_m: parseFloat(document.getElementById('geo-radius').value),
+        points: parseInt(document.getElementById('geo-points').value) || 2000
+    };
+    const batchId = document.getElementById('geo-entropy-source').value;
+    if (batchId) req.entropy_batch_id = parseInt(batchId);
+    req.strict_quantum = document.getElementById('geo-strict').checked;
+    const exclusions = document.getElementById('geo-exclusions').value.trim();
+    if (exclusions) {
+        try {
+            req.exclusions = JSON.parse(exclusions);
+        } catch (e) {
+            alert("Exclusion zones must be valid JSON.");
+            return;
+        }
+    }
+
+    const out = document.getElementById('geo-output');
+    out.innerHTML = 'Harvesting Quantum Entropy & Scanning...';
+    try {
+        const res = await fetch('/api/v1/tools/geolocation', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(req)
+        });
+        const geojson = await res.json();
+        renderGeolocation(geojson);
+    } catch (e) {
+        console.error(e);
+        out.innerHTML = '<span style="color:var(--fire)">Network Error.</span>';
+    }
+}
+
+function renderGeolocation(geojson) {
+    const out = document.getElementById('geo-output');
+    if (geojson.error) {
+        out.innerHTML = `<span style="color:var(--fire)">Error: ${geojson.error}</span>`;
+        return;
+    }
+
+    const features = geojson.features;
+    const center = features.find(f => f.properties.kind === 'center');
+    const [lon0, lat0] = center.geometry.coordinates;
+    const radius = center.properties.radius_m;
+
+    // Local equirectangular projection, metres east/north of the centre
+    const size = 500;
+    const scale = (size / 2 - 10) / radius;
+    const mPerDeg = 111320;
+    const project = ([lon, lat]) => [
+        size / 2 + (lon - lon0) * mPerDeg * Math.cos(lat0 * Math.PI / 180) * scale,
+        size / 2 - (lat - lat0) * mPerDeg * scale
+    ];
+
+    let svg = `<svg width="${size}" height="${size}" style="background:#000; border:1px solid #333;">`;
+    svg += `<circle cx="${size / 2}" cy="${size / 2}" r="${radius * scale}" fill="none" stroke="#333" />`;
+    // Heatmap: cells above uniform density glow green, cells below fade to red
+    features.filter(f => f.properties.kind === 'density').forEach(f => {
+        const p = f.properties;
+        const [x, y] = project(f.geometry.coordinates);
+        const side = p.cell_m * scale;
+        const color = p.power >= 0 ? '0,255,128' : '255,64,64';
+        const alpha = Math.min(Math.abs(p.power) / 4, 1) * 0.6;
+        svg += `<rect x="${(x - side / 2).toFixed(1)}" y="${(y - side / 2).toFixed(1)}" width="${side.toFixed(1)}" height="${side.toFixed(1)}" fill="rgba(${color},${alpha.toFixed(2)})" />`;
+    });
+    features.filter(f => f.properties.kind === 'exclusion').forEach(f => {
+        const coords = f.geometry.type === 'Polygon' ? f.geometry.coordinates[0] : f.geometry.coordinates;
+        const path = coords.map(c => project(c).map(v => v.toFixed(1)).join(',')).join(' ');
+        const width = Math.max(1, 2 * f.properties.buffer_m * scale);
+        svg += f.geometry.type === 'Polygon'
+            ? `<polygon points="${path}" fill="rgba(80,80,255,0.35)" stroke="#55f" stroke-width="${width}" />`
+            : `<polyline points="${path}" fill="none" stroke="rgba(80,80,255,0.5)" stroke-width="${width}" stroke-linecap="round" />`;
+    });
+    const cloud = features.filter(f => f.properties.kind === 'point');
+    const stride = Math.max(1, Math.ceil(cloud.length / 5000));
+    cloud.filter((_, i) => i % stride === 0).forEach(f => {
+        const [x, y] = project(f.geometry.coordinates);
+        svg += `<circle cx="${x.toFixed(1)}" cy="${y.toFixed(1)}" r="1.2" fill="#0ff" opacity="0.5" />`;
+    });
+
+    let rows = '';
+    features.filter(f => f.properties.kind === 'attractor' || f.properties.kind === 'void').forEach(f => {
+        const p = f.properties;
+        const [x, y] = project(f.geometry.coordinates);
+        const color = p.kind === 'attractor' ? 'var(--accent)' : 'var(--fire)';
+        svg += `<circle cx="${x}" cy="${y}" r="${p.radius_m * scale}" fill="none" stroke="${color}" stroke-width="2" />`;
+        svg += `<text x="${x + 4}" y="${y - 4}" fill="${color}" font-size="12">${p.kind[0].toUpperCase()}${p.rank}</text>`;
+        const [lon, lat] = f.geometry.coordinates;
+        rows += `<tr><td style="color:${color}">${p.kind} ${p.rank}</td><td>${lat.toFixed(5)}, ${lon.toFixed(5)}</td>` +
+            `<td>${p.power.toFixed(2)}</td><td>${p.count} / ${p.expected.toFixed(1)}</td><td>${Math.round(p.radius_m)} m</td>` +
+            `<td>${Math.round(p.distance_m)} m @ ${Math.round(p.bearing_deg)}°</td>` +
+            (geojson.history_id ? `<td><button class="cyber-btn" onclick="logGeolocationTrip(${geojson.history_id}, '${p.kind}', ${p.rank})">LOG TRIP</button></td>` : '') +
+            '</tr>';
+    });
+    svg += '</svg>';
+
+    const bytes = center.properties.entropy_ranges.reduce((sum, r) => sum + r.end - r.start, 0);
+    let provenance = `<p>${bytes} quantum bytes used`;
+    if (center.properties.redrawn > 0) provenance += `, ${center.properties.redrawn} masked draws redrawn`;
+    if (center.properties.fallback_draws > 0) provenance += `, ${center.properties.fallback_draws} PRNG fallback draws`;
+    provenance += '</p>';
+
+    out.innerHTML = provenance + svg +
+        `<table><tr><th>Spot</th><th>Coordinates</th><th>Power</th><th>Points / Expected</th><th>Radius</th><th>From Centre</th></tr>${rows}</table>`;
+}
+
+// Trip log: record a visit to a spot of an archived scan, then compare ratings with spot power
+async function logGeolocationTrip(historyId, kind, rank) {
+    const rating = parseInt(prompt(`How did the visit to ${kind} ${rank} go? (1-5)`, '3'));
+    if (!(rating >= 1 && rating <= 5)) return;
+    const notes = prompt('Notes (optional):', '') || null;
+    const res = await fetch('/api/v1/trips', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ history_id: historyId, target_kind: kind, target_rank: rank, rating, notes })
+    });
+    if (!res.ok) {
+        const err = await res.json().catch(() => ({}));
+        alert('Could not log trip: ' + (err.error || res.status));
+        return;
+    }
+    loadTripStats();
+}
+
+async function loadTripStats() {
+    const out = document.getElementById('geo-trip-stats');
+    const res = await fetch('/api/v1/trips/stats');
+    if (!res.ok) {
+        out.innerHTML = '<span style="color:var(--fire)">Could not load trip statistics.</span>';
+        return;
+    }
+    const stats = await res.json();
+    if (stats.trips === 0) {
+        out.innerHTML = '<p>No trips logged yet.</p>';
+        return;
+    }
+    let html = `<p>${stats.trips} trips logged.`;
+    if (stats.strength_correlation !== null) {
+        html += ` Rating vs |power|: r = ${stats.strength_correlation.toFixed(2)}, p = ${stats.p_value.toFixed(3)}` +
+            `, ${stats.slope.toFixed(2)} stars per unit of power.`;
+    }
+    html += '</p><table><tr><th>Kind</th><th>Trips</th><th>Mean Rating</th><th>Mean Power</th></tr>';
+    stats.by_kind.forEach(k => {
+        html += `<tr><td>${k.kind}</td><td>${k.trips}</td><td>${k.mean_rating.toFixed(2)}</td><td>${k.mean_power.toFixed(2)}</td></tr>`;
+    });
+    out.innerHTML = html + '</table>';
+}
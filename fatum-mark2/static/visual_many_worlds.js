@@ -29,7 +29,7 @@ async function runManyWorldsSimulation() {
     document.getElementById('mw-status').innerText = "Harvesting Quantum Entropy & Simulating...";
 
     try {
-        const response = await fetch('/api/tools/many_worlds', {
+        const response = await fetch('/api/v1/tools/many_worlds', {
             method: 'POST',
             headers: { 'Content-Type': 'application/json' },
             body: JSON.stringify({
@@ -85,6 +85,10 @@ function renderManyWorldsChart(data) {
             if (s.score > maxScore) maxScore = s.score;
         });
     });
+    data.aggregate_stats.forEach(s => {
+        if (s.p5 !== undefined && s.p5 < minScore) minScore = s.p5;
+        if (s.p95 !== undefined && s.p95 > maxScore) maxScore = s.p95;
+    });
 
     // Add padding to Y scale
     const yPadding = (maxScore - minScore) * 0.1;
@@ -121,6 +125,20 @@ function renderManyWorldsChart(data) {
         "Unknown": "#fff"
     };
 
+    // Draw Percentile Fan (p5-p95 outer band, p25-p75 inner band)
+    const drawBand = (lowKey, highKey, opacity) => {
+        if (data.aggregate_stats.length === 0 || data.aggregate_stats[0][lowKey] === undefined) return;
+        const upper = data.aggregate_stats.map(s => `${xScale(s.step_index)},${yScale(s[highKey])}`);
+        const lower = data.aggregate_stats.slice().reverse().map(s => `${xScale(s.step_index)},${yScale(s[lowKey])}`);
+        const band = document.createElementNS(ns, "polygon");
+        band.setAttribute("points", upper.concat(lower).join(" "));
+        band.setAttribute("fill", "#0ff");
+        band.setAttribute("opacity", opacity);
+        svg.appendChild(band);
+    };
+    drawBand("p5", "p95", "0.08");
+    drawBand("p25", "p75", "0.15");
+
     // Draw Paths
     data.paths.forEach(path => {
         // We draw line segments because color might change each step
@@ -166,7 +184,7 @@ function renderManyWorldsChart(data) {
     title.setAttribute("y", margin.top);
     title.setAttribute("text-anchor", "middle");
     title.setAttribute("fill", "#0ff");
-    title.textContent = `Simulated ${data.paths.length} Timelines`;
+    title.textContent = `Simulated ${data.total_worlds || data.paths.length} Timelines (showing ${data.paths.length})`;
     svg.appendChild(title);
 }
 
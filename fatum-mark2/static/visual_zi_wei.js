@@ -1,5 +1,26 @@
 // GLOBAL STATE for Zi Wei
 
+const SI_HUA_COLORS = {
+    'Hua Lu': '#4caf50', // Green
+    'Hua Quan': '#2196f3', // Blue
+    'Hua Ke': '#ffeb3b', // Yellow
+    'Hua Ji': '#f44336' // Red
+};
+
+const MINOR_STAR_COLORS = {
+    'auxiliary': '#b0bec5', // Greyish Blue
+    'malefic': '#8d6e63', // Brown
+    'peach_blossom': '#f48fb1' // Pink
+};
+
+// e.g. "Zi Wei (Emperor) [Miao] (Hua Ke)"
+function starLabel(s) {
+    let label = s.name_zh ? `${s.name_zh} ${s.name_pinyin}` : `${s.name_pinyin} (${s.name_en})`;
+    if (s.brightness) label += ` [${s.brightness}]`;
+    if (s.transformation) label += ` (${s.transformation})`;
+    return label;
+}
+
 async function runZiWei() {
     // Try to get active profile from Feng Shui tab selector
     const pVal = document.getElementById('fs-profile-select').value;
@@ -14,10 +35,13 @@ async function runZiWei() {
         birth_month: profile.birth_month,
         birth_day: profile.birth_day,
         birth_hour: profile.birth_hour,
-        gender: profile.gender
+        gender: profile.gender,
+        leap_month: document.getElementById('zw-leap-month').value,
+        quantum_mode: document.getElementById('zw-quantum').checked,
+        location: readLocation()
     };
 
-    const res = await fetch('/api/tools/ziwei', {
+    const res = await fetch('/api/v1/tools/ziwei', {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
         body: JSON.stringify(req)
@@ -101,7 +125,7 @@ function renderZiWeiChart(chart) {
                     cell.style.flexDirection = "column";
                     cell.style.alignItems = "center";
                     cell.style.justifyContent = "center";
-                    cell.innerHTML = `<h3>ZI WEI DOU SHU</h3><p>Element Phase: ${chart.element_phase}</p>`;
+                    cell.innerHTML = `<h3>ZI WEI DOU SHU</h3><p>Element Phase: ${chart.element_phase}</p>${chart.lunar_birth ? `<p>Lunar ${chart.lunar_birth.year}, ${chart.lunar_birth.leap ? 'Leap ' : ''}Month ${chart.lunar_birth.month}, Day ${chart.lunar_birth.day}</p>` : ''}`;
                 } else if ((r === 1 && c === 2) || (r === 2 && c === 1) || (r === 2 && c === 2)) {
                     continue;
                 }
@@ -109,6 +133,36 @@ function renderZiWeiChart(chart) {
             container.appendChild(cell);
         }
     }
+
+    renderZiWeiLimits(chart);
+}
+
+function renderZiWeiLimits(chart) {
+    let limits = document.getElementById('zw-limits');
+    if (!limits) {
+        limits = document.createElement('div');
+        limits.id = 'zw-limits';
+        limits.className = 'panel';
+        document.getElementById('zw-chart-container').after(limits);
+    }
+    const flown = (ts) => ts.map(t => `${t.star} ${t.transformation} → ${t.palace}`).join(', ');
+
+    let html = '<h3>Decade Limits (Da Xian)</h3><table><tr><th>Ages</th><th>Years</th><th>Palace</th><th>Pillar</th><th>Si Hua</th></tr>';
+    (chart.decade_limits || []).forEach(d => {
+        html += `<tr><td>${d.start_age}-${d.end_age}</td><td>${d.start_year}-${d.start_year + 9}</td><td>${d.palace}</td><td>${d.pillar}</td><td>${flown(d.transformations)}</td></tr>`;
+    });
+    html += '</table><h3>Flowing Years (Liu Nian)</h3><table><tr><th>Year</th><th>Age</th><th>Pillar</th><th>Year Life Palace</th><th>Si Hua</th></tr>';
+    (chart.annual_limits || []).forEach(a => {
+        html += `<tr><td>${a.year}</td><td>${a.age}</td><td>${a.pillar}</td><td>${a.palace}</td><td>${flown(a.transformations)}</td></tr>`;
+    });
+    if (chart.quantum) {
+        html += `</table><h3>Quantum Focus: ${chart.quantum.focus_palace}</h3>`;
+        html += chart.quantum.narrative.map(line => `<p>${line}</p>`).join('');
+        if (chart.quantum.anomalies.length) html += `<p style="color:var(--fire)">Anomalies: ${chart.quantum.anomalies.join('; ')}</p>`;
+        limits.innerHTML = html;
+        return;
+    }
+    limits.innerHTML = html + '</table>';
 }
 
 function renderPalaceContent(el, p, chart) {
@@ -150,16 +204,13 @@ function renderPalaceContent(el, p, chart) {
     // Major Stars
     p.major_stars.forEach(s => {
         const d = document.createElement('div');
-        d.textContent = s;
+        d.textContent = starLabel(s);
         d.style.color = '#ff4081'; // Pink/Red for major stars
         d.style.fontSize = '0.85em';
         d.style.fontWeight = 'bold';
 
         // Highlight Transformations
-        if (s.includes("(Hua Lu)")) d.style.color = "#4caf50"; // Green
-        if (s.includes("(Hua Quan)")) d.style.color = "#2196f3"; // Blue
-        if (s.includes("(Hua Ke)")) d.style.color = "#ffeb3b"; // Yellow
-        if (s.includes("(Hua Ji)")) d.style.color = "#f44336"; // Red
+        if (s.transformation) d.style.color = SI_HUA_COLORS[s.transformation];
 
         starsDiv.appendChild(d);
     });
@@ -167,15 +218,12 @@ function renderPalaceContent(el, p, chart) {
     // Minor Stars
     p.minor_stars.forEach(s => {
         const d = document.createElement('div');
-        d.textContent = s;
-        d.style.color = '#b0bec5'; // Greyish Blue
+        d.textContent = starLabel(s);
+        d.style.color = MINOR_STAR_COLORS[s.category] || '#b0bec5'; // Greyish Blue
         d.style.fontSize = '0.75em';
 
         // Highlight Transformations (if any applied to minors)
-        if (s.includes("(Hua Lu)")) d.style.color = "#4caf50";
-        if (s.includes("(Hua Quan)")) d.style.color = "#2196f3";
-        if (s.includes("(Hua Ke)")) d.style.color = "#ffeb3b";
-        if (s.includes("(Hua Ji)")) d.style.color = "#f44336";
+        if (s.transformation) d.style.color = SI_HUA_COLORS[s.transformation];
 
         starsDiv.appendChild(d);
     });